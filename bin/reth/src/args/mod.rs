@@ -4,6 +4,14 @@
 mod network_args;
 pub use network_args::{DiscoveryArgs, NetworkArgs};
 
+/// PruneArgs struct
+mod prune_args;
+pub use prune_args::PruneArgs;
+
 /// RpcServerArg struct
 mod rpc_server_args;
 pub use rpc_server_args::RpcServerArgs;
+
+/// TxPoolArgs struct
+mod txpool_args;
+pub use txpool_args::TxPoolArgs;
@@ -1,13 +1,17 @@
 //! clap [Args](clap::Args) for network related arguments.
 
-use crate::dirs::{KnownPeersPath, PlatformPath};
+use crate::dirs::{ChainPath, DataDirPath};
 use clap::Args;
 use reth_discv4::bootnodes::mainnet_nodes;
+use reth_discv5::{Discv5Config, DEFAULT_DISCOVERY_V5_PORT};
 use reth_net_nat::NatResolver;
-use reth_network::NetworkConfigBuilder;
+use reth_network::{NetworkConfigBuilder, PeersConfig};
 use reth_primitives::{ChainSpec, NodeRecord};
-use reth_staged_sync::Config;
-use std::path::PathBuf;
+use reth_staged_sync::{utils::parse_path, Config};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::PathBuf,
+};
 
 /// Parameters for configuring the network more granularity via CLI
 #[derive(Debug, Args)]
@@ -33,27 +37,59 @@ pub struct NetworkArgs {
     pub bootnodes: Option<Vec<NodeRecord>>,
 
     /// The path to the known peers file. Connected peers are
-    /// dumped to this file on node shutdown, and read on startup.
+    /// dumped to this file on node shutdown, along with their
+    /// reputation and last-seen time, and read on startup.
     /// Cannot be used with --no-persist-peers
-    #[arg(long, value_name = "FILE", verbatim_doc_comment, default_value_t)]
-    pub peers_file: PlatformPath<KnownPeersPath>,
+    ///
+    /// Defaults to `known-peers.json` in the data directory.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment, value_parser = parse_path)]
+    pub peers_file: Option<PathBuf>,
+
+    /// The path to the banned peers file. Banned peers are
+    /// dumped to this file on node shutdown, and banned again on startup.
+    /// Cannot be used with --no-persist-peers
+    ///
+    /// Defaults to `banned-peers.json` in the data directory.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment, value_parser = parse_path)]
+    pub banned_peers_file: Option<PathBuf>,
 
-    /// Do not persist peers. Cannot be used with --peers-file
-    #[arg(long, verbatim_doc_comment, conflicts_with = "peers_file")]
+    /// Do not persist peers. Cannot be used with --peers-file or --banned-peers-file
+    #[arg(long, verbatim_doc_comment, conflicts_with_all = ["peers_file", "banned_peers_file"])]
     pub no_persist_peers: bool,
 
     /// NAT resolution method.
     #[arg(long, default_value = "any")]
     pub nat: NatResolver,
+
+    /// Maximum number of outbound peers, overrides the one of the configuration file.
+    #[arg(long)]
+    pub max_outbound_peers: Option<usize>,
+
+    /// Maximum number of inbound peers, overrides the one of the configuration file.
+    #[arg(long)]
+    pub max_inbound_peers: Option<usize>,
 }
 
 impl NetworkArgs {
     /// Build a [`NetworkConfigBuilder`] from a [`Config`] and a [`ChainSpec`], in addition to the
     /// values in this option struct.
-    pub fn network_config(&self, config: &Config, chain_spec: ChainSpec) -> NetworkConfigBuilder {
-        let peers_file = (!self.no_persist_peers).then_some(&self.peers_file);
+    ///
+    /// The peers files default to the ones of the data directory.
+    pub fn network_config(
+        &self,
+        config: &Config,
+        chain_spec: ChainSpec,
+        data_dir: &ChainPath<DataDirPath>,
+    ) -> NetworkConfigBuilder {
+        let mut config = config.clone();
+        config.peers = self.peers_config(config.peers);
+
         let network_config_builder = config
-            .network_config(self.nat, peers_file.map(|f| f.as_ref().to_path_buf()))
+            .network_config(
+                self.nat,
+                self.persistent_peers_file(data_dir),
+                self.persistent_banned_peers_file(data_dir),
+            )
             .boot_nodes(self.bootnodes.clone().unwrap_or_else(mainnet_nodes))
             .chain_spec(chain_spec);
 
@@ -64,12 +100,35 @@ impl NetworkArgs {
 // === impl NetworkArgs ===
 
 impl NetworkArgs {
-    /// If `no_persist_peers` is true then this returns the path to the persistent peers file
-    pub fn persistent_peers_file(&self) -> Option<PathBuf> {
+    /// Applies the peer limits given on the command line to the [`PeersConfig`].
+    pub fn peers_config(&self, mut config: PeersConfig) -> PeersConfig {
+        if let Some(max_outbound) = self.max_outbound_peers {
+            config = config.with_max_outbound(max_outbound);
+        }
+        if let Some(max_inbound) = self.max_inbound_peers {
+            config = config.with_max_inbound(max_inbound);
+        }
+        config
+    }
+
+    /// If `no_persist_peers` is false then this returns the path to the persistent peers file
+    pub fn persistent_peers_file(&self, data_dir: &ChainPath<DataDirPath>) -> Option<PathBuf> {
         if self.no_persist_peers {
             return None
         }
-        Some(self.peers_file.clone().into())
+        Some(self.peers_file.clone().unwrap_or_else(|| data_dir.known_peers_path()))
+    }
+
+    /// If `no_persist_peers` is false then this returns the path to the persistent banned peers
+    /// file
+    pub fn persistent_banned_peers_file(
+        &self,
+        data_dir: &ChainPath<DataDirPath>,
+    ) -> Option<PathBuf> {
+        if self.no_persist_peers {
+            return None
+        }
+        Some(self.banned_peers_file.clone().unwrap_or_else(|| data_dir.banned_peers_path()))
     }
 }
 
@@ -87,6 +146,18 @@ pub struct DiscoveryArgs {
     /// Disable Discv4 discovery.
     #[arg(long, conflicts_with = "disable_discovery")]
     disable_discv4_discovery: bool,
+
+    /// Enable Discv5 discovery, alongside Discv4.
+    #[arg(long, conflicts_with = "disable_discovery")]
+    enable_discv5_discovery: bool,
+
+    /// The UDP port to use for Discv5 discovery.
+    #[arg(
+        long = "discovery.v5.port",
+        value_name = "PORT",
+        default_value_t = DEFAULT_DISCOVERY_V5_PORT
+    )]
+    discv5_port: u16,
 }
 
 impl DiscoveryArgs {
@@ -102,6 +173,13 @@ impl DiscoveryArgs {
         if self.disable_discovery || self.disable_discv4_discovery {
             network_config_builder = network_config_builder.disable_discv4_discovery();
         }
+
+        if !self.disable_discovery && self.enable_discv5_discovery {
+            network_config_builder = network_config_builder.discovery_v5(Discv5Config {
+                listen_addr: SocketAddr::from((Ipv4Addr::UNSPECIFIED, self.discv5_port)),
+                ..Default::default()
+            });
+        }
         network_config_builder
     }
 }
@@ -0,0 +1,86 @@
+//! clap [Args](clap::Args) for pruning related arguments.
+
+use clap::Args;
+use reth_staged_sync::config::PruneConfig;
+use reth_stages::PruneModes;
+
+/// Parameters for pruning the data of old blocks. Each distance is the number of recent blocks to
+/// keep the data of, and overrides the one of the configuration file.
+#[derive(Debug, Args, PartialEq, Default)]
+#[command(next_help_heading = "Pruning")]
+pub struct PruneArgs {
+    /// Number of recent blocks to keep the transaction receipts and logs of
+    #[arg(long = "prune.receipts.distance", value_name = "BLOCKS")]
+    pub receipts_distance: Option<u64>,
+
+    /// Number of recent blocks to keep the transaction hash lookups of
+    #[arg(long = "prune.transaction-lookup.distance", value_name = "BLOCKS")]
+    pub transaction_lookup_distance: Option<u64>,
+
+    /// Number of recent blocks to keep the recovered transaction senders of
+    #[arg(long = "prune.sender-recovery.distance", value_name = "BLOCKS")]
+    pub sender_recovery_distance: Option<u64>,
+
+    /// Number of recent blocks to keep the account changesets and history of
+    #[arg(long = "prune.account-history.distance", value_name = "BLOCKS")]
+    pub account_history_distance: Option<u64>,
+
+    /// Number of recent blocks to keep the storage changesets and history of
+    #[arg(long = "prune.storage-history.distance", value_name = "BLOCKS")]
+    pub storage_history_distance: Option<u64>,
+}
+
+impl PruneArgs {
+    /// Returns the distances to prune with, falling back to the ones of `config`.
+    pub fn prune_modes(&self, config: &PruneConfig) -> PruneModes {
+        PruneModes {
+            receipts: self.receipts_distance.or(config.receipts_distance),
+            transaction_lookup: self
+                .transaction_lookup_distance
+                .or(config.transaction_lookup_distance),
+            sender_recovery: self.sender_recovery_distance.or(config.sender_recovery_distance),
+            account_history: self.account_history_distance.or(config.account_history_distance),
+            storage_history: self.storage_history_distance.or(config.storage_history_distance),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_prune_args() {
+        let args = CommandParser::<PruneArgs>::parse_from([
+            "reth",
+            "--prune.receipts.distance",
+            "1000",
+            "--prune.storage-history.distance",
+            "500",
+        ])
+        .args;
+        let config = PruneConfig {
+            receipts_distance: Some(10),
+            sender_recovery_distance: Some(20),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            args.prune_modes(&config),
+            PruneModes {
+                receipts: Some(1000),
+                sender_recovery: Some(20),
+                storage_history: Some(500),
+                ..Default::default()
+            }
+        );
+    }
+}
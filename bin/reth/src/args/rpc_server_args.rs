@@ -1,12 +1,31 @@
 //! clap [Args](clap::Args) for RPC related arguments.
 
-use crate::dirs::{JwtSecretPath, PlatformPath};
 use clap::Args;
-use reth_rpc::{JwtError, JwtSecret};
-use reth_rpc_builder::RpcModuleSelection;
-use std::{net::IpAddr, path::Path};
+use reth_rpc::{
+    EthApiConfig, EthFilterConfig, JwtError, JwtSecret, RateLimitConfig,
+    DEFAULT_MAX_BLOCKS_PER_FILTER, DEFAULT_MAX_LOGS_PER_RESPONSE, DEFAULT_MAX_TRACING_REQUESTS,
+    DEFAULT_STALE_FILTER_TTL, RPC_DEFAULT_GAS_CAP,
+};
+use reth_rpc_builder::{
+    IpcServerBuilder, RpcModuleSelection, RpcServerConfig, ServerBuilder, TransportRpcModuleConfig,
+    DEFAULT_AUTH_PORT, DEFAULT_HTTP_RPC_PORT, DEFAULT_IPC_ENDPOINT, DEFAULT_WS_RPC_PORT,
+};
+use reth_staged_sync::{config::RpcConfig, utils::parse_path};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-/// Parameters for configuring the rpc more granularity via CLI
+/// Default max request size in megabytes.
+pub(crate) const RPC_DEFAULT_MAX_REQUEST_SIZE_MB: u32 = 15;
+/// Default max response size in megabytes.
+pub(crate) const RPC_DEFAULT_MAX_RESPONSE_SIZE_MB: u32 = 100;
+/// Default number of incoming connections.
+pub(crate) const RPC_DEFAULT_MAX_CONNECTIONS: u32 = 100;
+
+/// Parameters for configuring the rpc more granularity via CLI. The settings which are not set
+/// fall back to the ones of the configuration file, see [RpcServerArgs::apply_config].
 #[derive(Debug, Args, PartialEq, Default)]
 #[command(next_help_heading = "Rpc")]
 pub struct RpcServerArgs {
@@ -26,6 +45,10 @@ pub struct RpcServerArgs {
     #[arg(long = "http.api")]
     pub http_api: Option<RpcModuleSelection>,
 
+    /// Http Corsdomain to allow request from
+    #[arg(long = "http.corsdomain")]
+    pub http_corsdomain: Option<String>,
+
     /// Enable the WS-RPC server
     #[arg(long)]
     pub ws: bool,
@@ -50,12 +73,124 @@ pub struct RpcServerArgs {
     #[arg(long)]
     pub ipcpath: Option<String>,
 
+    /// Set the maximum RPC request payload size for both HTTP and WS in megabytes [default: 15]
+    #[arg(long = "rpc.max-request-size")]
+    pub rpc_max_request_size: Option<u32>,
+
+    /// Set the maximum RPC response payload size for both HTTP and WS in megabytes [default: 100]
+    #[arg(long = "rpc.max-response-size")]
+    pub rpc_max_response_size: Option<u32>,
+
+    /// Maximum number of RPC server connections [default: 100]
+    #[arg(long = "rpc.max-connections")]
+    pub rpc_max_connections: Option<u32>,
+
+    /// Maximum gas a call of eth_call, eth_estimateGas or eth_createAccessList can use
+    /// [default: 50000000]
+    #[arg(long = "rpc.gascap")]
+    pub rpc_gas_cap: Option<u64>,
+
+    /// Maximum number of debug_trace* and trace_* requests that are served concurrently
+    /// [default: 25]
+    #[arg(long = "rpc.max-tracing-requests")]
+    pub rpc_max_tracing_requests: Option<usize>,
+
+    /// Maximum number of blocks an eth_getLogs query or a log filter can span [default: 100000]
+    #[arg(long = "rpc.max-blocks-per-filter")]
+    pub rpc_max_blocks_per_filter: Option<u64>,
+
+    /// Maximum number of logs an eth_getLogs query or a log filter can return [default: 20000]
+    #[arg(long = "rpc.max-logs-per-response")]
+    pub rpc_max_logs_per_response: Option<usize>,
+
+    /// Number of seconds after which a filter that was not polled is uninstalled [default: 300]
+    #[arg(
+        long = "rpc.filter-ttl",
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub rpc_filter_ttl: Option<u64>,
+
+    /// Comma separated list of methods that are not served by any transport, e.g.
+    /// debug_traceTransaction,trace_filter
+    #[arg(long = "rpc.disabled-methods", value_delimiter = ',')]
+    pub rpc_disabled_methods: Vec<String>,
+
+    /// Maximum number of http and ipc requests a connection can make per second.
+    #[arg(long = "rpc.max-requests-per-connection")]
+    pub rpc_max_requests_per_connection: Option<u32>,
+
+    /// Comma separated list of the maximum number of calls of methods per second, across all
+    /// http and ipc connections, e.g. eth_getLogs=10,eth_call=100
+    #[arg(long = "rpc.method-rate-limits", value_delimiter = ',', value_parser = parse_method_rate_limit)]
+    pub rpc_method_rate_limits: Vec<(String, u32)>,
+
+    /// Auth server address to listen on
+    #[arg(long = "authrpc.addr")]
+    pub auth_addr: Option<IpAddr>,
+
+    /// Auth server port to listen on
+    #[arg(long = "authrpc.port")]
+    pub auth_port: Option<u16>,
+
     /// Path to a JWT secret to use for authenticated RPC endpoints
-    #[arg(long = "authrpc.jwtsecret", value_name = "PATH", global = true, required = false)]
-    authrpc_jwtsecret: Option<PlatformPath<JwtSecretPath>>,
+    #[arg(
+        long = "authrpc.jwtsecret",
+        value_name = "PATH",
+        global = true,
+        required = false,
+        value_parser = parse_path
+    )]
+    authrpc_jwtsecret: Option<PathBuf>,
 }
 
 impl RpcServerArgs {
+    /// Fills the settings which are not set on the command line with the ones of the
+    /// configuration file.
+    ///
+    /// The servers enabled in the configuration file are enabled as well.
+    pub(crate) fn apply_config(&mut self, config: &RpcConfig) {
+        self.http |= config.http;
+        self.http_addr = self.http_addr.or(config.http_addr);
+        self.http_port = self.http_port.or(config.http_port);
+        self.ws |= config.ws;
+        self.ws_addr = self.ws_addr.or(config.ws_addr);
+        self.ws_port = self.ws_port.or(config.ws_port);
+        self.auth_addr = self.auth_addr.or(config.auth_addr);
+        self.auth_port = self.auth_port.or(config.auth_port);
+        self.rpc_max_request_size = self.rpc_max_request_size.or(config.max_request_size);
+        self.rpc_max_response_size = self.rpc_max_response_size.or(config.max_response_size);
+        self.rpc_max_connections = self.rpc_max_connections.or(config.max_connections);
+        self.rpc_gas_cap = self.rpc_gas_cap.or(config.gas_cap);
+        self.rpc_max_tracing_requests =
+            self.rpc_max_tracing_requests.or(config.max_tracing_requests);
+        self.rpc_max_blocks_per_filter =
+            self.rpc_max_blocks_per_filter.or(config.max_blocks_per_filter);
+        self.rpc_max_logs_per_response =
+            self.rpc_max_logs_per_response.or(config.max_logs_per_response);
+        self.rpc_filter_ttl = self.rpc_filter_ttl.or(config.filter_ttl);
+    }
+
+    /// Returns the maximum number of tracing requests served concurrently.
+    pub(crate) fn max_tracing_requests(&self) -> usize {
+        self.rpc_max_tracing_requests.unwrap_or(DEFAULT_MAX_TRACING_REQUESTS)
+    }
+
+    /// Returns the maximum size of a request payload in bytes.
+    fn max_request_size(&self) -> u32 {
+        self.rpc_max_request_size.unwrap_or(RPC_DEFAULT_MAX_REQUEST_SIZE_MB) * 1024 * 1024
+    }
+
+    /// Returns the maximum size of a response payload in bytes.
+    fn max_response_size(&self) -> u32 {
+        self.rpc_max_response_size.unwrap_or(RPC_DEFAULT_MAX_RESPONSE_SIZE_MB) * 1024 * 1024
+    }
+
+    /// Returns the maximum number of connections to a server.
+    fn max_connections(&self) -> u32 {
+        self.rpc_max_connections.unwrap_or(RPC_DEFAULT_MAX_CONNECTIONS)
+    }
+
     /// The execution layer and consensus layer clients SHOULD accept a configuration parameter:
     /// jwt-secret, which designates a file containing the hex-encoded 256 bit secret key to be used
     /// for verifying/generating JWT tokens.
@@ -66,24 +201,143 @@ impl RpcServerArgs {
     /// If such a parameter is not given, the client SHOULD generate such a token, valid for the
     /// duration of the execution, and SHOULD store the hex-encoded secret as a jwt.hex file on
     /// the filesystem. This file can then be used to provision the counterpart client.
-    pub(crate) fn jwt_secret(&self) -> Result<JwtSecret, JwtError> {
-        let arg = self.authrpc_jwtsecret.as_ref();
-        let path: Option<&Path> = arg.map(|p| p.as_ref());
-        match path {
+    ///
+    /// A secret previously generated at the default path is reused, so that the counterpart
+    /// client stays provisioned across restarts.
+    pub(crate) fn jwt_secret(&self, default_jwt_path: &Path) -> Result<JwtSecret, JwtError> {
+        match self.authrpc_jwtsecret.as_deref() {
             Some(fpath) => JwtSecret::from_file(fpath),
             None => {
-                let default_path = PlatformPath::<JwtSecretPath>::default();
-                let fpath = default_path.as_ref();
-                JwtSecret::try_create(fpath)
+                if default_jwt_path.exists() {
+                    JwtSecret::from_file(default_jwt_path)
+                } else {
+                    JwtSecret::try_create(default_jwt_path)
+                }
             }
         }
     }
+
+    /// Returns the address of the auth server, which listens on localhost by default.
+    pub(crate) fn auth_server_address(&self) -> SocketAddr {
+        SocketAddr::new(
+            self.auth_addr.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            self.auth_port.unwrap_or(DEFAULT_AUTH_PORT),
+        )
+    }
+
+    /// Returns the settings of the `eth_` namespace handlers.
+    pub(crate) fn eth_config(&self) -> EthApiConfig {
+        EthApiConfig {
+            rpc_gas_cap: self.rpc_gas_cap.unwrap_or(RPC_DEFAULT_GAS_CAP),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the limits of the log queries.
+    pub(crate) fn filter_config(&self) -> EthFilterConfig {
+        EthFilterConfig {
+            max_blocks_per_filter: self
+                .rpc_max_blocks_per_filter
+                .unwrap_or(DEFAULT_MAX_BLOCKS_PER_FILTER),
+            max_logs_per_response: self
+                .rpc_max_logs_per_response
+                .unwrap_or(DEFAULT_MAX_LOGS_PER_RESPONSE),
+            stale_filter_ttl: self
+                .rpc_filter_ttl
+                .map_or(DEFAULT_STALE_FILTER_TTL, Duration::from_secs),
+        }
+    }
+
+    /// Returns the rate limits of the http server, if any are configured.
+    fn rate_limit_config(&self) -> Option<RateLimitConfig> {
+        if self.rpc_max_requests_per_connection.is_none() && self.rpc_method_rate_limits.is_empty()
+        {
+            return None
+        }
+        Some(RateLimitConfig {
+            max_requests_per_connection: self.rpc_max_requests_per_connection,
+            max_calls_per_method: self.rpc_method_rate_limits.iter().cloned().collect(),
+            max_request_body_size: self.max_request_size() as usize,
+        })
+    }
+
+    /// Returns the modules to install per transport.
+    ///
+    /// The http and ws servers serve the standard modules unless configured otherwise, the ipc
+    /// server serves all modules.
+    pub(crate) fn transport_rpc_module_config(&self) -> TransportRpcModuleConfig {
+        let mut config = TransportRpcModuleConfig::default();
+        if self.http {
+            config = config.with_http(self.http_api.clone().unwrap_or_default());
+        }
+        if self.ws {
+            config = config.with_ws(self.ws_api.clone().unwrap_or_default());
+        }
+        if !self.ipcdisable {
+            config = config.with_ipc(RpcModuleSelection::All);
+        }
+        config
+    }
+
+    /// Returns the configuration of the http, ws and ipc servers.
+    pub(crate) fn rpc_server_config(&self) -> RpcServerConfig {
+        let mut config = RpcServerConfig::default();
+
+        if self.http {
+            let socket_address = SocketAddr::new(
+                self.http_addr.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+                self.http_port.unwrap_or(DEFAULT_HTTP_RPC_PORT),
+            );
+            config = config
+                .with_http_address(socket_address)
+                .with_http(self.http_ws_server_builder())
+                .with_cors(self.http_corsdomain.clone())
+                .with_rate_limit(self.rate_limit_config());
+        }
+
+        if self.ws {
+            let socket_address = SocketAddr::new(
+                self.ws_addr.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+                self.ws_port.unwrap_or(DEFAULT_WS_RPC_PORT),
+            );
+            config = config.with_ws_address(socket_address).with_ws(self.http_ws_server_builder());
+        }
+
+        if !self.ipcdisable {
+            let ipc_builder = IpcServerBuilder::default()
+                .max_request_body_size(self.max_request_size())
+                .max_response_body_size(self.max_response_size())
+                .max_connections(self.max_connections());
+            config = config.with_ipc(ipc_builder).with_ipc_endpoint(
+                self.ipcpath.clone().unwrap_or_else(|| DEFAULT_IPC_ENDPOINT.to_string()),
+            );
+        }
+
+        config
+    }
+
+    /// Creates the [ServerBuilder] of the http and ws servers, with the configured limits.
+    fn http_ws_server_builder(&self) -> ServerBuilder {
+        ServerBuilder::new()
+            .max_request_body_size(self.max_request_size())
+            .max_response_body_size(self.max_response_size())
+            .max_connections(self.max_connections())
+    }
+}
+
+/// Parses a limit of the calls of a method, e.g. `eth_getLogs=10`.
+fn parse_method_rate_limit(s: &str) -> Result<(String, u32), String> {
+    let (method, limit) =
+        s.split_once('=').ok_or_else(|| format!("expected <method>=<limit>, got {s}"))?;
+    let limit = limit.parse().map_err(|err| format!("invalid limit of {method}: {err}"))?;
+    Ok((method.to_string(), limit))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use clap::Parser;
+    use reth_rpc_builder::RethRpcModule;
 
     /// A helper type to parse Args more easily
     #[derive(Parser)]
@@ -103,4 +357,81 @@ mod tests {
 
         assert_eq!(apis, expected);
     }
+
+    #[test]
+    fn test_transport_rpc_module_config() {
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--http",
+            "--http.api",
+            "eth,admin",
+            "--ipcdisable",
+        ])
+        .args;
+
+        assert_eq!(
+            args.transport_rpc_module_config(),
+            TransportRpcModuleConfig::http([RethRpcModule::Eth, RethRpcModule::Admin])
+        );
+    }
+
+    #[test]
+    fn test_rpc_limits_parser() {
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--rpc.gascap",
+            "1000000",
+            "--rpc.disabled-methods",
+            "debug_traceTransaction,trace_filter",
+            "--rpc.method-rate-limits",
+            "eth_getLogs=10,eth_call=100",
+        ])
+        .args;
+
+        assert_eq!(args.eth_config().rpc_gas_cap, 1_000_000);
+        assert_eq!(args.max_tracing_requests(), DEFAULT_MAX_TRACING_REQUESTS);
+        assert_eq!(args.rpc_disabled_methods, ["debug_traceTransaction", "trace_filter"]);
+
+        let rate_limit = args.rate_limit_config().unwrap();
+        assert_eq!(rate_limit.max_requests_per_connection, None);
+        assert_eq!(rate_limit.max_calls_per_method["eth_getLogs"], 10);
+        assert_eq!(rate_limit.max_calls_per_method["eth_call"], 100);
+
+        assert!(CommandParser::<RpcServerArgs>::try_parse_from([
+            "reth",
+            "--rpc.method-rate-limits",
+            "eth_getLogs"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_apply_config() {
+        let mut args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--http.port",
+            "9545",
+            "--rpc.gascap",
+            "1000000",
+        ])
+        .args;
+        args.apply_config(&RpcConfig {
+            http: true,
+            http_port: Some(8555),
+            ws_port: Some(8556),
+            gas_cap: Some(2_000_000),
+            max_logs_per_response: Some(100),
+            filter_ttl: Some(60),
+            ..Default::default()
+        });
+
+        assert!(args.http);
+        assert!(!args.ws);
+        assert_eq!(args.http_port, Some(9545));
+        assert_eq!(args.ws_port, Some(8556));
+        assert_eq!(args.eth_config().rpc_gas_cap, 1_000_000);
+        assert_eq!(args.filter_config().max_logs_per_response, 100);
+        assert_eq!(args.filter_config().max_blocks_per_filter, DEFAULT_MAX_BLOCKS_PER_FILTER);
+        assert_eq!(args.filter_config().stale_filter_ttl, Duration::from_secs(60));
+    }
 }
@@ -0,0 +1,91 @@
+//! clap [Args](clap::Args) for transaction pool related arguments.
+
+use clap::Args;
+use eyre::eyre;
+use reth_primitives::eip4844::{load_trusted_setup, KzgSettings};
+use reth_staged_sync::utils::parse_path;
+use reth_transaction_pool::PoolConfig;
+use std::{path::PathBuf, sync::Arc};
+
+/// Parameters for configuring the transaction pool
+#[derive(Debug, Args, PartialEq, Default)]
+#[command(next_help_heading = "TxPool")]
+pub struct TxPoolArgs {
+    /// Treat local transactions, e.g. the ones submitted via `eth_sendRawTransaction`, like
+    /// transactions received from peers.
+    ///
+    /// Local transactions are no longer exempt from the max number of transactions per account,
+    /// and can be evicted when the pool is full.
+    #[arg(long = "txpool.no-locals")]
+    pub no_locals: bool,
+
+    /// The path to the KZG trusted setup the blobs of the
+    /// blob transactions are verified with, in the format
+    /// of the `c-kzg` trusted setups.
+    ///
+    /// Blob transactions are rejected if it is not given.
+    #[arg(
+        long = "txpool.kzg-trusted-setup",
+        value_name = "FILE",
+        verbatim_doc_comment,
+        value_parser = parse_path
+    )]
+    pub kzg_trusted_setup: Option<PathBuf>,
+}
+
+impl TxPoolArgs {
+    /// Returns the configuration of the transaction pool.
+    pub fn pool_config(&self) -> PoolConfig {
+        PoolConfig { no_local_exemptions: self.no_locals, ..Default::default() }
+    }
+
+    /// Loads the KZG trusted setup, if one is given.
+    pub fn kzg_settings(&self) -> eyre::Result<Option<Arc<KzgSettings>>> {
+        self.kzg_trusted_setup
+            .as_deref()
+            .map(|path| {
+                load_trusted_setup(path).map(Arc::new).map_err(|err| {
+                    eyre!("Could not load the KZG trusted setup {}: {err:?}", path.display())
+                })
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_txpool_args() {
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args;
+        assert!(!args.pool_config().no_local_exemptions);
+
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth", "--txpool.no-locals"]).args;
+        assert!(args.pool_config().no_local_exemptions);
+    }
+
+    #[test]
+    fn test_parse_kzg_trusted_setup() {
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.kzg_trusted_setup, None);
+        assert!(args.kzg_settings().unwrap().is_none());
+
+        let args = CommandParser::<TxPoolArgs>::parse_from([
+            "reth",
+            "--txpool.kzg-trusted-setup",
+            "trusted_setup.txt",
+        ])
+        .args;
+        assert_eq!(args.kzg_trusted_setup, Some(PathBuf::from("trusted_setup.txt")));
+        assert!(args.kzg_settings().is_err());
+    }
+}
@@ -0,0 +1,266 @@
+//! Writing of era1 files, the archive format of the pre-merge blocks.
+//!
+//! An era1 file is an [e2store] file holding up to [`BLOCKS_PER_ERA1`] consecutive blocks,
+//! starting at a multiple of it:
+//!
+//! ```text
+//! Version | (CompressedHeader | CompressedBody | CompressedReceipts | TotalDifficulty)* |
+//! Accumulator | BlockIndex
+//! ```
+//!
+//! The headers, bodies and receipts are RLP encoded then compressed with the framed snappy
+//! format. The accumulator is the SSZ root of the list of the hashes and total difficulties of
+//! the blocks, and the block index holds the offsets of the headers.
+//!
+//! [e2store]: https://github.com/status-im/nimbus-eth2/blob/stable/docs/e2store.md
+use reth_eth_wire::BlockBody;
+use reth_primitives::{Block, BlockNumber, Receipt, H256, U256};
+use reth_rlp::{encode_list, Encodable};
+use sha2::{Digest, Sha256};
+use std::io::{self, Write};
+
+/// The number of blocks of a full era1 file.
+pub(crate) const BLOCKS_PER_ERA1: u64 = 8192;
+
+const VERSION: u16 = 0x3265;
+const COMPRESSED_HEADER: u16 = 0x03;
+const COMPRESSED_BODY: u16 = 0x04;
+const COMPRESSED_RECEIPTS: u16 = 0x05;
+const TOTAL_DIFFICULTY: u16 = 0x06;
+const ACCUMULATOR: u16 = 0x07;
+const BLOCK_INDEX: u16 = 0x3266;
+
+/// Writes the blocks of an era1 file, in increasing order of their numbers.
+pub(crate) struct Era1Writer<W> {
+    writer: W,
+    /// The number of bytes written so far.
+    position: u64,
+    /// The number of the first block of the file.
+    start: BlockNumber,
+    /// The offsets of the headers of the written blocks.
+    offsets: Vec<u64>,
+    /// The hashes and total difficulties of the written blocks.
+    records: Vec<(H256, U256)>,
+}
+
+impl<W: Write> Era1Writer<W> {
+    /// Creates a writer of the file whose first block is `start`.
+    pub(crate) fn new(writer: W, start: BlockNumber) -> io::Result<Self> {
+        let mut era = Self { writer, position: 0, start, offsets: Vec::new(), records: Vec::new() };
+        era.write_entry(VERSION, &[])?;
+        Ok(era)
+    }
+
+    /// Returns the number of the next block to write.
+    pub(crate) fn next_block(&self) -> BlockNumber {
+        self.start + self.records.len() as u64
+    }
+
+    /// Returns whether the file holds all the blocks of its era.
+    pub(crate) fn is_full(&self) -> bool {
+        self.records.len() as u64 == BLOCKS_PER_ERA1
+    }
+
+    /// Writes the next block, with its receipts and the total difficulty of the chain up to it.
+    pub(crate) fn write_block(
+        &mut self,
+        block: Block,
+        receipts: &[Receipt],
+        total_difficulty: U256,
+    ) -> io::Result<()> {
+        if self.is_full() || block.header.number != self.next_block() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("block {} does not belong to the file at this position", block.number),
+            ))
+        }
+
+        let hash = block.header.hash_slow();
+        self.offsets.push(self.position);
+        let mut header = Vec::new();
+        block.header.encode(&mut header);
+        self.write_compressed(COMPRESSED_HEADER, &header)?;
+
+        let mut body = Vec::new();
+        BlockBody {
+            transactions: block.body,
+            ommers: block.ommers,
+            withdrawals: block.withdrawals,
+        }
+        .encode(&mut body);
+        self.write_compressed(COMPRESSED_BODY, &body)?;
+
+        let mut encoded_receipts = Vec::new();
+        encode_list::<Receipt, _>(receipts, &mut encoded_receipts);
+        self.write_compressed(COMPRESSED_RECEIPTS, &encoded_receipts)?;
+        self.write_entry(TOTAL_DIFFICULTY, &total_difficulty.to_le_bytes::<32>())?;
+        self.records.push((hash, total_difficulty));
+        Ok(())
+    }
+
+    /// Writes the accumulator and the block index, returns the accumulator root.
+    pub(crate) fn finish(mut self) -> io::Result<H256> {
+        let root = accumulator_root(&self.records);
+        self.write_entry(ACCUMULATOR, root.as_bytes())?;
+
+        // the offsets are relative to the start of the block index
+        let mut index = Vec::with_capacity(16 + self.offsets.len() * 8);
+        index.extend_from_slice(&self.start.to_le_bytes());
+        for offset in &self.offsets {
+            index.extend_from_slice(&(*offset as i64 - self.position as i64).to_le_bytes());
+        }
+        index.extend_from_slice(&(self.offsets.len() as u64).to_le_bytes());
+        self.write_entry(BLOCK_INDEX, &index)?;
+
+        self.writer.flush()?;
+        Ok(root)
+    }
+
+    /// Writes an entry whose data is compressed with the framed snappy format.
+    fn write_compressed(&mut self, kind: u16, encoded: &[u8]) -> io::Result<()> {
+        let mut encoder = snap::write::FrameEncoder::new(Vec::new());
+        encoder.write_all(encoded)?;
+        let compressed = encoder.into_inner().map_err(|err| err.into_error())?;
+        self.write_entry(kind, &compressed)
+    }
+
+    /// Writes an e2store entry: its type and the length of its data, then the data.
+    fn write_entry(&mut self, kind: u16, data: &[u8]) -> io::Result<()> {
+        let length = u32::try_from(data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "entry too large"))?;
+        self.writer.write_all(&kind.to_le_bytes())?;
+        self.writer.write_all(&length.to_le_bytes())?;
+        self.writer.write_all(&[0; 2])?;
+        self.writer.write_all(data)?;
+        self.position += 8 + data.len() as u64;
+        Ok(())
+    }
+}
+
+/// Returns the name of the era1 file of the chain, given its epoch and accumulator root.
+pub(crate) fn era1_file_name(network: &str, epoch: u64, root: H256) -> String {
+    format!("{network}-{epoch:05}-{}.era1", reth_primitives::hex::encode(&root.as_bytes()[..4]))
+}
+
+/// Returns the SSZ root of the list of the header records, with a limit of [`BLOCKS_PER_ERA1`].
+fn accumulator_root(records: &[(H256, U256)]) -> H256 {
+    let mut layer = records
+        .iter()
+        .map(|(hash, total_difficulty)| {
+            sha256(hash.as_bytes(), &total_difficulty.to_le_bytes::<32>())
+        })
+        .collect::<Vec<_>>();
+
+    // the list is merkleized as if it was padded with zero leaves up to its limit
+    let mut zero = [0; 32];
+    for _ in 0..BLOCKS_PER_ERA1.trailing_zeros() {
+        if layer.len() % 2 == 1 {
+            layer.push(zero);
+        }
+        layer = layer.chunks(2).map(|pair| sha256(&pair[0], &pair[1])).collect();
+        zero = sha256(&zero, &zero);
+    }
+
+    let mut length = [0; 32];
+    length[..8].copy_from_slice(&(records.len() as u64).to_le_bytes());
+    H256(sha256(&layer.first().copied().unwrap_or(zero), &length))
+}
+
+fn sha256(left: &[u8], right: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update(left).chain_update(right).finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::Header;
+    use std::io::Read;
+
+    fn block(number: BlockNumber) -> Block {
+        Block { header: Header { number, ..Default::default() }, ..Default::default() }
+    }
+
+    /// Returns the type and the data of the entries of the file.
+    fn entries(mut file: &[u8]) -> Vec<(u16, Vec<u8>)> {
+        let mut entries = Vec::new();
+        while !file.is_empty() {
+            let kind = u16::from_le_bytes([file[0], file[1]]);
+            let length = u32::from_le_bytes(file[2..6].try_into().unwrap()) as usize;
+            entries.push((kind, file[8..8 + length].to_vec()));
+            file = &file[8 + length..];
+        }
+        entries
+    }
+
+    #[test]
+    fn write_era1_file() {
+        let mut file = Vec::new();
+        let mut era = Era1Writer::new(&mut file, BLOCKS_PER_ERA1).unwrap();
+        assert!(era.write_block(block(0), &[], U256::from(1)).is_err());
+        era.write_block(block(BLOCKS_PER_ERA1), &[], U256::from(1)).unwrap();
+        era.write_block(block(BLOCKS_PER_ERA1 + 1), &[Receipt::default()], U256::from(2)).unwrap();
+        assert_eq!(era.next_block(), BLOCKS_PER_ERA1 + 2);
+        let root = era.finish().unwrap();
+        assert_eq!(
+            root,
+            accumulator_root(&[
+                (block(BLOCKS_PER_ERA1).header.hash_slow(), U256::from(1)),
+                (block(BLOCKS_PER_ERA1 + 1).header.hash_slow(), U256::from(2)),
+            ])
+        );
+
+        let entries = entries(&file);
+        let kinds = entries.iter().map(|(kind, _)| *kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                VERSION,
+                COMPRESSED_HEADER,
+                COMPRESSED_BODY,
+                COMPRESSED_RECEIPTS,
+                TOTAL_DIFFICULTY,
+                COMPRESSED_HEADER,
+                COMPRESSED_BODY,
+                COMPRESSED_RECEIPTS,
+                TOTAL_DIFFICULTY,
+                ACCUMULATOR,
+                BLOCK_INDEX
+            ]
+        );
+
+        let mut header = Vec::new();
+        snap::read::FrameDecoder::new(&entries[5].1[..]).read_to_end(&mut header).unwrap();
+        let mut expected = Vec::new();
+        block(BLOCKS_PER_ERA1 + 1).header.encode(&mut expected);
+        assert_eq!(header, expected);
+        assert_eq!(entries[8].1, U256::from(2).to_le_bytes::<32>());
+        assert_eq!(entries[9].1, root.as_bytes());
+
+        // the offsets of the headers point back from the block index
+        let index = &entries[10].1;
+        assert_eq!(index.len(), 32);
+        assert_eq!(u64::from_le_bytes(index[..8].try_into().unwrap()), BLOCKS_PER_ERA1);
+        assert_eq!(u64::from_le_bytes(index[24..].try_into().unwrap()), 2);
+        let index_position = file.len() - 8 - index.len();
+        for (i, offset) in index[8..24].chunks(8).enumerate() {
+            let offset = i64::from_le_bytes(offset.try_into().unwrap());
+            let header = (index_position as i64 + offset) as usize;
+            assert_eq!(u16::from_le_bytes([file[header], file[header + 1]]), COMPRESSED_HEADER);
+            assert_eq!(entries[1 + 4 * i].0, COMPRESSED_HEADER);
+        }
+    }
+
+    #[test]
+    fn accumulator_of_no_records() {
+        // the root of an empty list is the root of the zero leaves mixed with a zero length
+        let mut zero = [0; 32];
+        for _ in 0..13 {
+            zero = sha256(&zero, &zero);
+        }
+        assert_eq!(accumulator_root(&[]), H256(sha256(&zero, &[0; 32])));
+        assert_eq!(
+            era1_file_name("mainnet", 12, H256::repeat_byte(0xab)),
+            "mainnet-00012-abababab.era1"
+        );
+    }
+}
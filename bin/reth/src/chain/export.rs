@@ -0,0 +1,196 @@
+use super::era::{era1_file_name, Era1Writer, BLOCKS_PER_ERA1};
+use crate::dirs::{DataDirPath, MaybePlatformPath};
+use clap::{Parser, ValueEnum};
+use eyre::{bail, eyre, WrapErr};
+use reth_db::mdbx::{Env, EnvKind, WriteMap};
+use reth_primitives::{
+    rpc::{BlockId, BlockNumber as RpcBlockNumber},
+    Block, BlockHashOrNumber, BlockNumber, ChainSpec,
+};
+use reth_provider::{BlockProvider, HeaderProvider, ReceiptProvider, ShareableDatabase};
+use reth_rlp::Encodable;
+use reth_staged_sync::utils::{chainspec::genesis_value_parser, parse_path};
+use reth_static_file::StaticFileProvider;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+use tracing::info;
+
+/// Exports the blocks of the database to a file.
+#[derive(Debug, Parser)]
+pub struct ExportCommand {
+    /// The path to the data directory, the database is in its `db` folder.
+    ///
+    /// Defaults to the OS-specific data directory of the chain:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/<CHAIN>` or `$HOME/.local/share/reth/<CHAIN>`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/<CHAIN>`
+    /// - macOS: `$HOME/Library/Application Support/reth/<CHAIN>`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = genesis_value_parser
+    )]
+    chain: ChainSpec,
+
+    /// The first block to export.
+    ///
+    /// In the era1 format, it must be the first block of an era, i.e. a multiple of 8192.
+    #[arg(long, default_value_t = 0)]
+    from: BlockNumber,
+
+    /// The last block to export.
+    ///
+    /// Defaults to the last fully synced block.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+
+    /// The format of the export.
+    #[arg(long, value_enum, default_value_t = ExportFormat::Rlp)]
+    format: ExportFormat,
+
+    /// The file to write the blocks to, or the directory of the era1 files.
+    #[arg(value_name = "PATH", value_parser = parse_path)]
+    path: PathBuf,
+}
+
+/// The formats of the exported blocks.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+enum ExportFormat {
+    /// The RLP encoded blocks one after another, as read by `reth import`.
+    Rlp,
+    /// The era1 archive files, with the receipts and the total difficulty of the blocks. Only
+    /// meant for the blocks before the merge.
+    Era1,
+}
+
+impl ExportCommand {
+    /// Execute `export` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db_path = data_dir.db_path();
+        info!(target: "reth::cli", path = %db_path.display(), "Opening database");
+        let db = Env::<WriteMap>::open(&db_path, EnvKind::RO)?;
+        db.check_schema_version()?;
+        let static_files = StaticFileProvider::open(data_dir.static_files_path())?;
+        let provider = ShareableDatabase::new(db).with_static_files(static_files);
+
+        let to = match self.to {
+            Some(to) => to,
+            None => provider.chain_info()?.best_number,
+        };
+        if self.from > to {
+            bail!("The range of blocks to export {}..={to} is empty", self.from)
+        }
+
+        info!(target: "reth::cli", from = self.from, to, format = ?self.format, "Exporting blocks");
+        match self.format {
+            ExportFormat::Rlp => self.export_rlp(&provider, to)?,
+            ExportFormat::Era1 => self.export_era1(&provider, to)?,
+        }
+        info!(target: "reth::cli", path = %self.path.display(), "Blocks exported");
+        Ok(())
+    }
+
+    /// Writes the RLP encoded blocks to a single file.
+    fn export_rlp(&self, provider: &impl BlockProvider, to: BlockNumber) -> eyre::Result<()> {
+        let mut file = BufWriter::new(File::create(&self.path)?);
+        let mut buf = Vec::new();
+        for number in self.from..=to {
+            let block = read_block(provider, number)?;
+            buf.clear();
+            block.encode(&mut buf);
+            file.write_all(&buf)?;
+            log_progress(number, to);
+        }
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Writes one era1 file per era.
+    fn export_era1<P>(&self, provider: &P, to: BlockNumber) -> eyre::Result<()>
+    where
+        P: BlockProvider + HeaderProvider + ReceiptProvider,
+    {
+        if self.from % BLOCKS_PER_ERA1 != 0 {
+            bail!("The era1 files start at a multiple of {BLOCKS_PER_ERA1}, not at {}", self.from)
+        }
+        std::fs::create_dir_all(&self.path)?;
+
+        let network = self.chain.chain.to_string();
+        for start in (self.from..=to).step_by(BLOCKS_PER_ERA1 as usize) {
+            let epoch = start / BLOCKS_PER_ERA1;
+            let end = to.min(start + BLOCKS_PER_ERA1 - 1);
+
+            // the name of the file depends on its accumulator, so it is renamed once written
+            let tmp_path = self.path.join(format!("{network}-{epoch:05}.era1.tmp"));
+            let mut era = Era1Writer::new(BufWriter::new(File::create(&tmp_path)?), start)?;
+            for number in start..=end {
+                let block = read_block(provider, number)?;
+                let receipts = provider
+                    .receipts_by_block(BlockHashOrNumber::Number(number))?
+                    .ok_or_else(|| eyre!("The receipts of the block {number} are missing"))?;
+                let total_difficulty =
+                    provider.header_td(&block.header.hash_slow())?.ok_or_else(|| {
+                        eyre!("The total difficulty of the block {number} is missing")
+                    })?;
+                era.write_block(block, &receipts, total_difficulty)?;
+                log_progress(number, to);
+            }
+            let root = era.finish()?;
+
+            let path = self.path.join(era1_file_name(&network, epoch, root));
+            std::fs::rename(&tmp_path, &path).wrap_err("Could not rename the era1 file")?;
+            info!(target: "reth::cli", path = %path.display(), "Era1 file written");
+        }
+        Ok(())
+    }
+}
+
+fn read_block(provider: &impl BlockProvider, number: BlockNumber) -> eyre::Result<Block> {
+    provider
+        .block(BlockId::Number(RpcBlockNumber::Number(number.into())))?
+        .ok_or_else(|| eyre!("The block {number} is missing"))
+}
+
+fn log_progress(number: BlockNumber, to: BlockNumber) {
+    if number % 10_000 == 0 || number == to {
+        info!(target: "reth::cli", number, to, "Exported blocks");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_export_command() {
+        let command = ExportCommand::try_parse_from(["reth", "--to", "100", "blocks.rlp"]).unwrap();
+        assert_eq!(command.from, 0);
+        assert_eq!(command.to, Some(100));
+        assert_eq!(command.format, ExportFormat::Rlp);
+        assert_eq!(command.path, PathBuf::from("blocks.rlp"));
+
+        let command =
+            ExportCommand::try_parse_from(["reth", "--format", "era1", "--from", "8192", "era"])
+                .unwrap();
+        assert_eq!(command.format, ExportFormat::Era1);
+        assert_eq!(command.from, 8192);
+        assert_eq!(command.to, None);
+    }
+}
@@ -1,12 +1,12 @@
 use crate::{
-    dirs::{ConfigPath, DbPath, PlatformPath},
+    dirs::{DataDirPath, MaybePlatformPath},
     node::{handle_events, NodeEvent},
 };
 use clap::{crate_version, Parser};
 use eyre::Context;
 use futures::{Stream, StreamExt};
 use reth_consensus::beacon::BeaconConsensus;
-use reth_db::mdbx::{Env, WriteMap};
+use reth_db::database::Database;
 use reth_downloaders::{
     bodies::bodies::BodiesDownloaderBuilder,
     headers::reverse_headers::ReverseHeadersDownloaderBuilder, test_utils::FileClient,
@@ -16,11 +16,12 @@ use reth_interfaces::{
     p2p::headers::client::NoopStatusUpdater,
     sync::SyncStateUpdater,
 };
-use reth_primitives::ChainSpec;
+use reth_primitives::{BlockNumber, ChainSpec};
 use reth_staged_sync::{
     utils::{
         chainspec::genesis_value_parser,
         init::{init_db, init_genesis},
+        parse_path,
     },
     Config,
 };
@@ -28,25 +29,30 @@ use reth_stages::{
     prelude::*,
     stages::{ExecutionStage, SenderRecoveryStage, TotalDifficultyStage},
 };
-use std::sync::Arc;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use tracing::{debug, info};
 
 /// Syncs RLP encoded blocks from a file.
 #[derive(Debug, Parser)]
 pub struct ImportCommand {
     /// The path to the configuration file to use.
-    #[arg(long, value_name = "FILE", verbatim_doc_comment, default_value_t)]
-    config: PlatformPath<ConfigPath>,
+    ///
+    /// Defaults to `reth.toml` in the data directory.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment, value_parser = parse_path)]
+    config: Option<PathBuf>,
 
-    /// The path to the database folder.
+    /// The path to the data directory, the database is in its `db` folder.
     ///
-    /// Defaults to the OS-specific data directory:
+    /// Defaults to the OS-specific data directory of the chain:
     ///
-    /// - Linux: `$XDG_DATA_HOME/reth/db` or `$HOME/.local/share/reth/db`
-    /// - Windows: `{FOLDERID_RoamingAppData}/reth/db`
-    /// - macOS: `$HOME/Library/Application Support/reth/db`
-    #[arg(long, value_name = "PATH", verbatim_doc_comment, default_value_t)]
-    db: PlatformPath<DbPath>,
+    /// - Linux: `$XDG_DATA_HOME/reth/<CHAIN>` or `$HOME/.local/share/reth/<CHAIN>`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/<CHAIN>`
+    /// - macOS: `$HOME/Library/Application Support/reth/<CHAIN>`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
 
     /// The chain this node is running.
     ///
@@ -65,12 +71,13 @@ pub struct ImportCommand {
     )]
     chain: ChainSpec,
 
-    /// The path to a block file for import.
+    /// The path to a block file for import, with the RLP encoded blocks one after another as
+    /// written by `reth export` or `geth export`.
     ///
     /// The online stages (headers and bodies) are replaced by a file import, after which the
     /// remaining stages are executed.
-    #[arg(long, value_name = "IMPORT_PATH", verbatim_doc_comment)]
-    path: PlatformPath<ConfigPath>,
+    #[arg(value_name = "IMPORT_PATH", verbatim_doc_comment, value_parser = parse_path)]
+    path: PathBuf,
 }
 
 impl ImportCommand {
@@ -78,11 +85,16 @@ impl ImportCommand {
     pub async fn execute(self) -> eyre::Result<()> {
         info!(target: "reth::cli", "reth {} starting", crate_version!());
 
-        let config: Config = self.load_config()?;
-        info!(target: "reth::cli", path = %self.db, "Configuration loaded");
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let _lock = data_dir.lock()?;
+
+        let config_path = self.config.clone().unwrap_or_else(|| data_dir.config_path());
+        let config: Config = self.load_config(&config_path)?;
+        info!(target: "reth::cli", path = %config_path.display(), "Configuration loaded");
 
-        info!(target: "reth::cli", path = %self.db, "Opening database");
-        let db = Arc::new(init_db(&self.db)?);
+        let db_path = data_dir.db_path();
+        info!(target: "reth::cli", path = %db_path.display(), "Opening database");
+        let db = Arc::new(init_db(&db_path, config.db.env_config())?);
         info!(target: "reth::cli", "Database opened");
 
         debug!(target: "reth::cli", chain=%self.chain.chain, genesis=?self.chain.genesis_hash(), "Initializing genesis");
@@ -94,8 +106,9 @@ impl ImportCommand {
         let file_client = Arc::new(FileClient::new(&self.path).await?);
 
         // override the tip
-        let tip = file_client.tip().expect("file client has no tip");
-        info!(target: "reth::cli", "Chain file imported");
+        let tip = file_client.tip().ok_or_else(|| eyre::eyre!("The chain file has no blocks"))?;
+        let max_block = file_client.max_block().unwrap_or_default();
+        info!(target: "reth::cli", max_block, "Chain file imported");
 
         let (consensus, notifier) = BeaconConsensus::builder().build(self.chain.clone());
         debug!(target: "reth::cli", %tip, "Tip manually set");
@@ -106,8 +119,9 @@ impl ImportCommand {
         })?;
         info!(target: "reth::cli", "Consensus engine initialized");
 
-        let (mut pipeline, events) =
-            self.build_import_pipeline(config, db.clone(), &consensus, file_client).await?;
+        let (mut pipeline, events) = self
+            .build_import_pipeline(config, db.clone(), &consensus, file_client, max_block)
+            .await?;
 
         tokio::spawn(handle_events(events));
 
@@ -122,14 +136,16 @@ impl ImportCommand {
         Ok(())
     }
 
-    async fn build_import_pipeline<C>(
+    async fn build_import_pipeline<DB, C>(
         &self,
         config: Config,
-        db: Arc<Env<WriteMap>>,
+        db: Arc<DB>,
         consensus: &Arc<C>,
         file_client: Arc<FileClient>,
-    ) -> eyre::Result<(Pipeline<Env<WriteMap>, impl SyncStateUpdater>, impl Stream<Item = NodeEvent>)>
+        max_block: BlockNumber,
+    ) -> eyre::Result<(Pipeline<DB, impl SyncStateUpdater>, impl Stream<Item = NodeEvent>)>
     where
+        DB: Database + 'static,
         C: Consensus + 'static,
     {
         let header_downloader = ReverseHeadersDownloaderBuilder::from(config.stages.headers)
@@ -159,9 +175,10 @@ impl ImportCommand {
                 .set(ExecutionStage {
                     chain_spec: self.chain.clone(),
                     commit_threshold: config.stages.execution.commit_threshold,
+                    prefetch: true,
                 }),
             )
-            .with_max_block(0)
+            .with_max_block(max_block)
             .build();
 
         let events = pipeline.events().map(Into::into);
@@ -169,7 +186,7 @@ impl ImportCommand {
         Ok((pipeline, events))
     }
 
-    fn load_config(&self) -> eyre::Result<Config> {
-        confy::load_path::<Config>(&self.config).wrap_err("Could not load config")
+    fn load_config(&self, path: &Path) -> eyre::Result<Config> {
+        confy::load_path::<Config>(path).wrap_err("Could not load config")
     }
 }
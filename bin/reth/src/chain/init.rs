@@ -1,5 +1,6 @@
-use crate::dirs::{DbPath, PlatformPath};
+use crate::dirs::{DataDirPath, MaybePlatformPath};
 use clap::Parser;
+use reth_db::mdbx::EnvConfig;
 use reth_primitives::ChainSpec;
 use reth_staged_sync::utils::{
     chainspec::genesis_value_parser,
@@ -11,15 +12,15 @@ use tracing::info;
 /// Initializes the database with the genesis block.
 #[derive(Debug, Parser)]
 pub struct InitCommand {
-    /// The path to the database folder.
+    /// The path to the data directory, the database is in its `db` folder.
     ///
-    /// Defaults to the OS-specific data directory:
+    /// Defaults to the OS-specific data directory of the chain:
     ///
-    /// - Linux: `$XDG_DATA_HOME/reth/db` or `$HOME/.local/share/reth/db`
-    /// - Windows: `{FOLDERID_RoamingAppData}/reth/db`
-    /// - macOS: `$HOME/Library/Application Support/reth/db`
-    #[arg(long, value_name = "PATH", verbatim_doc_comment, default_value_t)]
-    db: PlatformPath<DbPath>,
+    /// - Linux: `$XDG_DATA_HOME/reth/<CHAIN>` or `$HOME/.local/share/reth/<CHAIN>`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/<CHAIN>`
+    /// - macOS: `$HOME/Library/Application Support/reth/<CHAIN>`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
 
     /// The chain this node is running.
     ///
@@ -44,8 +45,12 @@ impl InitCommand {
     pub async fn execute(&self) -> eyre::Result<()> {
         info!(target: "reth::cli", "reth import starting");
 
-        info!(target: "reth::cli", path = %self.db, "Opening database");
-        let db = Arc::new(init_db(&self.db)?);
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let _lock = data_dir.lock()?;
+
+        let db_path = data_dir.db_path();
+        info!(target: "reth::cli", path = %db_path.display(), "Opening database");
+        let db = Arc::new(init_db(&db_path, EnvConfig::default())?);
         info!(target: "reth::cli", "Database opened");
 
         info!(target: "reth::cli", "Writing genesis block");
@@ -28,6 +28,7 @@ pub fn run() -> eyre::Result<()> {
         Commands::Node(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
         Commands::Init(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::Import(command) => runner.run_until_ctrl_c(command.execute()),
+        Commands::Export(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::Db(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::Stage(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::DumpStage(command) => runner.run_until_ctrl_c(command.execute()),
@@ -49,6 +50,9 @@ pub enum Commands {
     /// This syncs RLP encoded blocks from a file.
     #[command(name = "import")]
     Import(chain::ImportCommand),
+    /// Export the blocks of the database to an RLP file or to era1 files.
+    #[command(name = "export")]
+    Export(chain::ExportCommand),
     /// Database debugging utilities
     #[command(name = "db")]
     Db(db::Command),
@@ -1,18 +1,28 @@
 //! Database debugging tool
-use crate::dirs::{DbPath, PlatformPath};
+use crate::dirs::{DataDirPath, MaybePlatformPath};
 use clap::{Parser, Subcommand};
 use comfy_table::{Cell, Row, Table as ComfyTable};
 use eyre::{Result, WrapErr};
 use reth_db::{
-    cursor::{DbCursorRO, Walker},
+    cursor::{DbCursorRO, DbDupCursorRO, Walker},
     database::Database,
-    table::Table,
-    tables,
-    transaction::DbTx,
+    mdbx::{Env, WriteMap},
+    models::StoredBlockBody,
+    table::{DupSort, Encode, Key, Table},
+    tables::{self, TableViewer, Tables},
+    transaction::{DbTx, DbTxMut},
+    verify::{self, Inconsistency},
 };
 use reth_interfaces::test_utils::generators::random_block_range;
-use reth_provider::insert_canonical_block;
-use std::collections::BTreeMap;
+use reth_primitives::{hex, BlockNumber, ChainSpec, Header, TxNumber};
+use reth_provider::{insert_canonical_block, Transaction};
+use reth_staged_sync::utils::chainspec::chain_spec_value_parser;
+use reth_stages::stages::{calculate_state_root, MERKLE_EXECUTION};
+use reth_static_file::StaticFileProvider;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 use tracing::{error, info};
 
 /// DB List TUI
@@ -21,15 +31,32 @@ mod tui;
 /// `reth db` command
 #[derive(Debug, Parser)]
 pub struct Command {
-    /// The path to the database folder.
+    /// The path to the data directory, the database is in its `db` folder.
+    ///
+    /// Defaults to the OS-specific data directory of the chain:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/<CHAIN>` or `$HOME/.local/share/reth/<CHAIN>`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/<CHAIN>`
+    /// - macOS: `$HOME/Library/Application Support/reth/<CHAIN>`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain of the data directory.
     ///
-    /// Defaults to the OS-specific data directory:
+    /// Possible values are either a built-in chain or the path to a chain specification file.
     ///
-    /// - Linux: `$XDG_DATA_HOME/reth/db` or `$HOME/.local/share/reth/db`
-    /// - Windows: `{FOLDERID_RoamingAppData}/reth/db`
-    /// - macOS: `$HOME/Library/Application Support/reth/db`
-    #[arg(long, value_name = "PATH", verbatim_doc_comment, default_value_t)]
-    db: PlatformPath<DbPath>,
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: ChainSpec,
 
     #[clap(subcommand)]
     command: Subcommands,
@@ -50,15 +77,52 @@ pub enum Subcommands {
         #[arg(default_value = DEFAULT_NUM_ITEMS)]
         len: u64,
     },
-    /// Deletes all database entries
-    Drop,
+    /// Gets the value of a key in a table, or all its values in a `DUPSORT` table
+    Get(GetArgs),
+    /// Deletes the entries of a table, or the whole database and its static files if no table is
+    /// given
+    Drop {
+        /// The table to empty
+        table: Option<Tables>,
+    },
+    /// Deletes the entries of all the tables and the static files, keeping the database
+    Clear,
+    /// Copies a compacted snapshot of the database into another directory
+    Backup(BackupArgs),
+    /// Checks the consistency of the canonical tables
+    Check {
+        /// Also check the hashed state against the plain state, recompute the state root and
+        /// compare it with the last merkleized header
+        #[arg(long)]
+        state_root: bool,
+    },
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db backup` command
+pub struct BackupArgs {
+    /// The directory to write the backup to
+    path: PathBuf,
+    /// Limits the backup I/O to this many bytes per second
+    #[arg(long, value_name = "BYTES")]
+    max_bytes_per_sec: Option<u64>,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db get` command
+pub struct GetArgs {
+    /// The table name
+    table: Tables,
+    /// The key, either a number for the tables keyed by numbers, e.g. block numbers, or the hex
+    /// encoding of the key prefixed by `0x`
+    key: String,
 }
 
 #[derive(Parser, Debug)]
 /// The arguments for the `reth db list` command
 pub struct ListArgs {
     /// The table name
-    table: String, // TODO: Convert to enum
+    table: Tables,
     /// Where to start iterating
     #[arg(long, short, default_value = "0")]
     start: usize,
@@ -70,13 +134,30 @@ pub struct ListArgs {
 impl Command {
     /// Execute `db` command
     pub async fn execute(&self) -> eyre::Result<()> {
-        std::fs::create_dir_all(&self.db)?;
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db_path = data_dir.db_path();
+
+        // the commands writing to the database can not run along a node, nor can the check,
+        // which writes the state trie in a transaction it drops
+        let lock = match self.command {
+            Subcommands::Seed { .. } |
+            Subcommands::Drop { .. } |
+            Subcommands::Clear |
+            Subcommands::Check { .. } => Some(data_dir.lock()?),
+            _ => None,
+        };
+        std::fs::create_dir_all(&db_path)?;
 
         // TODO: Auto-impl for Database trait
-        let db = reth_db::mdbx::Env::<reth_db::mdbx::WriteMap>::open(
-            self.db.as_ref(),
-            reth_db::mdbx::EnvKind::RW,
-        )?;
+        let db = Env::<WriteMap>::open(&db_path, reth_db::mdbx::EnvKind::RW)?;
+        // the database can only be migrated while the data directory is locked, and is not
+        // migrated just to be checked
+        if lock.is_some() && !matches!(self.command, Subcommands::Check { .. }) {
+            db.create_tables()?;
+            db.migrate()?;
+        } else {
+            db.check_schema_version()?;
+        }
 
         let mut tool = DbTool::new(&db)?;
 
@@ -88,99 +169,84 @@ impl Command {
                 stats_table.set_header([
                     "Table Name",
                     "# Entries",
+                    "Depth",
                     "Branch Pages",
                     "Leaf Pages",
                     "Overflow Pages",
                     "Total Size (KB)",
                 ]);
 
-                tool.db.view(|tx| {
-                    for table in tables::TABLES.iter().map(|(_, name)| name) {
-                        let table_db =
-                            tx.inner.open_db(Some(table)).wrap_err("Could not open db.")?;
-
-                        let stats = tx
-                            .inner
-                            .db_stat(&table_db)
-                            .wrap_err(format!("Could not find table: {table}"))?;
-
-                        // Defaults to 16KB right now but we should
-                        // re-evaluate depending on the DB we end up using
-                        // (e.g. REDB does not have these options as configurable intentionally)
-                        let page_size = stats.page_size() as usize;
-                        let leaf_pages = stats.leaf_pages();
-                        let branch_pages = stats.branch_pages();
-                        let overflow_pages = stats.overflow_pages();
-                        let num_pages = leaf_pages + branch_pages + overflow_pages;
-                        let table_size = page_size * num_pages;
-
-                        let mut row = Row::new();
-                        row.add_cell(Cell::new(table))
-                            .add_cell(Cell::new(stats.entries()))
-                            .add_cell(Cell::new(branch_pages))
-                            .add_cell(Cell::new(leaf_pages))
-                            .add_cell(Cell::new(overflow_pages))
-                            .add_cell(Cell::new(table_size / 1024));
-                        stats_table.add_row(row);
-                    }
-                    Ok::<(), eyre::Report>(())
-                })??;
+                let stats = tool.db.stats()?;
+                for table in &stats.tables {
+                    let mut row = Row::new();
+                    row.add_cell(Cell::new(table.name))
+                        .add_cell(Cell::new(table.entries))
+                        .add_cell(Cell::new(table.depth))
+                        .add_cell(Cell::new(table.branch_pages))
+                        .add_cell(Cell::new(table.leaf_pages))
+                        .add_cell(Cell::new(table.overflow_pages))
+                        .add_cell(Cell::new(stats.table_size(table) / 1024));
+                    stats_table.add_row(row);
+                }
 
                 println!("{stats_table}");
+                println!(
+                    "Freelist: {} pages ({} KB), file size: {} KB",
+                    stats.freelist_pages,
+                    stats.freelist_size() / 1024,
+                    stats.file_size / 1024
+                );
             }
             Subcommands::Seed { len } => {
                 tool.seed(*len)?;
             }
             Subcommands::List(args) => {
-                macro_rules! table_tui {
-                    ($arg:expr, $start:expr, $len:expr => [$($table:ident),*]) => {
-                        match $arg {
-                            $(stringify!($table) => {
-                                tool.db.view(|tx| {
-                                    let table_db = tx.inner.open_db(Some(stringify!($table))).wrap_err("Could not open db.")?;
-                                    let stats = tx.inner.db_stat(&table_db).wrap_err(format!("Could not find table: {}", stringify!($table)))?;
-                                    let total_entries = stats.entries();
-                                    if $start > total_entries - 1 {
-                                        error!(
-                                            target: "reth::cli",
-                                            "Start index {start} is greater than the final entry index ({final_entry_idx}) in the table {table}",
-                                            start = $start,
-                                            final_entry_idx = total_entries - 1,
-                                            table = stringify!($table)
-                                        );
-                                        return Ok(());
-                                    }
-                                    let map = tool.list::<tables::$table>($start, $len)?;
-                                    tui::DbListTUI::<tables::$table>::show_tui(map, $start, total_entries)
-                                })??
-                            },)*
-                            _ => {
-                                error!(target: "reth::cli", "Unknown table.");
-                                return Ok(());
-                            }
-                        }
-                    }
+                args.table.view(&ListTableViewer { tool: &tool, args })?;
+            }
+            Subcommands::Get(args) => {
+                args.table.view(&GetValueViewer { tool: &tool, key: &args.key })?;
+            }
+            Subcommands::Drop { table: None } => {
+                tool.drop(&db_path)?;
+                drop_static_files(&data_dir.static_files_path())?;
+            }
+            Subcommands::Drop { table: Some(table) } => {
+                info!(target: "reth::cli", "Clearing the table {}", table.name());
+                table.view(&ClearViewer { db: tool.db })?;
+            }
+            Subcommands::Clear => {
+                info!(target: "reth::cli", "Clearing all the tables");
+                for table in Tables::ALL {
+                    table.view(&ClearViewer { db: tool.db })?;
                 }
-
-                table_tui!(args.table.as_str(), args.start, args.len => [
-                    CanonicalHeaders,
-                    HeaderTD,
-                    HeaderNumbers,
-                    Headers,
-                    BlockBodies,
-                    BlockOmmers,
-                    TxHashNumber,
-                    PlainStorageState,
-                    PlainAccountState,
-                    BlockTransitionIndex,
-                    TxTransitionIndex,
-                    SyncStage,
-                    TxHashNumber,
-                    Transactions
-                ]);
+                // the blocks moved to the static files are gone along with the tables
+                drop_static_files(&data_dir.static_files_path())?;
+                info!(target: "reth::cli", "Database cleared");
+            }
+            Subcommands::Backup(args) => {
+                info!(target: "reth::cli", path = ?args.path, "Backing up the database");
+                match args.max_bytes_per_sec {
+                    Some(bytes_per_sec) => db.backup_throttled(&args.path, bytes_per_sec)?,
+                    None => db.backup(&args.path)?,
+                }
+                info!(target: "reth::cli", "Backup done");
             }
-            Subcommands::Drop => {
-                tool.drop(&self.db)?;
+            Subcommands::Check { state_root } => {
+                let static_files = StaticFileProvider::open(data_dir.static_files_path())?;
+                let tx = tool.db.tx()?;
+                let blocks = CheckedBlocks { tx: &tx, static_files: &static_files };
+                let mut inconsistencies = verify::check_all(&tx, &blocks, *state_root)?;
+                drop(tx);
+                if *state_root {
+                    inconsistencies.extend(check_state_root(tool.db)?);
+                }
+                for inconsistency in &inconsistencies {
+                    error!(target: "reth::cli", "{inconsistency}");
+                }
+                if !inconsistencies.is_empty() {
+                    eyre::bail!("Found {} inconsistencies in the database", inconsistencies.len());
+                }
+                info!(target: "reth::cli", "No inconsistencies found");
             }
         }
 
@@ -217,7 +283,7 @@ impl<'a, DB: Database> DbTool<'a, DB> {
 
     /// Grabs the contents of the table within a certain index range and places the
     /// entries into a [`HashMap`][std::collections::HashMap].
-    fn list<T: Table>(&mut self, start: usize, len: usize) -> Result<BTreeMap<T::Key, T::Value>> {
+    fn list<T: Table>(&self, start: usize, len: usize) -> Result<BTreeMap<T::Key, T::Value>> {
         let data = self.db.view(|tx| {
             let mut cursor = tx.cursor_read::<T>().expect("Was not able to obtain a cursor.");
 
@@ -233,9 +299,194 @@ impl<'a, DB: Database> DbTool<'a, DB> {
             .map_err(|e| eyre::eyre!(e))
     }
 
-    fn drop(&mut self, path: &PlatformPath<DbPath>) -> Result<()> {
-        info!(target: "reth::cli", "Dropping db at {}", path);
+    fn drop(&mut self, path: &Path) -> Result<()> {
+        info!(target: "reth::cli", "Dropping db at {}", path.display());
         std::fs::remove_dir_all(path).wrap_err("Dropping the database failed")?;
         Ok(())
     }
 }
+
+/// Reads the blocks for the checks through the transaction of the checks, then through the static
+/// files for the blocks moved out of the database.
+struct CheckedBlocks<'a, TX> {
+    tx: &'a TX,
+    static_files: &'a StaticFileProvider,
+}
+
+impl<'a, TX: DbTx<'a>> verify::BlockTables for CheckedBlocks<'_, TX> {
+    fn header(&self, number: BlockNumber) -> reth_interfaces::Result<Option<Header>> {
+        match self.tx.get::<tables::Headers>(number)? {
+            Some(header) => Ok(Some(header)),
+            None => Ok(self.static_files.get::<tables::Headers>(number)?),
+        }
+    }
+
+    fn block_body(&self, number: BlockNumber) -> reth_interfaces::Result<Option<StoredBlockBody>> {
+        match self.tx.get::<tables::BlockBodies>(number)? {
+            Some(body) => Ok(Some(body)),
+            None => Ok(self.static_files.get::<tables::BlockBodies>(number)?),
+        }
+    }
+
+    fn has_transaction(&self, id: TxNumber) -> reth_interfaces::Result<bool> {
+        if self.tx.get::<tables::Transactions>(id)?.is_some() {
+            return Ok(true)
+        }
+        Ok(self.static_files.get::<tables::Transactions>(id)?.is_some())
+    }
+}
+
+/// Removes the static files holding the finalized blocks moved out of the database, if any.
+fn drop_static_files(path: &Path) -> Result<()> {
+    if path.exists() {
+        info!(target: "reth::cli", "Dropping static files at {}", path.display());
+        std::fs::remove_dir_all(path).wrap_err("Dropping the static files failed")?;
+    }
+    Ok(())
+}
+
+/// Recomputes the state trie from the hashed state and checks its root against the header of the
+/// last block processed by the merkle stage.
+///
+/// This rebuilds the whole trie in a write transaction, which is dropped afterwards, so it is
+/// much slower than the other checks.
+fn check_state_root<DB: Database>(db: &DB) -> Result<Vec<Inconsistency>> {
+    let mut tx = Transaction::new(db)?;
+
+    let number = match MERKLE_EXECUTION.get_progress(&**tx)? {
+        Some(number) => number,
+        None => return Ok(Vec::new()),
+    };
+    let expected = tx.get_header(number)?.state_root;
+    let got = calculate_state_root(&tx)?;
+    tx.drop()?;
+
+    Ok(if got == expected {
+        Vec::new()
+    } else {
+        vec![Inconsistency::StateRoot { number, expected, got }]
+    })
+}
+
+/// Shows the entries of a table, picked at runtime, in the [`tui::DbListTUI`].
+struct ListTableViewer<'a> {
+    tool: &'a DbTool<'a, Env<WriteMap>>,
+    args: &'a ListArgs,
+}
+
+impl TableViewer<()> for ListTableViewer<'_> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<(), Self::Error> {
+        self.tool.db.view(|tx| {
+            let table_db = tx.inner.open_db(Some(T::NAME)).wrap_err("Could not open db.")?;
+            let stats = tx
+                .inner
+                .db_stat(&table_db)
+                .wrap_err(format!("Could not find table: {}", T::NAME))?;
+            let total_entries = stats.entries();
+            if self.args.start > total_entries - 1 {
+                error!(
+                    target: "reth::cli",
+                    "Start index {start} is greater than the final entry index ({final_entry_idx}) in the table {table}",
+                    start = self.args.start,
+                    final_entry_idx = total_entries - 1,
+                    table = T::NAME
+                );
+                return Ok(())
+            }
+            let map = self.tool.list::<T>(self.args.start, self.args.len)?;
+            tui::DbListTUI::<T>::show_tui(map, self.args.start, total_entries)
+        })?
+    }
+}
+
+/// Prints the value of a key in a table picked at runtime, as JSON.
+struct GetValueViewer<'a> {
+    tool: &'a DbTool<'a, Env<WriteMap>>,
+    key: &'a str,
+}
+
+impl TableViewer<()> for GetValueViewer<'_> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<(), Self::Error> {
+        let key = parse_key::<T::Key>(self.key)?;
+        match self.tool.db.view(|tx| tx.get::<T>(key))?? {
+            Some(value) => println!("{}", serde_json::to_string_pretty(&value)?),
+            None => error!(target: "reth::cli", "No entry for the key {} in {}", self.key, T::NAME),
+        }
+        Ok(())
+    }
+
+    fn view_dupsort<T: DupSort>(&self) -> Result<(), Self::Error> {
+        let key = parse_key::<T::Key>(self.key)?;
+        let values = self.tool.db.view(|tx| {
+            let mut cursor = tx.cursor_dup_read::<T>()?;
+            let walker = cursor.walk_dup(Some(key.clone()), None)?;
+            walker
+                .take_while(|entry| entry.as_ref().map_or(true, |(k, _)| *k == key))
+                .map(|entry| entry.map(|(_, value)| value))
+                .collect::<Result<Vec<_>, _>>()
+        })??;
+        if values.is_empty() {
+            error!(target: "reth::cli", "No entry for the key {} in {}", self.key, T::NAME);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&values)?);
+        }
+        Ok(())
+    }
+}
+
+/// Deletes all the entries of a table picked at runtime.
+struct ClearViewer<'a, DB: Database> {
+    db: &'a DB,
+}
+
+impl<DB: Database> TableViewer<()> for ClearViewer<'_, DB> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<(), Self::Error> {
+        self.db.update(|tx| tx.clear::<T>())??;
+        Ok(())
+    }
+}
+
+/// Parses a key given on the command line: `0x` followed by the hex encoding of the key, or a
+/// number which is encoded the way the numeric keys are.
+fn parse_key<K: Key>(key: &str) -> Result<K> {
+    let encoded = match key.strip_prefix("0x") {
+        Some(encoded) => hex::decode(encoded).wrap_err("Invalid hex key")?,
+        None => {
+            let number = key.parse::<u64>().wrap_err("The key is neither a number nor hex")?;
+            number.encode().as_ref().to_vec()
+        }
+    };
+    K::decode(encoded).map_err(|err| eyre::eyre!("Invalid key {key} for the table: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::models::BlockNumHash;
+    use reth_primitives::H256;
+
+    #[test]
+    fn parse_keys() {
+        assert_eq!(parse_key::<u64>("1234").unwrap(), 1234);
+        assert_eq!(
+            parse_key::<u64>(&format!("0x{}", hex::encode(1234u64.encode()))).unwrap(),
+            1234
+        );
+        assert!(parse_key::<u64>("key").is_err());
+        assert!(parse_key::<u64>("0xzz").is_err());
+
+        let hash = H256::repeat_byte(0x12);
+        assert_eq!(parse_key::<H256>(&format!("{hash:?}")).unwrap(), hash);
+        let key = BlockNumHash((1, hash));
+        assert_eq!(
+            parse_key::<BlockNumHash>(&format!("0x{}", hex::encode(key.encode()))).unwrap(),
+            key
+        );
+    }
+}
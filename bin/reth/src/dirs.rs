@@ -1,8 +1,12 @@
 //! reth data directories.
+use eyre::WrapErr;
+use reth_primitives::Chain;
 use reth_staged_sync::utils::parse_path;
 use std::{
     env::VarError,
     fmt::{Debug, Display, Formatter},
+    fs::{self, File, OpenOptions},
+    io::{self, Seek, Write},
     path::{Path, PathBuf},
     str::FromStr,
 };
@@ -42,62 +46,29 @@ pub fn logs_dir() -> Option<PathBuf> {
     cache_dir().map(|root| root.join("logs"))
 }
 
-/// Returns the path to the reth jwtsecret directory.
-///
-/// Refer to [dirs_next::cache_dir] for cross-platform behavior.
-pub fn jwt_secret_dir() -> Option<PathBuf> {
-    data_dir().map(|root| root.join("jwtsecret"))
-}
-
-/// Returns the path to the reth database.
-///
-/// Refer to [dirs_next::data_dir] for cross-platform behavior.
-#[derive(Default, Debug, Clone)]
-#[non_exhaustive]
-pub struct DbPath;
-
-impl XdgPath for DbPath {
-    fn resolve() -> Option<PathBuf> {
-        database_path()
-    }
-}
-
-/// Returns the path to the default JWT secret hex file.
+/// Returns the path to the reth data directory.
 ///
 /// Refer to [dirs_next::data_dir] for cross-platform behavior.
 #[derive(Default, Debug, Clone, PartialEq)]
 #[non_exhaustive]
-pub struct JwtSecretPath;
-
-impl XdgPath for JwtSecretPath {
-    fn resolve() -> Option<PathBuf> {
-        jwt_secret_dir().map(|p| p.join("jwt.hex"))
-    }
-}
-
-/// Returns the path to the default reth configuration file.
-///
-/// Refer to [dirs_next::config_dir] for cross-platform behavior.
-#[derive(Default, Debug, Clone)]
-#[non_exhaustive]
-pub struct ConfigPath;
+pub struct DataDirPath;
 
-impl XdgPath for ConfigPath {
+impl XdgPath for DataDirPath {
     fn resolve() -> Option<PathBuf> {
-        config_dir().map(|p| p.join("reth.toml"))
+        data_dir()
     }
 }
 
-/// Returns the path to the default reth known peers file.
+/// Returns the path to the reth database.
 ///
-/// Refer to [dirs_next::config_dir] for cross-platform behavior.
+/// Refer to [dirs_next::data_dir] for cross-platform behavior.
 #[derive(Default, Debug, Clone)]
 #[non_exhaustive]
-pub struct KnownPeersPath;
+pub struct DbPath;
 
-impl XdgPath for KnownPeersPath {
+impl XdgPath for DbPath {
     fn resolve() -> Option<PathBuf> {
-        database_path().map(|p| p.join("known-peers.json"))
+        database_path()
     }
 }
 
@@ -116,7 +87,8 @@ impl XdgPath for LogsDir {
 
 /// A small helper trait for unit structs that represent a standard path following the XDG
 /// path specification.
-trait XdgPath {
+pub trait XdgPath {
+    /// Returns the default path, if it can be resolved on this platform.
     fn resolve() -> Option<PathBuf>;
 }
 
@@ -176,3 +148,241 @@ impl<D> From<PlatformPath<D>> for PathBuf {
         value.0
     }
 }
+
+/// An optional user-given path, which defaults to the OS-specific path of the chain.
+///
+/// # Example
+///
+/// ```
+/// use reth::dirs::{DataDirPath, MaybePlatformPath};
+/// use reth_primitives::Chain;
+/// use std::str::FromStr;
+///
+/// // Resolves to the platform-specific data directory of mainnet, e.g.
+/// // `$HOME/.local/share/reth/mainnet` on linux
+/// let default: MaybePlatformPath<DataDirPath> = MaybePlatformPath::default();
+/// let mainnet = default.unwrap_or_chain_default(Chain::mainnet());
+///
+/// // Resolves to `$(pwd)/my/datadir`, whatever the chain
+/// let custom: MaybePlatformPath<DataDirPath> = MaybePlatformPath::from_str("my/datadir").unwrap();
+/// let custom = custom.unwrap_or_chain_default(Chain::mainnet());
+///
+/// assert_ne!(mainnet.data_dir_path(), custom.data_dir_path());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaybePlatformPath<D>(Option<PlatformPath<D>>);
+
+impl<D: XdgPath> MaybePlatformPath<D> {
+    /// Returns the user-given path, or the default path joined with the chain.
+    pub fn unwrap_or_chain_default(&self, chain: Chain) -> ChainPath<D> {
+        let path = match &self.0 {
+            Some(path) => path.0.clone(),
+            None => PlatformPath::<D>::default().0.join(chain.to_string()),
+        };
+        ChainPath(path, std::marker::PhantomData)
+    }
+}
+
+impl<D> Default for MaybePlatformPath<D> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<D> Display for MaybePlatformPath<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(path) => write!(f, "{path}"),
+            // the chain is not known yet, this is only shown as the default value of the argument
+            None => write!(f, "default"),
+        }
+    }
+}
+
+impl<D> FromStr for MaybePlatformPath<D> {
+    type Err = shellexpand::LookupError<VarError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(Some(PlatformPath::from_str(s)?)))
+    }
+}
+
+/// The data directory of a chain, with the paths of the files it holds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainPath<D>(PathBuf, std::marker::PhantomData<D>);
+
+impl<D> ChainPath<D> {
+    /// Returns the path to the data directory.
+    pub fn data_dir_path(&self) -> PathBuf {
+        self.0.clone()
+    }
+
+    /// Returns the path to the database: `<datadir>/db`.
+    pub fn db_path(&self) -> PathBuf {
+        self.0.join("db")
+    }
+
+    /// Returns the path to the static files: `<datadir>/static_files`.
+    pub fn static_files_path(&self) -> PathBuf {
+        self.0.join("static_files")
+    }
+
+    /// Returns the path to the blobs of the transaction pool: `<datadir>/blobstore`.
+    pub fn blobstore_path(&self) -> PathBuf {
+        self.0.join("blobstore")
+    }
+
+    /// Returns the path to the configuration file: `<datadir>/reth.toml`.
+    pub fn config_path(&self) -> PathBuf {
+        self.0.join("reth.toml")
+    }
+
+    /// Returns the path to the JWT secret of the auth server: `<datadir>/jwt.hex`.
+    pub fn jwt_path(&self) -> PathBuf {
+        self.0.join("jwt.hex")
+    }
+
+    /// Returns the path to the known peers file: `<datadir>/known-peers.json`.
+    pub fn known_peers_path(&self) -> PathBuf {
+        self.0.join("known-peers.json")
+    }
+
+    /// Returns the path to the banned peers file: `<datadir>/banned-peers.json`.
+    pub fn banned_peers_path(&self) -> PathBuf {
+        self.0.join("banned-peers.json")
+    }
+
+    /// Returns the path to the lock file of the running instance: `<datadir>/reth.pid`.
+    pub fn lock_path(&self) -> PathBuf {
+        self.0.join("reth.pid")
+    }
+
+    /// Creates the data directory and locks it, see [DataDirLock].
+    pub fn lock(&self) -> eyre::Result<DataDirLock> {
+        fs::create_dir_all(&self.0)
+            .wrap_err_with(|| format!("Could not create data directory {}", self.0.display()))?;
+        DataDirLock::acquire(self.lock_path())
+    }
+}
+
+impl<D> Display for ChainPath<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.display())
+    }
+}
+
+/// The lock of a data directory, which prevents a second instance from running on it.
+///
+/// The lock is held on the lock file by the operating system until the lock is dropped, or the
+/// process exits, even if it did not shut down cleanly. The file holds the process id of the last
+/// instance which locked it, and is left in place.
+#[derive(Debug)]
+pub struct DataDirLock {
+    /// The locked file, it is unlocked when closed.
+    _file: File,
+}
+
+impl DataDirLock {
+    /// Acquires the lock at the given path, fails if it is held by another instance.
+    pub fn acquire(path: PathBuf) -> eyre::Result<Self> {
+        let mut file = match lock_file(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                // the file can not be read while it is locked on windows
+                let pid = fs::read_to_string(&path)
+                    .map(|pid| format!(" (pid {})", pid.trim()))
+                    .unwrap_or_default();
+                eyre::bail!(
+                    "The data directory is in use by another instance{pid}, lock file {}",
+                    path.display()
+                )
+            }
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("Could not open lock file {}", path.display()))
+            }
+        };
+        write_pid(&mut file)
+            .wrap_err_with(|| format!("Could not write lock file {}", path.display()))?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// Replaces the content of the lock file with the id of this process.
+fn write_pid(file: &mut File) -> io::Result<()> {
+    file.set_len(0)?;
+    file.rewind()?;
+    file.write_all(std::process::id().to_string().as_bytes())?;
+    file.sync_all()
+}
+
+/// Opens the lock file and locks it exclusively, with an advisory lock of the whole file.
+#[cfg(unix)]
+fn lock_file(path: &Path) -> io::Result<File> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = OpenOptions::new().read(true).write(true).create(true).open(path)?;
+    // SAFETY: the descriptor is owned by `file`, the lock is released when it is closed.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        return Err(io::Error::last_os_error())
+    }
+    Ok(file)
+}
+
+/// Opens the lock file without sharing it, so that it can not be opened by another instance until
+/// it is closed.
+#[cfg(windows)]
+fn lock_file(path: &Path) -> io::Result<File> {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    match OpenOptions::new().read(true).write(true).create(true).share_mode(0).open(path) {
+        Err(err) if err.raw_os_error() == Some(ERROR_SHARING_VIOLATION) => {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_paths() {
+        let default = MaybePlatformPath::<DataDirPath>::default()
+            .unwrap_or_chain_default(Chain::goerli())
+            .data_dir_path();
+        assert_eq!(default, data_dir().unwrap().join("goerli"));
+
+        let custom = MaybePlatformPath::<DataDirPath>::from_str("my/datadir")
+            .unwrap()
+            .unwrap_or_chain_default(Chain::goerli());
+        assert!(custom.data_dir_path().ends_with("my/datadir"));
+        assert_eq!(custom.db_path(), custom.data_dir_path().join("db"));
+        assert_eq!(custom.config_path(), custom.data_dir_path().join("reth.toml"));
+    }
+
+    #[test]
+    fn data_dir_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let datadir = MaybePlatformPath::<DataDirPath>::from_str(dir.path().to_str().unwrap())
+            .unwrap()
+            .unwrap_or_chain_default(Chain::mainnet());
+
+        // the process id of a previous instance is replaced
+        fs::create_dir_all(datadir.data_dir_path()).unwrap();
+        fs::write(datadir.lock_path(), u32::MAX.to_string()).unwrap();
+
+        let lock = datadir.lock().unwrap();
+        assert_eq!(
+            fs::read_to_string(datadir.lock_path()).unwrap(),
+            std::process::id().to_string()
+        );
+        let err = datadir.lock().unwrap_err();
+        assert!(err.to_string().contains(&format!("pid {}", std::process::id())));
+
+        drop(lock);
+        assert!(datadir.lock().is_ok());
+    }
+}
@@ -7,27 +7,46 @@ use execution::dump_execution_stage;
 
 use crate::{
     db::DbTool,
-    dirs::{DbPath, PlatformPath},
+    dirs::{DataDirPath, DbPath, MaybePlatformPath, PlatformPath},
 };
 use clap::Parser;
 use reth_db::{
-    cursor::DbCursorRO, database::Database, table::TableImporter, tables, transaction::DbTx,
+    cursor::DbCursorRO, database::Database, mdbx::EnvConfig, table::TableImporter, tables,
+    transaction::DbTx,
 };
-use reth_staged_sync::utils::init::init_db;
+use reth_primitives::ChainSpec;
+use reth_staged_sync::utils::{chainspec::chain_spec_value_parser, init::init_db};
 use tracing::info;
 
 /// `reth dump-stage` command
 #[derive(Debug, Parser)]
 pub struct Command {
-    /// The path to the database folder.
+    /// The path to the data directory, the database is in its `db` folder.
     ///
-    /// Defaults to the OS-specific data directory:
+    /// Defaults to the OS-specific data directory of the chain:
     ///
-    /// - Linux: `$XDG_DATA_HOME/reth/db` or `$HOME/.local/share/reth/db`
-    /// - Windows: `{FOLDERID_RoamingAppData}/reth/db`
-    /// - macOS: `$HOME/Library/Application Support/reth/db`
-    #[arg(long, value_name = "PATH", verbatim_doc_comment, default_value_t)]
-    db: PlatformPath<DbPath>,
+    /// - Linux: `$XDG_DATA_HOME/reth/<CHAIN>` or `$HOME/.local/share/reth/<CHAIN>`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/<CHAIN>`
+    /// - macOS: `$HOME/Library/Application Support/reth/<CHAIN>`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain of the data directory.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: ChainSpec,
 
     #[clap(subcommand)]
     command: Stages,
@@ -69,13 +88,15 @@ pub struct StageCommand {
 impl Command {
     /// Execute `dump-stage` command
     pub async fn execute(&self) -> eyre::Result<()> {
-        std::fs::create_dir_all(&self.db)?;
+        let db_path = self.datadir.unwrap_or_chain_default(self.chain.chain).db_path();
+        std::fs::create_dir_all(&db_path)?;
 
         // TODO: Auto-impl for Database trait
         let db = reth_db::mdbx::Env::<reth_db::mdbx::WriteMap>::open(
-            self.db.as_ref(),
+            &db_path,
             reth_db::mdbx::EnvKind::RW,
         )?;
+        db.check_schema_version()?;
 
         let mut tool = DbTool::new(&db)?;
 
@@ -104,7 +125,7 @@ pub(crate) fn setup<DB: Database>(
 
     info!(target: "reth::cli", "Creating separate db at {}", output_db);
 
-    let output_db = init_db(output_db)?;
+    let output_db = init_db(output_db, EnvConfig::default())?;
 
     output_db.update(|tx| {
         tx.import_table_with_range::<tables::BlockTransitionIndex, _>(
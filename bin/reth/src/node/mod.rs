@@ -2,8 +2,8 @@
 //!
 //! Starts the client
 use crate::{
-    args::{NetworkArgs, RpcServerArgs},
-    dirs::{ConfigPath, DbPath, PlatformPath},
+    args::{NetworkArgs, PruneArgs, RpcServerArgs, TxPoolArgs},
+    dirs::{ChainPath, DataDirPath, MaybePlatformPath},
     prometheus_exporter,
     runner::CliContext,
 };
@@ -12,16 +12,15 @@ use eyre::Context;
 use fdlimit::raise_fd_limit;
 use futures::{pin_mut, stream::select as stream_select, Stream, StreamExt};
 use reth_consensus::beacon::BeaconConsensus;
-use reth_db::{
-    database::Database,
-    mdbx::{Env, WriteMap},
-    tables,
-    transaction::DbTx,
-};
+use reth_db::{database::Database, tables, transaction::DbTx};
 use reth_downloaders::{
     bodies::bodies::BodiesDownloaderBuilder,
     headers::reverse_headers::ReverseHeadersDownloaderBuilder,
 };
+use reth_executor::blockchain_tree::{
+    maintain_state_cache, BlockchainTree, CanonStateNotifications, CanonStateSubscriptions,
+};
+use reth_exex::{ExExManager, FinishedExExHeight};
 use reth_interfaces::{
     consensus::{Consensus, ForkchoiceState},
     p2p::{
@@ -34,41 +33,78 @@ use reth_network::{
     error::NetworkError, NetworkConfig, NetworkEvent, NetworkHandle, NetworkManager,
 };
 use reth_network_api::NetworkInfo;
-use reth_primitives::{BlockNumber, ChainSpec, Head, H256};
-use reth_provider::{BlockProvider, HeaderProvider, ShareableDatabase};
-use reth_rpc_builder::{RethRpcModule, RpcServerConfig, TransportRpcModuleConfig};
+use reth_payload_builder::{PayloadBuilder, PayloadBuilderConfig, PayloadBuilderService};
+use reth_primitives::{
+    eip4844::KzgSettings, BlockNumber, ChainSpec, Head, IntoRecoveredTransaction, H256,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ShareableDatabase, StateCache, StateProviderFactory,
+};
+use reth_rpc_builder::RpcModuleBuilder;
+use reth_rpc_engine_api::EngineApi;
 use reth_staged_sync::{
     utils::{
         chainspec::genesis_value_parser,
         init::{init_db, init_genesis},
-        parse_socket_address,
+        parse_path, parse_socket_address,
     },
     Config,
 };
 use reth_stages::{
     prelude::*,
-    stages::{ExecutionStage, SenderRecoveryStage, TotalDifficultyStage, FINISH},
+    stages::{
+        ExecutionStage, SenderRecoveryStage, TotalDifficultyStage, TransactionLookupStage, FINISH,
+        TRANSACTION_LOOKUP,
+    },
+    PrunePart, Pruner, MINIMUM_PRUNING_DISTANCE,
 };
-use reth_tasks::TaskExecutor;
-use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+use reth_static_file::{StaticFileProducer, StaticFileProvider};
+use reth_tasks::{shutdown::Shutdown, TaskExecutor};
+use reth_transaction_pool::{
+    blobstore::DiskFileBlobStore, maintain::maintain_transaction_pool, CostOrdering,
+    EthTransactionValidator, Pool, PooledTransaction, TransactionPool,
+    TransactionValidationTaskExecutor,
+};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{mpsc::unbounded_channel, watch};
 use tracing::{debug, info, trace, warn};
 
+/// The number of tasks validating the transactions of the pool.
+const TRANSACTION_VALIDATION_TASKS: usize = 1;
+
+/// The transaction pool of the node, validating ethereum transactions against the state of
+/// `Client`.
+type EthPool<Client> = Pool<
+    TransactionValidationTaskExecutor<EthTransactionValidator<Client, PooledTransaction>>,
+    CostOrdering<PooledTransaction>,
+    DiskFileBlobStore,
+>;
+
 /// Start the node
 #[derive(Debug, Parser)]
 pub struct Command {
     /// The path to the configuration file to use.
-    #[arg(long, value_name = "FILE", verbatim_doc_comment, default_value_t)]
-    config: PlatformPath<ConfigPath>,
+    ///
+    /// Defaults to `reth.toml` in the data directory, which is created with the default
+    /// configuration if it does not exist.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment, value_parser = parse_path)]
+    config: Option<PathBuf>,
 
-    /// The path to the database folder.
+    /// The path to the data directory, holding the database, the static files, the configuration
+    /// file, the JWT secret and the peers files.
     ///
-    /// Defaults to the OS-specific data directory:
+    /// Defaults to the OS-specific data directory of the chain:
     ///
-    /// - Linux: `$XDG_DATA_HOME/reth/db` or `$HOME/.local/share/reth/db`
-    /// - Windows: `{FOLDERID_RoamingAppData}/reth/db`
-    /// - macOS: `$HOME/Library/Application Support/reth/db`
-    #[arg(long, value_name = "PATH", verbatim_doc_comment, default_value_t)]
-    db: PlatformPath<DbPath>,
+    /// - Linux: `$XDG_DATA_HOME/reth/<CHAIN>` or `$HOME/.local/share/reth/<CHAIN>`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/<CHAIN>`
+    /// - macOS: `$HOME/Library/Application Support/reth/<CHAIN>`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
 
     /// The chain this node is running.
     ///
@@ -108,55 +144,163 @@ pub struct Command {
 
     #[clap(flatten)]
     rpc: RpcServerArgs,
+
+    #[clap(flatten)]
+    prune: PruneArgs,
+
+    #[clap(flatten)]
+    txpool: TxPoolArgs,
+
+    /// Move the finalized blocks out of the database, into static files.
+    ///
+    /// The static files are stored in the `static_files` folder of the data directory.
+    #[arg(long = "static-files", verbatim_doc_comment)]
+    static_files: bool,
 }
 
 impl Command {
     /// Execute `node` command
     // TODO: RPC
-    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+    pub async fn execute(mut self, ctx: CliContext) -> eyre::Result<()> {
         info!(target: "reth::cli", "reth {} starting", crate_version!());
 
         // Raise the fd limit of the process.
         // Does not do anything on windows.
         raise_fd_limit();
 
-        let mut config: Config = self.load_config()?;
-        info!(target: "reth::cli", path = %self.db, "Configuration loaded");
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        // held until the node shuts down
+        let _lock = data_dir.lock()?;
+        info!(target: "reth::cli", path = %data_dir, "Data directory locked");
+
+        let config_path = self.config.clone().unwrap_or_else(|| data_dir.config_path());
+        let mut config: Config = self.load_config(&config_path)?;
+        info!(target: "reth::cli", path = %config_path.display(), "Configuration loaded");
 
-        info!(target: "reth::cli", path = %self.db, "Opening database");
-        let db = Arc::new(init_db(&self.db)?);
+        // the settings given on the command line take precedence
+        self.rpc.apply_config(&config.rpc);
+
+        let db_path = data_dir.db_path();
+        info!(target: "reth::cli", path = %db_path.display(), "Opening database");
+        let db = Arc::new(init_db(&db_path, config.db.env_config())?);
         info!(target: "reth::cli", "Database opened");
 
+        let static_files = StaticFileProvider::open(data_dir.static_files_path())?;
+
         self.start_metrics_endpoint()?;
 
         debug!(target: "reth::cli", chain=%self.chain.chain, genesis=?self.chain.genesis_hash(), "Initializing genesis");
 
         init_genesis(db.clone(), self.chain.clone())?;
 
-        let consensus = self.init_consensus()?;
+        // a crash can leave the last moved blocks in both the static files and the database
+        static_files.reconcile(db.as_ref())?;
+
+        let (consensus, forkchoice_state_tx) = self.init_consensus()?;
         info!(target: "reth::cli", "Consensus engine initialized");
 
         self.init_trusted_nodes(&mut config);
 
+        // the tree commits the payloads of the consensus layer once they become canonical
+        let tree = BlockchainTree::new(db.clone(), consensus.clone(), self.chain.clone())
+            .with_static_files(static_files.clone());
+        let canon_state_notifications = tree.canon_state_notifications();
+
+        // the blocks the ExExs have not finished processing yet are not pruned
+        let exex_manager = ExExManager::new();
+        let finished_exex_height = exex_manager.finished_height();
+        ctx.task_executor.spawn_critical(
+            "exex manager",
+            exex_manager.run(canon_state_notifications.subscribe_to_canonical_state()),
+        );
+
+        // the latest state is cached for the RPC and the payload builder, the changes of the tree
+        // are evicted from the cache, and the pipeline clears it
+        let state_cache = StateCache::default();
+        ctx.task_executor.spawn_critical(
+            "state cache maintenance",
+            maintain_state_cache(
+                state_cache.clone(),
+                canon_state_notifications.subscribe_to_canonical_state(),
+            ),
+        );
+
+        let kzg_settings = self.txpool.kzg_settings()?;
+        if kzg_settings.is_none() {
+            warn!(target: "reth::cli", "No KZG trusted setup given, blob transactions are not accepted");
+        }
+        let pool = self.build_pool(
+            ShareableDatabase::new(db.clone()).with_static_files(static_files.clone()),
+            kzg_settings.clone(),
+            &data_dir,
+            &ctx.task_executor,
+            canon_state_notifications.subscribe_to_canonical_state(),
+        )?;
+        info!(target: "reth::cli", "Transaction pool initialized");
+
         info!(target: "reth::cli", "Connecting to P2P network");
-        let network_config =
-            self.load_network_config(&config, Arc::clone(&db), ctx.task_executor.clone());
-        let network = self.start_network(network_config, &ctx.task_executor, ()).await?;
+        let network_config = self.load_network_config(
+            &config,
+            Arc::clone(&db),
+            static_files.clone(),
+            ctx.task_executor.clone(),
+            &data_dir,
+        );
+        let network = self
+            .start_network(
+                network_config,
+                &ctx.task_executor,
+                pool.clone(),
+                kzg_settings.clone(),
+                &data_dir,
+            )
+            .await?;
         info!(target: "reth::cli", peer_id = %network.peer_id(), local_addr = %network.local_addr(), "Connected to P2P network");
 
-        // TODO: Use the resolved secret to spawn the Engine API server
-        // Look at `reth_rpc::AuthLayer` for integration hints
-        let _secret = self.rpc.jwt_secret();
+        let client = Arc::new(
+            ShareableDatabase::new(db.clone())
+                .with_static_files(static_files.clone())
+                .with_state_cache(state_cache.clone()),
+        );
 
-        // TODO(mattsse): cleanup, add cli args
-        let _rpc_server = reth_rpc_builder::launch(
-            ShareableDatabase::new(db.clone()),
-            reth_transaction_pool::test_utils::testing_pool(),
+        let (payload_service, payload_builder) = PayloadBuilderService::new(
+            PayloadBuilder::new(client.clone(), pool.clone(), self.chain.clone()),
+            PayloadBuilderConfig::default(),
+        );
+        ctx.task_executor.spawn_critical("payload builder service", payload_service);
+        info!(target: "reth::cli", "Payload builder service initialized");
+
+        let (engine_tx, engine_rx) = unbounded_channel();
+        let engine_api = EngineApi::new(client, self.chain.clone(), engine_rx, forkchoice_state_tx)
+            .with_payload_builder(payload_builder)
+            .with_blockchain_tree(Box::new(tree));
+        ctx.task_executor.spawn_critical("engine api", engine_api);
+        info!(target: "reth::cli", "Engine API handler initialized");
+
+        let jwt_secret = self.rpc.jwt_secret(&data_dir.jwt_path())?;
+        let _auth_server = reth_rpc_builder::launch_auth(
+            reth_rpc::EngineApi::new(engine_tx),
+            self.rpc.auth_server_address(),
+            jwt_secret,
+        )
+        .await?;
+        info!(target: "reth::cli", addr = %self.rpc.auth_server_address(), "Started Auth server");
+
+        let _rpc_server = RpcModuleBuilder::new(
+            ShareableDatabase::new(db.clone())
+                .with_static_files(static_files.clone())
+                .with_state_cache(state_cache.clone()),
+            pool,
             network.clone(),
-            TransportRpcModuleConfig::default()
-                .with_http(vec![RethRpcModule::Admin, RethRpcModule::Eth]),
-            RpcServerConfig::default().with_http(Default::default()),
         )
+        .with_chain_spec(Arc::new(self.chain.clone()))
+        .with_chain_events(canon_state_notifications)
+        .with_eth_config(self.rpc.eth_config())
+        .with_filter_config(self.rpc.filter_config())
+        .with_max_tracing_requests(self.rpc.max_tracing_requests())
+        .with_disabled_methods(self.rpc.rpc_disabled_methods.clone())
+        .build(self.rpc.transport_rpc_module_config())
+        .start_server(self.rpc.rpc_server_config())
         .await?;
         info!(target: "reth::cli", "Started RPC server");
 
@@ -166,6 +310,9 @@ impl Command {
                 network.clone(),
                 &consensus,
                 db.clone(),
+                static_files,
+                finished_exex_height,
+                state_cache,
                 &ctx.task_executor,
             )
             .await?;
@@ -175,7 +322,7 @@ impl Command {
         // Run pipeline
         let (rx, tx) = tokio::sync::oneshot::channel();
         info!(target: "reth::cli", "Starting sync pipeline");
-        ctx.task_executor.spawn_critical_blocking("pipeline task", async move {
+        ctx.task_executor.spawn_critical_blocking_with_signal("pipeline task", |_| async move {
             let res = pipeline.run(db.clone()).await;
             let _ = rx.send(res);
         });
@@ -186,14 +333,19 @@ impl Command {
         Ok(())
     }
 
-    async fn build_networked_pipeline(
+    async fn build_networked_pipeline<DB>(
         &self,
         config: &mut Config,
         network: NetworkHandle,
         consensus: &Arc<dyn Consensus>,
-        db: Arc<Env<WriteMap>>,
+        db: Arc<DB>,
+        static_files: StaticFileProvider,
+        finished_exex_height: watch::Receiver<FinishedExExHeight>,
+        state_cache: StateCache,
         task_executor: &TaskExecutor,
-    ) -> eyre::Result<(Pipeline<Env<WriteMap>, impl SyncStateUpdater>, impl Stream<Item = NodeEvent>)>
+    ) -> eyre::Result<(Pipeline<DB, impl SyncStateUpdater>, impl Stream<Item = NodeEvent>)>
+    where
+        DB: Database + 'static,
     {
         // building network downloaders using the fetch client
         let fetch_client = Arc::new(network.fetch_client().await?);
@@ -207,7 +359,17 @@ impl Command {
             .into_task_with(task_executor);
 
         let mut pipeline = self
-            .build_pipeline(config, header_downloader, body_downloader, network.clone(), consensus)
+            .build_pipeline(
+                config,
+                header_downloader,
+                body_downloader,
+                network.clone(),
+                consensus,
+                static_files,
+                finished_exex_height,
+                state_cache,
+                task_executor.on_shutdown_signal().clone(),
+            )
             .await?;
 
         let events = stream_select(
@@ -217,8 +379,50 @@ impl Command {
         Ok((pipeline, events))
     }
 
-    fn load_config(&self) -> eyre::Result<Config> {
-        confy::load_path::<Config>(&self.config).wrap_err("Could not load config")
+    /// Builds the transaction pool, which validates the transactions against the latest state of
+    /// the client and keeps its blobs in the data directory, and spawns the task updating it with
+    /// the changes of the canonical chain.
+    ///
+    /// Blob transactions are only accepted if there is a KZG trusted setup to verify them with.
+    fn build_pool<Client>(
+        &self,
+        client: Client,
+        kzg_settings: Option<Arc<KzgSettings>>,
+        data_dir: &ChainPath<DataDirPath>,
+        task_executor: &TaskExecutor,
+        canon_state_notifications: CanonStateNotifications,
+    ) -> eyre::Result<EthPool<Client>>
+    where
+        Client:
+            StateProviderFactory + BlockProvider + HeaderProvider + Clone + Send + Sync + 'static,
+    {
+        let blob_store = DiskFileBlobStore::open(data_dir.blobstore_path())?;
+        let mut validator =
+            EthTransactionValidator::new(client.clone(), Arc::new(self.chain.clone()));
+        if let Some(kzg_settings) = kzg_settings {
+            validator = validator.with_kzg_settings(kzg_settings);
+        }
+        let validator = TransactionValidationTaskExecutor::spawn(
+            validator,
+            task_executor,
+            TRANSACTION_VALIDATION_TASKS,
+        );
+        let pool = Pool::new(
+            Arc::new(validator),
+            Arc::new(CostOrdering::default()),
+            blob_store,
+            self.txpool.pool_config(),
+        );
+
+        task_executor.spawn_critical(
+            "txpool maintenance",
+            maintain_transaction_pool(client, pool.clone(), canon_state_notifications),
+        );
+        Ok(pool)
+    }
+
+    fn load_config(&self, path: &Path) -> eyre::Result<Config> {
+        confy::load_path::<Config>(path).wrap_err("Could not load config")
     }
 
     fn init_trusted_nodes(&self, config: &mut Config) {
@@ -241,7 +445,9 @@ impl Command {
         }
     }
 
-    fn init_consensus(&self) -> eyre::Result<Arc<dyn Consensus>> {
+    /// Initializes the consensus and returns it with the sender of the forkchoice states it
+    /// follows.
+    fn init_consensus(&self) -> eyre::Result<(Arc<dyn Consensus>, watch::Sender<ForkchoiceState>)> {
         let (consensus, notifier) = BeaconConsensus::builder().build(self.chain.clone());
 
         if let Some(tip) = self.tip {
@@ -252,44 +458,54 @@ impl Command {
                 finalized_block_hash: tip,
             })?;
         } else {
-            let warn_msg = "No tip specified. \
-            reth cannot communicate with consensus clients, \
-            so a tip must manually be provided for the online stages with --debug.tip <HASH>.";
-            warn!(target: "reth::cli", warn_msg);
+            info!(target: "reth::cli", "No tip specified, waiting for the consensus layer to send a forkchoice update");
         }
 
-        Ok(consensus)
+        Ok((consensus, notifier))
     }
 
     /// Spawns the configured network and associated tasks and returns the [NetworkHandle] connected
     /// to that network.
-    async fn start_network<C>(
+    ///
+    /// The sidecars of the blob transactions received from the peers are verified with the given
+    /// KZG trusted setup.
+    async fn start_network<C, Pool>(
         &self,
         config: NetworkConfig<C>,
         task_executor: &TaskExecutor,
-        // TODO: integrate pool
-        _pool: (),
+        pool: Pool,
+        kzg_settings: Option<Arc<KzgSettings>>,
+        data_dir: &ChainPath<DataDirPath>,
     ) -> Result<NetworkHandle, NetworkError>
     where
         C: BlockProvider + HeaderProvider + 'static,
+        Pool: TransactionPool + Unpin + 'static,
+        Pool::Transaction: IntoRecoveredTransaction,
     {
         let client = config.client.clone();
-        let (handle, network, _txpool, eth) =
-            NetworkManager::builder(config).await?.request_handler(client).split_with_handle();
+        let (handle, network, mut transactions, eth) = NetworkManager::builder(config)
+            .await?
+            .transactions(pool)
+            .request_handler(client)
+            .split_with_handle();
+        if let Some(kzg_settings) = kzg_settings {
+            transactions = transactions.with_kzg_settings(kzg_settings);
+        }
 
-        let known_peers_file = self.network.persistent_peers_file();
+        let known_peers_file = self.network.persistent_peers_file(data_dir);
+        let banned_peers_file = self.network.persistent_banned_peers_file(data_dir);
         task_executor.spawn_critical_with_signal("p2p network task", |shutdown| async move {
-            run_network_until_shutdown(shutdown, network, known_peers_file).await
+            run_network_until_shutdown(shutdown, network, known_peers_file, banned_peers_file).await
         });
 
         task_executor.spawn_critical("p2p eth request handler", async move { eth.await });
 
-        // TODO spawn pool
+        task_executor.spawn_critical("p2p transactions manager", transactions);
 
         Ok(handle)
     }
 
-    fn fetch_head(&self, db: Arc<Env<WriteMap>>) -> Result<Head, reth_interfaces::db::Error> {
+    fn fetch_head<DB: Database>(&self, db: Arc<DB>) -> Result<Head, reth_interfaces::db::Error> {
         db.view(|tx| {
             let head = FINISH.get_progress(tx)?.unwrap_or_default();
             let header = tx
@@ -312,71 +528,111 @@ impl Command {
         .map_err(Into::into)
     }
 
-    fn load_network_config(
+    fn load_network_config<DB: Database>(
         &self,
         config: &Config,
-        db: Arc<Env<WriteMap>>,
+        db: Arc<DB>,
+        static_files: StaticFileProvider,
         executor: TaskExecutor,
-    ) -> NetworkConfig<ShareableDatabase<Arc<Env<WriteMap>>>> {
+        data_dir: &ChainPath<DataDirPath>,
+    ) -> NetworkConfig<ShareableDatabase<Arc<DB>>> {
         let head = self.fetch_head(Arc::clone(&db)).expect("the head block is missing");
 
         self.network
-            .network_config(config, self.chain.clone())
+            .network_config(config, self.chain.clone(), data_dir)
             .with_task_executor(Box::new(executor))
             .set_head(head)
-            .build(Arc::new(ShareableDatabase::new(db)))
+            .build(Arc::new(ShareableDatabase::new(db).with_static_files(static_files)))
     }
 
-    async fn build_pipeline<H, B, U>(
+    async fn build_pipeline<DB, H, B, U>(
         &self,
         config: &Config,
         header_downloader: H,
         body_downloader: B,
         updater: U,
         consensus: &Arc<dyn Consensus>,
-    ) -> eyre::Result<Pipeline<Env<WriteMap>, U>>
+        static_files: StaticFileProvider,
+        finished_exex_height: watch::Receiver<FinishedExExHeight>,
+        state_cache: StateCache,
+        shutdown: Shutdown,
+    ) -> eyre::Result<Pipeline<DB, U>>
     where
+        DB: Database,
         H: HeaderDownloader + 'static,
         B: BodyDownloader + 'static,
         U: SyncStateUpdater + StatusUpdater + Clone + 'static,
     {
         let stage_conf = &config.stages;
 
-        let mut builder = Pipeline::builder();
+        // the pipeline stops on its own once the progress of the running stage is committed
+        let mut builder = Pipeline::builder()
+            .with_shutdown_signal(shutdown)
+            .with_static_files(static_files.clone())
+            .with_state_cache(state_cache);
 
         if let Some(max_block) = self.max_block {
             debug!(target: "reth::cli", max_block, "Configuring builder to use max block");
             builder = builder.with_max_block(max_block)
         }
 
-        let pipeline = builder
-            .with_sync_state_updater(updater.clone())
-            .add_stages(
-                DefaultStages::new(consensus.clone(), header_downloader, body_downloader, updater)
-                    .set(TotalDifficultyStage {
-                        chain_spec: self.chain.clone(),
-                        commit_threshold: stage_conf.total_difficulty.commit_threshold,
-                    })
-                    .set(SenderRecoveryStage {
-                        commit_threshold: stage_conf.sender_recovery.commit_threshold,
-                    })
-                    .set(ExecutionStage {
-                        chain_spec: self.chain.clone(),
-                        commit_threshold: stage_conf.execution.commit_threshold,
-                    }),
+        let prune_modes = self.prune.prune_modes(&config.prune);
+        if !prune_modes.is_empty() {
+            debug!(target: "reth::cli", ?prune_modes, "Configuring builder to prune old blocks");
+            builder = builder.with_pruner(
+                Pruner::new(prune_modes).with_finished_exex_height(finished_exex_height),
             )
-            .build();
+        }
+
+        if self.static_files {
+            // The pruner only deletes from the database, so blocks are only moved once they are
+            // pruned.
+            let distance = PrunePart::ALL
+                .into_iter()
+                .filter_map(|part| prune_modes.distance(part))
+                .fold(MINIMUM_PRUNING_DISTANCE, u64::max);
+            debug!(target: "reth::cli", distance, "Configuring builder to move finalized blocks to static files");
+            builder =
+                builder.with_static_file_producer(StaticFileProducer::new(static_files, distance))
+        }
+
+        let mut stages = DefaultStages::new(
+            consensus.clone(),
+            header_downloader,
+            body_downloader,
+            updater.clone(),
+        )
+        .set(TotalDifficultyStage {
+            chain_spec: self.chain.clone(),
+            commit_threshold: stage_conf.total_difficulty.commit_threshold,
+        })
+        .set(SenderRecoveryStage { commit_threshold: stage_conf.sender_recovery.commit_threshold })
+        .set(ExecutionStage {
+            chain_spec: self.chain.clone(),
+            commit_threshold: stage_conf.execution.commit_threshold,
+            prefetch: true,
+        })
+        .set(TransactionLookupStage::new(stage_conf.transaction_lookup.commit_threshold));
+
+        if !stage_conf.transaction_lookup.enabled {
+            debug!(target: "reth::cli", "Disabling the transaction lookup stage");
+            stages = stages.disable(TRANSACTION_LOOKUP);
+        }
+
+        let pipeline = builder.with_sync_state_updater(updater).add_stages(stages).build();
 
         Ok(pipeline)
     }
 }
 
-/// Drives the [NetworkManager] future until a [Shutdown](reth_tasks::shutdown::Shutdown) signal is
-/// received. If configured, this writes known peers to `persistent_peers_file` afterwards.
+/// Drives the [NetworkManager] future until a [Shutdown] signal is received. If configured, this
+/// writes known peers to `persistent_peers_file` and banned peers to `persistent_banned_peers_file`
+/// afterwards.
 async fn run_network_until_shutdown<C>(
-    shutdown: reth_tasks::shutdown::Shutdown,
+    shutdown: Shutdown,
     network: NetworkManager<C>,
     persistent_peers_file: Option<PathBuf>,
+    persistent_banned_peers_file: Option<PathBuf>,
 ) where
     C: BlockProvider + HeaderProvider + 'static,
 {
@@ -388,7 +644,7 @@ async fn run_network_until_shutdown<C>(
     }
 
     if let Some(file_path) = persistent_peers_file {
-        let known_peers = network.all_peers().collect::<Vec<_>>();
+        let known_peers = network.persisted_peers().collect::<Vec<_>>();
         if let Ok(known_peers) = serde_json::to_string_pretty(&known_peers) {
             trace!(target : "reth::cli", peers_file =?file_path, num_peers=%known_peers.len(), "Saving current peers");
             match std::fs::write(&file_path, known_peers) {
@@ -401,6 +657,21 @@ async fn run_network_until_shutdown<C>(
             }
         }
     }
+
+    if let Some(file_path) = persistent_banned_peers_file {
+        let banned_peers = network.banned_peers();
+        if let Ok(banned) = serde_json::to_string_pretty(&banned_peers) {
+            trace!(target : "reth::cli", banned_peers_file =?file_path, num_peers=%banned_peers.peers.len(), num_ips=%banned_peers.ips.len(), "Saving banned peers");
+            match std::fs::write(&file_path, banned) {
+                Ok(_) => {
+                    info!(target: "reth::cli", banned_peers_file=?file_path, "Wrote banned peers to file");
+                }
+                Err(err) => {
+                    warn!(target: "reth::cli", ?err, banned_peers_file=?file_path, "Failed to write banned peers to file");
+                }
+            }
+        }
+    }
 }
 
 /// The current high-level state of the node.
@@ -1,7 +1,7 @@
 //! P2P Debugging tool
 use crate::{
     args::DiscoveryArgs,
-    dirs::{ConfigPath, PlatformPath},
+    dirs::{DataDirPath, MaybePlatformPath},
 };
 use backon::{ConstantBackoff, Retryable};
 use clap::{Parser, Subcommand};
@@ -15,17 +15,29 @@ use reth_network::FetchClient;
 use reth_primitives::{BlockHashOrNumber, ChainSpec, NodeRecord, SealedHeader};
 use reth_provider::ShareableDatabase;
 use reth_staged_sync::{
-    utils::{chainspec::chain_spec_value_parser, hash_or_num_value_parser},
+    utils::{chainspec::chain_spec_value_parser, hash_or_num_value_parser, parse_path},
     Config,
 };
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 /// `reth p2p` command
 #[derive(Debug, Parser)]
 pub struct Command {
     /// The path to the configuration file to use.
-    #[arg(long, value_name = "FILE", verbatim_doc_comment, default_value_t)]
-    config: PlatformPath<ConfigPath>,
+    ///
+    /// Defaults to `reth.toml` in the data directory.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment, value_parser = parse_path)]
+    config: Option<PathBuf>,
+
+    /// The path to the data directory, the configuration file is read from it by default.
+    ///
+    /// Defaults to the OS-specific data directory of the chain:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/<CHAIN>` or `$HOME/.local/share/reth/<CHAIN>`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/<CHAIN>`
+    /// - macOS: `$HOME/Library/Application Support/reth/<CHAIN>`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
 
     /// The chain this node is running.
     ///
@@ -89,7 +101,10 @@ impl Command {
         let tempdir = tempfile::TempDir::new()?;
         let noop_db = Arc::new(Env::<WriteMap>::open(&tempdir.into_path(), EnvKind::RW)?);
 
-        let mut config: Config = confy::load_path(&self.config).unwrap_or_default();
+        let config_path = self.config.clone().unwrap_or_else(|| {
+            self.datadir.unwrap_or_chain_default(self.chain.chain).config_path()
+        });
+        let mut config: Config = confy::load_path(config_path).unwrap_or_default();
 
         if let Some(peer) = self.trusted_peer {
             config.peers.trusted_nodes.insert(peer);
@@ -102,7 +117,7 @@ impl Command {
         config.peers.connect_trusted_nodes_only = self.trusted_only;
 
         let mut network_config_builder =
-            config.network_config(self.nat, None).chain_spec(self.chain.clone());
+            config.network_config(self.nat, None, None).chain_spec(self.chain.clone());
 
         network_config_builder = self.discovery.apply_to_builder(network_config_builder);
 
@@ -2,8 +2,8 @@
 
 use futures::pin_mut;
 use reth_tasks::{TaskExecutor, TaskManager};
-use std::future::Future;
-use tracing::trace;
+use std::{future::Future, time::Duration};
+use tracing::{debug, trace};
 
 /// Used to execute cli commands
 #[derive(Default, Debug)]
@@ -28,7 +28,7 @@ impl CliRunner {
     {
         let AsyncCliRunner { context, task_manager, tokio_runtime } = AsyncCliRunner::new()?;
 
-        // Executes the command until it finished or ctrl-c was fired
+        // Executes the command until it finished or an exit signal was received
         let task_manager = tokio_runtime.block_on(run_to_completion_or_panic(
             task_manager,
             run_until_ctrl_c(command(context)),
@@ -37,12 +37,13 @@ impl CliRunner {
         // fires the shutdown signal to all tasks spawned via the task executor
         drop(task_manager);
 
-        // give all tasks that are now being shut down some time to finish before tokio leaks them
+        // give all tasks that are now being shut down some time to finish before tokio leaks them,
+        // e.g. the pipeline commits the progress of the stage it is running before it stops
         // see [Runtime::shutdown_timeout](tokio::runtime::Runtime::shutdown_timeout)
-        // TODO: enable this again, when pipeline/stages are not longer blocking tasks
-        std::process::exit(0);
-        // warn!(target: "reth::cli", "Received shutdown signal, waiting up to 30 seconds for
-        // tasks."); tokio_runtime.shutdown_timeout(Duration::from_secs(30));
+        debug!(target: "reth::cli", "Shutting down, waiting up to 30 seconds for tasks");
+        tokio_runtime.shutdown_timeout(Duration::from_secs(30));
+
+        Ok(())
     }
 
     /// Executes a regular future until completion or until external signal received.
@@ -107,20 +108,31 @@ where
     Ok(tasks)
 }
 
-/// Runs the future to completion or until a `ctrl-c` is received.
+/// Runs the future to completion or until a `ctrl-c` or, on unix, a `SIGTERM` is received.
 async fn run_until_ctrl_c<F, E>(fut: F) -> Result<(), E>
 where
     F: Future<Output = Result<(), E>>,
-    E: Send + Sync + 'static,
+    E: Send + Sync + 'static + From<std::io::Error>,
 {
     let ctrl_c = tokio::signal::ctrl_c();
 
-    pin_mut!(ctrl_c, fut);
+    #[cfg(unix)]
+    let sigterm = {
+        let mut stream = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        async move { stream.recv().await }
+    };
+    #[cfg(not(unix))]
+    let sigterm = futures::future::pending::<()>();
+
+    pin_mut!(ctrl_c, sigterm, fut);
 
     tokio::select! {
         _ = ctrl_c => {
             trace!(target: "reth::cli",  "Received ctrl-c");
         },
+        _ = sigterm => {
+            trace!(target: "reth::cli",  "Received SIGTERM");
+        },
         res = fut => res?,
     }
 
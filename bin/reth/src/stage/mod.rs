@@ -3,7 +3,7 @@
 //! Stage debugging tool
 use crate::{
     args::NetworkArgs,
-    dirs::{ConfigPath, DbPath, PlatformPath},
+    dirs::{DataDirPath, MaybePlatformPath},
     prometheus_exporter,
 };
 use clap::{Parser, ValueEnum};
@@ -12,32 +12,35 @@ use reth_downloaders::bodies::bodies::BodiesDownloaderBuilder;
 use reth_primitives::ChainSpec;
 use reth_provider::{ShareableDatabase, Transaction};
 use reth_staged_sync::{
-    utils::{chainspec::chain_spec_value_parser, init::init_db},
+    utils::{chainspec::chain_spec_value_parser, init::init_db, parse_path},
     Config,
 };
 use reth_stages::{
     stages::{BodyStage, ExecutionStage, SenderRecoveryStage},
     ExecInput, Stage, StageId, UnwindInput,
 };
-use std::{net::SocketAddr, sync::Arc};
+use reth_static_file::StaticFileProvider;
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 use tracing::*;
 
 /// `reth stage` command
 #[derive(Debug, Parser)]
 pub struct Command {
-    /// The path to the database folder.
+    /// The path to the data directory, the database is in its `db` folder.
     ///
-    /// Defaults to the OS-specific data directory:
+    /// Defaults to the OS-specific data directory of the chain:
     ///
-    /// - Linux: `$XDG_DATA_HOME/reth/db` or `$HOME/.local/share/reth/db`
-    /// - Windows: `{FOLDERID_RoamingAppData}/reth/db`
-    /// - macOS: `$HOME/Library/Application Support/reth/db`
-    #[arg(long, value_name = "PATH", verbatim_doc_comment, default_value_t)]
-    db: PlatformPath<DbPath>,
+    /// - Linux: `$XDG_DATA_HOME/reth/<CHAIN>` or `$HOME/.local/share/reth/<CHAIN>`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/<CHAIN>`
+    /// - macOS: `$HOME/Library/Application Support/reth/<CHAIN>`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
 
     /// The path to the configuration file to use.
-    #[arg(long, value_name = "FILE", verbatim_doc_comment, default_value_t)]
-    config: PlatformPath<ConfigPath>,
+    ///
+    /// Defaults to `reth.toml` in the data directory.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment, value_parser = parse_path)]
+    config: Option<PathBuf>,
 
     /// The chain this node is running.
     ///
@@ -106,7 +109,11 @@ impl Command {
             prometheus_exporter::initialize(listen_addr)?;
         }
 
-        let config: Config = confy::load_path(&self.config).unwrap_or_default();
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let _lock = data_dir.lock()?;
+
+        let config_path = self.config.clone().unwrap_or_else(|| data_dir.config_path());
+        let config: Config = confy::load_path(config_path).unwrap_or_default();
         info!(target: "reth::cli", "reth {} starting stage {:?}", clap::crate_version!(), self.stage);
 
         let input = ExecInput {
@@ -116,8 +123,10 @@ impl Command {
 
         let unwind = UnwindInput { stage_progress: self.to, unwind_to: self.from, bad_block: None };
 
-        let db = Arc::new(init_db(&self.db)?);
-        let mut tx = Transaction::new(db.as_ref())?;
+        let db = Arc::new(init_db(data_dir.db_path(), config.db.env_config())?);
+        let static_files = StaticFileProvider::open(data_dir.static_files_path())?;
+        static_files.reconcile(db.as_ref())?;
+        let mut tx = Transaction::new(db.as_ref())?.with_static_files(Some(static_files.clone()));
 
         let num_blocks = self.to - self.from + 1;
 
@@ -135,8 +144,10 @@ impl Command {
 
                 let network = self
                     .network
-                    .network_config(&config, self.chain.clone())
-                    .build(Arc::new(ShareableDatabase::new(db.clone())))
+                    .network_config(&config, self.chain.clone(), &data_dir)
+                    .build(Arc::new(
+                        ShareableDatabase::new(db.clone()).with_static_files(static_files),
+                    ))
                     .start_network()
                     .await?;
                 let fetch_client = Arc::new(network.fetch_client().await?);
@@ -148,6 +159,9 @@ impl Command {
                         .with_max_buffered_responses(
                             config.stages.bodies.downloader_max_buffered_responses,
                         )
+                        .with_max_buffered_blocks_size_bytes(
+                            config.stages.bodies.downloader_max_buffered_blocks_size_bytes,
+                        )
                         .with_concurrent_requests_range(
                             config.stages.bodies.downloader_min_concurrent_requests..=
                                 config.stages.bodies.downloader_max_concurrent_requests,
@@ -171,8 +185,12 @@ impl Command {
                 stage.execute(&mut tx, input).await?;
             }
             StageEnum::Execution => {
-                let mut stage =
-                    ExecutionStage { chain_spec: self.chain.clone(), commit_threshold: num_blocks };
+                // The unwind is not committed, so the state can't be prefetched from the database
+                let mut stage = ExecutionStage {
+                    chain_spec: self.chain.clone(),
+                    commit_threshold: num_blocks,
+                    prefetch: false,
+                };
                 if !self.skip_unwind {
                     stage.unwind(&mut tx, unwind).await?;
                 }
@@ -1,7 +1,7 @@
 //! Consensus for ethereum network
-use crate::validation;
+use crate::{ethash, validation};
 use reth_interfaces::consensus::{Consensus, Error, ForkchoiceState};
-use reth_primitives::{ChainSpec, Hardfork, SealedBlock, SealedHeader, EMPTY_OMMER_ROOT, U256};
+use reth_primitives::{ChainSpec, ForkCondition, Hardfork, SealedBlock, SealedHeader, U256};
 use tokio::sync::watch;
 
 use super::BeaconConsensusBuilder;
@@ -16,6 +16,9 @@ pub struct BeaconConsensus {
     forkchoice_state_rx: watch::Receiver<ForkchoiceState>,
     /// Configuration
     chain_spec: ChainSpec,
+    /// Verifies the proof-of-work seals of the headers before the merge
+    #[cfg(feature = "ethash")]
+    ethash: ethash::EthashVerifier,
 }
 
 impl BeaconConsensus {
@@ -24,13 +27,35 @@ impl BeaconConsensus {
         chain_spec: ChainSpec,
         forkchoice_state_rx: watch::Receiver<ForkchoiceState>,
     ) -> Self {
-        Self { chain_spec, forkchoice_state_rx }
+        Self {
+            chain_spec,
+            forkchoice_state_rx,
+            #[cfg(feature = "ethash")]
+            ethash: Default::default(),
+        }
     }
 
     /// Create new [BeaconConsensusBuilder].
     pub fn builder() -> BeaconConsensusBuilder {
         BeaconConsensusBuilder::default()
     }
+
+    /// Returns `true` if the header is before the merge, i.e. if Paris is not active at it.
+    ///
+    /// The total difficulty is not known before the headers are executed, so the merge is
+    /// identified by the first block after it. If that is not known, only headers with a
+    /// difficulty can be before the merge.
+    fn is_pre_merge(&self, header: &SealedHeader) -> bool {
+        match self.chain_spec.fork(Hardfork::Paris) {
+            ForkCondition::TTD { fork_block, .. } => {
+                match fork_block.or(self.chain_spec.paris_block) {
+                    Some(block) => header.number < block,
+                    None => header.difficulty != U256::ZERO,
+                }
+            }
+            paris => !paris.active_at_block(header.number),
+        }
+    }
 }
 
 impl Consensus for BeaconConsensus {
@@ -46,32 +71,23 @@ impl Consensus for BeaconConsensus {
         validation::validate_header_standalone(header, &self.chain_spec)?;
         validation::validate_header_regarding_parent(parent, header, &self.chain_spec)?;
 
+        if self.is_pre_merge(header) {
+            ethash::validate_header_regarding_parent(parent, header, &self.chain_spec)?;
+        }
+
         Ok(())
     }
 
     fn validate_header(&self, header: &SealedHeader, total_difficulty: U256) -> Result<(), Error> {
         if self.chain_spec.fork(Hardfork::Paris).active_at_ttd(total_difficulty, header.difficulty)
         {
-            // EIP-3675: Upgrade consensus to Proof-of-Stake:
-            // https://eips.ethereum.org/EIPS/eip-3675#replacing-difficulty-with-0
-            if header.difficulty != U256::ZERO {
-                return Err(Error::TheMergeDifficultyIsNotZero)
-            }
-
-            if header.nonce != 0 {
-                return Err(Error::TheMergeNonceIsNotZero)
-            }
-
-            if header.ommers_hash != EMPTY_OMMER_ROOT {
-                return Err(Error::TheMergeOmmerRootIsNotEmpty)
-            }
-
-            // mixHash is used instead of difficulty inside EVM
-            // https://eips.ethereum.org/EIPS/eip-4399#using-mixhash-field-instead-of-difficulty
+            validation::validate_header_post_merge(header)?;
         } else {
-            // TODO Consensus checks for old blocks:
-            //  * difficulty, mix_hash & nonce aka PoW stuff
-            // low priority as syncing is done in reverse order
+            // The difficulty is checked against the parent in `pre_validate_header`, the seal is
+            // only verified with the `ethash` feature since generating the cache of an epoch is
+            // expensive, and syncing is done in reverse order anyway
+            #[cfg(feature = "ethash")]
+            self.ethash.verify_seal(header)?;
         }
 
         Ok(())
@@ -89,7 +105,7 @@ impl Consensus for BeaconConsensus {
 #[cfg(test)]
 mod test {
     use reth_interfaces::consensus::Consensus;
-    use reth_primitives::{ChainSpecBuilder, U256};
+    use reth_primitives::{ChainSpecBuilder, Header, MAINNET, U256};
 
     use super::BeaconConsensus;
 
@@ -99,4 +115,19 @@ mod test {
         let (consensus, _) = BeaconConsensus::builder().build(chain_spec);
         assert!(consensus.has_block_reward(U256::ZERO, U256::ZERO));
     }
+
+    #[test]
+    fn test_pre_merge_headers() {
+        let merge_block = MAINNET.paris_block.unwrap();
+        let (consensus, _) = BeaconConsensus::builder().build(MAINNET.clone());
+
+        let header = |number, difficulty| {
+            Header { number, difficulty: U256::from(difficulty), ..Default::default() }.seal_slow()
+        };
+        assert!(consensus.is_pre_merge(&header(merge_block - 1, 1)));
+        // a header after the merge is not validated as a proof-of-work header, even with a
+        // difficulty
+        assert!(!consensus.is_pre_merge(&header(merge_block, 1)));
+        assert!(!consensus.is_pre_merge(&header(merge_block, 0)));
+    }
 }
@@ -0,0 +1,267 @@
+//! Proof-of-work consensus rules of the blocks before the merge.
+use reth_interfaces::consensus::Error;
+use reth_primitives::{
+    BlockNumber, ChainSpec, Hardfork, Header, SealedHeader, EMPTY_OMMER_ROOT, U256,
+};
+
+#[cfg(feature = "ethash")]
+mod pow;
+#[cfg(feature = "ethash")]
+pub use pow::{cache_size, dataset_size, seal_hash, seed_hash, EthashCache, EthashVerifier};
+
+/// The minimum difficulty of a block.
+pub const MINIMUM_DIFFICULTY: u64 = 131_072;
+
+/// The divisor of the parent difficulty that gives the step the difficulty is adjusted by.
+pub const DIFFICULTY_BOUND_DIVISOR: u64 = 2048;
+
+/// The time between blocks below which the difficulty increases before Homestead.
+pub const DURATION_LIMIT: u64 = 13;
+
+/// The number of blocks after which the difficulty bomb doubles.
+pub const EXP_DIFF_PERIOD: u64 = 100_000;
+
+/// The minimum gas limit of a block.
+pub const MINIMUM_GAS_LIMIT: u64 = 5000;
+
+/// The maximum gas limit of a block.
+pub const MAXIMUM_GAS_LIMIT: u64 = i64::MAX as u64;
+
+/// The delays of the difficulty bomb, in blocks, by the hardfork that introduced them.
+const BOMB_DELAYS: [(Hardfork, u64); 6] = [
+    // EIP-5133
+    (Hardfork::GrayGlacier, 11_400_000),
+    // EIP-4345
+    (Hardfork::ArrowGlacier, 10_700_000),
+    // EIP-3554
+    (Hardfork::London, 9_700_000),
+    // EIP-2384
+    (Hardfork::MuirGlacier, 9_000_000),
+    // EIP-1234
+    (Hardfork::Constantinople, 5_000_000),
+    // EIP-649
+    (Hardfork::Byzantium, 3_000_000),
+];
+
+/// Calculate the difficulty of the block with the given number and timestamp on top of `parent`,
+/// as specified by the hardforks active at the block.
+pub fn calculate_difficulty(
+    parent: &Header,
+    number: BlockNumber,
+    timestamp: u64,
+    chain_spec: &ChainSpec,
+) -> U256 {
+    let elapsed = timestamp.saturating_sub(parent.timestamp) as i128;
+
+    // The difficulty is adjusted by a multiple of the step, depending on the time since the parent.
+    let multiplier = if chain_spec.fork(Hardfork::Byzantium).active_at_block(number) {
+        // EIP-100: the ommers of the parent are taken into account
+        let ommers = if parent.ommers_hash == EMPTY_OMMER_ROOT { 1 } else { 2 };
+        (ommers - elapsed / 9).max(-99)
+    } else if chain_spec.fork(Hardfork::Homestead).active_at_block(number) {
+        // EIP-2
+        (1 - elapsed / 10).max(-99)
+    } else if elapsed < DURATION_LIMIT as i128 {
+        1
+    } else {
+        -1
+    };
+
+    let step = parent.difficulty / U256::from(DIFFICULTY_BOUND_DIVISOR);
+    let adjustment = step * U256::from(multiplier.unsigned_abs());
+    let mut difficulty = if multiplier >= 0 {
+        parent.difficulty.saturating_add(adjustment)
+    } else {
+        parent.difficulty.saturating_sub(adjustment)
+    };
+    difficulty = difficulty.max(U256::from(MINIMUM_DIFFICULTY));
+
+    // The difficulty bomb, which the glacier hardforks delay
+    let bomb_delay = BOMB_DELAYS
+        .iter()
+        .find(|(fork, _)| chain_spec.fork(*fork).active_at_block(number))
+        .map_or(0, |(_, delay)| *delay);
+    let period = number.saturating_sub(bomb_delay) / EXP_DIFF_PERIOD;
+    if period > 1 {
+        difficulty = difficulty.saturating_add(U256::from(1) << (period - 2) as usize);
+    }
+
+    difficulty
+}
+
+/// Validate a proof-of-work header in regards to its parent.
+///
+/// Checks that the timestamp is after the parent, that the gas limit is within its bounds and that
+/// the difficulty is the one calculated from the parent.
+pub fn validate_header_regarding_parent(
+    parent: &SealedHeader,
+    header: &SealedHeader,
+    chain_spec: &ChainSpec,
+) -> Result<(), Error> {
+    if header.timestamp <= parent.timestamp {
+        return Err(Error::TimestampIsInPast {
+            parent_timestamp: parent.timestamp,
+            timestamp: header.timestamp,
+        })
+    }
+
+    if !(MINIMUM_GAS_LIMIT..=MAXIMUM_GAS_LIMIT).contains(&header.gas_limit) {
+        return Err(Error::GasLimitOutOfBounds { gas_limit: header.gas_limit })
+    }
+
+    let expected = calculate_difficulty(parent, header.number, header.timestamp, chain_spec);
+    if header.difficulty != expected {
+        return Err(Error::DifficultyDiff { expected, got: header.difficulty })
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use reth_primitives::{ChainSpecBuilder, ForkCondition, H256, MAINNET};
+
+    fn parent(difficulty: u64, timestamp: u64) -> Header {
+        Header { difficulty: U256::from(difficulty), timestamp, ..Default::default() }
+    }
+
+    #[test]
+    fn frontier_difficulty() {
+        // Mainnet block 1
+        let genesis = MAINNET.genesis_header();
+        assert_eq!(
+            calculate_difficulty(&genesis, 1, 1438269988, &MAINNET),
+            U256::from(17171480576u64)
+        );
+
+        let chain_spec = ChainSpecBuilder::mainnet().frontier_activated().build();
+        let parent = parent(2048 * 1000, 1000);
+        assert_eq!(
+            calculate_difficulty(&parent, 1, 1012, &chain_spec),
+            U256::from(2048 * 1000 + 1000)
+        );
+        assert_eq!(
+            calculate_difficulty(&parent, 1, 1013, &chain_spec),
+            U256::from(2048 * 1000 - 1000)
+        );
+
+        // The difficulty does not go below the minimum
+        let parent = Header { difficulty: U256::from(MINIMUM_DIFFICULTY), ..parent };
+        assert_eq!(
+            calculate_difficulty(&parent, 1, 2000, &chain_spec),
+            U256::from(MINIMUM_DIFFICULTY)
+        );
+    }
+
+    #[test]
+    fn homestead_difficulty() {
+        let chain_spec = ChainSpecBuilder::mainnet().homestead_activated().build();
+        let parent = parent(2048 * 1000, 1000);
+        assert_eq!(
+            calculate_difficulty(&parent, 1, 1009, &chain_spec),
+            U256::from(2048 * 1000 + 1000)
+        );
+        assert_eq!(calculate_difficulty(&parent, 1, 1019, &chain_spec), U256::from(2048 * 1000));
+        assert_eq!(
+            calculate_difficulty(&parent, 1, 1035, &chain_spec),
+            U256::from(2048 * 1000 - 2 * 1000)
+        );
+        // The adjustment is capped at 99 steps
+        assert_eq!(
+            calculate_difficulty(&parent, 1, 10_000, &chain_spec),
+            U256::from(2048 * 1000 - 99 * 1000)
+        );
+    }
+
+    #[test]
+    fn byzantium_difficulty() {
+        let chain_spec = ChainSpecBuilder::mainnet().byzantium_activated().build();
+        let parent = parent(2048 * 1000, 1000);
+        assert_eq!(calculate_difficulty(&parent, 1, 1009, &chain_spec), U256::from(2048 * 1000));
+
+        // EIP-100: the ommers of the parent increase the difficulty
+        let with_ommers = Header { ommers_hash: H256::repeat_byte(1), ..parent.clone() };
+        assert_eq!(
+            calculate_difficulty(&with_ommers, 1, 1009, &chain_spec),
+            U256::from(2048 * 1000 + 1000)
+        );
+
+        // The bomb is delayed by 3m blocks, and doubles every 100k blocks
+        assert_eq!(
+            calculate_difficulty(&parent, 3_199_999, 1009, &chain_spec),
+            U256::from(2048 * 1000)
+        );
+        assert_eq!(
+            calculate_difficulty(&parent, 3_200_000, 1009, &chain_spec),
+            U256::from(2048 * 1000 + 1)
+        );
+        assert_eq!(
+            calculate_difficulty(&parent, 3_400_000, 1009, &chain_spec),
+            U256::from(2048 * 1000 + 4)
+        );
+    }
+
+    #[test]
+    fn glacier_bomb_delays() {
+        let parent = parent(2048 * 1000, 1000);
+        for (fork, delay) in BOMB_DELAYS {
+            let chain_spec = ChainSpecBuilder::mainnet()
+                .byzantium_activated()
+                .with_fork(fork, ForkCondition::Block(0))
+                .build();
+            assert_eq!(
+                calculate_difficulty(&parent, delay + 399_999, 1009, &chain_spec),
+                U256::from(2048 * 1000 + 2),
+                "{fork:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn validate_against_parent() {
+        let chain_spec = ChainSpecBuilder::mainnet().homestead_activated().build();
+        let parent = Header { number: 1, gas_limit: 5000, ..parent(2048 * 1000, 1000) };
+        let header = Header {
+            number: 2,
+            timestamp: 1009,
+            gas_limit: 5000,
+            difficulty: U256::from(2048 * 1000 + 1000),
+            ..Default::default()
+        };
+        let sealed_parent = parent.clone().seal_slow();
+        assert_eq!(
+            validate_header_regarding_parent(
+                &sealed_parent,
+                &header.clone().seal_slow(),
+                &chain_spec
+            ),
+            Ok(())
+        );
+
+        let same_timestamp = Header { timestamp: 1000, ..header.clone() };
+        assert_matches!(
+            validate_header_regarding_parent(
+                &sealed_parent,
+                &same_timestamp.seal_slow(),
+                &chain_spec
+            ),
+            Err(Error::TimestampIsInPast { .. })
+        );
+
+        let low_gas_limit =
+            Header { gas_limit: MINIMUM_GAS_LIMIT - 1, ..header.clone() }.seal_slow();
+        assert_matches!(
+            validate_header_regarding_parent(&sealed_parent, &low_gas_limit, &chain_spec),
+            Err(Error::GasLimitOutOfBounds { gas_limit }) if gas_limit == MINIMUM_GAS_LIMIT - 1
+        );
+
+        let wrong_difficulty = Header { difficulty: U256::from(2048 * 1000), ..header }.seal_slow();
+        assert_matches!(
+            validate_header_regarding_parent(&sealed_parent, &wrong_difficulty, &chain_spec),
+            Err(Error::DifficultyDiff { expected, got })
+                if expected == U256::from(2048 * 1000 + 1000) && got == U256::from(2048 * 1000)
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Light verification of the ethash proof-of-work seal of a header.
+//!
+//! See the [ethash specification].
+//!
+//! [ethash specification]: https://ethereum.org/en/developers/docs/consensus-mechanisms/pow/mining-algorithms/ethash/
+use reth_interfaces::consensus::Error;
+use reth_primitives::{
+    keccak256,
+    tiny_keccak::{Hasher, Keccak},
+    Header, H256, U256,
+};
+use reth_rlp::Encodable;
+use std::sync::{Arc, Mutex};
+
+/// The number of bytes of the dataset at genesis.
+const DATASET_BYTES_INIT: u64 = 1 << 30;
+/// The number of bytes the dataset grows by every epoch.
+const DATASET_BYTES_GROWTH: u64 = 1 << 23;
+/// The number of bytes of the cache at genesis.
+const CACHE_BYTES_INIT: u64 = 1 << 24;
+/// The number of bytes the cache grows by every epoch.
+const CACHE_BYTES_GROWTH: u64 = 1 << 17;
+/// The number of blocks of an epoch.
+const EPOCH_LENGTH: u64 = 30_000;
+/// The width of the mix.
+const MIX_BYTES: u64 = 128;
+/// The number of bytes of a node of the cache or the dataset.
+const HASH_BYTES: u64 = 64;
+/// The number of parents of an item of the dataset.
+const DATASET_PARENTS: u32 = 256;
+/// The number of rounds of the cache generation.
+const CACHE_ROUNDS: usize = 3;
+/// The number of accesses to the dataset in hashimoto.
+const ACCESSES: u32 = 64;
+
+/// A node of the cache or the dataset, as little endian words.
+type Node = [u32; 16];
+
+fn keccak512(data: &[u8]) -> [u8; 64] {
+    let mut hasher = Keccak::v512();
+    hasher.update(data);
+    let mut output = [0u8; 64];
+    hasher.finalize(&mut output);
+    output
+}
+
+fn to_node(bytes: &[u8; 64]) -> Node {
+    let mut node = [0u32; 16];
+    for (word, chunk) in node.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().expect("chunk of 4 bytes"));
+    }
+    node
+}
+
+fn hash_node(node: &Node) -> Node {
+    let mut bytes = [0u8; 64];
+    for (chunk, word) in bytes.chunks_exact_mut(4).zip(node) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    to_node(&keccak512(&bytes))
+}
+
+fn fnv(a: u32, b: u32) -> u32 {
+    a.wrapping_mul(0x01000193) ^ b
+}
+
+fn is_prime(n: u64) -> bool {
+    n >= 2 && (2..).take_while(|i| i * i <= n).all(|i| n % i != 0)
+}
+
+/// Returns the size in bytes of the cache of the epoch.
+pub fn cache_size(epoch: u64) -> u64 {
+    let mut size = CACHE_BYTES_INIT + CACHE_BYTES_GROWTH * epoch - HASH_BYTES;
+    while !is_prime(size / HASH_BYTES) {
+        size -= 2 * HASH_BYTES;
+    }
+    size
+}
+
+/// Returns the size in bytes of the dataset of the epoch.
+pub fn dataset_size(epoch: u64) -> u64 {
+    let mut size = DATASET_BYTES_INIT + DATASET_BYTES_GROWTH * epoch - MIX_BYTES;
+    while !is_prime(size / MIX_BYTES) {
+        size -= 2 * MIX_BYTES;
+    }
+    size
+}
+
+/// Returns the seed of the cache of the epoch.
+pub fn seed_hash(epoch: u64) -> H256 {
+    (0..epoch).fold(H256::zero(), |seed, _| keccak256(seed))
+}
+
+/// Returns the hash of the header without its seal, the mix hash and the nonce, which is the
+/// input of the proof-of-work.
+pub fn seal_hash(header: &Header) -> H256 {
+    let mut payload = Vec::new();
+    header.parent_hash.encode(&mut payload);
+    header.ommers_hash.encode(&mut payload);
+    header.beneficiary.encode(&mut payload);
+    header.state_root.encode(&mut payload);
+    header.transactions_root.encode(&mut payload);
+    header.receipts_root.encode(&mut payload);
+    header.logs_bloom.encode(&mut payload);
+    header.difficulty.encode(&mut payload);
+    U256::from(header.number).encode(&mut payload);
+    U256::from(header.gas_limit).encode(&mut payload);
+    U256::from(header.gas_used).encode(&mut payload);
+    header.timestamp.encode(&mut payload);
+    header.extra_data.encode(&mut payload);
+    if let Some(base_fee) = header.base_fee_per_gas {
+        U256::from(base_fee).encode(&mut payload);
+    }
+
+    let mut out = Vec::with_capacity(payload.len() + 4);
+    reth_rlp::Header { list: true, payload_length: payload.len() }.encode(&mut out);
+    out.extend_from_slice(&payload);
+    keccak256(out)
+}
+
+/// The cache of an epoch, from which the items of the dataset are computed.
+#[derive(Debug)]
+pub struct EthashCache {
+    epoch: u64,
+    nodes: Vec<Node>,
+}
+
+impl EthashCache {
+    /// Generates the cache of the epoch.
+    ///
+    /// This is expensive, the cache of the first epoch is 16MB.
+    pub fn new(epoch: u64) -> Self {
+        let len = (cache_size(epoch) / HASH_BYTES) as usize;
+        let mut nodes = Vec::with_capacity(len);
+        let mut item = keccak512(seed_hash(epoch).as_bytes());
+        nodes.push(to_node(&item));
+        for _ in 1..len {
+            item = keccak512(&item);
+            nodes.push(to_node(&item));
+        }
+
+        for _ in 0..CACHE_ROUNDS {
+            for i in 0..len {
+                let other = nodes[i][0] as usize % len;
+                let mut node = nodes[(i + len - 1) % len];
+                for (word, other_word) in node.iter_mut().zip(&nodes[other]) {
+                    *word ^= other_word;
+                }
+                nodes[i] = hash_node(&node);
+            }
+        }
+
+        Self { epoch, nodes }
+    }
+
+    /// Returns the epoch of the cache.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Computes the item of the dataset with the given index.
+    fn dataset_item(&self, index: u32) -> Node {
+        let len = self.nodes.len();
+        let mut mix = self.nodes[index as usize % len];
+        mix[0] ^= index;
+        mix = hash_node(&mix);
+        for parent in 0..DATASET_PARENTS {
+            let parent = fnv(index ^ parent, mix[parent as usize % mix.len()]) as usize % len;
+            for (word, parent_word) in mix.iter_mut().zip(&self.nodes[parent]) {
+                *word = fnv(*word, *parent_word);
+            }
+        }
+        hash_node(&mix)
+    }
+
+    /// Computes the mix hash and the result of the proof-of-work for the seal hash of a header and
+    /// its nonce.
+    pub fn hashimoto(&self, seal_hash: H256, nonce: u64) -> (H256, H256) {
+        let mut input = [0u8; 40];
+        input[..32].copy_from_slice(seal_hash.as_bytes());
+        input[32..].copy_from_slice(&nonce.to_le_bytes());
+        let seed = keccak512(&input);
+        let seed_node = to_node(&seed);
+
+        // The mix is the seed repeated, and mixed with two consecutive items of the dataset at
+        // every access.
+        let mut mix = [0u32; 32];
+        mix[..16].copy_from_slice(&seed_node);
+        mix[16..].copy_from_slice(&seed_node);
+        let rows = (dataset_size(self.epoch) / MIX_BYTES) as u32;
+        for access in 0..ACCESSES {
+            let row = fnv(access ^ seed_node[0], mix[access as usize % mix.len()]) % rows;
+            for (half, index) in mix.chunks_exact_mut(16).zip([row * 2, row * 2 + 1]) {
+                for (word, item_word) in half.iter_mut().zip(self.dataset_item(index)) {
+                    *word = fnv(*word, item_word);
+                }
+            }
+        }
+
+        // Compress the mix into the mix hash
+        let mut mix_hash = H256::zero();
+        for (chunk, words) in mix_hash.as_bytes_mut().chunks_exact_mut(4).zip(mix.chunks_exact(4)) {
+            let word = fnv(fnv(fnv(words[0], words[1]), words[2]), words[3]);
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+
+        let mut result = [0u8; 96];
+        result[..64].copy_from_slice(&seed);
+        result[64..].copy_from_slice(mix_hash.as_bytes());
+        (mix_hash, keccak256(result))
+    }
+}
+
+/// Verifies the ethash seals of headers, with the cache of the epoch of the last verified header.
+#[derive(Debug, Default)]
+pub struct EthashVerifier {
+    cache: Mutex<Option<Arc<EthashCache>>>,
+}
+
+impl EthashVerifier {
+    /// Returns the cache of the epoch, which is generated if it is not the cached epoch.
+    fn cache(&self, epoch: u64) -> Arc<EthashCache> {
+        let mut cache = self.cache.lock().expect("ethash cache lock is not poisoned");
+        match &*cache {
+            Some(cache) if cache.epoch() == epoch => Arc::clone(cache),
+            _ => Arc::clone(cache.insert(Arc::new(EthashCache::new(epoch)))),
+        }
+    }
+
+    /// Verify the proof-of-work seal of the header: the mix hash must be the one computed for the
+    /// nonce, and the result must meet the difficulty.
+    pub fn verify_seal(&self, header: &Header) -> Result<(), Error> {
+        let cache = self.cache(header.number / EPOCH_LENGTH);
+        let (mix_hash, result) = cache.hashimoto(seal_hash(header), header.nonce);
+        if mix_hash != header.mix_hash {
+            return Err(Error::EthashMixHashDiff { got: header.mix_hash, expected: mix_hash })
+        }
+
+        // The result must not exceed 2^256 / difficulty
+        let target = if header.difficulty > U256::from(1) {
+            U256::MAX / header.difficulty
+        } else {
+            U256::MAX
+        };
+        if U256::from_be_bytes(result.to_fixed_bytes()) > target {
+            return Err(Error::EthashResultAboveTarget { result, difficulty: header.difficulty })
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{hex_literal::hex, Bytes, EMPTY_OMMER_ROOT};
+    use std::str::FromStr;
+
+    #[test]
+    fn epoch_sizes() {
+        assert_eq!(cache_size(0), 16776896);
+        assert_eq!(dataset_size(0), 1073739904);
+        assert_eq!(cache_size(1), 16907456);
+        assert_eq!(dataset_size(1), 1082130304);
+    }
+
+    #[test]
+    fn seeds() {
+        assert_eq!(seed_hash(0), H256::zero());
+        assert_eq!(seed_hash(1), keccak256(H256::zero()));
+    }
+
+    #[test]
+    fn verify_mainnet_block_1() {
+        let empty_root =
+            H256(hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"));
+        let header = Header {
+            parent_hash: H256(hex!(
+                "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+            )),
+            ommers_hash: EMPTY_OMMER_ROOT,
+            beneficiary: hex!("05a56e2d52c817161883f50c441c3228cfe54d9f").into(),
+            state_root: H256(hex!(
+                "d67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3"
+            )),
+            transactions_root: empty_root,
+            receipts_root: empty_root,
+            difficulty: U256::from(17171480576u64),
+            number: 1,
+            gas_limit: 5000,
+            gas_used: 0,
+            timestamp: 1438269988,
+            extra_data: Bytes::from_str("0x476574682f76312e302e302f6c696e75782f676f312e342e32")
+                .unwrap(),
+            mix_hash: H256(hex!(
+                "969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59"
+            )),
+            nonce: 0x539bd4979fef1ec4,
+            ..Default::default()
+        };
+        assert_eq!(
+            header.hash_slow(),
+            H256(hex!("88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6"))
+        );
+
+        let verifier = EthashVerifier::default();
+        assert_eq!(verifier.verify_seal(&header), Ok(()));
+
+        let wrong_nonce = Header { nonce: header.nonce + 1, ..header };
+        assert!(matches!(
+            verifier.verify_seal(&wrong_nonce),
+            Err(Error::EthashMixHashDiff { got, .. }) if got == wrong_nonce.mix_hash
+        ));
+    }
+}
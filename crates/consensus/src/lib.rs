@@ -9,5 +9,8 @@
 /// Beacon consensus implementation.
 pub mod beacon;
 
+/// Proof-of-work consensus rules of the blocks before the merge.
+pub mod ethash;
+
 /// Collection of consensus validation methods.
 pub mod validation;
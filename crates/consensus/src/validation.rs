@@ -2,7 +2,8 @@
 use reth_interfaces::{consensus::Error, Result as RethResult};
 use reth_primitives::{
     BlockNumber, ChainSpec, Hardfork, Header, SealedBlock, SealedHeader, Transaction,
-    TransactionSignedEcRecovered, TxEip1559, TxEip2930, TxLegacy,
+    TransactionKind, TransactionSignedEcRecovered, TxEip1559, TxEip2930, TxEip4844, TxLegacy,
+    EMPTY_OMMER_ROOT, U256,
 };
 use reth_provider::{AccountProvider, HeaderProvider};
 use std::{
@@ -46,25 +47,86 @@ pub fn validate_header_standalone(
     }
 
     // EIP-4895: Beacon chain push withdrawals as operations
-    if chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(header.timestamp) &&
-        header.withdrawals_root.is_none()
-    {
-        return Err(Error::WithdrawalsRootMissing)
+    if chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(header.timestamp) {
+        if header.withdrawals_root.is_none() {
+            return Err(Error::WithdrawalsRootMissing)
+        }
+
+        // Shanghai is only scheduled after the merge, so the header must be a proof-of-stake
+        // header, even if the total difficulty is not known yet.
+        validate_header_post_merge(header)?;
     } else if header.withdrawals_root.is_some() {
         return Err(Error::WithdrawalsRootUnexpected)
     }
 
+    // EIP-4844: Shard Blob Transactions
+    if chain_spec.fork(Hardfork::Cancun).active_at_timestamp(header.timestamp) {
+        let blob_gas_used = header.blob_gas_used.ok_or(Error::BlobGasUsedMissing)?;
+        if header.excess_blob_gas.is_none() {
+            return Err(Error::ExcessBlobGasMissing)
+        }
+
+        if blob_gas_used > constants::MAX_DATA_GAS_PER_BLOCK {
+            return Err(Error::BlobGasUsedExceedsMaxBlobGasPerBlock {
+                blob_gas_used,
+                max_blob_gas_per_block: constants::MAX_DATA_GAS_PER_BLOCK,
+            })
+        }
+        if blob_gas_used % constants::DATA_GAS_PER_BLOB != 0 {
+            return Err(Error::BlobGasUsedNotMultipleOfBlobGasPerBlob {
+                blob_gas_used,
+                blob_gas_per_blob: constants::DATA_GAS_PER_BLOB,
+            })
+        }
+
+        // EIP-4788: Beacon block root in the EVM
+        if header.parent_beacon_block_root.is_none() {
+            return Err(Error::ParentBeaconBlockRootMissing)
+        }
+    } else if header.blob_gas_used.is_some() {
+        return Err(Error::BlobGasUsedUnexpected)
+    } else if header.excess_blob_gas.is_some() {
+        return Err(Error::ExcessBlobGasUnexpected)
+    } else if header.parent_beacon_block_root.is_some() {
+        return Err(Error::ParentBeaconBlockRootUnexpected)
+    }
+
+    Ok(())
+}
+
+/// Validate the fields of a header that are fixed after the merge.
+///
+/// EIP-3675: Upgrade consensus to Proof-of-Stake:
+/// <https://eips.ethereum.org/EIPS/eip-3675#replacing-difficulty-with-0>
+pub fn validate_header_post_merge(header: &Header) -> Result<(), Error> {
+    if header.difficulty != U256::ZERO {
+        return Err(Error::TheMergeDifficultyIsNotZero)
+    }
+
+    if header.nonce != 0 {
+        return Err(Error::TheMergeNonceIsNotZero)
+    }
+
+    if header.ommers_hash != EMPTY_OMMER_ROOT {
+        return Err(Error::TheMergeOmmerRootIsNotEmpty)
+    }
+
+    // mixHash is used instead of difficulty inside EVM
+    // https://eips.ethereum.org/EIPS/eip-4399#using-mixhash-field-instead-of-difficulty
+
     Ok(())
 }
 
 /// Validate a transaction in regards to a block header.
 ///
-/// The only parameter from the header that affects the transaction is `base_fee`.
+/// The parameters from the header that affect the transaction are `base_fee` and `blob_fee`.
 pub fn validate_transaction_regarding_header(
     transaction: &Transaction,
     chain_spec: &ChainSpec,
     at_block_number: BlockNumber,
+    at_timestamp: u64,
     base_fee: Option<u64>,
+    blob_fee: Option<u128>,
 ) -> Result<(), Error> {
     let chain_id = match transaction {
         Transaction::Legacy(TxLegacy { chain_id, .. }) => {
@@ -100,6 +162,39 @@ pub fn validate_transaction_regarding_header(
                 return Err(Error::TransactionPriorityFeeMoreThenMaxFee)
             }
 
+            Some(*chain_id)
+        }
+        Transaction::Eip4844(TxEip4844 {
+            chain_id,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            to,
+            blob_versioned_hashes,
+            ..
+        }) => {
+            // EIP-4844: Shard Blob Transactions https://eips.ethereum.org/EIPS/eip-4844
+            if !chain_spec.fork(Hardfork::Cancun).active_at_timestamp(at_timestamp) {
+                return Err(Error::TransactionEip4844Disabled)
+            }
+
+            if max_priority_fee_per_gas > max_fee_per_gas {
+                return Err(Error::TransactionPriorityFeeMoreThenMaxFee)
+            }
+
+            // Blob transactions can not create contracts, and must carry at least one blob.
+            if let TransactionKind::Create = to {
+                return Err(Error::TransactionEip4844Create)
+            }
+            if blob_versioned_hashes.is_empty() {
+                return Err(Error::TransactionEip4844NoBlobs)
+            }
+            if let Some(hash) = blob_versioned_hashes
+                .iter()
+                .find(|hash| hash[0] != constants::VERSIONED_HASH_VERSION_KZG)
+            {
+                return Err(Error::TransactionEip4844InvalidVersionedHash { version: hash[0] })
+            }
+
             Some(*chain_id)
         }
     };
@@ -115,6 +210,14 @@ pub fn validate_transaction_regarding_header(
             return Err(Error::TransactionMaxFeeLessThenBaseFee)
         }
     }
+    // The blob fee is only paid by the blob transactions.
+    if let (Some(max_fee_per_blob_gas), Some(blob_fee)) =
+        (transaction.max_fee_per_blob_gas(), blob_fee)
+    {
+        if max_fee_per_blob_gas < blob_fee {
+            return Err(Error::TransactionMaxFeePerBlobGasLessThenBlobFee)
+        }
+    }
 
     Ok(())
 }
@@ -137,7 +240,9 @@ pub fn validate_all_transaction_regarding_block_and_nonces<
             transaction,
             chain_spec,
             header.number,
+            header.timestamp,
             header.base_fee_per_gas,
+            header.blob_fee(),
         )?;
 
         // Get nonce, if there is previous transaction from same sender we need
@@ -225,6 +330,18 @@ pub fn validate_block_standalone(block: &SealedBlock, chain_spec: &ChainSpec) ->
         }
     }
 
+    // EIP-4844: Shard Blob Transactions
+    if chain_spec.fork(Hardfork::Cancun).active_at_timestamp(block.timestamp) {
+        let header_blob_gas_used = block.blob_gas_used.ok_or(Error::BlobGasUsedMissing)?;
+        let blob_gas_used = block.body.iter().map(|tx| tx.blob_gas()).sum::<u64>();
+        if blob_gas_used != header_blob_gas_used {
+            return Err(Error::BlobGasUsedDiff {
+                got: header_blob_gas_used,
+                expected: blob_gas_used,
+            })
+        }
+    }
+
     Ok(())
 }
 
@@ -276,11 +393,8 @@ pub fn validate_header_regarding_parent(
         })
     }
 
-    // difficulty check is done by consensus.
-    // TODO(onbjerg): Unsure what the check here is supposed to be, but it should be moved to
-    // [BeaconConsensus]. if chain_spec.paris_status().block_number() > Some(child.number) {
-    //    // TODO how this needs to be checked? As ice age did increment it by some formula
-    //}
+    // The difficulty of proof-of-work headers is checked by
+    // [crate::ethash::validate_header_regarding_parent].
 
     let mut parent_gas_limit = parent.gas_limit;
 
@@ -325,6 +439,20 @@ pub fn validate_header_regarding_parent(
         }
     }
 
+    // EIP-4844 check excess blob gas
+    if chain_spec.fork(Hardfork::Cancun).active_at_timestamp(child.timestamp) {
+        let excess_blob_gas = child.excess_blob_gas.ok_or(Error::ExcessBlobGasMissing)?;
+
+        // The first block of the fork has a parent without blob gas, whose excess is zero.
+        let expected_excess_blob_gas = parent.next_block_excess_blob_gas().unwrap_or_default();
+        if expected_excess_blob_gas != excess_blob_gas {
+            return Err(Error::ExcessBlobGasDiff {
+                expected: expected_excess_blob_gas,
+                got: excess_blob_gas,
+            })
+        }
+    }
+
     Ok(())
 }
 
@@ -389,7 +517,7 @@ mod tests {
     use reth_interfaces::Result;
     use reth_primitives::{
         hex_literal::hex, proofs, Account, Address, BlockHash, Bytes, ChainSpecBuilder, Header,
-        Signature, TransactionKind, TransactionSigned, Withdrawal, MAINNET, U256,
+        Signature, TransactionKind, TransactionSigned, Withdrawal, H256, MAINNET,
     };
     use std::ops::RangeBounds;
 
@@ -506,7 +634,10 @@ mod tests {
             mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
             nonce: 0x0000000000000000,
             base_fee_per_gas: 0x28f0001df.into(),
-            withdrawals_root: None
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
         };
         // size: 0x9b5
 
@@ -638,4 +769,101 @@ mod tests {
             Err(Error::WithdrawalIndexInvalid { .. })
         );
     }
+
+    #[test]
+    fn header_standalone_after_cancun() {
+        let chain_spec = ChainSpecBuilder::mainnet().cancun_activated().build();
+        let header = Header {
+            base_fee_per_gas: Some(constants::EIP1559_INITIAL_BASE_FEE),
+            withdrawals_root: Some(H256::zero()),
+            blob_gas_used: Some(constants::DATA_GAS_PER_BLOB),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(H256::zero()),
+            ..Default::default()
+        };
+        let validate =
+            |header: Header| validate_header_standalone(&header.seal_slow(), &chain_spec);
+        assert_eq!(validate(header.clone()), Ok(()));
+
+        assert_eq!(
+            validate(Header { withdrawals_root: None, ..header.clone() }),
+            Err(Error::WithdrawalsRootMissing)
+        );
+        assert_eq!(
+            validate(Header { difficulty: U256::from(1), ..header.clone() }),
+            Err(Error::TheMergeDifficultyIsNotZero)
+        );
+        assert_eq!(
+            validate(Header { nonce: 1, ..header.clone() }),
+            Err(Error::TheMergeNonceIsNotZero)
+        );
+        assert_eq!(
+            validate(Header { ommers_hash: H256::zero(), ..header.clone() }),
+            Err(Error::TheMergeOmmerRootIsNotEmpty)
+        );
+        assert_eq!(
+            validate(Header { excess_blob_gas: None, ..header.clone() }),
+            Err(Error::ExcessBlobGasMissing)
+        );
+        assert_matches!(
+            validate(Header { blob_gas_used: Some(1), ..header.clone() }),
+            Err(Error::BlobGasUsedNotMultipleOfBlobGasPerBlob { .. })
+        );
+        assert_eq!(
+            validate(Header { parent_beacon_block_root: None, ..header }),
+            Err(Error::ParentBeaconBlockRootMissing)
+        );
+    }
+
+    #[test]
+    fn header_standalone_before_cancun() {
+        let chain_spec = ChainSpecBuilder::mainnet().shanghai_activated().build();
+        let header = Header {
+            base_fee_per_gas: Some(constants::EIP1559_INITIAL_BASE_FEE),
+            withdrawals_root: Some(H256::zero()),
+            ..Default::default()
+        };
+        let validate =
+            |header: Header| validate_header_standalone(&header.seal_slow(), &chain_spec);
+        assert_eq!(validate(header.clone()), Ok(()));
+
+        assert_eq!(
+            validate(Header { blob_gas_used: Some(0), ..header.clone() }),
+            Err(Error::BlobGasUsedUnexpected)
+        );
+        assert_eq!(
+            validate(Header { parent_beacon_block_root: Some(H256::zero()), ..header }),
+            Err(Error::ParentBeaconBlockRootUnexpected)
+        );
+    }
+
+    #[test]
+    fn excess_blob_gas_regarding_parent() {
+        let chain_spec = ChainSpecBuilder::mainnet().cancun_activated().build();
+        // At the gas target, so the base fee of the child is the one of the parent
+        let parent = Header {
+            gas_limit: 30_000_000,
+            gas_used: 15_000_000,
+            base_fee_per_gas: Some(constants::EIP1559_INITIAL_BASE_FEE),
+            blob_gas_used: Some(4 * constants::DATA_GAS_PER_BLOB),
+            excess_blob_gas: Some(constants::DATA_GAS_PER_BLOB),
+            ..Default::default()
+        };
+        let child = |excess_blob_gas| {
+            Header { number: 1, excess_blob_gas: Some(excess_blob_gas), ..parent.clone() }
+                .seal_slow()
+        };
+
+        // One blob above the target, on top of the excess of the parent
+        let expected = 2 * constants::DATA_GAS_PER_BLOB;
+        let parent = parent.clone().seal_slow();
+        assert_eq!(
+            validate_header_regarding_parent(&parent, &child(expected), &chain_spec),
+            Ok(())
+        );
+        assert_eq!(
+            validate_header_regarding_parent(&parent, &child(0), &chain_spec),
+            Err(Error::ExcessBlobGasDiff { expected, got: 0 })
+        );
+    }
 }
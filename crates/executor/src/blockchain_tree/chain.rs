@@ -0,0 +1,143 @@
+use crate::execution_result::{AccountInfoChangeSet, ExecutionResult};
+use reth_primitives::{
+    Account, Address, BlockHash, BlockNumber, ChainSpec, Hardfork, SealedBlock, U256,
+};
+use reth_provider::StateOverlay;
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+/// A chain of blocks that extends a canonical block, together with the results of their execution.
+///
+/// The chain has at least one block, the blocks are consecutive and the first one is a child of
+/// the fork block.
+#[derive(Debug, Clone)]
+pub struct Chain {
+    /// Number and hash of the canonical block the chain forks from.
+    fork_block: (BlockNumber, BlockHash),
+    /// The blocks of the chain, in ascending order.
+    blocks: Vec<SealedBlock>,
+    /// The execution results of the blocks.
+    results: Vec<ExecutionResult>,
+}
+
+impl Chain {
+    /// Creates a new chain with a single block on top of the fork block.
+    pub fn new(
+        fork_block: (BlockNumber, BlockHash),
+        block: SealedBlock,
+        result: ExecutionResult,
+    ) -> Self {
+        Self { fork_block, blocks: vec![block], results: vec![result] }
+    }
+
+    /// Returns the number and hash of the canonical block the chain forks from.
+    pub fn fork_block(&self) -> (BlockNumber, BlockHash) {
+        self.fork_block
+    }
+
+    /// Returns the blocks of the chain.
+    pub fn blocks(&self) -> &[SealedBlock] {
+        &self.blocks
+    }
+
+    /// Returns the execution results of the blocks of the chain.
+    pub fn results(&self) -> &[ExecutionResult] {
+        &self.results
+    }
+
+    /// Returns the range of the numbers of the blocks of the chain.
+    pub fn range(&self) -> RangeInclusive<BlockNumber> {
+        self.fork_block.0 + 1..=self.tip().number
+    }
+
+    /// Returns the accounts whose nonce or balance the blocks of the chain changed, with their
+    /// state after the tip of the chain, or `None` if they were destroyed.
+    pub fn changed_accounts(&self) -> BTreeMap<Address, Option<Account>> {
+        let mut accounts = BTreeMap::new();
+        for result in &self.results {
+            let changes = result
+                .tx_changesets
+                .iter()
+                .flat_map(|tx| tx.changeset.iter())
+                .chain(&result.block_changesets)
+                .map(|(address, change)| (address, &change.account));
+            for (address, change) in changes {
+                match change {
+                    AccountInfoChangeSet::Created { new } |
+                    AccountInfoChangeSet::Changed { new, .. } => {
+                        accounts.insert(*address, Some(*new));
+                    }
+                    AccountInfoChangeSet::Destroyed { .. } => {
+                        accounts.insert(*address, None);
+                    }
+                    AccountInfoChangeSet::NoChange => {}
+                }
+            }
+        }
+        accounts
+    }
+
+    /// Returns the last block of the chain.
+    pub fn tip(&self) -> &SealedBlock {
+        self.blocks.last().expect("chain is never empty")
+    }
+
+    /// Returns the block with the given hash.
+    pub fn block(&self, hash: BlockHash) -> Option<&SealedBlock> {
+        self.blocks.iter().find(|block| block.hash() == hash)
+    }
+
+    /// Returns the sum of the difficulties of the blocks of the chain.
+    pub fn difficulty(&self) -> U256 {
+        self.blocks.iter().fold(U256::ZERO, |difficulty, block| difficulty + block.difficulty)
+    }
+
+    /// Appends a block on top of the tip of the chain, the block must be a child of the tip.
+    pub fn push(&mut self, block: SealedBlock, result: ExecutionResult) {
+        self.blocks.push(block);
+        self.results.push(result);
+    }
+
+    /// Returns a new chain with the blocks up to and including the block with the given hash.
+    pub(crate) fn prefix(&self, hash: BlockHash) -> Option<Self> {
+        let index = self.index_of(hash)?;
+        Some(Self {
+            fork_block: self.fork_block,
+            blocks: self.blocks[..=index].to_vec(),
+            results: self.results[..=index].to_vec(),
+        })
+    }
+
+    /// Splits the chain after the block with the given hash.
+    ///
+    /// Returns the chain up to and including the block, and the chain of the blocks after it that
+    /// forks from the block, if any. The chain is returned unchanged if it has no such block.
+    pub(crate) fn split(mut self, hash: BlockHash) -> (Self, Option<Self>) {
+        let Some(index) = self.index_of(hash) else { return (self, None) };
+        if index + 1 == self.blocks.len() {
+            return (self, None)
+        }
+        let blocks = self.blocks.split_off(index + 1);
+        let results = self.results.split_off(index + 1);
+        let fork_block = (self.tip().number, self.tip().hash());
+        (self, Some(Self { fork_block, blocks, results }))
+    }
+
+    /// Applies the state changes of the blocks of the chain, and their hashes, to the overlay.
+    pub(crate) fn apply_to_overlay(&self, overlay: &mut StateOverlay, chain_spec: &ChainSpec) {
+        for (block, result) in self.blocks.iter().zip(&self.results) {
+            let has_state_clear_eip =
+                chain_spec.fork(Hardfork::SpuriousDragon).active_at_block(block.number);
+            result.apply_to_overlay(overlay, has_state_clear_eip);
+            overlay.insert_block_hash(block.number, block.hash());
+        }
+    }
+
+    /// Consumes the chain and returns its blocks with their execution results.
+    pub(crate) fn into_inner(self) -> (Vec<SealedBlock>, Vec<ExecutionResult>) {
+        (self.blocks, self.results)
+    }
+
+    fn index_of(&self, hash: BlockHash) -> Option<usize> {
+        self.blocks.iter().position(|block| block.hash() == hash)
+    }
+}
@@ -0,0 +1,952 @@
+//! Tree of the side chains that fork from the canonical chain.
+//!
+//! Blocks that don't extend the canonical chain in the database, e.g. the payloads of a fork the
+//! consensus layer has not chosen yet, are validated and executed on top of the state of their
+//! fork block and kept in memory. When a block of a side chain becomes the head of the canonical
+//! chain, the canonical blocks after the fork block are reverted and the side chain is committed
+//! to the database.
+
+mod chain;
+mod notification;
+mod state_cache;
+
+pub use chain::Chain;
+pub use notification::{
+    CanonStateNotification, CanonStateNotificationSender, CanonStateNotifications,
+    CanonStateSubscriptions, CANON_STATE_NOTIFICATION_CHANNEL_SIZE,
+};
+pub use state_cache::maintain_state_cache;
+
+use crate::{
+    executor,
+    revm_wrap::{State, SubState},
+};
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::{AccountBeforeTx, TransitionIdAddress},
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_interfaces::{
+    consensus::{self, Consensus},
+    db::Error as DbError,
+    executor::Error as ExecutionError,
+    provider::Error as ProviderError,
+};
+use reth_primitives::{
+    Address, BlockHash, BlockNumber, ChainSpec, Hardfork, Header, SealedBlock, SealedHeader,
+    StorageEntry, U256,
+};
+use reth_provider::{
+    insert_block, trie, LatestStateProviderRef, OverlayStateProvider, StateOverlay, Transaction,
+    TransactionError,
+};
+use reth_static_file::StaticFileProvider;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tracing::debug;
+
+/// Default number of blocks below the canonical tip from which side chains can fork.
+pub const DEFAULT_MAX_REORG_DEPTH: u64 = 64;
+
+/// Identifier of a side chain in the [BlockchainTree].
+pub type ChainId = u64;
+
+/// The status of a block inserted into the [BlockchainTree].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStatus {
+    /// The block is canonical, or is valid and its chain forks from the canonical tip.
+    Valid,
+    /// The block is valid and its chain forks from a block below the canonical tip.
+    Accepted,
+}
+
+/// Errors of the [BlockchainTree].
+#[derive(Debug, thiserror::Error)]
+pub enum BlockchainTreeError {
+    /// The parent of the block is neither canonical nor in the tree.
+    #[error("Parent {parent:?} of block {hash:?} is unknown")]
+    ParentUnknown {
+        /// The hash of the block.
+        hash: BlockHash,
+        /// The hash of the parent.
+        parent: BlockHash,
+    },
+    /// The block forks from a canonical block that is too far below the canonical tip.
+    #[error("Block {hash:?} forks from block #{fork_number}, too far below the canonical tip #{tip_number}")]
+    ForkTooDeep {
+        /// The hash of the block.
+        hash: BlockHash,
+        /// The number of the fork block.
+        fork_number: BlockNumber,
+        /// The number of the canonical tip.
+        tip_number: BlockNumber,
+    },
+    /// The block is neither canonical nor in the tree.
+    #[error("Block {0:?} is unknown")]
+    BlockUnknown(BlockHash),
+    /// The block is invalid.
+    #[error(transparent)]
+    Consensus(#[from] consensus::Error),
+    /// The block failed to execute.
+    #[error(transparent)]
+    Execution(#[from] ExecutionError),
+    /// The database returned an error.
+    #[error(transparent)]
+    Database(#[from] DbError),
+    /// The database is missing data.
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// The database transaction failed.
+    #[error(transparent)]
+    Transaction(#[from] TransactionError),
+    /// A block failed to be written.
+    #[error(transparent)]
+    Internal(#[from] reth_interfaces::Error),
+}
+
+/// A tree of side chains on top of the canonical chain in the database.
+///
+/// Every side chain has its own copy of the blocks from its fork block on, so that it can be
+/// committed on its own. The state of a side chain is the latest state, with the changes of the
+/// canonical blocks after the fork block reverted from the changesets and the changes of the
+/// blocks of the side chain applied on top.
+///
+/// The tree keeps all the tables of the committed and reverted blocks consistent with the stages
+/// of the pipeline: the blocks, their receipts, the plain and hashed state, the tries, the
+/// changesets and the history indices, and moves the checkpoints of the stages to the new tip.
+/// The state root of an inserted block is computed from the tries of the latest state, with the
+/// changes of the reverted canonical blocks, of its side chain and of the block applied, in a
+/// write transaction that is dropped. Blocks with a wrong state root are rejected. The state roots
+/// are checked again when the chain is committed.
+#[derive(Debug)]
+pub struct BlockchainTree<DB> {
+    db: DB,
+    consensus: Arc<dyn Consensus>,
+    chain_spec: ChainSpec,
+    /// Side chains forking further below the canonical tip are rejected and pruned.
+    max_reorg_depth: u64,
+    chains: HashMap<ChainId, Chain>,
+    /// The side chain of every block of the tree.
+    block_chains: HashMap<BlockHash, ChainId>,
+    next_chain_id: ChainId,
+    /// Notified of the changes of the canonical chain.
+    canon_state_notifications: CanonStateNotificationSender,
+    /// The static files the finalized blocks moved out of the database are read from.
+    static_files: Option<StaticFileProvider>,
+}
+
+impl<DB: Database> BlockchainTree<DB> {
+    /// Creates a new, empty tree on top of the canonical chain of the database.
+    pub fn new(db: DB, consensus: Arc<dyn Consensus>, chain_spec: ChainSpec) -> Self {
+        Self {
+            db,
+            consensus,
+            chain_spec,
+            max_reorg_depth: DEFAULT_MAX_REORG_DEPTH,
+            chains: Default::default(),
+            block_chains: Default::default(),
+            next_chain_id: 0,
+            canon_state_notifications: Default::default(),
+            static_files: None,
+        }
+    }
+
+    /// Sets how many blocks below the canonical tip side chains can fork from.
+    pub fn with_max_reorg_depth(mut self, max_reorg_depth: u64) -> Self {
+        self.max_reorg_depth = max_reorg_depth;
+        self
+    }
+
+    /// Sets the static files the finalized blocks are read from, once they were moved out of the
+    /// database.
+    pub fn with_static_files(mut self, static_files: StaticFileProvider) -> Self {
+        self.static_files = Some(static_files);
+        self
+    }
+
+    /// Returns a handle to subscribe to the changes of the canonical chain.
+    ///
+    /// Every call of [BlockchainTree::make_canonical] that changes the canonical chain sends a
+    /// single notification, with the reverted blocks on a reorg.
+    pub fn canon_state_notifications(&self) -> CanonStateNotificationSender {
+        self.canon_state_notifications.clone()
+    }
+
+    /// Returns `true` if the block is in a side chain of the tree.
+    pub fn contains(&self, hash: BlockHash) -> bool {
+        self.block_chains.contains_key(&hash)
+    }
+
+    /// Returns the side chain that contains the block.
+    pub fn chain(&self, hash: BlockHash) -> Option<&Chain> {
+        self.block_chains.get(&hash).map(|chain_id| &self.chains[chain_id])
+    }
+
+    /// Validates and executes the block, and adds it to the side chain of its parent.
+    ///
+    /// A new side chain is created if the parent is canonical, or is not the tip of its side
+    /// chain.
+    pub fn insert_block(&mut self, block: SealedBlock) -> Result<BlockStatus, BlockchainTreeError> {
+        let hash = block.hash();
+        let tx = self.db.tx()?;
+        let (tip_number, tip_hash) = canonical_tip(&tx)?;
+
+        if let Some(chain_id) = self.block_chains.get(&hash) {
+            return Ok(self.status(*chain_id, tip_hash))
+        }
+        if tx.get::<tables::CanonicalHeaders>(block.number)? == Some(hash) {
+            return Ok(BlockStatus::Valid)
+        }
+
+        // The block extends the tip of a side chain.
+        let extended_chain = self
+            .block_chains
+            .get(&block.parent_hash)
+            .filter(|chain_id| self.chains[*chain_id].tip().hash() == block.parent_hash)
+            .copied();
+        if let Some(chain_id) = extended_chain {
+            let chain = &self.chains[&chain_id];
+            let result = self.validate_and_execute(
+                &tx,
+                &block,
+                &chain.tip().header,
+                chain.fork_block(),
+                Some(chain),
+            )?;
+            drop(tx);
+
+            debug!(target: "blockchain_tree", ?hash, number = block.number, chain_id, "Extending side chain");
+            self.chains.get_mut(&chain_id).expect("chain exists").push(block, result);
+            self.block_chains.insert(hash, chain_id);
+            return Ok(self.status(chain_id, tip_hash))
+        }
+
+        let chain = match self.block_chains.get(&block.parent_hash) {
+            // The block forks from the middle of a side chain, the new side chain starts with a
+            // copy of the blocks up to the parent.
+            Some(chain_id) => {
+                let mut chain = self.chains[chain_id]
+                    .prefix(block.parent_hash)
+                    .expect("parent is in the chain");
+                let result = self.validate_and_execute(
+                    &tx,
+                    &block,
+                    &chain.tip().header,
+                    chain.fork_block(),
+                    Some(&chain),
+                )?;
+                chain.push(block, result);
+                chain
+            }
+            // The block forks from a canonical block.
+            None => {
+                let parent_number = match tx.get::<tables::HeaderNumbers>(block.parent_hash)? {
+                    Some(number)
+                        if tx.get::<tables::CanonicalHeaders>(number)? ==
+                            Some(block.parent_hash) =>
+                    {
+                        number
+                    }
+                    _ => {
+                        return Err(BlockchainTreeError::ParentUnknown {
+                            hash,
+                            parent: block.parent_hash,
+                        })
+                    }
+                };
+                if parent_number + self.max_reorg_depth < tip_number {
+                    return Err(BlockchainTreeError::ForkTooDeep {
+                        hash,
+                        fork_number: parent_number,
+                        tip_number,
+                    })
+                }
+
+                let parent = tx
+                    .get::<tables::Headers>(parent_number)?
+                    .ok_or(ProviderError::Header { number: parent_number })?
+                    .seal(block.parent_hash);
+                let fork_block = (parent_number, block.parent_hash);
+                let result = self.validate_and_execute(&tx, &block, &parent, fork_block, None)?;
+                Chain::new(fork_block, block, result)
+            }
+        };
+        drop(tx);
+
+        let chain_id = self.insert_chain(chain);
+        debug!(target: "blockchain_tree", ?hash, chain_id, "Created side chain");
+        Ok(self.status(chain_id, tip_hash))
+    }
+
+    /// Makes the block the head of the canonical chain.
+    ///
+    /// The canonical blocks after the fork block of its side chain are reverted and inserted into
+    /// the tree as a side chain, then the blocks of the side chain up to the block are committed
+    /// to the database. Side chains that forked from reverted blocks are rebuilt on top of them,
+    /// and side chains that now fork too far below the canonical tip are removed.
+    ///
+    /// The subscribers of [BlockchainTree::canon_state_notifications] are notified of the
+    /// committed blocks, and of the reverted blocks with the results of their execution on a
+    /// reorg.
+    ///
+    /// If the chain is invalid, nothing is committed and the side chains with the block are
+    /// removed from the tree.
+    pub fn make_canonical(&mut self, hash: BlockHash) -> Result<(), BlockchainTreeError> {
+        let Some(chain_id) = self.block_chains.get(&hash).copied() else {
+            let tx = self.db.tx()?;
+            let is_canonical = match tx.get::<tables::HeaderNumbers>(hash)? {
+                Some(number) => tx.get::<tables::CanonicalHeaders>(number)? == Some(hash),
+                None => false,
+            };
+            return if is_canonical { Ok(()) } else { Err(BlockchainTreeError::BlockUnknown(hash)) }
+        };
+
+        let chain = self.chains[&chain_id].prefix(hash).expect("block is in the chain");
+        let (fork_number, _) = chain.fork_block();
+        let committed = chain.blocks().iter().map(|block| block.hash()).collect::<HashSet<_>>();
+        let tip_number = chain.tip().number;
+        let new = Arc::new(chain.clone());
+
+        let committed_chain = (|| {
+            let mut tx = Transaction::new(&self.db)?.with_static_files(self.static_files.clone());
+            let (canonical_tip_number, _) = canonical_tip(&*tx)?;
+            let reverted = if fork_number < canonical_tip_number {
+                debug!(target: "blockchain_tree", fork_number, canonical_tip_number, "Reverting canonical blocks");
+                revert_canonical(&tx, fork_number)?
+            } else {
+                Vec::new()
+            };
+            self.commit_chain(&tx, chain)?;
+            tx.commit()?;
+            Ok::<_, BlockchainTreeError>(reverted)
+        })();
+        let reverted = match committed_chain {
+            Ok(reverted) => reverted,
+            Err(
+                error @ (BlockchainTreeError::Consensus(_) | BlockchainTreeError::Execution(_)),
+            ) => {
+                debug!(target: "blockchain_tree", ?hash, ?error, "Removing invalid side chains");
+                let invalid = self
+                    .chains
+                    .iter()
+                    .filter(|(_, chain)| chain.block(hash).is_some())
+                    .map(|(chain_id, _)| *chain_id)
+                    .collect::<Vec<_>>();
+                for chain_id in invalid {
+                    self.remove_chain(chain_id);
+                }
+                return Err(error)
+            }
+            Err(error) => return Err(error),
+        };
+        debug!(target: "blockchain_tree", ?hash, tip_number, reverted = reverted.len(), "Committed canonical chain");
+
+        let reverted_hashes = reverted.iter().map(|block| block.hash()).collect::<Vec<_>>();
+        let mut reinserted = reverted;
+        for chain_id in self.chains.keys().copied().collect::<Vec<_>>() {
+            let chain = self.remove_chain(chain_id);
+
+            // The chain forks from a block that is no longer canonical, its blocks are executed
+            // again on top of the reverted blocks.
+            if reverted_hashes.contains(&chain.fork_block().1) {
+                reinserted.extend(chain.into_inner().0);
+                continue
+            }
+
+            // The committed blocks of the chain are canonical now.
+            let last_committed = chain
+                .blocks()
+                .iter()
+                .rev()
+                .map(|block| block.hash())
+                .find(|hash| committed.contains(hash));
+            let chain = match last_committed {
+                Some(hash) => match chain.split(hash) {
+                    (_, Some(pending)) => pending,
+                    (_, None) => continue,
+                },
+                None => chain,
+            };
+
+            if chain.fork_block().0 + self.max_reorg_depth < tip_number {
+                debug!(target: "blockchain_tree", chain_id, fork_block = ?chain.fork_block(), "Removing side chain forking too deep");
+                continue
+            }
+            self.insert_chain(chain);
+        }
+
+        for block in reinserted {
+            let hash = block.hash();
+            if let Err(error) = self.insert_block(block) {
+                debug!(target: "blockchain_tree", ?hash, ?error, "Failed to reinsert block");
+            }
+        }
+
+        // The reverted blocks are executed again when they're reinserted, the side chain of the
+        // last reinserted one has their execution results.
+        let old = reverted_hashes
+            .iter()
+            .rev()
+            .find_map(|hash| self.chain(*hash).and_then(|chain| chain.prefix(*hash)));
+        let notification = match old {
+            Some(old) => CanonStateNotification::Reorg { old: Arc::new(old), new },
+            None => {
+                if !reverted_hashes.is_empty() {
+                    debug!(target: "blockchain_tree", ?hash, "Reverted blocks can not be notified");
+                }
+                CanonStateNotification::Commit { new }
+            }
+        };
+        self.canon_state_notifications.notify(notification);
+
+        Ok(())
+    }
+
+    /// Validates the block against its parent and executes it on top of the state of the side
+    /// chain, or of the fork block if the block starts a new side chain, then checks its state
+    /// root.
+    fn validate_and_execute<'a, TX: DbTx<'a>>(
+        &self,
+        tx: &TX,
+        block: &SealedBlock,
+        parent: &SealedHeader,
+        fork_block: (BlockNumber, BlockHash),
+        chain: Option<&Chain>,
+    ) -> Result<crate::execution_result::ExecutionResult, BlockchainTreeError> {
+        let (fork_number, _) = fork_block;
+        let fork_td: U256 = tx
+            .get::<tables::HeaderTD>(fork_number)?
+            .ok_or(ProviderError::TotalDifficulty { number: fork_number })?
+            .into();
+        let total_difficulty =
+            fork_td + chain.map_or(U256::ZERO, Chain::difficulty) + block.difficulty;
+
+        self.consensus.pre_validate_header(&block.header, parent)?;
+        self.consensus.validate_header(&block.header, total_difficulty)?;
+        self.consensus.pre_validate_block(block)?;
+
+        let mut overlay = revert_overlay(tx, fork_number)?;
+        if let Some(chain) = chain {
+            chain.apply_to_overlay(&mut overlay, &self.chain_spec);
+        }
+        let state = OverlayStateProvider::new(LatestStateProviderRef::new(tx), overlay.clone());
+        let mut state = SubState::new(State::new(state));
+
+        let result = executor::execute_and_verify_receipt(
+            &block.clone().unseal(),
+            total_difficulty,
+            None,
+            &self.chain_spec,
+            &mut state,
+        )?;
+
+        // the overlay turns the latest state into the state after the block
+        let has_state_clear_eip =
+            self.chain_spec.fork(Hardfork::SpuriousDragon).active_at_block(block.number);
+        result.apply_to_overlay(&mut overlay, has_state_clear_eip);
+        let (tip_number, _) = canonical_tip(tx)?;
+        let tip_root = tx
+            .get::<tables::Headers>(tip_number)?
+            .ok_or(ProviderError::Header { number: tip_number })?
+            .state_root;
+        let state_root =
+            trie::state_root_with_changes(&self.db, tip_root, &overlay.hashed_changes())?;
+        executor::verify_state_root(&block.header, state_root)?;
+
+        Ok(result)
+    }
+
+    /// Writes the blocks of the chain and the results of their execution on top of its fork
+    /// block, which must be the canonical tip.
+    fn commit_chain(
+        &self,
+        tx: &Transaction<'_, DB>,
+        chain: Chain,
+    ) -> Result<(), BlockchainTreeError> {
+        let (fork_number, _) = chain.fork_block();
+        let tip_number = chain.tip().number;
+        let mut total_difficulty = tx.get_td(fork_number)?;
+        let mut parent_root = tx.get_header(fork_number)?.state_root;
+        let (blocks, results) = chain.into_inner();
+        for (block, result) in blocks.iter().zip(results) {
+            total_difficulty += block.difficulty;
+            let (first_tx_id, first_transition_id) = tx.get_next_block_ids(block.number)?;
+            let has_block_reward =
+                self.consensus.has_block_reward(total_difficulty, block.difficulty);
+            // the ids are given, since the body of the parent may be in the static files
+            insert_block(&**tx, block, has_block_reward, Some((first_tx_id, first_transition_id)))?;
+            tx.put::<tables::HeaderTD>(block.number, total_difficulty.into())?;
+
+            for (index, (transaction, changeset)) in
+                block.body.iter().zip(&result.tx_changesets).enumerate()
+            {
+                let tx_id = first_tx_id + index as u64;
+                tx.put::<tables::TxHashNumber>(transaction.hash(), tx_id)?;
+                tx.put::<tables::Receipts>(tx_id, changeset.receipt.clone())?;
+            }
+
+            let has_state_clear_eip =
+                self.chain_spec.fork(Hardfork::SpuriousDragon).active_at_block(block.number);
+            result.write_to_db(&**tx, first_transition_id, has_state_clear_eip)?;
+
+            let transitions = first_transition_id..tx.get_block_transition(block.number)?;
+            tx.insert_account_hashes(transitions.clone())?;
+            tx.insert_storage_hashes(transitions.clone())?;
+            let state_root = tx.update_state_root(parent_root, transitions.clone())?;
+            executor::verify_state_root(&block.header, state_root)?;
+            tx.insert_account_history_indices(transitions.clone())?;
+            tx.insert_storage_history_indices(transitions)?;
+            parent_root = block.state_root;
+        }
+
+        tx.update_stage_checkpoints(tip_number)?;
+        Ok(())
+    }
+
+    fn status(&self, chain_id: ChainId, tip_hash: BlockHash) -> BlockStatus {
+        if self.chains[&chain_id].fork_block().1 == tip_hash {
+            BlockStatus::Valid
+        } else {
+            BlockStatus::Accepted
+        }
+    }
+
+    fn insert_chain(&mut self, chain: Chain) -> ChainId {
+        let chain_id = self.next_chain_id;
+        self.next_chain_id += 1;
+        for block in chain.blocks() {
+            // blocks copied from another chain keep pointing to it
+            self.block_chains.entry(block.hash()).or_insert(chain_id);
+        }
+        self.chains.insert(chain_id, chain);
+        chain_id
+    }
+
+    fn remove_chain(&mut self, chain_id: ChainId) -> Chain {
+        let chain = self.chains.remove(&chain_id).expect("chain exists");
+        for block in chain.blocks() {
+            let hash = block.hash();
+            if self.block_chains.get(&hash) != Some(&chain_id) {
+                continue
+            }
+            // point the block to another chain that has a copy of it
+            match self.chains.iter().find(|(_, chain)| chain.block(hash).is_some()) {
+                Some((other, _)) => self.block_chains.insert(hash, *other),
+                None => self.block_chains.remove(&hash),
+            };
+        }
+        chain
+    }
+}
+
+/// The operations of the [BlockchainTree] the engine API drives, without its database type.
+pub trait BlockchainTreeEngine: Send {
+    /// See [BlockchainTree::insert_block].
+    fn insert_block(&mut self, block: SealedBlock) -> Result<BlockStatus, BlockchainTreeError>;
+
+    /// See [BlockchainTree::make_canonical].
+    fn make_canonical(&mut self, hash: BlockHash) -> Result<(), BlockchainTreeError>;
+
+    /// See [BlockchainTree::contains].
+    fn contains(&self, hash: BlockHash) -> bool;
+}
+
+impl<DB: Database + Send> BlockchainTreeEngine for BlockchainTree<DB> {
+    fn insert_block(&mut self, block: SealedBlock) -> Result<BlockStatus, BlockchainTreeError> {
+        BlockchainTree::insert_block(self, block)
+    }
+
+    fn make_canonical(&mut self, hash: BlockHash) -> Result<(), BlockchainTreeError> {
+        BlockchainTree::make_canonical(self, hash)
+    }
+
+    fn contains(&self, hash: BlockHash) -> bool {
+        BlockchainTree::contains(self, hash)
+    }
+}
+
+/// Returns the number and hash of the last canonical block.
+fn canonical_tip<'a, TX: DbTx<'a>>(
+    tx: &TX,
+) -> Result<(BlockNumber, BlockHash), BlockchainTreeError> {
+    Ok(tx
+        .cursor_read::<tables::CanonicalHeaders>()?
+        .last()?
+        .ok_or(ProviderError::CanonicalHeader { block_number: 0 })?)
+}
+
+/// Returns the changes that revert the latest state to the state after the canonical block
+/// `fork_number`.
+///
+/// The first change of every account and storage slot after the block holds its value at the
+/// block.
+fn revert_overlay<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    fork_number: BlockNumber,
+) -> Result<StateOverlay, BlockchainTreeError> {
+    let transition = tx
+        .get::<tables::BlockTransitionIndex>(fork_number)?
+        .ok_or(ProviderError::BlockTransition { block_number: fork_number })?;
+    let mut overlay = StateOverlay::default();
+
+    let mut accounts = HashSet::new();
+    for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk(Some(transition))? {
+        let (_, AccountBeforeTx { address, info }) = entry?;
+        if accounts.insert(address) {
+            overlay.insert_account(address, info);
+        }
+    }
+
+    let mut slots = HashSet::new();
+    let start = TransitionIdAddress((transition, Address::zero()));
+    for entry in tx.cursor_read::<tables::StorageChangeSet>()?.walk(Some(start))? {
+        let (key, StorageEntry { key: slot, value }) = entry?;
+        if slots.insert((key.address(), slot)) {
+            overlay.insert_storage(key.address(), slot, value);
+        }
+    }
+
+    Ok(overlay)
+}
+
+/// Removes the canonical blocks after `fork_number` and reverts their state changes, and returns
+/// the removed blocks.
+///
+/// The hashed state, the tries and the history indices are reverted from the changesets, which
+/// are removed along with the plain state changes, and the checkpoints of the stages are moved to
+/// the fork block.
+fn revert_canonical<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    fork_number: BlockNumber,
+) -> Result<Vec<SealedBlock>, BlockchainTreeError> {
+    let (tip_number, _) = canonical_tip(&**tx)?;
+    let blocks = (fork_number + 1..=tip_number)
+        .map(|number| read_block(tx, number))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let transitions = tx.get_block_transition(fork_number)?..tx.get_block_transition(tip_number)?;
+    tx.unwind_account_history_indices(transitions.clone())?;
+    tx.unwind_storage_history_indices(transitions.clone())?;
+    tx.unwind_account_hashes(transitions.clone())?;
+    tx.unwind_storage_hashes(transitions.clone())?;
+    let tip_root = tx.get_header(tip_number)?.state_root;
+    let state_root = tx.update_state_root(tip_root, transitions.clone())?;
+    executor::verify_state_root(&tx.get_header(fork_number)?, state_root)?;
+    tx.unwind_plain_state(transitions)?;
+
+    for transaction in blocks.iter().flat_map(|block| block.body.iter()) {
+        tx.delete::<tables::TxHashNumber>(transaction.hash(), None)?;
+    }
+    let (first_tx_id, _) = tx.get_next_block_ids(fork_number + 1)?;
+    tx.delete_range::<tables::Transactions>(first_tx_id..)?;
+    tx.delete_range::<tables::TxSenders>(first_tx_id..)?;
+    tx.delete_range::<tables::TxTransitionIndex>(first_tx_id..)?;
+    tx.delete_range::<tables::Receipts>(first_tx_id..)?;
+    tx.delete_range::<tables::Logs>(first_tx_id..)?;
+
+    tx.unwind_table_by_walker::<tables::CanonicalHeaders, tables::HeaderNumbers>(fork_number + 1)?;
+    tx.unwind_table_by_num::<tables::CanonicalHeaders>(fork_number)?;
+    tx.unwind_table_by_num::<tables::Headers>(fork_number)?;
+    tx.unwind_table_by_num::<tables::HeaderTD>(fork_number)?;
+    tx.unwind_table_by_num::<tables::BlockBodies>(fork_number)?;
+    tx.unwind_table_by_num::<tables::BlockOmmers>(fork_number)?;
+    tx.unwind_table_by_num::<tables::BlockWithdrawals>(fork_number)?;
+    tx.unwind_table_by_num::<tables::BlockTransitionIndex>(fork_number)?;
+    tx.update_stage_checkpoints(fork_number)?;
+
+    Ok(blocks)
+}
+
+/// Reads the canonical block `number` from the database.
+fn read_block<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    number: BlockNumber,
+) -> Result<SealedBlock, BlockchainTreeError> {
+    let hash = tx.get_block_hash(number)?;
+    let header = tx.get_header(number)?;
+    let body = tx.get_block_body(number)?;
+    let transactions = tx
+        .get_transactions(body.tx_id_range())?
+        .into_iter()
+        .map(|(_, transaction)| transaction)
+        .collect();
+    let ommers = tx
+        .get::<tables::BlockOmmers>(number)?
+        .map(|stored| stored.ommers.into_iter().map(Header::seal_slow).collect())
+        .unwrap_or_default();
+    // empty withdrawals are not stored
+    let withdrawals = match header.withdrawals_root {
+        Some(_) => Some(
+            tx.get::<tables::BlockWithdrawals>(number)?
+                .map(|stored| stored.withdrawals)
+                .unwrap_or_default(),
+        ),
+        None => None,
+    };
+
+    Ok(SealedBlock { header: header.seal(hash), body: transactions, ommers, withdrawals })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution_result::ExecutionResult;
+    use assert_matches::assert_matches;
+    use reth_db::{
+        mdbx::{test_utils::create_test_db, Env, EnvKind, WriteMap},
+        models::ShardedKey,
+    };
+    use reth_interfaces::test_utils::TestConsensus;
+    use reth_primitives::{
+        keccak256,
+        proofs::{self, EMPTY_ROOT},
+        Account, ChainSpecBuilder, GenesisAccount, Withdrawal, H256,
+    };
+    use reth_provider::insert_canonical_block;
+
+    type TestTree = BlockchainTree<Arc<Env<WriteMap>>>;
+
+    fn setup() -> (TestTree, SealedHeader) {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let genesis = SealedBlock {
+            header: Header { gas_limit: 30_000_000, state_root: EMPTY_ROOT, ..Default::default() }
+                .seal_slow(),
+            ..Default::default()
+        };
+        let tx = db.tx_mut().unwrap();
+        insert_canonical_block(&tx, &genesis, true).unwrap();
+        tx.put::<tables::HeaderTD>(0, U256::ZERO.into()).unwrap();
+        tx.put::<tables::SyncStage>(b"Execution".to_vec(), 0).unwrap();
+        tx.commit().unwrap();
+
+        let chain_spec = ChainSpecBuilder::mainnet().shanghai_activated().build();
+        let tree = BlockchainTree::new(db, Arc::new(TestConsensus::default()), chain_spec);
+        (tree, genesis.header)
+    }
+
+    /// The state root of the accounts with the given balances, in gwei.
+    fn state_root(balances: &[(Address, u64)]) -> H256 {
+        let accounts = balances.iter().map(|(address, gwei)| {
+            let balance = U256::from(*gwei) * U256::from(1_000_000_000u64);
+            (*address, GenesisAccount { balance, ..Default::default() })
+        });
+        proofs::genesis_state_root(&accounts.collect())
+    }
+
+    /// An empty block on top of `parent` with a withdrawal of 1 gwei to `recipient`, and the
+    /// given state root.
+    fn block(parent: &SealedHeader, recipient: Address, state_root: H256) -> SealedBlock {
+        let withdrawals = vec![Withdrawal {
+            index: parent.number,
+            validator_index: 0,
+            address: recipient,
+            amount: 1,
+        }];
+        let header = Header {
+            parent_hash: parent.hash(),
+            number: parent.number + 1,
+            gas_limit: parent.gas_limit,
+            timestamp: parent.timestamp + 12,
+            state_root,
+            receipts_root: EMPTY_ROOT,
+            withdrawals_root: Some(proofs::calculate_withdrawals_root(withdrawals.iter())),
+            ..Default::default()
+        };
+        SealedBlock {
+            header: header.seal_slow(),
+            body: Vec::new(),
+            ommers: Vec::new(),
+            withdrawals: Some(withdrawals),
+        }
+    }
+
+    fn canonical_hash(tree: &TestTree, number: BlockNumber) -> Option<H256> {
+        tree.db.tx().unwrap().get::<tables::CanonicalHeaders>(number).unwrap()
+    }
+
+    fn balance(tree: &TestTree, address: Address) -> Option<U256> {
+        let account: Option<Account> =
+            tree.db.tx().unwrap().get::<tables::PlainAccountState>(address).unwrap();
+        account.map(|account| account.balance)
+    }
+
+    fn hashed_balance(tree: &TestTree, address: Address) -> Option<U256> {
+        let account: Option<Account> =
+            tree.db.tx().unwrap().get::<tables::HashedAccount>(keccak256(address)).unwrap();
+        account.map(|account| account.balance)
+    }
+
+    fn account_history(tree: &TestTree, address: Address) -> Vec<usize> {
+        let key = ShardedKey::new(address, u64::MAX);
+        let list = tree.db.tx().unwrap().get::<tables::AccountHistory>(key).unwrap();
+        list.map(|list| list.iter(0).collect()).unwrap_or_default()
+    }
+
+    fn checkpoint(tree: &TestTree) -> Option<BlockNumber> {
+        tree.db.tx().unwrap().get::<tables::SyncStage>(b"Execution".to_vec()).unwrap()
+    }
+
+    #[test]
+    fn extend_canonical_tip() {
+        let (mut tree, genesis) = setup();
+        let recipient = Address::random();
+        let block1 = block(&genesis, recipient, state_root(&[(recipient, 1)]));
+        let block2 = block(&block1.header, recipient, state_root(&[(recipient, 2)]));
+
+        assert_matches!(tree.insert_block(block1.clone()), Ok(BlockStatus::Valid));
+        assert_matches!(tree.insert_block(block2.clone()), Ok(BlockStatus::Valid));
+        assert_eq!(tree.chain(block2.hash()).unwrap().blocks(), &[block1.clone(), block2.clone()]);
+
+        let mut notifications = tree.canon_state_notifications().subscribe_to_canonical_state();
+        tree.make_canonical(block1.hash()).unwrap();
+        assert_matches!(
+            notifications.try_recv(),
+            Ok(CanonStateNotification::Commit { new }) if new.blocks() == [block1.clone()]
+        );
+        assert_eq!(canonical_hash(&tree, 1), Some(block1.hash()));
+        assert_eq!(canonical_hash(&tree, 2), None);
+        assert_eq!(balance(&tree, recipient), Some(U256::from(1_000_000_000u64)));
+        assert_eq!(hashed_balance(&tree, recipient), Some(U256::from(1_000_000_000u64)));
+        assert_eq!(account_history(&tree, recipient), vec![0]);
+        assert_eq!(checkpoint(&tree), Some(1));
+        assert!(!tree.contains(block1.hash()));
+        assert_eq!(tree.chain(block2.hash()).unwrap().fork_block(), (1, block1.hash()));
+
+        tree.make_canonical(block2.hash()).unwrap();
+        assert_eq!(canonical_hash(&tree, 2), Some(block2.hash()));
+        assert_eq!(balance(&tree, recipient), Some(U256::from(2_000_000_000u64)));
+        assert_eq!(account_history(&tree, recipient), vec![0, 1]);
+        assert_eq!(checkpoint(&tree), Some(2));
+        assert!(!tree.contains(block2.hash()));
+
+        // canonical blocks are already committed
+        tree.make_canonical(block1.hash()).unwrap();
+        assert_matches!(tree.insert_block(block2), Ok(BlockStatus::Valid));
+    }
+
+    #[test]
+    fn reorg_to_side_chain() {
+        let (mut tree, genesis) = setup();
+        let canonical_recipient = Address::random();
+        let canonical =
+            block(&genesis, canonical_recipient, state_root(&[(canonical_recipient, 1)]));
+        tree.insert_block(canonical.clone()).unwrap();
+        tree.make_canonical(canonical.hash()).unwrap();
+
+        let side_recipient = Address::random();
+        let side1 = block(&genesis, side_recipient, state_root(&[(side_recipient, 1)]));
+        let side2 = block(&side1.header, side_recipient, state_root(&[(side_recipient, 2)]));
+        assert_matches!(tree.insert_block(side1.clone()), Ok(BlockStatus::Accepted));
+        assert_matches!(tree.insert_block(side2.clone()), Ok(BlockStatus::Accepted));
+
+        let mut notifications = tree.canon_state_notifications().subscribe_to_canonical_state();
+        tree.make_canonical(side2.hash()).unwrap();
+        let notification = notifications.try_recv().unwrap();
+        assert!(notifications.try_recv().is_err());
+        let (old, new) = match notification {
+            CanonStateNotification::Reorg { old, new } => (old, new),
+            CanonStateNotification::Commit { .. } => panic!("expected a reorg"),
+        };
+        assert_eq!(old.blocks(), &[canonical.clone()]);
+        assert_eq!(new.blocks(), &[side1.clone(), side2.clone()]);
+        assert_eq!(new.range(), 1..=2);
+        assert_eq!(
+            new.changed_accounts().get(&side_recipient).copied().flatten().map(|a| a.balance),
+            Some(U256::from(2_000_000_000u64))
+        );
+        assert_eq!(canonical_hash(&tree, 1), Some(side1.hash()));
+        assert_eq!(canonical_hash(&tree, 2), Some(side2.hash()));
+        assert_eq!(balance(&tree, canonical_recipient), None);
+        assert_eq!(balance(&tree, side_recipient), Some(U256::from(2_000_000_000u64)));
+        assert_eq!(hashed_balance(&tree, canonical_recipient), None);
+        assert_eq!(hashed_balance(&tree, side_recipient), Some(U256::from(2_000_000_000u64)));
+        assert_eq!(account_history(&tree, canonical_recipient), Vec::<usize>::new());
+        assert_eq!(account_history(&tree, side_recipient), vec![0, 1]);
+        assert_eq!(checkpoint(&tree), Some(2));
+
+        // the reverted block is kept as a side chain and can become canonical again
+        assert_eq!(tree.chain(canonical.hash()).unwrap().fork_block(), (0, genesis.hash()));
+        tree.make_canonical(canonical.hash()).unwrap();
+        assert_eq!(canonical_hash(&tree, 1), Some(canonical.hash()));
+        assert_eq!(canonical_hash(&tree, 2), None);
+        assert_eq!(balance(&tree, canonical_recipient), Some(U256::from(1_000_000_000u64)));
+        assert_eq!(balance(&tree, side_recipient), None);
+        assert_eq!(hashed_balance(&tree, side_recipient), None);
+        assert_eq!(account_history(&tree, canonical_recipient), vec![0]);
+        assert_eq!(account_history(&tree, side_recipient), Vec::<usize>::new());
+        assert_eq!(checkpoint(&tree), Some(1));
+        assert!(tree.contains(side1.hash()) && tree.contains(side2.hash()));
+    }
+
+    #[test]
+    fn reject_wrong_state_root() {
+        let (mut tree, genesis) = setup();
+        let recipient = Address::random();
+        let invalid = block(&genesis, recipient, H256::random());
+
+        assert_matches!(
+            tree.insert_block(invalid.clone()),
+            Err(BlockchainTreeError::Consensus(consensus::Error::BodyStateRootDiff { .. }))
+        );
+        assert!(!tree.contains(invalid.hash()));
+        assert_matches!(
+            tree.make_canonical(invalid.hash()),
+            Err(BlockchainTreeError::BlockUnknown(hash)) if hash == invalid.hash()
+        );
+        assert_eq!(canonical_hash(&tree, 1), None);
+        assert_eq!(balance(&tree, recipient), None);
+        assert_eq!(hashed_balance(&tree, recipient), None);
+        assert_eq!(checkpoint(&tree), Some(0));
+
+        // a valid block still extends the canonical chain
+        let valid = block(&genesis, recipient, state_root(&[(recipient, 1)]));
+        tree.insert_block(valid.clone()).unwrap();
+        tree.make_canonical(valid.hash()).unwrap();
+        assert_eq!(canonical_hash(&tree, 1), Some(valid.hash()));
+    }
+
+    #[test]
+    fn remove_chain_failing_to_commit() {
+        let (mut tree, genesis) = setup();
+        let recipient = Address::random();
+        let valid = block(&genesis, recipient, state_root(&[(recipient, 1)]));
+        tree.insert_block(valid.clone()).unwrap();
+
+        // a block that bypassed the checks of its insertion
+        let invalid = block(&valid.header, recipient, H256::random());
+        let result =
+            ExecutionResult { tx_changesets: Vec::new(), block_changesets: Default::default() };
+        let chain_id = tree.block_chains[&valid.hash()];
+        tree.chains.get_mut(&chain_id).unwrap().push(invalid.clone(), result);
+        tree.block_chains.insert(invalid.hash(), chain_id);
+
+        assert_matches!(
+            tree.make_canonical(invalid.hash()),
+            Err(BlockchainTreeError::Consensus(consensus::Error::BodyStateRootDiff { .. }))
+        );
+        assert!(!tree.contains(invalid.hash()) && !tree.contains(valid.hash()));
+        assert_eq!(canonical_hash(&tree, 1), None);
+        assert_eq!(checkpoint(&tree), Some(0));
+    }
+
+    #[test]
+    fn reject_unknown_parent() {
+        let (mut tree, genesis) = setup();
+        let mut orphan = block(&genesis, Address::random(), EMPTY_ROOT).unseal();
+        orphan.header.parent_hash = H256::random();
+        let orphan = orphan.seal_slow();
+
+        assert_matches!(
+            tree.insert_block(orphan.clone()),
+            Err(BlockchainTreeError::ParentUnknown { hash, .. }) if hash == orphan.hash()
+        );
+        assert_matches!(
+            tree.make_canonical(orphan.hash()),
+            Err(BlockchainTreeError::BlockUnknown(hash)) if hash == orphan.hash()
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Notifications of the changes of the canonical chain.
+
+use super::Chain;
+use reth_primitives::SealedBlock;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// The number of notifications a subscriber can lag behind before it misses notifications.
+pub const CANON_STATE_NOTIFICATION_CHANNEL_SIZE: usize = 256;
+
+/// Type alias for a receiver that receives [CanonStateNotification]s.
+pub type CanonStateNotifications = broadcast::Receiver<CanonStateNotification>;
+
+/// A type that allows to subscribe to the changes of the canonical chain.
+pub trait CanonStateSubscriptions {
+    /// Get notified when blocks are committed to or reverted from the canonical chain.
+    fn subscribe_to_canonical_state(&self) -> CanonStateNotifications;
+}
+
+/// A change of the canonical chain, with the execution results of the blocks.
+///
+/// The chains of a notification are consecutive: the reverted chain, if any, starts after the
+/// fork block of the committed chain, and the committed chain ends at the new canonical tip.
+#[derive(Debug, Clone)]
+pub enum CanonStateNotification {
+    /// The chain was extended by the blocks of `new`.
+    Commit {
+        /// The committed blocks.
+        new: Arc<Chain>,
+    },
+    /// The blocks of `old` were reverted and the blocks of `new` were committed on top of the
+    /// fork block.
+    Reorg {
+        /// The reverted blocks.
+        old: Arc<Chain>,
+        /// The committed blocks.
+        new: Arc<Chain>,
+    },
+}
+
+impl CanonStateNotification {
+    /// Returns the reverted blocks, if any.
+    pub fn reverted(&self) -> Option<Arc<Chain>> {
+        match self {
+            CanonStateNotification::Commit { .. } => None,
+            CanonStateNotification::Reorg { old, .. } => Some(Arc::clone(old)),
+        }
+    }
+
+    /// Returns the committed blocks.
+    pub fn committed(&self) -> Arc<Chain> {
+        match self {
+            CanonStateNotification::Commit { new } | CanonStateNotification::Reorg { new, .. } => {
+                Arc::clone(new)
+            }
+        }
+    }
+
+    /// Returns the new canonical tip.
+    pub fn tip(&self) -> &SealedBlock {
+        match self {
+            CanonStateNotification::Commit { new } | CanonStateNotification::Reorg { new, .. } => {
+                new.tip()
+            }
+        }
+    }
+}
+
+/// Sends [CanonStateNotification]s to all the receivers subscribed with
+/// [CanonStateSubscriptions::subscribe_to_canonical_state].
+///
+/// Clones share the same subscribers.
+#[derive(Clone, Debug)]
+pub struct CanonStateNotificationSender {
+    sender: broadcast::Sender<CanonStateNotification>,
+}
+
+impl CanonStateNotificationSender {
+    /// Sends the notification to all subscribers.
+    pub fn notify(&self, notification: CanonStateNotification) {
+        // there may be no subscribers
+        let _ = self.sender.send(notification);
+    }
+}
+
+impl Default for CanonStateNotificationSender {
+    fn default() -> Self {
+        Self { sender: broadcast::channel(CANON_STATE_NOTIFICATION_CHANNEL_SIZE).0 }
+    }
+}
+
+impl CanonStateSubscriptions for CanonStateNotificationSender {
+    fn subscribe_to_canonical_state(&self) -> CanonStateNotifications {
+        self.sender.subscribe()
+    }
+}
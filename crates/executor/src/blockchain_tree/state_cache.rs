@@ -0,0 +1,124 @@
+//! Invalidation of the [StateCache] by the changes of the canonical chain.
+
+use super::{CanonStateNotification, CanonStateNotifications, Chain};
+use crate::execution_result::AccountInfoChangeSet;
+use reth_primitives::{Address, StorageKey, H256};
+use reth_provider::StateCache;
+use std::collections::BTreeSet;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{trace, warn};
+
+/// Keeps the [StateCache] of the latest state consistent with the canonical chain, by evicting
+/// the accounts and storage slots the committed and reverted blocks of every notification changed.
+///
+/// If notifications are missed, the whole cache is cleared.
+///
+/// This runs until the sender of the `events` is dropped.
+pub async fn maintain_state_cache(cache: StateCache, mut events: CanonStateNotifications) {
+    loop {
+        let notification = match events.recv().await {
+            Ok(notification) => notification,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(target: "state_cache", skipped, "Missed canonical state notifications");
+                // the changes are not known, so nothing cached can be trusted
+                cache.clear();
+                continue
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        let mut changes = ChangedState::default();
+        if let Some(reverted) = notification.reverted() {
+            changes.extend(&reverted);
+        }
+        changes.extend(&notification.committed());
+        trace!(
+            target: "state_cache",
+            accounts = changes.accounts.len(),
+            storage = changes.storage.len(),
+            "Invalidating changed state"
+        );
+        cache.invalidate(changes.accounts, changes.storage, changes.wiped_storage);
+    }
+}
+
+/// The accounts and storage slots changed by the blocks of a [CanonStateNotification].
+#[derive(Debug, Default)]
+struct ChangedState {
+    accounts: BTreeSet<Address>,
+    storage: BTreeSet<(Address, StorageKey)>,
+    wiped_storage: BTreeSet<Address>,
+}
+
+impl ChangedState {
+    /// Add the changes of the blocks of the chain.
+    fn extend(&mut self, chain: &Chain) {
+        let changes = chain.results().iter().flat_map(|result| {
+            result
+                .tx_changesets
+                .iter()
+                .flat_map(|tx| tx.changeset.iter())
+                .chain(&result.block_changesets)
+        });
+        for (address, change) in changes {
+            match change.account {
+                AccountInfoChangeSet::NoChange => {}
+                AccountInfoChangeSet::Destroyed { .. } => {
+                    self.accounts.insert(*address);
+                    self.wiped_storage.insert(*address);
+                }
+                AccountInfoChangeSet::Created { .. } | AccountInfoChangeSet::Changed { .. } => {
+                    self.accounts.insert(*address);
+                }
+            }
+            if change.wipe_storage {
+                self.wiped_storage.insert(*address);
+            }
+            self.storage
+                .extend(change.storage.keys().map(|key| (*address, H256(key.to_be_bytes()))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution_result::{AccountChangeSet, ExecutionResult};
+    use reth_primitives::{Account, Header, SealedBlock, U256};
+    use std::{collections::BTreeMap, sync::Arc};
+
+    #[test]
+    fn changed_state_of_reorg() {
+        let (changed, wiped, other) =
+            (Address::from_low_u64_be(1), Address::from_low_u64_be(2), Address::from_low_u64_be(3));
+        let chain = |changes: BTreeMap<Address, AccountChangeSet>| {
+            let block = SealedBlock {
+                header: Header { number: 2, ..Default::default() }.seal_slow(),
+                ..Default::default()
+            };
+            let result = ExecutionResult { tx_changesets: vec![], block_changesets: changes };
+            Arc::new(Chain::new((1, H256::zero()), block, result))
+        };
+
+        let old = chain(BTreeMap::from([(
+            changed,
+            AccountChangeSet {
+                account: AccountInfoChangeSet::Created { new: Account::default() },
+                storage: BTreeMap::from([(U256::from(1), (U256::ZERO, U256::from(1)))]),
+                ..Default::default()
+            },
+        )]));
+        let new = chain(BTreeMap::from([
+            (wiped, AccountChangeSet { wipe_storage: true, ..Default::default() }),
+            (other, AccountChangeSet::default()),
+        ]));
+        let notification = CanonStateNotification::Reorg { old, new };
+
+        let mut changes = ChangedState::default();
+        changes.extend(&notification.reverted().unwrap());
+        changes.extend(&notification.committed());
+        assert_eq!(changes.accounts, BTreeSet::from([changed]));
+        assert_eq!(changes.storage, BTreeSet::from([(changed, H256::from_low_u64_be(1))]));
+        assert_eq!(changes.wiped_storage, BTreeSet::from([wiped]));
+    }
+}
@@ -0,0 +1,14 @@
+//! Beacon roots contract constants from [EIP-4788](https://eips.ethereum.org/EIPS/eip-4788).
+//! It is called before the transactions of every block after Cancun.
+use reth_primitives::{hex_literal::hex, H160};
+
+/// The address the beacon roots contract is called from, which is not charged for the call.
+pub static SYSTEM_ADDRESS: H160 = H160(hex!("fffffffffffffffffffffffffffffffffffffffe"));
+
+/// The address of the beacon roots contract, which stores the parent beacon block root of every
+/// block.
+pub static BEACON_ROOTS_ADDRESS: H160 = H160(hex!("000f3df6d732807ef1319fb7b8bb8522d0beac02"));
+
+/// The gas limit of the call to the beacon roots contract, which does not count towards the gas
+/// used by the block.
+pub const BEACON_ROOTS_CALL_GAS_LIMIT: u64 = 30_000_000;
@@ -1,17 +1,98 @@
-use reth_db::{models::AccountBeforeTx, tables, transaction::DbTxMut, Error as DbError};
-use reth_primitives::{Account, Address, Receipt, H256, U256};
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    models::{AccountBeforeTx, TransitionIdAddress},
+    tables,
+    transaction::{DbTx, DbTxMut},
+    Error as DbError,
+};
+use reth_primitives::{Account, Address, Bytes, Receipt, StorageEntry, TransitionId, H256, U256};
+use reth_provider::StateOverlay;
 use revm::primitives::Bytecode;
 use std::collections::BTreeMap;
+use tracing::trace;
 
 /// Execution Result containing vector of transaction changesets
 /// and block reward if present
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExecutionResult {
     /// Transaction changeset containing [Receipt], changed [Accounts][Account] and Storages.
+    ///
+    /// The changes made before the transactions of the block, by system calls or irregular state
+    /// changes (DAO fork), are part of the changeset of the first transaction.
     pub tx_changesets: Vec<TransactionChangeSet>,
-    /// Post block account changesets. This might include block reward, uncle rewards, withdrawals
-    /// or irregular state changes (DAO fork).
-    pub block_changesets: BTreeMap<Address, AccountInfoChangeSet>,
+    /// Post block account changesets. This might include block reward, uncle rewards or
+    /// withdrawals, and the pre block changes if the block has no transactions.
+    pub block_changesets: BTreeMap<Address, AccountChangeSet>,
+}
+
+impl ExecutionResult {
+    /// Returns the gas used by the transactions of the block.
+    pub fn gas_used(&self) -> u64 {
+        self.tx_changesets.last().map_or(0, |tx| tx.receipt.cumulative_gas_used)
+    }
+
+    /// Apply the changes of the block to the plain state of the database, and record the changed
+    /// accounts and storage slots in the changesets.
+    ///
+    /// The changes of the transactions are recorded starting at `first_transition_id`, one
+    /// transition per transaction, and the post block changes at the transition after the last
+    /// transaction.
+    pub fn write_to_db<'a, TX: DbTxMut<'a> + DbTx<'a>>(
+        self,
+        tx: &TX,
+        first_transition_id: TransitionId,
+        has_state_clear_eip: bool,
+    ) -> Result<(), DbError> {
+        let mut current_transition_id = first_transition_id;
+
+        // insert state change set
+        for result in self.tx_changesets.into_iter() {
+            for (address, account_change_set) in result.changeset.into_iter() {
+                account_change_set.apply_to_db(
+                    tx,
+                    address,
+                    current_transition_id,
+                    has_state_clear_eip,
+                )?;
+            }
+            // insert bytecode
+            for (hash, bytecode) in result.new_bytecodes.into_iter() {
+                // make different types of bytecode. Checked and maybe even analyzed (needs to
+                // be packed). Currently save only raw bytes.
+                let bytecode = bytecode.bytes();
+                trace!(target: "executor", ?hash, ?bytecode, len = bytecode.len(), "Inserting bytecode");
+                tx.put::<tables::Bytecodes>(hash, bytecode[..bytecode.len()].to_vec())?;
+
+                // NOTE: bytecode bytes are not inserted in change set and can be found in
+                // separate table
+            }
+            current_transition_id += 1;
+        }
+
+        // If there are any post block changes, we will add account changesets to db.
+        for (address, changeset) in self.block_changesets.into_iter() {
+            trace!(target: "executor", ?address, current_transition_id, "Applying block reward");
+            changeset.apply_to_db(tx, address, current_transition_id, has_state_clear_eip)?;
+        }
+        Ok(())
+    }
+
+    /// Apply the changes of the block to a [StateOverlay], to read the state after the block
+    /// without committing it to the database.
+    pub fn apply_to_overlay(&self, overlay: &mut StateOverlay, has_state_clear_eip: bool) {
+        for tx_changeset in &self.tx_changesets {
+            for (address, changeset) in &tx_changeset.changeset {
+                changeset.apply_to_overlay(overlay, *address, has_state_clear_eip);
+            }
+            for (hash, bytecode) in &tx_changeset.new_bytecodes {
+                overlay.insert_bytecode(*hash, Bytes::from(bytecode.bytes().clone()));
+            }
+        }
+
+        for (address, changeset) in &self.block_changesets {
+            changeset.apply_to_overlay(overlay, *address, has_state_clear_eip);
+        }
+    }
 }
 
 /// After transaction is executed this structure contain
@@ -29,7 +110,7 @@ pub struct TransactionChangeSet {
 }
 
 /// Contains old/new account changes
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub enum AccountInfoChangeSet {
     /// The account is newly created.
     Created {
@@ -51,10 +132,36 @@ pub enum AccountInfoChangeSet {
         old: Account,
     },
     /// Nothing was changed for the account (nonce/balance).
+    #[default]
     NoChange,
 }
 
 impl AccountInfoChangeSet {
+    /// Combines this change with a `later` change of the same account into one change, from the
+    /// account before this change to the account after the later one.
+    pub fn merge(self, later: AccountInfoChangeSet) -> AccountInfoChangeSet {
+        let old = match self {
+            AccountInfoChangeSet::NoChange => return later,
+            AccountInfoChangeSet::Created { .. } => None,
+            AccountInfoChangeSet::Destroyed { old } | AccountInfoChangeSet::Changed { old, .. } => {
+                Some(old)
+            }
+        };
+        let new = match later {
+            AccountInfoChangeSet::NoChange => return self,
+            AccountInfoChangeSet::Destroyed { .. } => None,
+            AccountInfoChangeSet::Created { new } | AccountInfoChangeSet::Changed { new, .. } => {
+                Some(new)
+            }
+        };
+        match (old, new) {
+            (None, Some(new)) => AccountInfoChangeSet::Created { new },
+            (Some(old), None) => AccountInfoChangeSet::Destroyed { old },
+            (Some(old), Some(new)) if old != new => AccountInfoChangeSet::Changed { new, old },
+            _ => AccountInfoChangeSet::NoChange,
+        }
+    }
+
     /// Apply the changes from the changeset to a database transaction.
     pub fn apply_to_db<'a, TX: DbTxMut<'a>>(
         self,
@@ -98,10 +205,32 @@ impl AccountInfoChangeSet {
         }
         Ok(())
     }
+
+    /// Apply the changes from the changeset to a [StateOverlay].
+    pub fn apply_to_overlay(
+        &self,
+        overlay: &mut StateOverlay,
+        address: Address,
+        has_state_clear_eip: bool,
+    ) {
+        match self {
+            AccountInfoChangeSet::Changed { new, .. } => {
+                overlay.insert_account(address, Some(*new))
+            }
+            AccountInfoChangeSet::Created { new } => {
+                // Same as in the database, empty created accounts are ignored after SpuriousDragon.
+                if !(has_state_clear_eip && new.is_empty()) {
+                    overlay.insert_account(address, Some(*new));
+                }
+            }
+            AccountInfoChangeSet::Destroyed { .. } => overlay.insert_account(address, None),
+            AccountInfoChangeSet::NoChange => {}
+        }
+    }
 }
 
 /// Diff change set that is needed for creating history index and updating current world state.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct AccountChangeSet {
     /// Old and New account account change.
     pub account: AccountInfoChangeSet,
@@ -114,6 +243,112 @@ pub struct AccountChangeSet {
     pub wipe_storage: bool,
 }
 
+impl AccountChangeSet {
+    /// Combines this change with a `later` change of the same account into one change.
+    ///
+    /// The storage slots keep their value before this change and get their value after the later
+    /// one.
+    pub fn merge(mut self, later: AccountChangeSet) -> AccountChangeSet {
+        for (key, (old_value, new_value)) in later.storage {
+            self.storage
+                .entry(key)
+                .and_modify(|(_, value)| *value = new_value)
+                .or_insert((old_value, new_value));
+        }
+        AccountChangeSet {
+            account: self.account.merge(later.account),
+            storage: self.storage,
+            wipe_storage: self.wipe_storage || later.wipe_storage,
+        }
+    }
+
+    /// Apply the changes of the account and its storage to a database transaction.
+    pub fn apply_to_db<'a, TX: DbTxMut<'a> + DbTx<'a>>(
+        self,
+        tx: &TX,
+        address: Address,
+        transition_id: TransitionId,
+        has_state_clear_eip: bool,
+    ) -> Result<(), DbError> {
+        let AccountChangeSet { account, wipe_storage, storage } = self;
+        // apply account change to db. Updates AccountChangeSet and PlainAccountState
+        // tables.
+        trace!(target: "executor", ?address, transition_id, ?account, wipe_storage, "Applying account changeset");
+        account.apply_to_db(tx, address, transition_id, has_state_clear_eip)?;
+
+        let storage_id = TransitionIdAddress((transition_id, address));
+
+        // cast key to H256 and trace the change
+        let storage = storage
+            .into_iter()
+            .map(|(key, (old_value, new_value))| {
+                let hkey = H256(key.to_be_bytes());
+                trace!(target: "executor", ?address, transition_id, ?hkey, ?old_value, ?new_value, "Applying storage changeset");
+                (hkey, old_value, new_value)
+            })
+            .collect::<Vec<_>>();
+
+        let mut cursor_storage_changeset = tx.cursor_write::<tables::StorageChangeSet>()?;
+        cursor_storage_changeset.seek_exact(storage_id)?;
+
+        if wipe_storage {
+            // iterate over storage and save them before entry is deleted.
+            tx.cursor_read::<tables::PlainStorageState>()?
+                .walk(Some(address))?
+                .take_while(|res| res.as_ref().map(|(k, _)| *k == address).unwrap_or_default())
+                .try_for_each(|entry| {
+                    let (_, old_value) = entry?;
+                    cursor_storage_changeset.append(storage_id, old_value)
+                })?;
+
+            // delete all entries
+            tx.delete::<tables::PlainStorageState>(address, None)?;
+
+            // insert storage changeset
+            for (key, _, new_value) in storage {
+                // old values are already cleared.
+                if new_value != U256::ZERO {
+                    tx.put::<tables::PlainStorageState>(
+                        address,
+                        StorageEntry { key, value: new_value },
+                    )?;
+                }
+            }
+        } else {
+            // insert storage changeset
+            for (key, old_value, new_value) in storage {
+                let old_entry = StorageEntry { key, value: old_value };
+                let new_entry = StorageEntry { key, value: new_value };
+                // insert into StorageChangeSet
+                cursor_storage_changeset.append(storage_id, old_entry)?;
+
+                // Always delete old value as duplicate table, put will not override it
+                tx.delete::<tables::PlainStorageState>(address, Some(old_entry))?;
+                if new_value != U256::ZERO {
+                    tx.put::<tables::PlainStorageState>(address, new_entry)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the changes of the account and its storage to a [StateOverlay].
+    pub fn apply_to_overlay(
+        &self,
+        overlay: &mut StateOverlay,
+        address: Address,
+        has_state_clear_eip: bool,
+    ) {
+        self.account.apply_to_overlay(overlay, address, has_state_clear_eip);
+        if self.wipe_storage {
+            overlay.wipe_storage(address);
+        }
+        for (key, (_, new_value)) in &self.storage {
+            overlay.insert_storage(address, H256(key.to_be_bytes()), *new_value);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -127,6 +362,63 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn merge_account_changesets() {
+        let acc1 = Account { balance: U256::from(1), ..Default::default() };
+        let acc2 = Account { balance: U256::from(2), ..Default::default() };
+        let acc3 = Account { balance: U256::from(3), ..Default::default() };
+
+        let created = AccountInfoChangeSet::Created { new: acc1 };
+        let changed = AccountInfoChangeSet::Changed { new: acc2, old: acc1 };
+        assert_eq!(
+            created.clone().merge(changed.clone()),
+            AccountInfoChangeSet::Created { new: acc2 }
+        );
+        assert_eq!(
+            changed.clone().merge(AccountInfoChangeSet::Changed { new: acc3, old: acc2 }),
+            AccountInfoChangeSet::Changed { new: acc3, old: acc1 }
+        );
+        assert_eq!(
+            changed.clone().merge(AccountInfoChangeSet::Changed { new: acc1, old: acc2 }),
+            AccountInfoChangeSet::NoChange
+        );
+        assert_eq!(
+            changed.clone().merge(AccountInfoChangeSet::Destroyed { old: acc2 }),
+            AccountInfoChangeSet::Destroyed { old: acc1 }
+        );
+        assert_eq!(
+            created.merge(AccountInfoChangeSet::Destroyed { old: acc1 }),
+            AccountInfoChangeSet::NoChange
+        );
+        assert_eq!(AccountInfoChangeSet::NoChange.merge(changed.clone()), changed);
+        assert_eq!(changed.clone().merge(AccountInfoChangeSet::NoChange), changed);
+
+        let earlier = AccountChangeSet {
+            account: AccountInfoChangeSet::NoChange,
+            storage: BTreeMap::from([(U256::from(1), (U256::ZERO, U256::from(1)))]),
+            wipe_storage: false,
+        };
+        let later = AccountChangeSet {
+            account: changed.clone(),
+            storage: BTreeMap::from([
+                (U256::from(1), (U256::from(1), U256::from(2))),
+                (U256::from(2), (U256::from(3), U256::from(4))),
+            ]),
+            wipe_storage: false,
+        };
+        assert_eq!(
+            earlier.merge(later),
+            AccountChangeSet {
+                account: changed,
+                storage: BTreeMap::from([
+                    (U256::from(1), (U256::ZERO, U256::from(2))),
+                    (U256::from(2), (U256::from(3), U256::from(4))),
+                ]),
+                wipe_storage: false,
+            }
+        );
+    }
+
     #[test]
     fn apply_account_info_changeset() {
         let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
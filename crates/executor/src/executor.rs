@@ -1,15 +1,20 @@
 use crate::{
     config::{revm_spec, WEI_2ETH, WEI_3ETH, WEI_5ETH},
+    eip4788::{BEACON_ROOTS_ADDRESS, BEACON_ROOTS_CALL_GAS_LIMIT, SYSTEM_ADDRESS},
     execution_result::{
         AccountChangeSet, AccountInfoChangeSet, ExecutionResult, TransactionChangeSet,
     },
     revm_wrap::{self, into_reth_log, to_reth_acc, SubState},
 };
 use hashbrown::hash_map::Entry;
-use reth_interfaces::executor::{BlockExecutor, Error};
+use reth_interfaces::{
+    consensus::Error as ConsensusError,
+    executor::{BlockExecutor, Error},
+};
 use reth_primitives::{
-    bloom::logs_bloom, Account, Address, Block, Bloom, ChainSpec, Hardfork, Head, Header, Log,
-    Receipt, TransactionSigned, H256, U256,
+    bloom::{logs_bloom, receipts_bloom},
+    Account, Address, Block, Bloom, ChainSpec, Hardfork, Head, Header, Log, Receipt,
+    TransactionSigned, H256, KECCAK_EMPTY, U256,
 };
 use reth_provider::StateProvider;
 use revm::{
@@ -59,7 +64,7 @@ where
                 Err(Error::SenderRecoveryError)
             }
         } else {
-            body.iter().map(|tx| tx.recover_signer().ok_or(Error::SenderRecoveryError)).collect()
+            TransactionSigned::recover_signers(body).ok_or(Error::SenderRecoveryError)
         }
     }
 
@@ -205,8 +210,71 @@ where
         (change, new_bytecodes)
     }
 
+    /// Apply the changes made before the transactions of the block: the irregular state change
+    /// of the DAO fork, and the call to the beacon roots contract after Cancun.
+    fn apply_pre_block_changes(
+        &mut self,
+        header: &Header,
+    ) -> Result<BTreeMap<Address, AccountChangeSet>, Error> {
+        let mut changes = BTreeMap::new();
+
+        if self.chain_spec.fork(Hardfork::Dao).transitions_at_block(header.number) {
+            for (address, account) in self.dao_fork_changeset()? {
+                changes.insert(address, AccountChangeSet { account, ..Default::default() });
+            }
+        }
+
+        if self.chain_spec.fork(Hardfork::Cancun).active_at_timestamp(header.timestamp) {
+            let beacon_root_changes = self.apply_beacon_root_contract_call(header)?;
+            changes = merge_changes(changes, beacon_root_changes);
+        }
+
+        Ok(changes)
+    }
+
+    /// EIP-4788: store the parent beacon block root of the block in the beacon roots contract, by
+    /// calling it from the system address.
+    ///
+    /// The call is skipped if the contract is not deployed. Only the storage of the contract is
+    /// changed, the call pays no gas and does not count towards the gas used by the block.
+    fn apply_beacon_root_contract_call(
+        &mut self,
+        header: &Header,
+    ) -> Result<BTreeMap<Address, AccountChangeSet>, Error> {
+        let parent_beacon_block_root =
+            header.parent_beacon_block_root.ok_or(Error::ParentBeaconBlockRootMissing)?;
+
+        let contract =
+            self.db().load_account(BEACON_ROOTS_ADDRESS).map_err(|_| Error::ProviderError)?;
+        if contract.info.code_hash == KECCAK_EMPTY {
+            return Ok(BTreeMap::new())
+        }
+
+        revm_wrap::fill_tx_env_with_system_call(
+            &mut self.evm.env.tx,
+            SYSTEM_ADDRESS,
+            BEACON_ROOTS_ADDRESS,
+            BEACON_ROOTS_CALL_GAS_LIMIT,
+            parent_beacon_block_root.as_bytes().to_vec().into(),
+        );
+
+        // The call is neither limited by the base fee nor by the gas limit of the block.
+        let block_env = self.evm.env.block.clone();
+        self.evm.env.block.basefee = U256::ZERO;
+        self.evm.env.block.gas_limit = U256::from(u64::MAX);
+        let out = self.evm.transact();
+        self.evm.env.block = block_env;
+
+        let ResultAndState { mut state, .. } = out.map_err(|e| Error::EVM(format!("{e:?}")))?;
+        // The system address and the beneficiary are touched by the call, but are not changed.
+        state.retain(|address, _| *address == BEACON_ROOTS_ADDRESS);
+
+        let (changeset, _) = self.commit_changes(state);
+        Ok(changeset)
+    }
+
     /// Collect all balance changes at the end of the block. Balance changes might include block
-    /// reward, uncle rewards, withdrawals or irregular state changes (DAO fork).
+    /// reward, uncle rewards or withdrawals.
     fn post_block_balance_increments(
         &mut self,
         block: &Block,
@@ -342,6 +410,42 @@ where
             }
         }
     }
+
+    /// Returns the blob fee paid by the transaction, which is zero for all transactions but
+    /// eip4844 ones.
+    ///
+    /// revm does not know about blob gas, so the fee cap is checked here, as well as whether the
+    /// sender can pay for its max cost, blob gas included.
+    fn blob_gas_fee(
+        &mut self,
+        header: &Header,
+        transaction: &TransactionSigned,
+        sender: Address,
+    ) -> Result<U256, Error> {
+        let Some(max_fee_per_blob_gas) = transaction.max_fee_per_blob_gas() else {
+            return Ok(U256::ZERO)
+        };
+        let blob_fee = header.blob_fee().ok_or(Error::BlobFeeMissing)?;
+        if max_fee_per_blob_gas < blob_fee {
+            return Err(Error::TransactionMaxFeePerBlobGasLessThenBlobFee {
+                max_fee_per_blob_gas,
+                blob_fee,
+            })
+        }
+
+        let blob_gas = U256::from(transaction.blob_gas());
+        let max_cost = U256::from(transaction.gas_limit()) *
+            U256::from(transaction.max_fee_per_gas()) +
+            U256::from(*transaction.value()) +
+            blob_gas * U256::from(max_fee_per_blob_gas);
+        let available_funds =
+            self.db().load_account(sender).map_err(|_| Error::ProviderError)?.info.balance;
+        if available_funds < max_cost {
+            return Err(Error::InsufficientFundsForBlobTransaction { max_cost, available_funds })
+        }
+
+        Ok(blob_gas * U256::from(blob_fee))
+    }
 }
 
 impl<'a, DB> BlockExecutor<ExecutionResult> for Executor<'a, DB>
@@ -358,6 +462,10 @@ where
 
         self.init_block_env(&block.header, total_difficulty);
 
+        // The changes before the transactions are recorded with the first transaction, or with
+        // the post block changes if there are no transactions.
+        let mut pre_block_changes = self.apply_pre_block_changes(&block.header)?;
+
         let mut cumulative_gas_used = 0;
         // output of execution
         let mut tx_changesets = Vec::with_capacity(block.body.len());
@@ -373,6 +481,8 @@ where
                 })
             }
 
+            let blob_gas_fee = self.blob_gas_fee(&block.header, transaction, sender)?;
+
             // Fill revm structure.
             revm_wrap::fill_tx_env(&mut self.evm.env.tx, transaction, sender);
 
@@ -392,10 +502,23 @@ where
             };
 
             // cast the error and extract returnables.
-            let ResultAndState { result, state } = out.map_err(|e| Error::EVM(format!("{e:?}")))?;
+            let ResultAndState { result, mut state } =
+                out.map_err(|e| Error::EVM(format!("{e:?}")))?;
+
+            // EIP-4844: the blob fee is burned, it is taken from the sender before committing.
+            if blob_gas_fee > U256::ZERO {
+                let sender = state.get_mut(&sender).ok_or(Error::ExecutionFatalError)?;
+                let balance = sender.info.balance;
+                sender.info.balance = balance
+                    .checked_sub(blob_gas_fee)
+                    .ok_or(Error::InsufficientFundsForBlobFee { blob_gas_fee, balance })?;
+            }
 
             // commit changes
-            let (changeset, new_bytecodes) = self.commit_changes(state);
+            let (mut changeset, new_bytecodes) = self.commit_changes(state);
+            if tx_changesets.is_empty() {
+                changeset = merge_changes(std::mem::take(&mut pre_block_changes), changeset);
+            }
 
             // append gas used
             cumulative_gas_used += result.gas_used();
@@ -419,30 +542,52 @@ where
             });
         }
 
-        // Check if gas used matches the value set in header.
-        if block.gas_used != cumulative_gas_used {
-            return Err(Error::BlockGasUsed { got: cumulative_gas_used, expected: block.gas_used })
-        }
-
-        let mut block_changesets = BTreeMap::default();
+        let mut post_block_changes = BTreeMap::default();
         let balance_increments = self.post_block_balance_increments(block, total_difficulty)?;
         for (address, increment) in balance_increments {
-            let changeset = self.account_balance_increment_changeset(address, increment)?;
-            block_changesets.insert(address, changeset);
-        }
-
-        if self.chain_spec.fork(Hardfork::Dao).transitions_at_block(block.number) {
-            for (address, changeset) in self.dao_fork_changeset()? {
-                // No account collision between rewarded accounts and DAO fork related accounts.
-                block_changesets.insert(address, changeset);
-            }
+            let account = self.account_balance_increment_changeset(address, increment)?;
+            post_block_changes.insert(address, AccountChangeSet { account, ..Default::default() });
         }
+        let block_changesets = merge_changes(pre_block_changes, post_block_changes);
 
         Ok(ExecutionResult { tx_changesets, block_changesets })
     }
 }
 
+/// Merge the `later` changes into the `earlier` changes, as if they were made in one transition.
+fn merge_changes(
+    mut earlier: BTreeMap<Address, AccountChangeSet>,
+    later: BTreeMap<Address, AccountChangeSet>,
+) -> BTreeMap<Address, AccountChangeSet> {
+    for (address, change) in later {
+        let change = match earlier.remove(&address) {
+            Some(earlier_change) => earlier_change.merge(change),
+            None => change,
+        };
+        earlier.insert(address, change);
+    }
+    earlier
+}
+
+/// Apply the EIP-4788 call to the beacon roots contract of the block with the given header to the
+/// state, as done before the transactions of every block after Cancun.
+///
+/// Returns the changes of the call, which are empty if the contract is not deployed.
+pub fn apply_beacon_root_contract_call<DB: StateProvider>(
+    header: &Header,
+    total_difficulty: U256,
+    chain_spec: &ChainSpec,
+    db: &mut SubState<DB>,
+) -> Result<BTreeMap<Address, AccountChangeSet>, Error> {
+    let mut executor = Executor::new(chain_spec, db);
+    executor.init_block_env(header, total_difficulty);
+    executor.apply_beacon_root_contract_call(header)
+}
+
 /// Execute and verify block
+///
+/// The state root of the block is not verified, it is computed separately from the execution and
+/// verified with [verify_state_root].
 pub fn execute_and_verify_receipt<DB: StateProvider>(
     block: &Block,
     total_difficulty: U256,
@@ -451,19 +596,46 @@ pub fn execute_and_verify_receipt<DB: StateProvider>(
     db: &mut SubState<DB>,
 ) -> Result<ExecutionResult, Error> {
     let execution_result = execute(block, total_difficulty, senders, chain_spec, db)?;
+    verify_execution_result(&block.header, &execution_result, chain_spec)?;
+    Ok(execution_result)
+}
 
+/// Verify the result of the execution of the block against its header: the gas used by the
+/// transactions and, after Byzantium, the receipts root and the logs bloom.
+pub fn verify_execution_result(
+    header: &Header,
+    execution_result: &ExecutionResult,
+    chain_spec: &ChainSpec,
+) -> Result<(), ConsensusError> {
     let receipts_iter = execution_result.tx_changesets.iter().map(|changeset| &changeset.receipt);
 
-    if chain_spec.fork(Hardfork::Byzantium).active_at_block(block.header.number) {
-        verify_receipt(block.header.receipts_root, block.header.logs_bloom, receipts_iter)?;
+    // Check if gas used matches the value set in header.
+    let gas_used = execution_result.gas_used();
+    if header.gas_used != gas_used {
+        let mut previous_cumulative_gas_used = 0;
+        let gas_spent_by_tx = receipts_iter
+            .enumerate()
+            .map(|(index, receipt)| {
+                let gas_spent = receipt.cumulative_gas_used - previous_cumulative_gas_used;
+                previous_cumulative_gas_used = receipt.cumulative_gas_used;
+                (index as u64, gas_spent)
+            })
+            .collect();
+        return Err(ConsensusError::BlockGasUsed {
+            got: gas_used,
+            expected: header.gas_used,
+            gas_spent_by_tx,
+        })
     }
 
-    // TODO Before Byzantium, receipts contained state root that would mean that expensive operation
-    // as hashing that is needed for state root got calculated in every transaction
-    // This was replaced with is_success flag.
+    // Before Byzantium, receipts contained the state root after each transaction instead of the
+    // success flag, which would require to hash the state after every transaction.
     // See more about EIP here: https://eips.ethereum.org/EIPS/eip-658
+    if chain_spec.fork(Hardfork::Byzantium).active_at_block(header.number) {
+        verify_receipt(header.receipts_root, header.logs_bloom, receipts_iter)?;
+    }
 
-    Ok(execution_result)
+    Ok(())
 }
 
 /// Verify receipts
@@ -471,17 +643,20 @@ pub fn verify_receipt<'a>(
     expected_receipts_root: H256,
     expected_logs_bloom: Bloom,
     receipts: impl Iterator<Item = &'a Receipt> + Clone,
-) -> Result<(), Error> {
+) -> Result<(), ConsensusError> {
     // Check receipts root.
     let receipts_root = reth_primitives::proofs::calculate_receipt_root(receipts.clone());
     if receipts_root != expected_receipts_root {
-        return Err(Error::ReceiptRootDiff { got: receipts_root, expected: expected_receipts_root })
+        return Err(ConsensusError::BodyReceiptsRootDiff {
+            got: receipts_root,
+            expected: expected_receipts_root,
+        })
     }
 
     // Create header log bloom.
-    let logs_bloom = receipts.fold(Bloom::zero(), |bloom, r| bloom | r.bloom);
+    let logs_bloom = receipts_bloom(receipts.map(|receipt| &receipt.bloom));
     if logs_bloom != expected_logs_bloom {
-        return Err(Error::BloomLogDiff {
+        return Err(ConsensusError::BodyBloomLogDiff {
             expected: Box::new(expected_logs_bloom),
             got: Box::new(logs_bloom),
         })
@@ -489,6 +664,20 @@ pub fn verify_receipt<'a>(
     Ok(())
 }
 
+/// Verify the state root after the execution of the block against its header.
+///
+/// Computing the state root is expensive, so its verification can be deferred and done for many
+/// blocks at once, e.g. by the merkle stage during sync.
+pub fn verify_state_root(header: &Header, state_root: H256) -> Result<(), ConsensusError> {
+    if header.state_root != state_root {
+        return Err(ConsensusError::BodyStateRootDiff {
+            got: state_root,
+            expected: header.state_root,
+        })
+    }
+    Ok(())
+}
+
 /// Verify block. Execute all transaction and compare results.
 /// Returns ChangeSet on transaction granularity.
 /// NOTE: If block reward is still active (Before Paris/Merge) we would return
@@ -691,7 +880,10 @@ mod tests {
 
         // check block reward changeset
         assert_eq!(
-            out.block_changesets,
+            out.block_changesets
+                .into_iter()
+                .map(|(address, change)| (address, change.account))
+                .collect::<BTreeMap<_, _>>(),
             BTreeMap::from([
                 (
                     account2,
@@ -725,6 +917,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn post_execution_validation() {
+        let tx_changesets = [21_000, 50_000]
+            .into_iter()
+            .map(|cumulative_gas_used| TransactionChangeSet {
+                receipt: Receipt { success: true, cumulative_gas_used, ..Default::default() },
+                changeset: BTreeMap::new(),
+                new_bytecodes: BTreeMap::new(),
+            })
+            .collect();
+        let result = ExecutionResult { tx_changesets, block_changesets: BTreeMap::new() };
+        let receipts = result.tx_changesets.iter().map(|changeset| &changeset.receipt);
+        let header = Header {
+            gas_used: 50_000,
+            receipts_root: reth_primitives::proofs::calculate_receipt_root(receipts.clone()),
+            logs_bloom: receipts_bloom(receipts.map(|receipt| &receipt.bloom)),
+            ..Header::default()
+        };
+        let chain_spec = ChainSpecBuilder::mainnet().berlin_activated().build();
+        assert_eq!(verify_execution_result(&header, &result, &chain_spec), Ok(()));
+
+        let header_gas_used = Header { gas_used: 40_000, ..header.clone() };
+        assert_eq!(
+            verify_execution_result(&header_gas_used, &result, &chain_spec),
+            Err(ConsensusError::BlockGasUsed {
+                got: 50_000,
+                expected: 40_000,
+                gas_spent_by_tx: vec![(0, 21_000), (1, 29_000)]
+            })
+        );
+
+        let header_receipts_root = Header { receipts_root: H256::zero(), ..header.clone() };
+        assert!(matches!(
+            verify_execution_result(&header_receipts_root, &result, &chain_spec),
+            Err(ConsensusError::BodyReceiptsRootDiff { expected, .. }) if expected.is_zero()
+        ));
+
+        let header_logs_bloom = Header { logs_bloom: Bloom::from_low_u64_be(1), ..header.clone() };
+        assert!(matches!(
+            verify_execution_result(&header_logs_bloom, &result, &chain_spec),
+            Err(ConsensusError::BodyBloomLogDiff { .. })
+        ));
+
+        // The state root is verified on its own
+        assert_eq!(verify_state_root(&header, header.state_root), Ok(()));
+        assert_eq!(
+            verify_state_root(&header, H256::zero()),
+            Err(ConsensusError::BodyStateRootDiff {
+                got: H256::zero(),
+                expected: header.state_root
+            })
+        );
+    }
+
     #[test]
     fn dao_hardfork_irregular_state_change() {
         let header = Header { number: 1, ..Header::default() };
@@ -774,7 +1020,7 @@ mod tests {
         let change_set =
             out.block_changesets.get(&crate::eth_dao_fork::DAO_HARDFORK_BENEFICIARY).unwrap();
         assert_eq!(
-            *change_set,
+            change_set.account,
             AccountInfoChangeSet::Changed {
                 new: Account { balance: U256::from(beneficiary_balance), ..Default::default() },
                 old: Account { balance: U256::ZERO, ..Default::default() }
@@ -783,7 +1029,7 @@ mod tests {
         for (i, address) in crate::eth_dao_fork::DAO_HARDKFORK_ACCOUNTS.iter().enumerate() {
             let change_set = out.block_changesets.get(address).unwrap();
             assert_eq!(
-                *change_set,
+                change_set.account,
                 AccountInfoChangeSet::Changed {
                     new: Account { balance: U256::ZERO, ..Default::default() },
                     old: Account { balance: U256::from(i), ..Default::default() }
@@ -872,6 +1118,54 @@ mod tests {
         assert!(selfdestroyer_changeset.wipe_storage);
     }
 
+    #[test]
+    fn beacon_root_contract_call() {
+        let parent_beacon_block_root = H256::from_low_u64_be(0x4788);
+        let header = Header {
+            number: 1,
+            timestamp: 1,
+            base_fee_per_gas: Some(7),
+            parent_beacon_block_root: Some(parent_beacon_block_root),
+            ..Header::default()
+        };
+        let block = Block { header, body: vec![], ommers: vec![], withdrawals: Some(vec![]) };
+
+        // The contract stores the first word of the calldata in the first slot
+        let mut db = StateProviderTest::default();
+        db.insert_account(
+            BEACON_ROOTS_ADDRESS,
+            Account::default(),
+            Some(hex!("60003560005500").into()),
+            HashMap::new(),
+        );
+
+        let chain_spec = ChainSpecBuilder::mainnet().cancun_activated().build();
+
+        let mut db = SubState::new(State::new(db));
+        let out =
+            execute_and_verify_receipt(&block, U256::ZERO, None, &chain_spec, &mut db).unwrap();
+        assert_eq!(out.tx_changesets.len(), 0, "No tx");
+        assert_eq!(out.gas_used(), 0);
+
+        let root = U256::from_be_bytes(parent_beacon_block_root.to_fixed_bytes());
+        let contract = db.accounts.get(&BEACON_ROOTS_ADDRESS).unwrap();
+        assert_eq!(contract.storage.get(&U256::ZERO), Some(&root));
+
+        // Only the storage of the contract is changed
+        assert_eq!(out.block_changesets.len(), 1);
+        let changeset = out.block_changesets.get(&BEACON_ROOTS_ADDRESS).unwrap();
+        assert_eq!(changeset.account, AccountInfoChangeSet::NoChange);
+        assert_eq!(changeset.storage, BTreeMap::from([(U256::ZERO, (U256::ZERO, root))]));
+
+        // The parent beacon block root is required after Cancun
+        let mut block = block;
+        block.header.parent_beacon_block_root = None;
+        assert_eq!(
+            execute_and_verify_receipt(&block, U256::ZERO, None, &chain_spec, &mut db).unwrap_err(),
+            Error::ParentBeaconBlockRootMissing
+        );
+    }
+
     // Test vector from https://github.com/ethereum/tests/blob/3156db5389921125bb9e04142d18e0e7b0cf8d64/BlockchainTests/EIPTests/bc4895-withdrawals/twoIdenticalIndexDifferentValidator.json
     #[test]
     fn test_withdrawals() {
@@ -901,7 +1195,7 @@ mod tests {
 
         assert_eq!(out.block_changesets.len(), 1);
         assert_eq!(
-            out.block_changesets.get(&withdrawal_beneficiary),
+            out.block_changesets.get(&withdrawal_beneficiary).map(|change| &change.account),
             Some(&AccountInfoChangeSet::Created {
                 new: Account { nonce: 0, balance: withdrawal_sum, bytecode_hash: None },
             })
@@ -914,7 +1208,7 @@ mod tests {
 
         assert_eq!(out.block_changesets.len(), 1);
         assert_eq!(
-            out.block_changesets.get(&withdrawal_beneficiary),
+            out.block_changesets.get(&withdrawal_beneficiary).map(|change| &change.account),
             Some(&AccountInfoChangeSet::Changed {
                 old: Account { nonce: 0, balance: withdrawal_sum, bytecode_hash: None },
                 new: Account {
@@ -7,7 +7,10 @@
 
 //! Reth executor executes transaction in block of data.
 
+/// Blockchain tree of the side chains that fork from the canonical chain
+pub mod blockchain_tree;
 pub mod config;
+pub mod eip4788;
 pub mod eth_dao_fork;
 
 /// Execution result types
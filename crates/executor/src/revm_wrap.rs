@@ -1,7 +1,7 @@
 use reth_interfaces::Error;
 use reth_primitives::{
-    Account, Address, Header, Log as RethLog, Transaction, TransactionKind, TransactionSigned,
-    TxEip1559, TxEip2930, TxLegacy, H160, H256, KECCAK_EMPTY, U256,
+    Account, Address, Bytes, Header, Log as RethLog, Transaction, TransactionKind,
+    TransactionSigned, TxEip1559, TxEip2930, TxEip4844, TxLegacy, H160, H256, KECCAK_EMPTY, U256,
 };
 use reth_provider::StateProvider;
 use revm::{
@@ -177,9 +177,69 @@ pub fn fill_tx_env(tx_env: &mut TxEnv, transaction: &TransactionSigned, sender:
                 })
                 .collect();
         }
+        // The blob fee is not handled by revm, it is charged by the executor.
+        Transaction::Eip4844(TxEip4844 {
+            nonce,
+            chain_id,
+            gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            to,
+            value,
+            input,
+            access_list,
+            ..
+        }) => {
+            tx_env.gas_limit = *gas_limit;
+            tx_env.gas_price = U256::from(*max_fee_per_gas);
+            tx_env.gas_priority_fee = Some(U256::from(*max_priority_fee_per_gas));
+            tx_env.transact_to = match to {
+                TransactionKind::Call(to) => TransactTo::Call(*to),
+                TransactionKind::Create => TransactTo::create(),
+            };
+            tx_env.value = U256::from(*value);
+            tx_env.data = input.0.clone();
+            tx_env.chain_id = Some(*chain_id);
+            tx_env.nonce = Some(*nonce);
+            tx_env.access_list = access_list
+                .0
+                .iter()
+                .map(|l| {
+                    (
+                        l.address,
+                        l.storage_keys
+                            .iter()
+                            .map(|k| U256::from_be_bytes(k.to_fixed_bytes()))
+                            .collect(),
+                    )
+                })
+                .collect();
+        }
     }
 }
 
+/// Fill transaction environment for a system call of the `contract` with the given `data`.
+///
+/// The caller is not charged for the call, it has no nonce check and no value.
+pub fn fill_tx_env_with_system_call(
+    tx_env: &mut TxEnv,
+    caller: Address,
+    contract: Address,
+    gas_limit: u64,
+    data: Bytes,
+) {
+    tx_env.caller = caller;
+    tx_env.gas_limit = gas_limit;
+    tx_env.gas_price = U256::ZERO;
+    tx_env.gas_priority_fee = None;
+    tx_env.transact_to = TransactTo::Call(contract);
+    tx_env.value = U256::ZERO;
+    tx_env.data = data.0;
+    tx_env.chain_id = None;
+    tx_env.nonce = None;
+    tx_env.access_list = Vec::new();
+}
+
 /// Check equality between [`reth_primitives::Log`] and [`revm::primitives::Log`]
 pub fn is_log_equal(revm_log: &Log, reth_log: &reth_primitives::Log) -> bool {
     revm_log.topics.len() == reth_log.topics.len() &&
@@ -0,0 +1,50 @@
+//! The types an ExEx is run with.
+
+use reth_executor::blockchain_tree::CanonStateNotification;
+use reth_primitives::BlockNumber;
+use tokio::sync::mpsc::{self, error::SendError};
+
+/// An event an ExEx reports to the [`ExExManager`](crate::ExExManager).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExExEvent {
+    /// The ExEx has finished processing all the blocks up to and including the given block, and
+    /// does not need their data anymore.
+    ///
+    /// Until an ExEx reports a height, no blocks are pruned.
+    FinishedHeight(BlockNumber),
+}
+
+/// Sends the [`ExExEvent`]s of an ExEx to the [`ExExManager`](crate::ExExManager).
+#[derive(Debug, Clone)]
+pub struct ExExEventSender {
+    /// The index of the ExEx in the manager.
+    pub(crate) index: usize,
+    /// The events of all ExExs, tagged with their index.
+    pub(crate) sender: mpsc::UnboundedSender<(usize, ExExEvent)>,
+}
+
+impl ExExEventSender {
+    /// Sends the event to the manager.
+    ///
+    /// Fails if the manager was dropped.
+    pub fn send(&self, event: ExExEvent) -> Result<(), SendError<ExExEvent>> {
+        self.sender.send((self.index, event)).map_err(|err| SendError(err.0 .1))
+    }
+}
+
+/// Everything an ExEx needs to follow the canonical chain, returned by
+/// [`ExExManager::install`](crate::ExExManager::install).
+#[derive(Debug)]
+pub struct ExExContext<Client> {
+    /// The id of the ExEx.
+    pub id: String,
+    /// The client to read the chain and state of the node from.
+    pub client: Client,
+    /// The changes of the canonical chain, in the order they happened.
+    ///
+    /// An ExEx that does not keep up with the notifications holds up the other ExExs, since the
+    /// manager waits until every ExEx has room for the next notification.
+    pub notifications: mpsc::Receiver<CanonStateNotification>,
+    /// Reports the progress of the ExEx.
+    pub events: ExExEventSender,
+}
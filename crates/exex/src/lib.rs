@@ -0,0 +1,36 @@
+#![warn(missing_docs, unreachable_pub)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Execution extensions (ExExs) for reth.
+//!
+//! An ExEx is an async task that runs in the node and follows the canonical chain: it receives
+//! every [`CanonStateNotification`](reth_executor::blockchain_tree::CanonStateNotification), with
+//! the blocks that were committed or reverted and the results of their execution, so it can be
+//! used to build e.g. indexers or bridges without polling the RPC.
+//!
+//! ExExs are installed on the [`ExExManager`], which forwards the notifications of the canonical
+//! chain to all of them. Each ExEx reports the highest block it has finished processing with an
+//! [`ExExEvent::FinishedHeight`], and the lowest of the reported heights, the
+//! [`FinishedExExHeight`], is the highest block the pruner may prune up to, so no ExEx misses data
+//! it still needs.
+//!
+//! ```
+//! use reth_exex::{ExExContext, ExExEvent};
+//!
+//! async fn exex<Client>(mut ctx: ExExContext<Client>) {
+//!     while let Some(notification) = ctx.notifications.recv().await {
+//!         // process the committed and reverted blocks of the notification
+//!         let _ = ctx.events.send(ExExEvent::FinishedHeight(notification.tip().number));
+//!     }
+//! }
+//! ```
+
+mod context;
+mod manager;
+
+pub use context::{ExExContext, ExExEvent, ExExEventSender};
+pub use manager::{ExExManager, FinishedExExHeight, EXEX_NOTIFICATION_CHANNEL_SIZE};
@@ -0,0 +1,241 @@
+//! Forwards the changes of the canonical chain to the installed ExExs.
+
+use crate::{ExExContext, ExExEvent, ExExEventSender};
+use reth_executor::blockchain_tree::{CanonStateNotification, CanonStateNotifications};
+use reth_primitives::BlockNumber;
+use tokio::sync::{broadcast::error::RecvError, mpsc, watch};
+use tracing::{debug, warn};
+
+/// The number of notifications that are buffered for an ExEx before the manager waits for it.
+pub const EXEX_NOTIFICATION_CHANNEL_SIZE: usize = 1024;
+
+/// The highest block all the ExExs have finished processing, which gates pruning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinishedExExHeight {
+    /// No ExExs are installed, so pruning is not restricted.
+    NoExExs,
+    /// Not all the ExExs reported a finished height yet, so no blocks may be pruned.
+    NotReady,
+    /// The lowest finished height of all the ExExs.
+    Height(BlockNumber),
+}
+
+impl FinishedExExHeight {
+    /// Returns the highest block up to which blocks may be pruned, given the tip of the chain, or
+    /// `None` if no blocks may be pruned.
+    pub fn prune_tip(&self, tip: BlockNumber) -> Option<BlockNumber> {
+        match self {
+            FinishedExExHeight::NoExExs => Some(tip),
+            FinishedExExHeight::NotReady => None,
+            FinishedExExHeight::Height(height) => Some(tip.min(*height)),
+        }
+    }
+}
+
+/// An installed ExEx, as seen by the manager.
+#[derive(Debug)]
+struct ExExHandle {
+    /// The id of the ExEx.
+    id: String,
+    /// Sends the notifications to the ExEx.
+    notifications: mpsc::Sender<CanonStateNotification>,
+    /// The highest block the ExEx reported as finished.
+    finished_height: Option<BlockNumber>,
+}
+
+/// Forwards every [`CanonStateNotification`] to all the installed ExExs, and tracks the
+/// [`FinishedExExHeight`].
+///
+/// ExExs are installed with [`ExExManager::install`] before the manager is run, and the returned
+/// [`ExExContext`] is given to the task of the ExEx. An ExEx whose task exits is uninstalled, so it
+/// no longer holds up pruning.
+#[derive(Debug)]
+pub struct ExExManager {
+    /// The installed ExExs.
+    exexs: Vec<ExExHandle>,
+    /// The events of the ExExs, tagged with their index.
+    events: mpsc::UnboundedReceiver<(usize, ExExEvent)>,
+    /// Handed to the installed ExExs to report their events.
+    events_sender: mpsc::UnboundedSender<(usize, ExExEvent)>,
+    /// Publishes the lowest finished height of the ExExs.
+    finished_height: watch::Sender<FinishedExExHeight>,
+}
+
+// === impl ExExManager ===
+
+impl ExExManager {
+    /// Creates a new manager without ExExs.
+    pub fn new() -> Self {
+        let (events_sender, events) = mpsc::unbounded_channel();
+        let (finished_height, _) = watch::channel(FinishedExExHeight::NoExExs);
+        Self { exexs: Vec::new(), events, events_sender, finished_height }
+    }
+
+    /// Installs an ExEx with the given id and returns the context the ExEx must be run with.
+    pub fn install<Client>(
+        &mut self,
+        id: impl Into<String>,
+        client: Client,
+    ) -> ExExContext<Client> {
+        let id = id.into();
+        let (notifications, receiver) = mpsc::channel(EXEX_NOTIFICATION_CHANNEL_SIZE);
+        let events =
+            ExExEventSender { index: self.exexs.len(), sender: self.events_sender.clone() };
+        self.exexs.push(ExExHandle { id: id.clone(), notifications, finished_height: None });
+        self.update_finished_height();
+        ExExContext { id, client, notifications: receiver, events }
+    }
+
+    /// Returns the number of installed ExExs.
+    pub fn len(&self) -> usize {
+        self.exexs.len()
+    }
+
+    /// Returns `true` if no ExExs are installed.
+    pub fn is_empty(&self) -> bool {
+        self.exexs.is_empty()
+    }
+
+    /// Returns a receiver of the lowest finished height of the ExExs, to gate pruning with.
+    pub fn finished_height(&self) -> watch::Receiver<FinishedExExHeight> {
+        self.finished_height.subscribe()
+    }
+
+    /// Forwards the canonical state notifications to the ExExs and processes their events, until
+    /// the sender of the `notifications` is dropped.
+    pub async fn run(mut self, mut notifications: CanonStateNotifications) {
+        loop {
+            tokio::select! {
+                notification = notifications.recv() => match notification {
+                    Ok(notification) => self.forward(notification).await,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(
+                            target: "exex",
+                            skipped,
+                            "ExExs missed canonical state notifications"
+                        );
+                    }
+                    Err(RecvError::Closed) => return,
+                },
+                Some((index, event)) = self.events.recv() => self.on_event(index, event),
+            }
+        }
+    }
+
+    /// Sends the notification to every ExEx whose task is still running.
+    async fn forward(&self, notification: CanonStateNotification) {
+        for exex in &self.exexs {
+            if exex.notifications.is_closed() {
+                continue
+            }
+            if exex.notifications.send(notification.clone()).await.is_err() {
+                debug!(target: "exex", id = %exex.id, "ExEx exited");
+            }
+        }
+        // ExExs that exited no longer count towards the finished height
+        self.update_finished_height();
+    }
+
+    /// Records the event of the ExEx with the given index.
+    fn on_event(&mut self, index: usize, event: ExExEvent) {
+        let Some(exex) = self.exexs.get_mut(index) else { return };
+        match event {
+            ExExEvent::FinishedHeight(height) => {
+                debug!(target: "exex", id = %exex.id, height, "ExEx finished height");
+                exex.finished_height = Some(height);
+            }
+        }
+        self.update_finished_height();
+    }
+
+    /// Publishes the lowest finished height of the ExExs whose task is running.
+    fn update_finished_height(&self) {
+        let mut running =
+            self.exexs.iter().filter(|exex| !exex.notifications.is_closed()).peekable();
+        let finished_height = if running.peek().is_none() {
+            FinishedExExHeight::NoExExs
+        } else {
+            running
+                .map(|exex| exex.finished_height)
+                .min()
+                .flatten()
+                .map_or(FinishedExExHeight::NotReady, FinishedExExHeight::Height)
+        };
+        self.finished_height.send_if_modified(|current| {
+            let modified = *current != finished_height;
+            *current = finished_height;
+            modified
+        });
+    }
+}
+
+impl Default for ExExManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_executor::{
+        blockchain_tree::{CanonStateNotificationSender, CanonStateSubscriptions, Chain},
+        execution_result::ExecutionResult,
+    };
+    use reth_primitives::{Header, SealedBlock, H256};
+    use std::sync::Arc;
+
+    fn commit(number: BlockNumber) -> CanonStateNotification {
+        let block = SealedBlock {
+            header: Header { number, ..Default::default() }.seal_slow(),
+            ..Default::default()
+        };
+        let result =
+            ExecutionResult { tx_changesets: vec![], block_changesets: Default::default() };
+        CanonStateNotification::Commit {
+            new: Arc::new(Chain::new((number - 1, H256::zero()), block, result)),
+        }
+    }
+
+    async fn wait_for(
+        finished_height: &mut watch::Receiver<FinishedExExHeight>,
+        expected: FinishedExExHeight,
+    ) {
+        while *finished_height.borrow() != expected {
+            finished_height.changed().await.unwrap();
+        }
+    }
+
+    #[test]
+    fn prune_tip() {
+        assert_eq!(FinishedExExHeight::NoExExs.prune_tip(10), Some(10));
+        assert_eq!(FinishedExExHeight::NotReady.prune_tip(10), None);
+        assert_eq!(FinishedExExHeight::Height(5).prune_tip(10), Some(5));
+        assert_eq!(FinishedExExHeight::Height(15).prune_tip(10), Some(10));
+    }
+
+    #[tokio::test]
+    async fn forwards_notifications_and_tracks_finished_height() {
+        let sender = CanonStateNotificationSender::default();
+        let mut manager = ExExManager::new();
+        let mut first = manager.install("first", ());
+        let mut second = manager.install("second", ());
+        let mut finished_height = manager.finished_height();
+        assert_eq!(*finished_height.borrow(), FinishedExExHeight::NotReady);
+        tokio::spawn(manager.run(sender.subscribe_to_canonical_state()));
+
+        sender.notify(commit(1));
+        assert_eq!(first.notifications.recv().await.unwrap().tip().number, 1);
+        assert_eq!(second.notifications.recv().await.unwrap().tip().number, 1);
+
+        first.events.send(ExExEvent::FinishedHeight(1)).unwrap();
+        second.events.send(ExExEvent::FinishedHeight(0)).unwrap();
+        wait_for(&mut finished_height, FinishedExExHeight::Height(0)).await;
+
+        // The finished height of an exited ExEx no longer holds up pruning
+        drop(second);
+        sender.notify(commit(2));
+        assert_eq!(first.notifications.recv().await.unwrap().tip().number, 2);
+        wait_for(&mut finished_height, FinishedExExHeight::Height(1)).await;
+    }
+}
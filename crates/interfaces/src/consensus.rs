@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use reth_primitives::{BlockHash, BlockNumber, SealedBlock, SealedHeader, H256, U256};
+use reth_primitives::{BlockHash, BlockNumber, Bloom, SealedBlock, SealedHeader, H256, U256};
 use std::fmt::Debug;
 use tokio::sync::watch::Receiver;
 
@@ -62,6 +62,10 @@ pub enum Error {
     BodyTransactionRootDiff { got: H256, expected: H256 },
     #[error("Block receipts root ({got:?}) is different from expected ({expected:?})")]
     BodyReceiptsRootDiff { got: H256, expected: H256 },
+    #[error("Block logs bloom ({got:?}) is different from expected ({expected:?})")]
+    BodyBloomLogDiff { got: Box<Bloom>, expected: Box<Bloom> },
+    #[error("Block gas used ({got}) is different from expected ({expected}), gas spent by each transaction: {gas_spent_by_tx:?}")]
+    BlockGasUsed { got: u64, expected: u64, gas_spent_by_tx: Vec<(u64, u64)> },
     #[error("Block withdrawals root ({got:?}) is different from expected ({expected:?})")]
     BodyWithdrawalsRootDiff { got: H256, expected: H256 },
     #[error("Block with [hash:{hash:?},number: {number:}] is already known.")]
@@ -114,6 +118,14 @@ pub enum Error {
     TransactionTypeReceiptTypeDiff,
     #[error("Extra data {len} exceeds max length: ")]
     ExtraDataExceedsMax { len: usize },
+    #[error("Block difficulty ({got}) is different from expected ({expected})")]
+    DifficultyDiff { expected: U256, got: U256 },
+    #[error("Block gas limit {gas_limit} is out of bounds")]
+    GasLimitOutOfBounds { gas_limit: u64 },
+    #[error("Block mix hash ({got:?}) is different from expected ({expected:?})")]
+    EthashMixHashDiff { got: H256, expected: H256 },
+    #[error("Proof-of-work result {result:?} does not meet the difficulty {difficulty}")]
+    EthashResultAboveTarget { result: H256, difficulty: U256 },
     #[error("Difficulty after merge is not zero")]
     TheMergeDifficultyIsNotZero,
     #[error("Nonce after merge is not zero")]
@@ -130,4 +142,34 @@ pub enum Error {
     WithdrawalIndexInvalid { got: u64, expected: u64 },
     #[error("Missing withdrawals")]
     BodyWithdrawalsMissing,
+    #[error("Missing blob gas used")]
+    BlobGasUsedMissing,
+    #[error("Missing excess blob gas")]
+    ExcessBlobGasMissing,
+    #[error("Unexpected blob gas used")]
+    BlobGasUsedUnexpected,
+    #[error("Unexpected excess blob gas")]
+    ExcessBlobGasUnexpected,
+    #[error("Missing parent beacon block root")]
+    ParentBeaconBlockRootMissing,
+    #[error("Unexpected parent beacon block root")]
+    ParentBeaconBlockRootUnexpected,
+    #[error("Blob gas used {blob_gas_used} exceeds the maximum of {max_blob_gas_per_block}.")]
+    BlobGasUsedExceedsMaxBlobGasPerBlock { blob_gas_used: u64, max_blob_gas_per_block: u64 },
+    #[error("Blob gas used {blob_gas_used} is not a multiple of the blob gas per blob {blob_gas_per_blob}.")]
+    BlobGasUsedNotMultipleOfBlobGasPerBlob { blob_gas_used: u64, blob_gas_per_blob: u64 },
+    #[error("Block blob gas used ({got:?}) is different from the transactions: ({expected:?})")]
+    BlobGasUsedDiff { got: u64, expected: u64 },
+    #[error("Block excess blob gas ({got:?}) is different then expected: ({expected:?}).")]
+    ExcessBlobGasDiff { expected: u64, got: u64 },
+    #[error("Eip4844 transaction is enabled after cancun hardfork.")]
+    TransactionEip4844Disabled,
+    #[error("Eip4844 transaction has no blobs.")]
+    TransactionEip4844NoBlobs,
+    #[error("Eip4844 transaction can not create a contract.")]
+    TransactionEip4844Create,
+    #[error("Eip4844 transaction has a blob versioned hash of unsupported version {version}.")]
+    TransactionEip4844InvalidVersionedHash { version: u8 },
+    #[error("Transaction max fee per blob gas is less than the block blob fee.")]
+    TransactionMaxFeePerBlobGasLessThenBlobFee,
 }
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use reth_primitives::{Address, Block, Bloom, H256, U256};
+use reth_primitives::{Address, Block, U256};
 use thiserror::Error;
 
 /// An executor capable of executing a block.
@@ -41,17 +41,25 @@ pub enum Error {
     ReceiptLogDiff,
     #[error("Receipt log is different.")]
     ExecutionSuccessDiff { got: bool, expected: bool },
-    #[error("Receipt root {got:?} is different than expected {expected:?}.")]
-    ReceiptRootDiff { got: H256, expected: H256 },
-    #[error("Header bloom filter {got:?} is different than expected {expected:?}.")]
-    BloomLogDiff { got: Box<Bloom>, expected: Box<Bloom> },
     #[error("Transaction gas limit {transaction_gas_limit} is more than blocks available gas {block_available_gas}")]
     TransactionGasLimitMoreThenAvailableBlockGas {
         transaction_gas_limit: u64,
         block_available_gas: u64,
     },
-    #[error("Block gas used {got} is different from expected gas used {expected}.")]
-    BlockGasUsed { got: u64, expected: u64 },
     #[error("Provider error")]
     ProviderError,
+    #[error("Block has blob transactions but no blob fee.")]
+    BlobFeeMissing,
+    #[error("Transaction max fee per blob gas {max_fee_per_blob_gas} is less than the block blob fee {blob_fee}.")]
+    TransactionMaxFeePerBlobGasLessThenBlobFee { max_fee_per_blob_gas: u128, blob_fee: u128 },
+    #[error("Sender balance {available_funds} does not cover the max cost {max_cost} of the blob transaction.")]
+    InsufficientFundsForBlobTransaction { max_cost: U256, available_funds: U256 },
+    #[error(
+        "Sender balance {balance} after execution does not cover the blob fee {blob_gas_fee}."
+    )]
+    InsufficientFundsForBlobFee { blob_gas_fee: U256, balance: U256 },
+    #[error("Block after Cancun has no parent beacon block root.")]
+    ParentBeaconBlockRootMissing,
+    #[error(transparent)]
+    Validation(#[from] crate::consensus::Error),
 }
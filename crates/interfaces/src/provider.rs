@@ -22,6 +22,9 @@ pub enum Error {
     /// The block transition id for a certain block number is missing.
     #[error("Block transition id does not exist for block #{block_number}")]
     BlockTransition { block_number: BlockNumber },
+    /// The history needed to read the state of the block was pruned.
+    #[error("State of block #{block_number} is pruned, history is kept from block #{checkpoint}")]
+    HistoryPruned { block_number: BlockNumber, checkpoint: BlockNumber },
     /// The transition id was found for the given address and storage key, but the changeset was
     /// not found.
     #[error("Storage ChangeSet address: ({address:?} key: {storage_key:?}) for transition:#{transition_id} does not exist")]
@@ -68,4 +71,10 @@ pub enum Error {
     /// Reached the end of the transaction sender table.
     #[error("Got to the end of the transaction sender table")]
     EndOfTransactionSenderTable,
+    /// A trie node is missing.
+    #[error("Trie node {hash:?} does not exist")]
+    TrieNode { hash: H256 },
+    /// A trie node could not be decoded.
+    #[error("Trie node {hash:?} is invalid")]
+    InvalidTrieNode { hash: H256 },
 }
@@ -89,6 +89,29 @@ impl BanList {
         self.banned_peers.contains_key(peer_id)
     }
 
+    /// Returns an iterator over all banned peers.
+    pub fn banned_peers(&self) -> impl Iterator<Item = PeerId> + '_ {
+        self.banned_peers.keys().copied()
+    }
+
+    /// Returns an iterator over all banned ip addresses.
+    pub fn banned_ips(&self) -> impl Iterator<Item = IpAddr> + '_ {
+        self.banned_ips.keys().copied()
+    }
+
+    /// Returns an iterator over all banned peers, with the timestamp their ban expires at, if any.
+    pub fn banned_peers_with_timeout(
+        &self,
+    ) -> impl Iterator<Item = (PeerId, Option<Instant>)> + '_ {
+        self.banned_peers.iter().map(|(peer_id, until)| (*peer_id, *until))
+    }
+
+    /// Returns an iterator over all banned ip addresses, with the timestamp their ban expires at,
+    /// if any.
+    pub fn banned_ips_with_timeout(&self) -> impl Iterator<Item = (IpAddr, Option<Instant>)> + '_ {
+        self.banned_ips.iter().map(|(ip, until)| (*ip, *until))
+    }
+
     /// Unbans the ip address
     pub fn unban_ip(&mut self, ip: &IpAddr) {
         self.banned_ips.remove(ip);
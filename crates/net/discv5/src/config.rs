@@ -0,0 +1,47 @@
+//! Configuration of the discv5 service.
+
+use discv5::{Discv5ConfigBuilder, Enr};
+use reth_primitives::ForkId;
+use std::{
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    time::Duration,
+};
+
+/// The default port of discv5, which has its own UDP socket besides the one of discv4.
+pub const DEFAULT_DISCOVERY_V5_PORT: u16 = 9000;
+
+/// The default port of the RLPx listener, which is advertised in the local ENR.
+pub const DEFAULT_TCP_PORT: u16 = 30303;
+
+/// Settings for the discv5 service.
+#[derive(Debug, Clone)]
+pub struct Discv5Config {
+    /// Settings of the underlying discv5 protocol.
+    pub discv5_config: discv5::Discv5Config,
+    /// The address of the UDP socket.
+    pub listen_addr: SocketAddr,
+    /// The TCP port of the RLPx listener, advertised in the local ENR.
+    pub tcp_port: u16,
+    /// Nodes to start discovery with.
+    pub bootstrap_nodes: Vec<Enr>,
+    /// The interval at which a lookup of a random node id is started, to discover new nodes.
+    pub lookup_interval: Duration,
+    /// The `eth` fork id advertised in the local ENR.
+    pub fork_id: Option<ForkId>,
+}
+
+impl Default for Discv5Config {
+    fn default() -> Self {
+        Self {
+            discv5_config: Discv5ConfigBuilder::new().build(),
+            listen_addr: SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::UNSPECIFIED,
+                DEFAULT_DISCOVERY_V5_PORT,
+            )),
+            tcp_port: DEFAULT_TCP_PORT,
+            bootstrap_nodes: Vec::new(),
+            lookup_interval: Duration::from_secs(20),
+            fork_id: None,
+        }
+    }
+}
@@ -0,0 +1,15 @@
+//! Error types that can occur in this crate.
+
+/// Errors that can occur when starting the discv5 service.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Discv5Error {
+    #[error("Failed to build the local ENR: {0}")]
+    Enr(String),
+    #[error("Failed to create discv5: {0}")]
+    Init(&'static str),
+    #[error("Failed to start discv5: {0:?}")]
+    Start(discv5::Discv5Error),
+    #[error("Invalid bootstrap node: {0}")]
+    BootstrapNode(&'static str),
+}
@@ -0,0 +1,306 @@
+#![warn(missing_docs, unused_crate_dependencies)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Discovery v5 support: <https://github.com/ethereum/devp2p/blob/master/discv5/discv5.md>
+//!
+//! Discv5 finds nodes through ENRs exchanged over an encrypted UDP session protocol, which is
+//! implemented by the [`discv5`] crate. Some nodes only advertise themselves via discv5, so this
+//! runs alongside discv4.
+//!
+//! This crate consists of a [`Discv5`] and [`Discv5Service`] pair. The service regularly looks up
+//! random node ids, and produces a [`Discv5Update`] for each discovered node whose `eth` fork id
+//! is compatible with the local [`ForkFilter`]. Nodes without an `eth` entry in their ENR, e.g.
+//! consensus layer nodes, are ignored.
+
+use discv5::{
+    enr::{CombinedKey, EnrBuilder, EnrPublicKey, NodeId},
+    Discv5Event, Enr,
+};
+use reth_primitives::{bytes::BytesMut, ForkFilter, ForkId, Head, NodeRecord, PeerId};
+use reth_rlp::{Decodable, Encodable};
+use secp256k1::SecretKey;
+use std::{net::IpAddr, sync::Arc};
+use tokio::{
+    sync::{
+        mpsc::{self, error::TrySendError},
+        watch,
+    },
+    task::JoinHandle,
+    time::Interval,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, trace};
+
+mod config;
+mod error;
+
+pub use config::{Discv5Config, DEFAULT_DISCOVERY_V5_PORT};
+pub use discv5;
+pub use error::Discv5Error;
+
+/// The key of the `eth` fork id entry of an ENR.
+const ETH_ENR_KEY: &str = "eth";
+
+/// The frontend to a running discv5 service.
+#[derive(Clone)]
+pub struct Discv5 {
+    inner: Arc<discv5::Discv5>,
+    /// The head the fork filter of the service is updated with.
+    head: Arc<watch::Sender<Head>>,
+}
+
+impl Discv5 {
+    /// Starts discv5 on the address of the config, with the identity of `secret_key`.
+    ///
+    /// Returns the frontend and the [`Discv5Service`] that must be spawned to discover nodes.
+    pub async fn start(
+        secret_key: &SecretKey,
+        config: Discv5Config,
+        fork_filter: ForkFilter,
+    ) -> Result<(Self, Discv5Service), Discv5Error> {
+        let mut key_bytes = secret_key.secret_bytes();
+        let enr_key = CombinedKey::secp256k1_from_bytes(&mut key_bytes)
+            .map_err(|err| Discv5Error::Enr(err.to_string()))?;
+        let local_enr = build_local_enr(&enr_key, &config)?;
+        debug!(target : "discv5", enr=%local_enr, "starting discv5");
+
+        let mut discv5 = discv5::Discv5::new(local_enr, enr_key, config.discv5_config)
+            .map_err(Discv5Error::Init)?;
+        for enr in config.bootstrap_nodes {
+            discv5.add_enr(enr).map_err(Discv5Error::BootstrapNode)?;
+        }
+        discv5.start(config.listen_addr).await.map_err(Discv5Error::Start)?;
+        let events = discv5.event_stream().await.map_err(Discv5Error::Start)?;
+
+        let discv5 = Arc::new(discv5);
+        let (head_tx, head_rx) = watch::channel(Head::default());
+        let service = Discv5Service {
+            discv5: discv5.clone(),
+            events,
+            lookup_interval: tokio::time::interval(config.lookup_interval),
+            fork_filter,
+            head: head_rx,
+            update_listeners: Vec::new(),
+        };
+        Ok((Self { inner: discv5, head: Arc::new(head_tx) }, service))
+    }
+
+    /// Returns the local ENR.
+    pub fn local_enr(&self) -> Enr {
+        self.inner.local_enr()
+    }
+
+    /// Updates the head of the fork filter that is used to validate the discovered nodes.
+    pub fn set_head(&self, head: Head) {
+        self.head.send_replace(head);
+    }
+
+    /// Updates the `eth` fork id entry of the local ENR.
+    pub fn set_fork_id(&self, fork_id: ForkId) {
+        let mut rlp = BytesMut::new();
+        fork_id.encode(&mut rlp);
+        if let Err(err) = self.inner.enr_insert(ETH_ENR_KEY, &rlp) {
+            debug!(target : "discv5", ?err, "failed to update the fork id of the local enr");
+        }
+    }
+
+    /// Returns the number of connected discv5 peers.
+    pub fn connected_peers(&self) -> usize {
+        self.inner.connected_peers()
+    }
+}
+
+impl std::fmt::Debug for Discv5 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Discv5").field("local_enr", &self.local_enr()).finish()
+    }
+}
+
+/// Builds the ENR of the local node, listening on the address of the config.
+fn build_local_enr(enr_key: &CombinedKey, config: &Discv5Config) -> Result<Enr, Discv5Error> {
+    let mut builder = EnrBuilder::new("v4");
+    let ip = config.listen_addr.ip();
+    if !ip.is_unspecified() {
+        builder.ip(ip);
+    }
+    if ip.is_ipv4() {
+        builder.udp4(config.listen_addr.port()).tcp4(config.tcp_port);
+    } else {
+        builder.udp6(config.listen_addr.port()).tcp6(config.tcp_port);
+    }
+    if let Some(fork_id) = config.fork_id {
+        let mut rlp = BytesMut::new();
+        fork_id.encode(&mut rlp);
+        builder.add_value_rlp(ETH_ENR_KEY, rlp.freeze());
+    }
+    builder.build(enr_key).map_err(|err| Discv5Error::Enr(err.to_string()))
+}
+
+/// A node discovered via discv5, that is compatible with the local fork filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discv5Update {
+    /// The record of the discovered node.
+    pub node_record: NodeRecord,
+    /// The fork id of the `eth` entry of the ENR of the node.
+    pub fork_id: ForkId,
+}
+
+/// Discovers `eth` nodes via discv5.
+#[must_use = "Service does nothing unless spawned"]
+pub struct Discv5Service {
+    /// The running discv5 protocol.
+    discv5: Arc<discv5::Discv5>,
+    /// Events of the discv5 protocol.
+    events: mpsc::Receiver<Discv5Event>,
+    /// The interval at which random lookups are started.
+    lookup_interval: Interval,
+    /// Filter for the fork ids of the discovered nodes.
+    fork_filter: ForkFilter,
+    /// Receives the head changes of the local node.
+    head: watch::Receiver<Head>,
+    /// All subscribers of discovered nodes.
+    update_listeners: Vec<mpsc::Sender<Discv5Update>>,
+}
+
+impl Discv5Service {
+    /// Creates a new stream that yields all discovered nodes.
+    pub fn update_stream(&mut self) -> ReceiverStream<Discv5Update> {
+        let (tx, rx) = mpsc::channel(512);
+        self.update_listeners.push(tx);
+        ReceiverStream::new(rx)
+    }
+
+    /// Spawns the service onto a new task.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::task::spawn(self.run())
+    }
+
+    /// Handles the discv5 events and starts the lookups, until discv5 is shut down.
+    async fn run(mut self) {
+        loop {
+            tokio::select! {
+                event = self.events.recv() => match event {
+                    Some(Discv5Event::Discovered(enr)) |
+                    Some(Discv5Event::SessionEstablished(enr, _)) => self.on_discovered(&enr),
+                    Some(_) => {}
+                    None => {
+                        debug!(target : "discv5", "event stream closed");
+                        return
+                    }
+                },
+                Ok(()) = self.head.changed() => {
+                    let head = *self.head.borrow();
+                    self.fork_filter.set_head(head);
+                }
+                _ = self.lookup_interval.tick() => {
+                    // the nodes found by the lookup are emitted as `Discovered` events
+                    let lookup = self.discv5.find_node(NodeId::random());
+                    tokio::task::spawn(async move {
+                        if let Err(err) = lookup.await {
+                            trace!(target : "discv5", ?err, "lookup failed");
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    fn on_discovered(&mut self, enr: &Enr) {
+        let Some(update) = node_record_update(enr) else { return };
+        if let Err(err) = self.fork_filter.validate(update.fork_id) {
+            trace!(target : "discv5", peer_id=?update.node_record.id, ?err, "ignoring node with incompatible fork id");
+            return
+        }
+        self.notify(update);
+    }
+
+    /// Sends the update to all listeners.
+    ///
+    /// Remove channels that got closed.
+    fn notify(&mut self, update: Discv5Update) {
+        self.update_listeners.retain_mut(|listener| match listener.try_send(update.clone()) {
+            Ok(()) => true,
+            Err(err) => match err {
+                TrySendError::Full(_) => true,
+                TrySendError::Closed(_) => false,
+            },
+        });
+    }
+}
+
+/// Converts the ENR of an `eth` node to a [`Discv5Update`].
+///
+/// Returns `None` if the ENR has no address, no secp256k1 key or no `eth` fork id.
+fn node_record_update(enr: &Enr) -> Option<Discv5Update> {
+    let public_key = enr.public_key().encode_uncompressed();
+    if public_key.len() != 64 {
+        return None
+    }
+
+    let node_record = NodeRecord {
+        address: enr.ip4().map(IpAddr::from).or_else(|| enr.ip6().map(IpAddr::from))?,
+        tcp_port: enr.tcp4().or_else(|| enr.tcp6())?,
+        udp_port: enr.udp4().or_else(|| enr.udp6())?,
+        id: PeerId::from_slice(&public_key),
+    }
+    .into_ipv4_mapped();
+
+    let mut fork_id = enr.get(ETH_ENR_KEY)?;
+    let fork_id = ForkId::decode(&mut fork_id).ok()?;
+
+    Some(Discv5Update { node_record, fork_id })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{ForkHash, Head, MAINNET};
+    use std::net::Ipv4Addr;
+
+    fn enr(fork_id: Option<ForkId>) -> Enr {
+        let key = CombinedKey::generate_secp256k1();
+        let config = Discv5Config {
+            listen_addr: (Ipv4Addr::new(10, 0, 0, 1), 9001).into(),
+            tcp_port: 30304,
+            fork_id,
+            ..Default::default()
+        };
+        build_local_enr(&key, &config).unwrap()
+    }
+
+    #[test]
+    fn convert_enr() {
+        let fork_id = MAINNET.fork_id(&Head::default());
+        let enr = enr(Some(fork_id));
+
+        let update = node_record_update(&enr).unwrap();
+        assert_eq!(update.fork_id, fork_id);
+        assert_eq!(update.node_record.address, IpAddr::from(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(update.node_record.tcp_port, 30304);
+        assert_eq!(update.node_record.udp_port, 9001);
+        assert_eq!(
+            update.node_record.id,
+            PeerId::from_slice(&enr.public_key().encode_uncompressed())
+        );
+
+        // nodes without an `eth` entry are not `eth` nodes
+        assert_eq!(node_record_update(&self::enr(None)), None);
+    }
+
+    #[test]
+    fn filter_fork_id() {
+        let fork_filter = MAINNET.fork_filter(Head::default());
+        let compatible = MAINNET.fork_id(&Head::default());
+        let incompatible = ForkId { hash: ForkHash([0xde, 0xad, 0xbe, 0xef]), next: 0 };
+        assert!(fork_filter
+            .validate(node_record_update(&enr(Some(compatible))).unwrap().fork_id)
+            .is_ok());
+        assert!(fork_filter
+            .validate(node_record_update(&enr(Some(incompatible))).unwrap().fork_id)
+            .is_err());
+    }
+}
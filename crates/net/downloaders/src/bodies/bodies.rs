@@ -16,6 +16,7 @@ use reth_interfaces::{
     },
 };
 use reth_primitives::{BlockNumber, SealedHeader};
+use reth_rlp::Encodable;
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
 use std::{
     cmp::Ordering,
@@ -35,6 +36,9 @@ use std::{
 /// in the near future.
 const CONCURRENCY_PEER_MULTIPLIER: usize = 4;
 
+/// The soft limit of the size of a bodies response, which peers truncate their responses to.
+const SOFT_RESPONSE_LIMIT_BYTES: usize = 2 * 1024 * 1024;
+
 /// The scope for headers downloader metrics.
 pub const BODIES_DOWNLOADER_SCOPE: &str = "downloaders.bodies";
 
@@ -53,12 +57,19 @@ pub struct BodiesDownloader<B: BodiesClient, DB> {
     db: Arc<DB>,
     /// The maximum number of non-empty blocks per one request
     request_limit: u64,
+    /// The moving average of the encoded size of the received non-empty blocks, which the number
+    /// of blocks per request is adapted to.
+    avg_block_size_bytes: Option<usize>,
     /// The maximum number of block bodies returned at once from the stream
     stream_batch_size: usize,
     /// The allowed range for number of concurrent requests.
     concurrent_requests_range: RangeInclusive<usize>,
     /// Maximum amount of received bodies to buffer internally.
     max_buffered_responses: usize,
+    /// Maximum size in bytes of the blocks of the buffered responses.
+    max_buffered_blocks_size_bytes: usize,
+    /// Current size in bytes of the blocks of the buffered responses.
+    buffered_blocks_size_bytes: usize,
     /// The range of block numbers for body download.
     download_range: Range<BlockNumber>,
     /// The latest block number returned.
@@ -85,10 +96,42 @@ where
             None => self.download_range.start,
         };
 
-        let limit = self.download_range.end.saturating_sub(start_at).min(self.request_limit);
+        let limit = self.download_range.end.saturating_sub(start_at).min(self.next_request_limit());
         self.query_headers(start_at..self.download_range.end, limit)
     }
 
+    /// Returns the maximum number of non-empty blocks of the next request.
+    ///
+    /// Peers truncate responses that exceed their soft limit, so fewer blocks are requested once
+    /// the received blocks are so large that `request_limit` of them would exceed it.
+    fn next_request_limit(&self) -> u64 {
+        match self.avg_block_size_bytes {
+            Some(avg) if avg > 0 => {
+                ((SOFT_RESPONSE_LIMIT_BYTES / avg) as u64).max(1).min(self.request_limit)
+            }
+            _ => self.request_limit,
+        }
+    }
+
+    /// Updates the average size of the non-empty blocks with the blocks of a response.
+    fn update_block_size_estimate(&mut self, response: &[BlockResponse]) {
+        let (count, size) = response
+            .iter()
+            .filter_map(|block| match block {
+                BlockResponse::Full(block) => Some(block.length()),
+                BlockResponse::Empty(_) => None,
+            })
+            .fold((0, 0), |(count, size), len| (count + 1, size + len));
+        if count == 0 {
+            return
+        }
+        let sample = size / count;
+        self.avg_block_size_bytes = Some(match self.avg_block_size_bytes {
+            Some(avg) => (avg * 3 + sample) / 4,
+            None => sample,
+        });
+    }
+
     /// Retrieve a batch of headers from the database starting from provided block number.
     ///
     /// This method is going to return the batch as soon as one of the conditions below
@@ -206,6 +249,7 @@ where
         self.latest_queued_block_number.take();
         self.in_progress_queue.clear();
         self.buffered_responses.clear();
+        self.buffered_blocks_size_bytes = 0;
         self.queued_bodies.clear();
 
         self.metrics.in_flight_requests.set(0.);
@@ -218,6 +262,31 @@ where
         self.queued_bodies.extend(bodies.into_iter());
     }
 
+    /// Returns `true` if more responses can be buffered, i.e. neither the maximum number of
+    /// buffered responses nor the maximum size of their blocks is reached.
+    #[inline]
+    fn has_buffer_capacity(&self) -> bool {
+        self.buffered_responses.len() < self.max_buffered_responses &&
+            self.buffered_blocks_size_bytes < self.max_buffered_blocks_size_bytes
+    }
+
+    /// Buffers a response, which may be out of order.
+    fn buffer_response(&mut self, response: Vec<BlockResponse>) {
+        self.update_block_size_estimate(&response);
+        let response = OrderedBodiesResponse::new(response);
+        self.buffered_blocks_size_bytes += response.size;
+        self.buffered_responses.push(response);
+        self.metrics.buffered_responses.increment(1.);
+    }
+
+    /// Removes the next buffered response.
+    fn pop_buffered(&mut self) -> Option<OrderedBodiesResponse> {
+        let response = self.buffered_responses.pop()?;
+        self.buffered_blocks_size_bytes -= response.size;
+        self.metrics.buffered_responses.decrement(1.);
+        Some(response)
+    }
+
     /// Returns a response if it's first block number matches the next expected.
     fn try_next_buffered(&mut self) -> Option<Vec<BlockResponse>> {
         if let Some(next) = self.buffered_responses.peek() {
@@ -225,10 +294,9 @@ where
             let next_block_rng = next.block_range();
 
             if next_block_rng.contains(&expected) {
-                return self.buffered_responses.pop().map(|buffered| {
-                    self.metrics.buffered_responses.decrement(1.);
+                return self.pop_buffered().map(|buffered| {
                     buffered
-                        .0
+                        .response
                         .into_iter()
                         .skip_while(|b| b.block_number() < expected)
                         .take_while(|b| self.download_range.contains(&b.block_number()))
@@ -238,8 +306,7 @@ where
 
             // Drop buffered response since we passed that range
             if *next_block_rng.end() < expected {
-                self.metrics.buffered_responses.decrement(1.);
-                self.buffered_responses.pop();
+                self.pop_buffered();
             }
         }
         None
@@ -325,11 +392,7 @@ where
             while let Poll::Ready(Some(response)) = this.in_progress_queue.poll_next_unpin(cx) {
                 this.metrics.in_flight_requests.decrement(1.);
                 match response {
-                    Ok(response) => {
-                        let response = OrderedBodiesResponse(response);
-                        this.buffered_responses.push(response);
-                        this.metrics.buffered_responses.increment(1.);
-                    }
+                    Ok(response) => this.buffer_response(response),
                     Err(error) => {
                         tracing::error!(target: "downloaders::bodies", ?error, "Request failed");
                         this.clear();
@@ -343,7 +406,7 @@ where
             // Submit new requests
             let concurrent_requests_limit = this.concurrent_request_limit();
             'inner: while this.in_progress_queue.len() < concurrent_requests_limit &&
-                this.buffered_responses.len() < this.max_buffered_responses
+                this.has_buffer_capacity()
             {
                 match this.next_headers_request() {
                     Ok(Some(request)) => {
@@ -398,15 +461,30 @@ where
 }
 
 #[derive(Debug)]
-struct OrderedBodiesResponse(Vec<BlockResponse>);
+struct OrderedBodiesResponse {
+    response: Vec<BlockResponse>,
+    /// The RLP encoded size of the blocks of the response.
+    size: usize,
+}
 
 impl OrderedBodiesResponse {
+    fn new(response: Vec<BlockResponse>) -> Self {
+        let size = response
+            .iter()
+            .map(|block| match block {
+                BlockResponse::Full(block) => block.length(),
+                BlockResponse::Empty(header) => header.length(),
+            })
+            .sum();
+        Self { response, size }
+    }
+
     /// Returns the block number of the first element
     ///
     /// # Panics
     /// If the response vec is empty.
     fn first_block_number(&self) -> u64 {
-        self.0.first().expect("is not empty").block_number()
+        self.response.first().expect("is not empty").block_number()
     }
 
     /// Returns the range of the block numbers in the response
@@ -414,7 +492,7 @@ impl OrderedBodiesResponse {
     /// # Panics
     /// If the response vec is empty.
     fn block_range(&self) -> RangeInclusive<u64> {
-        self.first_block_number()..=self.0.last().expect("is not empty").block_number()
+        self.first_block_number()..=self.response.last().expect("is not empty").block_number()
     }
 }
 
@@ -446,6 +524,8 @@ pub struct BodiesDownloaderBuilder {
     stream_batch_size: usize,
     /// Maximum amount of received bodies to buffer internally.
     max_buffered_responses: usize,
+    /// Maximum size in bytes of the blocks of the buffered responses.
+    max_buffered_blocks_size_bytes: usize,
     /// The maximum number of requests to send concurrently.
     concurrent_requests_range: RangeInclusive<usize>,
 }
@@ -456,6 +536,7 @@ impl Default for BodiesDownloaderBuilder {
             request_limit: 200,
             stream_batch_size: 1000,
             max_buffered_responses: 30000,
+            max_buffered_blocks_size_bytes: 2 * 1024 * 1024 * 1024, // ~2GB
             concurrent_requests_range: 5..=100,
         }
     }
@@ -489,6 +570,15 @@ impl BodiesDownloaderBuilder {
         self
     }
 
+    /// Set the maximum size in bytes of the blocks that are buffered by the downloader.
+    pub fn with_max_buffered_blocks_size_bytes(
+        mut self,
+        max_buffered_blocks_size_bytes: usize,
+    ) -> Self {
+        self.max_buffered_blocks_size_bytes = max_buffered_blocks_size_bytes;
+        self
+    }
+
     /// Consume self and return the concurrent donwloader.
     pub fn build<B, DB>(
         self,
@@ -505,6 +595,7 @@ impl BodiesDownloaderBuilder {
             stream_batch_size,
             concurrent_requests_range,
             max_buffered_responses,
+            max_buffered_blocks_size_bytes,
         } = self;
         let metrics = DownloaderMetrics::new(BODIES_DOWNLOADER_SCOPE);
         let in_progress_queue = BodiesRequestQueue::new(metrics.clone());
@@ -513,8 +604,11 @@ impl BodiesDownloaderBuilder {
             consensus,
             db,
             request_limit,
+            avg_block_size_bytes: None,
             stream_batch_size,
             max_buffered_responses,
+            max_buffered_blocks_size_bytes,
+            buffered_blocks_size_bytes: 0,
             concurrent_requests_range,
             in_progress_queue,
             metrics,
@@ -604,6 +698,31 @@ mod tests {
         assert_eq!(client.times_requested(), 20);
     }
 
+    // Check that all bodies are downloaded in order if the buffered blocks exceed the size limit.
+    #[tokio::test]
+    async fn streams_bodies_in_order_with_buffer_size_limit() {
+        // Generate some random blocks
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let (headers, mut bodies) = generate_bodies(0..100);
+
+        insert_headers(&db, &headers);
+
+        let client = Arc::new(
+            TestBodiesClient::default().with_bodies(bodies.clone()).with_should_delay(true),
+        );
+        let mut downloader = BodiesDownloaderBuilder::default()
+            .with_request_limit(10)
+            .with_max_buffered_blocks_size_bytes(1)
+            .build(client.clone(), Arc::new(TestConsensus::default()), db);
+        downloader.set_download_range(0..100).expect("failed to set download range");
+
+        assert_matches!(
+            downloader.next().await,
+            Some(Ok(res)) => assert_eq!(res, zip_blocks(headers.iter(), &mut bodies))
+        );
+        assert_eq!(downloader.buffered_blocks_size_bytes, 0);
+    }
+
     // Check that bodies are returned in correct order
     // after resetting the download range multiple times.
     #[tokio::test]
@@ -671,4 +790,31 @@ mod tests {
             Some(Ok(res)) => assert_eq!(res, zip_blocks(headers.iter().skip(100), &mut bodies))
         );
     }
+
+    // Check that fewer blocks are requested once the received blocks are large.
+    #[test]
+    fn adapts_request_limit_to_block_size() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut downloader = BodiesDownloaderBuilder::default().with_request_limit(100).build(
+            Arc::new(TestBodiesClient::default()),
+            Arc::new(TestConsensus::default()),
+            db,
+        );
+        assert_eq!(downloader.next_request_limit(), 100);
+
+        // small blocks don't reduce the limit
+        let blocks = random_block_range(0..10, H256::zero(), 1..2);
+        downloader.update_block_size_estimate(
+            &blocks.into_iter().map(BlockResponse::Full).collect::<Vec<_>>(),
+        );
+        assert!(downloader.avg_block_size_bytes.is_some());
+        assert_eq!(downloader.next_request_limit(), 100);
+
+        downloader.avg_block_size_bytes = Some(SOFT_RESPONSE_LIMIT_BYTES / 10);
+        assert_eq!(downloader.next_request_limit(), 10);
+
+        // blocks that exceed the soft limit are requested one by one
+        downloader.avg_block_size_bytes = Some(SOFT_RESPONSE_LIMIT_BYTES * 2);
+        assert_eq!(downloader.next_request_limit(), 1);
+    }
 }
@@ -119,7 +119,12 @@ impl FileClient {
 
     /// Get the tip hash of the chain.
     pub fn tip(&self) -> Option<H256> {
-        self.headers.get(&(self.headers.len() as u64)).map(|h| h.hash_slow())
+        self.max_block().and_then(|number| self.headers.get(&number)).map(|h| h.hash_slow())
+    }
+
+    /// Returns the number of the highest block of the file.
+    pub fn max_block(&self) -> Option<BlockNumber> {
+        self.headers.keys().max().copied()
     }
 
     /// Use the provided bodies as the file client's block body buffer.
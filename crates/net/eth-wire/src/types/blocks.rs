@@ -283,6 +283,9 @@ mod test {
                     nonce: 0x0000000000000000u64,
                     base_fee_per_gas: None,
                     withdrawals_root: None,
+                    blob_gas_used: None,
+                    excess_blob_gas: None,
+                    parent_beacon_block_root: None,
                 },
             ]),
         }.encode(&mut data);
@@ -314,6 +317,9 @@ mod test {
                     nonce: 0x0000000000000000u64,
                     base_fee_per_gas: None,
                     withdrawals_root: None,
+                    blob_gas_used: None,
+                    excess_blob_gas: None,
+                    parent_beacon_block_root: None,
                 },
             ]),
         };
@@ -426,6 +432,9 @@ mod test {
                             nonce: 0x0000000000000000u64,
                             base_fee_per_gas: None,
                             withdrawals_root: None,
+                            blob_gas_used: None,
+                            excess_blob_gas: None,
+                            parent_beacon_block_root: None,
                         },
                     ],
                     withdrawals: None,
@@ -510,6 +519,9 @@ mod test {
                             nonce: 0x0000000000000000u64,
                             base_fee_per_gas: None,
                             withdrawals_root: None,
+                            blob_gas_used: None,
+                            excess_blob_gas: None,
+                            parent_beacon_block_root: None,
                         },
                     ],
                     withdrawals: None,
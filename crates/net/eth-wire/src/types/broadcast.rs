@@ -1,7 +1,11 @@
 //! Types for broadcasting new data.
+use crate::EthVersion;
 use reth_codecs::derive_arbitrary;
-use reth_primitives::{Block, TransactionSigned, H256, U128};
-use reth_rlp::{RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper};
+use reth_primitives::{bytes::BufMut, Block, TransactionSigned, H256, U128};
+use reth_rlp::{
+    Decodable, DecodeError, Encodable, Header, RlpDecodable, RlpDecodableWrapper, RlpEncodable,
+    RlpEncodableWrapper,
+};
 use std::sync::Arc;
 
 #[cfg(feature = "serde")]
@@ -117,6 +121,152 @@ impl From<Vec<H256>> for NewPooledTransactionHashes {
     }
 }
 
+/// The `eth/68` version of [`NewPooledTransactionHashes`], which also announces the type and the
+/// size of each transaction, so peers can decide whether to request it.
+///
+/// The three lists have the same length, the type and the size at an index are those of the
+/// transaction of the hash at the same index.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NewPooledTransactionHashes68 {
+    /// Transaction types of the announced transactions.
+    pub types: Vec<u8>,
+    /// Transaction sizes of the announced transactions, as encoded in a
+    /// [`PooledTransactions`](crate::PooledTransactions) response.
+    pub sizes: Vec<usize>,
+    /// Transaction hashes of the announced transactions.
+    pub hashes: Vec<H256>,
+}
+
+impl NewPooledTransactionHashes68 {
+    fn payload_length(&self) -> usize {
+        self.types.as_slice().length() + self.sizes.length() + self.hashes.length()
+    }
+}
+
+impl From<NewPooledTransactionHashes68> for NewPooledTransactionHashes {
+    fn from(msg: NewPooledTransactionHashes68) -> Self {
+        NewPooledTransactionHashes(msg.hashes)
+    }
+}
+
+/// A `NewPooledTransactionHashes` announcement in the form of the `eth` version of a session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PooledTransactionHashes {
+    /// The announcement of `eth/66` and `eth/67`, which only has the hashes.
+    Eth66(NewPooledTransactionHashes),
+    /// The announcement of `eth/68`, which also has the types and sizes of the transactions.
+    Eth68(NewPooledTransactionHashes68),
+}
+
+impl PooledTransactionHashes {
+    /// Creates an empty announcement in the form of the given `eth` version.
+    pub fn new(version: EthVersion) -> Self {
+        match version {
+            EthVersion::Eth68 => PooledTransactionHashes::Eth68(Default::default()),
+            EthVersion::Eth66 | EthVersion::Eth67 => {
+                PooledTransactionHashes::Eth66(Default::default())
+            }
+        }
+    }
+
+    /// Adds a transaction to the announcement.
+    ///
+    /// The type and the size are only announced in the `eth/68` form.
+    pub fn push(&mut self, hash: H256, tx_type: u8, size: usize) {
+        match self {
+            PooledTransactionHashes::Eth66(msg) => msg.0.push(hash),
+            PooledTransactionHashes::Eth68(msg) => {
+                msg.types.push(tx_type);
+                msg.sizes.push(size);
+                msg.hashes.push(hash);
+            }
+        }
+    }
+
+    /// Returns the announced hashes.
+    pub fn hashes(&self) -> &[H256] {
+        match self {
+            PooledTransactionHashes::Eth66(msg) => &msg.0,
+            PooledTransactionHashes::Eth68(msg) => &msg.hashes,
+        }
+    }
+
+    /// Consumes the announcement and returns the announced hashes.
+    pub fn into_hashes(self) -> Vec<H256> {
+        match self {
+            PooledTransactionHashes::Eth66(msg) => msg.0,
+            PooledTransactionHashes::Eth68(msg) => msg.hashes,
+        }
+    }
+
+    /// Returns true if no transaction is announced.
+    pub fn is_empty(&self) -> bool {
+        self.hashes().is_empty()
+    }
+}
+
+impl From<NewPooledTransactionHashes> for PooledTransactionHashes {
+    fn from(msg: NewPooledTransactionHashes) -> Self {
+        PooledTransactionHashes::Eth66(msg)
+    }
+}
+
+impl From<NewPooledTransactionHashes68> for PooledTransactionHashes {
+    fn from(msg: NewPooledTransactionHashes68) -> Self {
+        PooledTransactionHashes::Eth68(msg)
+    }
+}
+
+impl From<PooledTransactionHashes> for NewPooledTransactionHashes {
+    fn from(msg: PooledTransactionHashes) -> Self {
+        NewPooledTransactionHashes(msg.into_hashes())
+    }
+}
+
+impl Encodable for NewPooledTransactionHashes68 {
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header { list: true, payload_length: self.payload_length() }.encode(out);
+        // the types are encoded as a byte string rather than a list of integers
+        self.types.as_slice().encode(out);
+        self.sizes.encode(out);
+        self.hashes.encode(out);
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + reth_rlp::length_of_length(payload_length)
+    }
+}
+
+impl Decodable for NewPooledTransactionHashes68 {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(DecodeError::UnexpectedString)
+        }
+        let started_len = buf.len();
+
+        let msg = Self {
+            types: reth_primitives::bytes::Bytes::decode(buf)?.to_vec(),
+            sizes: Decodable::decode(buf)?,
+            hashes: Decodable::decode(buf)?,
+        };
+
+        let consumed = started_len - buf.len();
+        if consumed != header.payload_length {
+            return Err(DecodeError::ListLengthMismatch {
+                expected: header.payload_length,
+                got: consumed,
+            })
+        }
+        if msg.types.len() != msg.hashes.len() || msg.sizes.len() != msg.hashes.len() {
+            return Err(DecodeError::Custom("mismatched lengths of types, sizes and hashes"))
+        }
+        Ok(msg)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,4 +282,45 @@ mod tests {
         let latest = blocks.latest().unwrap();
         assert_eq!(latest.number, 100);
     }
+
+    #[test]
+    fn pooled_transaction_hashes_of_version() {
+        let hash = H256::random();
+
+        let mut msg = PooledTransactionHashes::new(EthVersion::Eth67);
+        msg.push(hash, 0x02, 0x7b);
+        assert_eq!(msg, PooledTransactionHashes::Eth66(NewPooledTransactionHashes(vec![hash])));
+
+        let mut msg = PooledTransactionHashes::new(EthVersion::Eth68);
+        msg.push(hash, 0x02, 0x7b);
+        assert_eq!(
+            msg,
+            PooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+                types: vec![0x02],
+                sizes: vec![0x7b],
+                hashes: vec![hash],
+            })
+        );
+        assert_eq!(msg.into_hashes(), vec![hash]);
+    }
+
+    #[test]
+    fn encode_decode_new_pooled_transaction_hashes_68() {
+        let msg = NewPooledTransactionHashes68 {
+            types: vec![0x00, 0x02],
+            sizes: vec![0x6b, 0x7b],
+            hashes: vec![H256::repeat_byte(0x01), H256::repeat_byte(0x02)],
+        };
+        let mut encoded = Vec::new();
+        msg.encode(&mut encoded);
+        assert_eq!(encoded.len(), msg.length());
+        // types are a two byte string, not a list
+        assert_eq!(&encoded[2..5], &[0x82, 0x00, 0x02]);
+        assert_eq!(NewPooledTransactionHashes68::decode(&mut &encoded[..]).unwrap(), msg);
+
+        let mismatched = NewPooledTransactionHashes68 { types: vec![0x00], ..msg };
+        let mut encoded = Vec::new();
+        mismatched.encode(&mut encoded);
+        assert!(NewPooledTransactionHashes68::decode(&mut &encoded[..]).is_err());
+    }
 }
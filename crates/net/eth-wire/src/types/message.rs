@@ -1,8 +1,9 @@
 #![allow(missing_docs)]
 use super::{
-    broadcast::NewBlockHashes, BlockBodies, BlockHeaders, GetBlockBodies, GetBlockHeaders,
-    GetNodeData, GetPooledTransactions, GetReceipts, NewBlock, NewPooledTransactionHashes,
-    NodeData, PooledTransactions, Receipts, Status, Transactions,
+    broadcast::NewBlockHashes, BlockBodies, BlockHeaders, EthVersion, GetBlockBodies,
+    GetBlockHeaders, GetNodeData, GetPooledTransactions, GetReceipts, NewBlock,
+    NewPooledTransactionHashes, NewPooledTransactionHashes68, NodeData, PooledTransactions,
+    Receipts, Status, Transactions,
 };
 use crate::SharedTransactions;
 use reth_primitives::bytes::{Buf, BufMut};
@@ -21,11 +22,16 @@ pub struct ProtocolMessage {
 }
 
 impl ProtocolMessage {
-    /// Create a new ProtocolMessage from a message type and message rlp bytes.
+    /// Decodes a protocol message of the given `eth` version from bytes, using the first byte to
+    /// determine the message type.
+    ///
+    /// This decodes `eth/66` request ids for each message type, and the `eth/68` format of
+    /// `NewPooledTransactionHashes` if the version is `eth/68`.
     pub fn decode_message(
-        message_type: EthMessageID,
+        version: EthVersion,
         buf: &mut &[u8],
     ) -> Result<Self, reth_rlp::DecodeError> {
+        let message_type = EthMessageID::decode(buf)?;
         let message = match message_type {
             EthMessageID::Status => EthMessage::Status(Status::decode(buf)?),
             EthMessageID::NewBlockHashes => {
@@ -34,7 +40,13 @@ impl ProtocolMessage {
             EthMessageID::NewBlock => EthMessage::NewBlock(Box::new(NewBlock::decode(buf)?)),
             EthMessageID::Transactions => EthMessage::Transactions(Transactions::decode(buf)?),
             EthMessageID::NewPooledTransactionHashes => {
-                EthMessage::NewPooledTransactionHashes(NewPooledTransactionHashes::decode(buf)?)
+                if version == EthVersion::Eth68 {
+                    EthMessage::NewPooledTransactionHashes68(NewPooledTransactionHashes68::decode(
+                        buf,
+                    )?)
+                } else {
+                    EthMessage::NewPooledTransactionHashes(NewPooledTransactionHashes::decode(buf)?)
+                }
             }
             EthMessageID::GetBlockHeaders => {
                 let request_pair = RequestPair::<GetBlockHeaders>::decode(buf)?;
@@ -93,15 +105,6 @@ impl Encodable for ProtocolMessage {
     }
 }
 
-/// Decodes a protocol message from bytes, using the first byte to determine the message type.
-/// This decodes `eth/66` request ids for each message type.
-impl Decodable for ProtocolMessage {
-    fn decode(buf: &mut &[u8]) -> Result<Self, reth_rlp::DecodeError> {
-        let message_type = EthMessageID::decode(buf)?;
-        Self::decode_message(message_type, buf)
-    }
-}
-
 impl From<EthMessage> for ProtocolMessage {
     fn from(message: EthMessage) -> Self {
         ProtocolMessage { message_type: message.message_id(), message }
@@ -133,7 +136,7 @@ impl From<EthBroadcastMessage> for ProtocolBroadcastMessage {
     }
 }
 
-/// Represents a message in the eth wire protocol, versions 66 and 67.
+/// Represents a message in the eth wire protocol, versions 66, 67 and 68.
 ///
 /// The ethereum wire protocol is a set of messages that are broadcasted to the network in two
 /// styles:
@@ -143,6 +146,9 @@ impl From<EthBroadcastMessage> for ProtocolBroadcastMessage {
 ///
 ///  The newer `eth/66` is an efficiency upgrade on top of `eth/65`, introducing a request id to
 ///  correlate request-response message pairs. This allows for request multiplexing.
+///
+///  The `eth/68` upgrade announces the types and sizes of the new pooled transactions along with
+///  their hashes, in [`NewPooledTransactionHashes68`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EthMessage {
@@ -153,6 +159,7 @@ pub enum EthMessage {
     NewBlock(Box<NewBlock>),
     Transactions(Transactions),
     NewPooledTransactionHashes(NewPooledTransactionHashes),
+    NewPooledTransactionHashes68(NewPooledTransactionHashes68),
 
     // The following messages are request-response message pairs
     GetBlockHeaders(RequestPair<GetBlockHeaders>),
@@ -175,7 +182,8 @@ impl EthMessage {
             EthMessage::NewBlockHashes(_) => EthMessageID::NewBlockHashes,
             EthMessage::NewBlock(_) => EthMessageID::NewBlock,
             EthMessage::Transactions(_) => EthMessageID::Transactions,
-            EthMessage::NewPooledTransactionHashes(_) => EthMessageID::NewPooledTransactionHashes,
+            EthMessage::NewPooledTransactionHashes(_) |
+            EthMessage::NewPooledTransactionHashes68(_) => EthMessageID::NewPooledTransactionHashes,
             EthMessage::GetBlockHeaders(_) => EthMessageID::GetBlockHeaders,
             EthMessage::BlockHeaders(_) => EthMessageID::BlockHeaders,
             EthMessage::GetBlockBodies(_) => EthMessageID::GetBlockBodies,
@@ -198,6 +206,7 @@ impl Encodable for EthMessage {
             EthMessage::NewBlock(new_block) => new_block.encode(out),
             EthMessage::Transactions(transactions) => transactions.encode(out),
             EthMessage::NewPooledTransactionHashes(hashes) => hashes.encode(out),
+            EthMessage::NewPooledTransactionHashes68(hashes) => hashes.encode(out),
             EthMessage::GetBlockHeaders(request) => request.encode(out),
             EthMessage::BlockHeaders(headers) => headers.encode(out),
             EthMessage::GetBlockBodies(request) => request.encode(out),
@@ -217,6 +226,7 @@ impl Encodable for EthMessage {
             EthMessage::NewBlock(new_block) => new_block.length(),
             EthMessage::Transactions(transactions) => transactions.length(),
             EthMessage::NewPooledTransactionHashes(hashes) => hashes.length(),
+            EthMessage::NewPooledTransactionHashes68(hashes) => hashes.length(),
             EthMessage::GetBlockHeaders(request) => request.length(),
             EthMessage::BlockHeaders(headers) => headers.length(),
             EthMessage::GetBlockBodies(request) => request.length(),
@@ -403,8 +413,10 @@ where
 
 #[cfg(test)]
 mod test {
+    use super::*;
     use crate::types::message::RequestPair;
     use hex_literal::hex;
+    use reth_primitives::H256;
     use reth_rlp::{Decodable, Encodable};
 
     fn encode<T: Encodable>(value: T) -> Vec<u8> {
@@ -438,4 +450,27 @@ mod test {
         assert_eq!(expected.length(), raw_pair.len());
         assert_eq!(expected, got);
     }
+
+    #[test]
+    fn decode_new_pooled_transaction_hashes_by_version() {
+        let hashes = NewPooledTransactionHashes(vec![H256::repeat_byte(0x01)]);
+        let hashes68 = NewPooledTransactionHashes68 {
+            types: vec![0x02],
+            sizes: vec![100],
+            hashes: hashes.0.clone(),
+        };
+
+        let raw =
+            encode(ProtocolMessage::from(EthMessage::NewPooledTransactionHashes(hashes.clone())));
+        let msg = ProtocolMessage::decode_message(EthVersion::Eth67, &mut &raw[..]).unwrap();
+        assert_eq!(msg.message, EthMessage::NewPooledTransactionHashes(hashes));
+        assert!(ProtocolMessage::decode_message(EthVersion::Eth68, &mut &raw[..]).is_err());
+
+        let raw = encode(ProtocolMessage::from(EthMessage::NewPooledTransactionHashes68(
+            hashes68.clone(),
+        )));
+        let msg = ProtocolMessage::decode_message(EthVersion::Eth68, &mut &raw[..]).unwrap();
+        assert_eq!(msg.message_type, EthMessageID::NewPooledTransactionHashes);
+        assert_eq!(msg.message, EthMessage::NewPooledTransactionHashes68(hashes68));
+    }
 }
@@ -0,0 +1,370 @@
+//! Implements the message types of the `snap/1` protocol:
+//! <https://github.com/ethereum/devp2p/blob/master/caps/snap.md>
+//!
+//! Unlike the `eth/66` messages, these messages are not wrapped in a
+//! [`RequestPair`](crate::RequestPair), the request id is the first field of each message.
+#![allow(missing_docs)]
+use reth_primitives::{
+    bytes::{Buf, BufMut},
+    proofs::EMPTY_ROOT,
+    Bytes, H256, KECCAK_EMPTY, U256,
+};
+use reth_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The latest version of the `snap` protocol.
+pub const SNAP_PROTOCOL_VERSION: usize = 1;
+
+/// The number of messages of the `snap/1` protocol.
+pub const SNAP_TOTAL_MESSAGES: u8 = 8;
+
+/// A request for the accounts of the state trie with the given root, starting at the hash
+/// `starting_hash`.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetAccountRange {
+    pub request_id: u64,
+    /// The root of the state trie to serve.
+    pub root_hash: H256,
+    /// The hash of the first account to retrieve.
+    pub starting_hash: H256,
+    /// The hash after which to stop serving accounts.
+    pub limit_hash: H256,
+    /// The soft limit of the response size in bytes.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetAccountRange`].
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccountRange {
+    pub request_id: u64,
+    /// The consecutive accounts starting at the requested hash.
+    pub accounts: Vec<AccountData>,
+    /// The trie nodes proving the first and the last account of the range.
+    pub proof: Vec<Bytes>,
+}
+
+/// An account of an [`AccountRange`].
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccountData {
+    /// The hash of the address of the account.
+    pub hash: H256,
+    /// The account in the slim format.
+    pub body: SlimAccount,
+}
+
+/// An account in the slim format of the `snap` protocol, in which the empty storage root and the
+/// empty code hash are encoded as empty strings.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SlimAccount {
+    pub nonce: u64,
+    pub balance: U256,
+    pub storage_root: Bytes,
+    pub code_hash: Bytes,
+}
+
+impl SlimAccount {
+    /// Creates the slim format of an account with the given storage root and code hash.
+    pub fn new(nonce: u64, balance: U256, storage_root: H256, code_hash: H256) -> Self {
+        let slim = |hash: H256, empty: H256| {
+            if hash == empty {
+                Bytes::default()
+            } else {
+                Bytes::from(hash.as_bytes())
+            }
+        };
+        Self {
+            nonce,
+            balance,
+            storage_root: slim(storage_root, EMPTY_ROOT),
+            code_hash: slim(code_hash, KECCAK_EMPTY),
+        }
+    }
+}
+
+/// A request for the storage slots of the given accounts in the state trie with the given root.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetStorageRanges {
+    pub request_id: u64,
+    /// The root of the state trie to serve.
+    pub root_hash: H256,
+    /// The hashes of the addresses of the accounts to serve the storage of.
+    pub account_hashes: Vec<H256>,
+    /// The hash of the first slot to retrieve, empty to start at the first slot.
+    ///
+    /// This only applies to the first account.
+    pub starting_hash: Bytes,
+    /// The hash after which to stop serving slots, empty to not stop.
+    ///
+    /// This only applies to the last account.
+    pub limit_hash: Bytes,
+    /// The soft limit of the response size in bytes.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetStorageRanges`].
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StorageRanges {
+    pub request_id: u64,
+    /// The consecutive slots of each served account.
+    pub slots: Vec<Vec<StorageData>>,
+    /// The trie nodes proving the boundaries of the last storage range if it is incomplete.
+    pub proof: Vec<Bytes>,
+}
+
+/// A storage slot of a [`StorageRanges`].
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StorageData {
+    /// The hash of the slot.
+    pub hash: H256,
+    /// The RLP encoded value of the slot.
+    pub data: Bytes,
+}
+
+/// A request for bytecodes by their hashes.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetByteCodes {
+    pub request_id: u64,
+    /// The hashes of the bytecodes.
+    pub hashes: Vec<H256>,
+    /// The soft limit of the response size in bytes.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetByteCodes`].
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ByteCodes {
+    pub request_id: u64,
+    /// The requested bytecodes, in the order of the request, without the unavailable ones.
+    pub codes: Vec<Bytes>,
+}
+
+/// A request for trie nodes by their paths in the state trie with the given root.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetTrieNodes {
+    pub request_id: u64,
+    /// The root of the state trie to serve.
+    pub root_hash: H256,
+    /// Each path set consists of the compact encoded path of a node in the account trie, or of
+    /// the path of an account followed by the compact encoded paths of nodes in its storage trie.
+    pub paths: Vec<Vec<Bytes>>,
+    /// The soft limit of the response size in bytes.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetTrieNodes`].
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TrieNodes {
+    pub request_id: u64,
+    /// The requested trie nodes, in the order of the request, until the first unavailable one.
+    pub nodes: Vec<Bytes>,
+}
+
+/// Represents a message of the `snap/1` protocol.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SnapMessage {
+    GetAccountRange(GetAccountRange),
+    AccountRange(AccountRange),
+    GetStorageRanges(GetStorageRanges),
+    StorageRanges(StorageRanges),
+    GetByteCodes(GetByteCodes),
+    ByteCodes(ByteCodes),
+    GetTrieNodes(GetTrieNodes),
+    TrieNodes(TrieNodes),
+}
+
+impl SnapMessage {
+    /// Returns the message's ID.
+    pub fn message_id(&self) -> SnapMessageID {
+        match self {
+            SnapMessage::GetAccountRange(_) => SnapMessageID::GetAccountRange,
+            SnapMessage::AccountRange(_) => SnapMessageID::AccountRange,
+            SnapMessage::GetStorageRanges(_) => SnapMessageID::GetStorageRanges,
+            SnapMessage::StorageRanges(_) => SnapMessageID::StorageRanges,
+            SnapMessage::GetByteCodes(_) => SnapMessageID::GetByteCodes,
+            SnapMessage::ByteCodes(_) => SnapMessageID::ByteCodes,
+            SnapMessage::GetTrieNodes(_) => SnapMessageID::GetTrieNodes,
+            SnapMessage::TrieNodes(_) => SnapMessageID::TrieNodes,
+        }
+    }
+
+    /// Returns the request id of the message.
+    pub fn request_id(&self) -> u64 {
+        match self {
+            SnapMessage::GetAccountRange(msg) => msg.request_id,
+            SnapMessage::AccountRange(msg) => msg.request_id,
+            SnapMessage::GetStorageRanges(msg) => msg.request_id,
+            SnapMessage::StorageRanges(msg) => msg.request_id,
+            SnapMessage::GetByteCodes(msg) => msg.request_id,
+            SnapMessage::ByteCodes(msg) => msg.request_id,
+            SnapMessage::GetTrieNodes(msg) => msg.request_id,
+            SnapMessage::TrieNodes(msg) => msg.request_id,
+        }
+    }
+
+    /// Decodes a message from bytes, using the first byte to determine the message type.
+    pub fn decode_message(buf: &mut &[u8]) -> Result<Self, reth_rlp::DecodeError> {
+        let message = match SnapMessageID::decode(buf)? {
+            SnapMessageID::GetAccountRange => {
+                SnapMessage::GetAccountRange(GetAccountRange::decode(buf)?)
+            }
+            SnapMessageID::AccountRange => SnapMessage::AccountRange(AccountRange::decode(buf)?),
+            SnapMessageID::GetStorageRanges => {
+                SnapMessage::GetStorageRanges(GetStorageRanges::decode(buf)?)
+            }
+            SnapMessageID::StorageRanges => SnapMessage::StorageRanges(StorageRanges::decode(buf)?),
+            SnapMessageID::GetByteCodes => SnapMessage::GetByteCodes(GetByteCodes::decode(buf)?),
+            SnapMessageID::ByteCodes => SnapMessage::ByteCodes(ByteCodes::decode(buf)?),
+            SnapMessageID::GetTrieNodes => SnapMessage::GetTrieNodes(GetTrieNodes::decode(buf)?),
+            SnapMessageID::TrieNodes => SnapMessage::TrieNodes(TrieNodes::decode(buf)?),
+        };
+        Ok(message)
+    }
+
+    /// Encodes the message into bytes, prepended by the message type.
+    pub fn encode_message(&self, out: &mut dyn BufMut) {
+        self.message_id().encode(out);
+        match self {
+            SnapMessage::GetAccountRange(msg) => msg.encode(out),
+            SnapMessage::AccountRange(msg) => msg.encode(out),
+            SnapMessage::GetStorageRanges(msg) => msg.encode(out),
+            SnapMessage::StorageRanges(msg) => msg.encode(out),
+            SnapMessage::GetByteCodes(msg) => msg.encode(out),
+            SnapMessage::ByteCodes(msg) => msg.encode(out),
+            SnapMessage::GetTrieNodes(msg) => msg.encode(out),
+            SnapMessage::TrieNodes(msg) => msg.encode(out),
+        }
+    }
+}
+
+/// Represents message IDs for `snap` protocol messages.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SnapMessageID {
+    GetAccountRange = 0x00,
+    AccountRange = 0x01,
+    GetStorageRanges = 0x02,
+    StorageRanges = 0x03,
+    GetByteCodes = 0x04,
+    ByteCodes = 0x05,
+    GetTrieNodes = 0x06,
+    TrieNodes = 0x07,
+}
+
+impl Encodable for SnapMessageID {
+    fn encode(&self, out: &mut dyn BufMut) {
+        out.put_u8(*self as u8);
+    }
+    fn length(&self) -> usize {
+        1
+    }
+}
+
+impl Decodable for SnapMessageID {
+    fn decode(buf: &mut &[u8]) -> Result<Self, reth_rlp::DecodeError> {
+        let id = buf.first().ok_or(reth_rlp::DecodeError::InputTooShort)?;
+        let id = match id {
+            0x00 => SnapMessageID::GetAccountRange,
+            0x01 => SnapMessageID::AccountRange,
+            0x02 => SnapMessageID::GetStorageRanges,
+            0x03 => SnapMessageID::StorageRanges,
+            0x04 => SnapMessageID::GetByteCodes,
+            0x05 => SnapMessageID::ByteCodes,
+            0x06 => SnapMessageID::GetTrieNodes,
+            0x07 => SnapMessageID::TrieNodes,
+            _ => return Err(reth_rlp::DecodeError::Custom("Invalid message ID")),
+        };
+        buf.advance(1);
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn roundtrip(message: SnapMessage) {
+        let mut buf = vec![];
+        message.encode_message(&mut buf);
+        assert_eq!(buf[0], message.message_id() as u8);
+        assert_eq!(SnapMessage::decode_message(&mut &buf[..]).unwrap(), message);
+    }
+
+    #[test]
+    fn snap_messages_roundtrip() {
+        let hash = H256::repeat_byte(0x11);
+        roundtrip(SnapMessage::GetAccountRange(GetAccountRange {
+            request_id: 1,
+            root_hash: hash,
+            starting_hash: H256::zero(),
+            limit_hash: H256::repeat_byte(0xff),
+            response_bytes: 512 * 1024,
+        }));
+        roundtrip(SnapMessage::AccountRange(AccountRange {
+            request_id: 2,
+            accounts: vec![AccountData {
+                hash,
+                body: SlimAccount::new(1, U256::from(2), EMPTY_ROOT, hash),
+            }],
+            proof: vec![Bytes::from(vec![0xc0])],
+        }));
+        roundtrip(SnapMessage::GetStorageRanges(GetStorageRanges {
+            request_id: 3,
+            root_hash: hash,
+            account_hashes: vec![hash, H256::repeat_byte(0x22)],
+            starting_hash: Bytes::default(),
+            limit_hash: Bytes::from(hash.as_bytes()),
+            response_bytes: 1024,
+        }));
+        roundtrip(SnapMessage::StorageRanges(StorageRanges {
+            request_id: 4,
+            slots: vec![vec![StorageData { hash, data: Bytes::from(vec![0x01]) }], vec![]],
+            proof: vec![],
+        }));
+        roundtrip(SnapMessage::GetByteCodes(GetByteCodes {
+            request_id: 5,
+            hashes: vec![hash],
+            response_bytes: 1024,
+        }));
+        roundtrip(SnapMessage::ByteCodes(ByteCodes {
+            request_id: 6,
+            codes: vec![Bytes::from(vec![0x60, 0x00])],
+        }));
+        roundtrip(SnapMessage::GetTrieNodes(GetTrieNodes {
+            request_id: 7,
+            root_hash: hash,
+            paths: vec![
+                vec![Bytes::from(vec![0x00])],
+                vec![Bytes::from(vec![0x11]), Bytes::default()],
+            ],
+            response_bytes: 1024,
+        }));
+        roundtrip(SnapMessage::TrieNodes(TrieNodes { request_id: 8, nodes: vec![] }));
+    }
+
+    #[test]
+    fn slim_account_omits_empty_hashes() {
+        let empty = SlimAccount::new(0, U256::ZERO, EMPTY_ROOT, KECCAK_EMPTY);
+        assert!(empty.storage_root.is_empty());
+        assert!(empty.code_hash.is_empty());
+
+        let hash = H256::repeat_byte(0x11);
+        let account = SlimAccount::new(0, U256::ZERO, hash, hash);
+        assert_eq!(account.storage_root.as_ref(), hash.as_bytes());
+        assert_eq!(account.code_hash.as_ref(), hash.as_bytes());
+    }
+}
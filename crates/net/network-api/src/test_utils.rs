@@ -1,6 +1,6 @@
 use crate::{
-    EthProtocolInfo, NetworkError, NetworkInfo, NetworkStatus, PeerKind, Peers, PeersInfo,
-    ReputationChangeKind,
+    EthProtocolInfo, NetworkError, NetworkInfo, NetworkStatus, PeerInfo, PeerKind, Peers,
+    PeersInfo, ReputationChangeKind,
 };
 use async_trait::async_trait;
 use reth_eth_wire::{DisconnectReason, ProtocolVersion};
@@ -35,6 +35,10 @@ impl NetworkInfo for NoopNetwork {
     fn chain_id(&self) -> u64 {
         Mainnet.into()
     }
+
+    fn is_syncing(&self) -> bool {
+        false
+    }
 }
 
 impl PeersInfo for NoopNetwork {
@@ -47,6 +51,7 @@ impl PeersInfo for NoopNetwork {
     }
 }
 
+#[async_trait]
 impl Peers for NoopNetwork {
     fn add_peer_kind(&self, _peer: PeerId, _kind: PeerKind, _addr: SocketAddr) {}
 
@@ -57,4 +62,12 @@ impl Peers for NoopNetwork {
     fn disconnect_peer_with_reason(&self, _peer: PeerId, _reason: DisconnectReason) {}
 
     fn reputation_change(&self, _peer_id: PeerId, _kind: ReputationChangeKind) {}
+
+    async fn get_peers(&self) -> Result<Vec<PeerInfo>, NetworkError> {
+        Ok(vec![])
+    }
+
+    async fn get_peer_by_id(&self, _peer_id: PeerId) -> Result<Option<PeerInfo>, NetworkError> {
+        Ok(None)
+    }
 }
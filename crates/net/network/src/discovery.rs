@@ -3,10 +3,11 @@
 use crate::error::NetworkError;
 use futures::StreamExt;
 use reth_discv4::{DiscoveryUpdate, Discv4, Discv4Config};
+use reth_discv5::{Discv5, Discv5Config, Discv5Update};
 use reth_dns_discovery::{
     DnsDiscoveryConfig, DnsDiscoveryHandle, DnsDiscoveryService, DnsNodeRecordUpdate, DnsResolver,
 };
-use reth_primitives::{ForkId, NodeRecord, PeerId};
+use reth_primitives::{ForkFilter, ForkId, Head, NodeRecord, PeerId};
 use secp256k1::SecretKey;
 use std::{
     collections::{hash_map::Entry, HashMap, VecDeque},
@@ -33,6 +34,12 @@ pub struct Discovery {
     discv4_updates: Option<ReceiverStream<DiscoveryUpdate>>,
     /// The handle to the spawned discv4 service
     _discv4_service: Option<JoinHandle<()>>,
+    /// Handler to interact with the Discovery v5 service
+    discv5: Option<Discv5>,
+    /// All `eth` nodes discovered by the discv5 service.
+    discv5_updates: Option<ReceiverStream<Discv5Update>>,
+    /// The handle to the spawned discv5 service
+    _discv5_service: Option<JoinHandle<()>>,
     /// Handler to interact with the DNS discovery service
     _dns_discovery: Option<DnsDiscoveryHandle>,
     /// Updates from the DNS discovery service.
@@ -48,10 +55,14 @@ impl Discovery {
     ///
     /// This will spawn the [`reth_discv4::Discv4Service`] onto a new task and establish a listener
     /// channel to receive all discovered nodes.
+    ///
+    /// If configured, the [`reth_discv5::Discv5Service`] is spawned as well, and only yields the
+    /// nodes whose fork id passes the given [`ForkFilter`].
     pub async fn new(
         discovery_addr: SocketAddr,
         sk: SecretKey,
         discv4_config: Option<Discv4Config>,
+        discv5_config: Option<(Discv5Config, ForkFilter)>,
         dns_discovery_config: Option<DnsDiscoveryConfig>,
     ) -> Result<Self, NetworkError> {
         // setup discv4
@@ -69,6 +80,17 @@ impl Discovery {
             (None, None, None)
         };
 
+        // setup discv5
+        let (discv5, discv5_updates, _discv5_service) =
+            if let Some((config, fork_filter)) = discv5_config {
+                let (discv5, mut discv5_service) = Discv5::start(&sk, config, fork_filter).await?;
+                let discv5_updates = discv5_service.update_stream();
+                let _discv5_service = discv5_service.spawn();
+                (Some(discv5), Some(discv5_updates), Some(_discv5_service))
+            } else {
+                (None, None, None)
+            };
+
         // setup DNS discovery
         let (_dns_discovery, dns_discovery_updates, _dns_disc_service) =
             if let Some(dns_config) = dns_discovery_config {
@@ -88,6 +110,9 @@ impl Discovery {
             discv4,
             discv4_updates,
             _discv4_service,
+            discv5,
+            discv5_updates,
+            _discv5_service,
             discovered_nodes: Default::default(),
             queued_events: Default::default(),
             _dns_disc_service,
@@ -96,12 +121,21 @@ impl Discovery {
         })
     }
 
-    /// Updates the `eth:ForkId` field in discv4.
-    #[allow(unused)]
+    /// Updates the `eth:ForkId` field in discv4 and discv5.
     pub(crate) fn update_fork_id(&self, fork_id: ForkId) {
         if let Some(discv4) = &self.discv4 {
             discv4.set_eip868_rlp("eth".as_bytes().to_vec(), fork_id)
         }
+        if let Some(discv5) = &self.discv5 {
+            discv5.set_fork_id(fork_id)
+        }
+    }
+
+    /// Updates the head the discv5 nodes are filtered with.
+    pub(crate) fn update_head(&self, head: Head) {
+        if let Some(discv5) = &self.discv5 {
+            discv5.set_head(head)
+        }
     }
 
     /// Bans the [`IpAddr`] in the discovery service.
@@ -176,6 +210,12 @@ impl Discovery {
                 self.on_discv4_update(update)
             }
 
+            while let Some(Poll::Ready(Some(update))) =
+                self.discv5_updates.as_mut().map(|updates| updates.poll_next_unpin(cx))
+            {
+                self.on_node_record_update(update.node_record, Some(update.fork_id));
+            }
+
             while let Some(Poll::Ready(Some(update))) =
                 self.dns_discovery_updates.as_mut().map(|updates| updates.poll_next_unpin(cx))
             {
@@ -207,6 +247,9 @@ impl Discovery {
             discv4_updates: Default::default(),
             queued_events: Default::default(),
             _discv4_service: Default::default(),
+            discv5: None,
+            discv5_updates: None,
+            _discv5_service: None,
             _dns_discovery: None,
             dns_discovery_updates: None,
             _dns_disc_service: None,
@@ -234,9 +277,14 @@ mod tests {
         let mut rng = thread_rng();
         let (secret_key, _) = SECP256K1.generate_keypair(&mut rng);
         let discovery_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
-        let _discovery =
-            Discovery::new(discovery_addr, secret_key, Default::default(), Default::default())
-                .await
-                .unwrap();
+        let _discovery = Discovery::new(
+            discovery_addr,
+            secret_key,
+            Default::default(),
+            None,
+            Default::default(),
+        )
+        .await
+        .unwrap();
     }
 }
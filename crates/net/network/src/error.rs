@@ -17,6 +17,9 @@ pub enum NetworkError {
     /// IO error when creating the discovery service
     #[error("Failed to launch discovery service: {0}")]
     Discovery(io::Error),
+    /// Error when creating the discv5 service
+    #[error(transparent)]
+    Discv5(#[from] reth_discv5::Discv5Error),
     /// Error when setting up the DNS resolver failed
     ///
     /// See also [DnsResolver](reth_dns_discovery::DnsResolver::from_system_conf)
@@ -133,6 +133,7 @@ mod metrics;
 mod network;
 pub mod peers;
 mod session;
+pub mod snap_requests;
 mod state;
 mod swarm;
 pub mod transactions;
@@ -143,7 +144,7 @@ pub use fetch::FetchClient;
 pub use manager::{NetworkEvent, NetworkManager};
 pub use message::PeerRequest;
 pub use network::NetworkHandle;
-pub use peers::PeersConfig;
+pub use peers::{BannedEntry, BannedPeers, PeersConfig, PersistedPeer};
 pub use session::PeerInfo;
 
 pub use reth_eth_wire::DisconnectReason;
@@ -25,7 +25,7 @@ use crate::{
     message::{NewBlockMessage, PeerMessage, PeerRequest, PeerRequestSender},
     metrics::NetworkMetrics,
     network::{NetworkHandle, NetworkHandleMessage},
-    peers::{PeersHandle, PeersManager},
+    peers::{BannedPeers, PeersHandle, PeersManager, PersistedPeer},
     session::SessionManager,
     state::NetworkState,
     swarm::{NetworkConnectionState, Swarm, SwarmEvent},
@@ -34,6 +34,7 @@ use crate::{
 };
 use futures::{Future, StreamExt};
 use parking_lot::Mutex;
+use reth_discv5::Discv5Config;
 use reth_eth_wire::{
     capability::{Capabilities, CapabilityMessage},
     DisconnectReason, Status,
@@ -150,6 +151,7 @@ where
             client,
             secret_key,
             mut discovery_v4_config,
+            discovery_v5_config,
             discovery_addr,
             listener_addr,
             peers_config,
@@ -179,9 +181,24 @@ where
             disc_config
         });
 
-        let discovery =
-            Discovery::new(discovery_addr, secret_key, discovery_v4_config, dns_discovery_config)
-                .await?;
+        let discovery_v5_config = discovery_v5_config.map(|disc_config| {
+            // advertise our fork id and RLPx port
+            let disc_config = Discv5Config {
+                fork_id: Some(status.forkid),
+                tcp_port: incoming.local_address().port(),
+                ..disc_config
+            };
+            (disc_config, fork_filter.clone())
+        });
+
+        let discovery = Discovery::new(
+            discovery_addr,
+            secret_key,
+            discovery_v4_config,
+            discovery_v5_config,
+            dns_discovery_config,
+        )
+        .await?;
         // need to retrieve the addr here since provided port could be `0`
         let local_peer_id = discovery.local_id();
 
@@ -300,6 +317,16 @@ where
         self.swarm.state().peers().iter_peers()
     }
 
+    /// Returns the peers to persist across restarts, see [`PersistedPeer`].
+    pub fn persisted_peers(&self) -> impl Iterator<Item = PersistedPeer> + '_ {
+        self.swarm.state().peers().persisted_peers()
+    }
+
+    /// Returns all peers and ip addresses that are currently banned.
+    pub fn banned_peers(&self) -> BannedPeers {
+        self.swarm.state().peers().banned_peers()
+    }
+
     /// Returns a new [`PeersHandle`] that can be cloned and shared.
     ///
     /// The [`PeersHandle`] can be used to interact with the network's peer set.
@@ -539,6 +566,7 @@ where
                 let _ = tx.send(self.status());
             }
             NetworkHandleMessage::StatusUpdate { head } => {
+                self.swarm.state_mut().update_head(head);
                 if let Some(transition) = self.swarm.sessions_mut().on_status_update(head) {
                     self.swarm.state_mut().update_fork_id(transition.current);
                 }
@@ -7,11 +7,11 @@ use futures::FutureExt;
 use reth_eth_wire::{
     capability::RawCapabilityMessage, message::RequestPair, BlockBodies, BlockBody, BlockHeaders,
     EthMessage, GetBlockBodies, GetBlockHeaders, GetNodeData, GetPooledTransactions, GetReceipts,
-    NewBlock, NewBlockHashes, NewPooledTransactionHashes, NodeData, PooledTransactions, Receipts,
+    NewBlock, NewBlockHashes, NodeData, PooledTransactionHashes, PooledTransactions, Receipts,
     SharedTransactions, Transactions,
 };
 use reth_interfaces::p2p::error::{RequestError, RequestResult};
-use reth_primitives::{Bytes, Header, PeerId, Receipt, TransactionSigned, H256};
+use reth_primitives::{Bytes, Header, PeerId, PooledTransactionsElement, Receipt, H256};
 use std::{
     fmt,
     sync::Arc,
@@ -50,7 +50,9 @@ pub enum PeerMessage {
     /// Broadcast transactions _from_ local _to_ a peer.
     SendTransactions(SharedTransactions),
     /// Send new pooled transactions
-    PooledTransactions(NewPooledTransactionHashes),
+    ///
+    /// Sessions announce them in the form of their `eth` version.
+    PooledTransactions(PooledTransactionHashes),
     /// All `eth` request variants.
     EthRequest(PeerRequest),
     /// Other than eth namespace message
@@ -197,7 +199,7 @@ impl PeerResponse {
 pub enum PeerResponseResult {
     BlockHeaders(RequestResult<Vec<Header>>),
     BlockBodies(RequestResult<Vec<BlockBody>>),
-    PooledTransactions(RequestResult<Vec<TransactionSigned>>),
+    PooledTransactions(RequestResult<Vec<PooledTransactionsElement>>),
     NodeData(RequestResult<Vec<Bytes>>),
     Receipts(RequestResult<Vec<Vec<Receipt>>>),
 }
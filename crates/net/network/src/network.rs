@@ -1,19 +1,20 @@
 use crate::{
     config::NetworkMode, manager::NetworkEvent, message::PeerRequest, peers::PeersHandle,
-    session::PeerInfo, FetchClient,
+    FetchClient,
 };
 use async_trait::async_trait;
 use parking_lot::Mutex;
-use reth_eth_wire::{DisconnectReason, NewBlock, NewPooledTransactionHashes, SharedTransactions};
+use reth_eth_wire::{DisconnectReason, NewBlock, PooledTransactionHashes, SharedTransactions};
 use reth_interfaces::{
     p2p::headers::client::StatusUpdater,
     sync::{SyncState, SyncStateProvider, SyncStateUpdater},
 };
 use reth_net_common::bandwidth_meter::BandwidthMeter;
 use reth_network_api::{
-    NetworkError, NetworkInfo, NetworkStatus, PeerKind, Peers, PeersInfo, ReputationChangeKind,
+    NetworkError, NetworkInfo, NetworkStatus, PeerInfo, PeerKind, Peers, PeersInfo,
+    ReputationChangeKind,
 };
-use reth_primitives::{Head, NodeRecord, PeerId, TransactionSigned, TxHash, H256};
+use reth_primitives::{Head, NodeRecord, PeerId, TransactionSigned, H256};
 use std::{
     net::SocketAddr,
     sync::{
@@ -94,25 +95,6 @@ impl NetworkHandle {
         rx.await
     }
 
-    /// Returns [`PeerInfo`] for all connected peers
-    pub async fn get_peers(&self) -> Result<Vec<PeerInfo>, oneshot::error::RecvError> {
-        let (tx, rx) = oneshot::channel();
-        let _ = self.manager().send(NetworkHandleMessage::GetPeerInfo(tx));
-        rx.await
-    }
-
-    /// Returns [`PeerInfo`] for a given peer.
-    ///
-    /// Returns `None` if there's no active session to the peer.
-    pub async fn get_peer_by_id(
-        &self,
-        peer_id: PeerId,
-    ) -> Result<Option<PeerInfo>, oneshot::error::RecvError> {
-        let (tx, rx) = oneshot::channel();
-        let _ = self.manager().send(NetworkHandleMessage::GetPeerInfoById(peer_id, tx));
-        rx.await
-    }
-
     /// Returns the mode of the network, either pow, or pos
     pub fn mode(&self) -> &NetworkMode {
         &self.inner.network_mode
@@ -141,11 +123,11 @@ impl NetworkHandle {
     }
 
     /// Send transactions hashes to the peer.
-    pub fn send_transactions_hashes(&self, peer_id: PeerId, msg: Vec<TxHash>) {
-        self.send_message(NetworkHandleMessage::SendPooledTransactionHashes {
-            peer_id,
-            msg: NewPooledTransactionHashes(msg),
-        })
+    ///
+    /// `eth/68` peers also need the types and sizes of the transactions, so only the `eth/68`
+    /// form is announced to them, see [`PooledTransactionHashes::new`].
+    pub fn send_transactions_hashes(&self, peer_id: PeerId, msg: PooledTransactionHashes) {
+        self.send_message(NetworkHandleMessage::SendPooledTransactionHashes { peer_id, msg })
     }
 
     /// Send full transactions to the peer
@@ -186,6 +168,7 @@ impl PeersInfo for NetworkHandle {
     }
 }
 
+#[async_trait]
 impl Peers for NetworkHandle {
     /// Sends a message to the [`NetworkManager`](crate::NetworkManager) to add a peer to the known
     /// set, with the given kind.
@@ -215,6 +198,22 @@ impl Peers for NetworkHandle {
     fn reputation_change(&self, peer_id: PeerId, kind: ReputationChangeKind) {
         self.send_message(NetworkHandleMessage::ReputationChange(peer_id, kind));
     }
+
+    /// Returns [`PeerInfo`] for all connected peers
+    async fn get_peers(&self) -> Result<Vec<PeerInfo>, NetworkError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.manager().send(NetworkHandleMessage::GetPeerInfo(tx));
+        rx.await.map_err(Into::into)
+    }
+
+    /// Returns [`PeerInfo`] for a given peer.
+    ///
+    /// Returns `None` if there's no active session to the peer.
+    async fn get_peer_by_id(&self, peer_id: PeerId) -> Result<Option<PeerInfo>, NetworkError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.manager().send(NetworkHandleMessage::GetPeerInfoById(peer_id, tx));
+        rx.await.map_err(Into::into)
+    }
 }
 
 #[async_trait]
@@ -232,6 +231,10 @@ impl NetworkInfo for NetworkHandle {
     fn chain_id(&self) -> u64 {
         self.inner.chain_id.load(Ordering::Relaxed)
     }
+
+    fn is_syncing(&self) -> bool {
+        SyncStateProvider::is_syncing(self)
+    }
 }
 
 impl StatusUpdater for NetworkHandle {
@@ -292,7 +295,7 @@ pub(crate) enum NetworkHandleMessage {
     /// Sends the list of transactions to the given peer.
     SendTransaction { peer_id: PeerId, msg: SharedTransactions },
     /// Sends the list of transactions hashes to the given peer.
-    SendPooledTransactionHashes { peer_id: PeerId, msg: NewPooledTransactionHashes },
+    SendPooledTransactionHashes { peer_id: PeerId, msg: PooledTransactionHashes },
     /// Send an `eth` protocol request to the peer.
     EthRequest {
         /// The peer to send the request to.
@@ -121,6 +121,7 @@ impl PeersManager {
             trusted_nodes,
             connect_trusted_nodes_only,
             basic_nodes,
+            persisted_peers,
             ..
         } = config;
         let (manager_tx, handle_rx) = mpsc::unbounded_channel();
@@ -129,7 +130,8 @@ impl PeersManager {
         // We use half of the interval to decrease the max duration to `150%` in worst case
         let unban_interval = ban_duration.min(backoff_durations.low) / 2;
 
-        let mut peers = HashMap::with_capacity(trusted_nodes.len() + basic_nodes.len());
+        let mut peers =
+            HashMap::with_capacity(trusted_nodes.len() + basic_nodes.len() + persisted_peers.len());
 
         for NodeRecord { address, tcp_port, udp_port: _, id } in trusted_nodes {
             peers.entry(id).or_insert_with(|| Peer::trusted(SocketAddr::from((address, tcp_port))));
@@ -139,6 +141,19 @@ impl PeersManager {
             peers.entry(id).or_insert_with(|| Peer::new(SocketAddr::from((address, tcp_port))));
         }
 
+        // the peers of the previous run keep their reputation, so the good ones are dialed first
+        for PersistedPeer { record, reputation, last_seen } in persisted_peers {
+            if is_banned_reputation(reputation) {
+                continue
+            }
+            let addr = SocketAddr::from((record.address, record.tcp_port));
+            peers.entry(record.id).or_insert_with(|| Peer {
+                reputation,
+                last_seen,
+                ..Peer::new(addr)
+            });
+        }
+
         Self {
             peers,
             manager_tx,
@@ -174,10 +189,46 @@ impl PeersManager {
         self.peers.iter().map(|(peer_id, v)| NodeRecord::new(v.addr, *peer_id))
     }
 
+    /// Returns the peers to persist across restarts, with their reputation and the last time a
+    /// session to them was active. Peers with a banned reputation are skipped.
+    pub(crate) fn persisted_peers(&self) -> impl Iterator<Item = PersistedPeer> + '_ {
+        let now = unix_timestamp();
+        self.peers.iter().filter(|(_, peer)| !peer.is_banned()).map(move |(peer_id, peer)| {
+            PersistedPeer {
+                record: NodeRecord::new(peer.addr, *peer_id),
+                reputation: peer.reputation,
+                last_seen: if peer.state.is_connected() { Some(now) } else { peer.last_seen },
+            }
+        })
+    }
+
+    /// Returns all peers and ip addresses that are currently banned, with the unix timestamps
+    /// their bans expire at.
+    pub(crate) fn banned_peers(&self) -> BannedPeers {
+        let (now, unix_now) = (std::time::Instant::now(), unix_timestamp());
+        let expiry = |until: Option<std::time::Instant>| {
+            until.map(|until| unix_now + until.saturating_duration_since(now).as_secs())
+        };
+        BannedPeers {
+            peers: self
+                .ban_list
+                .banned_peers_with_timeout()
+                .map(|(entry, until)| BannedEntry { entry, until: expiry(until) })
+                .collect(),
+            ips: self
+                .ban_list
+                .banned_ips_with_timeout()
+                .map(|(entry, until)| BannedEntry { entry, until: expiry(until) })
+                .collect(),
+        }
+    }
+
     /// Invoked when a new _incoming_ tcp connection is accepted.
     ///
     /// returns an error if the inbound ip address is on the ban list or
-    /// we have reached our limit for max inbound connections
+    /// we have reached our limit for max inbound connections.
+    ///
+    /// Connections from the ip address of a trusted peer are accepted regardless of the limit.
     pub(crate) fn on_incoming_pending_session(
         &mut self,
         addr: IpAddr,
@@ -185,7 +236,7 @@ impl PeersManager {
         if self.ban_list.is_banned_ip(&addr) {
             return Err(InboundConnectionError::IpBanned)
         }
-        if !self.connection_info.has_in_capacity() {
+        if !self.connection_info.has_in_capacity() && !self.is_trusted_ip(&addr) {
             return Err(InboundConnectionError::ExceedsLimit(self.connection_info.max_inbound))
         }
         // keep track of new connection
@@ -193,6 +244,11 @@ impl PeersManager {
         Ok(())
     }
 
+    /// Returns `true` if the ip address belongs to a trusted peer.
+    fn is_trusted_ip(&self, addr: &IpAddr) -> bool {
+        self.peers.values().any(|peer| peer.is_trusted() && peer.addr.ip() == *addr)
+    }
+
     /// Invoked when a previous call to [Self::on_incoming_pending_session] succeeded but it was
     /// rejected.
     pub(crate) fn on_incoming_pending_session_rejected_internally(&mut self) {
@@ -239,7 +295,8 @@ impl PeersManager {
     /// This will update the state of the peer if not yet tracked.
     ///
     /// If the reputation of the peer is below the `BANNED_REPUTATION` threshold, a disconnect will
-    /// be scheduled.
+    /// be scheduled. If `connect_trusted_nodes_only` is enabled, untrusted peers are disconnected
+    /// as well.
     pub(crate) fn on_active_inbound_session(&mut self, peer_id: PeerId, addr: SocketAddr) {
         // we only need to check the peer id here as the ip address will have been checked at
         // on_inbound_pending_session
@@ -248,6 +305,18 @@ impl PeersManager {
             return
         }
 
+        if self.connect_trusted_nodes_only &&
+            !matches!(self.peers.get(&peer_id), Some(peer) if peer.is_trusted())
+        {
+            // the session is not tracked, so its slot is released right away
+            self.connection_info.decr_in();
+            self.queued_actions.push_back(PeerAction::Disconnect {
+                peer_id,
+                reason: Some(DisconnectReason::UselessPeer),
+            });
+            return
+        }
+
         match self.peers.entry(peer_id) {
             Entry::Occupied(mut entry) => {
                 let value = entry.get_mut();
@@ -256,9 +325,11 @@ impl PeersManager {
                     return
                 }
                 value.state = PeerConnectionState::In;
+                value.last_seen = Some(unix_timestamp());
             }
             Entry::Vacant(entry) => {
-                entry.insert(Peer::with_state(addr, PeerConnectionState::In));
+                let peer = entry.insert(Peer::with_state(addr, PeerConnectionState::In));
+                peer.last_seen = Some(unix_timestamp());
                 self.queued_actions.push_back(PeerAction::PeerAdded(peer_id));
             }
         }
@@ -288,13 +359,18 @@ impl PeersManager {
         self.queued_actions.push_back(PeerAction::UnBanPeer { peer_id });
     }
 
-    /// Apply the corresponding reputation change to the given peer
+    /// Apply the corresponding reputation change to the given peer.
+    ///
+    /// Trusted peers are exempt from reputation changes, so they are never banned because of them.
     pub(crate) fn apply_reputation_change(&mut self, peer_id: &PeerId, rep: ReputationChangeKind) {
         let reputation_change = self.reputation_weights.change(rep);
-        let outcome = if let Some(peer) = self.peers.get_mut(peer_id) {
-            peer.apply_reputation(reputation_change.as_i32())
-        } else {
-            return
+        let outcome = match self.peers.get_mut(peer_id) {
+            Some(peer) if peer.is_trusted() => {
+                trace!(target: "net::peers", ?peer_id, ?rep, "ignoring reputation change of trusted peer");
+                return
+            }
+            Some(peer) => peer.apply_reputation(reputation_change.as_i32()),
+            None => return,
         };
 
         match outcome {
@@ -349,6 +425,7 @@ impl PeersManager {
                     // session to that peer
                     entry.get_mut().backoff_counter = 0;
                     entry.get_mut().state = PeerConnectionState::Idle;
+                    entry.get_mut().last_seen = Some(unix_timestamp());
                     return
                 }
             }
@@ -432,7 +509,10 @@ impl PeersManager {
 
                 self.connection_info.decr_state(peer.state);
                 peer.state = PeerConnectionState::Idle;
-                peer.reputation = peer.reputation.saturating_add(reputation_change.as_i32());
+                // trusted peers are exempt from reputation changes, they're only backed off
+                if !peer.is_trusted() {
+                    peer.reputation = peer.reputation.saturating_add(reputation_change.as_i32());
+                }
             }
             if let Some(backoff_until) = backoff_until {
                 self.backoff_peer_until(*peer_id, backoff_until);
@@ -558,16 +638,18 @@ impl PeersManager {
     /// Returns the idle peer with the highest reputation.
     ///
     /// Peers that are `trusted`, see [PeerKind], are prioritized as long as they're not currently
-    /// marked as banned. Peers with a `forkId` are considered better than peers without.
+    /// marked as banned or backed off. Peers with a `forkId` are considered better than peers
+    /// without.
     ///
     /// If `connect_trusted_nodes_only` is enabled, see [PeersConfig], then this will only consider
     /// `trusted` peers.
     ///
     /// Returns `None` if no peer is available.
     fn best_unconnected(&mut self) -> Option<(PeerId, &mut Peer)> {
-        let mut unconnected = self.peers.iter_mut().filter(|(_, peer)| {
+        let mut unconnected = self.peers.iter_mut().filter(|(peer_id, peer)| {
             peer.state.is_unconnected() &&
                 !peer.is_banned() &&
+                !self.ban_list.is_banned(peer_id, &peer.addr.ip()) &&
                 (!self.connect_trusted_nodes_only || peer.is_trusted())
         });
 
@@ -601,7 +683,7 @@ impl PeersManager {
     /// [`PeerAction::Connect`] actions.
     ///
     /// New connections are only initiated, if slots are available and appropriate peers are
-    /// available.
+    /// available. Trusted peers are connected to, even if there are no free slots.
     fn fill_outbound_slots(&mut self) {
         // as long as there a slots available try to fill them with the best peers
         while self.connection_info.has_out_capacity() {
@@ -625,6 +707,23 @@ impl PeersManager {
             self.connection_info.inc_out();
             self.queued_actions.push_back(action);
         }
+
+        // trusted peers bypass the slot limit, but not the backoff
+        for (peer_id, peer) in self.peers.iter_mut() {
+            if !peer.is_trusted() ||
+                !peer.state.is_unconnected() ||
+                peer.is_banned() ||
+                self.ban_list.is_banned(peer_id, &peer.addr.ip())
+            {
+                continue
+            }
+            trace!(target : "net::peers",  ?peer_id, addr=?peer.addr, "schedule outbound connection to trusted peer");
+
+            peer.state = PeerConnectionState::Out;
+            self.connection_info.inc_out();
+            self.queued_actions
+                .push_back(PeerAction::Connect { peer_id: *peer_id, remote_addr: peer.addr });
+        }
     }
 
     /// Advances the state.
@@ -768,6 +867,8 @@ pub struct Peer {
     kind: PeerKind,
     /// Counts number of times the peer was backed off   
     backoff_counter: u32,
+    /// Unix timestamp of the last time a session to the peer was active, if any.
+    last_seen: Option<u64>,
 }
 
 // === impl Peer ===
@@ -790,6 +891,7 @@ impl Peer {
             remove_after_disconnect: false,
             kind: Default::default(),
             backoff_counter: 0,
+            last_seen: None,
         }
     }
 
@@ -956,6 +1058,9 @@ pub struct PeersConfig {
     /// Basic nodes to connect to.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub basic_nodes: HashSet<NodeRecord>,
+    /// Peers of a previous run to connect to, with their reputation.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub persisted_peers: Vec<PersistedPeer>,
     /// How long to ban bad peers.
     #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
     pub ban_duration: Duration,
@@ -984,6 +1089,7 @@ impl Default for PeersConfig {
             trusted_nodes: Default::default(),
             connect_trusted_nodes_only: false,
             basic_nodes: Default::default(),
+            persisted_peers: Default::default(),
         }
     }
 }
@@ -1035,23 +1141,124 @@ impl PeersConfig {
         self
     }
 
-    /// Read from file nodes available at launch. Ignored if None.
+    /// Peers of a previous run available at launch.
+    pub fn with_persisted_peers(mut self, peers: Vec<PersistedPeer>) -> Self {
+        self.persisted_peers = peers;
+        self
+    }
+
+    /// Read from file the peers of a previous run available at launch, see [`PersistedPeer`].
+    /// Ignored if None.
+    ///
+    /// Files that only list the node records of the peers are supported as well.
     pub fn with_basic_nodes_from_file(
         self,
         optional_file: Option<impl AsRef<Path>>,
     ) -> Result<Self, io::Error> {
-        let Some(file_path) = optional_file else {
-            return Ok(self)
-        };
+        let Some(file_path) = optional_file else { return Ok(self) };
         let reader = match std::fs::File::open(file_path.as_ref()) {
             Ok(file) => std::io::BufReader::new(file),
             Err(e) if e.kind() == ErrorKind::NotFound => return Ok(self),
             Err(e) => Err(e)?,
         };
         info!(target: "net::peers", file = %file_path.as_ref().display(), "Loading saved peers");
-        let nodes: HashSet<NodeRecord> = serde_json::from_reader(reader)?;
-        Ok(self.with_basic_nodes(nodes))
+        let peers: Vec<SavedPeer> = serde_json::from_reader(reader)?;
+        Ok(self.with_persisted_peers(peers.into_iter().map(Into::into).collect()))
     }
+
+    /// Read from file peers and ip addresses that were banned when the node was shut down, and ban
+    /// them again for the remainder of their bans. Ignored if None.
+    pub fn with_banned_peers_from_file(
+        mut self,
+        optional_file: Option<impl AsRef<Path>>,
+    ) -> Result<Self, io::Error> {
+        let Some(file_path) = optional_file else { return Ok(self) };
+        let reader = match std::fs::File::open(file_path.as_ref()) {
+            Ok(file) => std::io::BufReader::new(file),
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(self),
+            Err(e) => Err(e)?,
+        };
+        info!(target: "net::peers", file = %file_path.as_ref().display(), "Loading banned peers");
+        let banned: BannedPeers = serde_json::from_reader(reader)?;
+        let (now, unix_now) = (std::time::Instant::now(), unix_timestamp());
+        // bans that expired while the node was down are dropped
+        let remaining = |until: Option<u64>| match until {
+            Some(until) if until <= unix_now => None,
+            Some(until) => Some(Some(now + Duration::from_secs(until - unix_now))),
+            None => Some(None),
+        };
+        for BannedEntry { entry, until } in banned.peers {
+            if let Some(until) = remaining(until) {
+                self.ban_list.ban_peer_with(entry, until);
+            }
+        }
+        for BannedEntry { entry, until } in banned.ips {
+            if let Some(until) = remaining(until) {
+                self.ban_list.ban_ip_with(entry, until);
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// A known peer, as it is persisted across restarts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PersistedPeer {
+    /// Where to reach the peer.
+    pub record: NodeRecord,
+    /// Reputation of the peer when it was persisted.
+    pub reputation: i32,
+    /// Unix timestamp of the last time a session to the peer was active, if any.
+    pub last_seen: Option<u64>,
+}
+
+/// An entry of a peers file, which used to only hold the node records of the peers.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+enum SavedPeer {
+    Record(NodeRecord),
+    Peer(PersistedPeer),
+}
+
+impl From<SavedPeer> for PersistedPeer {
+    fn from(peer: SavedPeer) -> Self {
+        match peer {
+            SavedPeer::Record(record) => {
+                PersistedPeer { record, reputation: DEFAULT_REPUTATION, last_seen: None }
+            }
+            SavedPeer::Peer(peer) => peer,
+        }
+    }
+}
+
+/// Returns the current unix timestamp in seconds.
+fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Peers and ip addresses that are banned, e.g. to persist them across restarts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BannedPeers {
+    /// Banned peers.
+    pub peers: Vec<BannedEntry<PeerId>>,
+    /// Banned ip addresses.
+    pub ips: Vec<BannedEntry<IpAddr>>,
+}
+
+/// A banned peer or ip address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BannedEntry<T> {
+    /// The banned peer or ip address.
+    pub entry: T,
+    /// Unix timestamp in seconds at which the ban expires, `None` if it's banned indefinitely.
+    pub until: Option<u64>,
 }
 
 /// The durations to use when a backoff should be applied to a peer.
@@ -1121,7 +1328,11 @@ mod test {
     use crate::{
         error::BackoffKind,
         peers::{
-            manager::{ConnectionInfo, PeerBackoffDurations, PeerConnectionState},
+            manager::{
+                unix_timestamp, BannedEntry, BannedPeers, ConnectionInfo, PeerBackoffDurations,
+                PeerConnectionState,
+            },
+            reputation::DEFAULT_REPUTATION,
             PeerAction,
         },
         session::PendingSessionHandshakeError,
@@ -1712,6 +1923,87 @@ mod test {
         assert!(peer_manager.peers.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_persist_banned_peers() {
+        let peer = PeerId::random();
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2));
+        let expired_ip = IpAddr::V4(Ipv4Addr::new(127, 0, 1, 3));
+        let mut peers = PeersManager::default();
+        peers.ban_peer(peer);
+        peers.ban_ip(ip);
+
+        let mut banned = peers.banned_peers();
+        let until = unix_timestamp() + peers.ban_duration.as_secs();
+        assert_eq!(banned.peers.iter().map(|banned| banned.entry).collect::<Vec<_>>(), vec![peer]);
+        assert_eq!(banned.ips.iter().map(|banned| banned.entry).collect::<Vec<_>>(), vec![ip]);
+        assert!(
+            matches!(banned.peers[0].until, Some(expiry) if (until - 2..=until).contains(&expiry))
+        );
+        assert!(
+            matches!(banned.ips[0].until, Some(expiry) if (until - 2..=until).contains(&expiry))
+        );
+        banned.ips.push(BannedEntry { entry: expired_ip, until: Some(unix_timestamp() - 1) });
+        let expiring_peer = PeerId::random();
+        banned.peers.push(BannedEntry { entry: expiring_peer, until: Some(unix_timestamp() + 60) });
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("banned-peers.json");
+        std::fs::write(&file, serde_json::to_string(&banned).unwrap()).unwrap();
+
+        let config = PeersConfig::default().with_banned_peers_from_file(Some(&file)).unwrap();
+        let mut peers = PeersManager::new(config);
+        assert!(peers.ban_list.is_banned(&peer, &ip));
+        // the bans are restored with their remaining duration
+        let (_, until) = peers
+            .ban_list
+            .banned_peers_with_timeout()
+            .find(|(peer_id, _)| *peer_id == expiring_peer)
+            .unwrap();
+        assert!(until.unwrap() <= std::time::Instant::now() + Duration::from_secs(60));
+        assert!(!peers.ban_list.is_banned_ip(&expired_ip));
+        peers.add_peer(peer, SocketAddr::new(ip, 8008), None);
+        assert!(peers.peers.is_empty());
+
+        // a missing file is ignored
+        let config = PeersConfig::default()
+            .with_banned_peers_from_file(Some(dir.path().join("missing.json")))
+            .unwrap();
+        assert_eq!(config.ban_list, BanList::default());
+    }
+
+    #[tokio::test]
+    async fn test_persist_known_peers() {
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let mut peers = PeersManager::default();
+        peers.add_peer(peer, socket_addr, None);
+        peers.on_active_inbound_session(peer, socket_addr);
+        peers.apply_reputation_change(&peer, ReputationChangeKind::Timeout);
+        let reputation = peers.peers[&peer].reputation;
+
+        let persisted = peers.persisted_peers().collect::<Vec<_>>();
+        assert_eq!(persisted.len(), 1);
+        assert_eq!(persisted[0].record, NodeRecord::new(socket_addr, peer));
+        assert_eq!(persisted[0].reputation, reputation);
+        assert!(persisted[0].last_seen.is_some());
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("known-peers.json");
+        std::fs::write(&file, serde_json::to_string(&persisted).unwrap()).unwrap();
+        let config = PeersConfig::default().with_basic_nodes_from_file(Some(&file)).unwrap();
+        let peers = PeersManager::new(config);
+        assert_eq!(peers.peers[&peer].reputation, reputation);
+        assert_eq!(peers.peers[&peer].last_seen, persisted[0].last_seen);
+
+        // the files of the previous versions only hold the node records
+        let records = vec![NodeRecord::new(socket_addr, peer)];
+        std::fs::write(&file, serde_json::to_string(&records).unwrap()).unwrap();
+        let config = PeersConfig::default().with_basic_nodes_from_file(Some(&file)).unwrap();
+        let peers = PeersManager::new(config);
+        assert_eq!(peers.peers[&peer].reputation, DEFAULT_REPUTATION);
+        assert_eq!(peers.peers[&peer].last_seen, None);
+    }
+
     #[tokio::test]
     async fn test_on_pending_ban_list() {
         let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2));
@@ -1742,7 +2034,11 @@ mod test {
         let mut peer_manager = PeersManager::new(config);
         peer_manager.on_active_inbound_session(given_peer_id, socket_addr);
 
-        let Some(PeerAction::DisconnectBannedIncoming { peer_id }) = peer_manager.queued_actions.pop_front() else { panic!() };
+        let Some(PeerAction::DisconnectBannedIncoming { peer_id }) =
+            peer_manager.queued_actions.pop_front()
+        else {
+            panic!()
+        };
 
         assert_eq!(peer_id, given_peer_id)
     }
@@ -1860,4 +2156,117 @@ mod test {
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn test_trusted_peers_bypass_limits() {
+        let trusted_peer = PeerId::random();
+        let trusted_sock = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let config = PeersConfig::default()
+            .with_trusted_nodes(HashSet::from([NodeRecord {
+                address: IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)),
+                tcp_port: 8008,
+                udp_port: 8008,
+                id: trusted_peer,
+            }]))
+            .with_max_outbound(0)
+            .with_max_inbound(0);
+        let mut peers = PeersManager::new(config);
+
+        match event!(peers) {
+            PeerAction::Connect { peer_id, remote_addr } => {
+                assert_eq!(peer_id, trusted_peer);
+                assert_eq!(remote_addr, trusted_sock);
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(peers.num_outbound_connections(), 1);
+
+        // reputation changes don't ban trusted peers
+        peers.apply_reputation_change(&trusted_peer, ReputationChangeKind::BadProtocol);
+        assert!(!peers.peers.get(&trusted_peer).unwrap().is_banned());
+
+        assert!(peers.on_incoming_pending_session(trusted_sock.ip()).is_ok());
+        assert!(peers
+            .on_incoming_pending_session(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 3)))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_backoff_trusted_peer() {
+        let trusted_peer = PeerId::random();
+        let trusted_sock = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let backoff_durations =
+            PeerBackoffDurations { low: Duration::from_millis(200), ..Default::default() };
+        let config = PeersConfig { backoff_durations, ..Default::default() }
+            .with_trusted_nodes(HashSet::from([NodeRecord {
+                address: IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)),
+                tcp_port: 8008,
+                udp_port: 8008,
+                id: trusted_peer,
+            }]))
+            .with_max_outbound(0);
+        let mut peers = PeersManager::new(config);
+
+        match event!(peers) {
+            PeerAction::Connect { peer_id, .. } => {
+                assert_eq!(peer_id, trusted_peer);
+            }
+            _ => unreachable!(),
+        }
+
+        peers.on_active_session_dropped(
+            &trusted_sock,
+            &trusted_peer,
+            &EthStreamError::P2PStreamError(P2PStreamError::Disconnected(
+                DisconnectReason::TooManyPeers,
+            )),
+        );
+
+        // the trusted peer is not dialed again while it's backed off
+        poll_fn(|cx| {
+            assert!(peers.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+
+        assert!(peers.ban_list.is_banned_peer(&trusted_peer));
+        assert_eq!(peers.peers.get(&trusted_peer).unwrap().reputation, DEFAULT_REPUTATION);
+
+        tokio::time::sleep(backoff_durations.low).await;
+
+        match event!(peers) {
+            PeerAction::UnBanPeer { peer_id, .. } => {
+                assert_eq!(peer_id, trusted_peer);
+            }
+            _ => unreachable!(),
+        }
+
+        match event!(peers) {
+            PeerAction::Connect { peer_id, .. } => {
+                assert_eq!(peer_id, trusted_peer);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_untrusted_incoming_trusted_only() {
+        let config = PeersConfig::default().with_connect_trusted_nodes_only(true);
+        let mut peers = PeersManager::new(config);
+
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        peers.on_incoming_pending_session(socket_addr.ip()).unwrap();
+        peers.on_active_inbound_session(peer, socket_addr);
+
+        match event!(peers) {
+            PeerAction::Disconnect { peer_id, reason } => {
+                assert_eq!(peer_id, peer);
+                assert_eq!(reason, Some(DisconnectReason::UselessPeer));
+            }
+            _ => unreachable!(),
+        }
+        assert!(peers.peers.is_empty());
+        assert_eq!(peers.num_inbound_connections(), 0);
+    }
 }
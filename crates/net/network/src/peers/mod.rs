@@ -3,8 +3,8 @@
 mod manager;
 mod reputation;
 
+pub use manager::{BannedEntry, BannedPeers, Peer, PeersConfig, PeersHandle, PersistedPeer};
 pub(crate) use manager::{InboundConnectionError, PeerAction, PeersManager};
-pub use manager::{Peer, PeersConfig, PeersHandle};
 pub use reputation::ReputationChangeWeights;
 pub use reth_network_api::PeerKind;
 
@@ -16,7 +16,7 @@ use reth_eth_wire::{
     capability::Capabilities,
     errors::{EthHandshakeError, EthStreamError, P2PStreamError},
     message::{EthBroadcastMessage, RequestPair},
-    DisconnectReason, EthMessage, EthStream, P2PStream,
+    DisconnectReason, EthMessage, EthStream, EthVersion, P2PStream, PooledTransactionHashes,
 };
 use reth_interfaces::p2p::error::RequestError;
 use reth_metrics_common::metered_sender::MeteredSender;
@@ -179,7 +179,10 @@ impl ActiveSession {
                 self.try_emit_broadcast(PeerMessage::ReceivedTransaction(msg)).into()
             }
             EthMessage::NewPooledTransactionHashes(msg) => {
-                self.try_emit_broadcast(PeerMessage::PooledTransactions(msg)).into()
+                self.try_emit_broadcast(PeerMessage::PooledTransactions(msg.into())).into()
+            }
+            EthMessage::NewPooledTransactionHashes68(msg) => {
+                self.try_emit_broadcast(PeerMessage::PooledTransactions(msg.into())).into()
             }
             EthMessage::GetBlockHeaders(req) => {
                 on_request!(req, BlockHeaders, GetBlockHeaders)
@@ -237,7 +240,22 @@ impl ActiveSession {
                 self.queued_outgoing.push_back(EthBroadcastMessage::NewBlock(msg.block).into());
             }
             PeerMessage::PooledTransactions(msg) => {
-                self.queued_outgoing.push_back(EthMessage::NewPooledTransactionHashes(msg).into());
+                let msg = if self.conn.version() == EthVersion::Eth68 {
+                    match msg {
+                        PooledTransactionHashes::Eth68(msg) => {
+                            EthMessage::NewPooledTransactionHashes68(msg)
+                        }
+                        PooledTransactionHashes::Eth66(_) => {
+                            // eth/68 announcements also need the types and sizes of the
+                            // transactions
+                            trace!(target: "net::session", remote_peer_id=?self.remote_peer_id, "skipping pooled transaction hashes without types and sizes for eth/68 peer");
+                            return
+                        }
+                    }
+                } else {
+                    EthMessage::NewPooledTransactionHashes(msg.into())
+                };
+                self.queued_outgoing.push_back(msg.into());
             }
             PeerMessage::EthRequest(req) => {
                 let deadline = self.request_deadline();
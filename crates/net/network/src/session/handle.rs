@@ -63,6 +63,8 @@ pub(crate) struct ActiveSessionHandle {
     pub(crate) client_version: String,
     /// The address we're connected to
     pub(crate) remote_addr: SocketAddr,
+    /// The `Status` the peer sent during the handshake.
+    pub(crate) status: Status,
 }
 
 // === impl ActiveSessionHandle ===
@@ -75,21 +77,6 @@ impl ActiveSessionHandle {
     }
 }
 
-/// Info about an active peer session.
-#[derive(Debug, Clone)]
-pub struct PeerInfo {
-    /// Announced capabilities of the peer
-    pub capabilities: Arc<Capabilities>,
-    /// The identifier of the remote peer
-    pub remote_id: PeerId,
-    /// The client's name and version
-    pub client_version: String,
-    /// The address we're connected to
-    pub remote_addr: SocketAddr,
-    /// The direction of the session
-    pub direction: Direction,
-}
-
 /// Events a pending session can produce.
 ///
 /// This represents the state changes a session can undergo until it is ready to send capability messages <https://github.com/ethereum/devp2p/blob/6b0abc3d956a626c28dce1307ee9f546db17b6bd/rlpx.md>.
@@ -1,4 +1,5 @@
 //! Support for handling peer sessions.
+pub use crate::message::PeerRequestSender;
 use crate::{
     message::PeerMessage,
     session::{
@@ -10,7 +11,6 @@ use crate::{
         },
     },
 };
-pub use crate::{message::PeerRequestSender, session::handle::PeerInfo};
 use fnv::FnvHashMap;
 use futures::{future::Either, io, FutureExt, StreamExt};
 use reth_ecies::{stream::ECIESStream, ECIESError};
@@ -24,6 +24,7 @@ use reth_net_common::{
     bandwidth_meter::{BandwidthMeter, MeteredStream},
     stream::HasRemoteAddr,
 };
+pub use reth_network_api::{Direction, PeerInfo};
 use reth_primitives::{ForkFilter, ForkId, ForkTransition, Head, PeerId};
 use reth_tasks::TaskSpawner;
 use secp256k1::SecretKey;
@@ -466,6 +467,7 @@ impl SessionManager {
                     commands_to_session,
                     client_version: client_id,
                     remote_addr,
+                    status,
                 };
 
                 self.active_sessions.insert(peer_id, handle);
@@ -562,6 +564,7 @@ impl SessionManager {
                 remote_addr: session.remote_addr,
                 capabilities: session.capabilities.clone(),
                 client_version: session.client_version.clone(),
+                status: session.status,
             })
             .collect()
     }
@@ -576,6 +579,7 @@ impl SessionManager {
             remote_addr: session.remote_addr,
             capabilities: session.capabilities.clone(),
             client_version: session.client_version.clone(),
+            status: session.status,
         })
     }
 }
@@ -664,22 +668,6 @@ pub(crate) enum PendingSessionHandshakeError {
     Ecies(ECIESError),
 }
 
-/// The direction of the connection.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum Direction {
-    /// Incoming connection.
-    Incoming,
-    /// Outgoing connection to a specific node.
-    Outgoing(PeerId),
-}
-
-impl Direction {
-    /// Returns `true` if this an incoming connection.
-    pub(crate) fn is_incoming(&self) -> bool {
-        matches!(self, Direction::Incoming)
-    }
-}
-
 /// The error thrown when the max configured limit has been reached and no more connections are
 /// accepted.
 #[derive(Debug, Clone, thiserror::Error)]
@@ -0,0 +1,362 @@
+//! State snapshot serving for the `snap` protocol.
+
+use futures::StreamExt;
+use reth_eth_wire::{
+    AccountData, AccountRange, ByteCodes, GetAccountRange, GetByteCodes, GetStorageRanges,
+    GetTrieNodes, SlimAccount, StorageData, StorageRanges, TrieNodes,
+};
+use reth_interfaces::{p2p::error::RequestResult, Result};
+use reth_primitives::{Bytes, PeerId, H256, U256};
+use reth_provider::{
+    trie::{compact_to_nibbles, trie_node_at_path, trie_proof},
+    BlockProvider, HeaderProvider, StateProvider, StateProviderFactory, StateTrieProvider,
+};
+use reth_rlp::{Decodable, Encodable};
+use reth_rlp_derive::RlpDecodable;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::{mpsc::UnboundedReceiver, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+// Limits: <https://github.com/ethereum/go-ethereum/blob/master/eth/protocols/snap/handler.go>
+
+/// Maximum size of replies, regardless of the requested size.
+const SOFT_RESPONSE_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Maximum number of accounts or storage slots to look up for a single range.
+const MAX_RANGE_SERVE: usize = 4096;
+
+/// Maximum number of bytecodes to serve.
+const MAX_CODE_LOOKUPS: usize = 1024;
+
+/// Maximum number of trie nodes to serve.
+const MAX_TRIE_NODE_LOOKUPS: usize = 1024;
+
+/// Estimated size in bytes of a slim account, in addition to its hash.
+const APPROX_ACCOUNT_SIZE: usize = 100;
+
+/// Manages `snap` requests on top of the p2p network.
+///
+/// Requests are only served for the state root of the latest block, for which the state tries are
+/// stored. Requests for other roots get empty responses.
+///
+/// This can be spawned to another task and is supposed to be run as background service.
+///
+/// Note: `snap` is not negotiated in the sessions yet, since the
+/// [`P2PStream`](reth_eth_wire::P2PStream) only multiplexes a single capability, so the network
+/// does not send it any requests until it does.
+#[must_use = "Manager does nothing unless polled."]
+pub struct SnapRequestHandler<C> {
+    /// The client type that can interact with the chain.
+    client: Arc<C>,
+    /// Incoming request from the [NetworkManager](crate::NetworkManager).
+    incoming_requests: UnboundedReceiverStream<IncomingSnapRequest>,
+}
+
+// === impl SnapRequestHandler ===
+impl<C> SnapRequestHandler<C> {
+    /// Create a new instance
+    pub fn new(client: Arc<C>, incoming: UnboundedReceiver<IncomingSnapRequest>) -> Self {
+        Self { client, incoming_requests: UnboundedReceiverStream::new(incoming) }
+    }
+}
+
+impl<C> SnapRequestHandler<C>
+where
+    C: BlockProvider + HeaderProvider + StateProviderFactory + StateTrieProvider,
+{
+    /// Returns `true` if `root` is the state root of the latest block.
+    fn is_latest_state_root(&self, root: H256) -> Result<bool> {
+        let best_hash = self.client.chain_info()?.best_hash;
+        Ok(self.client.header(&best_hash)?.map_or(false, |header| header.state_root == root))
+    }
+
+    /// Returns the account with the given hashed address from the state trie.
+    fn trie_account(&self, root: H256, hashed_address: H256) -> Result<Option<TrieAccount>> {
+        let proof = trie_proof(root, hashed_address, |hash| self.client.account_trie_node(hash))?;
+        Ok(proof.value.and_then(|value| TrieAccount::decode(&mut value.as_ref()).ok()))
+    }
+
+    /// Returns the proof nodes of both keys in the trie, without duplicates.
+    fn range_proof<F>(
+        &self,
+        root: H256,
+        first: H256,
+        last: H256,
+        mut get_node: F,
+    ) -> Result<Vec<Bytes>>
+    where
+        F: FnMut(H256) -> Result<Option<Bytes>>,
+    {
+        let mut nodes = trie_proof(root, first, &mut get_node)?.nodes;
+        if last != first {
+            for node in trie_proof(root, last, &mut get_node)?.nodes {
+                if !nodes.contains(&node) {
+                    nodes.push(node);
+                }
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn get_account_range(
+        &self,
+        request: &GetAccountRange,
+    ) -> Result<(Vec<AccountData>, Vec<Bytes>)> {
+        let GetAccountRange { root_hash, starting_hash, limit_hash, response_bytes, .. } = *request;
+        if !self.is_latest_state_root(root_hash)? {
+            return Ok(Default::default())
+        }
+
+        let limit = (response_bytes as usize).min(SOFT_RESPONSE_LIMIT);
+        let mut accounts = Vec::new();
+        let mut size = 0;
+        for (hash, _) in self.client.hashed_accounts(starting_hash, MAX_RANGE_SERVE)? {
+            let Some(account) = self.trie_account(root_hash, hash)? else { break };
+            accounts.push(AccountData { hash, body: account.into() });
+            size += 32 + APPROX_ACCOUNT_SIZE;
+            if hash >= limit_hash || size >= limit {
+                break
+            }
+        }
+
+        let last = accounts.last().map_or(starting_hash, |account| account.hash);
+        let proof = self.range_proof(root_hash, starting_hash, last, |hash| {
+            self.client.account_trie_node(hash)
+        })?;
+        Ok((accounts, proof))
+    }
+
+    fn get_storage_ranges(
+        &self,
+        request: &GetStorageRanges,
+    ) -> Result<(Vec<Vec<StorageData>>, Vec<Bytes>)> {
+        let root_hash = request.root_hash;
+        if !self.is_latest_state_root(root_hash)? {
+            return Ok(Default::default())
+        }
+
+        let to_hash = |bytes: &Bytes| (bytes.len() == 32).then(|| H256::from_slice(bytes.as_ref()));
+        let starting_hash = to_hash(&request.starting_hash).unwrap_or_default();
+        let limit_hash = to_hash(&request.limit_hash);
+
+        let limit = (request.response_bytes as usize).min(SOFT_RESPONSE_LIMIT);
+        let mut ranges = Vec::new();
+        let mut proof = Vec::new();
+        let mut size = 0;
+        for (idx, hashed_address) in request.account_hashes.iter().copied().enumerate() {
+            let Some(account) = self.trie_account(root_hash, hashed_address)? else { break };
+            // the origin only applies to the first account, and the limit only to the last one
+            let start = if idx == 0 { starting_hash } else { H256::zero() };
+            let is_last = idx + 1 == request.account_hashes.len();
+
+            let slots = self.client.hashed_storage(hashed_address, start, MAX_RANGE_SERVE)?;
+            let mut incomplete = slots.len() == MAX_RANGE_SERVE;
+            let mut range = Vec::with_capacity(slots.len());
+            for (hash, value) in slots {
+                let mut data = Vec::with_capacity(value.length());
+                value.encode(&mut data);
+                size += 32 + data.len();
+                range.push(StorageData { hash, data: data.into() });
+
+                if is_last && limit_hash.map_or(false, |limit_hash| hash >= limit_hash) {
+                    break
+                }
+                if size >= limit {
+                    incomplete = true;
+                    break
+                }
+            }
+
+            // only a partial range needs to be proven
+            if incomplete || start != H256::zero() {
+                let last = range.last().map_or(start, |slot| slot.hash);
+                proof = self.range_proof(account.storage_root, start, last, |hash| {
+                    self.client.storage_trie_node(hashed_address, hash)
+                })?;
+            }
+            ranges.push(range);
+
+            if incomplete || size >= limit {
+                break
+            }
+        }
+
+        Ok((ranges, proof))
+    }
+
+    fn get_byte_codes(&self, request: &GetByteCodes) -> Result<Vec<Bytes>> {
+        let limit = (request.response_bytes as usize).min(SOFT_RESPONSE_LIMIT);
+        let state = self.client.latest()?;
+        let mut codes = Vec::new();
+        let mut size = 0;
+        for hash in request.hashes.iter().take(MAX_CODE_LOOKUPS) {
+            if let Some(code) = state.bytecode_by_hash(*hash)? {
+                size += code.len();
+                codes.push(code);
+                if size >= limit {
+                    break
+                }
+            }
+        }
+        Ok(codes)
+    }
+
+    fn get_trie_nodes(&self, request: &GetTrieNodes) -> Result<Vec<Bytes>> {
+        let root_hash = request.root_hash;
+        if !self.is_latest_state_root(root_hash)? {
+            return Ok(Vec::new())
+        }
+
+        let limit = (request.response_bytes as usize).min(SOFT_RESPONSE_LIMIT);
+        let mut nodes = Vec::new();
+        let mut size = 0;
+        'paths: for paths in request.paths.iter() {
+            match paths.as_slice() {
+                [] => break,
+                [path] => {
+                    // a node of the account trie
+                    let Some(path) = compact_to_nibbles(path) else { break };
+                    let Some(node) = trie_node_at_path(root_hash, &path, |hash| {
+                        self.client.account_trie_node(hash)
+                    })?
+                    else {
+                        break
+                    };
+                    size += node.len();
+                    nodes.push(node);
+                }
+                [account, storage_paths @ ..] => {
+                    // nodes of the storage trie of the account with the given hashed address
+                    if account.len() != 32 {
+                        break
+                    }
+                    let hashed_address = H256::from_slice(account.as_ref());
+                    let Some(account) = self.trie_account(root_hash, hashed_address)? else {
+                        break
+                    };
+                    for path in storage_paths {
+                        let Some(path) = compact_to_nibbles(path) else { break 'paths };
+                        let Some(node) = trie_node_at_path(account.storage_root, &path, |hash| {
+                            self.client.storage_trie_node(hashed_address, hash)
+                        })?
+                        else {
+                            break 'paths
+                        };
+                        size += node.len();
+                        nodes.push(node);
+                    }
+                }
+            }
+
+            if size >= limit || nodes.len() >= MAX_TRIE_NODE_LOOKUPS {
+                break
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn on_incoming_request(&mut self, incoming: IncomingSnapRequest) {
+        match incoming {
+            IncomingSnapRequest::GetAccountRange { request, response, .. } => {
+                let (accounts, proof) = self.get_account_range(&request).unwrap_or_default();
+                let request_id = request.request_id;
+                let _ = response.send(Ok(AccountRange { request_id, accounts, proof }));
+            }
+            IncomingSnapRequest::GetStorageRanges { request, response, .. } => {
+                let (slots, proof) = self.get_storage_ranges(&request).unwrap_or_default();
+                let request_id = request.request_id;
+                let _ = response.send(Ok(StorageRanges { request_id, slots, proof }));
+            }
+            IncomingSnapRequest::GetByteCodes { request, response, .. } => {
+                let codes = self.get_byte_codes(&request).unwrap_or_default();
+                let request_id = request.request_id;
+                let _ = response.send(Ok(ByteCodes { request_id, codes }));
+            }
+            IncomingSnapRequest::GetTrieNodes { request, response, .. } => {
+                let nodes = self.get_trie_nodes(&request).unwrap_or_default();
+                let request_id = request.request_id;
+                let _ = response.send(Ok(TrieNodes { request_id, nodes }));
+            }
+        }
+    }
+}
+
+/// An endless future.
+///
+/// This should be spawned or used as part of `tokio::select!`.
+impl<C> Future for SnapRequestHandler<C>
+where
+    C: BlockProvider + HeaderProvider + StateProviderFactory + StateTrieProvider,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            match this.incoming_requests.poll_next_unpin(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => return Poll::Ready(()),
+                Poll::Ready(Some(incoming)) => this.on_incoming_request(incoming),
+            }
+        }
+    }
+}
+
+/// An account as it is stored in the leaves of the state trie.
+#[derive(Debug, RlpDecodable)]
+struct TrieAccount {
+    nonce: u64,
+    balance: U256,
+    storage_root: H256,
+    code_hash: H256,
+}
+
+impl From<TrieAccount> for SlimAccount {
+    fn from(account: TrieAccount) -> Self {
+        SlimAccount::new(account.nonce, account.balance, account.storage_root, account.code_hash)
+    }
+}
+
+/// All `snap` requests delegated by the network.
+#[derive(Debug)]
+#[allow(missing_docs)]
+pub enum IncomingSnapRequest {
+    /// Request a range of accounts from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetAccountRange {
+        peer_id: PeerId,
+        request: GetAccountRange,
+        response: oneshot::Sender<RequestResult<AccountRange>>,
+    },
+    /// Request ranges of storage slots from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetStorageRanges {
+        peer_id: PeerId,
+        request: GetStorageRanges,
+        response: oneshot::Sender<RequestResult<StorageRanges>>,
+    },
+    /// Request bytecodes from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetByteCodes {
+        peer_id: PeerId,
+        request: GetByteCodes,
+        response: oneshot::Sender<RequestResult<ByteCodes>>,
+    },
+    /// Request trie nodes from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetTrieNodes {
+        peer_id: PeerId,
+        request: GetTrieNodes,
+        response: oneshot::Sender<RequestResult<TrieNodes>>,
+    },
+}
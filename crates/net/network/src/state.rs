@@ -15,7 +15,7 @@ use reth_eth_wire::{
     capability::Capabilities, BlockHashNumber, DisconnectReason, NewBlockHashes, Status,
 };
 use reth_network_api::PeerKind;
-use reth_primitives::{ForkId, PeerId, H256};
+use reth_primitives::{ForkId, Head, PeerId, H256};
 use reth_provider::BlockProvider;
 use std::{
     collections::{HashMap, VecDeque},
@@ -229,6 +229,11 @@ where
         self.discovery.update_fork_id(fork_id)
     }
 
+    /// Invoked when the head of the local node changed.
+    pub(crate) fn update_head(&mut self, head: Head) {
+        self.discovery.update_head(head)
+    }
+
     /// Invoked after a `NewBlock` message was received by the peer.
     ///
     /// This will keep track of blocks we know a peer has
@@ -10,18 +10,21 @@ use crate::{
 };
 use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
 use reth_eth_wire::{
-    GetPooledTransactions, NewPooledTransactionHashes, PooledTransactions, Transactions,
+    EthVersion, GetPooledTransactions, NewPooledTransactionHashes, PooledTransactionHashes,
+    PooledTransactions, Transactions,
 };
 use reth_interfaces::{p2p::error::RequestResult, sync::SyncStateProvider};
 use reth_network_api::{Peers, ReputationChangeKind};
 use reth_primitives::{
-    FromRecoveredTransaction, IntoRecoveredTransaction, PeerId, TransactionSigned, TxHash, H256,
+    eip4844::KzgSettings, BlobTransaction, FromRecoveredTransaction, IntoRecoveredTransaction,
+    PeerId, PooledTransactionsElement, TransactionSigned, TxHash, TxType, H256,
 };
+use reth_rlp::Encodable;
 use reth_transaction_pool::{
-    error::PoolResult, PropagateKind, PropagatedTransactions, TransactionPool,
+    error::PoolResult, PoolTransaction, PropagateKind, PropagatedTransactions, TransactionPool,
 };
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     future::Future,
     num::NonZeroUsize,
     pin::Pin,
@@ -35,6 +38,11 @@ use tracing::trace;
 /// Cache limit of transactions to keep track of for a single peer.
 const PEER_TRANSACTION_CACHE_LIMIT: usize = 1024 * 10;
 
+/// Soft limit for the number of hashes in a `GetPooledTransactions` request.
+///
+/// Larger announcements are requested in multiple batches.
+const GET_POOLED_TRANSACTION_SOFT_LIMIT_NUM_HASHES: usize = 256;
+
 /// The future for inserting a function into the pool
 pub type PoolImportFuture = Pin<Box<dyn Future<Output = PoolResult<TxHash>> + Send + 'static>>;
 
@@ -85,6 +93,10 @@ pub struct TransactionsManager<Pool> {
     network_events: UnboundedReceiverStream<NetworkEvent>,
     /// All currently active requests for pooled transactions.
     inflight_requests: Vec<GetPooledTxRequest>,
+    /// The hashes of all transactions that are currently requested, from any peer.
+    ///
+    /// Announced transactions that are already requested from another peer aren't requested again.
+    inflight_hashes: HashSet<TxHash>,
     /// All currently pending transactions grouped by peers.
     ///
     /// This way we can track incoming transactions and prevent multiple pool imports for the same
@@ -102,6 +114,10 @@ pub struct TransactionsManager<Pool> {
     pending_transactions: ReceiverStream<TxHash>,
     /// Incoming events from the [`NetworkManager`](crate::NetworkManager).
     transaction_events: UnboundedReceiverStream<NetworkTransactionEvent>,
+    /// The KZG trusted setup the sidecars of the received blob transactions are verified with.
+    ///
+    /// Blob transactions are not imported without it.
+    kzg_settings: Option<Arc<KzgSettings>>,
     /// TransactionsManager metrics
     metrics: TransactionsManagerMetrics,
 }
@@ -126,6 +142,7 @@ impl<Pool: TransactionPool> TransactionsManager<Pool> {
             network,
             network_events,
             inflight_requests: Default::default(),
+            inflight_hashes: Default::default(),
             transactions_by_peers: Default::default(),
             pool_imports: Default::default(),
             peers: Default::default(),
@@ -133,9 +150,16 @@ impl<Pool: TransactionPool> TransactionsManager<Pool> {
             command_rx: UnboundedReceiverStream::new(command_rx),
             pending_transactions: ReceiverStream::new(pending),
             transaction_events: UnboundedReceiverStream::new(from_network),
+            kzg_settings: None,
             metrics: Default::default(),
         }
     }
+
+    /// Sets the KZG trusted setup used to verify the sidecars of the received blob transactions.
+    pub fn with_kzg_settings(mut self, kzg_settings: Arc<KzgSettings>) -> Self {
+        self.kzg_settings = Some(kzg_settings);
+        self
+    }
 }
 
 // === impl TransactionsManager ===
@@ -151,6 +175,9 @@ where
     }
 
     /// Request handler for an incoming request for transactions
+    ///
+    /// Blob transactions are served with the sidecar from the blob store of the pool, and skipped
+    /// if the sidecar is not available.
     fn on_get_pooled_transactions(
         &mut self,
         peer_id: PeerId,
@@ -158,12 +185,14 @@ where
         response: oneshot::Sender<RequestResult<PooledTransactions>>,
     ) {
         if let Some(peer) = self.peers.get_mut(&peer_id) {
-            let transactions = self
-                .pool
+            let pool = &self.pool;
+            let transactions = pool
                 .get_all(request.0)
                 .into_iter()
-                .map(|tx| tx.transaction.to_recovered_transaction().into_signed())
-                .collect::<Vec<_>>();
+                .filter_map(|tx| {
+                    to_pooled_element(pool, tx.transaction.to_recovered_transaction().into_signed())
+                })
+                .collect::<Vec<PooledTransactionsElement>>();
 
             // we sent a response at which point we assume that the peer is aware of the transaction
             peer.transactions.extend(transactions.iter().map(|tx| tx.hash()));
@@ -184,6 +213,12 @@ where
     /// complete transaction object if it is unknown to them. The dissemination of complete
     /// transactions to a fraction of peers usually ensures that all nodes receive the transaction
     /// and won't need to request it.
+    ///
+    /// Local transactions, e.g. the ones submitted via RPC, are sent in full to all peers, so they
+    /// don't depend on peers requesting them.
+    ///
+    /// Blob transactions are only ever announced, since the peers need their sidecar, which is
+    /// only sent in `PooledTransactions`.
     fn on_new_transactions(&mut self, hashes: impl IntoIterator<Item = TxHash>) {
         // Nothing to propagate while syncing
         if self.network.is_syncing() {
@@ -197,7 +232,14 @@ where
                 .get_all(hashes)
                 .into_iter()
                 .map(|tx| {
-                    (*tx.hash(), Arc::new(tx.transaction.to_recovered_transaction().into_signed()))
+                    let transaction = tx.transaction.to_recovered_transaction().into_signed();
+                    PropagateTransaction {
+                        hash: *tx.hash(),
+                        is_blob: transaction.tx_type() == TxType::EIP4844,
+                        size: pooled_size(&self.pool, &transaction),
+                        transaction: Arc::new(transaction),
+                        is_local: tx.is_local(),
+                    }
                 })
                 .collect(),
         );
@@ -206,10 +248,7 @@ where
         self.pool.on_propagated(propagated);
     }
 
-    fn propagate_transactions(
-        &mut self,
-        txs: Vec<(TxHash, Arc<TransactionSigned>)>,
-    ) -> PropagatedTransactions {
+    fn propagate_transactions(&mut self, txs: Vec<PropagateTransaction>) -> PropagatedTransactions {
         let mut propagated = PropagatedTransactions::default();
 
         // send full transactions to a fraction fo the connected peers (square root of the total
@@ -218,25 +257,26 @@ where
 
         // Note: Assuming ~random~ order due to random state of the peers map hasher
         for (idx, (peer_id, peer)) in self.peers.iter_mut().enumerate() {
-            let (hashes, full): (Vec<_>, Vec<_>) =
-                txs.iter().filter(|(hash, _)| peer.transactions.insert(*hash)).cloned().unzip();
-
-            if !full.is_empty() {
-                if idx > max_num_full {
-                    for hash in &hashes {
-                        propagated.0.entry(*hash).or_default().push(PropagateKind::Hash(*peer_id));
-                    }
-                    // send hashes of transactions
-                    self.network.send_transactions_hashes(*peer_id, hashes);
+            let mut hashes = PooledTransactionHashes::new(peer.version);
+            let mut full = Vec::new();
+            for tx in txs.iter().filter(|tx| peer.transactions.insert(tx.hash)) {
+                if tx.is_blob || (idx > max_num_full && !tx.is_local) {
+                    hashes.push(tx.hash, tx.transaction.tx_type() as u8, tx.size);
+                    propagated.0.entry(tx.hash).or_default().push(PropagateKind::Hash(*peer_id));
                 } else {
-                    // send full transactions
-                    self.network.send_transactions(*peer_id, full);
-
-                    for hash in hashes {
-                        propagated.0.entry(hash).or_default().push(PropagateKind::Full(*peer_id));
-                    }
+                    full.push(Arc::clone(&tx.transaction));
+                    propagated.0.entry(tx.hash).or_default().push(PropagateKind::Full(*peer_id));
                 }
             }
+
+            if !hashes.is_empty() {
+                // send hashes of transactions
+                self.network.send_transactions_hashes(*peer_id, hashes);
+            }
+            if !full.is_empty() {
+                // send full transactions
+                self.network.send_transactions(*peer_id, full);
+            }
         }
 
         // Update propagated transactions metrics
@@ -246,11 +286,7 @@ where
     }
 
     /// Request handler for an incoming `NewPooledTransactionHashes`
-    fn on_new_pooled_transaction_hashes(
-        &mut self,
-        peer_id: PeerId,
-        msg: NewPooledTransactionHashes,
-    ) {
+    fn on_new_pooled_transaction_hashes(&mut self, peer_id: PeerId, msg: PooledTransactionHashes) {
         // If the node is currently syncing, ignore transactions
         if self.network.is_syncing() {
             return
@@ -259,7 +295,7 @@ where
         let mut num_already_seen = 0;
 
         if let Some(peer) = self.peers.get_mut(&peer_id) {
-            let mut transactions = msg.0;
+            let mut transactions = msg.into_hashes();
 
             // keep track of the transactions the peer knows
             for tx in transactions.iter().copied() {
@@ -270,20 +306,32 @@ where
 
             self.pool.retain_unknown(&mut transactions);
 
+            // don't request transactions that are already requested from another peer
+            transactions.retain(|hash| !self.inflight_hashes.contains(hash));
+
             if transactions.is_empty() {
                 // nothing to request
                 return
             }
 
-            // request the missing transactions
-            let (response, rx) = oneshot::channel();
-            let req = PeerRequest::GetPooledTransactions {
-                request: GetPooledTransactions(transactions),
-                response,
-            };
+            // request the missing transactions in batches
+            for batch in transactions.chunks(GET_POOLED_TRANSACTION_SOFT_LIMIT_NUM_HASHES) {
+                let (response, rx) = oneshot::channel();
+                let req = PeerRequest::GetPooledTransactions {
+                    request: GetPooledTransactions(batch.to_vec()),
+                    response,
+                };
 
-            if peer.request_tx.try_send(req).is_ok() {
-                self.inflight_requests.push(GetPooledTxRequest { peer_id, response: rx })
+                if peer.request_tx.try_send(req).is_err() {
+                    // the peer's request channel is full
+                    break
+                }
+                self.inflight_hashes.extend(batch.iter().copied());
+                self.inflight_requests.push(GetPooledTxRequest {
+                    peer_id,
+                    hashes: batch.to_vec(),
+                    response: rx,
+                })
             }
         }
 
@@ -292,11 +340,30 @@ where
         }
     }
 
+    /// Returns the announcement of all transactions in the pool, in the form of the given `eth`
+    /// version.
+    fn pooled_transaction_hashes(&self, version: EthVersion) -> PooledTransactionHashes {
+        if version != EthVersion::Eth68 {
+            return NewPooledTransactionHashes(self.pool.pooled_transactions()).into()
+        }
+        let mut msg = PooledTransactionHashes::new(version);
+        for tx in self.pool.get_all(self.pool.pooled_transactions()) {
+            let transaction = tx.transaction.to_recovered_transaction().into_signed();
+            msg.push(
+                transaction.hash,
+                transaction.tx_type() as u8,
+                pooled_size(&self.pool, &transaction),
+            );
+        }
+        msg
+    }
+
     /// Handles dedicated transaction events related tot the `eth` protocol.
     fn on_network_tx_event(&mut self, event: NetworkTransactionEvent) {
         match event {
             NetworkTransactionEvent::IncomingTransactions { peer_id, msg } => {
-                self.import_transactions(peer_id, msg.0, TransactionSource::Broadcast);
+                let transactions = msg.0.into_iter().map(Into::into).collect();
+                self.import_transactions(peer_id, transactions, TransactionSource::Broadcast);
             }
             NetworkTransactionEvent::IncomingPooledTransactionHashes { peer_id, msg } => {
                 self.on_new_pooled_transaction_hashes(peer_id, msg)
@@ -323,7 +390,8 @@ where
                 // remove the peer
                 self.peers.remove(&peer_id);
             }
-            NetworkEvent::SessionEstablished { peer_id, messages, .. } => {
+            NetworkEvent::SessionEstablished { peer_id, messages, status, .. } => {
+                let version = EthVersion::try_from(status.version).unwrap_or(EthVersion::Eth66);
                 // insert a new peer
                 self.peers.insert(
                     peer_id,
@@ -332,13 +400,14 @@ where
                             NonZeroUsize::new(PEER_TRANSACTION_CACHE_LIMIT).unwrap(),
                         ),
                         request_tx: messages,
+                        version,
                     },
                 );
 
                 // Send a `NewPooledTransactionHashes` to the peer with _all_ transactions in the
                 // pool
                 if !self.network.is_syncing() {
-                    let msg = NewPooledTransactionHashes(self.pool.pooled_transactions());
+                    let msg = self.pooled_transaction_hashes(version);
                     self.network.send_message(NetworkHandleMessage::SendPooledTransactionHashes {
                         peer_id,
                         msg,
@@ -351,10 +420,13 @@ where
     }
 
     /// Starts the import process for the given transactions.
+    ///
+    /// Blob transactions are only accepted with their sidecar, which is verified against the KZG
+    /// trusted setup before the transaction is imported.
     fn import_transactions(
         &mut self,
         peer_id: PeerId,
-        transactions: Vec<TransactionSigned>,
+        transactions: Vec<PooledTransactionsElement>,
         source: TransactionSource,
     ) {
         // If the node is currently syncing, ignore transactions
@@ -368,6 +440,30 @@ where
 
         if let Some(peer) = self.peers.get_mut(&peer_id) {
             for tx in transactions {
+                let (tx, sidecar) = match tx {
+                    PooledTransactionsElement::Transaction(tx) => {
+                        // blob transactions are useless without their sidecar, so they must only
+                        // be announced
+                        if tx.tx_type() == TxType::EIP4844 {
+                            has_bad_transactions = true;
+                            continue
+                        }
+                        (tx, None)
+                    }
+                    PooledTransactionsElement::BlobTransaction(tx) => {
+                        let Some(kzg_settings) = &self.kzg_settings else {
+                            trace!(target: "net::tx", hash = ?tx.hash(), "No KZG trusted setup to verify the blob transaction");
+                            continue
+                        };
+                        if tx.validate(kzg_settings).is_err() {
+                            has_bad_transactions = true;
+                            continue
+                        }
+                        let BlobTransaction { transaction, sidecar } = tx;
+                        (transaction, Some(sidecar))
+                    }
+                };
+
                 // recover transaction
                 let tx = if let Some(tx) = tx.into_ecrecovered() {
                     tx
@@ -391,7 +487,12 @@ where
                     }
                     Entry::Vacant(entry) => {
                         // this is a new transaction that should be imported into the pool
-                        let pool_transaction = <Pool::Transaction as FromRecoveredTransaction>::from_recovered_transaction(tx);
+                        let pool_transaction = match sidecar {
+                            Some(sidecar) => {
+                                Pool::Transaction::from_recovered_blob_transaction(tx, sidecar)
+                            }
+                            None => Pool::Transaction::from_recovered_transaction(tx),
+                        };
 
                         let pool = self.pool.clone();
                         let import = Box::pin(async move {
@@ -462,6 +563,7 @@ where
             match req.response.poll_unpin(cx) {
                 Poll::Pending => {
                     this.inflight_requests.push(req);
+                    continue
                 }
                 Poll::Ready(Ok(Ok(txs))) => {
                     this.import_transactions(req.peer_id, txs.0, TransactionSource::Response);
@@ -473,6 +575,10 @@ where
                     this.report_bad_message(req.peer_id);
                 }
             }
+            // the transactions can be requested again, if they're announced by another peer
+            for hash in &req.hashes {
+                this.inflight_hashes.remove(hash);
+            }
         }
 
         // Advance all imports
@@ -519,19 +625,59 @@ impl TransactionSource {
     }
 }
 
+/// Returns the transaction as it's sent in a `PooledTransactions` response.
+///
+/// Blob transactions are sent with the sidecar from the blob store of the pool, `None` is returned
+/// if the sidecar is not available.
+fn to_pooled_element<Pool: TransactionPool>(
+    pool: &Pool,
+    tx: TransactionSigned,
+) -> Option<PooledTransactionsElement> {
+    if tx.tx_type() != TxType::EIP4844 {
+        return Some(tx.into())
+    }
+    let sidecar = pool.get_blob(tx.hash).ok().flatten()?;
+    BlobTransaction::new(tx, sidecar).map(Into::into)
+}
+
+/// Returns the size of the transaction in a `PooledTransactions` response, as announced to `eth/68`
+/// peers.
+fn pooled_size<Pool: TransactionPool>(pool: &Pool, tx: &TransactionSigned) -> usize {
+    if tx.tx_type() != TxType::EIP4844 {
+        return tx.length()
+    }
+    to_pooled_element(pool, tx.clone()).map_or_else(|| tx.length(), |tx| tx.length())
+}
+
 /// An inflight request for `PooledTransactions` from a peer
 #[allow(missing_docs)]
 struct GetPooledTxRequest {
     peer_id: PeerId,
+    /// The requested transactions.
+    hashes: Vec<TxHash>,
     response: oneshot::Receiver<RequestResult<PooledTransactions>>,
 }
 
+/// A transaction to propagate to the peers.
+struct PropagateTransaction {
+    hash: TxHash,
+    transaction: Arc<TransactionSigned>,
+    /// Whether the transaction was submitted locally.
+    is_local: bool,
+    /// Whether this is a blob transaction, which is only announced.
+    is_blob: bool,
+    /// The size of the transaction in a `PooledTransactions` response.
+    size: usize,
+}
+
 /// Tracks a single peer
 struct Peer {
     /// Keeps track of transactions that we know the peer has seen.
     transactions: LruCache<H256>,
     /// A communication channel directly to the session task.
     request_tx: PeerRequestSender,
+    /// The negotiated `eth` version of the session, which determines the form of announcements.
+    version: EthVersion,
 }
 
 /// Commands to send to the [`TransactionsManager`](crate::transactions::TransactionsManager)
@@ -546,7 +692,7 @@ pub enum NetworkTransactionEvent {
     /// Received list of transactions from the given peer.
     IncomingTransactions { peer_id: PeerId, msg: Transactions },
     /// Received list of transactions hashes to the given peer.
-    IncomingPooledTransactionHashes { peer_id: PeerId, msg: NewPooledTransactionHashes },
+    IncomingPooledTransactionHashes { peer_id: PeerId, msg: PooledTransactionHashes },
     /// Incoming `GetPooledTransactions` request from a peer.
     GetPooledTransactions {
         peer_id: PeerId,
@@ -559,6 +705,7 @@ pub enum NetworkTransactionEvent {
 mod tests {
     use super::*;
     use crate::{NetworkConfigBuilder, NetworkManager};
+    use reth_eth_wire::NewPooledTransactionHashes68;
     use reth_interfaces::sync::{SyncState, SyncStateUpdater};
     use reth_provider::test_utils::NoopProvider;
     use reth_transaction_pool::test_utils::testing_pool;
@@ -594,4 +741,83 @@ mod tests {
 
         assert!(pool.is_empty());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_request_announced_transactions_once() {
+        reth_tracing::init_test_tracing();
+
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+
+        let client = Arc::new(NoopProvider::default());
+        let pool = testing_pool();
+        let config = NetworkConfigBuilder::new(secret_key).build(Arc::clone(&client));
+        let (_handle, network, mut transactions, _) = NetworkManager::new(config)
+            .await
+            .unwrap()
+            .into_builder()
+            .transactions(pool.clone())
+            .split_with_handle();
+
+        tokio::task::spawn(network);
+
+        let mut add_peer = |peer_id| {
+            let (to_session_tx, to_session_rx) = mpsc::channel(16);
+            transactions.peers.insert(
+                peer_id,
+                Peer {
+                    transactions: LruCache::new(
+                        NonZeroUsize::new(PEER_TRANSACTION_CACHE_LIMIT).unwrap(),
+                    ),
+                    request_tx: PeerRequestSender::new(peer_id, to_session_tx),
+                    version: EthVersion::Eth67,
+                },
+            );
+            to_session_rx
+        };
+        let (first_peer, second_peer) = (PeerId::random(), PeerId::random());
+        let mut first_requests = add_peer(first_peer);
+        let mut second_requests = add_peer(second_peer);
+
+        let mut hashes: Vec<_> =
+            (0..GET_POOLED_TRANSACTION_SOFT_LIMIT_NUM_HASHES + 1).map(|_| H256::random()).collect();
+        transactions.on_new_pooled_transaction_hashes(
+            first_peer,
+            NewPooledTransactionHashes(hashes.clone()).into(),
+        );
+
+        // the announcement is requested in batches
+        let mut requested = Vec::new();
+        while let Ok(PeerRequest::GetPooledTransactions { request, .. }) = first_requests.try_recv()
+        {
+            requested.push(request.0);
+        }
+        assert_eq!(
+            requested,
+            vec![
+                hashes[..GET_POOLED_TRANSACTION_SOFT_LIMIT_NUM_HASHES].to_vec(),
+                hashes[GET_POOLED_TRANSACTION_SOFT_LIMIT_NUM_HASHES..].to_vec()
+            ]
+        );
+        assert_eq!(transactions.inflight_hashes.len(), hashes.len());
+
+        // only the transactions that aren't requested yet are requested from the second peer
+        let new_hash = H256::random();
+        hashes.push(new_hash);
+        transactions.on_new_pooled_transaction_hashes(
+            second_peer,
+            NewPooledTransactionHashes68 {
+                types: vec![0x02; hashes.len()],
+                sizes: vec![0x7b; hashes.len()],
+                hashes,
+            }
+            .into(),
+        );
+        match second_requests.try_recv() {
+            Ok(PeerRequest::GetPooledTransactions { request, .. }) => {
+                assert_eq!(request.0, vec![new_hash]);
+            }
+            _ => unreachable!(),
+        }
+        assert!(second_requests.try_recv().is_err());
+    }
 }
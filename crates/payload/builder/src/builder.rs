@@ -0,0 +1,300 @@
+use crate::{trie, BuiltPayload, PayloadBuilderAttributes, PayloadBuilderError};
+use reth_consensus::validation::calculate_next_block_base_fee;
+use reth_executor::{
+    config::revm_spec,
+    executor::apply_beacon_root_contract_call,
+    revm_wrap::{fill_block_env, fill_tx_env, into_reth_log, State},
+};
+use reth_primitives::{
+    bloom::{logs_bloom, receipts_bloom},
+    constants::EIP1559_INITIAL_BASE_FEE,
+    proofs::{self, EMPTY_LIST_HASH},
+    ChainSpec, Hardfork, Head, Header, IntoRecoveredTransaction, Receipt, SealedBlock, U256,
+};
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory, StateTrieProvider};
+use reth_transaction_pool::{BestTransactions, TransactionPool};
+use revm::{
+    db::{AccountState, CacheDB},
+    primitives::{EVMError, Env, ResultAndState, SpecId},
+    DatabaseCommit, EVM,
+};
+use std::sync::Arc;
+use tracing::trace;
+
+/// Builds payloads on top of the latest block, out of the best transactions of the pool.
+#[derive(Debug)]
+pub struct PayloadBuilder<Client, Pool> {
+    client: Arc<Client>,
+    pool: Pool,
+    chain_spec: ChainSpec,
+}
+
+impl<Client, Pool> PayloadBuilder<Client, Pool>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + StateTrieProvider,
+    Pool: TransactionPool,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    /// Creates a new [PayloadBuilder] that reads the chain from `client` and the transactions to
+    /// include from `pool`.
+    pub fn new(client: Arc<Client>, pool: Pool, chain_spec: ChainSpec) -> Self {
+        Self { client, pool, chain_spec }
+    }
+
+    /// Builds a payload with the given attributes.
+    ///
+    /// The best transactions of the pool are executed in order on top of the state of the parent,
+    /// transactions that don't fit into the block or fail to execute are skipped, together with
+    /// their descendants.
+    ///
+    /// Blob transactions are skipped too, since `engine_getPayloadV3` does not return the blobs
+    /// bundle of the payload.
+    pub fn build(
+        &self,
+        attributes: &PayloadBuilderAttributes,
+    ) -> Result<BuiltPayload, PayloadBuilderError> {
+        let parent = self
+            .client
+            .header(&attributes.parent)?
+            .ok_or(PayloadBuilderError::ParentUnknown(attributes.parent))?;
+        if self.client.chain_info()?.best_hash != attributes.parent {
+            return Err(PayloadBuilderError::ParentNotLatest(attributes.parent))
+        }
+        let parent_td = self.client.header_td(&attributes.parent)?.unwrap_or_default();
+
+        let is_shanghai =
+            self.chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(attributes.timestamp);
+        let is_cancun =
+            self.chain_spec.fork(Hardfork::Cancun).active_at_timestamp(attributes.timestamp);
+
+        let base_fee = parent.base_fee_per_gas.map_or(EIP1559_INITIAL_BASE_FEE, |base_fee| {
+            calculate_next_block_base_fee(parent.gas_used, parent.gas_limit, base_fee)
+        });
+
+        // The header of the payload, its roots and gas used are filled in once the transactions
+        // are executed.
+        let mut header = Header {
+            parent_hash: attributes.parent,
+            ommers_hash: EMPTY_LIST_HASH,
+            beneficiary: attributes.suggested_fee_recipient,
+            number: parent.number + 1,
+            gas_limit: parent.gas_limit,
+            timestamp: attributes.timestamp,
+            mix_hash: attributes.prev_randao,
+            base_fee_per_gas: Some(base_fee),
+            excess_blob_gas: is_cancun.then(|| parent.next_block_excess_blob_gas().unwrap_or(0)),
+            parent_beacon_block_root: attributes.parent_beacon_block_root.filter(|_| is_cancun),
+            ..Default::default()
+        };
+
+        let spec_id = revm_spec(
+            &self.chain_spec,
+            Head {
+                number: header.number,
+                timestamp: header.timestamp,
+                difficulty: U256::ZERO,
+                total_difficulty: parent_td,
+                hash: Default::default(),
+            },
+        );
+        let mut env = Env::default();
+        env.cfg.chain_id = U256::from(self.chain_spec.chain().id());
+        env.cfg.spec_id = spec_id;
+        fill_block_env(&mut env.block, &header, spec_id >= SpecId::MERGE);
+
+        let mut db =
+            CacheDB::new(State::new(self.client.history_by_block_hash(attributes.parent)?));
+
+        // EIP-4788: the parent beacon block root is stored before the transactions are executed.
+        if is_cancun {
+            apply_beacon_root_contract_call(&header, parent_td, &self.chain_spec, &mut db)
+                .map_err(|err| PayloadBuilderError::Evm(format!("{err:?}")))?;
+        }
+
+        let mut cumulative_gas_used = 0;
+        let mut fees = U256::ZERO;
+        let mut transactions = Vec::new();
+        let mut receipts = Vec::new();
+
+        let mut best_transactions = self.pool.best_transactions();
+        while let Some(pool_tx) = best_transactions.next() {
+            let tx = pool_tx.transaction.to_recovered_transaction();
+
+            // The transaction must fit into the remaining gas of the block, and pay the base fee.
+            if cumulative_gas_used + tx.gas_limit() > header.gas_limit ||
+                tx.max_fee_per_gas() < base_fee as u128
+            {
+                trace!(target: "payload_builder", hash = ?tx.hash(), "Skipping transaction");
+                best_transactions.mark_invalid(&pool_tx);
+                continue
+            }
+
+            // EIP-4844: the payload would be returned without the blobs of the transaction.
+            if tx.max_fee_per_blob_gas().is_some() {
+                trace!(target: "payload_builder", hash = ?tx.hash(), "Skipping blob transaction");
+                best_transactions.mark_invalid(&pool_tx);
+                continue
+            }
+
+            let out = {
+                let mut evm = EVM::new();
+                evm.env = env.clone();
+                fill_tx_env(&mut evm.env.tx, &tx, tx.signer());
+                evm.database(&mut db);
+                evm.transact()
+            };
+            let ResultAndState { result, state } = match out {
+                Ok(result) => result,
+                Err(EVMError::Transaction(err)) => {
+                    trace!(target: "payload_builder", hash = ?tx.hash(), ?err, "Invalid transaction");
+                    best_transactions.mark_invalid(&pool_tx);
+                    continue
+                }
+                Err(err) => return Err(PayloadBuilderError::Evm(format!("{err:?}"))),
+            };
+
+            db.commit(state);
+
+            cumulative_gas_used += result.gas_used();
+
+            let priority_fee = tx
+                .max_priority_fee_per_gas()
+                .map_or(tx.max_fee_per_gas(), |priority_fee| {
+                    (base_fee as u128 + priority_fee).min(tx.max_fee_per_gas())
+                })
+                .saturating_sub(base_fee as u128);
+            fees += U256::from(result.gas_used()) * U256::from(priority_fee);
+
+            let logs = result.logs().into_iter().map(into_reth_log).collect::<Vec<_>>();
+            receipts.push(Receipt {
+                tx_type: tx.tx_type(),
+                success: result.is_success(),
+                cumulative_gas_used,
+                bloom: logs_bloom(logs.iter()),
+                logs,
+            });
+            transactions.push(tx.into_signed());
+        }
+
+        let withdrawals = if is_shanghai { attributes.withdrawals.clone() } else { None };
+        if let Some(withdrawals) = &withdrawals {
+            for withdrawal in withdrawals {
+                let account = db
+                    .load_account(withdrawal.address)
+                    .map_err(|err| PayloadBuilderError::Evm(format!("{err:?}")))?;
+                account.info.balance += withdrawal.amount_wei();
+                account.account_state = match account.account_state {
+                    AccountState::NotExisting => AccountState::StorageCleared,
+                    AccountState::None => AccountState::Touched,
+                    state => state,
+                };
+            }
+        }
+
+        // The state root is computed with the tries, which always hold the latest state, so the
+        // parent must still be the latest block once it is computed.
+        header.state_root = trie::state_root(self.client.as_ref(), parent.state_root, &db)?;
+        if self.client.chain_info()?.best_hash != attributes.parent {
+            return Err(PayloadBuilderError::ParentNotLatest(attributes.parent))
+        }
+        header.transactions_root = proofs::calculate_transaction_root(transactions.iter());
+        header.receipts_root = proofs::calculate_receipt_root(receipts.iter());
+        header.withdrawals_root = withdrawals
+            .as_ref()
+            .map(|withdrawals| proofs::calculate_withdrawals_root(withdrawals.iter()));
+        header.logs_bloom = receipts_bloom(receipts.iter().map(|receipt| &receipt.bloom));
+        header.gas_used = cumulative_gas_used;
+        header.blob_gas_used = is_cancun.then_some(0);
+
+        let block = SealedBlock {
+            header: header.seal_slow(),
+            body: transactions,
+            ommers: Vec::new(),
+            withdrawals,
+        };
+        Ok(BuiltPayload { id: attributes.payload_id(), block, fees })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use reth_primitives::{proofs::EMPTY_ROOT, Address, ChainSpecBuilder, H256};
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_transaction_pool::test_utils::{testing_pool, TestPool};
+
+    fn parent_header(number: u64) -> Header {
+        Header {
+            number,
+            gas_limit: 30_000_000,
+            gas_used: 15_000_000,
+            base_fee_per_gas: Some(7),
+            timestamp: 1000 + number * 12,
+            ..Default::default()
+        }
+    }
+
+    fn attributes(parent: &Header) -> PayloadBuilderAttributes {
+        PayloadBuilderAttributes {
+            parent: parent.hash_slow(),
+            timestamp: parent.timestamp + 12,
+            prev_randao: H256::random(),
+            suggested_fee_recipient: Address::random(),
+            withdrawals: Some(Vec::new()),
+            parent_beacon_block_root: None,
+        }
+    }
+
+    fn builder(client: MockEthProvider) -> PayloadBuilder<MockEthProvider, TestPool> {
+        let chain_spec = ChainSpecBuilder::mainnet().shanghai_activated().build();
+        PayloadBuilder::new(Arc::new(client), testing_pool(), chain_spec)
+    }
+
+    #[test]
+    fn build_empty_payload() {
+        let client = MockEthProvider::default();
+        let parent = parent_header(1);
+        client.add_header(parent.hash_slow(), parent.clone());
+        let attributes = attributes(&parent);
+
+        let payload = builder(client).build(&attributes).unwrap();
+        assert_eq!(payload.id, attributes.payload_id());
+        assert_eq!(payload.fees, U256::ZERO);
+
+        let block = payload.block;
+        assert!(block.body.is_empty());
+        assert_eq!(block.parent_hash, attributes.parent);
+        assert_eq!(block.number, 2);
+        assert_eq!(block.beneficiary, attributes.suggested_fee_recipient);
+        assert_eq!(block.mix_hash, attributes.prev_randao);
+        assert_eq!(block.gas_used, 0);
+        assert_eq!(block.base_fee_per_gas, Some(7));
+        assert_eq!(block.state_root, EMPTY_ROOT);
+        assert_eq!(block.withdrawals, Some(Vec::new()));
+        assert_eq!(block.withdrawals_root, Some(EMPTY_ROOT));
+        assert_eq!(block.blob_gas_used, None);
+        assert_eq!(block.parent_beacon_block_root, None);
+    }
+
+    #[test]
+    fn parent_must_be_latest() {
+        let client = MockEthProvider::default();
+        let parent = parent_header(1);
+        client.add_header(parent.hash_slow(), parent.clone());
+        let latest = parent_header(2);
+        client.add_header(latest.hash_slow(), latest);
+        let builder = builder(client);
+
+        assert_matches!(
+            builder.build(&attributes(&parent)),
+            Err(PayloadBuilderError::ParentNotLatest(hash)) if hash == parent.hash_slow()
+        );
+
+        let unknown = attributes(&parent_header(3));
+        assert_matches!(
+            builder.build(&unknown),
+            Err(PayloadBuilderError::ParentUnknown(hash)) if hash == unknown.parent
+        );
+    }
+}
@@ -0,0 +1,18 @@
+use reth_primitives::H256;
+
+/// Errors that can occur while building a payload.
+#[derive(Debug, thiserror::Error)]
+pub enum PayloadBuilderError {
+    /// The parent block of the payload is unknown.
+    #[error("Parent block {0} is unknown")]
+    ParentUnknown(H256),
+    /// Payloads can only be built on top of the latest block, its state is the one of the tries.
+    #[error("Parent block {0} is not the latest block")]
+    ParentNotLatest(H256),
+    /// The EVM failed for a reason unrelated to the transaction.
+    #[error("EVM error: {0}")]
+    Evm(String),
+    /// Error while reading from the database.
+    #[error(transparent)]
+    Provider(#[from] reth_interfaces::Error),
+}
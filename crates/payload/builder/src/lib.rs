@@ -0,0 +1,27 @@
+#![warn(missing_docs, unreachable_pub)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Building of execution payloads for the consensus layer.
+//!
+//! The [PayloadBuilderService] starts a payload job for every `engine_forkchoiceUpdated` call
+//! with payload attributes. A job assembles a block on top of the latest block out of the best
+//! transactions of the pool, and keeps rebuilding it while new transactions come in, until the
+//! payload is resolved by `engine_getPayload`.
+
+mod builder;
+mod error;
+mod payload;
+mod service;
+mod trie;
+
+pub use builder::PayloadBuilder;
+pub use error::PayloadBuilderError;
+pub use payload::{BuiltPayload, PayloadBuilderAttributes, PayloadId};
+pub use service::{
+    PayloadBuilderConfig, PayloadBuilderHandle, PayloadBuilderService, DEFAULT_PAYLOAD_DEADLINE,
+    DEFAULT_PAYLOAD_REBUILD_INTERVAL,
+};
@@ -0,0 +1,89 @@
+use reth_primitives::{keccak256, Address, SealedBlock, Withdrawal, H256, H64, U256};
+use reth_rlp::Encodable;
+
+/// The identifier of a payload, returned by `engine_forkchoiceUpdated` and used to retrieve the
+/// payload with `engine_getPayload`.
+pub type PayloadId = H64;
+
+/// The attributes of a payload to build, as sent by the consensus layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadBuilderAttributes {
+    /// Hash of the block the payload is built on top of.
+    pub parent: H256,
+    /// Timestamp of the payload.
+    pub timestamp: u64,
+    /// Value of the `prevRandao` field of the payload.
+    pub prev_randao: H256,
+    /// Address that receives the priority fees of the payload.
+    pub suggested_fee_recipient: Address,
+    /// Withdrawals of the payload, after Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// Root of the parent beacon block of the payload, after Cancun.
+    pub parent_beacon_block_root: Option<H256>,
+}
+
+impl PayloadBuilderAttributes {
+    /// Returns the identifier of the payload built with these attributes.
+    ///
+    /// The identifier is derived from the attributes, so that the same attributes always result
+    /// in the same payload job.
+    pub fn payload_id(&self) -> PayloadId {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(self.parent.as_bytes());
+        buf.extend_from_slice(&self.timestamp.to_be_bytes());
+        buf.extend_from_slice(self.prev_randao.as_bytes());
+        buf.extend_from_slice(self.suggested_fee_recipient.as_bytes());
+        if let Some(withdrawals) = &self.withdrawals {
+            withdrawals.encode(&mut buf);
+        }
+        if let Some(parent_beacon_block_root) = &self.parent_beacon_block_root {
+            buf.extend_from_slice(parent_beacon_block_root.as_bytes());
+        }
+        H64::from_slice(&keccak256(buf)[..8])
+    }
+}
+
+/// A payload built by the [PayloadBuilder](crate::PayloadBuilder).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltPayload {
+    /// The identifier of the payload.
+    pub id: PayloadId,
+    /// The block of the payload.
+    pub block: SealedBlock,
+    /// The priority fees the fee recipient receives for the block.
+    pub fees: U256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_id_is_deterministic() {
+        let attributes = PayloadBuilderAttributes {
+            parent: H256::random(),
+            timestamp: 1681338455,
+            prev_randao: H256::random(),
+            suggested_fee_recipient: Address::random(),
+            withdrawals: None,
+            parent_beacon_block_root: None,
+        };
+        assert_eq!(attributes.payload_id(), attributes.clone().payload_id());
+
+        let later =
+            PayloadBuilderAttributes { timestamp: attributes.timestamp + 1, ..attributes.clone() };
+        assert_ne!(attributes.payload_id(), later.payload_id());
+
+        let with_withdrawals = PayloadBuilderAttributes {
+            withdrawals: Some(vec![Withdrawal::default()]),
+            ..attributes.clone()
+        };
+        assert_ne!(attributes.payload_id(), with_withdrawals.payload_id());
+
+        let with_parent_beacon_block_root = PayloadBuilderAttributes {
+            parent_beacon_block_root: Some(H256::random()),
+            ..attributes.clone()
+        };
+        assert_ne!(attributes.payload_id(), with_parent_beacon_block_root.payload_id());
+    }
+}
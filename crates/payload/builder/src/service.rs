@@ -0,0 +1,292 @@
+use crate::{
+    BuiltPayload, PayloadBuilder, PayloadBuilderAttributes, PayloadBuilderError, PayloadId,
+};
+use futures::{FutureExt, StreamExt};
+use reth_primitives::IntoRecoveredTransaction;
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory, StateTrieProvider};
+use reth_transaction_pool::TransactionPool;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    sync::{
+        mpsc::{unbounded_channel, UnboundedSender},
+        oneshot,
+    },
+    task::JoinHandle,
+    time::{Instant, Interval},
+};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::{debug, warn};
+
+/// Default interval at which the payloads are rebuilt.
+pub const DEFAULT_PAYLOAD_REBUILD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default duration a payload is improved for, the length of a slot.
+pub const DEFAULT_PAYLOAD_DEADLINE: Duration = Duration::from_secs(12);
+
+/// Configuration of the [PayloadBuilderService].
+#[derive(Debug, Clone, Copy)]
+pub struct PayloadBuilderConfig {
+    /// Interval at which the payloads of the jobs are rebuilt.
+    pub interval: Duration,
+    /// How long a job improves its payload. Once the deadline is reached the best payload is
+    /// kept until it is resolved or the deadline is reached a second time.
+    pub deadline: Duration,
+}
+
+impl Default for PayloadBuilderConfig {
+    fn default() -> Self {
+        Self { interval: DEFAULT_PAYLOAD_REBUILD_INTERVAL, deadline: DEFAULT_PAYLOAD_DEADLINE }
+    }
+}
+
+/// Commands sent to the [PayloadBuilderService] by its [PayloadBuilderHandle].
+#[derive(Debug)]
+enum PayloadServiceCommand {
+    /// Start a job building a payload with the attributes.
+    NewPayload(PayloadBuilderAttributes),
+    /// Stop improving the payload of the job and return it.
+    Resolve(PayloadId, oneshot::Sender<Option<BuiltPayload>>),
+}
+
+/// A handle to the [PayloadBuilderService].
+#[derive(Debug, Clone)]
+pub struct PayloadBuilderHandle {
+    to_service: UnboundedSender<PayloadServiceCommand>,
+}
+
+impl PayloadBuilderHandle {
+    /// Starts building a payload with the given attributes, and returns its identifier.
+    ///
+    /// If a payload with the same attributes is already being built, the existing job is kept.
+    pub fn new_payload(&self, attributes: PayloadBuilderAttributes) -> PayloadId {
+        let id = attributes.payload_id();
+        let _ = self.to_service.send(PayloadServiceCommand::NewPayload(attributes));
+        id
+    }
+
+    /// Stops building the payload with the given identifier, and returns the best payload built
+    /// so far.
+    ///
+    /// The payload is kept until its job expires, so resolving it again returns the same payload.
+    /// The returned receiver resolves to `None` if the payload is unknown.
+    pub fn resolve(&self, id: PayloadId) -> oneshot::Receiver<Option<BuiltPayload>> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.to_service.send(PayloadServiceCommand::Resolve(id, tx));
+        rx
+    }
+}
+
+/// A job improving a payload until it is resolved.
+#[derive(Debug)]
+struct PayloadJob {
+    attributes: Arc<PayloadBuilderAttributes>,
+    /// The payload with the highest fees built so far.
+    best: Option<BuiltPayload>,
+    /// The payload is no longer improved after the deadline.
+    deadline: Instant,
+    /// The build in progress, at most one at a time.
+    pending: Option<JoinHandle<Result<BuiltPayload, PayloadBuilderError>>>,
+    /// Whether the payload was resolved, after which it is no longer improved.
+    resolved: bool,
+    /// The senders of the resolve requests that wait for the first payload of the job.
+    resolve: Vec<oneshot::Sender<Option<BuiltPayload>>>,
+}
+
+impl PayloadJob {
+    /// Keeps the built payload if it pays more fees than the best one.
+    ///
+    /// A resolved payload is no longer replaced, so that it is returned every time.
+    fn on_built(&mut self, id: PayloadId, result: Result<BuiltPayload, PayloadBuilderError>) {
+        if self.resolved && self.best.is_some() {
+            return
+        }
+        match result {
+            Ok(payload) => {
+                if self.best.as_ref().map_or(true, |best| payload.fees > best.fees) {
+                    let txs = payload.block.body.len();
+                    debug!(
+                        target: "payload_builder", ?id, fees = %payload.fees, txs,
+                        "Built better payload"
+                    );
+                    self.best = Some(payload);
+                }
+            }
+            Err(error) => {
+                warn!(target: "payload_builder", ?id, ?error, "Failed to build payload");
+            }
+        }
+    }
+}
+
+/// A service that builds payloads for the payload jobs it is sent over its
+/// [PayloadBuilderHandle]s.
+///
+/// A payload is built as soon as its job is started, and then rebuilt at every interval until it
+/// is resolved, to include the transactions that arrived in the pool in the meantime. The payload
+/// with the highest fees is returned.
+///
+/// The payloads are built on the blocking threads of the runtime, the service only polls the
+/// builds for their results.
+#[must_use = "PayloadBuilderService does nothing unless polled."]
+pub struct PayloadBuilderService<Client, Pool> {
+    builder: Arc<PayloadBuilder<Client, Pool>>,
+    config: PayloadBuilderConfig,
+    jobs: HashMap<PayloadId, PayloadJob>,
+    command_rx: UnboundedReceiverStream<PayloadServiceCommand>,
+    interval: Interval,
+}
+
+impl<Client, Pool> PayloadBuilderService<Client, Pool>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + StateTrieProvider + 'static,
+    Pool: TransactionPool + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    /// Creates a new [PayloadBuilderService] and a [PayloadBuilderHandle] to send it jobs.
+    pub fn new(
+        builder: PayloadBuilder<Client, Pool>,
+        config: PayloadBuilderConfig,
+    ) -> (Self, PayloadBuilderHandle) {
+        let (to_service, command_rx) = unbounded_channel();
+        let service = Self {
+            builder: Arc::new(builder),
+            config,
+            jobs: Default::default(),
+            command_rx: UnboundedReceiverStream::new(command_rx),
+            interval: tokio::time::interval(config.interval),
+        };
+        (service, PayloadBuilderHandle { to_service })
+    }
+
+    fn on_command(&mut self, command: PayloadServiceCommand) {
+        match command {
+            PayloadServiceCommand::NewPayload(attributes) => {
+                let id = attributes.payload_id();
+                if self.jobs.contains_key(&id) {
+                    return
+                }
+                let mut job = PayloadJob {
+                    attributes: Arc::new(attributes),
+                    best: None,
+                    deadline: Instant::now() + self.config.deadline,
+                    pending: None,
+                    resolved: false,
+                    resolve: Vec::new(),
+                };
+                self.improve(&mut job);
+                self.jobs.insert(id, job);
+            }
+            PayloadServiceCommand::Resolve(id, tx) => {
+                let Some(job) = self.jobs.get_mut(&id) else {
+                    let _ = tx.send(None);
+                    return
+                };
+                // the job is kept until it expires, so that the payload can be resolved again
+                job.resolved = true;
+                if job.best.is_none() && job.pending.is_some() {
+                    // the first payload of the job is still being built, the request waits for it
+                    job.resolve.push(tx);
+                } else {
+                    let _ = tx.send(job.best.clone());
+                }
+            }
+        }
+    }
+
+    /// Starts rebuilding the payload of the job, unless a build is already in progress.
+    fn improve(&self, job: &mut PayloadJob) {
+        if job.pending.is_some() {
+            return
+        }
+        let builder = Arc::clone(&self.builder);
+        let attributes = Arc::clone(&job.attributes);
+        job.pending = Some(tokio::task::spawn_blocking(move || builder.build(&attributes)));
+    }
+
+    /// Polls the builds in progress, and answers the resolve requests waiting for them.
+    fn poll_builds(&mut self, cx: &mut Context<'_>) {
+        for (id, job) in self.jobs.iter_mut() {
+            let Some(pending) = &mut job.pending else { continue };
+            let Poll::Ready(result) = pending.poll_unpin(cx) else { continue };
+            job.pending = None;
+            match result {
+                Ok(result) => job.on_built(*id, result),
+                Err(error) => {
+                    warn!(target: "payload_builder", ?id, ?error, "Payload build task failed")
+                }
+            }
+
+            for tx in job.resolve.drain(..) {
+                let _ = tx.send(job.best.clone());
+            }
+        }
+    }
+
+    /// Improves the payloads of the jobs before their deadline, and removes the jobs that have
+    /// not been resolved in time.
+    fn on_interval(&mut self) {
+        let now = Instant::now();
+        let expiry = self.config.deadline;
+        self.jobs.retain(|id, job| {
+            let expired = job.deadline + expiry <= now;
+            if expired {
+                debug!(target: "payload_builder", ?id, "Removing expired payload job");
+            }
+            !expired
+        });
+
+        let mut jobs = std::mem::take(&mut self.jobs);
+        for job in jobs.values_mut() {
+            if job.deadline > now && !job.resolved {
+                self.improve(job);
+            }
+        }
+        self.jobs = jobs;
+    }
+}
+
+impl<Client, Pool> Future for PayloadBuilderService<Client, Pool>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + StateTrieProvider + 'static,
+    Pool: TransactionPool + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            match this.command_rx.poll_next_unpin(cx) {
+                Poll::Ready(Some(command)) => this.on_command(command),
+                // all handles were dropped
+                Poll::Ready(None) => return Poll::Ready(()),
+                Poll::Pending => break,
+            }
+        }
+
+        while this.interval.poll_tick(cx).is_ready() {
+            this.on_interval();
+        }
+
+        // new builds may have been started above
+        this.poll_builds(cx);
+
+        Poll::Pending
+    }
+}
+
+impl<Client, Pool> std::fmt::Debug for PayloadBuilderService<Client, Pool> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PayloadBuilderService")
+            .field("jobs", &self.jobs.len())
+            .finish_non_exhaustive()
+    }
+}
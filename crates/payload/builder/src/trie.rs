@@ -0,0 +1,61 @@
+//! State root calculation of a payload.
+//!
+//! The accounts and storage slots changed by the payload are hashed and applied to the tries of
+//! the latest state by the client, see [StateTrieProvider::state_root_with_changes], which leaves
+//! the tries in the database untouched.
+
+use reth_executor::revm_wrap::{to_reth_acc, SubState};
+use reth_primitives::{keccak256, H256};
+use reth_provider::{
+    trie::{HashedStateChanges, HashedStorageChanges},
+    StateProvider, StateTrieProvider,
+};
+use revm::db::AccountState;
+
+/// Calculates the state root after applying the accounts of the cached database to the state
+/// with the given root, which must be the latest state.
+///
+/// Accounts that were only read are skipped, accounts that no longer exist or are empty are
+/// removed from the trie.
+pub(crate) fn state_root<Client, DB>(
+    client: &Client,
+    root: H256,
+    db: &SubState<DB>,
+) -> reth_interfaces::Result<H256>
+where
+    Client: StateTrieProvider,
+    DB: StateProvider,
+{
+    client.state_root_with_changes(root, &hashed_changes(db))
+}
+
+/// Returns the changes of the cached database, by hashed address and hashed slot.
+fn hashed_changes<DB: StateProvider>(db: &SubState<DB>) -> HashedStateChanges {
+    let mut changes = HashedStateChanges::default();
+    for (address, account) in db.accounts.iter() {
+        let (removed, wiped) = match account.account_state {
+            AccountState::None => continue,
+            AccountState::NotExisting => (true, true),
+            AccountState::StorageCleared => (account.info.is_empty(), true),
+            AccountState::Touched => (account.info.is_empty(), false),
+        };
+        let hashed_address = keccak256(address);
+
+        // the storage of a removed account is removed along with its storage trie
+        if removed {
+            let storage = HashedStorageChanges { wiped: true, ..Default::default() };
+            changes.accounts.insert(hashed_address, None);
+            changes.storages.insert(hashed_address, storage);
+            continue
+        }
+
+        changes.accounts.insert(hashed_address, Some(to_reth_acc(&account.info)));
+        let slots = account
+            .storage
+            .iter()
+            .map(|(slot, value)| (keccak256(H256(slot.to_be_bytes())), *value))
+            .collect();
+        changes.storages.insert(hashed_address, HashedStorageChanges { wiped, slots });
+    }
+    changes
+}
@@ -27,6 +27,26 @@ pub struct Block {
     pub withdrawals: Option<Vec<Withdrawal>>,
 }
 
+impl Block {
+    /// Seal the block with a known hash.
+    ///
+    /// WARNING: This method does not perform validation whether the hash is correct.
+    pub fn seal(self, hash: H256) -> SealedBlock {
+        SealedBlock {
+            header: self.header.seal(hash),
+            body: self.body,
+            ommers: self.ommers.into_iter().map(Header::seal_slow).collect(),
+            withdrawals: self.withdrawals,
+        }
+    }
+
+    /// Calculate the header hash and seal the block so that it can't be changed.
+    pub fn seal_slow(self) -> SealedBlock {
+        let hash = self.header.hash_slow();
+        self.seal(hash)
+    }
+}
+
 impl Deref for Block {
     type Target = Header;
     fn deref(&self) -> &Self::Target {
@@ -570,4 +590,17 @@ mod test {
         );
         assert_eq!(parsed, expected);
     }
+
+    #[test]
+    fn seal_block() {
+        let block = Block {
+            header: Header { number: 1, ..Default::default() },
+            ommers: vec![Header { number: 2, ..Default::default() }],
+            ..Default::default()
+        };
+        let sealed = block.clone().seal_slow();
+        assert_eq!(sealed.hash(), block.header.hash_slow());
+        assert_eq!(sealed.ommers[0].hash(), block.ommers[0].hash_slow());
+        assert_eq!(sealed.unseal(), block);
+    }
 }
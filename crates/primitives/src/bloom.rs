@@ -12,14 +12,43 @@ pub const BLOOM_BYTE_LENGTH: usize = 256;
 
 impl_fixed_hash_type!((Bloom, BLOOM_BYTE_LENGTH));
 
+impl Bloom {
+    /// Adds the bits of `input` to the bloom filter.
+    pub fn accrue(&mut self, input: &[u8]) {
+        *self |= bloom_of(input);
+    }
+
+    /// Adds the address and the topics of a log to the bloom filter.
+    pub fn accrue_log(&mut self, log: &Log) {
+        self.accrue(log.address.as_bytes());
+        for topic in &log.topics {
+            self.accrue(topic.as_bytes());
+        }
+    }
+
+    /// Returns whether `input` may have been added to the bloom filter.
+    ///
+    /// False positives are possible, false negatives are not.
+    pub fn contains_input(&self, input: &[u8]) -> bool {
+        self.contains_bloom(&bloom_of(input))
+    }
+
+    /// Returns whether all the bits of `other` are set in the bloom filter.
+    pub fn contains_bloom(&self, other: &Bloom) -> bool {
+        self.0.iter().zip(other.0.iter()).all(|(a, b)| a & b == *b)
+    }
+}
+
 // See Section 4.3.1 "Transaction Receipt" of the Yellow Paper
-fn m3_2048(bloom: &mut Bloom, x: &[u8]) {
+fn bloom_of(x: &[u8]) -> Bloom {
+    let mut bloom = Bloom::zero();
     let hash = keccak256(x);
     let h: &[u8; 32] = hash.as_ref();
     for i in [0, 2, 4] {
         let bit = (h[i + 1] as usize + ((h[i] as usize) << 8)) & 0x7FF;
         bloom.0[BLOOM_BYTE_LENGTH - 1 - bit / 8] |= 1 << (bit % 8);
     }
+    bloom
 }
 
 /// Calculate receipt logs bloom.
@@ -29,14 +58,19 @@ where
 {
     let mut bloom = Bloom::zero();
     for log in logs {
-        m3_2048(&mut bloom, log.address.as_bytes());
-        for topic in &log.topics {
-            m3_2048(&mut bloom, topic.as_bytes());
-        }
+        bloom.accrue_log(log);
     }
     bloom
 }
 
+/// Calculate the bloom of a block from the blooms of its receipts.
+pub fn receipts_bloom<'a, It>(blooms: It) -> Bloom
+where
+    It: IntoIterator<Item = &'a Bloom>,
+{
+    blooms.into_iter().fold(Bloom::zero(), |bloom, receipt_bloom| bloom | *receipt_bloom)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,4 +110,21 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn contains() {
+        let address = hex!("22341ae42d6dd7384bc8584e50419ea3ac75b83f");
+        let topic = hex!("04491edcd115127caedbd478e2e7895ed80c7847e903431f94f9cfa579cad47f");
+        let log = Log { address: address.into(), topics: vec![topic.into()], data: vec![].into() };
+
+        let bloom = logs_bloom([&log]);
+        assert!(bloom.contains_input(&address));
+        assert!(bloom.contains_input(&topic));
+        assert!(!bloom.contains_input(&[0u8; 20]));
+
+        let block_bloom = receipts_bloom(&[bloom, logs_bloom(&[])]);
+        assert_eq!(block_bloom, bloom);
+        assert!(block_bloom.contains_bloom(&bloom));
+        assert!(!Bloom::zero().contains_bloom(&bloom));
+    }
 }
@@ -17,6 +17,7 @@ pub static MAINNET: Lazy<ChainSpec> = Lazy::new(|| ChainSpec {
     genesis_hash: Some(H256(hex!(
         "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
     ))),
+    paris_block: Some(15537394),
     hardforks: BTreeMap::from([
         (Hardfork::Frontier, ForkCondition::Block(0)),
         (Hardfork::Homestead, ForkCondition::Block(1150000)),
@@ -50,6 +51,7 @@ pub static GOERLI: Lazy<ChainSpec> = Lazy::new(|| ChainSpec {
     genesis_hash: Some(H256(hex!(
         "bf7e331f7f7c1dd2e05159666b3bf8bc7a8a3a9eb1d518969eab529dd9b88c1a"
     ))),
+    paris_block: Some(7382819),
     hardforks: BTreeMap::from([
         (Hardfork::Frontier, ForkCondition::Block(0)),
         (Hardfork::Istanbul, ForkCondition::Block(1561651)),
@@ -70,6 +72,7 @@ pub static SEPOLIA: Lazy<ChainSpec> = Lazy::new(|| ChainSpec {
     genesis_hash: Some(H256(hex!(
         "25a5cc106eea7138acab33231d7160d69cb777ee0c2c553fcddf5138993e6dd9"
     ))),
+    paris_block: Some(1735371),
     hardforks: BTreeMap::from([
         (Hardfork::Frontier, ForkCondition::Block(0)),
         (Hardfork::Homestead, ForkCondition::Block(0)),
@@ -113,6 +116,14 @@ pub struct ChainSpec {
     #[serde(skip, default)]
     pub genesis_hash: Option<H256>,
 
+    /// The first block after the merge, i.e. the first block at which
+    /// [Paris](Hardfork::Paris) is active.
+    ///
+    /// This is known ahead of time for the public chains. Unlike the `fork_block` of
+    /// [`ForkCondition::TTD`], it does not change the fork id.
+    #[serde(skip, default)]
+    pub paris_block: Option<BlockNumber>,
+
     /// The genesis block
     pub genesis: Genesis,
 
@@ -142,6 +153,15 @@ impl ChainSpec {
             None
         };
 
+        // If Cancun is activated at genesis, the genesis block has no blob gas and no excess, and
+        // its parent beacon block root is zero.
+        let (blob_gas_used, excess_blob_gas, parent_beacon_block_root) =
+            if self.fork(Hardfork::Cancun).active_at_timestamp(self.genesis.timestamp) {
+                (Some(0), Some(0), Some(H256::zero()))
+            } else {
+                (None, None, None)
+            };
+
         Header {
             gas_limit: self.genesis.gas_limit,
             difficulty: self.genesis.difficulty,
@@ -152,6 +172,9 @@ impl ChainSpec {
             mix_hash: self.genesis.mix_hash,
             beneficiary: self.genesis.coinbase,
             base_fee_per_gas,
+            blob_gas_used,
+            excess_blob_gas,
+            parent_beacon_block_root,
             ..Default::default()
         }
     }
@@ -279,12 +302,13 @@ impl From<EthersGenesis> for ChainSpec {
         }
 
         // Time-based hardforks
-        let time_hardforks = genesis
-            .config
-            .shanghai_time
-            .map(|time| (Hardfork::Shanghai, ForkCondition::Timestamp(time)))
-            .into_iter()
-            .collect::<BTreeMap<_, _>>();
+        let time_hardforks = [
+            (Hardfork::Shanghai, genesis.config.shanghai_time),
+            (Hardfork::Cancun, genesis.config.cancun_time),
+        ]
+        .into_iter()
+        .filter_map(|(hardfork, opt)| opt.map(|time| (hardfork, ForkCondition::Timestamp(time))))
+        .collect::<BTreeMap<_, _>>();
 
         hardforks.extend(time_hardforks);
 
@@ -292,6 +316,7 @@ impl From<EthersGenesis> for ChainSpec {
             chain: genesis.config.chain_id.into(),
             genesis: genesis_block,
             genesis_hash: None,
+            paris_block: None,
             hardforks,
         }
     }
@@ -443,6 +468,13 @@ impl ChainSpecBuilder {
         self
     }
 
+    /// Enable Cancun at genesis.
+    pub fn cancun_activated(mut self) -> Self {
+        self = self.shanghai_activated();
+        self.hardforks.insert(Hardfork::Cancun, ForkCondition::Timestamp(0));
+        self
+    }
+
     /// Build the resulting [`ChainSpec`].
     ///
     /// # Panics
@@ -454,6 +486,7 @@ impl ChainSpecBuilder {
             chain: self.chain.expect("The chain is required"),
             genesis: self.genesis.expect("The genesis is required"),
             genesis_hash: None,
+            paris_block: None,
             hardforks: self.hardforks,
         }
     }
@@ -892,6 +925,42 @@ mod tests {
         );
     }
 
+    /// Checks that a time-based fork following another one is announced as the next fork, and
+    /// that the hash of both timestamps is applied once it is active.
+    #[test]
+    fn cancun_fork_id() {
+        let spec = ChainSpecBuilder::mainnet()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(1668000000))
+            .with_fork(Hardfork::Cancun, ForkCondition::Timestamp(1700000000))
+            .build();
+
+        let shanghai = ForkHash([0x71, 0x14, 0x76, 0x44]);
+        let mut cancun = shanghai;
+        cancun += 1700000000u64;
+
+        test_fork_ids(
+            &spec,
+            &[
+                (
+                    Head { number: 20000000, timestamp: 1668000000, ..Default::default() },
+                    ForkId { hash: shanghai, next: 1700000000 },
+                ), // First Shanghai block
+                (
+                    Head { number: 20000000, timestamp: 1699999999, ..Default::default() },
+                    ForkId { hash: shanghai, next: 1700000000 },
+                ), // Last Shanghai block
+                (
+                    Head { number: 20000001, timestamp: 1700000000, ..Default::default() },
+                    ForkId { hash: cancun, next: 0 },
+                ), // First Cancun block
+            ],
+        );
+
+        let spec = ChainSpecBuilder::mainnet().cancun_activated().build();
+        assert!(spec.fork(Hardfork::Shanghai).active_at_timestamp(0));
+        assert!(spec.fork(Hardfork::Cancun).active_at_timestamp(0));
+    }
+
     /// Checks that the fork is not active at a terminal ttd block.
     #[test]
     fn check_terminal_ttd() {
@@ -933,6 +1002,7 @@ mod tests {
             "arrowGlacierBlock": 0,
             "grayGlacierBlock": 0,
             "shanghaiTime": 0,
+            "cancunTime": 1000,
             "terminalTotalDifficulty": 0,
             "terminalTotalDifficultyPassed": true,
             "ethash": {}
@@ -1027,5 +1097,9 @@ mod tests {
             chainspec.hardforks.get(&Hardfork::Shanghai).unwrap(),
             &ForkCondition::Timestamp(0)
         );
+        assert_eq!(
+            chainspec.hardforks.get(&Hardfork::Cancun).unwrap(),
+            &ForkCondition::Timestamp(1000)
+        );
     }
 }
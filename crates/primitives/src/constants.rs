@@ -34,3 +34,27 @@ pub const KECCAK_EMPTY: H256 =
 /// Ommer root of empty list.
 pub const EMPTY_OMMER_ROOT: H256 =
     H256(hex!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"));
+
+/// Gas consumption of a single data blob as defined in [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+pub const DATA_GAS_PER_BLOB: u64 = 131_072; // 32*4096 = 131072 == 2^17 == 0x20000
+
+/// Target number of data blobs in a single block as defined in [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+pub const TARGET_BLOBS_PER_BLOCK: u64 = 3;
+
+/// Maximum number of data blobs in a single block as defined in [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+pub const MAX_BLOBS_PER_BLOCK: u64 = 6;
+
+/// Target consumable blob gas for data blobs per block as defined in [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+pub const TARGET_DATA_GAS_PER_BLOCK: u64 = TARGET_BLOBS_PER_BLOCK * DATA_GAS_PER_BLOB;
+
+/// Maximum consumable blob gas for data blobs per block as defined in [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+pub const MAX_DATA_GAS_PER_BLOCK: u64 = MAX_BLOBS_PER_BLOCK * DATA_GAS_PER_BLOB;
+
+/// Minimum gas price for a data blob as defined in [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+pub const BLOB_TX_MIN_BLOB_GASPRICE: u128 = 1;
+
+/// Controls the maximum rate of change for blob gas price as defined in [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+pub const BLOB_GASPRICE_UPDATE_FRACTION: u128 = 3_338_477;
+
+/// Version byte of the versioned hashes of KZG commitments as defined in [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
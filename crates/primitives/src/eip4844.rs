@@ -0,0 +1,139 @@
+//! Helpers for the blob transactions and the blob gas market of
+//! [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844).
+
+use crate::{
+    constants::{
+        BLOB_GASPRICE_UPDATE_FRACTION, BLOB_TX_MIN_BLOB_GASPRICE, TARGET_DATA_GAS_PER_BLOCK,
+        VERSIONED_HASH_VERSION_KZG,
+    },
+    H256,
+};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Re-export of the KZG types used to verify the blobs.
+pub use c_kzg::{Blob, Bytes48, KzgProof, KzgSettings, BYTES_PER_BLOB};
+
+/// Calculates the versioned hash of a KZG commitment.
+pub fn kzg_to_versioned_hash(commitment: &[u8]) -> H256 {
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    H256(hash)
+}
+
+/// Loads the KZG trusted setup used to verify the blobs, from a file in the format of the `c-kzg`
+/// trusted setups.
+pub fn load_trusted_setup(path: &Path) -> Result<KzgSettings, c_kzg::Error> {
+    KzgSettings::load_trusted_setup_file(path.to_path_buf())
+}
+
+/// Calculates the excess blob gas of a block from the excess blob gas and the blob gas used of its
+/// parent.
+///
+/// The values come from the headers of the blocks, so this saturates rather than overflows.
+pub fn calculate_excess_blob_gas(parent_excess_blob_gas: u64, parent_blob_gas_used: u64) -> u64 {
+    parent_excess_blob_gas
+        .saturating_add(parent_blob_gas_used)
+        .saturating_sub(TARGET_DATA_GAS_PER_BLOCK)
+}
+
+/// Calculates the price of a unit of blob gas from the excess blob gas of the block.
+///
+/// Saturates to [`u128::MAX`] if the price does not fit, which no transaction can pay.
+pub fn calculate_blob_gasprice(excess_blob_gas: u64) -> u128 {
+    fake_exponential(
+        BLOB_TX_MIN_BLOB_GASPRICE,
+        excess_blob_gas as u128,
+        BLOB_GASPRICE_UPDATE_FRACTION,
+    )
+}
+
+/// Approximates `factor * e ** (numerator / denominator)` using Taylor expansion.
+///
+/// Returns [`u128::MAX`] if any of the intermediate values overflows.
+fn fake_exponential(factor: u128, numerator: u128, denominator: u128) -> u128 {
+    let approximate = || {
+        let mut i = 1u128;
+        let mut output = 0u128;
+        let mut numerator_accum = factor.checked_mul(denominator)?;
+        while numerator_accum > 0 {
+            output = output.checked_add(numerator_accum)?;
+            numerator_accum =
+                numerator_accum.checked_mul(numerator)?.checked_div(denominator.checked_mul(i)?)?;
+            i += 1;
+        }
+        output.checked_div(denominator)
+    };
+    approximate().unwrap_or(u128::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::DATA_GAS_PER_BLOB;
+
+    #[test]
+    fn fake_exp() {
+        for (factor, numerator, denominator, expected) in [
+            (1u128, 0u128, 1u128, 1u128),
+            (38493, 0, 1000, 38493),
+            (0, 1234, 2345, 0),
+            (1, 2, 1, 6),
+            (1, 4, 2, 6),
+            (1, 3, 1, 16),
+            (1, 6, 2, 18),
+            (1, 4, 1, 49),
+            (1, 8, 2, 50),
+            (10, 8, 2, 542),
+            (11, 8, 2, 596),
+            (1, 5, 1, 136),
+            (1, 5, 2, 11),
+            (2, 5, 2, 23),
+        ] {
+            assert_eq!(fake_exponential(factor, numerator, denominator), expected);
+        }
+
+        // Overflows saturate
+        assert_eq!(fake_exponential(u128::MAX, 1, 2), u128::MAX);
+        assert_eq!(fake_exponential(1, u128::MAX, 1), u128::MAX);
+    }
+
+    #[test]
+    fn excess_blob_gas() {
+        // Below the target, the excess goes down to zero
+        assert_eq!(calculate_excess_blob_gas(0, 0), 0);
+        assert_eq!(calculate_excess_blob_gas(0, TARGET_DATA_GAS_PER_BLOCK), 0);
+        assert_eq!(calculate_excess_blob_gas(DATA_GAS_PER_BLOB, 0), 0);
+
+        // Above the target, the excess accumulates
+        assert_eq!(
+            calculate_excess_blob_gas(0, TARGET_DATA_GAS_PER_BLOCK + DATA_GAS_PER_BLOB),
+            DATA_GAS_PER_BLOB
+        );
+        assert_eq!(
+            calculate_excess_blob_gas(DATA_GAS_PER_BLOB, TARGET_DATA_GAS_PER_BLOCK),
+            DATA_GAS_PER_BLOB
+        );
+
+        // Values taken from the headers can't overflow
+        assert_eq!(
+            calculate_excess_blob_gas(u64::MAX, u64::MAX),
+            u64::MAX - TARGET_DATA_GAS_PER_BLOCK
+        );
+    }
+
+    #[test]
+    fn blob_gasprice() {
+        assert_eq!(calculate_blob_gasprice(0), BLOB_TX_MIN_BLOB_GASPRICE);
+        assert_eq!(calculate_blob_gasprice(2_314_057), 1);
+        assert_eq!(calculate_blob_gasprice(2_314_058), 2);
+        assert_eq!(calculate_blob_gasprice(10 * 1024 * 1024), 23);
+        assert_eq!(calculate_blob_gasprice(u64::MAX), u128::MAX);
+    }
+
+    #[test]
+    fn versioned_hash() {
+        let hash = kzg_to_versioned_hash(&[0u8; 48]);
+        assert_eq!(hash[0], VERSIONED_HASH_VERSION_KZG);
+    }
+}
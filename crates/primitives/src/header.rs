@@ -1,4 +1,5 @@
 use crate::{
+    eip4844::{calculate_blob_gasprice, calculate_excess_blob_gas},
     keccak256,
     proofs::{EMPTY_LIST_HASH, EMPTY_ROOT},
     BlockHash, BlockNumber, Bloom, Bytes, H160, H256, U256,
@@ -33,7 +34,8 @@ pub struct Head {
 }
 
 /// Block header
-#[main_codec]
+#[main_codec(no_arbitrary)]
+#[add_arbitrary_tests(rlp, compact)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Header {
     /// The Keccak 256-bit hash of the parent
@@ -87,6 +89,19 @@ pub struct Header {
     /// above the gas target, and decreasing when blocks are below the gas target. The base fee per
     /// gas is burned.
     pub base_fee_per_gas: Option<u64>,
+    // The fields of later forks take unused bits of the flags of the compact encoding, and are
+    // `None` in the headers stored before them. They go before `extra_data`, which is read up to
+    // the end of the value.
+    /// The total amount of blob gas consumed by the transactions within the block, added in
+    /// EIP-4844.
+    pub blob_gas_used: Option<u64>,
+    /// A running total of blob gas consumed in excess of the target, prior to the block. Blocks
+    /// with above-target blob gas consumption increase this value, blocks with below-target blob
+    /// gas consumption decrease it (bounded at 0). This was added in EIP-4844.
+    pub excess_blob_gas: Option<u64>,
+    /// The hash of the parent beacon block's root, which is stored in the beacon roots contract
+    /// before the transactions of the block are executed. This was added in EIP-4788.
+    pub parent_beacon_block_root: Option<H256>,
     /// An arbitrary byte array containing data relevant to this block. This must be 32 bytes or
     /// fewer; formally Hx.
     pub extra_data: Bytes,
@@ -112,6 +127,9 @@ impl Default for Header {
             nonce: 0,
             base_fee_per_gas: None,
             withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
         }
     }
 }
@@ -145,6 +163,19 @@ impl Header {
         self.transactions_root == EMPTY_ROOT
     }
 
+    /// Returns the price of a unit of blob gas in the block, if the block is after EIP-4844.
+    pub fn blob_fee(&self) -> Option<u128> {
+        self.excess_blob_gas.map(calculate_blob_gasprice)
+    }
+
+    /// Calculates the excess blob gas of the next block, if this block is after EIP-4844.
+    ///
+    /// A block which does not have a blob gas used can not be followed by blobs, so this is
+    /// `None` if either value is missing.
+    pub fn next_block_excess_blob_gas(&self) -> Option<u64> {
+        Some(calculate_excess_blob_gas(self.excess_blob_gas?, self.blob_gas_used?))
+    }
+
     /// Seal the header with a known hash.
     ///
     /// WARNING: This method does not perform validation whether the hash is correct.
@@ -158,6 +189,26 @@ impl Header {
         self.seal(hash)
     }
 
+    /// Unsets the fields of the forks after the first one whose fields are missing, as the fields
+    /// of a header are only present in fork order.
+    #[cfg(any(test, feature = "arbitrary"))]
+    fn with_fork_ordered_fields(mut self) -> Self {
+        if self.base_fee_per_gas.is_none() {
+            self.withdrawals_root = None;
+        }
+        if self.withdrawals_root.is_none() ||
+            self.blob_gas_used.is_none() ||
+            self.excess_blob_gas.is_none()
+        {
+            self.blob_gas_used = None;
+            self.excess_blob_gas = None;
+        }
+        if self.excess_blob_gas.is_none() {
+            self.parent_beacon_block_root = None;
+        }
+        self
+    }
+
     fn header_payload_length(&self) -> usize {
         let mut length = 0;
         length += self.parent_hash.length();
@@ -178,11 +229,33 @@ impl Header {
 
         if let Some(base_fee) = self.base_fee_per_gas {
             length += U256::from(base_fee).length();
-        } else if self.withdrawals_root.is_some() {
+        } else if self.withdrawals_root.is_some() ||
+            self.blob_gas_used.is_some() ||
+            self.excess_blob_gas.is_some() ||
+            self.parent_beacon_block_root.is_some()
+        {
             length += 1; // EMTY STRING CODE
         }
         if let Some(root) = self.withdrawals_root {
             length += root.length();
+        } else if self.blob_gas_used.is_some() ||
+            self.excess_blob_gas.is_some() ||
+            self.parent_beacon_block_root.is_some()
+        {
+            length += 1; // EMTY STRING CODE
+        }
+        if let Some(blob_gas_used) = self.blob_gas_used {
+            length += U256::from(blob_gas_used).length();
+        } else if self.excess_blob_gas.is_some() || self.parent_beacon_block_root.is_some() {
+            length += 1; // EMTY STRING CODE
+        }
+        if let Some(excess_blob_gas) = self.excess_blob_gas {
+            length += U256::from(excess_blob_gas).length();
+        } else if self.parent_beacon_block_root.is_some() {
+            length += 1; // EMTY STRING CODE
+        }
+        if let Some(root) = self.parent_beacon_block_root {
+            length += root.length();
         }
 
         length
@@ -211,15 +284,46 @@ impl Encodable for Header {
         H64::from_low_u64_be(self.nonce).encode(out);
 
         // Encode base fee. Put empty string if base fee is missing,
-        // but withdrawals root is present.
+        // but withdrawals root, blob gas fields or parent beacon block root are present.
         if let Some(ref base_fee) = self.base_fee_per_gas {
             U256::from(*base_fee).encode(out);
-        } else if self.withdrawals_root.is_some() {
+        } else if self.withdrawals_root.is_some() ||
+            self.blob_gas_used.is_some() ||
+            self.excess_blob_gas.is_some() ||
+            self.parent_beacon_block_root.is_some()
+        {
             out.put_u8(EMPTY_STRING_CODE);
         }
 
+        // Encode withdrawals root. Put empty string if withdrawals root is missing,
+        // but blob gas fields or parent beacon block root are present.
         if let Some(ref root) = self.withdrawals_root {
             root.encode(out);
+        } else if self.blob_gas_used.is_some() ||
+            self.excess_blob_gas.is_some() ||
+            self.parent_beacon_block_root.is_some()
+        {
+            out.put_u8(EMPTY_STRING_CODE);
+        }
+
+        // Encode blob gas used. Put empty string if blob gas used is missing,
+        // but excess blob gas or parent beacon block root are present.
+        if let Some(ref blob_gas_used) = self.blob_gas_used {
+            U256::from(*blob_gas_used).encode(out);
+        } else if self.excess_blob_gas.is_some() || self.parent_beacon_block_root.is_some() {
+            out.put_u8(EMPTY_STRING_CODE);
+        }
+
+        // Encode excess blob gas. Put empty string if excess blob gas is missing,
+        // but parent beacon block root is present.
+        if let Some(ref excess_blob_gas) = self.excess_blob_gas {
+            U256::from(*excess_blob_gas).encode(out);
+        } else if self.parent_beacon_block_root.is_some() {
+            out.put_u8(EMPTY_STRING_CODE);
+        }
+
+        if let Some(ref root) = self.parent_beacon_block_root {
+            root.encode(out);
         }
     }
 
@@ -256,6 +360,9 @@ impl Decodable for Header {
             nonce: H64::decode(buf)?.to_low_u64_be(),
             base_fee_per_gas: None,
             withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
         };
         if started_len - buf.len() < rlp_head.payload_length {
             if buf.first().map(|b| *b == EMPTY_STRING_CODE).unwrap_or_default() {
@@ -265,7 +372,22 @@ impl Decodable for Header {
             }
         }
         if started_len - buf.len() < rlp_head.payload_length {
-            this.withdrawals_root = Some(Decodable::decode(buf)?);
+            if buf.first().map(|b| *b == EMPTY_STRING_CODE).unwrap_or_default() {
+                buf.advance(1)
+            } else {
+                this.withdrawals_root = Some(Decodable::decode(buf)?);
+            }
+        }
+        // Both blob gas fields are present since EIP-4844, so an empty string is a zero blob gas
+        // used rather than a missing one.
+        if started_len - buf.len() < rlp_head.payload_length {
+            this.blob_gas_used = Some(U256::decode(buf)?.to::<u64>());
+        }
+        if started_len - buf.len() < rlp_head.payload_length {
+            this.excess_blob_gas = Some(U256::decode(buf)?.to::<u64>());
+        }
+        if started_len - buf.len() < rlp_head.payload_length {
+            this.parent_beacon_block_root = Some(Decodable::decode(buf)?);
         }
         let consumed = started_len - buf.len();
         if consumed != rlp_head.payload_length {
@@ -278,6 +400,114 @@ impl Decodable for Header {
     }
 }
 
+#[cfg(any(test, feature = "arbitrary"))]
+impl proptest::arbitrary::Arbitrary for Header {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Header>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::{any, Strategy};
+
+        let hashes = any::<(H256, H256, H160, H256, H256, H256, Option<H256>, Bloom, U256)>();
+        let fields = any::<(
+            BlockNumber,
+            u64,
+            u64,
+            u64,
+            H256,
+            u64,
+            Option<u64>,
+            Option<u64>,
+            Option<u64>,
+            Option<H256>,
+            Bytes,
+        )>();
+        (hashes, fields)
+            .prop_map(
+                |(
+                    (
+                        parent_hash,
+                        ommers_hash,
+                        beneficiary,
+                        state_root,
+                        transactions_root,
+                        receipts_root,
+                        withdrawals_root,
+                        logs_bloom,
+                        difficulty,
+                    ),
+                    (
+                        number,
+                        gas_limit,
+                        gas_used,
+                        timestamp,
+                        mix_hash,
+                        nonce,
+                        base_fee_per_gas,
+                        blob_gas_used,
+                        excess_blob_gas,
+                        parent_beacon_block_root,
+                        extra_data,
+                    ),
+                )| {
+                    Header {
+                        parent_hash,
+                        ommers_hash,
+                        beneficiary,
+                        state_root,
+                        transactions_root,
+                        receipts_root,
+                        withdrawals_root,
+                        logs_bloom,
+                        difficulty,
+                        number,
+                        gas_limit,
+                        gas_used,
+                        timestamp,
+                        mix_hash,
+                        nonce,
+                        base_fee_per_gas,
+                        blob_gas_used,
+                        excess_blob_gas,
+                        parent_beacon_block_root,
+                        extra_data,
+                    }
+                    .with_fork_ordered_fields()
+                },
+            )
+            .boxed()
+    }
+}
+
+#[cfg(any(test, feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for Header {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let header = Header {
+            parent_hash: u.arbitrary()?,
+            ommers_hash: u.arbitrary()?,
+            beneficiary: u.arbitrary()?,
+            state_root: u.arbitrary()?,
+            transactions_root: u.arbitrary()?,
+            receipts_root: u.arbitrary()?,
+            withdrawals_root: u.arbitrary()?,
+            logs_bloom: u.arbitrary()?,
+            difficulty: u.arbitrary()?,
+            number: u.arbitrary()?,
+            gas_limit: u.arbitrary()?,
+            gas_used: u.arbitrary()?,
+            timestamp: u.arbitrary()?,
+            mix_hash: u.arbitrary()?,
+            nonce: u.arbitrary()?,
+            base_fee_per_gas: u.arbitrary()?,
+            blob_gas_used: u.arbitrary()?,
+            excess_blob_gas: u.arbitrary()?,
+            parent_beacon_block_root: u.arbitrary()?,
+            extra_data: u.arbitrary()?,
+        };
+        Ok(header.with_fork_ordered_fields())
+    }
+}
+
 /// A [`Header`] that is sealed at a precalculated hash, use [`SealedHeader::unseal()`] if you want
 /// to modify header.
 #[add_arbitrary_tests(rlp)]
@@ -464,10 +694,120 @@ impl From<HeadersDirection> for bool {
 #[cfg(test)]
 mod tests {
     use super::{Bytes, Decodable, Encodable, Header, H256};
-    use crate::{Address, HeadersDirection, U256};
+    use crate::{Address, BlockNumber, Bloom, HeadersDirection, H160, U256};
     use ethers_core::utils::hex::{self, FromHex};
+    use reth_codecs::{main_codec, Compact};
     use std::str::FromStr;
 
+    /// The layout of the headers stored before EIP-4844.
+    #[main_codec(no_arbitrary)]
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    struct StoredHeaderBeforeCancun {
+        parent_hash: H256,
+        ommers_hash: H256,
+        beneficiary: H160,
+        state_root: H256,
+        transactions_root: H256,
+        receipts_root: H256,
+        withdrawals_root: Option<H256>,
+        logs_bloom: Bloom,
+        difficulty: U256,
+        number: BlockNumber,
+        gas_limit: u64,
+        gas_used: u64,
+        timestamp: u64,
+        mix_hash: H256,
+        nonce: u64,
+        base_fee_per_gas: Option<u64>,
+        extra_data: Bytes,
+    }
+
+    #[test]
+    fn decode_header_stored_before_cancun() {
+        let stored = StoredHeaderBeforeCancun {
+            parent_hash: H256::repeat_byte(0x01),
+            state_root: H256::repeat_byte(0x02),
+            withdrawals_root: Some(H256::repeat_byte(0x03)),
+            difficulty: U256::from(0x8ae_u64),
+            number: 0xd05,
+            gas_limit: 0x115c,
+            gas_used: 0x15b3,
+            timestamp: 0x1a0a,
+            nonce: 0x42,
+            base_fee_per_gas: Some(0x07),
+            extra_data: Bytes::from(vec![0xaa, 0xbb]),
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        let len = stored.clone().to_compact(&mut buf);
+
+        let header = Header {
+            parent_hash: stored.parent_hash,
+            ommers_hash: stored.ommers_hash,
+            beneficiary: stored.beneficiary,
+            state_root: stored.state_root,
+            transactions_root: stored.transactions_root,
+            receipts_root: stored.receipts_root,
+            withdrawals_root: stored.withdrawals_root,
+            logs_bloom: stored.logs_bloom,
+            difficulty: stored.difficulty,
+            number: stored.number,
+            gas_limit: stored.gas_limit,
+            gas_used: stored.gas_used,
+            timestamp: stored.timestamp,
+            mix_hash: stored.mix_hash,
+            nonce: stored.nonce,
+            base_fee_per_gas: stored.base_fee_per_gas,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            extra_data: stored.extra_data,
+        };
+        assert_eq!(Header::from_compact(&buf, len).0, header);
+
+        // the headers without the new fields keep the same encoding
+        let mut header_buf = vec![];
+        assert_eq!(header.to_compact(&mut header_buf), len);
+        assert_eq!(header_buf, buf);
+    }
+
+    #[test]
+    fn arbitrary_fields_in_fork_order() {
+        let header = Header {
+            base_fee_per_gas: None,
+            withdrawals_root: Some(H256::zero()),
+            blob_gas_used: Some(1),
+            excess_blob_gas: Some(1),
+            ..Default::default()
+        }
+        .with_fork_ordered_fields();
+        assert_eq!(header.withdrawals_root, None);
+        assert_eq!(header.blob_gas_used, None);
+        assert_eq!(header.excess_blob_gas, None);
+
+        let header = Header {
+            base_fee_per_gas: Some(1),
+            withdrawals_root: Some(H256::zero()),
+            blob_gas_used: None,
+            excess_blob_gas: Some(1),
+            ..Default::default()
+        }
+        .with_fork_ordered_fields();
+        assert_eq!(header.withdrawals_root, Some(H256::zero()));
+        assert_eq!(header.excess_blob_gas, None);
+
+        let header = Header {
+            base_fee_per_gas: Some(1),
+            withdrawals_root: Some(H256::zero()),
+            blob_gas_used: Some(1),
+            excess_blob_gas: None,
+            parent_beacon_block_root: Some(H256::zero()),
+            ..Default::default()
+        }
+        .with_fork_ordered_fields();
+        assert_eq!(header.parent_beacon_block_root, None);
+    }
+
     // Test vector from: https://eips.ethereum.org/EIPS/eip-2481
     #[test]
     fn test_encode_block_header() {
@@ -515,6 +855,9 @@ mod tests {
             nonce: 0,
             base_fee_per_gas: Some(0x036b_u64),
             withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
         };
         assert_eq!(header.hash_slow(), expected_hash);
     }
@@ -593,6 +936,57 @@ mod tests {
         assert_eq!(header.hash_slow(), expected_hash);
     }
 
+    #[test]
+    fn header_with_blob_gas_roundtrip() {
+        let header = Header {
+            number: 0x01,
+            gas_limit: 0x7fffffffffffffff,
+            base_fee_per_gas: Some(0x09),
+            withdrawals_root: Some(H256::from_low_u64_be(1)),
+            blob_gas_used: Some(0x020000),
+            excess_blob_gas: Some(0x060000),
+            parent_beacon_block_root: Some(H256::from_low_u64_be(2)),
+            ..Default::default()
+        };
+        let mut data = vec![];
+        header.encode(&mut data);
+        assert_eq!(header.length(), data.len());
+        assert_eq!(<Header as Decodable>::decode(&mut data.as_slice()).unwrap(), header);
+
+        // Missing fields before the blob gas ones are encoded as empty strings, and a zero blob
+        // gas used is not mistaken for a missing one
+        let header =
+            Header { blob_gas_used: Some(0), excess_blob_gas: Some(0), ..Default::default() };
+        let mut data = vec![];
+        header.encode(&mut data);
+        assert_eq!(header.length(), data.len());
+        assert_eq!(<Header as Decodable>::decode(&mut data.as_slice()).unwrap(), header);
+
+        let header = Header {
+            blob_gas_used: Some(0),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(H256::from_low_u64_be(2)),
+            ..Default::default()
+        };
+        let mut data = vec![];
+        header.encode(&mut data);
+        assert_eq!(header.length(), data.len());
+        assert_eq!(<Header as Decodable>::decode(&mut data.as_slice()).unwrap(), header);
+    }
+
+    #[test]
+    fn next_block_excess_blob_gas() {
+        let header = Header {
+            blob_gas_used: Some(0x0c0000),
+            excess_blob_gas: Some(0x020000),
+            ..Default::default()
+        };
+        assert_eq!(header.next_block_excess_blob_gas(), Some(0x080000));
+        assert_eq!(header.blob_fee(), Some(1));
+        assert_eq!(Header::default().next_block_excess_blob_gas(), None);
+        assert_eq!(Header::default().blob_fee(), None);
+    }
+
     #[test]
     fn sanity_direction() {
         let reverse = true;
@@ -15,6 +15,7 @@ mod block;
 pub mod bloom;
 mod chain;
 pub mod constants;
+pub mod eip4844;
 mod error;
 mod forkid;
 mod genesis;
@@ -58,9 +59,10 @@ pub use peer::{PeerId, WithPeerId};
 pub use receipt::Receipt;
 pub use storage::{StorageEntry, StorageTrieEntry};
 pub use transaction::{
-    AccessList, AccessListItem, FromRecoveredTransaction, IntoRecoveredTransaction, Signature,
-    Transaction, TransactionKind, TransactionSigned, TransactionSignedEcRecovered, TxEip1559,
-    TxEip2930, TxLegacy, TxType,
+    AccessList, AccessListItem, BlobTransaction, BlobTransactionSidecar,
+    BlobTransactionValidationError, FromRecoveredTransaction, IntoRecoveredTransaction,
+    PooledTransactionsElement, Signature, Transaction, TransactionKind, TransactionSigned,
+    TransactionSignedEcRecovered, TxEip1559, TxEip2930, TxEip4844, TxLegacy, TxType,
 };
 pub use withdrawal::Withdrawal;
 
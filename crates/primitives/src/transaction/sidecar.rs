@@ -0,0 +1,534 @@
+//! Blob transactions as they are sent over the network, with the blobs they commit to.
+
+use super::{Signature, Transaction, TransactionSigned, TxEip4844, TxType};
+use crate::{
+    eip4844::{kzg_to_versioned_hash, Blob, Bytes48, KzgProof, KzgSettings, BYTES_PER_BLOB},
+    keccak256, Bytes, TxHash, H256,
+};
+use bytes::{Buf, BufMut};
+use reth_rlp::{length_of_length, Decodable, DecodeError, Encodable, Header};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::Deref;
+
+/// Errors of the validation of the sidecar of a blob transaction.
+#[derive(Debug, thiserror::Error)]
+pub enum BlobTransactionValidationError {
+    /// The transaction is not a blob transaction.
+    #[error("transaction is not a blob transaction")]
+    NotBlobTransaction,
+    /// The numbers of blobs, commitments and proofs do not match the versioned hashes.
+    #[error("expected {expected} blobs, commitments and proofs, got {blobs}, {commitments} and {proofs}")]
+    LengthMismatch {
+        /// Number of versioned hashes of the transaction.
+        expected: usize,
+        /// Number of blobs of the sidecar.
+        blobs: usize,
+        /// Number of commitments of the sidecar.
+        commitments: usize,
+        /// Number of proofs of the sidecar.
+        proofs: usize,
+    },
+    /// A commitment does not match the versioned hash of the transaction.
+    #[error("versioned hash {0:?} does not match its commitment")]
+    VersionedHashMismatch(H256),
+    /// The proofs do not prove the blobs against their commitments.
+    #[error("invalid KZG proofs")]
+    InvalidProofs,
+    /// The blobs, commitments or proofs could not be verified by KZG.
+    #[error("KZG error: {0:?}")]
+    Kzg(c_kzg::Error),
+}
+
+/// Serde representation of a [`BlobTransactionSidecar`], whose KZG types are not serde.
+#[derive(Serialize, Deserialize)]
+struct SerdeSidecar {
+    blobs: Vec<Bytes>,
+    commitments: Vec<Bytes>,
+    proofs: Vec<Bytes>,
+}
+
+/// The blobs of a blob transaction, with the KZG commitments and proofs to verify them.
+///
+/// It is only sent alongside the transaction over the network, and is not part of the block.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BlobTransactionSidecar {
+    /// The blobs of the transaction.
+    pub blobs: Vec<Blob>,
+    /// The KZG commitments to the blobs, whose versioned hashes are in the transaction.
+    pub commitments: Vec<Bytes48>,
+    /// The KZG proofs of the blobs against their commitments.
+    pub proofs: Vec<Bytes48>,
+}
+
+impl BlobTransactionSidecar {
+    /// Verifies that the sidecar holds the blobs committed to by `versioned_hashes`, checking the
+    /// KZG proofs against the trusted setup in `settings`.
+    pub fn validate(
+        &self,
+        versioned_hashes: &[H256],
+        settings: &KzgSettings,
+    ) -> Result<(), BlobTransactionValidationError> {
+        let expected = versioned_hashes.len();
+        if self.blobs.len() != expected ||
+            self.commitments.len() != expected ||
+            self.proofs.len() != expected
+        {
+            return Err(BlobTransactionValidationError::LengthMismatch {
+                expected,
+                blobs: self.blobs.len(),
+                commitments: self.commitments.len(),
+                proofs: self.proofs.len(),
+            })
+        }
+
+        for (commitment, versioned_hash) in self.commitments.iter().zip(versioned_hashes) {
+            if kzg_to_versioned_hash(&commitment[..]) != *versioned_hash {
+                return Err(BlobTransactionValidationError::VersionedHashMismatch(*versioned_hash))
+            }
+        }
+
+        let valid = KzgProof::verify_blob_kzg_proof_batch(
+            &self.blobs,
+            &self.commitments,
+            &self.proofs,
+            settings,
+        )
+        .map_err(BlobTransactionValidationError::Kzg)?;
+        if !valid {
+            return Err(BlobTransactionValidationError::InvalidProofs)
+        }
+
+        Ok(())
+    }
+
+    /// Outputs the length of the blobs, commitments and proofs lists, without a RLP header.
+    pub(crate) fn fields_len(&self) -> usize {
+        fixed_bytes_list_len(&self.blobs) +
+            fixed_bytes_list_len(&self.commitments) +
+            fixed_bytes_list_len(&self.proofs)
+    }
+
+    /// Encodes the blobs, commitments and proofs lists, without a RLP header.
+    pub(crate) fn encode_fields(&self, out: &mut dyn BufMut) {
+        encode_fixed_bytes_list(&self.blobs, out);
+        encode_fixed_bytes_list(&self.commitments, out);
+        encode_fixed_bytes_list(&self.proofs, out);
+    }
+
+    /// Decodes the blobs, commitments and proofs lists, without a RLP header.
+    pub(crate) fn decode_fields(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(Self {
+            blobs: decode_fixed_bytes_list::<_, BYTES_PER_BLOB>(buf, Blob::from_bytes)?,
+            commitments: decode_fixed_bytes_list::<_, 48>(buf, Bytes48::from_bytes)?,
+            proofs: decode_fixed_bytes_list::<_, 48>(buf, Bytes48::from_bytes)?,
+        })
+    }
+}
+
+impl Serialize for BlobTransactionSidecar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        fn to_bytes<T: Deref<Target = [u8; N]>, const N: usize>(items: &[T]) -> Vec<Bytes> {
+            items.iter().map(|item| Bytes::from(item.deref().to_vec())).collect()
+        }
+
+        SerdeSidecar {
+            blobs: to_bytes(&self.blobs),
+            commitments: to_bytes(&self.commitments),
+            proofs: to_bytes(&self.proofs),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlobTransactionSidecar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        fn from_bytes<T, E: de::Error>(
+            items: Vec<Bytes>,
+            convert: impl Fn(&[u8]) -> Result<T, c_kzg::Error>,
+        ) -> Result<Vec<T>, E> {
+            items
+                .iter()
+                .map(|item| {
+                    convert(item).map_err(|err| E::custom(format!("invalid sidecar: {err:?}")))
+                })
+                .collect()
+        }
+
+        let sidecar = SerdeSidecar::deserialize(deserializer)?;
+        Ok(Self {
+            blobs: from_bytes(sidecar.blobs, Blob::from_bytes)?,
+            commitments: from_bytes(sidecar.commitments, Bytes48::from_bytes)?,
+            proofs: from_bytes(sidecar.proofs, Bytes48::from_bytes)?,
+        })
+    }
+}
+
+/// Outputs the length of a RLP list of fixed size byte arrays.
+fn fixed_bytes_list_len<T, const N: usize>(items: &[T]) -> usize
+where
+    T: Deref<Target = [u8; N]>,
+{
+    let payload_length = items.iter().map(|item| item.deref().length()).sum::<usize>();
+    length_of_length(payload_length) + payload_length
+}
+
+/// Encodes a RLP list of fixed size byte arrays.
+fn encode_fixed_bytes_list<T, const N: usize>(items: &[T], out: &mut dyn BufMut)
+where
+    T: Deref<Target = [u8; N]>,
+{
+    let payload_length = items.iter().map(|item| item.deref().length()).sum::<usize>();
+    Header { list: true, payload_length }.encode(out);
+    for item in items {
+        item.deref().encode(out);
+    }
+}
+
+/// Decodes a RLP list of byte arrays of size `N`, converting each of them with `from_bytes`.
+fn decode_fixed_bytes_list<T, const N: usize>(
+    buf: &mut &[u8],
+    from_bytes: impl Fn(&[u8]) -> Result<T, c_kzg::Error>,
+) -> Result<Vec<T>, DecodeError> {
+    let header = Header::decode(buf)?;
+    if !header.list {
+        return Err(DecodeError::UnexpectedString)
+    }
+    if buf.len() < header.payload_length {
+        return Err(DecodeError::InputTooShort)
+    }
+
+    let (mut payload, rest) = buf.split_at(header.payload_length);
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let header = Header::decode(&mut payload)?;
+        if header.list {
+            return Err(DecodeError::UnexpectedList)
+        }
+        if header.payload_length != N {
+            return Err(DecodeError::UnexpectedLength)
+        }
+        if payload.len() < N {
+            return Err(DecodeError::InputTooShort)
+        }
+        let item = from_bytes(&payload[..N])
+            .map_err(|_| DecodeError::Custom("invalid blob, commitment or proof"))?;
+        items.push(item);
+        payload.advance(N);
+    }
+
+    *buf = rest;
+    Ok(items)
+}
+
+/// A blob transaction with its sidecar, as it is sent in the `PooledTransactions` messages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobTransaction {
+    /// The transaction, as it is included in a block.
+    pub transaction: TransactionSigned,
+    /// The blobs of the transaction.
+    pub sidecar: BlobTransactionSidecar,
+}
+
+impl BlobTransaction {
+    /// Creates a blob transaction from a signed transaction and its sidecar.
+    ///
+    /// Returns `None` if the transaction is not a blob transaction.
+    pub fn new(transaction: TransactionSigned, sidecar: BlobTransactionSidecar) -> Option<Self> {
+        matches!(transaction.transaction, Transaction::Eip4844(_))
+            .then_some(Self { transaction, sidecar })
+    }
+
+    /// Transaction hash, which does not cover the sidecar.
+    pub fn hash(&self) -> TxHash {
+        self.transaction.hash
+    }
+
+    /// Verifies that the sidecar holds the blobs committed to by the transaction, checking the KZG
+    /// proofs against the trusted setup in `settings`.
+    pub fn validate(&self, settings: &KzgSettings) -> Result<(), BlobTransactionValidationError> {
+        let versioned_hashes = self
+            .transaction
+            .blob_versioned_hashes()
+            .ok_or(BlobTransactionValidationError::NotBlobTransaction)?;
+        self.sidecar.validate(versioned_hashes, settings)
+    }
+
+    /// Returns the transaction, dropping its sidecar.
+    pub fn into_transaction(self) -> TransactionSigned {
+        self.transaction
+    }
+
+    /// Outputs the length of the signed transaction fields, without a RLP header.
+    fn transaction_payload_len(&self) -> usize {
+        self.transaction.transaction.fields_len() + self.transaction.signature.payload_len()
+    }
+
+    /// Outputs the length of the transaction and sidecar fields, without a RLP header.
+    fn payload_len(&self) -> usize {
+        let transaction_payload_length = self.transaction_payload_len();
+        length_of_length(transaction_payload_length) +
+            transaction_payload_length +
+            self.sidecar.fields_len()
+    }
+
+    /// Outputs the length of [`BlobTransaction::encode_enveloped`].
+    pub(crate) fn enveloped_len(&self) -> usize {
+        let payload_length = self.payload_len();
+        // 'transaction type byte length' + 'header length' + 'payload length'
+        1 + length_of_length(payload_length) + payload_length
+    }
+
+    /// Encodes the transaction in its network format:
+    /// `type` + `rlp([tx_payload_body, blobs, commitments, proofs])`
+    ///
+    /// The `tx_payload_body` is the list of the fields of the signed transaction, as they are
+    /// encoded in a block.
+    pub fn encode_enveloped(&self, out: &mut dyn BufMut) {
+        out.put_u8(TxType::EIP4844 as u8);
+        Header { list: true, payload_length: self.payload_len() }.encode(out);
+        Header { list: true, payload_length: self.transaction_payload_len() }.encode(out);
+        self.transaction.transaction.encode_fields(out);
+        self.transaction.signature.encode(out);
+        self.sidecar.encode_fields(out);
+    }
+
+    /// Decodes a transaction in its network format.
+    ///
+    /// CAUTION: this expects that `data` is `[id, rlp([tx_payload_body, blobs, commitments,
+    /// proofs])]`
+    pub fn decode_enveloped(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        let tx_type = *data.first().ok_or(DecodeError::InputTooShort)?;
+        if tx_type != TxType::EIP4844 as u8 {
+            return Err(DecodeError::Custom("not a blob transaction"))
+        }
+        data.advance(1);
+
+        let header = Header::decode(data)?;
+        if !header.list {
+            return Err(DecodeError::Custom("blob tx fields must be encoded as a list"))
+        }
+        let started_len = data.len();
+
+        // keep this around so we can use it to calculate the hash
+        let original_encoding = *data;
+        let transaction_header = Header::decode(data)?;
+        if !transaction_header.list {
+            return Err(DecodeError::Custom("typed tx fields must be encoded as a list"))
+        }
+        let transaction = Transaction::Eip4844(TxEip4844::decode_fields(data)?);
+        let signature = Signature::decode(data)?;
+
+        // the hash is the one of the transaction as it is included in a block, without the
+        // sidecar: keccak256(type + rlp(tx_payload_body))
+        let tx_length = transaction_header.length() + transaction_header.payload_length;
+        let mut encoded = Vec::with_capacity(1 + tx_length);
+        encoded.put_u8(tx_type);
+        encoded.put_slice(&original_encoding[..tx_length]);
+        let hash = keccak256(&encoded);
+
+        let sidecar = BlobTransactionSidecar::decode_fields(data)?;
+
+        let consumed = started_len - data.len();
+        if consumed != header.payload_length {
+            return Err(DecodeError::ListLengthMismatch {
+                expected: header.payload_length,
+                got: consumed,
+            })
+        }
+
+        Ok(Self { transaction: TransactionSigned { transaction, hash, signature }, sidecar })
+    }
+}
+
+/// A transaction of a `PooledTransactions` message.
+///
+/// Blob transactions are sent with their sidecar, and all the other transactions as they are
+/// included in a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PooledTransactionsElement {
+    /// A transaction which is not a blob transaction.
+    Transaction(TransactionSigned),
+    /// A blob transaction with its sidecar.
+    BlobTransaction(BlobTransaction),
+}
+
+impl PooledTransactionsElement {
+    /// Transaction hash. Used to identify transaction.
+    pub fn hash(&self) -> TxHash {
+        match self {
+            PooledTransactionsElement::Transaction(tx) => tx.hash,
+            PooledTransactionsElement::BlobTransaction(tx) => tx.hash(),
+        }
+    }
+
+    /// Returns the transaction, dropping the sidecar of blob transactions.
+    pub fn into_transaction(self) -> TransactionSigned {
+        match self {
+            PooledTransactionsElement::Transaction(tx) => tx,
+            PooledTransactionsElement::BlobTransaction(tx) => tx.into_transaction(),
+        }
+    }
+
+    /// Decodes the "raw" format of a pooled transaction (e.g. `eth_sendRawTransaction`).
+    ///
+    /// Blob transactions are expected in their network format, see
+    /// [`BlobTransaction::decode_enveloped`], and the other transactions as in
+    /// [`TransactionSigned::decode_enveloped`].
+    pub fn decode_enveloped(tx: Bytes) -> Result<Self, DecodeError> {
+        if tx.first() == Some(&(TxType::EIP4844 as u8)) {
+            BlobTransaction::decode_enveloped(&mut tx.as_ref()).map(Self::BlobTransaction)
+        } else {
+            TransactionSigned::decode_enveloped(tx).map(Self::Transaction)
+        }
+    }
+}
+
+impl From<TransactionSigned> for PooledTransactionsElement {
+    fn from(tx: TransactionSigned) -> Self {
+        PooledTransactionsElement::Transaction(tx)
+    }
+}
+
+impl From<BlobTransaction> for PooledTransactionsElement {
+    fn from(tx: BlobTransaction) -> Self {
+        PooledTransactionsElement::BlobTransaction(tx)
+    }
+}
+
+impl Encodable for PooledTransactionsElement {
+    fn encode(&self, out: &mut dyn BufMut) {
+        match self {
+            PooledTransactionsElement::Transaction(tx) => tx.encode(out),
+            PooledTransactionsElement::BlobTransaction(tx) => {
+                Header { list: false, payload_length: tx.enveloped_len() }.encode(out);
+                tx.encode_enveloped(out);
+            }
+        }
+    }
+
+    fn length(&self) -> usize {
+        match self {
+            PooledTransactionsElement::Transaction(tx) => tx.length(),
+            PooledTransactionsElement::BlobTransaction(tx) => {
+                let len = tx.enveloped_len();
+                length_of_length(len) + len
+            }
+        }
+    }
+}
+
+/// This `Decodable` implementation only supports decoding the rlp encoded transactions of p2p
+/// messages.
+///
+/// CAUTION: this expects that the given buf contains rlp
+impl Decodable for PooledTransactionsElement {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let mut original_encoding = *buf;
+        let header = Header::decode(buf)?;
+
+        // blob transactions are typed transactions, so they are encoded as a string
+        if !header.list && buf.first() == Some(&(TxType::EIP4844 as u8)) {
+            BlobTransaction::decode_enveloped(buf).map(Self::BlobTransaction)
+        } else {
+            TransactionSigned::decode(&mut original_encoding).map(|tx| {
+                *buf = original_encoding;
+                Self::Transaction(tx)
+            })
+        }
+    }
+}
+
+/// Blob transactions can not be sent without a sidecar, so arbitrary ones are given an empty one.
+#[cfg(any(test, feature = "arbitrary"))]
+fn arbitrary_element(transaction: TransactionSigned) -> PooledTransactionsElement {
+    if matches!(transaction.transaction, Transaction::Eip4844(_)) {
+        PooledTransactionsElement::BlobTransaction(BlobTransaction {
+            transaction,
+            sidecar: Default::default(),
+        })
+    } else {
+        PooledTransactionsElement::Transaction(transaction)
+    }
+}
+
+#[cfg(any(test, feature = "arbitrary"))]
+impl proptest::arbitrary::Arbitrary for PooledTransactionsElement {
+    type Parameters = ();
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::{any, Strategy};
+
+        any::<TransactionSigned>().prop_map(arbitrary_element).boxed()
+    }
+
+    type Strategy = proptest::strategy::BoxedStrategy<PooledTransactionsElement>;
+}
+
+#[cfg(any(test, feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for PooledTransactionsElement {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(arbitrary_element(TransactionSigned::arbitrary(u)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eip4844::kzg_to_versioned_hash, Address, TransactionKind, U256};
+    use bytes::BytesMut;
+
+    fn blob_transaction(sidecar: BlobTransactionSidecar) -> BlobTransaction {
+        let transaction = Transaction::Eip4844(TxEip4844 {
+            chain_id: 1,
+            nonce: 2,
+            gas_limit: 21_000,
+            max_fee_per_gas: 3,
+            max_priority_fee_per_gas: 1,
+            to: TransactionKind::Call(Address::from_low_u64_be(4)),
+            value: 5,
+            access_list: Default::default(),
+            blob_versioned_hashes: sidecar
+                .commitments
+                .iter()
+                .map(|commitment| kzg_to_versioned_hash(&commitment[..]))
+                .collect(),
+            max_fee_per_blob_gas: 6,
+            input: Bytes::from(vec![7]),
+        });
+        let signature = Signature { odd_y_parity: true, r: U256::from(8), s: U256::from(9) };
+        let transaction = TransactionSigned::from_transaction_and_signature(transaction, signature);
+        BlobTransaction::new(transaction, sidecar).unwrap()
+    }
+
+    #[test]
+    fn pooled_blob_transaction_roundtrip() {
+        let sidecar = BlobTransactionSidecar {
+            blobs: vec![Blob::from_bytes(&[1; BYTES_PER_BLOB]).unwrap()],
+            commitments: vec![Bytes48::from_bytes(&[2; 48]).unwrap()],
+            proofs: vec![Bytes48::from_bytes(&[3; 48]).unwrap()],
+        };
+        let element = PooledTransactionsElement::from(blob_transaction(sidecar));
+
+        let mut encoded = BytesMut::new();
+        element.encode(&mut encoded);
+        assert_eq!(encoded.len(), element.length());
+
+        let decoded = PooledTransactionsElement::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(decoded, element);
+
+        // The hash of the transaction is the one it has in a block, without the sidecar
+        let transaction = decoded.into_transaction();
+        assert_eq!(transaction.hash(), transaction.recalculate_hash());
+    }
+
+    #[test]
+    fn blob_transaction_without_sidecar_in_block() {
+        let transaction = blob_transaction(Default::default()).into_transaction();
+
+        let mut encoded = BytesMut::new();
+        transaction.encode(&mut encoded);
+        assert_eq!(TransactionSigned::decode(&mut &encoded[..]).unwrap(), transaction);
+
+        // A pooled blob transaction must have its sidecar
+        assert!(PooledTransactionsElement::decode(&mut &encoded[..]).is_err());
+    }
+}
@@ -3,7 +3,7 @@ use reth_codecs::{main_codec, Compact};
 use reth_rlp::{RlpDecodable, RlpEncodable};
 
 /// Withdrawal represents a validator withdrawal from the consensus layer.
-#[main_codec]
+#[main_codec(rlp)]
 #[derive(Debug, Clone, PartialEq, Eq, Default, RlpEncodable, RlpDecodable)]
 pub struct Withdrawal {
     /// Monotonically increasing identifier issued by consensus layer.
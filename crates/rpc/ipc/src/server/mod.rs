@@ -23,7 +23,7 @@ use tokio::{
     io::{AsyncRead, AsyncWrite},
     sync::{oneshot, watch, OwnedSemaphorePermit},
 };
-use tower::{layer::util::Identity, Service};
+use tower::{layer::util::Identity, Layer, Service};
 use tracing::{trace, warn};
 
 // re-export so can be used during builder setup
@@ -46,7 +46,13 @@ pub struct IpcServer<B = Identity, L = ()> {
     service_builder: tower::ServiceBuilder<B>,
 }
 
-impl IpcServer {
+impl<B> IpcServer<B>
+where
+    B: Layer<TowerService<()>> + Send + 'static,
+    B::Service: Service<String, Response = String> + Send + 'static,
+    <B::Service as Service<String>>::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    <B::Service as Service<String>>::Future: Send,
+{
     /// Start responding to connections requests.
     ///
     /// This will run on the tokio runtime until the server is stopped or the ServerHandle is
@@ -493,7 +499,7 @@ impl<B, L> Builder<B, L> {
     /// # Examples
     ///
     /// ```rust
-    /// 
+    ///
     /// #[tokio::main]
     /// async fn main() {
     ///     let builder = tower::ServiceBuilder::new();
@@ -1,12 +1,16 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use reth_primitives::NodeRecord;
-use reth_rpc_types::NodeInfo;
+use reth_rpc_types::{NodeInfo, PeerInfo};
 
 /// Admin namespace rpc interface that gives access to several non-standard RPC methods.
 #[cfg_attr(not(feature = "client"), rpc(server))]
 #[cfg_attr(feature = "client", rpc(server, client))]
 #[async_trait::async_trait]
 pub trait AdminApi {
+    /// Returns the info of all connected peers.
+    #[method(name = "admin_peers")]
+    async fn peers(&self) -> RpcResult<Vec<PeerInfo>>;
+
     /// Adds the given node record to the peerset.
     #[method(name = "admin_addPeer")]
     fn add_peer(&self, record: NodeRecord) -> RpcResult<bool>;
@@ -1,6 +1,12 @@
 use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
-use reth_primitives::{rpc::BlockId, Bytes, H256};
-use reth_rpc_types::RichBlock;
+use reth_primitives::{
+    rpc::{BlockId, BlockNumber},
+    Bytes, H256,
+};
+use reth_rpc_types::{
+    trace::geth::{GethDebugTracingOptions, GethTraceFrame, TraceResult},
+    CallRequest, RichBlock,
+};
 
 /// Debug rpc interface.
 #[cfg_attr(not(feature = "client"), rpc(server))]
@@ -25,4 +31,39 @@ pub trait DebugApi {
     /// Returns an array of recent bad blocks that the client has seen on the network.
     #[method(name = "debug_getBadBlocks")]
     async fn bad_blocks(&self) -> Result<Vec<RichBlock>>;
+
+    /// Re-executes the transactions of the block with the given number and traces them.
+    #[method(name = "debug_traceBlockByNumber")]
+    async fn debug_trace_block_by_number(
+        &self,
+        block: BlockNumber,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<Vec<TraceResult>>;
+
+    /// Re-executes the transactions of the block with the given hash and traces them.
+    #[method(name = "debug_traceBlockByHash")]
+    async fn debug_trace_block_by_hash(
+        &self,
+        block: H256,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<Vec<TraceResult>>;
+
+    /// Re-executes the mined transaction with the given hash on top of the state it was executed
+    /// on, and traces it.
+    #[method(name = "debug_traceTransaction")]
+    async fn debug_trace_transaction(
+        &self,
+        tx_hash: H256,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<GethTraceFrame>;
+
+    /// Executes the call on top of the state of the given block, or of the latest block, and
+    /// traces it.
+    #[method(name = "debug_traceCall")]
+    async fn debug_trace_call(
+        &self,
+        request: CallRequest,
+        block_number: Option<BlockId>,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<GethTraceFrame>;
 }
@@ -4,8 +4,8 @@ use reth_primitives::{
     Address, Bytes, H256, H64, U256, U64,
 };
 use reth_rpc_types::{
-    CallRequest, EIP1186AccountProofResponse, FeeHistory, Index, RichBlock, SyncStatus,
-    Transaction, TransactionReceipt, TransactionRequest, Work,
+    BlockOverrides, CallRequest, EIP1186AccountProofResponse, FeeHistory, Index, RichBlock,
+    StateOverride, SyncStatus, Transaction, TransactionReceipt, TransactionRequest, Work,
 };
 
 /// Eth rpc interface: <https://ethereum.github.io/execution-apis/api-documentation/>
@@ -127,8 +127,16 @@ pub trait EthApi {
     async fn get_code(&self, address: Address, block_number: Option<BlockId>) -> Result<Bytes>;
 
     /// Executes a new message call immediately without creating a transaction on the block chain.
+    ///
+    /// The state and the block the call is executed on can be overridden.
     #[method(name = "eth_call")]
-    async fn call(&self, request: CallRequest, block_number: Option<BlockId>) -> Result<Bytes>;
+    async fn call(
+        &self,
+        request: CallRequest,
+        block_number: Option<BlockId>,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<Box<BlockOverrides>>,
+    ) -> Result<Bytes>;
 
     /// Generates an access list for a transaction.
     ///
@@ -213,6 +221,14 @@ pub trait EthApi {
     #[method(name = "eth_sendRawTransaction")]
     async fn send_raw_transaction(&self, bytes: Bytes) -> Result<H256>;
 
+    /// Sends a batch of signed transactions, returning their hashes.
+    ///
+    /// This is not part of the Ethereum JSON-RPC specification. The call fails if any of the
+    /// transactions can not be decoded, or with the error of the first transaction the pool
+    /// rejected, in which case the other transactions may still have been added to the pool.
+    #[method(name = "eth_sendRawTransactions")]
+    async fn send_raw_transactions(&self, transactions: Vec<Bytes>) -> Result<Vec<H256>>;
+
     /// Returns an Ethereum specific signature with: sign(keccak256("\x19Ethereum Signed Message:\n"
     /// + len(message) + message))).
     #[method(name = "eth_sign")]
@@ -0,0 +1,36 @@
+use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
+use reth_primitives::Address;
+use reth_rpc_types::txpool::{TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolStatus};
+
+/// Txpool rpc interface.
+#[cfg_attr(not(feature = "client"), rpc(server))]
+#[cfg_attr(feature = "client", rpc(server, client))]
+pub trait TxPoolApi {
+    /// Returns the number of transactions currently pending for inclusion in the next block(s), as
+    /// well as the ones that are being scheduled for future execution only.
+    ///
+    /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_status) for more details
+    #[method(name = "txpool_status")]
+    fn txpool_status(&self) -> Result<TxpoolStatus>;
+
+    /// Returns a summary of all the transactions currently pending for inclusion in the next
+    /// block(s), as well as the ones that are being scheduled for future execution only.
+    ///
+    /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_inspect) for more details
+    #[method(name = "txpool_inspect")]
+    fn txpool_inspect(&self) -> Result<TxpoolInspect>;
+
+    /// Retrieves the transactions contained within the txpool, returning pending as well as queued
+    /// transactions of this address, grouped by nonce.
+    ///
+    /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_contentFrom) for more details
+    #[method(name = "txpool_contentFrom")]
+    fn txpool_content_from(&self, from: Address) -> Result<TxpoolContentFrom>;
+
+    /// Returns the details of all transactions currently pending for inclusion in the next
+    /// block(s), as well as the ones that are being scheduled for future execution only.
+    ///
+    /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_content) for more details
+    #[method(name = "txpool_content")]
+    fn txpool_content(&self) -> Result<TxpoolContent>;
+}
@@ -0,0 +1,23 @@
+//! Configure the auth server that serves the `engine_` namespace to the consensus layer.
+
+use jsonrpsee::{
+    core::Error as RpcError,
+    server::{ServerBuilder, ServerHandle},
+};
+use reth_rpc::{AuthLayer, EngineApi, JwtAuthValidator, JwtSecret};
+use reth_rpc_api::servers::EngineApiServer;
+use std::net::SocketAddr;
+use tower::ServiceBuilder;
+
+/// Launches the auth server on the given address, serving the `engine_` namespace.
+///
+/// Requests are only served if they carry a JWT signed with the given secret.
+pub async fn launch_auth(
+    engine_api: EngineApi,
+    socket_addr: SocketAddr,
+    secret: JwtSecret,
+) -> Result<ServerHandle, RpcError> {
+    let middleware = ServiceBuilder::new().layer(AuthLayer::new(JwtAuthValidator::new(secret)));
+    let server = ServerBuilder::new().set_middleware(middleware).build(socket_addr).await?;
+    server.start(engine_api.into_rpc())
+}
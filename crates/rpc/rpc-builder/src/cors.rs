@@ -0,0 +1,60 @@
+//! CORS configuration of the http server.
+
+use http::{HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+/// Error thrown when parsing cors domains went wrong
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum CorsDomainError {
+    #[error("{domain} is an invalid header value")]
+    InvalidHeader { domain: String },
+    #[error("Wildcard origin (`*`) cannot be passed as part of a list: {input}")]
+    WildCardNotAllowed { input: String },
+}
+
+/// Creates a [CorsLayer] from the given domains.
+///
+/// The domains are a comma separated list of allowed origins, or `*` to allow any origin.
+pub(crate) fn create_cors_layer(http_cors_domains: &str) -> Result<CorsLayer, CorsDomainError> {
+    let cors = match http_cors_domains.trim() {
+        "*" => CorsLayer::new().allow_origin(Any),
+        domains => {
+            let iter = domains.split(',').map(str::trim);
+            if iter.clone().any(|domain| domain == "*") {
+                return Err(CorsDomainError::WildCardNotAllowed {
+                    input: http_cors_domains.to_string(),
+                })
+            }
+
+            let origins = iter
+                .map(|domain| {
+                    domain
+                        .parse::<HeaderValue>()
+                        .map_err(|_| CorsDomainError::InvalidHeader { domain: domain.to_string() })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            CorsLayer::new().allow_origin(AllowOrigin::list(origins))
+        }
+    };
+    Ok(cors.allow_methods([Method::GET, Method::POST]).allow_headers(Any))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cors_domains() {
+        assert!(create_cors_layer("*").is_ok());
+        assert!(create_cors_layer("http://localhost:3000, https://reth.rs").is_ok());
+        assert!(matches!(
+            create_cors_layer("http://localhost:3000,*"),
+            Err(CorsDomainError::WildCardNotAllowed { .. })
+        ));
+        assert!(matches!(
+            create_cors_layer("http://local\nhost"),
+            Err(CorsDomainError::InvalidHeader { .. })
+        ));
+    }
+}
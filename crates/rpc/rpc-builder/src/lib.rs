@@ -26,13 +26,15 @@
 //!
 //! ```
 //! use reth_network_api::{NetworkInfo, Peers};
-//! use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
+//! use reth_primitives::IntoRecoveredTransaction;
+//! use reth_provider::{BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, StateTrieProvider, TransactionsProvider};
 //! use reth_rpc_builder::{RethRpcModule, RpcModuleBuilder, RpcServerConfig, ServerBuilder, TransportRpcModuleConfig};
 //! use reth_transaction_pool::TransactionPool;
 //! pub async fn launch<Client, Pool, Network>(client: Client, pool: Pool, network: Network)
 //! where
-//!     Client: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
+//!     Client: BlockProvider + HeaderProvider + StateProviderFactory + TransactionsProvider + ReceiptProvider + StateTrieProvider + Clone + 'static,
 //!     Pool: TransactionPool + Clone + 'static,
+//!     Pool::Transaction: IntoRecoveredTransaction,
 //!     Network: NetworkInfo + Peers + Clone + 'static,
 //! {
 //!     // configure the rpc module per transport
@@ -51,27 +53,47 @@
 //! }
 //! ```
 
+pub use crate::{auth::launch_auth, cors::CorsDomainError};
 pub use jsonrpsee::server::ServerBuilder;
 use jsonrpsee::{
     core::{server::rpc_module::Methods, Error as RpcError},
     server::{Server, ServerHandle},
     RpcModule,
 };
+use reth_executor::blockchain_tree::CanonStateNotificationSender;
 use reth_ipc::server::IpcServer;
 pub use reth_ipc::server::{Builder as IpcServerBuilder, Endpoint};
 use reth_network_api::{NetworkInfo, Peers};
-use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
-use reth_rpc::{AdminApi, DebugApi, EthApi, NetApi, TraceApi, Web3Api};
+use reth_primitives::{ChainSpec, IntoRecoveredTransaction, MAINNET};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, StateTrieProvider,
+    TransactionsProvider,
+};
+use reth_rpc::{
+    AdminApi, DebugApi, EthApi, EthApiConfig, EthFilter, EthFilterConfig, EthPubSub, NetApi,
+    RateLimitConfig, RateLimitLayer, TraceApi, TracingCallGuard, TxPoolApi, Web3Api,
+    DEFAULT_MAX_TRACING_REQUESTS,
+};
 use reth_rpc_api::servers::*;
 use reth_transaction_pool::TransactionPool;
 use serde::{Deserialize, Serialize, Serializer};
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     fmt,
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
     str::FromStr,
+    sync::Arc,
 };
 use strum::{AsRefStr, EnumString, EnumVariantNames, ParseError, VariantNames};
+use tower::{
+    layer::util::{Identity, Stack},
+    util::Either,
+    ServiceBuilder,
+};
+use tower_http::cors::CorsLayer;
+
+mod auth;
+mod cors;
 
 /// The default port for the http server
 pub const DEFAULT_HTTP_RPC_PORT: u16 = 8545;
@@ -79,6 +101,9 @@ pub const DEFAULT_HTTP_RPC_PORT: u16 = 8545;
 /// The default port for the ws server
 pub const DEFAULT_WS_RPC_PORT: u16 = 8546;
 
+/// The default port for the auth server
+pub const DEFAULT_AUTH_PORT: u16 = 8551;
+
 /// The default IPC endpoint
 #[cfg(windows)]
 pub const DEFAULT_IPC_ENDPOINT: &str = r"\\.\pipe\reth.ipc";
@@ -92,17 +117,27 @@ pub async fn launch<Client, Pool, Network>(
     client: Client,
     pool: Pool,
     network: Network,
+    chain_spec: Arc<ChainSpec>,
     module_config: impl Into<TransportRpcModuleConfig>,
     server_config: impl Into<RpcServerConfig>,
 ) -> Result<RpcServerHandle, RpcError>
 where
-    Client: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateTrieProvider
+        + Clone
+        + 'static,
     Pool: TransactionPool + Clone + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
     Network: NetworkInfo + Peers + Clone + 'static,
 {
     let module_config = module_config.into();
     let server_config = server_config.into();
     RpcModuleBuilder::new(client, pool, network)
+        .with_chain_spec(chain_spec)
         .build(module_config)
         .start_server(server_config)
         .await
@@ -119,6 +154,18 @@ pub struct RpcModuleBuilder<Client, Pool, Network> {
     pool: Pool,
     /// The Network type to when creating all rpc handlers
     network: Network,
+    /// The chain the rpc handlers serve, mainnet by default
+    chain_spec: Arc<ChainSpec>,
+    /// Notifies the `eth_subscribe` subscriptions about the changes of the canonical chain
+    chain_events: CanonStateNotificationSender,
+    /// The settings of the `eth_` namespace handlers
+    eth_config: EthApiConfig,
+    /// The limits of the log queries
+    filter_config: EthFilterConfig,
+    /// The maximum number of tracing requests that are served concurrently
+    max_tracing_requests: usize,
+    /// The methods that are not served by any transport
+    disabled_methods: HashSet<String>,
 }
 
 // === impl RpcBuilder ===
@@ -126,7 +173,17 @@ pub struct RpcModuleBuilder<Client, Pool, Network> {
 impl<Client, Pool, Network> RpcModuleBuilder<Client, Pool, Network> {
     /// Create a new instance of the builder
     pub fn new(client: Client, pool: Pool, network: Network) -> Self {
-        Self { client, pool, network }
+        Self {
+            client,
+            pool,
+            network,
+            chain_spec: Arc::new(MAINNET.clone()),
+            chain_events: Default::default(),
+            eth_config: Default::default(),
+            filter_config: Default::default(),
+            max_tracing_requests: DEFAULT_MAX_TRACING_REQUESTS,
+            disabled_methods: Default::default(),
+        }
     }
 
     /// Configure the client instance.
@@ -134,8 +191,28 @@ impl<Client, Pool, Network> RpcModuleBuilder<Client, Pool, Network> {
     where
         C: BlockProvider + StateProviderFactory + 'static,
     {
-        let Self { pool, network, .. } = self;
-        RpcModuleBuilder { client, network, pool }
+        let Self {
+            pool,
+            network,
+            chain_spec,
+            chain_events,
+            eth_config,
+            filter_config,
+            max_tracing_requests,
+            disabled_methods,
+            ..
+        } = self;
+        RpcModuleBuilder {
+            client,
+            network,
+            pool,
+            chain_spec,
+            chain_events,
+            eth_config,
+            filter_config,
+            max_tracing_requests,
+            disabled_methods,
+        }
     }
 
     /// Configure the transaction pool instance.
@@ -143,8 +220,28 @@ impl<Client, Pool, Network> RpcModuleBuilder<Client, Pool, Network> {
     where
         P: TransactionPool + 'static,
     {
-        let Self { client, network, .. } = self;
-        RpcModuleBuilder { client, network, pool }
+        let Self {
+            client,
+            network,
+            chain_spec,
+            chain_events,
+            eth_config,
+            filter_config,
+            max_tracing_requests,
+            disabled_methods,
+            ..
+        } = self;
+        RpcModuleBuilder {
+            client,
+            network,
+            pool,
+            chain_spec,
+            chain_events,
+            eth_config,
+            filter_config,
+            max_tracing_requests,
+            disabled_methods,
+        }
     }
 
     /// Configure the network instance.
@@ -152,15 +249,86 @@ impl<Client, Pool, Network> RpcModuleBuilder<Client, Pool, Network> {
     where
         N: NetworkInfo + Peers + 'static,
     {
-        let Self { client, pool, .. } = self;
-        RpcModuleBuilder { client, network, pool }
+        let Self {
+            client,
+            pool,
+            chain_spec,
+            chain_events,
+            eth_config,
+            filter_config,
+            max_tracing_requests,
+            disabled_methods,
+            ..
+        } = self;
+        RpcModuleBuilder {
+            client,
+            network,
+            pool,
+            chain_spec,
+            chain_events,
+            eth_config,
+            filter_config,
+            max_tracing_requests,
+            disabled_methods,
+        }
+    }
+
+    /// Configure the chain the rpc handlers serve.
+    pub fn with_chain_spec(mut self, chain_spec: Arc<ChainSpec>) -> Self {
+        self.chain_spec = chain_spec;
+        self
+    }
+
+    /// Configure the notifications of the changes of the canonical chain, which drive the
+    /// `newHeads`, `logs` and `syncing` subscriptions.
+    pub fn with_chain_events(mut self, chain_events: CanonStateNotificationSender) -> Self {
+        self.chain_events = chain_events;
+        self
+    }
+
+    /// Configure the `eth_` namespace handlers, e.g. the gas price oracle and the gas cap of
+    /// calls.
+    pub fn with_eth_config(mut self, eth_config: EthApiConfig) -> Self {
+        self.eth_config = eth_config;
+        self
+    }
+
+    /// Configure the limits of the log queries of `eth_getLogs` and the filters.
+    pub fn with_filter_config(mut self, filter_config: EthFilterConfig) -> Self {
+        self.filter_config = filter_config;
+        self
+    }
+
+    /// Configure the maximum number of `debug_trace*` and `trace_*` requests that are served
+    /// concurrently.
+    pub fn with_max_tracing_requests(mut self, max_tracing_requests: usize) -> Self {
+        self.max_tracing_requests = max_tracing_requests;
+        self
+    }
+
+    /// Configure methods that are not served by any transport, e.g. expensive methods of public
+    /// endpoints.
+    pub fn with_disabled_methods(
+        mut self,
+        disabled_methods: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.disabled_methods = disabled_methods.into_iter().map(Into::into).collect();
+        self
     }
 }
 
 impl<Client, Pool, Network> RpcModuleBuilder<Client, Pool, Network>
 where
-    Client: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateTrieProvider
+        + Clone
+        + 'static,
     Pool: TransactionPool + Clone + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
     Network: NetworkInfo + Peers + Clone + 'static,
 {
     /// Configures all [RpcModule]s specific to the given [TransportRpcModuleConfig] which can be
@@ -170,9 +338,24 @@ where
     pub fn build(self, module_config: TransportRpcModuleConfig) -> TransportRpcModules<()> {
         let mut modules = TransportRpcModules::default();
 
-        let Self { client, pool, network } = self;
-
-        let mut registry = RethModuleRegistry::new(client, pool, network);
+        let Self {
+            client,
+            pool,
+            network,
+            chain_spec,
+            chain_events,
+            eth_config,
+            filter_config,
+            max_tracing_requests,
+            disabled_methods,
+        } = self;
+
+        let mut registry = RethModuleRegistry::new(client, pool, network, chain_spec)
+            .with_chain_events(chain_events)
+            .with_eth_config(eth_config)
+            .with_filter_config(filter_config)
+            .with_max_tracing_requests(max_tracing_requests)
+            .with_disabled_methods(disabled_methods);
 
         if !module_config.is_empty() {
             let TransportRpcModuleConfig { http, ws, ipc } = module_config;
@@ -258,13 +441,22 @@ impl RpcModuleSelection {
         client: Client,
         pool: Pool,
         network: Network,
+        chain_spec: Arc<ChainSpec>,
     ) -> RpcModule<()>
     where
-        Client: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
+        Client: BlockProvider
+            + HeaderProvider
+            + StateProviderFactory
+            + TransactionsProvider
+            + ReceiptProvider
+            + StateTrieProvider
+            + Clone
+            + 'static,
         Pool: TransactionPool + Clone + 'static,
+        Pool::Transaction: IntoRecoveredTransaction,
         Network: NetworkInfo + Peers + Clone + 'static,
     {
-        let mut registry = RethModuleRegistry::new(client, pool, network);
+        let mut registry = RethModuleRegistry::new(client, pool, network, chain_spec);
         registry.module_for(self)
     }
 
@@ -324,6 +516,8 @@ pub enum RethRpcModule {
     Net,
     /// `trace_` module
     Trace,
+    /// `txpool_` module
+    Txpool,
     /// `web3_` module
     Web3,
 }
@@ -348,9 +542,19 @@ pub struct RethModuleRegistry<Client, Pool, Network> {
     client: Client,
     pool: Pool,
     network: Network,
+    chain_spec: Arc<ChainSpec>,
+    chain_events: CanonStateNotificationSender,
+    eth_config: EthApiConfig,
+    filter_config: EthFilterConfig,
+    tracing_call_guard: TracingCallGuard,
+    /// Methods that are removed from the modules
+    disabled_methods: HashSet<String>,
     /// Holds a clone of the actual [EthApi] namespace impl since this can be required by other
     /// namespaces
     eth_api: Option<EthApi<Client, Pool, Network>>,
+    /// Holds the [EthFilter] shared by the `eth_` namespaces of all transports, so that a filter
+    /// installed on one of them can be polled on the others
+    eth_filter: Option<EthFilter<Client, Pool>>,
     /// Contains the [Methods] of a module
     modules: HashMap<RethRpcModule, Methods>,
 }
@@ -359,8 +563,55 @@ pub struct RethModuleRegistry<Client, Pool, Network> {
 
 impl<Client, Pool, Network> RethModuleRegistry<Client, Pool, Network> {
     /// Creates a new, empty instance.
-    pub fn new(client: Client, pool: Pool, network: Network) -> Self {
-        Self { client, pool, network, eth_api: None, modules: Default::default() }
+    pub fn new(client: Client, pool: Pool, network: Network, chain_spec: Arc<ChainSpec>) -> Self {
+        Self {
+            client,
+            pool,
+            network,
+            chain_spec,
+            chain_events: Default::default(),
+            eth_config: Default::default(),
+            filter_config: Default::default(),
+            tracing_call_guard: Default::default(),
+            disabled_methods: Default::default(),
+            eth_api: None,
+            eth_filter: None,
+            modules: Default::default(),
+        }
+    }
+
+    /// Configure the notifications of the changes of the canonical chain, which drive the
+    /// `eth_subscribe` subscriptions.
+    pub fn with_chain_events(mut self, chain_events: CanonStateNotificationSender) -> Self {
+        self.chain_events = chain_events;
+        self
+    }
+
+    /// Configure the `eth_` namespace handlers, e.g. the gas price oracle and the gas cap of
+    /// calls.
+    pub fn with_eth_config(mut self, eth_config: EthApiConfig) -> Self {
+        self.eth_config = eth_config;
+        self
+    }
+
+    /// Configure the limits of the log queries of `eth_getLogs` and the filters.
+    pub fn with_filter_config(mut self, filter_config: EthFilterConfig) -> Self {
+        self.filter_config = filter_config;
+        self
+    }
+
+    /// Configure the maximum number of `debug_trace*` and `trace_*` requests that are served
+    /// concurrently.
+    pub fn with_max_tracing_requests(mut self, max_tracing_requests: usize) -> Self {
+        self.tracing_call_guard = TracingCallGuard::new(max_tracing_requests);
+        self
+    }
+
+    /// Configure methods that are removed from the modules returned by
+    /// [RethModuleRegistry::module_for].
+    pub fn with_disabled_methods(mut self, disabled_methods: HashSet<String>) -> Self {
+        self.disabled_methods = disabled_methods;
+        self
     }
 
     /// Returns all installed methods
@@ -399,21 +650,47 @@ where
 
 impl<Client, Pool, Network> RethModuleRegistry<Client, Pool, Network>
 where
-    Client: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateTrieProvider
+        + Clone
+        + 'static,
     Pool: TransactionPool + Clone + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
     Network: NetworkInfo + Peers + Clone + 'static,
 {
     /// Register Eth Namespace
     pub fn register_eth(&mut self) -> &mut Self {
         let eth_api = self.eth_api();
-        self.modules.insert(RethRpcModule::Eth, eth_api.into_rpc().into());
+        let eth_filter = self.eth_filter();
+        let methods = eth_methods(
+            eth_api,
+            eth_filter,
+            self.pool.clone(),
+            self.chain_events.clone(),
+            self.network.clone(),
+        );
+        self.modules.insert(RethRpcModule::Eth, methods);
         self
     }
 
     /// Register Debug Namespace
     pub fn register_debug(&mut self) -> &mut Self {
         let eth_api = self.eth_api();
-        self.modules.insert(RethRpcModule::Debug, DebugApi::new(eth_api).into_rpc().into());
+        self.modules.insert(
+            RethRpcModule::Debug,
+            DebugApi::new(eth_api, self.tracing_call_guard.clone()).into_rpc().into(),
+        );
+        self
+    }
+
+    /// Register Txpool Namespace
+    pub fn register_txpool(&mut self) -> &mut Self {
+        self.modules
+            .insert(RethRpcModule::Txpool, TxPoolApi::new(self.pool.clone()).into_rpc().into());
         self
     }
 
@@ -435,13 +712,16 @@ where
     }
 
     /// Populates a new [RpcModule] based on the selected [RethRpcModule]s in the given
-    /// [RpcModuleSelection]
+    /// [RpcModuleSelection], without the disabled methods.
     pub fn module_for(&mut self, config: &RpcModuleSelection) -> RpcModule<()> {
         let mut module = RpcModule::new(());
         let all_methods = self.reth_methods(config.iter_selection());
         for methods in all_methods {
             module.merge(methods).expect("No conflicts");
         }
+        for method in &self.disabled_methods {
+            module.remove(method);
+        }
         module
     }
 
@@ -454,7 +734,11 @@ where
         namespaces: impl Iterator<Item = RethRpcModule>,
     ) -> Vec<Methods> {
         let eth_api = self.eth_api();
+        // the filter spawns a task, it is only created if the namespace is requested
+        let namespaces = namespaces.collect::<Vec<_>>();
+        let eth_filter = namespaces.contains(&RethRpcModule::Eth).then(|| self.eth_filter());
         namespaces
+            .into_iter()
             .map(|namespace| {
                 self.modules
                     .entry(namespace)
@@ -462,12 +746,29 @@ where
                         RethRpcModule::Admin => {
                             AdminApi::new(self.network.clone()).into_rpc().into()
                         }
-                        RethRpcModule::Debug => DebugApi::new(eth_api.clone()).into_rpc().into(),
-                        RethRpcModule::Eth => eth_api.clone().into_rpc().into(),
+                        RethRpcModule::Debug => {
+                            DebugApi::new(eth_api.clone(), self.tracing_call_guard.clone())
+                                .into_rpc()
+                                .into()
+                        }
+                        RethRpcModule::Eth => eth_methods(
+                            eth_api.clone(),
+                            eth_filter.clone().expect("created for the eth namespace"),
+                            self.pool.clone(),
+                            self.chain_events.clone(),
+                            self.network.clone(),
+                        ),
                         RethRpcModule::Net => {
                             NetApi::new(self.network.clone(), eth_api.clone()).into_rpc().into()
                         }
-                        RethRpcModule::Trace => TraceApi::new().into_rpc().into(),
+                        RethRpcModule::Trace => {
+                            TraceApi::new(eth_api.clone(), self.tracing_call_guard.clone())
+                                .into_rpc()
+                                .into()
+                        }
+                        RethRpcModule::Txpool => {
+                            TxPoolApi::new(self.pool.clone()).into_rpc().into()
+                        }
                         RethRpcModule::Web3 => Web3Api::new(self.network.clone()).into_rpc().into(),
                     })
                     .clone()
@@ -479,12 +780,55 @@ where
     fn eth_api(&mut self) -> EthApi<Client, Pool, Network> {
         self.eth_api
             .get_or_insert_with(|| {
-                EthApi::new(self.client.clone(), self.pool.clone(), self.network.clone())
+                EthApi::with_config(
+                    self.client.clone(),
+                    self.pool.clone(),
+                    self.network.clone(),
+                    self.chain_spec.clone(),
+                    self.eth_config,
+                )
+            })
+            .clone()
+    }
+
+    /// Returns the configured [EthFilter] or creates it if it does not exist yet
+    fn eth_filter(&mut self) -> EthFilter<Client, Pool> {
+        self.eth_filter
+            .get_or_insert_with(|| {
+                EthFilter::new(self.client.clone(), self.pool.clone(), self.filter_config)
             })
             .clone()
     }
 }
 
+/// Returns the [Methods] of the `eth_` namespace, served by the [EthApi], the [EthFilter] and
+/// [EthPubSub].
+fn eth_methods<Client, Pool, Network>(
+    eth_api: EthApi<Client, Pool, Network>,
+    eth_filter: EthFilter<Client, Pool>,
+    pool: Pool,
+    chain_events: CanonStateNotificationSender,
+    network: Network,
+) -> Methods
+where
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateTrieProvider
+        + Clone
+        + 'static,
+    Pool: TransactionPool + Clone + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
+    Network: NetworkInfo + Peers + Clone + 'static,
+{
+    let mut module = eth_api.into_rpc();
+    module.merge(eth_filter.into_rpc()).expect("No conflicts");
+    module.merge(EthPubSub::new(pool, chain_events, network).into_rpc()).expect("No conflicts");
+    module.into()
+}
+
 /// A builder type for configuring and launching the servers that will handle RPC requests.
 ///
 /// Supported server transports are:
@@ -500,6 +844,10 @@ where
 pub struct RpcServerConfig {
     /// Configs for JSON-RPC Http.
     http_server_config: Option<ServerBuilder>,
+    /// Allowed CORS Domains for http
+    http_cors_domains: Option<String>,
+    /// Limits of the rate of http and ipc requests
+    rate_limit: Option<RateLimitConfig>,
     /// Configs for WS server
     ws_server_config: Option<ServerBuilder>,
     /// Address where to bind the http server to
@@ -536,6 +884,24 @@ impl RpcServerConfig {
         self
     }
 
+    /// Configures the CORS domains of the http server
+    ///
+    /// The domains are a comma separated list of allowed origins, or `*` to allow any origin.
+    pub fn with_cors(mut self, cors_domain: Option<String>) -> Self {
+        self.http_cors_domains = cors_domain;
+        self
+    }
+
+    /// Configures the limits of the rate of requests per connection and of calls per method of the
+    /// http and ipc servers
+    ///
+    /// Note: jsonrpsee handles the messages of ws connections without the middleware, so the ws
+    /// server is not limited.
+    pub fn with_rate_limit(mut self, rate_limit: Option<RateLimitConfig>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
     /// Configures the ws server
     pub fn with_ws(mut self, config: ServerBuilder) -> Self {
         self.ws_server_config = Some(config.ws_only());
@@ -588,13 +954,24 @@ impl RpcServerConfig {
     pub async fn build(self) -> Result<RpcServer, RpcError> {
         let mut server = RpcServer::empty();
 
+        // the limits of the methods are shared by the http and ipc servers
+        let rate_limit = self.rate_limit.map(RateLimitLayer::new);
+
         let http_socket_addr = self.http_addr.unwrap_or(SocketAddr::V4(SocketAddrV4::new(
             Ipv4Addr::UNSPECIFIED,
             DEFAULT_HTTP_RPC_PORT,
         )));
 
         if let Some(builder) = self.http_server_config {
-            let http_server = builder.build(http_socket_addr).await?;
+            let cors = self
+                .http_cors_domains
+                .as_deref()
+                .map(cors::create_cors_layer)
+                .transpose()
+                .map_err(|err| RpcError::Custom(err.to_string()))?;
+            let middleware =
+                ServiceBuilder::new().option_layer(cors).option_layer(rate_limit.clone());
+            let http_server = builder.set_middleware(middleware).build(http_socket_addr).await?;
             server.http_local_addr = http_server.local_addr().ok();
             server.http = Some(http_server);
         }
@@ -614,7 +991,8 @@ impl RpcServerConfig {
             let ipc_path = self
                 .ipc_endpoint
                 .unwrap_or_else(|| Endpoint::new(DEFAULT_IPC_ENDPOINT.to_string()));
-            let ipc = builder.build(ipc_path.path())?;
+            let middleware = ServiceBuilder::new().option_layer(rate_limit);
+            let ipc = builder.set_middleware(middleware).build(ipc_path.path())?;
             server.ipc = Some(ipc);
         }
 
@@ -705,6 +1083,13 @@ impl TransportRpcModules<()> {
     }
 }
 
+/// The http server, with an optional CORS and rate limit layer.
+type HttpServer =
+    Server<Stack<Either<RateLimitLayer, Identity>, Stack<Either<CorsLayer, Identity>, Identity>>>;
+
+/// The ipc server, with an optional rate limit layer.
+type RateLimitedIpcServer = IpcServer<Stack<Either<RateLimitLayer, Identity>, Identity>>;
+
 /// Container type for each transport ie. http, ws, and ipc server
 pub struct RpcServer {
     /// The address of the http server
@@ -712,11 +1097,11 @@ pub struct RpcServer {
     /// The address of the ws server
     ws_local_addr: Option<SocketAddr>,
     /// http server
-    http: Option<Server>,
+    http: Option<HttpServer>,
     /// ws server
     ws: Option<Server>,
     /// ipc server
-    ipc: Option<IpcServer>,
+    ipc: Option<RateLimitedIpcServer>,
 }
 
 // === impl RpcServer ===
@@ -829,6 +1214,16 @@ impl RpcServerHandle {
         Ok(())
     }
 
+    /// Waits until all servers are stopped, e.g. after [RpcServerHandle::stop] has been called on
+    /// a clone of this handle.
+    ///
+    /// Servers finish the requests they accepted before they stop.
+    pub async fn stopped(self) {
+        for handle in [self.http, self.ws, self.ipc].into_iter().flatten() {
+            handle.stopped().await
+        }
+    }
+
     /// Returns the url to the http server
     pub fn http_url(&self) -> Option<String> {
         self.http_local_addr.map(|addr| format!("http://{addr}"))
@@ -891,6 +1286,7 @@ mod tests {
                 "eth" =>  RethRpcModule::Eth,
                 "net" =>  RethRpcModule::Net,
                 "trace" =>  RethRpcModule::Trace,
+                "txpool" =>  RethRpcModule::Txpool,
                 "web3" =>  RethRpcModule::Web3,
             );
     }
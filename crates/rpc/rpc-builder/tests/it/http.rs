@@ -1,6 +1,6 @@
 //! Standalone http tests
 
-use crate::utils::{launch_http, launch_http_ws, launch_ws};
+use crate::utils::{launch_http, launch_http_ws, launch_ws, test_address, test_rpc_builder};
 use jsonrpsee::{
     core::{
         client::{ClientT, SubscriptionClientT},
@@ -13,13 +13,14 @@ use reth_primitives::{
     rpc::{BlockId, BlockNumber},
     Address, Bytes, NodeRecord, H256, H64, U256,
 };
+use reth_rpc::RateLimitConfig;
 use reth_rpc_api::{
     clients::{AdminApiClient, EthApiClient},
-    DebugApiClient, NetApiClient, TraceApiClient, Web3ApiClient,
+    DebugApiClient, NetApiClient, TraceApiClient, TxPoolApiClient, Web3ApiClient,
 };
-use reth_rpc_builder::RethRpcModule;
+use reth_rpc_builder::{RethRpcModule, RpcServerConfig, TransportRpcModuleConfig};
 use reth_rpc_types::{trace::filter::TraceFilter, CallRequest, Index, TransactionRequest};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 fn is_unimplemented(err: Error) -> bool {
     match err {
@@ -38,6 +39,7 @@ where
     let url = "enode://6f8a80d14311c39f35f516fa664deaaaa13e85b2f7493f37f6144d86991ec012937307647bd3b9a82abe2974e1407241d54947bbb39763a4cac9f77166ad92a0@10.3.58.6:30303?discport=30301";
     let node: NodeRecord = url.parse().unwrap();
 
+    AdminApiClient::peers(client).await.unwrap();
     AdminApiClient::add_peer(client, node).await.unwrap();
     AdminApiClient::remove_peer(client, node).await.unwrap();
     AdminApiClient::add_trusted_peer(client, node).await.unwrap();
@@ -66,29 +68,41 @@ where
     EthApiClient::block_number(client).await.unwrap();
     EthApiClient::get_code(client, address, None).await.unwrap();
     EthApiClient::send_raw_transaction(client, tx).await.unwrap();
+    EthApiClient::send_raw_transactions(client, vec![]).await.unwrap();
     EthApiClient::fee_history(client, 0.into(), block_number.into(), None).await.unwrap();
-
-    // Unimplemented
-    assert!(is_unimplemented(EthApiClient::syncing(client).await.err().unwrap()));
-    assert!(is_unimplemented(EthApiClient::author(client).await.err().unwrap()));
-    assert!(is_unimplemented(
-        EthApiClient::block_by_hash(client, hash, false).await.err().unwrap()
-    ));
-    assert!(is_unimplemented(
-        EthApiClient::block_by_number(client, block_number, false).await.err().unwrap()
-    ));
-    assert!(is_unimplemented(
-        EthApiClient::block_transaction_count_by_hash(client, hash).await.err().unwrap()
+    EthApiClient::syncing(client).await.unwrap();
+    EthApiClient::block_by_hash(client, hash, false).await.unwrap();
+    EthApiClient::block_by_number(client, block_number, false).await.unwrap();
+    EthApiClient::block_transaction_count_by_hash(client, hash).await.unwrap();
+    EthApiClient::block_transaction_count_by_number(client, block_number).await.unwrap();
+    EthApiClient::block_uncles_count_by_hash(client, hash).await.unwrap();
+    EthApiClient::block_uncles_count_by_number(client, block_number).await.unwrap();
+    EthApiClient::transaction_by_hash(client, hash).await.unwrap();
+    EthApiClient::transaction_by_block_hash_and_index(client, hash, index).await.unwrap();
+    EthApiClient::transaction_by_block_number_and_index(client, block_number, index).await.unwrap();
+    EthApiClient::transaction_receipt(client, hash).await.unwrap();
+    EthApiClient::balance(client, address, None).await.unwrap();
+    EthApiClient::storage_at(client, address, U256::default(), None).await.unwrap();
+    EthApiClient::transaction_count(client, address, None).await.unwrap();
+    EthApiClient::gas_price(client).await.unwrap();
+    EthApiClient::max_priority_fee_per_gas(client).await.unwrap();
+    // The test provider has no block to execute calls on
+    assert!(!is_unimplemented(
+        EthApiClient::call(client, call_request.clone(), None, None, None).await.err().unwrap()
     ));
-    assert!(is_unimplemented(
-        EthApiClient::block_transaction_count_by_number(client, block_number).await.err().unwrap()
+    assert!(!is_unimplemented(
+        EthApiClient::estimate_gas(client, call_request.clone(), None).await.err().unwrap()
     ));
-    assert!(is_unimplemented(
-        EthApiClient::block_uncles_count_by_hash(client, hash).await.err().unwrap()
+    assert!(!is_unimplemented(
+        EthApiClient::create_access_list(client, call_request.clone(), None).await.err().unwrap()
     ));
-    assert!(is_unimplemented(
-        EthApiClient::block_uncles_count_by_number(client, block_number).await.err().unwrap()
+    // The test provider has no block to prove the state of
+    assert!(!is_unimplemented(
+        EthApiClient::get_proof(client, address, vec![], None).await.err().unwrap()
     ));
+
+    // Unimplemented
+    assert!(is_unimplemented(EthApiClient::author(client).await.err().unwrap()));
     assert!(is_unimplemented(
         EthApiClient::uncle_by_block_hash_and_index(client, hash, index).await.err().unwrap()
     ));
@@ -98,35 +112,6 @@ where
             .err()
             .unwrap()
     ));
-    assert!(is_unimplemented(EthApiClient::transaction_by_hash(client, hash).await.err().unwrap()));
-    assert!(is_unimplemented(
-        EthApiClient::transaction_by_block_hash_and_index(client, hash, index).await.err().unwrap()
-    ));
-    assert!(is_unimplemented(
-        EthApiClient::transaction_by_block_number_and_index(client, block_number, index)
-            .await
-            .err()
-            .unwrap()
-    ));
-    assert!(is_unimplemented(EthApiClient::transaction_receipt(client, hash).await.err().unwrap()));
-    assert!(is_unimplemented(EthApiClient::balance(client, address, None).await.err().unwrap()));
-    assert!(is_unimplemented(
-        EthApiClient::storage_at(client, address, U256::default(), None).await.err().unwrap()
-    ));
-    assert!(is_unimplemented(
-        EthApiClient::transaction_count(client, address, None).await.err().unwrap()
-    ));
-    assert!(is_unimplemented(
-        EthApiClient::call(client, call_request.clone(), None).await.err().unwrap()
-    ));
-    assert!(is_unimplemented(
-        EthApiClient::create_access_list(client, call_request.clone(), None).await.err().unwrap()
-    ));
-    assert!(is_unimplemented(
-        EthApiClient::estimate_gas(client, call_request.clone(), None).await.err().unwrap()
-    ));
-    assert!(is_unimplemented(EthApiClient::gas_price(client).await.err().unwrap()));
-    assert!(is_unimplemented(EthApiClient::max_priority_fee_per_gas(client).await.err().unwrap()));
     assert!(is_unimplemented(EthApiClient::is_mining(client).await.err().unwrap()));
     assert!(is_unimplemented(EthApiClient::hashrate(client).await.err().unwrap()));
     assert!(is_unimplemented(EthApiClient::get_work(client).await.err().unwrap()));
@@ -157,9 +142,6 @@ where
             .err()
             .unwrap()
     ));
-    assert!(is_unimplemented(
-        EthApiClient::get_proof(client, address, vec![], None).await.err().unwrap()
-    ));
 }
 
 async fn test_basic_debug_calls<C>(client: &C)
@@ -175,6 +157,29 @@ where
     ));
     assert!(is_unimplemented(DebugApiClient::raw_receipts(client, block_id).await.err().unwrap()));
     assert!(is_unimplemented(DebugApiClient::bad_blocks(client).await.err().unwrap()));
+
+    // The test provider has no blocks or transactions to trace
+    assert!(!is_unimplemented(
+        DebugApiClient::debug_trace_block_by_number(client, BlockNumber::default(), None)
+            .await
+            .err()
+            .unwrap()
+    ));
+    assert!(!is_unimplemented(
+        DebugApiClient::debug_trace_block_by_hash(client, H256::default(), None)
+            .await
+            .err()
+            .unwrap()
+    ));
+    assert!(!is_unimplemented(
+        DebugApiClient::debug_trace_transaction(client, H256::default(), None).await.err().unwrap()
+    ));
+    assert!(!is_unimplemented(
+        DebugApiClient::debug_trace_call(client, CallRequest::default(), None, None)
+            .await
+            .err()
+            .unwrap()
+    ));
 }
 
 async fn test_basic_net_calls<C>(client: &C)
@@ -200,39 +205,45 @@ where
         count: None,
     };
 
-    assert!(is_unimplemented(
+    // Implemented
+    TraceApiClient::replay_block_transactions(client, block_id, HashSet::default()).await.unwrap();
+    TraceApiClient::block(client, block_id).await.unwrap();
+    TraceApiClient::filter(client, trace_filter).await.unwrap();
+    TraceApiClient::trace(client, H256::default(), vec![]).await.unwrap();
+    TraceApiClient::transaction_traces(client, H256::default()).await.unwrap();
+    // The test provider has no block to execute calls on
+    assert!(!is_unimplemented(
         TraceApiClient::call(client, CallRequest::default(), HashSet::default(), None)
             .await
             .err()
             .unwrap()
     ));
-    assert!(is_unimplemented(TraceApiClient::call_many(client, vec![], None).await.err().unwrap()));
-    assert!(is_unimplemented(
-        TraceApiClient::raw_transaction(client, Bytes::default(), HashSet::default(), None)
-            .await
-            .err()
-            .unwrap()
-    ));
-    assert!(is_unimplemented(
-        TraceApiClient::replay_block_transactions(client, block_id, HashSet::default())
+    // The test provider has no transactions to replay
+    assert!(!is_unimplemented(
+        TraceApiClient::replay_transaction(client, H256::default(), HashSet::default())
             .await
             .err()
             .unwrap()
     ));
+
+    // Unimplemented
+    assert!(is_unimplemented(TraceApiClient::call_many(client, vec![], None).await.err().unwrap()));
     assert!(is_unimplemented(
-        TraceApiClient::replay_transaction(client, H256::default(), HashSet::default())
+        TraceApiClient::raw_transaction(client, Bytes::default(), HashSet::default(), None)
             .await
             .err()
             .unwrap()
     ));
-    assert!(is_unimplemented(TraceApiClient::block(client, block_id).await.err().unwrap()));
-    assert!(is_unimplemented(TraceApiClient::filter(client, trace_filter).await.err().unwrap()));
-    assert!(is_unimplemented(
-        TraceApiClient::trace(client, H256::default(), vec![]).await.err().unwrap()
-    ));
-    assert!(is_unimplemented(
-        TraceApiClient::transaction_traces(client, H256::default()).await.err().unwrap()
-    ));
+}
+
+async fn test_basic_txpool_calls<C>(client: &C)
+where
+    C: ClientT + SubscriptionClientT + Sync,
+{
+    TxPoolApiClient::txpool_status(client).await.unwrap();
+    TxPoolApiClient::txpool_inspect(client).await.unwrap();
+    TxPoolApiClient::txpool_content_from(client, Address::default()).await.unwrap();
+    TxPoolApiClient::txpool_content(client).await.unwrap();
 }
 
 async fn test_basic_web3_calls<C>(client: &C)
@@ -378,6 +389,33 @@ async fn test_call_trace_functions_http_and_ws() {
     test_basic_trace_calls(&client).await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_txpool_functions_http() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_http(vec![RethRpcModule::Txpool]).await;
+    let client = handle.http_client().unwrap();
+    test_basic_txpool_calls(&client).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_txpool_functions_ws() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_ws(vec![RethRpcModule::Txpool]).await;
+    let client = handle.ws_client().await.unwrap();
+    test_basic_txpool_calls(&client).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_txpool_functions_http_and_ws() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_http_ws(vec![RethRpcModule::Txpool]).await;
+    let client = handle.http_client().unwrap();
+    test_basic_txpool_calls(&client).await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_call_web3_functions_http() {
     reth_tracing::init_test_tracing();
@@ -404,3 +442,45 @@ async fn test_call_web3_functions_http_and_ws() {
     let client = handle.http_client().unwrap();
     test_basic_web3_calls(&client).await;
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_disabled_methods_are_not_served() {
+    reth_tracing::init_test_tracing();
+
+    let handle = test_rpc_builder()
+        .with_disabled_methods(["eth_blockNumber"])
+        .build(TransportRpcModuleConfig::http([RethRpcModule::Eth]))
+        .start_server(RpcServerConfig::http(Default::default()).with_http_address(test_address()))
+        .await
+        .unwrap();
+    let client = handle.http_client().unwrap();
+
+    let err = EthApiClient::block_number(&client).await.err().unwrap();
+    assert!(matches!(err, Error::Call(CallError::Custom(error_obj))
+        if error_obj.code() == ErrorCode::MethodNotFound.code()));
+    EthApiClient::chain_id(&client).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rate_limited_methods() {
+    reth_tracing::init_test_tracing();
+
+    let rate_limit = RateLimitConfig {
+        max_calls_per_method: HashMap::from([("eth_chainId".to_string(), 1)]),
+        ..Default::default()
+    };
+    let handle = test_rpc_builder()
+        .build(TransportRpcModuleConfig::http([RethRpcModule::Eth]))
+        .start_server(
+            RpcServerConfig::http(Default::default())
+                .with_http_address(test_address())
+                .with_rate_limit(Some(rate_limit)),
+        )
+        .await
+        .unwrap();
+    let client = handle.http_client().unwrap();
+
+    EthApiClient::chain_id(&client).await.unwrap();
+    assert!(EthApiClient::chain_id(&client).await.is_err());
+    EthApiClient::block_number(&client).await.unwrap();
+}
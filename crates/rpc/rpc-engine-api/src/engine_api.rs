@@ -1,34 +1,50 @@
-use crate::{EngineApiError, EngineApiMessage, EngineApiResult};
-use futures::StreamExt;
+use crate::{EngineApiError, EngineApiMessage, EngineApiMessageVersion, EngineApiResult};
+use futures::{FutureExt, StreamExt};
 use reth_executor::{
+    blockchain_tree::{BlockStatus, BlockchainTreeEngine, BlockchainTreeError},
     executor,
     revm_wrap::{State, SubState},
 };
 use reth_interfaces::consensus::ForkchoiceState;
+use reth_payload_builder::{BuiltPayload, PayloadBuilderAttributes, PayloadBuilderHandle};
 use reth_primitives::{
     proofs::{self, EMPTY_LIST_HASH},
     rpc::{BlockId, H256 as EthersH256},
-    ChainSpec, Hardfork, Header, SealedBlock, TransactionSigned, H64, U256,
+    ChainSpec, Hardfork, Header, SealedBlock, TransactionSigned, H256, U256,
 };
-use reth_provider::{BlockProvider, HeaderProvider, StateProvider};
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
 use reth_rlp::Decodable;
 use reth_rpc_types::engine::{
-    ExecutionPayload, ForkchoiceUpdated, PayloadAttributes, PayloadStatus, PayloadStatusEnum,
-    TransitionConfiguration,
+    ExecutionPayload, ExecutionPayloadEnvelope, ForkchoiceUpdated, PayloadAttributes,
+    PayloadStatus, PayloadStatusEnum, TransitionConfiguration,
 };
 use std::{
-    collections::HashMap,
     future::Future,
     pin::Pin,
     sync::Arc,
-    task::{ready, Context, Poll},
+    task::{Context, Poll},
 };
-use tokio::sync::{oneshot, watch};
+use tokio::sync::{mpsc::UnboundedReceiver, oneshot, watch};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 /// The Engine API response sender
 pub type EngineApiSender<Ok> = oneshot::Sender<EngineApiResult<Ok>>;
 
+/// The capabilities of the Engine API implementation, as returned by
+/// `engine_exchangeCapabilities`.
+pub const CAPABILITIES: [&str; 10] = [
+    "engine_forkchoiceUpdatedV1",
+    "engine_forkchoiceUpdatedV2",
+    "engine_forkchoiceUpdatedV3",
+    "engine_exchangeTransitionConfigurationV1",
+    "engine_getPayloadV1",
+    "engine_getPayloadV2",
+    "engine_getPayloadV3",
+    "engine_newPayloadV1",
+    "engine_newPayloadV2",
+    "engine_newPayloadV3",
+];
+
 /// The Engine API implementation that grants the Consensus layer access to data and
 /// functions in the Execution layer that are crucial for the consensus process.
 #[must_use = "EngineApi does nothing unless polled."]
@@ -38,23 +54,88 @@ pub struct EngineApi<Client> {
     chain_spec: ChainSpec,
     message_rx: UnboundedReceiverStream<EngineApiMessage>,
     forkchoice_state_tx: watch::Sender<ForkchoiceState>,
-    // TODO: Placeholder for storing future blocks. Make cache bounded.
-    // Use [lru](https://crates.io/crates/lru) crate
-    local_store: HashMap<H64, ExecutionPayload>,
-    // remote_store: HashMap<H64, ExecutionPayload>,
+    /// Handle to the payload builder, payloads are only built if it is set.
+    payload_builder: Option<PayloadBuilderHandle>,
+    /// The `engine_getPayload` requests waiting for their payload to be resolved.
+    pending_payloads: Vec<PendingPayload>,
+    /// The tree the payloads are inserted into and made canonical by, if it is set.
+    tree: Option<Box<dyn BlockchainTreeEngine>>,
+}
+
+/// An `engine_getPayload` request waiting for the payload builder.
+struct PendingPayload {
+    version: EngineApiMessageVersion,
+    rx: oneshot::Receiver<Option<BuiltPayload>>,
+    tx: EngineApiSender<ExecutionPayloadEnvelope>,
 }
 
-impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
+impl<Client: HeaderProvider + BlockProvider + StateProviderFactory> EngineApi<Client> {
+    /// Creates a new instance of [EngineApi] that handles the messages of the given channel.
+    ///
+    /// The forkchoice states received from the consensus layer are forwarded to
+    /// `forkchoice_state_tx`.
+    pub fn new(
+        client: Arc<Client>,
+        chain_spec: ChainSpec,
+        message_rx: UnboundedReceiver<EngineApiMessage>,
+        forkchoice_state_tx: watch::Sender<ForkchoiceState>,
+    ) -> Self {
+        Self {
+            client,
+            chain_spec,
+            message_rx: UnboundedReceiverStream::new(message_rx),
+            forkchoice_state_tx,
+            payload_builder: None,
+            pending_payloads: Vec::new(),
+            tree: None,
+        }
+    }
+
+    /// Sets the handle to the payload builder that builds the payloads requested by
+    /// `engine_forkchoiceUpdated`.
+    pub fn with_payload_builder(mut self, payload_builder: PayloadBuilderHandle) -> Self {
+        self.payload_builder = Some(payload_builder);
+        self
+    }
+
+    /// Sets the blockchain tree that executes the new payloads on top of their parent, canonical
+    /// or not, and commits the head of the forkchoice updates to the database.
+    ///
+    /// Without a tree, the payloads are only validated on top of the database and the forkchoice
+    /// updates are forwarded to the pipeline.
+    pub fn with_blockchain_tree(mut self, tree: Box<dyn BlockchainTreeEngine>) -> Self {
+        self.tree = Some(tree);
+        self
+    }
+
     fn on_message(&mut self, msg: EngineApiMessage) {
         match msg {
-            EngineApiMessage::GetPayload(payload_id, tx) => {
-                let _ = tx.send(self.get_payload(payload_id).ok_or(EngineApiError::PayloadUnknown));
-            }
-            EngineApiMessage::NewPayload(payload, tx) => {
-                let _ = tx.send(self.new_payload(payload));
+            EngineApiMessage::GetPayload(version, payload_id, tx) => match &self.payload_builder {
+                Some(payload_builder) => self.pending_payloads.push(PendingPayload {
+                    version,
+                    rx: payload_builder.resolve(payload_id),
+                    tx,
+                }),
+                None => {
+                    let _ = tx.send(Err(EngineApiError::PayloadUnknown));
+                }
+            },
+            EngineApiMessage::NewPayload(
+                version,
+                payload,
+                versioned_hashes,
+                parent_beacon_block_root,
+                tx,
+            ) => {
+                let _ = tx.send(self.new_payload(
+                    version,
+                    payload,
+                    versioned_hashes,
+                    parent_beacon_block_root,
+                ));
             }
-            EngineApiMessage::ForkchoiceUpdated(state, attrs, tx) => {
-                let _ = tx.send(self.fork_choice_updated(state, attrs));
+            EngineApiMessage::ForkchoiceUpdated(version, state, attrs, tx) => {
+                let _ = tx.send(self.fork_choice_updated(version, state, attrs));
             }
             EngineApiMessage::ExchangeTransitionConfiguration(config, tx) => {
                 let _ = tx.send(self.exchange_transition_configuration(config));
@@ -62,6 +143,54 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
         }
     }
 
+    /// Validates the presence of the withdrawals for the given method version at the given
+    /// timestamp.
+    ///
+    /// V1 methods never accept withdrawals, later versions require them exactly if Shanghai is
+    /// active.
+    fn validate_withdrawals_presence(
+        &self,
+        version: EngineApiMessageVersion,
+        timestamp: u64,
+        has_withdrawals: bool,
+    ) -> EngineApiResult<()> {
+        let is_shanghai = self.chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(timestamp);
+
+        match version {
+            EngineApiMessageVersion::V1 if has_withdrawals => {
+                Err(EngineApiError::WithdrawalsNotSupportedInV1)
+            }
+            EngineApiMessageVersion::V2 | EngineApiMessageVersion::V3 => {
+                if is_shanghai && !has_withdrawals {
+                    return Err(EngineApiError::NoWithdrawalsPostShanghai)
+                }
+                if !is_shanghai && has_withdrawals {
+                    return Err(EngineApiError::HasWithdrawalsPreShanghai)
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Validates that the given method version is the one of the fork active at the given
+    /// timestamp: V3 methods are only supported after Cancun, earlier versions only before.
+    fn validate_fork_version(
+        &self,
+        version: EngineApiMessageVersion,
+        timestamp: u64,
+    ) -> EngineApiResult<()> {
+        let is_cancun = self.chain_spec.fork(Hardfork::Cancun).active_at_timestamp(timestamp);
+
+        match version {
+            EngineApiMessageVersion::V3 if !is_cancun => Err(EngineApiError::UnsupportedFork),
+            EngineApiMessageVersion::V1 | EngineApiMessageVersion::V2 if is_cancun => {
+                Err(EngineApiError::UnsupportedFork)
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Try to construct a block from given payload. Perform addition validation of `extra_data` and
     /// `base_fee_per_gas` fields.
     ///
@@ -70,7 +199,11 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
     /// comparing the value with `payload.block_hash`.
     ///
     /// See <https://github.com/ethereum/go-ethereum/blob/79a478bb6176425c2400e949890e668a3d9a3d05/core/beacon/types.go#L145>
-    fn try_construct_block(&self, payload: ExecutionPayload) -> EngineApiResult<SealedBlock> {
+    fn try_construct_block(
+        &self,
+        payload: ExecutionPayload,
+        parent_beacon_block_root: Option<H256>,
+    ) -> EngineApiResult<SealedBlock> {
         if payload.extra_data.len() > 32 {
             return Err(EngineApiError::PayloadExtraData(payload.extra_data))
         }
@@ -103,6 +236,9 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
             timestamp: payload.timestamp.as_u64(),
             mix_hash: payload.prev_randao,
             base_fee_per_gas: Some(payload.base_fee_per_gas.to::<u64>()),
+            blob_gas_used: payload.blob_gas_used.map(|gas| gas.as_u64()),
+            excess_blob_gas: payload.excess_blob_gas.map(|gas| gas.as_u64()),
+            parent_beacon_block_root,
             extra_data: payload.extra_data,
             // Defaults
             ommers_hash: EMPTY_LIST_HASH,
@@ -126,13 +262,18 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
         })
     }
 
-    /// Called to retrieve the latest state of the network, validate new blocks, and maintain
-    /// consistency between the Consensus and Execution layers.
+    /// Returns the payload resolved by the payload builder for `engine_getPayload`, if it
+    /// belongs to a fork the given method version supports.
     ///
-    /// NOTE: Will always result in `PayloadUnknown` since we don't support block
-    /// building for now.
-    pub fn get_payload(&self, payload_id: H64) -> Option<ExecutionPayload> {
-        self.local_store.get(&payload_id).cloned()
+    /// The payload is `None` if the payload builder does not know it.
+    fn on_resolved_payload(
+        &self,
+        version: EngineApiMessageVersion,
+        payload: Option<BuiltPayload>,
+    ) -> EngineApiResult<ExecutionPayloadEnvelope> {
+        let BuiltPayload { block, fees, .. } = payload.ok_or(EngineApiError::PayloadUnknown)?;
+        self.validate_fork_version(version, block.timestamp)?;
+        Ok(ExecutionPayloadEnvelope { execution_payload: block.into(), block_value: fees })
     }
 
     /// When the Consensus layer receives a new block via the consensus gossip protocol,
@@ -141,8 +282,22 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
     /// state in the block header, then passes validation data back to Consensus layer, that
     /// adds the block to the head of its own blockchain and attests to it. The block is then
     /// broadcasted over the consensus p2p network in the form of a "Beacon block".
-    pub fn new_payload(&mut self, payload: ExecutionPayload) -> EngineApiResult<PayloadStatus> {
-        let block = match self.try_construct_block(payload) {
+    ///
+    /// The versioned hashes are the expected blob versioned hashes of the payload transactions,
+    /// and the parent beacon block root is committed to by the header of the block, they are
+    /// required by V3.
+    pub fn new_payload(
+        &mut self,
+        version: EngineApiMessageVersion,
+        payload: ExecutionPayload,
+        versioned_hashes: Option<Vec<H256>>,
+        parent_beacon_block_root: Option<H256>,
+    ) -> EngineApiResult<PayloadStatus> {
+        let timestamp = payload.timestamp.as_u64();
+        self.validate_withdrawals_presence(version, timestamp, payload.withdrawals.is_some())?;
+        self.validate_fork_version(version, timestamp)?;
+
+        let block = match self.try_construct_block(payload, parent_beacon_block_root) {
             Ok(b) => b,
             Err(err) => {
                 return Ok(PayloadStatus::from_status(PayloadStatusEnum::InvalidBlockHash {
@@ -153,14 +308,33 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
         let block_hash = block.header.hash();
         let parent_hash = block.parent_hash;
 
+        if let Some(versioned_hashes) = versioned_hashes {
+            let block_versioned_hashes = block
+                .body
+                .iter()
+                .filter_map(|tx| tx.transaction.blob_versioned_hashes())
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>();
+            if block_versioned_hashes != versioned_hashes {
+                return Ok(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
+                    validation_error: EngineApiError::VersionedHashesMismatch.to_string(),
+                }))
+            }
+        }
+
         // The block already exists in our database
         if self.client.is_known(&block_hash)? {
             return Ok(PayloadStatus::new(PayloadStatusEnum::Valid, block_hash))
         }
 
         let Some(parent) = self.client.block(BlockId::Hash(EthersH256(parent_hash.0)))? else {
-             // TODO: cache block for storing later
-             return Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing))
+            // The parent is in a side chain of the tree, the block extends it.
+            if let Some(tree) = self.tree.as_mut().filter(|tree| tree.contains(parent_hash)) {
+                return insert_into_tree(tree.as_mut(), block)
+            }
+            // TODO: cache block for storing later
+            return Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing))
         };
 
         let parent_td = if let Some(parent_td) = self.client.header_td(&block.parent_hash)? {
@@ -188,7 +362,12 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
             }))
         }
 
-        let mut state_provider = SubState::new(State::new(&*self.client));
+        if let Some(tree) = self.tree.as_mut() {
+            return insert_into_tree(tree.as_mut(), block)
+        }
+
+        let mut state_provider =
+            SubState::new(State::new(self.client.history_by_block_hash(parent_hash)?));
         let total_difficulty = parent_td + block.header.difficulty;
         match executor::execute_and_verify_receipt(
             &block.unseal(),
@@ -207,8 +386,11 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
 
     /// Called to resolve chain forks and ensure that the Execution layer is working with the latest
     /// valid chain.
+    ///
+    /// If the blockchain tree is set, the head block is made canonical by it.
     pub fn fork_choice_updated(
-        &self,
+        &mut self,
+        version: EngineApiMessageVersion,
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<PayloadAttributes>,
     ) -> EngineApiResult<ForkchoiceUpdated> {
@@ -220,8 +402,24 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
             }))
         }
 
+        let head_known = match self.tree.as_mut() {
+            Some(tree) => match tree.make_canonical(head_block_hash) {
+                Ok(()) => true,
+                Err(BlockchainTreeError::BlockUnknown(_)) => false,
+                Err(
+                    err @ (BlockchainTreeError::Consensus(_) | BlockchainTreeError::Execution(_)),
+                ) => {
+                    return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Invalid {
+                        validation_error: err.to_string(),
+                    }))
+                }
+                Err(err) => return Err(err.into()),
+            },
+            None => self.client.is_known(&head_block_hash)?,
+        };
+
         // Block is not known, nothing to do.
-        if !self.client.is_known(&head_block_hash)? {
+        if !head_known {
             return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing))
         }
 
@@ -234,13 +432,32 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
             tracing::error!(target: "rpc::engine_api", ?error, "Failed to update forkchoice state");
         }
 
-        if let Some(_attr) = payload_attributes {
-            // TODO: optionally build the block
+        let chain_info = self.client.chain_info()?;
+        let mut response = ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
+            .with_latest_valid_hash(chain_info.best_hash);
+
+        let Some(attrs) = payload_attributes else { return Ok(response) };
+
+        // The forkchoice state is applied even if the payload attributes are invalid.
+        let timestamp = attrs.timestamp.as_u64();
+        self.validate_withdrawals_presence(version, timestamp, attrs.withdrawals.is_some())
+            .map_err(|err| EngineApiError::InvalidPayloadAttributes(Box::new(err)))?;
+        self.validate_fork_version(version, timestamp)?;
+
+        // Start building a payload on top of the new head.
+        if let Some(payload_builder) = &self.payload_builder {
+            let payload_id = payload_builder.new_payload(PayloadBuilderAttributes {
+                parent: head_block_hash,
+                timestamp: attrs.timestamp.as_u64(),
+                prev_randao: attrs.prev_randao,
+                suggested_fee_recipient: attrs.suggested_fee_recipient,
+                withdrawals: attrs.withdrawals,
+                parent_beacon_block_root: attrs.parent_beacon_block_root,
+            });
+            response = response.with_payload_id(payload_id);
         }
 
-        let chain_info = self.client.chain_info()?;
-        Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
-            .with_latest_valid_hash(chain_info.best_hash))
+        Ok(response)
     }
 
     /// Called to verify network configuration parameters and ensure that Consensus and Execution
@@ -295,23 +512,62 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EngineApi<Client> {
     }
 }
 
+/// Inserts the block into the tree, which validates and executes it on top of its parent, and
+/// returns its status.
+fn insert_into_tree(
+    tree: &mut dyn BlockchainTreeEngine,
+    block: SealedBlock,
+) -> EngineApiResult<PayloadStatus> {
+    let block_hash = block.hash();
+    let parent_hash = block.parent_hash;
+    match tree.insert_block(block) {
+        Ok(BlockStatus::Valid) => Ok(PayloadStatus::new(PayloadStatusEnum::Valid, block_hash)),
+        Ok(BlockStatus::Accepted) => Ok(PayloadStatus::from_status(PayloadStatusEnum::Accepted)),
+        Err(BlockchainTreeError::ParentUnknown { .. }) => {
+            Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing))
+        }
+        Err(
+            err @ (BlockchainTreeError::Consensus(_) |
+            BlockchainTreeError::Execution(_) |
+            BlockchainTreeError::ForkTooDeep { .. }),
+        ) => Ok(PayloadStatus::new(
+            PayloadStatusEnum::Invalid { validation_error: err.to_string() },
+            parent_hash, // The parent is canonical or in the tree hence it is valid
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
 impl<Client> Future for EngineApi<Client>
 where
-    Client: HeaderProvider + BlockProvider + StateProvider + Unpin,
+    Client: HeaderProvider + BlockProvider + StateProviderFactory + Unpin,
 {
     type Output = ();
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
         loop {
-            match ready!(this.message_rx.poll_next_unpin(cx)) {
-                Some(msg) => this.on_message(msg),
-                None => {
+            match this.message_rx.poll_next_unpin(cx) {
+                Poll::Ready(Some(msg)) => this.on_message(msg),
+                Poll::Ready(None) => {
                     // channel closed
                     return Poll::Ready(())
                 }
+                Poll::Pending => break,
+            }
+        }
+
+        let mut idx = 0;
+        while idx < this.pending_payloads.len() {
+            if let Poll::Ready(payload) = this.pending_payloads[idx].rx.poll_unpin(cx) {
+                let PendingPayload { version, tx, .. } = this.pending_payloads.swap_remove(idx);
+                let _ = tx.send(this.on_resolved_payload(version, payload.ok().flatten()));
+            } else {
+                idx += 1;
             }
         }
+
+        Poll::Pending
     }
 }
 
@@ -320,7 +576,7 @@ mod tests {
     use super::*;
     use assert_matches::assert_matches;
     use reth_interfaces::test_utils::generators::random_block;
-    use reth_primitives::{H256, MAINNET};
+    use reth_primitives::{H256, H64, MAINNET};
     use reth_provider::test_utils::MockEthProvider;
     use tokio::sync::mpsc::unbounded_channel;
 
@@ -341,12 +597,7 @@ mod tests {
                 proofs::calculate_transaction_root(transformed.body.iter());
             transformed.header.ommers_hash =
                 proofs::calculate_ommers_root(transformed.ommers.iter());
-            SealedBlock {
-                header: transformed.header.seal_slow(),
-                body: transformed.body,
-                ommers: transformed.ommers.into_iter().map(Header::seal_slow).collect(),
-                withdrawals: transformed.withdrawals,
-            }
+            transformed.seal_slow()
         }
 
         #[tokio::test]
@@ -356,7 +607,9 @@ mod tests {
             let engine = EngineApi {
                 client: Arc::new(MockEthProvider::default()),
                 chain_spec: MAINNET.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -368,7 +621,10 @@ mod tests {
                 b.header.extra_data = BytesMut::zeroed(32).freeze().into();
                 b
             });
-            assert_matches!(engine.try_construct_block(block_with_valid_extra_data.into()), Ok(_));
+            assert_matches!(
+                engine.try_construct_block(block_with_valid_extra_data.into(), None),
+                Ok(_)
+            );
 
             // Invalid extra data
             let block_with_invalid_extra_data: Bytes = BytesMut::zeroed(33).freeze();
@@ -377,7 +633,7 @@ mod tests {
                 b
             });
             assert_matches!(
-                engine.try_construct_block(invalid_extra_data_block.into()),
+                engine.try_construct_block(invalid_extra_data_block.into(), None),
                 Err(EngineApiError::PayloadExtraData(data)) if data == block_with_invalid_extra_data
             );
 
@@ -387,7 +643,7 @@ mod tests {
                 b
             });
             assert_matches!(
-                engine.try_construct_block(block_with_zero_base_fee.into()),
+                engine.try_construct_block(block_with_zero_base_fee.into(), None),
                 Err(EngineApiError::PayloadBaseFee(val)) if val == U256::ZERO
             );
 
@@ -397,7 +653,7 @@ mod tests {
                 *tx = Bytes::new().into();
             });
             assert_matches!(
-                engine.try_construct_block(payload_with_invalid_txs),
+                engine.try_construct_block(payload_with_invalid_txs, None),
                 Err(EngineApiError::Decode(DecodeError::InputTooShort))
             );
 
@@ -407,7 +663,7 @@ mod tests {
                 b
             });
             assert_matches!(
-                engine.try_construct_block(block_with_ommers.clone().into()),
+                engine.try_construct_block(block_with_ommers.clone().into(), None),
                 Err(EngineApiError::PayloadBlockHash { consensus, .. })
                     if consensus == block_with_ommers.hash()
             );
@@ -418,7 +674,7 @@ mod tests {
                 b
             });
             assert_matches!(
-                engine.try_construct_block(block_with_difficulty.clone().into()),
+                engine.try_construct_block(block_with_difficulty.clone().into(), None),
                 Err(EngineApiError::PayloadBlockHash { consensus, .. })
                     if consensus == block_with_difficulty.hash()
             );
@@ -429,14 +685,14 @@ mod tests {
                 b
             });
             assert_matches!(
-                engine.try_construct_block(block_with_nonce.clone().into()),
+                engine.try_construct_block(block_with_nonce.clone().into(), None),
                 Err(EngineApiError::PayloadBlockHash { consensus, .. })
                     if consensus == block_with_nonce.hash()
             );
 
             // Valid block
             let valid_block = block;
-            assert_matches!(engine.try_construct_block(valid_block.into()), Ok(_));
+            assert_matches!(engine.try_construct_block(valid_block.into(), None), Ok(_));
         }
 
         #[tokio::test]
@@ -447,7 +703,9 @@ mod tests {
             let engine = EngineApi {
                 client: client.clone(),
                 chain_spec: MAINNET.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -462,7 +720,13 @@ mod tests {
 
             let (result_tx, result_rx) = oneshot::channel();
             msg_tx
-                .send(EngineApiMessage::NewPayload(execution_payload, result_tx))
+                .send(EngineApiMessage::NewPayload(
+                    EngineApiMessageVersion::V1,
+                    execution_payload,
+                    None,
+                    None,
+                    result_tx,
+                ))
                 .expect("failed to send engine msg");
 
             let result = result_rx.await;
@@ -478,7 +742,9 @@ mod tests {
             let engine = EngineApi {
                 client: Arc::new(MockEthProvider::default()),
                 chain_spec: MAINNET.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -488,7 +754,13 @@ mod tests {
             let (result_tx, result_rx) = oneshot::channel();
             let block = random_block(100, Some(H256::random()), None, Some(0)); // payload must have no ommers
             msg_tx
-                .send(EngineApiMessage::NewPayload(block.into(), result_tx))
+                .send(EngineApiMessage::NewPayload(
+                    EngineApiMessageVersion::V1,
+                    block.into(),
+                    None,
+                    None,
+                    result_tx,
+                ))
                 .expect("failed to send engine msg");
 
             let result = result_rx.await;
@@ -506,7 +778,9 @@ mod tests {
             let engine = EngineApi {
                 client: client.clone(),
                 chain_spec: chain_spec.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -524,7 +798,13 @@ mod tests {
             client.add_block(parent.hash(), parent.clone().unseal());
 
             msg_tx
-                .send(EngineApiMessage::NewPayload(block.clone().into(), result_tx))
+                .send(EngineApiMessage::NewPayload(
+                    EngineApiMessageVersion::V1,
+                    block.clone().into(),
+                    None,
+                    None,
+                    result_tx,
+                ))
                 .expect("failed to send engine msg");
 
             let result = result_rx.await;
@@ -544,7 +824,9 @@ mod tests {
             let engine = EngineApi {
                 client: client.clone(),
                 chain_spec: chain_spec.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -569,7 +851,13 @@ mod tests {
             client.add_block(parent.hash(), parent.clone().unseal());
 
             msg_tx
-                .send(EngineApiMessage::NewPayload(block.clone().into(), result_tx))
+                .send(EngineApiMessage::NewPayload(
+                    EngineApiMessageVersion::V1,
+                    block.clone().into(),
+                    None,
+                    None,
+                    result_tx,
+                ))
                 .expect("failed to send engine msg");
 
             let result = result_rx.await;
@@ -584,13 +872,64 @@ mod tests {
             assert_eq!(result.unwrap().unwrap(), expected_result);
         }
 
+        #[tokio::test]
+        async fn payload_version_mismatch() {
+            let (_msg_tx, msg_rx) = unbounded_channel();
+            let (tip_tx, _tip_rx) = watch::channel(ForkchoiceState::default());
+            let mut engine = EngineApi::new(
+                Arc::new(MockEthProvider::default()),
+                MAINNET.clone(),
+                msg_rx,
+                tip_tx,
+            );
+
+            let block = random_block(100, Some(H256::random()), None, Some(0));
+            let mut payload_with_withdrawals: ExecutionPayload = block.clone().into();
+            payload_with_withdrawals.withdrawals = Some(Vec::new());
+
+            // V1 doesn't accept withdrawals
+            assert_matches!(
+                engine.new_payload(
+                    EngineApiMessageVersion::V1,
+                    payload_with_withdrawals.clone(),
+                    None,
+                    None
+                ),
+                Err(EngineApiError::WithdrawalsNotSupportedInV1)
+            );
+
+            // Withdrawals are not accepted before Shanghai
+            assert_matches!(
+                engine.new_payload(
+                    EngineApiMessageVersion::V2,
+                    payload_with_withdrawals,
+                    None,
+                    None
+                ),
+                Err(EngineApiError::HasWithdrawalsPreShanghai)
+            );
+
+            // V3 is not supported before Cancun
+            assert_matches!(
+                engine.new_payload(
+                    EngineApiMessageVersion::V3,
+                    block.into(),
+                    Some(Vec::new()),
+                    Some(H256::zero())
+                ),
+                Err(EngineApiError::UnsupportedFork)
+            );
+        }
+
         // TODO: add execution tests
     }
 
     // non exhaustive tests for engine_getPayload
-    // TODO: amend when block building is implemented
     mod get_payload {
         use super::*;
+        use reth_payload_builder::{PayloadBuilder, PayloadBuilderConfig, PayloadBuilderService};
+        use reth_primitives::{Address, ChainSpecBuilder};
+        use reth_transaction_pool::test_utils::testing_pool;
 
         #[tokio::test]
         async fn payload_unknown() {
@@ -599,7 +938,9 @@ mod tests {
             let engine = EngineApi {
                 client: Arc::new(MockEthProvider::default()),
                 chain_spec: MAINNET.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -610,15 +951,96 @@ mod tests {
 
             let (result_tx, result_rx) = oneshot::channel();
             msg_tx
-                .send(EngineApiMessage::GetPayload(payload_id, result_tx))
+                .send(EngineApiMessage::GetPayload(
+                    EngineApiMessageVersion::V1,
+                    payload_id,
+                    result_tx,
+                ))
                 .expect("failed to send engine msg");
 
             assert_matches!(result_rx.await, Ok(Err(EngineApiError::PayloadUnknown)));
         }
+
+        #[tokio::test]
+        async fn payload_is_built() {
+            let (msg_tx, msg_rx) = unbounded_channel();
+            let (tip_tx, _tip_rx) = watch::channel(ForkchoiceState::default());
+            let client = Arc::new(MockEthProvider::default());
+            let chain_spec = ChainSpecBuilder::mainnet().shanghai_activated().build();
+
+            let (payload_service, payload_builder) = PayloadBuilderService::new(
+                PayloadBuilder::new(client.clone(), testing_pool(), chain_spec.clone()),
+                PayloadBuilderConfig::default(),
+            );
+            tokio::spawn(payload_service);
+
+            let engine = EngineApi::new(client.clone(), chain_spec, msg_rx, tip_tx)
+                .with_payload_builder(payload_builder);
+            tokio::spawn(engine);
+
+            let head = Header {
+                number: 100,
+                gas_limit: 30_000_000,
+                base_fee_per_gas: Some(7),
+                timestamp: 1000,
+                ..Default::default()
+            }
+            .seal_slow();
+            client.add_header(head.hash(), head.clone().unseal());
+
+            let state = ForkchoiceState { head_block_hash: head.hash(), ..Default::default() };
+            let attributes = PayloadAttributes {
+                timestamp: (head.timestamp + 12).into(),
+                prev_randao: H256::random(),
+                suggested_fee_recipient: Address::random(),
+                withdrawals: Some(Vec::new()),
+                parent_beacon_block_root: None,
+            };
+
+            let (result_tx, result_rx) = oneshot::channel();
+            msg_tx
+                .send(EngineApiMessage::ForkchoiceUpdated(
+                    EngineApiMessageVersion::V2,
+                    state,
+                    Some(attributes.clone()),
+                    result_tx,
+                ))
+                .expect("failed to send engine msg");
+            let payload_id = result_rx.await.unwrap().unwrap().payload_id.expect("no payload id");
+
+            let (result_tx, result_rx) = oneshot::channel();
+            msg_tx
+                .send(EngineApiMessage::GetPayload(
+                    EngineApiMessageVersion::V2,
+                    payload_id,
+                    result_tx,
+                ))
+                .expect("failed to send engine msg");
+            let envelope = result_rx.await.unwrap().unwrap();
+            assert_eq!(envelope.block_value, U256::ZERO);
+
+            let payload = envelope.execution_payload;
+            assert_eq!(payload.parent_hash, head.hash());
+            assert_eq!(payload.timestamp, attributes.timestamp);
+            assert_eq!(payload.fee_recipient, attributes.suggested_fee_recipient);
+            assert!(payload.transactions.is_empty());
+
+            // the payload job is done once the payload is resolved
+            let (result_tx, result_rx) = oneshot::channel();
+            msg_tx
+                .send(EngineApiMessage::GetPayload(
+                    EngineApiMessageVersion::V2,
+                    payload_id,
+                    result_tx,
+                ))
+                .expect("failed to send engine msg");
+            assert_matches!(result_rx.await, Ok(Err(EngineApiError::PayloadUnknown)));
+        }
     }
 
     mod fork_choice_updated {
         use reth_interfaces::test_utils::generators::random_header;
+        use reth_primitives::Address;
 
         use super::*;
 
@@ -629,7 +1051,9 @@ mod tests {
             let engine = EngineApi {
                 client: Arc::new(MockEthProvider::default()),
                 chain_spec: MAINNET.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -639,8 +1063,10 @@ mod tests {
             let (result_tx, result_rx) = oneshot::channel();
             msg_tx
                 .send(EngineApiMessage::ForkchoiceUpdated(
+                    EngineApiMessageVersion::V1,
                     ForkchoiceState::default(),
                     None,
+                    None,
                     result_tx,
                 ))
                 .expect("failed to send engine msg");
@@ -663,7 +1089,9 @@ mod tests {
             let engine = EngineApi {
                 client: Arc::new(MockEthProvider::default()),
                 chain_spec: MAINNET.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -674,7 +1102,13 @@ mod tests {
 
             let (result_tx, result_rx) = oneshot::channel();
             msg_tx
-                .send(EngineApiMessage::ForkchoiceUpdated(state, None, result_tx))
+                .send(EngineApiMessage::ForkchoiceUpdated(
+                    EngineApiMessageVersion::V1,
+                    state,
+                    None,
+                    None,
+                    result_tx,
+                ))
                 .expect("failed to send engine msg");
 
             let result = result_rx.await;
@@ -694,7 +1128,9 @@ mod tests {
             let engine = EngineApi {
                 client: client.clone(),
                 chain_spec: MAINNET.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -712,7 +1148,13 @@ mod tests {
 
             let (result_tx, result_rx) = oneshot::channel();
             msg_tx
-                .send(EngineApiMessage::ForkchoiceUpdated(state, None, result_tx))
+                .send(EngineApiMessage::ForkchoiceUpdated(
+                    EngineApiMessageVersion::V1,
+                    state,
+                    None,
+                    None,
+                    result_tx,
+                ))
                 .expect("failed to send engine msg");
 
             let result = result_rx.await;
@@ -732,7 +1174,9 @@ mod tests {
             let engine = EngineApi {
                 client: client.clone(),
                 chain_spec: MAINNET.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -754,7 +1198,13 @@ mod tests {
 
             let (result_tx, result_rx) = oneshot::channel();
             msg_tx
-                .send(EngineApiMessage::ForkchoiceUpdated(state.clone(), None, result_tx))
+                .send(EngineApiMessage::ForkchoiceUpdated(
+                    EngineApiMessageVersion::V1,
+                    state.clone(),
+                    None,
+                    None,
+                    result_tx,
+                ))
                 .expect("failed to send engine msg");
 
             let result = result_rx.await;
@@ -773,6 +1223,55 @@ mod tests {
             assert!(tip_rx.has_changed().unwrap());
             assert_eq!(tip_rx.borrow().clone(), state);
         }
+
+        #[tokio::test]
+        async fn forkchoice_state_is_updated_with_invalid_attributes() {
+            let (msg_tx, msg_rx) = unbounded_channel();
+            let (tip_tx, tip_rx) = watch::channel(ForkchoiceState::default());
+            let client = Arc::new(MockEthProvider::default());
+            let engine = EngineApi {
+                client: client.clone(),
+                chain_spec: MAINNET.clone(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
+                message_rx: UnboundedReceiverStream::new(msg_rx),
+                forkchoice_state_tx: tip_tx,
+            };
+
+            tokio::spawn(engine);
+
+            let head = random_header(100, None);
+            client.add_header(head.hash(), head.clone().unseal());
+            let state = ForkchoiceState { head_block_hash: head.hash(), ..Default::default() };
+
+            // No withdrawals at the Shanghai timestamp of mainnet
+            let attributes = PayloadAttributes {
+                timestamp: 1677557088u64.into(),
+                prev_randao: H256::random(),
+                suggested_fee_recipient: Address::random(),
+                withdrawals: None,
+                parent_beacon_block_root: None,
+            };
+
+            let (result_tx, result_rx) = oneshot::channel();
+            msg_tx
+                .send(EngineApiMessage::ForkchoiceUpdated(
+                    EngineApiMessageVersion::V2,
+                    state.clone(),
+                    Some(attributes),
+                    result_tx,
+                ))
+                .expect("failed to send engine msg");
+
+            assert_matches!(
+                result_rx.await,
+                Ok(Err(EngineApiError::InvalidPayloadAttributes(err)))
+                    if matches!(*err, EngineApiError::NoWithdrawalsPostShanghai)
+            );
+            assert!(tip_rx.has_changed().unwrap());
+            assert_eq!(tip_rx.borrow().clone(), state);
+        }
     }
 
     // https://github.com/ethereum/execution-apis/blob/main/src/engine/paris.md#specification-3
@@ -787,7 +1286,9 @@ mod tests {
             let engine = EngineApi {
                 client: Arc::new(MockEthProvider::default()),
                 chain_spec: chain_spec.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -825,7 +1326,9 @@ mod tests {
             let engine = EngineApi {
                 client: client.clone(),
                 chain_spec: chain_spec.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -889,7 +1392,9 @@ mod tests {
             let engine = EngineApi {
                 client: client.clone(),
                 chain_spec: chain_spec.clone(),
-                local_store: Default::default(),
+                payload_builder: None,
+                pending_payloads: Vec::new(),
+                tree: None,
                 message_rx: UnboundedReceiverStream::new(msg_rx),
                 forkchoice_state_tx: tip_tx,
             };
@@ -61,9 +61,28 @@ pub enum EngineApiError {
         /// Consensus terminal block hash.
         consensus: H256,
     },
+    /// The blob versioned hashes of the payload don't match the expected ones.
+    #[error("Invalid blob versioned hashes")]
+    VersionedHashesMismatch,
     /// Forkchoice zero hash head received.
     #[error("Received zero hash as forkchoice head")]
     ForkchoiceEmptyHead,
+    /// Withdrawals were provided to a V1 method.
+    #[error("Withdrawals not supported in V1")]
+    WithdrawalsNotSupportedInV1,
+    /// No withdrawals were provided after the Shanghai hardfork.
+    #[error("No withdrawals post-Shanghai")]
+    NoWithdrawalsPostShanghai,
+    /// Withdrawals were provided before the Shanghai hardfork.
+    #[error("Withdrawals pre-Shanghai")]
+    HasWithdrawalsPreShanghai,
+    /// The payload attributes of a forkchoice update are not valid. The forkchoice state was
+    /// applied nonetheless.
+    #[error("Invalid payload attributes: {0}")]
+    InvalidPayloadAttributes(Box<EngineApiError>),
+    /// The method version does not match the active fork at the timestamp of the payload.
+    #[error("Unsupported fork")]
+    UnsupportedFork,
     /// Chain spec merge terminal total difficulty is not set
     #[error("The merge terminal total difficulty is not known")]
     UnknownMergeTerminalTotalDifficulty,
@@ -73,4 +92,7 @@ pub enum EngineApiError {
     /// API encountered an internal error.
     #[error(transparent)]
     Internal(#[from] reth_interfaces::Error),
+    /// The blockchain tree failed to insert a payload or to make a block canonical.
+    #[error(transparent)]
+    BlockchainTree(#[from] reth_executor::blockchain_tree::BlockchainTreeError),
 }
@@ -17,6 +17,6 @@ pub mod message;
 /// Engine API error.
 pub mod error;
 
-pub use engine_api::{EngineApi, EngineApiSender};
+pub use engine_api::{EngineApi, EngineApiSender, CAPABILITIES};
 pub use error::{EngineApiError, EngineApiResult};
-pub use message::EngineApiMessage;
+pub use message::{EngineApiMessage, EngineApiMessageVersion};
@@ -1,19 +1,28 @@
 use crate::EngineApiSender;
 use reth_interfaces::consensus::ForkchoiceState;
-use reth_primitives::H64;
+use reth_primitives::{H256, H64};
 use reth_rpc_types::engine::{
-    ExecutionPayload, ForkchoiceUpdated, PayloadAttributes, PayloadStatus, TransitionConfiguration,
+    ExecutionPayload, ExecutionPayloadEnvelope, ForkchoiceUpdated, PayloadAttributes,
+    PayloadStatus, TransitionConfiguration,
 };
 
 /// Message type for communicating with [`EngineApi`][crate::EngineApi].
 #[derive(Debug)]
 pub enum EngineApiMessage {
-    /// New payload message
-    NewPayload(ExecutionPayload, EngineApiSender<PayloadStatus>),
+    /// New payload message, with the expected blob versioned hashes and the parent beacon block
+    /// root of V3
+    NewPayload(
+        EngineApiMessageVersion,
+        ExecutionPayload,
+        Option<Vec<H256>>,
+        Option<H256>,
+        EngineApiSender<PayloadStatus>,
+    ),
     /// Get payload message
-    GetPayload(H64, EngineApiSender<ExecutionPayload>),
+    GetPayload(EngineApiMessageVersion, H64, EngineApiSender<ExecutionPayloadEnvelope>),
     /// Forkchoice updated message
     ForkchoiceUpdated(
+        EngineApiMessageVersion,
         ForkchoiceState,
         Option<PayloadAttributes>,
         EngineApiSender<ForkchoiceUpdated>,
@@ -24,3 +33,17 @@ pub enum EngineApiMessage {
         EngineApiSender<TransitionConfiguration>,
     ),
 }
+
+/// The version of the Engine API method a message originates from.
+///
+/// The versions differ in the fields they accept: V2 adds withdrawals (Shanghai) and V3 adds the
+/// blob fields (Cancun).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EngineApiMessageVersion {
+    /// Version 1
+    V1,
+    /// Version 2
+    V2,
+    /// Version 3
+    V3,
+}
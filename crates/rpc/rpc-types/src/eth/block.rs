@@ -2,7 +2,7 @@
 use crate::Transaction;
 use reth_primitives::{
     Address, Block as PrimitiveBlock, Bloom, Bytes, Header as RethHeader, Withdrawal, H256, H64,
-    U256,
+    U256, U64,
 };
 use reth_rlp::Encodable;
 use serde::{ser::Error, Deserialize, Serialize, Serializer};
@@ -49,58 +49,49 @@ pub struct Block {
 }
 
 impl Block {
+    /// Converts the [primitive block](reth_primitives::Block) to a block response, with either
+    /// full transactions or only their hashes, using the total difficulty to populate its field in
+    /// the rpc response.
+    pub fn from_block(
+        block: PrimitiveBlock,
+        total_difficulty: U256,
+        full: bool,
+    ) -> Result<Self, BlockError> {
+        if full {
+            Self::from_block_full(block, total_difficulty)
+        } else {
+            Ok(Self::from_block_hashes(block, total_difficulty))
+        }
+    }
+
+    /// Create a new block response from a [primitive block](reth_primitives::Block), using the
+    /// total difficulty to populate its field in the rpc response.
+    ///
+    /// This will populate the `transactions` field with only the hashes of the transactions in the
+    /// block: [BlockTransactions::Hashes]
+    pub fn from_block_hashes(block: PrimitiveBlock, total_difficulty: U256) -> Self {
+        let block_hash = block.header.hash_slow();
+        let transactions = block.body.iter().map(|tx| tx.hash).collect();
+
+        Self::from_block_with_transactions(
+            block_hash,
+            block,
+            total_difficulty,
+            BlockTransactions::Hashes(transactions),
+        )
+    }
+
     /// Create a new block response from a [primitive block](reth_primitives::Block), using the
     /// total difficulty to populate its field in the rpc response.
+    ///
+    /// This will populate the `transactions` field with the _full_ transactions of the block:
+    /// [BlockTransactions::Full]
     pub fn from_block_full(
         block: PrimitiveBlock,
         total_difficulty: U256,
     ) -> Result<Self, BlockError> {
         let block_hash = block.header.hash_slow();
-        let header_length = block.header.length();
-        let block_length = block.length();
-        let uncles = block.ommers.into_iter().map(|h| h.hash_slow()).collect();
-
-        let RethHeader {
-            parent_hash,
-            ommers_hash,
-            beneficiary,
-            state_root,
-            transactions_root,
-            receipts_root,
-            logs_bloom,
-            difficulty,
-            number,
-            gas_limit,
-            gas_used,
-            timestamp,
-            mix_hash,
-            nonce,
-            base_fee_per_gas,
-            extra_data,
-            withdrawals_root,
-        } = block.header;
-
-        let header = Header {
-            hash: Some(block_hash),
-            parent_hash,
-            uncles_hash: ommers_hash,
-            author: beneficiary,
-            miner: beneficiary,
-            state_root,
-            transactions_root,
-            receipts_root,
-            withdrawals_root,
-            number: Some(U256::from(number)),
-            gas_used: U256::from(gas_used),
-            gas_limit: U256::from(gas_limit),
-            extra_data,
-            logs_bloom,
-            timestamp: U256::from(timestamp),
-            difficulty,
-            mix_hash,
-            nonce: Some(nonce.to_be_bytes().into()),
-            size: Some(U256::from(header_length)),
-        };
+        let block_number = block.number;
 
         let mut transactions = Vec::with_capacity(block.body.len());
         for (idx, tx) in block.body.iter().enumerate() {
@@ -108,20 +99,39 @@ impl Block {
             transactions.push(Transaction::from_recovered_with_block_context(
                 signed_tx,
                 block_hash,
-                number,
+                block_number,
                 U256::from(idx),
             ))
         }
 
-        Ok(Self {
+        Ok(Self::from_block_with_transactions(
+            block_hash,
+            block,
+            total_difficulty,
+            BlockTransactions::Full(transactions),
+        ))
+    }
+
+    fn from_block_with_transactions(
+        block_hash: H256,
+        block: PrimitiveBlock,
+        total_difficulty: U256,
+        transactions: BlockTransactions,
+    ) -> Self {
+        let block_length = block.length();
+        let uncles = block.ommers.into_iter().map(|h| h.hash_slow()).collect();
+        let base_fee_per_gas = block.header.base_fee_per_gas;
+        let header = Header::from_primitive_with_hash(block.header, block_hash);
+
+        Self {
             header,
             uncles,
-            transactions: BlockTransactions::Full(transactions),
+            transactions,
             base_fee_per_gas: base_fee_per_gas.map(U256::from),
             total_difficulty,
             size: Some(U256::from(block_length)),
             withdrawals: block.withdrawals,
-        })
+        }
     }
 }
 
@@ -168,6 +178,69 @@ pub struct Header {
     pub nonce: Option<H64>,
     /// Size in bytes
     pub size: Option<U256>,
+    /// Blob gas used by the block, for post-EIP4844 blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U256>,
+    /// Excess blob gas before the block, for post-EIP4844 blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub excess_blob_gas: Option<U256>,
+    /// Root of the parent beacon block, for post-EIP4788 blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<H256>,
+}
+
+impl Header {
+    /// Converts the primitive header, with the given hash, to its rpc representation.
+    pub fn from_primitive_with_hash(header: RethHeader, hash: H256) -> Self {
+        let header_length = header.length();
+        let RethHeader {
+            parent_hash,
+            ommers_hash,
+            beneficiary,
+            state_root,
+            transactions_root,
+            receipts_root,
+            logs_bloom,
+            difficulty,
+            number,
+            gas_limit,
+            gas_used,
+            timestamp,
+            mix_hash,
+            nonce,
+            base_fee_per_gas: _,
+            blob_gas_used,
+            excess_blob_gas,
+            parent_beacon_block_root,
+            extra_data,
+            withdrawals_root,
+        } = header;
+
+        Self {
+            hash: Some(hash),
+            parent_hash,
+            uncles_hash: ommers_hash,
+            author: beneficiary,
+            miner: beneficiary,
+            state_root,
+            transactions_root,
+            receipts_root,
+            withdrawals_root,
+            number: Some(U256::from(number)),
+            gas_used: U256::from(gas_used),
+            gas_limit: U256::from(gas_limit),
+            extra_data,
+            logs_bloom,
+            timestamp: U256::from(timestamp),
+            difficulty,
+            mix_hash,
+            nonce: Some(nonce.to_be_bytes().into()),
+            size: Some(U256::from(header_length)),
+            blob_gas_used: blob_gas_used.map(U256::from),
+            excess_blob_gas: excess_blob_gas.map(U256::from),
+            parent_beacon_block_root,
+        }
+    }
 }
 
 /// A Block representation that allows to include additional fields
@@ -186,6 +259,12 @@ pub struct Rich<T> {
     pub extra_info: BTreeMap<String, serde_json::Value>,
 }
 
+impl<T> From<T> for Rich<T> {
+    fn from(inner: T) -> Self {
+        Self { inner, extra_info: Default::default() }
+    }
+}
+
 impl<T> Deref for Rich<T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
@@ -215,3 +294,23 @@ impl<T: Serialize> Serialize for Rich<T> {
         }
     }
 }
+
+/// Overrides of the fields of the block a call is executed in, as accepted by `eth_call`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct BlockOverrides {
+    /// Overrides the block number.
+    pub number: Option<U256>,
+    /// Overrides the difficulty of the block.
+    pub difficulty: Option<U256>,
+    /// Overrides the timestamp of the block.
+    pub time: Option<U64>,
+    /// Overrides the gas limit of the block.
+    pub gas_limit: Option<U64>,
+    /// Overrides the beneficiary of the block.
+    pub coinbase: Option<Address>,
+    /// Overrides the prevrandao value of the block.
+    pub random: Option<H256>,
+    /// Overrides the base fee of the block.
+    pub base_fee: Option<U256>,
+}
@@ -32,6 +32,14 @@ pub struct ExecutionPayload {
     /// See <https://github.com/ethereum/execution-apis/blob/6709c2a795b707202e93c4f2867fa0bf2640a84f/src/engine/shanghai.md#executionpayloadv2>
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub withdrawals: Option<Vec<Withdrawal>>,
+    /// Blob gas used by the block, enabled with V3
+    /// See <https://github.com/ethereum/execution-apis/blob/main/src/engine/cancun.md#executionpayloadv3>
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U64>,
+    /// Excess blob gas before the block, enabled with V3
+    /// See <https://github.com/ethereum/execution-apis/blob/main/src/engine/cancun.md#executionpayloadv3>
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excess_blob_gas: Option<U64>,
 }
 
 impl From<SealedBlock> for ExecutionPayload {
@@ -61,10 +69,23 @@ impl From<SealedBlock> for ExecutionPayload {
             block_hash: value.hash(),
             transactions,
             withdrawals: value.withdrawals,
+            blob_gas_used: value.blob_gas_used.map(Into::into),
+            excess_blob_gas: value.excess_blob_gas.map(Into::into),
         }
     }
 }
 
+/// This structure wraps an execution payload with the value of the block, the fees its fee
+/// recipient receives, as returned by `engine_getPayloadV2` and later.
+///
+/// See also: <https://github.com/ethereum/execution-apis/blob/6709c2a795b707202e93c4f2867fa0bf2640a84f/src/engine/shanghai.md#response-2>
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPayloadEnvelope {
+    pub execution_payload: ExecutionPayload,
+    pub block_value: U256,
+}
+
 /// This structure contains a body of an execution payload.
 ///
 /// See also: <https://github.com/ethereum/execution-apis/blob/6452a6b194d7db269bf1dbd087a267251d3cc7f8/src/engine/shanghai.md#executionpayloadbodyv1>
@@ -95,6 +116,10 @@ pub struct PayloadAttributes {
     /// See <https://github.com/ethereum/execution-apis/blob/6452a6b194d7db269bf1dbd087a267251d3cc7f8/src/engine/shanghai.md#payloadattributesv2>
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub withdrawals: Option<Vec<Withdrawal>>,
+    /// Root of the parent beacon block enabled with V3
+    /// See <https://github.com/ethereum/execution-apis/blob/fe8e13c288c592ec154ce25c534e26cb7ce0530d/src/engine/cancun.md#payloadattributesv3>
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<H256>,
 }
 
 /// This structure contains the result of processing a payload
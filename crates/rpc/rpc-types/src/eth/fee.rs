@@ -38,14 +38,17 @@ impl FeeHistoryCache {
 /// [FeeHistoryCache] item.
 #[derive(Clone, Debug)]
 pub struct FeeHistoryCacheItem {
-    /// Block hash (`None` if it wasn't the oldest block in `eth_feeHistory` response where
-    /// cache is populated)
-    pub hash: Option<H256>,
+    /// Block hash, to detect entries of blocks that were reorged out.
+    pub hash: H256,
     /// Block base fee per gas. Zero for pre-EIP-1559 blocks.
     pub base_fee_per_gas: U256,
+    /// Base fee per gas of the next block. Zero for pre-EIP-1559 blocks.
+    pub next_base_fee_per_gas: U256,
     /// Block gas used ratio. Calculated as the ratio of `gasUsed` and `gasLimit`.
     pub gas_used_ratio: f64,
-    /// An (optional) array of effective priority fee per gas data points for a
-    /// block. All zeroes are returned if the block is empty.
-    pub reward: Option<Vec<U256>>,
+    /// The gas used by the block.
+    pub gas_used: u64,
+    /// The effective priority fees per gas paid by the transactions of the block, with the gas
+    /// each transaction used, sorted by the fee.
+    pub priority_fees: Vec<(U256, u64)>,
 }
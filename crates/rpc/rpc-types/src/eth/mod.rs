@@ -9,9 +9,11 @@ mod filter;
 mod index;
 mod log;
 pub mod pubsub;
+mod state;
 mod syncing;
 pub mod trace;
 mod transaction;
+pub mod txpool;
 mod work;
 
 pub use account::*;
@@ -21,6 +23,7 @@ pub use fee::{FeeHistory, FeeHistoryCache, FeeHistoryCacheItem};
 pub use filter::*;
 pub use index::Index;
 pub use log::Log;
+pub use state::{AccountOverride, StateOverride};
 pub use syncing::*;
 pub use transaction::*;
 pub use work::Work;
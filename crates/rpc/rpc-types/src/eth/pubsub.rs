@@ -1,6 +1,6 @@
 //! Ethereum types for pub-sub
 
-use crate::{Log, RichHeader};
+use crate::{Log, RichHeader, Transaction};
 use reth_primitives::{rpc::Filter, H256};
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 
@@ -14,6 +14,8 @@ pub enum SubscriptionResult {
     Log(Box<Log>),
     /// Transaction hash
     TransactionHash(H256),
+    /// Full transaction
+    FullTransaction(Box<Transaction>),
     /// SyncStatus
     SyncState(PubSubSyncStatus),
 }
@@ -49,6 +51,7 @@ impl Serialize for SubscriptionResult {
             SubscriptionResult::Header(ref header) => header.serialize(serializer),
             SubscriptionResult::Log(ref log) => log.serialize(serializer),
             SubscriptionResult::TransactionHash(ref hash) => hash.serialize(serializer),
+            SubscriptionResult::FullTransaction(ref tx) => tx.serialize(serializer),
             SubscriptionResult::SyncState(ref sync) => sync.serialize(serializer),
         }
     }
@@ -97,6 +100,8 @@ pub enum Params {
     None,
     /// Log parameters.
     Logs(Box<Filter>),
+    /// Boolean parameter, whether `newPendingTransactions` returns full transactions.
+    Bool(bool),
 }
 
 impl Serialize for Params {
@@ -107,6 +112,7 @@ impl Serialize for Params {
         match self {
             Params::None => (&[] as &[serde_json::Value]).serialize(serializer),
             Params::Logs(logs) => logs.serialize(serializer),
+            Params::Bool(full) => full.serialize(serializer),
         }
     }
 }
@@ -122,6 +128,10 @@ impl<'a> Deserialize<'a> for Params {
             return Ok(Params::None)
         }
 
+        if let Some(full) = v.as_bool() {
+            return Ok(Params::Bool(full))
+        }
+
         serde_json::from_value(v)
             .map(|f| Params::Logs(Box::new(f)))
             .map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {e}")))
@@ -0,0 +1,54 @@
+//! Types for overriding the state a call is executed on.
+
+use reth_primitives::{Address, Bytes, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Overrides of the state of accounts, keyed by their address, as accepted by `eth_call`.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Overrides of the state of an account.
+///
+/// `state` replaces the entire storage of the account, while `state_diff` only replaces the given
+/// slots, so at most one of them can be set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct AccountOverride {
+    /// Overrides the nonce of the account.
+    pub nonce: Option<U64>,
+    /// Overrides the code of the account.
+    pub code: Option<Bytes>,
+    /// Overrides the balance of the account.
+    pub balance: Option<U256>,
+    /// Replaces the storage of the account with the given slots.
+    pub state: Option<HashMap<H256, H256>>,
+    /// Overrides the given storage slots of the account.
+    pub state_diff: Option<HashMap<H256, H256>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_state_override() {
+        let s = r#"{
+            "0x0000000000000000000000000000000000000124": {
+                "code": "0x6080604052348015600e575f80fd5b50600436106026575f3560e01c80632096525514602a575b5f80fd5b"
+            },
+            "0x1b5212AF6b76113afD94cD2B5a78a73B7d7A8222": {
+                "balance": "0x39726378b58c400000",
+                "stateDiff": {}
+            }
+        }"#;
+        let state_override: StateOverride = serde_json::from_str(s).unwrap();
+        let code = &state_override[&Address::from_low_u64_be(0x124)];
+        assert!(code.code.is_some());
+        assert!(code.balance.is_none());
+        let account = state_override
+            .get(&"0x1b5212AF6b76113afD94cD2B5a78a73B7d7A8222".parse().unwrap())
+            .unwrap();
+        assert!(account.state_diff.is_some());
+        assert!(account.state.is_none());
+    }
+}
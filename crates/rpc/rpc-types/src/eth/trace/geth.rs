@@ -0,0 +1,260 @@
+//! Types for the geth-style `debug_trace*` methods.
+//!
+//! See <https://geth.ethereum.org/docs/interacting-with-geth/rpc/ns-debug>
+
+use reth_primitives::{Address, Bytes, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Options for the `debug_trace*` methods.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethDebugTracingOptions {
+    /// Options of the default struct logger.
+    #[serde(flatten)]
+    pub config: GethDefaultTracingOptions,
+    /// The built-in tracer to use instead of the struct logger.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracer: Option<GethDebugBuiltInTracerType>,
+    /// Options of the tracer, e.g. [CallConfig] of the `callTracer`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracer_config: Option<serde_json::Value>,
+    /// Timeout of the tracing, e.g. `5s`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+}
+
+/// Options of the default struct logger.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethDefaultTracingOptions {
+    /// Whether the memory is captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_memory: Option<bool>,
+    /// Whether the stack is omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_stack: Option<bool>,
+    /// Whether the storage is omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_storage: Option<bool>,
+    /// Whether the return data is captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_return_data: Option<bool>,
+    /// The maximum number of logs to capture, zero for no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+/// The built-in tracers.
+///
+/// JavaScript tracers are not supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GethDebugBuiltInTracerType {
+    /// Records the tree of calls, see [CallFrame].
+    #[serde(rename = "callTracer")]
+    CallTracer,
+    /// Records the state the transaction accessed, see [PreStateFrame].
+    #[serde(rename = "prestateTracer")]
+    PreStateTracer,
+    /// Counts the function selectors of the calls, see [FourByteFrame].
+    #[serde(rename = "4byteTracer")]
+    FourByteTracer,
+    /// Records nothing.
+    #[serde(rename = "noopTracer")]
+    NoopTracer,
+}
+
+/// Options of the `callTracer`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallConfig {
+    /// Whether only the top-level call is recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub only_top_call: Option<bool>,
+    /// Whether the logs emitted by the calls are recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub with_log: Option<bool>,
+}
+
+/// Options of the `prestateTracer`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreStateConfig {
+    /// Whether the state before and after the transaction is returned, instead of only the state
+    /// before.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_mode: Option<bool>,
+}
+
+/// The trace of a transaction, depending on the tracer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GethTraceFrame {
+    /// The trace of the default struct logger.
+    Default(DefaultFrame),
+    /// The trace of the `callTracer`.
+    CallTracer(CallFrame),
+    /// The trace of the `prestateTracer`.
+    PreStateTracer(PreStateFrame),
+    /// The trace of the `4byteTracer`.
+    FourByteTracer(FourByteFrame),
+    /// The trace of the `noopTracer`.
+    NoopTracer(NoopFrame),
+}
+
+/// The trace of a transaction of a block that was traced.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TraceResult {
+    /// The transaction was traced.
+    Success {
+        /// The trace of the transaction.
+        result: GethTraceFrame,
+    },
+    /// The transaction could not be traced.
+    Error {
+        /// Why the transaction could not be traced.
+        error: String,
+    },
+}
+
+/// The trace of the default struct logger.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultFrame {
+    /// Whether the transaction failed.
+    pub failed: bool,
+    /// The gas used by the transaction.
+    pub gas: u64,
+    /// The output of the transaction.
+    pub return_value: Bytes,
+    /// The executed instructions.
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// An executed instruction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    /// The program counter.
+    pub pc: u64,
+    /// The name of the opcode.
+    pub op: String,
+    /// The gas remaining before the instruction.
+    pub gas: u64,
+    /// The gas cost of the instruction.
+    pub gas_cost: u64,
+    /// The call depth.
+    pub depth: u64,
+    /// The error of the instruction, if it failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The stack before the instruction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<Vec<U256>>,
+    /// The memory before the instruction, in words of 32 bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Vec<String>>,
+    /// The return data of the last call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<Bytes>,
+    /// The storage slots of the contract that were read or written so far, recorded for
+    /// `SLOAD` and `SSTORE` instructions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// A call or create, with the calls and creates it made.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    /// The type of the call, e.g. `CALL` or `CREATE2`.
+    #[serde(rename = "type")]
+    pub typ: String,
+    /// The caller.
+    pub from: Address,
+    /// The callee, or the created contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// The transferred value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// The gas limit of the call.
+    pub gas: U256,
+    /// The gas used by the call.
+    pub gas_used: U256,
+    /// The input of the call, or the init code.
+    pub input: Bytes,
+    /// The output of the call, or the code of the created contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Bytes>,
+    /// The error of the call, if it failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The calls and creates made by this call.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<CallFrame>,
+    /// The logs emitted by this call, if requested with [CallConfig::with_log].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<CallLogFrame>,
+}
+
+/// A log emitted by a call.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallLogFrame {
+    /// The address that emitted the log.
+    pub address: Address,
+    /// The topics of the log.
+    pub topics: Vec<H256>,
+    /// The data of the log.
+    pub data: Bytes,
+}
+
+/// The trace of the `prestateTracer`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PreStateFrame {
+    /// The state of the accounts the transaction accessed, before it was executed.
+    Default(PreStateMode),
+    /// The state of the accounts the transaction changed, before and after it was executed.
+    Diff(DiffMode),
+}
+
+/// The state of the accounts the transaction accessed, before it was executed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreStateMode(pub BTreeMap<Address, AccountState>);
+
+/// The state of the accounts the transaction changed, before and after it was executed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffMode {
+    /// The state before the transaction, of the fields that changed.
+    pub pre: BTreeMap<Address, AccountState>,
+    /// The state after the transaction, of the fields that changed.
+    pub post: BTreeMap<Address, AccountState>,
+}
+
+/// The state of an account.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountState {
+    /// The balance of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// The code of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// The nonce of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// The storage slots of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// The number of calls per function selector and input length, keyed by
+/// `<selector>-<input length without the selector>`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FourByteFrame(pub BTreeMap<String, u64>);
+
+/// The empty trace of the `noopTracer`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoopFrame {}
@@ -1,4 +1,5 @@
 //! Types for tracing
 
 pub mod filter;
+pub mod geth;
 pub mod parity;
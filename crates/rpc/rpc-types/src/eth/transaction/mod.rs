@@ -10,8 +10,8 @@ pub use typed::*;
 
 use reth_primitives::{
     rpc::transaction::eip2930::AccessListItem, Address, BlockNumber, Bytes,
-    Transaction as PrimitiveTransaction, TransactionKind, TransactionSignedEcRecovered, TxType,
-    H256, U128, U256, U64,
+    Transaction as PrimitiveTransaction, TransactionKind, TransactionSignedEcRecovered, TxEip1559,
+    TxEip4844, TxType, H256, U128, U256, U64,
 };
 use serde::{Deserialize, Serialize};
 
@@ -46,6 +46,12 @@ pub struct Transaction {
     /// The miner's tip.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_priority_fee_per_gas: Option<U128>,
+    /// Max fee per blob gas the user is willing to pay, for EIP-4844 transactions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_blob_gas: Option<U128>,
+    /// Versioned hashes of the blobs, for EIP-4844 transactions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_versioned_hashes: Option<Vec<H256>>,
     /// Data
     pub input: Bytes,
     /// All _flattened_ fields of the transaction signature.
@@ -74,7 +80,7 @@ impl Transaction {
     ///
     /// The block hash, number, and tx index fields should be from the original block where the
     /// transaction was mined.
-    pub(crate) fn from_recovered_with_block_context(
+    pub fn from_recovered_with_block_context(
         tx: TransactionSignedEcRecovered,
         block_hash: H256,
         block_number: BlockNumber,
@@ -89,7 +95,7 @@ impl Transaction {
 
     /// Create a new rpc transaction result for a pending signed transaction, setting block
     /// environment related fields to `None`.
-    pub(crate) fn from_recovered(tx: TransactionSignedEcRecovered) -> Self {
+    pub fn from_recovered(tx: TransactionSignedEcRecovered) -> Self {
         let signer = tx.signer();
         let signed_tx = tx.into_signed();
 
@@ -101,7 +107,9 @@ impl Transaction {
         let (gas_price, max_fee_per_gas) = match signed_tx.tx_type() {
             TxType::Legacy => (Some(U128::from(signed_tx.max_fee_per_gas())), None),
             TxType::EIP2930 => (None, Some(U128::from(signed_tx.max_fee_per_gas()))),
-            TxType::EIP1559 => (None, Some(U128::from(signed_tx.max_fee_per_gas()))),
+            TxType::EIP1559 | TxType::EIP4844 => {
+                (None, Some(U128::from(signed_tx.max_fee_per_gas())))
+            }
         };
 
         let chain_id = signed_tx.chain_id().map(U64::from);
@@ -117,8 +125,9 @@ impl Transaction {
                     })
                     .collect(),
             ),
-            PrimitiveTransaction::Eip1559(tx) => Some(
-                tx.access_list
+            PrimitiveTransaction::Eip1559(TxEip1559 { access_list, .. }) |
+            PrimitiveTransaction::Eip4844(TxEip4844 { access_list, .. }) => Some(
+                access_list
                     .0
                     .iter()
                     .map(|item| AccessListItem {
@@ -141,6 +150,8 @@ impl Transaction {
             gas_price,
             max_fee_per_gas,
             max_priority_fee_per_gas: signed_tx.max_priority_fee_per_gas().map(U128::from),
+            max_fee_per_blob_gas: signed_tx.max_fee_per_blob_gas().map(U128::from),
+            blob_versioned_hashes: signed_tx.blob_versioned_hashes().map(<[H256]>::to_vec),
             signature: Some(Signature::from_primitive_signature(
                 signed_tx.signature().clone(),
                 signed_tx.chain_id(),
@@ -0,0 +1,143 @@
+//! Types for the `txpool` namespace.
+//!
+//! See <https://geth.ethereum.org/docs/interacting-with-geth/rpc/ns-txpool>
+
+use crate::Transaction;
+use reth_primitives::{Address, U256, U64};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// The transactions of the pool, keyed by their nonce, per sender.
+pub type TxpoolByAddress<T> = BTreeMap<Address, TxpoolByNonce<T>>;
+
+/// The transactions of a sender in the pool, keyed by their nonce in decimal.
+pub type TxpoolByNonce<T> = BTreeMap<String, T>;
+
+/// The number of transactions in the pool, as returned by `txpool_status`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolStatus {
+    /// The number of transactions that are ready to be included in a block.
+    pub pending: U64,
+    /// The number of transactions that are not ready to be included in a block yet.
+    pub queued: U64,
+}
+
+/// The transactions of the pool, as returned by `txpool_content`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolContent {
+    /// The transactions that are ready to be included in a block.
+    pub pending: TxpoolByAddress<Transaction>,
+    /// The transactions that are not ready to be included in a block yet.
+    pub queued: TxpoolByAddress<Transaction>,
+}
+
+/// The transactions of a sender in the pool, as returned by `txpool_contentFrom`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolContentFrom {
+    /// The transactions that are ready to be included in a block.
+    pub pending: TxpoolByNonce<Transaction>,
+    /// The transactions that are not ready to be included in a block yet.
+    pub queued: TxpoolByNonce<Transaction>,
+}
+
+/// Summaries of the transactions of the pool, as returned by `txpool_inspect`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolInspect {
+    /// The transactions that are ready to be included in a block.
+    pub pending: TxpoolByAddress<TxpoolInspectSummary>,
+    /// The transactions that are not ready to be included in a block yet.
+    pub queued: TxpoolByAddress<TxpoolInspectSummary>,
+}
+
+/// The summary of a transaction in the pool.
+///
+/// This is serialized like geth does, e.g.
+/// `0x0000000000000000000000000000000000000001: 1 wei + 21000 gas × 2 wei`, or
+/// `contract creation: 0 wei + 53000 gas × 2 wei`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxpoolInspectSummary {
+    /// The recipient, `None` for contract creations.
+    pub to: Option<Address>,
+    /// The transferred value.
+    pub value: U256,
+    /// The gas limit.
+    pub gas: u64,
+    /// The gas price, or the max fee per gas of EIP-1559 transactions.
+    pub gas_price: u128,
+}
+
+impl fmt::Display for TxpoolInspectSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to {
+            Some(to) => write!(f, "{to:?}")?,
+            None => f.write_str("contract creation")?,
+        }
+        write!(f, ": {} wei + {} gas × {} wei", self.value, self.gas, self.gas_price)
+    }
+}
+
+impl FromStr for TxpoolInspectSummary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid transaction summary: {s}");
+
+        let (to, rest) = s.split_once(": ").ok_or_else(invalid)?;
+        let to = match to {
+            "contract creation" => None,
+            to => Some(to.parse().map_err(|_| invalid())?),
+        };
+        let (value, rest) = rest.split_once(" wei + ").ok_or_else(invalid)?;
+        let (gas, rest) = rest.split_once(" gas × ").ok_or_else(invalid)?;
+        let gas_price = rest.strip_suffix(" wei").ok_or_else(invalid)?;
+
+        Ok(Self {
+            to,
+            value: value.parse().map_err(|_| invalid())?,
+            gas: gas.parse().map_err(|_| invalid())?,
+            gas_price: gas_price.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl Serialize for TxpoolInspectSummary {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TxpoolInspectSummary {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_inspect_summary() {
+        let summary = TxpoolInspectSummary {
+            to: Some(Address::from_low_u64_be(1)),
+            value: U256::from(1),
+            gas: 21000,
+            gas_price: 2,
+        };
+        let s = serde_json::to_string(&summary).unwrap();
+        assert_eq!(s, "\"0x0000000000000000000000000000000000000001: 1 wei + 21000 gas × 2 wei\"");
+        assert_eq!(serde_json::from_str::<TxpoolInspectSummary>(&s).unwrap(), summary);
+
+        let creation = TxpoolInspectSummary { to: None, ..summary };
+        let s = serde_json::to_string(&creation).unwrap();
+        assert_eq!(s, "\"contract creation: 1 wei + 21000 gas × 2 wei\"");
+        assert_eq!(serde_json::from_str::<TxpoolInspectSummary>(&s).unwrap(), creation);
+    }
+}
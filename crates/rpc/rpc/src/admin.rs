@@ -4,7 +4,7 @@ use jsonrpsee::core::RpcResult;
 use reth_network_api::{NetworkInfo, PeerKind, Peers};
 use reth_primitives::NodeRecord;
 use reth_rpc_api::AdminApiServer;
-use reth_rpc_types::NodeInfo;
+use reth_rpc_types::{EthProtocolInfo, NodeInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo};
 
 /// `admin` API implementation.
 ///
@@ -26,6 +26,37 @@ impl<N> AdminApiServer for AdminApi<N>
 where
     N: NetworkInfo + Peers + 'static,
 {
+    async fn peers(&self) -> RpcResult<Vec<PeerInfo>> {
+        let local_address = self.network.local_addr().to_string();
+        let peers = self.network.get_peers().await.to_rpc_result()?;
+
+        Ok(peers
+            .into_iter()
+            .map(|peer| PeerInfo {
+                id: Some(format!("{:x}", peer.remote_id)),
+                name: peer.client_version,
+                caps: peer
+                    .capabilities
+                    .capabilities()
+                    .iter()
+                    .map(|cap| format!("{}/{}", cap.name, cap.version))
+                    .collect(),
+                network: PeerNetworkInfo {
+                    remote_address: peer.remote_addr.to_string(),
+                    local_address: local_address.clone(),
+                },
+                protocols: PeerProtocolsInfo {
+                    eth: Some(EthProtocolInfo {
+                        version: peer.status.version as u32,
+                        difficulty: Some(peer.status.total_difficulty),
+                        head: format!("{:?}", peer.status.blockhash),
+                    }),
+                    pip: None,
+                },
+            })
+            .collect())
+    }
+
     fn add_peer(&self, record: NodeRecord) -> RpcResult<bool> {
         self.network.add_peer(record.id, record.tcp_addr());
         Ok(true)
@@ -0,0 +1,50 @@
+//! Limits the number of tracing requests that are served concurrently.
+
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// The default maximum number of tracing requests that are served concurrently.
+pub const DEFAULT_MAX_TRACING_REQUESTS: usize = 25;
+
+/// Limits the number of `debug_trace*` and `trace_*` requests that are served concurrently.
+///
+/// Tracing re-executes transactions, so it's much more expensive than other requests. Requests
+/// beyond the limit wait until a running one completes.
+///
+/// The guard is cheap to clone, clones share the limit.
+#[derive(Debug, Clone)]
+pub struct TracingCallGuard(Arc<Semaphore>);
+
+impl TracingCallGuard {
+    /// Creates a guard that allows the given number of concurrent tracing requests.
+    pub fn new(max_tracing_requests: usize) -> Self {
+        Self(Arc::new(Semaphore::new(max_tracing_requests)))
+    }
+
+    /// Waits until a tracing request can be served.
+    ///
+    /// The request counts against the limit until the returned permit is dropped.
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.0.clone().acquire_owned().await.expect("semaphore is never closed")
+    }
+}
+
+impl Default for TracingCallGuard {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_TRACING_REQUESTS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn limits_concurrent_requests() {
+        let guard = TracingCallGuard::new(1);
+        let permit = guard.acquire().await;
+        assert!(guard.0.try_acquire().is_err());
+        drop(permit);
+        assert!(guard.clone().0.try_acquire().is_ok());
+    }
+}
@@ -0,0 +1,416 @@
+use crate::{
+    eth::error::{EthApiError, EthResult},
+    result::internal_rpc_err,
+    EthApi, TracingCallGuard,
+};
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult as Result;
+use reth_executor::revm_wrap::{fill_tx_env, SubState};
+use reth_primitives::{
+    rpc::{BlockId, BlockNumber},
+    Block, Bytes, H256, KECCAK_EMPTY,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProvider, StateProviderFactory,
+    TransactionsProvider,
+};
+use reth_rpc_api::DebugApiServer;
+use reth_rpc_types::{
+    trace::geth::{
+        AccountState, CallConfig, DefaultFrame, DiffMode, GethDebugBuiltInTracerType,
+        GethDebugTracingOptions, GethTraceFrame, NoopFrame, PreStateConfig, PreStateFrame,
+        PreStateMode, TraceResult,
+    },
+    CallRequest, RichBlock,
+};
+use revm::{
+    db::DatabaseCommit,
+    primitives::{AccountInfo, Env, ExecutionResult, Output, ResultAndState, State as EvmState},
+    Database, Inspector, EVM,
+};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use tracers::{to_h256, CallTracer, FourByteTracer, StructLogger};
+
+mod tracers;
+
+/// `debug` API implementation.
+///
+/// This type provides the functionality for handling `debug` related requests.
+///
+/// Transactions are traced by re-executing them on top of the historical state they were
+/// executed on.
+pub struct DebugApi<Client, Pool, Network> {
+    /// The implementation of `eth` API
+    eth: EthApi<Client, Pool, Network>,
+    /// Limits the number of tracing requests that are served concurrently
+    tracing_call_guard: TracingCallGuard,
+}
+
+// === impl DebugApi ===
+
+impl<Client, Pool, Network> DebugApi<Client, Pool, Network> {
+    /// Create a new instance of the [DebugApi]
+    pub fn new(eth: EthApi<Client, Pool, Network>, tracing_call_guard: TracingCallGuard) -> Self {
+        Self { eth, tracing_call_guard }
+    }
+}
+
+impl<Client, Pool, Network> DebugApi<Client, Pool, Network>
+where
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + TransactionsProvider
+        + ReceiptProvider
+        + 'static,
+{
+    /// Re-executes the transactions of the block and traces them.
+    fn trace_block(
+        &self,
+        block: Block,
+        opts: GethDebugTracingOptions,
+    ) -> EthResult<Vec<TraceResult>> {
+        let (mut env, mut db) = self.eth.prepare_block_replay(&block.header)?;
+
+        let mut results = Vec::with_capacity(block.body.len());
+        for tx in &block.body {
+            let sender = tx.recover_signer().ok_or(EthApiError::InvalidTransactionSignature)?;
+            fill_tx_env(&mut env.tx, tx, sender);
+
+            // Later transactions of the block are executed on top of the changes of this one.
+            let (frame, state) = trace_transaction(&mut db, env.clone(), &opts)?;
+            db.commit(state);
+
+            results.push(TraceResult::Success { result: frame });
+        }
+        Ok(results)
+    }
+
+    /// Re-executes the transactions of the block with the given id and traces them.
+    fn trace_block_by_id(
+        &self,
+        block_id: BlockId,
+        opts: GethDebugTracingOptions,
+    ) -> EthResult<Vec<TraceResult>> {
+        let block = self.eth.client().block(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        self.trace_block(block, opts)
+    }
+
+    /// Re-executes the mined transaction with the given hash and traces it.
+    ///
+    /// The transactions before it in its block are executed first, without tracing.
+    fn trace_transaction_by_hash(
+        &self,
+        hash: H256,
+        opts: GethDebugTracingOptions,
+    ) -> EthResult<GethTraceFrame> {
+        let (block, index) =
+            self.eth.transaction_location(hash)?.ok_or(EthApiError::TransactionNotFound)?;
+        let (mut env, mut db) = self.eth.prepare_block_replay(&block.header)?;
+
+        for tx in &block.body[..index] {
+            let sender = tx.recover_signer().ok_or(EthApiError::InvalidTransactionSignature)?;
+            fill_tx_env(&mut env.tx, tx, sender);
+            let ResultAndState { state, .. } = transact(&mut db, env.clone())?;
+            db.commit(state);
+        }
+
+        let tx = &block.body[index];
+        let sender = tx.recover_signer().ok_or(EthApiError::InvalidTransactionSignature)?;
+        fill_tx_env(&mut env.tx, tx, sender);
+        let (frame, _) = trace_transaction(&mut db, env, &opts)?;
+        Ok(frame)
+    }
+
+    /// Executes the request on top of the state of the given block and traces it.
+    fn trace_call(
+        &self,
+        request: CallRequest,
+        block_id: Option<BlockId>,
+        opts: GethDebugTracingOptions,
+    ) -> EthResult<GethTraceFrame> {
+        let (env, mut db) = self.eth.prepare_call(request, block_id)?;
+        let (frame, _) = trace_transaction(&mut db, env, &opts)?;
+        Ok(frame)
+    }
+}
+
+#[async_trait]
+impl<Client, Pool, Network> DebugApiServer for DebugApi<Client, Pool, Network>
+where
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + TransactionsProvider
+        + ReceiptProvider
+        + 'static,
+    Pool: Send + Sync + 'static,
+    Network: Send + Sync + 'static,
+{
+    async fn raw_header(&self, _block_id: BlockId) -> Result<Bytes> {
+        Err(internal_rpc_err("unimplemented"))
+    }
+
+    async fn raw_block(&self, _block_id: BlockId) -> Result<Bytes> {
+        Err(internal_rpc_err("unimplemented"))
+    }
+
+    /// Returns the bytes of the transaction for the given hash.
+    async fn raw_transaction(&self, _hash: H256) -> Result<Bytes> {
+        Err(internal_rpc_err("unimplemented"))
+    }
+
+    async fn raw_receipts(&self, _block_id: BlockId) -> Result<Vec<Bytes>> {
+        Err(internal_rpc_err("unimplemented"))
+    }
+
+    async fn bad_blocks(&self) -> Result<Vec<RichBlock>> {
+        Err(internal_rpc_err("unimplemented"))
+    }
+
+    /// Handler for `debug_traceBlockByNumber`
+    async fn debug_trace_block_by_number(
+        &self,
+        block: BlockNumber,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<Vec<TraceResult>> {
+        let _permit = self.tracing_call_guard.acquire().await;
+        Ok(self.trace_block_by_id(BlockId::Number(block), opts.unwrap_or_default())?)
+    }
+
+    /// Handler for `debug_traceBlockByHash`
+    async fn debug_trace_block_by_hash(
+        &self,
+        block: H256,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<Vec<TraceResult>> {
+        let _permit = self.tracing_call_guard.acquire().await;
+        Ok(self.trace_block_by_id(BlockId::Hash(block.0.into()), opts.unwrap_or_default())?)
+    }
+
+    /// Handler for `debug_traceTransaction`
+    async fn debug_trace_transaction(
+        &self,
+        tx_hash: H256,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<GethTraceFrame> {
+        let _permit = self.tracing_call_guard.acquire().await;
+        Ok(self.trace_transaction_by_hash(tx_hash, opts.unwrap_or_default())?)
+    }
+
+    /// Handler for `debug_traceCall`
+    async fn debug_trace_call(
+        &self,
+        request: CallRequest,
+        block_number: Option<BlockId>,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> Result<GethTraceFrame> {
+        let _permit = self.tracing_call_guard.acquire().await;
+        Ok(self.trace_call(request, block_number, opts.unwrap_or_default())?)
+    }
+}
+
+impl<Client, Pool, Network> std::fmt::Debug for DebugApi<Client, Pool, Network> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DebugApi").finish_non_exhaustive()
+    }
+}
+
+/// Executes the transaction of the environment with the tracer of the options, without committing
+/// its state changes.
+///
+/// Returns the trace and the state changes of the transaction.
+fn trace_transaction<S: StateProvider>(
+    db: &mut SubState<S>,
+    env: Env,
+    opts: &GethDebugTracingOptions,
+) -> EthResult<(GethTraceFrame, EvmState)> {
+    let Some(tracer) = opts.tracer else {
+        let mut logger = StructLogger::new(opts.config.clone());
+        let ResultAndState { result, state } = inspect(db, env, &mut logger)?;
+        let frame = DefaultFrame {
+            failed: !result.is_success(),
+            gas: gas_used(&result),
+            return_value: output(&result),
+            struct_logs: logger.into_logs(),
+        };
+        return Ok((GethTraceFrame::Default(frame), state))
+    };
+
+    match tracer {
+        GethDebugBuiltInTracerType::CallTracer => {
+            let mut tracer = CallTracer::new(tracer_config::<CallConfig>(opts)?);
+            let ResultAndState { state, .. } = inspect(db, env, &mut tracer)?;
+            Ok((GethTraceFrame::CallTracer(tracer.into_frame()), state))
+        }
+        GethDebugBuiltInTracerType::PreStateTracer => {
+            let config = tracer_config::<PreStateConfig>(opts)?;
+            let ResultAndState { state, .. } = transact(db, env)?;
+            let frame = prestate_frame(db, &state, config.diff_mode.unwrap_or_default())?;
+            Ok((GethTraceFrame::PreStateTracer(frame), state))
+        }
+        GethDebugBuiltInTracerType::FourByteTracer => {
+            let mut tracer = FourByteTracer::default();
+            let ResultAndState { state, .. } = inspect(db, env, &mut tracer)?;
+            Ok((GethTraceFrame::FourByteTracer(tracer.into_frame()), state))
+        }
+        GethDebugBuiltInTracerType::NoopTracer => {
+            let ResultAndState { state, .. } = transact(db, env)?;
+            Ok((GethTraceFrame::NoopTracer(NoopFrame::default()), state))
+        }
+    }
+}
+
+/// Returns the state before the transaction of the accounts it accessed, or in diff mode the state
+/// before and after the transaction of the accounts it changed.
+///
+/// The database must not have the state changes of the transaction committed yet.
+fn prestate_frame<S: StateProvider>(
+    db: &mut SubState<S>,
+    state: &EvmState,
+    diff_mode: bool,
+) -> EthResult<PreStateFrame> {
+    let mut pre = BTreeMap::new();
+    let mut post = BTreeMap::new();
+    for (address, account) in state {
+        let info = db.basic(*address)?.unwrap_or_default();
+        let code = account_code(db, &info)?;
+
+        if !diff_mode {
+            let storage = account
+                .storage
+                .iter()
+                .map(|(key, slot)| (to_h256(*key), to_h256(slot.original_value())))
+                .collect::<BTreeMap<_, _>>();
+            pre.insert(
+                *address,
+                AccountState {
+                    balance: Some(info.balance),
+                    code,
+                    nonce: Some(info.nonce),
+                    storage: (!storage.is_empty()).then_some(storage),
+                },
+            );
+            continue
+        }
+
+        let changed_slots = account
+            .storage
+            .iter()
+            .filter(|(_, slot)| slot.original_value() != slot.present_value())
+            .collect::<Vec<_>>();
+        let info_changed = account.is_destroyed ||
+            account.info.balance != info.balance ||
+            account.info.nonce != info.nonce ||
+            account.info.code_hash != info.code_hash;
+        if !info_changed && changed_slots.is_empty() {
+            continue
+        }
+
+        let pre_storage = changed_slots
+            .iter()
+            .map(|(key, slot)| (to_h256(**key), to_h256(slot.original_value())))
+            .collect::<BTreeMap<_, _>>();
+        pre.insert(
+            *address,
+            AccountState {
+                balance: Some(info.balance),
+                code,
+                nonce: Some(info.nonce),
+                storage: (!pre_storage.is_empty()).then_some(pre_storage),
+            },
+        );
+
+        // Destroyed accounts have no state after the transaction.
+        if account.is_destroyed {
+            continue
+        }
+        let post_storage = changed_slots
+            .iter()
+            .map(|(key, slot)| (to_h256(**key), to_h256(slot.present_value())))
+            .collect::<BTreeMap<_, _>>();
+        post.insert(
+            *address,
+            AccountState {
+                balance: (account.info.balance != info.balance).then_some(account.info.balance),
+                code: if account.info.code_hash != info.code_hash {
+                    account_code(db, &account.info)?
+                } else {
+                    None
+                },
+                nonce: (account.info.nonce != info.nonce).then_some(account.info.nonce),
+                storage: (!post_storage.is_empty()).then_some(post_storage),
+            },
+        );
+    }
+
+    Ok(if diff_mode {
+        PreStateFrame::Diff(DiffMode { pre, post })
+    } else {
+        PreStateFrame::Default(PreStateMode(pre))
+    })
+}
+
+/// Returns the code of the account, or `None` if it has no code.
+fn account_code<S: StateProvider>(
+    db: &mut SubState<S>,
+    info: &AccountInfo,
+) -> EthResult<Option<Bytes>> {
+    if info.code_hash == KECCAK_EMPTY {
+        return Ok(None)
+    }
+    let code = match &info.code {
+        Some(code) => code.original_bytes(),
+        None => db.code_by_hash(info.code_hash)?.original_bytes(),
+    };
+    Ok((!code.is_empty()).then(|| code.into()))
+}
+
+/// Parses the tracer config of the options, or returns the default config if there is none.
+fn tracer_config<T: DeserializeOwned + Default>(opts: &GethDebugTracingOptions) -> EthResult<T> {
+    match &opts.tracer_config {
+        Some(config) => serde_json::from_value(config.clone())
+            .map_err(|err| EthApiError::InvalidTracerConfig(err.to_string())),
+        None => Ok(T::default()),
+    }
+}
+
+/// Executes the transaction of the environment with the inspector, without committing its state
+/// changes.
+fn inspect<'a, S, I>(db: &'a mut SubState<S>, env: Env, inspector: I) -> EthResult<ResultAndState>
+where
+    S: StateProvider,
+    I: Inspector<&'a mut SubState<S>>,
+{
+    let mut evm = EVM::new();
+    evm.env = env;
+    evm.database(db);
+    evm.inspect(inspector).map_err(|err| EthApiError::Execution(format!("{err:?}")))
+}
+
+/// Executes the transaction of the environment, without committing its state changes.
+fn transact<S: StateProvider>(db: &mut SubState<S>, env: Env) -> EthResult<ResultAndState> {
+    let mut evm = EVM::new();
+    evm.env = env;
+    evm.database(db);
+    evm.transact().map_err(|err| EthApiError::Execution(format!("{err:?}")))
+}
+
+fn gas_used(result: &ExecutionResult) -> u64 {
+    match result {
+        ExecutionResult::Success { gas_used, .. } |
+        ExecutionResult::Revert { gas_used, .. } |
+        ExecutionResult::Halt { gas_used, .. } => *gas_used,
+    }
+}
+
+fn output(result: &ExecutionResult) -> Bytes {
+    match result {
+        ExecutionResult::Success { output, .. } => match output {
+            Output::Call(output) | Output::Create(output, _) => output.clone().into(),
+        },
+        ExecutionResult::Revert { output, .. } => output.clone().into(),
+        ExecutionResult::Halt { .. } => Bytes::default(),
+    }
+}
@@ -0,0 +1,421 @@
+//! revm [Inspector]s that record the geth-style traces of a transaction.
+
+use reth_primitives::{H256, U256};
+use reth_rpc_types::trace::geth::{
+    CallConfig, CallFrame, CallLogFrame, FourByteFrame, GethDefaultTracingOptions, StructLog,
+};
+use revm::{
+    interpreter::{
+        opcode, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult, Interpreter,
+    },
+    primitives::{Bytes as RevmBytes, CreateScheme, B160, B256},
+    Database, EVMData, Inspector,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Records the executed instructions of a transaction, like geth's default struct logger.
+#[derive(Debug, Default)]
+pub(crate) struct StructLogger {
+    config: GethDefaultTracingOptions,
+    logs: Vec<StructLog>,
+    /// The instructions that are executing, innermost last.
+    ///
+    /// Instructions that make calls are executing until the call returns.
+    executing: Vec<ExecutingInstruction>,
+    /// The storage slots that were read or written so far, per contract.
+    storage: HashMap<B160, BTreeMap<H256, H256>>,
+}
+
+/// An instruction the [StructLogger] has not seen the result of yet.
+#[derive(Debug)]
+struct ExecutingInstruction {
+    /// The index of the log of the instruction, `None` if the limit of logs was reached.
+    log: Option<usize>,
+    /// The gas remaining before the instruction.
+    gas: u64,
+    /// The slot the instruction reads, for `SLOAD`.
+    loaded_slot: Option<U256>,
+}
+
+impl StructLogger {
+    /// Creates a struct logger with the given options.
+    pub(crate) fn new(config: GethDefaultTracingOptions) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    /// Returns the executed instructions.
+    pub(crate) fn into_logs(self) -> Vec<StructLog> {
+        self.logs
+    }
+
+    fn is_full(&self) -> bool {
+        matches!(self.config.limit, Some(limit) if limit > 0 && self.logs.len() as u64 >= limit)
+    }
+}
+
+impl<DB: Database> Inspector<DB> for StructLogger {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> InstructionResult {
+        let op = interp.current_opcode();
+        let gas = interp.gas.remaining();
+
+        let loaded_slot = if op == opcode::SLOAD { interp.stack.peek(0).ok() } else { None };
+        let mut storage = None;
+        if op == opcode::SSTORE && !self.config.disable_storage.unwrap_or_default() {
+            if let (Ok(key), Ok(value)) = (interp.stack.peek(0), interp.stack.peek(1)) {
+                let slots = self.storage.entry(interp.contract.address).or_default();
+                slots.insert(to_h256(key), to_h256(value));
+                storage = Some(slots.clone());
+            }
+        }
+
+        if self.is_full() {
+            self.executing.push(ExecutingInstruction { log: None, gas, loaded_slot });
+            return InstructionResult::Continue
+        }
+
+        let name = opcode::OPCODE_JUMPMAP[op as usize]
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("opcode {op:#x} not defined"));
+        let stack =
+            (!self.config.disable_stack.unwrap_or_default()).then(|| interp.stack.data().clone());
+        let memory = self
+            .config
+            .enable_memory
+            .unwrap_or_default()
+            .then(|| interp.memory.data().chunks(32).map(hex::encode).collect());
+        let return_data = self
+            .config
+            .enable_return_data
+            .unwrap_or_default()
+            .then(|| interp.return_data_buffer.clone().into());
+
+        self.executing.push(ExecutingInstruction { log: Some(self.logs.len()), gas, loaded_slot });
+        self.logs.push(StructLog {
+            pc: interp.program_counter() as u64,
+            op: name,
+            gas,
+            gas_cost: 0,
+            depth: data.journaled_state.depth(),
+            error: None,
+            stack,
+            memory,
+            return_data,
+            storage,
+        });
+        InstructionResult::Continue
+    }
+
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+        eval: InstructionResult,
+    ) -> InstructionResult {
+        let Some(instruction) = self.executing.pop() else { return InstructionResult::Continue };
+
+        // The value of the slot is on the stack once it has been read.
+        let mut storage = None;
+        if let Some(key) = instruction.loaded_slot {
+            if !self.config.disable_storage.unwrap_or_default() {
+                if let Ok(value) = interp.stack.peek(0) {
+                    let slots = self.storage.entry(interp.contract.address).or_default();
+                    slots.insert(to_h256(key), to_h256(value));
+                    storage = Some(slots.clone());
+                }
+            }
+        }
+
+        if let Some(log) = instruction.log.and_then(|index| self.logs.get_mut(index)) {
+            log.gas_cost = instruction.gas.saturating_sub(interp.gas.remaining());
+            log.error = geth_error(eval);
+            if storage.is_some() {
+                log.storage = storage;
+            }
+        }
+        InstructionResult::Continue
+    }
+}
+
+/// Records the tree of calls of a transaction, like geth's `callTracer`.
+#[derive(Debug, Default)]
+pub(crate) struct CallTracer {
+    config: CallConfig,
+    /// The calls and creates that are executing, innermost last.
+    executing: Vec<CallFrame>,
+    /// The top-level call, once it returned.
+    root: Option<CallFrame>,
+}
+
+impl CallTracer {
+    /// Creates a call tracer with the given options.
+    pub(crate) fn new(config: CallConfig) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    /// Returns the top-level call.
+    pub(crate) fn into_frame(self) -> CallFrame {
+        self.root.unwrap_or_default()
+    }
+
+    fn only_top_call(&self) -> bool {
+        self.config.only_top_call.unwrap_or_default()
+    }
+
+    /// Sets the result of the innermost executing call, and moves it into its parent.
+    fn finish_frame(
+        &mut self,
+        ret: InstructionResult,
+        remaining_gas: Gas,
+        out: &RevmBytes,
+        address: Option<B160>,
+    ) {
+        let only_top_call = self.only_top_call();
+        let Some(mut frame) = self.executing.pop() else { return };
+        frame.gas_used = U256::from(remaining_gas.spend());
+        if address.is_some() {
+            frame.to = address;
+        }
+        frame.error = geth_error(ret);
+        // The output of halted calls is meaningless.
+        if frame.error.is_none() || ret == InstructionResult::Revert {
+            frame.output = Some(out.clone().into());
+        }
+
+        match self.executing.last_mut() {
+            Some(parent) => {
+                if !only_top_call {
+                    parent.calls.push(frame);
+                }
+            }
+            None => self.root = Some(frame),
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CallTracer {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> InstructionResult {
+        // Selfdestructs are recorded before they are executed, since the balance of the contract
+        // is transferred to the refund address.
+        if interp.current_opcode() == opcode::SELFDESTRUCT && !self.only_top_call() {
+            if let (Ok(refund_address), Some(parent)) =
+                (interp.stack.peek(0), self.executing.last_mut())
+            {
+                let address = interp.contract.address;
+                let balance = data
+                    .journaled_state
+                    .state
+                    .get(&address)
+                    .map(|account| account.info.balance)
+                    .unwrap_or_default();
+                parent.calls.push(CallFrame {
+                    typ: "SELFDESTRUCT".to_string(),
+                    from: address,
+                    to: Some(B160::from_slice(&refund_address.to_be_bytes::<32>()[12..])),
+                    value: Some(balance),
+                    ..Default::default()
+                });
+            }
+        }
+        InstructionResult::Continue
+    }
+
+    fn log(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        address: &B160,
+        topics: &[B256],
+        data: &RevmBytes,
+    ) {
+        if !self.config.with_log.unwrap_or_default() {
+            return
+        }
+        if let Some(frame) = self.executing.last_mut() {
+            frame.logs.push(CallLogFrame {
+                address: *address,
+                topics: topics.iter().map(|topic| H256(topic.0)).collect(),
+                data: data.clone().into(),
+            });
+        }
+    }
+
+    fn call(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+        _is_static: bool,
+    ) -> (InstructionResult, Gas, RevmBytes) {
+        let (typ, to, value) = match inputs.context.scheme {
+            CallScheme::Call => ("CALL", inputs.contract, Some(inputs.context.apparent_value)),
+            CallScheme::StaticCall => ("STATICCALL", inputs.contract, None),
+            CallScheme::CallCode => {
+                ("CALLCODE", inputs.context.code_address, Some(inputs.context.apparent_value))
+            }
+            CallScheme::DelegateCall => ("DELEGATECALL", inputs.context.code_address, None),
+        };
+        self.executing.push(CallFrame {
+            typ: typ.to_string(),
+            from: inputs.context.caller,
+            to: Some(to),
+            value,
+            gas: U256::from(inputs.gas_limit),
+            input: inputs.input.clone().into(),
+            ..Default::default()
+        });
+        (InstructionResult::Continue, Gas::new(0), RevmBytes::new())
+    }
+
+    fn call_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CallInputs,
+        remaining_gas: Gas,
+        ret: InstructionResult,
+        out: RevmBytes,
+        _is_static: bool,
+    ) -> (InstructionResult, Gas, RevmBytes) {
+        self.finish_frame(ret, remaining_gas, &out, None);
+        (ret, remaining_gas, out)
+    }
+
+    fn create(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CreateInputs,
+    ) -> (InstructionResult, Option<B160>, Gas, RevmBytes) {
+        let typ = match inputs.scheme {
+            CreateScheme::Create => "CREATE",
+            CreateScheme::Create2 { .. } => "CREATE2",
+        };
+        self.executing.push(CallFrame {
+            typ: typ.to_string(),
+            from: inputs.caller,
+            value: Some(inputs.value),
+            gas: U256::from(inputs.gas_limit),
+            input: inputs.init_code.clone().into(),
+            ..Default::default()
+        });
+        (InstructionResult::Continue, None, Gas::new(0), RevmBytes::default())
+    }
+
+    fn create_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CreateInputs,
+        ret: InstructionResult,
+        address: Option<B160>,
+        remaining_gas: Gas,
+        out: RevmBytes,
+    ) -> (InstructionResult, Option<B160>, Gas, RevmBytes) {
+        self.finish_frame(ret, remaining_gas, &out, address);
+        (ret, address, remaining_gas, out)
+    }
+}
+
+/// Counts the function selectors of the calls of a transaction, like geth's `4byteTracer`.
+#[derive(Debug, Default)]
+pub(crate) struct FourByteTracer {
+    counts: BTreeMap<String, u64>,
+}
+
+impl FourByteTracer {
+    /// Returns the number of calls per selector and input length.
+    pub(crate) fn into_frame(self) -> FourByteFrame {
+        FourByteFrame(self.counts)
+    }
+}
+
+impl<DB: Database> Inspector<DB> for FourByteTracer {
+    fn call(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+        _is_static: bool,
+    ) -> (InstructionResult, Gas, RevmBytes) {
+        if inputs.input.len() >= 4 {
+            let (selector, arguments) = inputs.input.split_at(4);
+            let key = format!("0x{}-{}", hex::encode(selector), arguments.len());
+            *self.counts.entry(key).or_default() += 1;
+        }
+        (InstructionResult::Continue, Gas::new(0), RevmBytes::new())
+    }
+}
+
+/// Returns the geth error message of an instruction or call that failed with the result, or
+/// `None` if it succeeded.
+pub(crate) fn geth_error(ret: InstructionResult) -> Option<String> {
+    let error = match ret {
+        InstructionResult::Continue |
+        InstructionResult::Stop |
+        InstructionResult::Return |
+        InstructionResult::SelfDestruct => return None,
+        InstructionResult::Revert => "execution reverted",
+        InstructionResult::OutOfGas |
+        InstructionResult::MemoryOOG |
+        InstructionResult::MemoryLimitOOG |
+        InstructionResult::PrecompileOOG |
+        InstructionResult::InvalidOperandOOG => "out of gas",
+        InstructionResult::OpcodeNotFound | InstructionResult::InvalidFEOpcode => "invalid opcode",
+        InstructionResult::InvalidJump => "invalid jump destination",
+        InstructionResult::StackUnderflow => "stack underflow",
+        InstructionResult::StackOverflow => "stack limit reached",
+        InstructionResult::StateChangeDuringStaticCall => "write protection",
+        ret => return Some(format!("{ret:?}")),
+    };
+    Some(error.to_string())
+}
+
+/// Converts a storage key or value to its hash representation.
+pub(crate) fn to_h256(value: U256) -> H256 {
+    H256(value.to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::Address;
+
+    #[test]
+    fn only_top_call() {
+        let mut tracer = CallTracer::new(CallConfig { only_top_call: Some(true), with_log: None });
+        tracer.executing.push(CallFrame { typ: "CALL".to_string(), ..Default::default() });
+        tracer.executing.push(CallFrame { typ: "STATICCALL".to_string(), ..Default::default() });
+
+        let out = RevmBytes::new();
+        tracer.finish_frame(InstructionResult::Revert, Gas::new(0), &out, None);
+        tracer.finish_frame(InstructionResult::Return, Gas::new(0), &out, None);
+
+        let frame = tracer.into_frame();
+        assert_eq!(frame.typ, "CALL");
+        assert!(frame.calls.is_empty());
+        assert_eq!(frame.error, None);
+    }
+
+    #[test]
+    fn nested_calls() {
+        let mut tracer = CallTracer::default();
+        tracer.executing.push(CallFrame { typ: "CALL".to_string(), ..Default::default() });
+        tracer.executing.push(CallFrame { typ: "CREATE".to_string(), ..Default::default() });
+
+        let out = RevmBytes::new();
+        let address = Address::random();
+        tracer.finish_frame(InstructionResult::OutOfGas, Gas::new(0), &out, Some(address));
+        tracer.finish_frame(InstructionResult::Return, Gas::new(0), &out, None);
+
+        let frame = tracer.into_frame();
+        assert_eq!(frame.calls.len(), 1);
+        assert_eq!(frame.calls[0].to, Some(address));
+        assert_eq!(frame.calls[0].error.as_deref(), Some("out of gas"));
+        assert_eq!(frame.calls[0].output, None);
+    }
+}
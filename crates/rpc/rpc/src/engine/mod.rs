@@ -2,18 +2,29 @@ use crate::result::{internal_rpc_err, rpc_err};
 use async_trait::async_trait;
 use jsonrpsee::core::{Error, RpcResult as Result};
 use reth_interfaces::consensus::ForkchoiceState;
-use reth_primitives::{BlockHash, BlockNumber, H64};
+use reth_primitives::{BlockHash, BlockNumber, H256, H64};
 use reth_rpc_api::EngineApiServer;
-use reth_rpc_engine_api::{EngineApiError, EngineApiMessage, EngineApiResult};
+use reth_rpc_engine_api::{
+    EngineApiError, EngineApiMessage, EngineApiMessageVersion, EngineApiResult, CAPABILITIES,
+};
 use reth_rpc_types::engine::{
-    ExecutionPayload, ExecutionPayloadBody, ForkchoiceUpdated, PayloadAttributes, PayloadStatus,
-    TransitionConfiguration,
+    ExecutionPayload, ExecutionPayloadBody, ExecutionPayloadEnvelope, ForkchoiceUpdated,
+    PayloadAttributes, PayloadStatus, TransitionConfiguration,
 };
 use tokio::sync::{
     mpsc::UnboundedSender,
     oneshot::{self, Receiver},
 };
 
+/// Error code of an unknown payload.
+const UNKNOWN_PAYLOAD_CODE: i32 = -38001;
+
+/// Error code of payload attributes which are not valid.
+const INVALID_PAYLOAD_ATTRIBUTES_CODE: i32 = -38003;
+
+/// Error code of a request to a method version that is not supported at the fork of the payload.
+const UNSUPPORTED_FORK_CODE: i32 = -38005;
+
 /// The server implementation of Engine API
 pub struct EngineApi {
     /// Handle to the consensus engine
@@ -27,6 +38,56 @@ impl std::fmt::Debug for EngineApi {
 }
 
 impl EngineApi {
+    /// Creates a new instance of [EngineApi] that delegates the requests to the consensus engine
+    /// over the given channel.
+    pub fn new(engine_tx: UnboundedSender<EngineApiMessage>) -> Self {
+        Self { engine_tx }
+    }
+
+    async fn new_payload(
+        &self,
+        version: EngineApiMessageVersion,
+        payload: ExecutionPayload,
+        versioned_hashes: Option<Vec<H256>>,
+        parent_beacon_block_root: Option<H256>,
+    ) -> Result<PayloadStatus> {
+        let (tx, rx) = oneshot::channel();
+        self.delegate_request(
+            EngineApiMessage::NewPayload(
+                version,
+                payload,
+                versioned_hashes,
+                parent_beacon_block_root,
+                tx,
+            ),
+            rx,
+        )
+        .await
+    }
+
+    async fn fork_choice_updated(
+        &self,
+        version: EngineApiMessageVersion,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated> {
+        let (tx, rx) = oneshot::channel();
+        self.delegate_request(
+            EngineApiMessage::ForkchoiceUpdated(version, fork_choice_state, payload_attributes, tx),
+            rx,
+        )
+        .await
+    }
+
+    async fn get_payload(
+        &self,
+        version: EngineApiMessageVersion,
+        payload_id: H64,
+    ) -> Result<ExecutionPayloadEnvelope> {
+        let (tx, rx) = oneshot::channel();
+        self.delegate_request(EngineApiMessage::GetPayload(version, payload_id, tx), rx).await
+    }
+
     async fn delegate_request<T>(
         &self,
         msg: EngineApiMessage,
@@ -35,7 +96,14 @@ impl EngineApi {
         let _ = self.engine_tx.send(msg);
         rx.await.map_err(|err| Error::Custom(err.to_string()))?.map_err(|err| {
             let code = match err {
-                EngineApiError::PayloadUnknown => -38001,
+                EngineApiError::PayloadUnknown => UNKNOWN_PAYLOAD_CODE,
+                EngineApiError::UnsupportedFork => UNSUPPORTED_FORK_CODE,
+                EngineApiError::InvalidPayloadAttributes(_) => INVALID_PAYLOAD_ATTRIBUTES_CODE,
+                EngineApiError::WithdrawalsNotSupportedInV1 |
+                EngineApiError::NoWithdrawalsPostShanghai |
+                EngineApiError::HasWithdrawalsPreShanghai => {
+                    jsonrpsee::types::error::INVALID_PARAMS_CODE
+                }
                 // Any other server error
                 _ => jsonrpsee::types::error::INTERNAL_ERROR_CODE,
             };
@@ -49,14 +117,28 @@ impl EngineApiServer for EngineApi {
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_newpayloadv1>
     /// Caution: This should not accept the `withdrawals` field
     async fn new_payload_v1(&self, payload: ExecutionPayload) -> Result<PayloadStatus> {
-        let (tx, rx) = oneshot::channel();
-        self.delegate_request(EngineApiMessage::NewPayload(payload, tx), rx).await
+        self.new_payload(EngineApiMessageVersion::V1, payload, None, None).await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_newpayloadv1>
-    async fn new_payload_v2(&self, _payload: ExecutionPayload) -> Result<PayloadStatus> {
-        // TODO:
-        Err(internal_rpc_err("unimplemented"))
+    async fn new_payload_v2(&self, payload: ExecutionPayload) -> Result<PayloadStatus> {
+        self.new_payload(EngineApiMessageVersion::V2, payload, None, None).await
+    }
+
+    /// See also <https://github.com/ethereum/execution-apis/blob/fe8e13c288c592ec154ce25c534e26cb7ce0530d/src/engine/cancun.md#engine_newpayloadv3>
+    async fn new_payload_v3(
+        &self,
+        payload: ExecutionPayload,
+        versioned_hashes: Vec<H256>,
+        parent_beacon_block_root: H256,
+    ) -> Result<PayloadStatus> {
+        self.new_payload(
+            EngineApiMessageVersion::V3,
+            payload,
+            Some(versioned_hashes),
+            Some(parent_beacon_block_root),
+        )
+        .await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_forkchoiceUpdatedV1>
@@ -67,36 +149,48 @@ impl EngineApiServer for EngineApi {
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<PayloadAttributes>,
     ) -> Result<ForkchoiceUpdated> {
-        let (tx, rx) = oneshot::channel();
-        self.delegate_request(
-            EngineApiMessage::ForkchoiceUpdated(fork_choice_state, payload_attributes, tx),
-            rx,
-        )
-        .await
+        self.fork_choice_updated(EngineApiMessageVersion::V1, fork_choice_state, payload_attributes)
+            .await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/specification.md#engine_forkchoiceupdatedv2>
     async fn fork_choice_updated_v2(
         &self,
-        _fork_choice_state: ForkchoiceState,
-        _payload_attributes: Option<PayloadAttributes>,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
     ) -> Result<ForkchoiceUpdated> {
-        // TODO:
-        Err(internal_rpc_err("unimplemented"))
+        self.fork_choice_updated(EngineApiMessageVersion::V2, fork_choice_state, payload_attributes)
+            .await
+    }
+
+    /// See also <https://github.com/ethereum/execution-apis/blob/fe8e13c288c592ec154ce25c534e26cb7ce0530d/src/engine/cancun.md#engine_forkchoiceupdatedv3>
+    async fn fork_choice_updated_v3(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated> {
+        self.fork_choice_updated(EngineApiMessageVersion::V3, fork_choice_state, payload_attributes)
+            .await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_getPayloadV1>
     ///
     /// Caution: This should not return the `withdrawals` field
     async fn get_payload_v1(&self, payload_id: H64) -> Result<ExecutionPayload> {
-        let (tx, rx) = oneshot::channel();
-        self.delegate_request(EngineApiMessage::GetPayload(payload_id, tx), rx).await
+        Ok(self.get_payload(EngineApiMessageVersion::V1, payload_id).await?.execution_payload)
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/specification.md#engine_getpayloadv2>
-    async fn get_payload_v2(&self, _payload_id: H64) -> Result<ExecutionPayload> {
-        // TODO:
-        Err(internal_rpc_err("unimplemented"))
+    async fn get_payload_v2(&self, payload_id: H64) -> Result<ExecutionPayloadEnvelope> {
+        self.get_payload(EngineApiMessageVersion::V2, payload_id).await
+    }
+
+    /// See also <https://github.com/ethereum/execution-apis/blob/fe8e13c288c592ec154ce25c534e26cb7ce0530d/src/engine/cancun.md#engine_getpayloadv3>
+    ///
+    /// Caution: The blobs bundle is not returned, the pool does not keep the blobs of the
+    /// transactions.
+    async fn get_payload_v3(&self, payload_id: H64) -> Result<ExecutionPayloadEnvelope> {
+        self.get_payload(EngineApiMessageVersion::V3, payload_id).await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/6452a6b194d7db269bf1dbd087a267251d3cc7f8/src/engine/shanghai.md#engine_getpayloadbodiesbyhashv1>
@@ -127,4 +221,11 @@ impl EngineApiServer for EngineApi {
         self.delegate_request(EngineApiMessage::ExchangeTransitionConfiguration(config, tx), rx)
             .await
     }
+
+    /// See also <https://github.com/ethereum/execution-apis/blob/6452a6b194d7db269bf1dbd087a267251d3cc7f8/src/engine/common.md#capabilities>
+    ///
+    /// The capabilities of the consensus layer are not needed, only ours are returned.
+    async fn exchange_capabilities(&self, _capabilities: Vec<String>) -> Result<Vec<String>> {
+        Ok(CAPABILITIES.iter().map(|capability| capability.to_string()).collect())
+    }
 }
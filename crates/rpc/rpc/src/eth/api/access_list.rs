@@ -0,0 +1,99 @@
+//! A revm [Inspector] that records the accounts and storage slots a transaction accesses.
+
+use reth_primitives::{rpc::transaction::eip2930::AccessListItem, U256};
+use revm::{
+    interpreter::{opcode, InstructionResult, Interpreter},
+    primitives::B160,
+    Database, EVMData, Inspector,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Records the accounts and storage slots a transaction accesses, like geth's `accessListTracer`.
+///
+/// Storage slots are recorded for every account, while accounts that are accessed without their
+/// storage are only recorded if they are not excluded, i.e. the sender, the recipient and the
+/// precompiles, which are warm anyway.
+#[derive(Debug, Default)]
+pub(crate) struct AccessListInspector {
+    /// The accounts that are not recorded unless their storage is accessed.
+    excluded: BTreeSet<B160>,
+    /// The accessed accounts with their accessed storage slots.
+    access_list: BTreeMap<B160, BTreeSet<U256>>,
+}
+
+impl AccessListInspector {
+    /// Creates a new inspector that starts with the given access list.
+    pub(crate) fn new(
+        access_list: &[(B160, Vec<U256>)],
+        excluded: impl IntoIterator<Item = B160>,
+    ) -> Self {
+        Self {
+            excluded: excluded.into_iter().collect(),
+            access_list: access_list
+                .iter()
+                .map(|(address, slots)| (*address, slots.iter().copied().collect()))
+                .collect(),
+        }
+    }
+
+    /// Returns the recorded access list, in the format of the transaction environment.
+    pub(crate) fn access_list(&self) -> Vec<(B160, Vec<U256>)> {
+        self.access_list
+            .iter()
+            .map(|(address, slots)| (*address, slots.iter().copied().collect()))
+            .collect()
+    }
+
+    /// Returns the recorded access list, in the format of the RPC response.
+    pub(crate) fn into_access_list_items(self) -> Vec<AccessListItem> {
+        self.access_list
+            .into_iter()
+            .map(|(address, slots)| AccessListItem {
+                address: address.0.into(),
+                storage_keys: slots
+                    .into_iter()
+                    .map(|slot| slot.to_be_bytes::<32>().into())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn record_address(&mut self, address: B160) {
+        if !self.excluded.contains(&address) {
+            self.access_list.entry(address).or_default();
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for AccessListInspector {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> InstructionResult {
+        match interp.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(slot) = interp.stack.peek(0) {
+                    self.access_list.entry(interp.contract.address).or_default().insert(slot);
+                }
+            }
+            opcode::EXTCODECOPY |
+            opcode::EXTCODEHASH |
+            opcode::EXTCODESIZE |
+            opcode::BALANCE |
+            opcode::SELFDESTRUCT => {
+                if let Ok(address) = interp.stack.peek(0) {
+                    self.record_address(B160::from_slice(&address.to_be_bytes::<32>()[12..]));
+                }
+            }
+            opcode::DELEGATECALL | opcode::CALL | opcode::STATICCALL | opcode::CALLCODE => {
+                if let Ok(address) = interp.stack.peek(1) {
+                    self.record_address(B160::from_slice(&address.to_be_bytes::<32>()[12..]));
+                }
+            }
+            _ => {}
+        }
+        InstructionResult::Continue
+    }
+}
@@ -1,43 +1,52 @@
 //! Contains RPC handler implementations specific to blocks.
 
-use crate::{eth::error::EthResult, EthApi};
-use reth_primitives::{rpc::BlockId, H256};
-use reth_provider::{BlockProvider, StateProviderFactory};
-use reth_rpc_types::RichBlock;
+use crate::{
+    eth::error::{EthApiError, EthResult},
+    EthApi,
+};
+use reth_primitives::{rpc::BlockId, Block};
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
+use reth_rpc_types::{Block as RpcBlock, RichBlock};
 
 impl<Client, Pool, Network> EthApi<Client, Pool, Network>
 where
-    Client: BlockProvider + StateProviderFactory + 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
 {
-    pub(crate) async fn block_by_hash(
+    /// Returns the block with the given id, with either full transactions or only their hashes.
+    ///
+    /// Returns `None` if the block does not exist.
+    pub(crate) async fn block_by_id(
         &self,
-        hash: H256,
-        _full: bool,
+        block_id: BlockId,
+        full: bool,
     ) -> EthResult<Option<RichBlock>> {
-        let block = self.client().block(BlockId::Hash(hash.0.into()))?;
-        if let Some(_block) = block {
-            // TODO: GET TD FOR BLOCK - needs block provider? or header provider?
-            // let total_difficulty = todo!();
-            // let rich_block = Block::from_block_full(block, total_difficulty);
-            todo!()
-        } else {
-            Ok(None)
-        }
+        let Some(block) = self.client().block(block_id)? else { return Ok(None) };
+        let block_hash = block.header.hash_slow();
+        let total_difficulty = self.client().header_td(&block_hash)?.unwrap_or_default();
+
+        let block = RpcBlock::from_block(block, total_difficulty, full)
+            .map_err(|_| EthApiError::InvalidTransactionSignature)?;
+        Ok(Some(block.into()))
     }
 
-    pub(crate) async fn block_by_number(
-        &self,
-        number: u64,
-        _full: bool,
-    ) -> EthResult<Option<RichBlock>> {
-        let block = self.client().block(BlockId::Number(number.into()))?;
-        if let Some(_block) = block {
-            // TODO: GET TD FOR BLOCK - needs block provider? or header provider?
-            // let total_difficulty = todo!();
-            // let rich_block = Block::from_block_full(block, total_difficulty);
-            todo!()
-        } else {
-            Ok(None)
-        }
+    /// Returns the primitive block with the given id.
+    ///
+    /// Returns `None` if the block does not exist.
+    pub(crate) fn raw_block_by_id(&self, block_id: BlockId) -> EthResult<Option<Block>> {
+        Ok(self.client().block(block_id)?)
+    }
+
+    /// Returns the number of transactions in the block with the given id.
+    ///
+    /// Returns `None` if the block does not exist.
+    pub(crate) fn block_transaction_count(&self, block_id: BlockId) -> EthResult<Option<usize>> {
+        Ok(self.raw_block_by_id(block_id)?.map(|block| block.body.len()))
+    }
+
+    /// Returns the number of ommers of the block with the given id.
+    ///
+    /// Returns `None` if the block does not exist.
+    pub(crate) fn ommers_count(&self, block_id: BlockId) -> EthResult<Option<usize>> {
+        Ok(self.raw_block_by_id(block_id)?.map(|block| block.ommers.len()))
     }
 }
@@ -0,0 +1,431 @@
+//! Contains RPC handler implementations for executing calls.
+
+use crate::{
+    eth::{
+        api::access_list::AccessListInspector,
+        error::{EthApiError, EthResult},
+    },
+    EthApi,
+};
+use reth_executor::{
+    config::revm_spec,
+    revm_wrap::{fill_block_env, State, SubState},
+};
+use reth_primitives::{
+    rpc::{
+        self,
+        transaction::eip2930::{AccessList, AccessListWithGasUsed},
+        BlockId, BlockNumber,
+    },
+    rpc_utils::get_contract_address,
+    Bytes, Head, Header, H160, KECCAK_EMPTY, U256,
+};
+use reth_provider::{BlockProvider, HeaderProvider, StateProvider, StateProviderFactory};
+use reth_rpc_types::{AccountOverride, BlockOverrides, CallRequest, StateOverride};
+use revm::{
+    db::CacheDB,
+    precompile::{Precompiles, SpecId as PrecompileSpecId},
+    primitives::{
+        BlockEnv, Bytecode, EVMError, Env, ExecutionResult, Halt, Output, ResultAndState, SpecId,
+        TransactTo, TxEnv,
+    },
+    Inspector, EVM,
+};
+
+/// The gas a plain transfer uses.
+const MIN_TRANSACTION_GAS: u64 = 21_000;
+
+/// The gas that is passed to the callee of a call that transfers value, on top of the gas limit of
+/// the call.
+const CALL_STIPEND: u64 = 2_300;
+
+impl<Client, Pool, Network> EthApi<Client, Pool, Network>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+{
+    /// Returns the EVM environment of the block with the given id, or of the latest block, and the
+    /// number of the block.
+    fn evm_env_at(&self, block_id: Option<BlockId>) -> EthResult<(Env, u64)> {
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Latest));
+        let number =
+            self.client().block_number_for_id(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let header =
+            self.client().header_by_number(number)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        Ok((self.evm_env_for_header(&header)?, number))
+    }
+
+    /// Returns the EVM environment of the block with the given header.
+    pub(crate) fn evm_env_for_header(&self, header: &Header) -> EthResult<Env> {
+        let total_difficulty = self.client().header_td(&header.hash_slow())?.unwrap_or_default();
+
+        let spec_id = revm_spec(
+            self.chain_spec(),
+            Head {
+                number: header.number,
+                timestamp: header.timestamp,
+                difficulty: header.difficulty,
+                total_difficulty,
+                hash: Default::default(),
+            },
+        );
+
+        let mut env = Env::default();
+        env.cfg.chain_id = U256::from(self.chain_spec().chain().id());
+        env.cfg.spec_id = spec_id;
+        fill_block_env(&mut env.block, header, spec_id >= SpecId::MERGE);
+        Ok(env)
+    }
+
+    /// Prepares the environment and the state to re-execute the transactions of the block with the
+    /// given header, on top of the state after its parent.
+    pub(crate) fn prepare_block_replay(
+        &self,
+        header: &Header,
+    ) -> EthResult<(Env, SubState<<Client as StateProviderFactory>::HistorySP<'_>>)> {
+        let env = self.evm_env_for_header(header)?;
+        let state = self.state_at_number(header.number.saturating_sub(1))?;
+        Ok((env, CacheDB::new(State::new(state))))
+    }
+
+    /// Prepares the environment to execute the request on top of the state of the given block.
+    pub(crate) fn prepare_call(
+        &self,
+        request: CallRequest,
+        block_id: Option<BlockId>,
+    ) -> EthResult<(Env, SubState<<Client as StateProviderFactory>::HistorySP<'_>>)> {
+        self.prepare_call_with_overrides(request, block_id, None, None)
+    }
+
+    /// Prepares the environment to execute the request on top of the state of the given block,
+    /// with the given overrides of the state and the block.
+    pub(crate) fn prepare_call_with_overrides(
+        &self,
+        request: CallRequest,
+        block_id: Option<BlockId>,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<Box<BlockOverrides>>,
+    ) -> EthResult<(Env, SubState<<Client as StateProviderFactory>::HistorySP<'_>>)> {
+        let (mut env, number) = self.evm_env_at(block_id)?;
+        if let Some(block_overrides) = block_overrides {
+            apply_block_overrides(*block_overrides, &mut env.block);
+        }
+        env.tx = create_tx_env(request, &env.block, self.chain_spec().chain().id(), self.gas_cap());
+        // Calls that don't specify a gas price don't pay the base fee.
+        if env.tx.gas_price == U256::ZERO {
+            env.block.basefee = U256::ZERO;
+        }
+
+        let state = self.state_at_number(number)?;
+        let mut db = CacheDB::new(State::new(state));
+        if let Some(state_overrides) = state_overrides {
+            apply_state_overrides(state_overrides, &mut db)?;
+        }
+        Ok((env, db))
+    }
+
+    /// Executes the request on top of the state of the given block, without committing it.
+    ///
+    /// Returns the output of the call.
+    pub(crate) fn call(
+        &self,
+        request: CallRequest,
+        block_id: Option<BlockId>,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<Box<BlockOverrides>>,
+    ) -> EthResult<Bytes> {
+        let (env, mut db) =
+            self.prepare_call_with_overrides(request, block_id, state_overrides, block_overrides)?;
+        match transact(&mut db, env)? {
+            ExecutionResult::Success { output, .. } => match output {
+                Output::Call(output) | Output::Create(output, _) => Ok(output.into()),
+            },
+            ExecutionResult::Revert { output, .. } => Err(EthApiError::Reverted(output.into())),
+            ExecutionResult::Halt { reason, .. } => {
+                Err(EthApiError::ExecutionHalted(format!("{reason:?}")))
+            }
+        }
+    }
+
+    /// Estimates the gas limit the request needs to succeed on top of the state of the given
+    /// block.
+    ///
+    /// The gas limit is found by a binary search, between the gas used by the request and the
+    /// gas limit of the request or block, capped by the gas cap and the balance of the sender.
+    pub(crate) fn estimate_gas(
+        &self,
+        request: CallRequest,
+        block_id: Option<BlockId>,
+    ) -> EthResult<U256> {
+        let (mut env, mut db) = self.prepare_call(request, block_id)?;
+
+        // A plain transfer to an account without code uses exactly the intrinsic gas.
+        if let TransactTo::Call(to) = env.tx.transact_to {
+            if env.tx.data.is_empty() &&
+                env.tx.gas_limit >= MIN_TRANSACTION_GAS &&
+                db.load_account(to)?.info.code_hash == KECCAK_EMPTY
+            {
+                let mut transfer_env = env.clone();
+                transfer_env.tx.gas_limit = MIN_TRANSACTION_GAS;
+                if succeeds(&mut db, transfer_env)? {
+                    return Ok(U256::from(MIN_TRANSACTION_GAS))
+                }
+            }
+        }
+
+        // The sender can't pay for more gas than its balance allows.
+        if env.tx.gas_price > U256::ZERO {
+            let balance = db.load_account(env.tx.caller)?.info.balance;
+            let available =
+                balance.checked_sub(env.tx.value).ok_or(EthApiError::InsufficientFunds)?;
+            let allowance: u64 = (available / env.tx.gas_price).saturating_to();
+            env.tx.gas_limit = env.tx.gas_limit.min(allowance);
+        }
+
+        // If the request fails with the highest gas limit, it fails with any gas limit.
+        let mut highest = env.tx.gas_limit;
+        let (gas_used, gas_refunded) = match transact(&mut db, env.clone())? {
+            ExecutionResult::Success { gas_used, gas_refunded, .. } => (gas_used, gas_refunded),
+            ExecutionResult::Revert { output, .. } => {
+                return Err(EthApiError::Reverted(output.into()))
+            }
+            ExecutionResult::Halt { reason: Halt::OutOfGas(_), .. } => {
+                return Err(EthApiError::GasRequiredExceedsAllowance(highest))
+            }
+            ExecutionResult::Halt { reason, .. } => {
+                return Err(EthApiError::ExecutionHalted(format!("{reason:?}")))
+            }
+        };
+
+        // The gas used is net of the refund, so the request needs a gas limit of at least the gas
+        // it used.
+        let mut lowest = gas_used.saturating_sub(1).max(MIN_TRANSACTION_GAS - 1);
+
+        // Calls can only pass 63/64 of the remaining gas to their callee, so the request usually
+        // needs more gas than it used, see EIP-150. Trying a gas limit that accounts for that
+        // first saves most iterations of the search.
+        let optimistic = (gas_used + gas_refunded + CALL_STIPEND) * 64 / 63;
+        if lowest < optimistic && optimistic < highest {
+            env.tx.gas_limit = optimistic;
+            if succeeds(&mut db, env.clone())? {
+                highest = optimistic;
+            } else {
+                lowest = optimistic;
+            }
+        }
+
+        while lowest + 1 < highest {
+            let mid = lowest + (highest - lowest) / 2;
+            env.tx.gas_limit = mid;
+            if succeeds(&mut db, env.clone())? {
+                highest = mid;
+            } else {
+                lowest = mid;
+            }
+        }
+
+        Ok(U256::from(highest))
+    }
+
+    /// Creates the access list of the request on top of the state of the given block, and returns
+    /// it with the gas the request uses with it.
+    ///
+    /// Accessing accounts and slots can change the control flow, so the request is re-executed
+    /// with the recorded access list until it doesn't change anymore.
+    pub(crate) fn create_access_list(
+        &self,
+        request: CallRequest,
+        block_id: Option<BlockId>,
+    ) -> EthResult<AccessListWithGasUsed> {
+        let (mut env, mut db) = self.prepare_call(request, block_id)?;
+
+        let from = env.tx.caller;
+        let to = match env.tx.transact_to {
+            TransactTo::Call(to) => to,
+            TransactTo::Create(_) => {
+                let nonce = match env.tx.nonce {
+                    Some(nonce) => nonce,
+                    None => db.load_account(from)?.info.nonce,
+                };
+                H160(get_contract_address(rpc::H160(from.0), nonce).0)
+            }
+        };
+        let precompiles = Precompiles::new(PrecompileSpecId::from_spec_id(env.cfg.spec_id));
+        let excluded: Vec<_> =
+            [from, to].into_iter().chain(precompiles.addresses().into_iter().copied()).collect();
+
+        loop {
+            let mut inspector =
+                AccessListInspector::new(&env.tx.access_list, excluded.iter().copied());
+            let ResultAndState { result, .. } = inspect(&mut db, env.clone(), &mut inspector)?;
+
+            let access_list = inspector.access_list();
+            if access_list == env.tx.access_list {
+                let gas_used = match result {
+                    ExecutionResult::Success { gas_used, .. } |
+                    ExecutionResult::Revert { gas_used, .. } |
+                    ExecutionResult::Halt { gas_used, .. } => gas_used,
+                };
+                return Ok(AccessListWithGasUsed {
+                    access_list: AccessList(inspector.into_access_list_items()),
+                    gas_used: rpc::U256::from(gas_used),
+                })
+            }
+            env.tx.access_list = access_list;
+        }
+    }
+}
+
+/// Executes the transaction of the environment, without committing its state changes.
+fn transact<S: StateProvider>(db: &mut SubState<S>, env: Env) -> EthResult<ExecutionResult> {
+    let mut evm = EVM::new();
+    evm.env = env;
+    evm.database(db);
+    let ResultAndState { result, .. } =
+        evm.transact().map_err(|err| EthApiError::Execution(format!("{err:?}")))?;
+    Ok(result)
+}
+
+/// Executes the transaction of the environment with the inspector, without committing its state
+/// changes.
+fn inspect<'a, S, I>(db: &'a mut SubState<S>, env: Env, inspector: I) -> EthResult<ResultAndState>
+where
+    S: StateProvider,
+    I: Inspector<&'a mut SubState<S>>,
+{
+    let mut evm = EVM::new();
+    evm.env = env;
+    evm.database(db);
+    evm.inspect(inspector).map_err(|err| EthApiError::Execution(format!("{err:?}")))
+}
+
+/// Returns whether the transaction of the environment succeeds, without committing its state
+/// changes.
+///
+/// Gas limits that are too low to even start the execution count as failures.
+fn succeeds<S: StateProvider>(db: &mut SubState<S>, env: Env) -> EthResult<bool> {
+    let mut evm = EVM::new();
+    evm.env = env;
+    evm.database(db);
+    match evm.transact() {
+        Ok(ResultAndState { result, .. }) => Ok(result.is_success()),
+        Err(EVMError::Transaction(_)) => Ok(false),
+        Err(err) => Err(EthApiError::Execution(format!("{err:?}"))),
+    }
+}
+
+/// Applies the overrides to the block environment.
+fn apply_block_overrides(overrides: BlockOverrides, block_env: &mut BlockEnv) {
+    let BlockOverrides { number, difficulty, time, gas_limit, coinbase, random, base_fee } =
+        overrides;
+
+    if let Some(number) = number {
+        block_env.number = number;
+    }
+    if let Some(difficulty) = difficulty {
+        block_env.difficulty = difficulty;
+    }
+    if let Some(time) = time {
+        block_env.timestamp = U256::from(time.as_u64());
+    }
+    if let Some(gas_limit) = gas_limit {
+        block_env.gas_limit = U256::from(gas_limit.as_u64());
+    }
+    if let Some(coinbase) = coinbase {
+        block_env.coinbase = coinbase;
+    }
+    if let Some(random) = random {
+        block_env.prevrandao = Some(random);
+    }
+    if let Some(base_fee) = base_fee {
+        block_env.basefee = base_fee;
+    }
+}
+
+/// Applies the overrides to the state of the accounts.
+fn apply_state_overrides<S: StateProvider>(
+    overrides: StateOverride,
+    db: &mut SubState<S>,
+) -> EthResult<()> {
+    for (address, account_override) in overrides {
+        let AccountOverride { nonce, code, balance, state, state_diff } = account_override;
+        if state.is_some() && state_diff.is_some() {
+            return Err(EthApiError::BothStateAndStateDiffInOverride(address))
+        }
+
+        let mut info = db.load_account(address)?.info.clone();
+        if let Some(nonce) = nonce {
+            info.nonce = nonce.as_u64();
+        }
+        if let Some(balance) = balance {
+            info.balance = balance;
+        }
+        if let Some(code) = code {
+            // The hash of non-empty code is set when the account is inserted.
+            info.code_hash = KECCAK_EMPTY;
+            info.code = Some(Bytecode::new_raw(code.0));
+        }
+        db.insert_account_info(address, info);
+
+        if let Some(state) = state {
+            let storage = state
+                .into_iter()
+                .map(|(slot, value)| (U256::from_be_bytes(slot.0), U256::from_be_bytes(value.0)))
+                .collect();
+            db.replace_account_storage(address, storage)?;
+        }
+        for (slot, value) in state_diff.into_iter().flatten() {
+            db.insert_account_storage(
+                address,
+                U256::from_be_bytes(slot.0),
+                U256::from_be_bytes(value.0),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates the transaction environment of the request.
+///
+/// The gas limit defaults to, and is capped by, the gas limit of the block, and is capped by the
+/// gas cap.
+fn create_tx_env(request: CallRequest, block_env: &BlockEnv, chain_id: u64, gas_cap: u64) -> TxEnv {
+    let CallRequest {
+        from,
+        to,
+        gas_price,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        gas,
+        value,
+        data,
+        nonce,
+        access_list,
+        ..
+    } = request;
+
+    let gas_limit = gas
+        .map_or(block_env.gas_limit, |gas| gas.min(block_env.gas_limit))
+        .min(U256::from(gas_cap));
+
+    TxEnv {
+        caller: from.unwrap_or_default(),
+        gas_limit: gas_limit.saturating_to(),
+        gas_price: gas_price.or(max_fee_per_gas).map(U256::from).unwrap_or_default(),
+        gas_priority_fee: max_priority_fee_per_gas.map(U256::from),
+        transact_to: to.map(TransactTo::Call).unwrap_or_else(TransactTo::create),
+        value: value.unwrap_or_default(),
+        data: data.map(|data| data.0).unwrap_or_default(),
+        chain_id: Some(chain_id),
+        nonce: nonce.map(|nonce| nonce.saturating_to()),
+        access_list: access_list
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| {
+                (
+                    item.address.0.into(),
+                    item.storage_keys.iter().map(|key| U256::from_be_bytes(key.0)).collect(),
+                )
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,247 @@
+//! Contains RPC handler implementations for fee suggestions.
+
+use crate::{
+    eth::{
+        api::transactions::effective_gas_price,
+        error::{EthApiError, EthResult},
+    },
+    EthApi,
+};
+use reth_consensus::validation::calculate_next_block_base_fee;
+use reth_primitives::{rpc::BlockId, U256};
+use reth_provider::{BlockProvider, HeaderProvider, ReceiptProvider};
+use reth_rpc_types::{FeeHistory, FeeHistoryCacheItem};
+use std::ops::RangeInclusive;
+
+/// The maximum number of blocks `eth_feeHistory` returns, larger requests are truncated.
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
+/// The default number of recent blocks of which the paid priority fees are sampled.
+const DEFAULT_GAS_PRICE_ORACLE_BLOCKS: u64 = 20;
+
+/// The default percentile of the sampled priority fees that is suggested.
+const DEFAULT_GAS_PRICE_ORACLE_PERCENTILE: u64 = 60;
+
+/// The default suggested priority fee if the recent blocks have no transactions: 1 gwei.
+const DEFAULT_PRIORITY_FEE: u128 = 1_000_000_000;
+
+/// Settings of the gas price oracle that serves `eth_gasPrice` and `eth_maxPriorityFeePerGas`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasPriceOracleConfig {
+    /// The number of recent blocks of which the paid priority fees are sampled.
+    pub blocks: u64,
+    /// The percentile of the sampled priority fees that is suggested.
+    pub percentile: u64,
+    /// The suggested priority fee if the sampled blocks have no transactions.
+    pub default_priority_fee: U256,
+}
+
+impl Default for GasPriceOracleConfig {
+    fn default() -> Self {
+        Self {
+            blocks: DEFAULT_GAS_PRICE_ORACLE_BLOCKS,
+            percentile: DEFAULT_GAS_PRICE_ORACLE_PERCENTILE,
+            default_priority_fee: U256::from(DEFAULT_PRIORITY_FEE),
+        }
+    }
+}
+
+impl<Client, Pool, Network> EthApi<Client, Pool, Network>
+where
+    Client: BlockProvider + HeaderProvider + ReceiptProvider + 'static,
+{
+    /// Returns the fee history of the `block_count` blocks up to `newest_block`, with the given
+    /// percentiles of the priority fees paid in each block.
+    pub(crate) async fn fee_history(
+        &self,
+        block_count: u64,
+        newest_block: BlockId,
+        reward_percentiles: Option<Vec<f64>>,
+    ) -> EthResult<FeeHistory> {
+        if block_count == 0 {
+            return Ok(FeeHistory::default())
+        }
+        if let Some(percentiles) = &reward_percentiles {
+            if percentiles.iter().any(|p| !(0.0..=100.0).contains(p)) ||
+                percentiles.windows(2).any(|pair| pair[0] > pair[1])
+            {
+                return Err(EthApiError::InvalidRewardPercentiles)
+            }
+        }
+
+        let end_block = self
+            .client()
+            .block_number_for_id(newest_block)?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+        let block_count = block_count.min(MAX_FEE_HISTORY_BLOCKS).min(end_block + 1);
+        let start_block = end_block + 1 - block_count;
+
+        let entries = self.fee_history_entries(start_block..=end_block).await?;
+        // We should receive exactly the amount of blocks requested
+        if entries.len() != block_count as usize {
+            return Err(EthApiError::InvalidBlockRange)
+        }
+
+        let mut base_fee_per_gas: Vec<_> =
+            entries.iter().map(|entry| entry.base_fee_per_gas).collect();
+        base_fee_per_gas.extend(entries.last().map(|entry| entry.next_base_fee_per_gas));
+
+        Ok(FeeHistory {
+            base_fee_per_gas,
+            gas_used_ratio: entries.iter().map(|entry| entry.gas_used_ratio).collect(),
+            oldest_block: U256::from(start_block),
+            reward: reward_percentiles.map(|percentiles| {
+                entries.iter().map(|entry| rewards(entry, &percentiles)).collect()
+            }),
+        })
+    }
+
+    /// Suggests a priority fee, based on the priority fees paid by the transactions of the recent
+    /// blocks.
+    pub(crate) async fn suggested_priority_fee(&self) -> EthResult<U256> {
+        let config = self.gas_oracle_config();
+        let best_number = self.client().chain_info()?.best_number;
+        let first = best_number.saturating_sub(config.blocks.saturating_sub(1));
+
+        let mut priority_fees: Vec<_> = self
+            .fee_history_entries(first..=best_number)
+            .await?
+            .iter()
+            .flat_map(|entry| entry.priority_fees.iter().map(|(fee, _)| *fee))
+            .collect();
+
+        if priority_fees.is_empty() {
+            return Ok(config.default_priority_fee)
+        }
+        priority_fees.sort_unstable();
+        let index = (priority_fees.len() - 1) * config.percentile.min(100) as usize / 100;
+        Ok(priority_fees[index])
+    }
+
+    /// Suggests a gas price for legacy transactions: the base fee of the latest block plus the
+    /// suggested priority fee.
+    pub(crate) async fn gas_price(&self) -> EthResult<U256> {
+        let best_number = self.client().chain_info()?.best_number;
+        let base_fee = self
+            .client()
+            .header_by_number(best_number)?
+            .and_then(|header| header.base_fee_per_gas)
+            .unwrap_or_default();
+        Ok(U256::from(base_fee) + self.suggested_priority_fee().await?)
+    }
+
+    /// Returns the fee history entries of the blocks in the range that exist.
+    ///
+    /// Entries are cached, so only blocks that were not requested before, or that replaced a
+    /// cached block in a reorg, are read from the database.
+    async fn fee_history_entries(
+        &self,
+        range: RangeInclusive<u64>,
+    ) -> EthResult<Vec<FeeHistoryCacheItem>> {
+        let mut cache = self.fee_history_cache.0.lock().await;
+        let mut entries = Vec::new();
+
+        for number in range {
+            let Some(hash) = self.client().block_hash(U256::from(number))? else { continue };
+            if let Some(entry) = cache.get(&number).filter(|entry| entry.hash == hash) {
+                entries.push(entry.clone());
+                continue
+            }
+
+            let Some(block) = self.client().block(BlockId::Number(number.into()))? else {
+                continue
+            };
+            let receipts = self.client().receipts_by_block(number.into())?;
+
+            let base_fee = block.base_fee_per_gas;
+            let mut cumulative_gas_used = 0;
+            let mut priority_fees: Vec<_> = block
+                .body
+                .iter()
+                .enumerate()
+                .map(|(index, tx)| {
+                    // Without receipts, the gas limit is the best estimate of the gas used
+                    let gas_used = match receipts.as_ref().and_then(|receipts| receipts.get(index))
+                    {
+                        Some(receipt) => {
+                            let gas_used = receipt.cumulative_gas_used - cumulative_gas_used;
+                            cumulative_gas_used = receipt.cumulative_gas_used;
+                            gas_used
+                        }
+                        None => tx.gas_limit(),
+                    };
+                    let fee = effective_gas_price(tx, base_fee)
+                        .saturating_sub(base_fee.unwrap_or_default() as u128);
+                    (U256::from(fee), gas_used)
+                })
+                .collect();
+            priority_fees.sort_unstable_by_key(|(fee, _)| *fee);
+
+            let entry = FeeHistoryCacheItem {
+                hash,
+                // Zero for pre-EIP-1559 blocks
+                base_fee_per_gas: U256::from(base_fee.unwrap_or_default()),
+                next_base_fee_per_gas: U256::from(base_fee.map_or(0, |base_fee| {
+                    calculate_next_block_base_fee(block.gas_used, block.gas_limit, base_fee)
+                })),
+                gas_used_ratio: block.gas_used as f64 / block.gas_limit as f64,
+                gas_used: block.gas_used,
+                priority_fees,
+            };
+            cache.push(number, entry.clone());
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Returns the given percentiles of the priority fees paid in the block, weighted by the gas the
+/// transactions used.
+///
+/// All zeroes are returned if the block is empty.
+fn rewards(entry: &FeeHistoryCacheItem, percentiles: &[f64]) -> Vec<U256> {
+    if entry.priority_fees.is_empty() {
+        return vec![U256::ZERO; percentiles.len()]
+    }
+
+    percentiles
+        .iter()
+        .map(|percentile| {
+            let threshold = (entry.gas_used as f64 * percentile / 100.0) as u64;
+            let mut index = 0;
+            let mut gas_used = entry.priority_fees[0].1;
+            while gas_used < threshold && index < entry.priority_fees.len() - 1 {
+                index += 1;
+                gas_used += entry.priority_fees[index].1;
+            }
+            entry.priority_fees[index].0
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::H256;
+
+    #[test]
+    fn rewards_are_weighted_by_gas_used() {
+        let entry = FeeHistoryCacheItem {
+            hash: H256::zero(),
+            base_fee_per_gas: U256::ZERO,
+            next_base_fee_per_gas: U256::ZERO,
+            gas_used_ratio: 0.5,
+            gas_used: 100,
+            priority_fees: vec![(U256::from(1), 10), (U256::from(2), 60), (U256::from(3), 30)],
+        };
+
+        assert_eq!(
+            rewards(&entry, &[0.0, 10.0, 50.0, 70.0, 71.0, 100.0]),
+            [1u64, 1, 2, 2, 3, 3].map(U256::from).to_vec()
+        );
+
+        let empty = FeeHistoryCacheItem { gas_used: 0, priority_fees: vec![], ..entry };
+        assert_eq!(rewards(&empty, &[25.0, 75.0]), vec![U256::ZERO; 2]);
+    }
+}
@@ -9,23 +9,47 @@ use reth_interfaces::Result;
 use reth_network_api::NetworkInfo;
 use reth_primitives::{
     rpc::{BlockId, BlockNumber},
-    Address, ChainInfo, TransactionSigned, H256, U64,
+    Address, ChainInfo, ChainSpec, TransactionSigned, H256, U64,
 };
-use reth_provider::{BlockProvider, StateProviderFactory};
+use reth_provider::{BlockProvider, StateProvider, StateProviderFactory, TransactionsProvider};
 use std::num::NonZeroUsize;
 
 use reth_rpc_types::FeeHistoryCache;
 use reth_transaction_pool::TransactionPool;
 use std::sync::Arc;
 
+mod access_list;
 mod block;
+mod call;
+mod fees;
 mod server;
 mod state;
 mod transactions;
 
+pub use fees::GasPriceOracleConfig;
+
 /// Cache limit of block-level fee history for `eth_feeHistory` RPC method.
 const FEE_HISTORY_CACHE_LIMIT: usize = 2048;
 
+/// The default maximum gas a call of `eth_call`, `eth_estimateGas` and `eth_createAccessList` can
+/// use: 50M, like geth.
+pub const RPC_DEFAULT_GAS_CAP: u64 = 50_000_000;
+
+/// Settings of the `eth_` namespace handlers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthApiConfig {
+    /// The settings of the gas price oracle.
+    pub gas_oracle: GasPriceOracleConfig,
+    /// The maximum gas a call can use, regardless of the gas limit of the request.
+    pub rpc_gas_cap: u64,
+}
+
+impl Default for EthApiConfig {
+    fn default() -> Self {
+        Self { gas_oracle: Default::default(), rpc_gas_cap: RPC_DEFAULT_GAS_CAP }
+    }
+}
+
 /// `Eth` API trait.
 ///
 /// Defines core functionality of the `eth` API implementation.
@@ -65,8 +89,20 @@ pub struct EthApi<Client, Pool, Network> {
 
 impl<Client, Pool, Network> EthApi<Client, Pool, Network> {
     /// Creates a new, shareable instance.
-    pub fn new(client: Client, pool: Pool, network: Network) -> Self {
-        let inner = EthApiInner { client, pool, network, signers: Default::default() };
+    pub fn new(client: Client, pool: Pool, network: Network, chain_spec: Arc<ChainSpec>) -> Self {
+        Self::with_config(client, pool, network, chain_spec, Default::default())
+    }
+
+    /// Creates a new, shareable instance with the given settings.
+    pub fn with_config(
+        client: Client,
+        pool: Pool,
+        network: Network,
+        chain_spec: Arc<ChainSpec>,
+        config: EthApiConfig,
+    ) -> Self {
+        let inner =
+            EthApiInner { client, pool, network, chain_spec, config, signers: Default::default() };
         Self {
             inner: Arc::new(inner),
             fee_history_cache: FeeHistoryCache::new(
@@ -89,6 +125,21 @@ impl<Client, Pool, Network> EthApi<Client, Pool, Network> {
     pub(crate) fn pool(&self) -> &Pool {
         &self.inner.pool
     }
+
+    /// Returns the configured [ChainSpec]
+    pub(crate) fn chain_spec(&self) -> &ChainSpec {
+        &self.inner.chain_spec
+    }
+
+    /// Returns the settings of the gas price oracle
+    pub(crate) fn gas_oracle_config(&self) -> &GasPriceOracleConfig {
+        &self.inner.config.gas_oracle
+    }
+
+    /// Returns the maximum gas a call can use
+    pub(crate) fn gas_cap(&self) -> u64 {
+        self.inner.config.rpc_gas_cap
+    }
 }
 
 // === State access helpers ===
@@ -102,14 +153,20 @@ where
     }
 
     /// Returns the state at the given [BlockId] enum or the latest.
+    ///
+    /// The latest state is read with the latest state provider of the client, which may be
+    /// cached.
     pub(crate) fn state_at_block_id_or_latest(
         &self,
         block_id: Option<BlockId>,
-    ) -> Result<Option<<Client as StateProviderFactory>::HistorySP<'_>>> {
-        if let Some(block_id) = block_id {
-            self.state_at_block_id(block_id)
-        } else {
-            self.latest_state()
+    ) -> Result<Option<Box<dyn StateProvider + '_>>> {
+        match block_id {
+            None | Some(BlockId::Number(BlockNumber::Latest)) => {
+                Ok(Some(Box::new(self.latest_state()?)))
+            }
+            Some(block_id) => Ok(self
+                .state_at_block_id(block_id)?
+                .map(|state| Box::new(state) as Box<dyn StateProvider + '_>)),
         }
     }
 
@@ -155,10 +212,8 @@ where
     }
 
     /// Returns the _latest_ state
-    pub(crate) fn latest_state(
-        &self,
-    ) -> Result<Option<<Client as StateProviderFactory>::HistorySP<'_>>> {
-        self.state_at_block_number(BlockNumber::Latest)
+    pub(crate) fn latest_state(&self) -> Result<<Client as StateProviderFactory>::LatestSP<'_>> {
+        self.client().latest()
     }
 }
 
@@ -166,7 +221,7 @@ where
 impl<Client, Pool, Network> EthApiSpec for EthApi<Client, Pool, Network>
 where
     Pool: TransactionPool + Clone + 'static,
-    Client: BlockProvider + StateProviderFactory + 'static,
+    Client: BlockProvider + StateProviderFactory + TransactionsProvider + 'static,
     Network: NetworkInfo + 'static,
 {
     /// Returns the current ethereum protocol version.
@@ -191,8 +246,9 @@ where
         self.inner.signers.iter().flat_map(|s| s.accounts()).collect()
     }
 
-    async fn transaction_by_hash(&self, _hash: H256) -> Result<Option<TransactionSigned>> {
-        todo!()
+    /// Returns the mined transaction with the given hash.
+    async fn transaction_by_hash(&self, hash: H256) -> Result<Option<TransactionSigned>> {
+        self.client().transaction_by_hash(hash)
     }
 }
 
@@ -204,6 +260,10 @@ struct EthApiInner<Client, Pool, Network> {
     client: Client,
     /// An interface to interact with the network
     network: Network,
+    /// The chain the node is running, used to configure the EVM for calls.
+    chain_spec: Arc<ChainSpec>,
+    /// The settings of the handlers.
+    config: EthApiConfig,
     /// All configured Signers
     signers: Vec<Box<dyn EthSigner>>,
 }
@@ -2,23 +2,26 @@
 //! Handles RPC requests for the `eth_` namespace.
 
 use crate::{
-    eth::{api::EthApi, error::EthApiError},
+    eth::api::EthApi,
     result::{internal_rpc_err, ToRpcResult},
 };
 use jsonrpsee::core::RpcResult as Result;
+use reth_network_api::NetworkInfo;
 use reth_primitives::{
     rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId, BlockNumber},
-    Address, Bytes, Header, H256, H64, U256, U64,
+    Address, Bytes, H256, H64, U256, U64,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, StateTrieProvider,
+    TransactionsProvider,
 };
-use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
 use reth_rpc_api::EthApiServer;
 use reth_rpc_types::{
-    CallRequest, EIP1186AccountProofResponse, FeeHistory, FeeHistoryCacheItem, Index, RichBlock,
-    SyncStatus, TransactionReceipt, TransactionRequest, Work,
+    BlockOverrides, CallRequest, EIP1186AccountProofResponse, FeeHistory, Index, RichBlock,
+    StateOverride, SyncInfo, SyncStatus, TransactionReceipt, TransactionRequest, Work,
 };
 use reth_transaction_pool::TransactionPool;
 use serde_json::Value;
-use std::collections::BTreeMap;
 
 use super::EthApiSpec;
 
@@ -27,15 +30,33 @@ impl<Client, Pool, Network> EthApiServer for EthApi<Client, Pool, Network>
 where
     Self: EthApiSpec,
     Pool: TransactionPool + 'static,
-    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
-    Network: 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateTrieProvider
+        + 'static,
+    Network: NetworkInfo + 'static,
 {
     async fn protocol_version(&self) -> Result<U64> {
         EthApiSpec::protocol_version(self).await.to_rpc_result()
     }
 
     fn syncing(&self) -> Result<SyncStatus> {
-        Err(internal_rpc_err("unimplemented"))
+        if !self.network().is_syncing() {
+            return Ok(SyncStatus::None)
+        }
+        let current_block = U256::from(
+            EthApiSpec::chain_info(self).with_message("failed to read chain info")?.best_number,
+        );
+        Ok(SyncStatus::Info(SyncInfo {
+            starting_block: U256::ZERO,
+            current_block,
+            highest_block: current_block,
+            warp_chunks_amount: None,
+            warp_chunks_processed: None,
+        }))
     }
 
     async fn author(&self) -> Result<Address> {
@@ -56,35 +77,30 @@ where
         Ok(Some(EthApiSpec::chain_id(self)))
     }
 
-    async fn block_by_hash(&self, _hash: H256, _full: bool) -> Result<Option<RichBlock>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn block_by_hash(&self, hash: H256, full: bool) -> Result<Option<RichBlock>> {
+        Ok(EthApi::block_by_id(self, BlockId::Hash(hash.0.into()), full).await?)
     }
 
-    async fn block_by_number(
-        &self,
-        _number: BlockNumber,
-        _full: bool,
-    ) -> Result<Option<RichBlock>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<RichBlock>> {
+        Ok(EthApi::block_by_id(self, BlockId::Number(number), full).await?)
     }
 
-    async fn block_transaction_count_by_hash(&self, _hash: H256) -> Result<Option<U256>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn block_transaction_count_by_hash(&self, hash: H256) -> Result<Option<U256>> {
+        Ok(EthApi::block_transaction_count(self, BlockId::Hash(hash.0.into()))?.map(U256::from))
     }
 
-    async fn block_transaction_count_by_number(
-        &self,
-        _number: BlockNumber,
-    ) -> Result<Option<U256>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn block_transaction_count_by_number(&self, number: BlockNumber) -> Result<Option<U256>> {
+        Ok(EthApi::block_transaction_count(self, BlockId::Number(number))?.map(U256::from))
     }
 
-    async fn block_uncles_count_by_hash(&self, _hash: H256) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn block_uncles_count_by_hash(&self, hash: H256) -> Result<U256> {
+        Ok(U256::from(
+            EthApi::ommers_count(self, BlockId::Hash(hash.0.into()))?.unwrap_or_default(),
+        ))
     }
 
-    async fn block_uncles_count_by_number(&self, _number: BlockNumber) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn block_uncles_count_by_number(&self, number: BlockNumber) -> Result<U256> {
+        Ok(U256::from(EthApi::ommers_count(self, BlockId::Number(number))?.unwrap_or_default()))
     }
 
     async fn uncle_by_block_hash_and_index(
@@ -103,190 +119,97 @@ where
         Err(internal_rpc_err("unimplemented"))
     }
 
-    async fn transaction_by_hash(
-        &self,
-        _hash: H256,
-    ) -> Result<Option<reth_rpc_types::Transaction>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn transaction_by_hash(&self, hash: H256) -> Result<Option<reth_rpc_types::Transaction>> {
+        Ok(EthApi::transaction_by_hash_with_context(self, hash)?)
     }
 
     async fn transaction_by_block_hash_and_index(
         &self,
-        _hash: H256,
-        _index: Index,
+        hash: H256,
+        index: Index,
     ) -> Result<Option<reth_rpc_types::Transaction>> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(EthApi::transaction_by_block_and_index(self, BlockId::Hash(hash.0.into()), index)?)
     }
 
     async fn transaction_by_block_number_and_index(
         &self,
-        _number: BlockNumber,
-        _index: Index,
+        number: BlockNumber,
+        index: Index,
     ) -> Result<Option<reth_rpc_types::Transaction>> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(EthApi::transaction_by_block_and_index(self, BlockId::Number(number), index)?)
     }
 
-    async fn transaction_receipt(&self, _hash: H256) -> Result<Option<TransactionReceipt>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>> {
+        Ok(EthApi::transaction_receipt(self, hash)?)
     }
 
-    async fn balance(&self, _address: Address, _block_number: Option<BlockId>) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn balance(&self, address: Address, block_number: Option<BlockId>) -> Result<U256> {
+        Ok(EthApi::balance(self, address, block_number)?)
     }
 
     async fn storage_at(
         &self,
-        _address: Address,
-        _index: U256,
-        _block_number: Option<BlockId>,
+        address: Address,
+        index: U256,
+        block_number: Option<BlockId>,
     ) -> Result<H256> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(EthApi::storage_at(self, address, index, block_number)?)
     }
 
     async fn transaction_count(
         &self,
-        _address: Address,
-        _block_number: Option<BlockId>,
+        address: Address,
+        block_number: Option<BlockId>,
     ) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(EthApi::get_transaction_count(self, address, block_number)?)
     }
 
     async fn get_code(&self, address: Address, block_number: Option<BlockId>) -> Result<Bytes> {
         Ok(EthApi::get_code(self, address, block_number)?)
     }
 
-    async fn call(&self, _request: CallRequest, _block_number: Option<BlockId>) -> Result<Bytes> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn call(
+        &self,
+        request: CallRequest,
+        block_number: Option<BlockId>,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<Box<BlockOverrides>>,
+    ) -> Result<Bytes> {
+        Ok(EthApi::call(self, request, block_number, state_overrides, block_overrides)?)
     }
 
     async fn create_access_list(
         &self,
-        _request: CallRequest,
-        _block_number: Option<BlockId>,
+        request: CallRequest,
+        block_number: Option<BlockId>,
     ) -> Result<AccessListWithGasUsed> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(EthApi::create_access_list(self, request, block_number)?)
     }
 
     async fn estimate_gas(
         &self,
-        _request: CallRequest,
-        _block_number: Option<BlockId>,
+        request: CallRequest,
+        block_number: Option<BlockId>,
     ) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(EthApi::estimate_gas(self, request, block_number)?)
     }
 
     async fn gas_price(&self) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(EthApi::gas_price(self).await?)
     }
 
-    // FeeHistory is calculated based on lazy evaluation of fees for historical blocks, and further
-    // caching of it in the LRU cache.
-    // When new RPC call is executed, the cache gets locked, we check it for the historical fees
-    // according to the requested block range, and fill any cache misses (in both RPC response
-    // and cache itself) with the actual data queried from the database.
-    // To minimize the number of database seeks required to query the missing data, we calculate the
-    // first non-cached block number and last non-cached block number. After that, we query this
-    // range of consecutive blocks from the database.
     async fn fee_history(
         &self,
         block_count: U64,
         newest_block: BlockId,
-        _reward_percentiles: Option<Vec<f64>>,
+        reward_percentiles: Option<Vec<f64>>,
     ) -> Result<FeeHistory> {
-        let block_count = block_count.as_u64();
-
-        if block_count == 0 {
-            return Ok(FeeHistory::default())
-        }
-
-        let Some(end_block) = self.inner.client.block_number_for_id(newest_block).to_rpc_result()? else { return Err(EthApiError::UnknownBlockNumber.into())};
-
-        if end_block < block_count {
-            return Err(EthApiError::InvalidBlockRange.into())
-        }
-
-        let start_block = end_block - block_count;
-
-        let mut fee_history_cache = self.fee_history_cache.0.lock().await;
-
-        // Sorted map that's populated in two rounds:
-        // 1. Cache entries until first non-cached block
-        // 2. Database query from the first non-cached block
-        let mut fee_history_cache_items =
-            BTreeMap::<reth_primitives::BlockNumber, FeeHistoryCacheItem>::new();
-
-        let mut first_non_cached_block = None;
-        let mut last_non_cached_block = None;
-        for block in start_block..=end_block {
-            // Check if block exists in cache, and move it to the head of the list if so
-            if let Some(fee_history_cache_item) = fee_history_cache.get(&block) {
-                fee_history_cache_items.insert(block, fee_history_cache_item.clone());
-            } else {
-                // If block doesn't exist in cache, set it as a first non-cached block to query it
-                // from the database
-                first_non_cached_block.get_or_insert(block);
-                // And last non-cached block, so we could query the database until we reach it
-                last_non_cached_block = Some(block);
-            }
-        }
-
-        // If we had any cache misses, query the database starting with the first non-cached block
-        // and ending with the last
-        if let (Some(start_block), Some(end_block)) =
-            (first_non_cached_block, last_non_cached_block)
-        {
-            let headers: Vec<Header> =
-                self.inner.client.headers_range(start_block..=end_block).to_rpc_result()?;
-
-            // We should receive exactly the amount of blocks missing from the cache
-            if headers.len() != (end_block - start_block + 1) as usize {
-                return Err(EthApiError::InvalidBlockRange.into())
-            }
-
-            for header in headers {
-                let base_fee_per_gas = header.base_fee_per_gas.
-                        unwrap_or_default(). // Zero for pre-EIP-1559 blocks
-                        try_into().unwrap(); // u64 -> U256 won't fail
-                let gas_used_ratio = header.gas_used as f64 / header.gas_limit as f64;
-
-                let fee_history_cache_item = FeeHistoryCacheItem {
-                    hash: None,
-                    base_fee_per_gas,
-                    gas_used_ratio,
-                    reward: None, // TODO: calculate rewards per transaction
-                };
-
-                // Insert missing cache entries in the map for further response composition from it
-                fee_history_cache_items.insert(header.number, fee_history_cache_item.clone());
-                // And populate the cache with new entries
-                fee_history_cache.push(header.number, fee_history_cache_item);
-            }
-        }
-
-        let oldest_block_hash =
-            self.inner.client.block_hash(start_block.try_into().unwrap()).to_rpc_result()?.unwrap();
-
-        fee_history_cache_items.get_mut(&start_block).unwrap().hash = Some(oldest_block_hash);
-        fee_history_cache.get_mut(&start_block).unwrap().hash = Some(oldest_block_hash);
-
-        // `fee_history_cache_items` now contains full requested block range (populated from both
-        // cache and database), so we can iterate over it in order and populate the response fields
-        Ok(FeeHistory {
-            base_fee_per_gas: fee_history_cache_items
-                .values()
-                .map(|item| item.base_fee_per_gas)
-                .collect(),
-            gas_used_ratio: fee_history_cache_items
-                .values()
-                .map(|item| item.gas_used_ratio)
-                .collect(),
-            oldest_block: U256::from_be_bytes(oldest_block_hash.0),
-            reward: None,
-        })
+        Ok(EthApi::fee_history(self, block_count.as_u64(), newest_block, reward_percentiles)
+            .await?)
     }
 
     async fn max_priority_fee_per_gas(&self) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(EthApi::suggested_priority_fee(self).await?)
     }
 
     async fn is_mining(&self) -> Result<bool> {
@@ -317,6 +240,10 @@ where
         Ok(EthApi::send_raw_transaction(self, tx).await?)
     }
 
+    async fn send_raw_transactions(&self, transactions: Vec<Bytes>) -> Result<Vec<H256>> {
+        Ok(EthApi::send_raw_transactions(self, transactions).await?)
+    }
+
     async fn sign(&self, _address: Address, _message: Bytes) -> Result<Bytes> {
         Err(internal_rpc_err("unimplemented"))
     }
@@ -331,11 +258,11 @@ where
 
     async fn get_proof(
         &self,
-        _address: Address,
-        _keys: Vec<H256>,
-        _block_number: Option<BlockId>,
+        address: Address,
+        keys: Vec<H256>,
+        block_number: Option<BlockId>,
     ) -> Result<EIP1186AccountProofResponse> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(EthApi::get_proof(self, address, keys, block_number)?)
     }
 }
 
@@ -345,18 +272,29 @@ mod tests {
         core::{error::Error as RpcError, RpcResult},
         types::error::{CallError, INVALID_PARAMS_CODE},
     };
-    use rand::random;
+    use rand::{random, Rng};
+    use reth_consensus::validation::calculate_next_block_base_fee;
     use reth_network_api::test_utils::NoopNetwork;
-    use reth_primitives::{rpc::BlockNumber, Block, Header, H256, U256};
+    use reth_primitives::{
+        hex_literal::hex, rpc::BlockNumber, Block, Header, TransactionSigned, H256, MAINNET, U256,
+    };
     use reth_provider::test_utils::{MockEthProvider, NoopProvider};
+    use reth_rlp::Decodable;
     use reth_rpc_api::EthApiServer;
+    use reth_rpc_types::BlockTransactions;
     use reth_transaction_pool::test_utils::testing_pool;
+    use std::sync::Arc;
 
     use crate::EthApi;
 
     #[tokio::test]
     async fn test_fee_history() {
-        let eth_api = EthApi::new(NoopProvider::default(), testing_pool(), NoopNetwork::default());
+        let eth_api = EthApi::new(
+            NoopProvider::default(),
+            testing_pool(),
+            NoopNetwork::default(),
+            Arc::new(MAINNET.clone()),
+        );
 
         let response = eth_api.fee_history(1.into(), BlockNumber::Latest.into(), None).await;
         assert!(matches!(response, RpcResult::Err(RpcError::Call(CallError::Custom(_)))));
@@ -366,18 +304,19 @@ mod tests {
         let block_count = 10;
         let newest_block = 1337;
 
-        let mut oldest_block = None;
         let mut gas_used_ratios = Vec::new();
         let mut base_fees_per_gas = Vec::new();
+        let mut next_base_fee_per_gas = U256::ZERO;
 
         let mock_provider = MockEthProvider::default();
+        let mut rng = rand::thread_rng();
 
-        for i in (0..=block_count).rev() {
+        for i in (0..block_count).rev() {
             let hash = H256::random();
-            let gas_limit: u64 = random();
-            let gas_used: u64 = random();
+            let gas_limit: u64 = rng.gen_range(1..30_000_000);
+            let gas_used: u64 = rng.gen_range(0..=gas_limit);
             let base_fee_per_gas: Option<u64> =
-                if random::<bool>() { Some(random()) } else { None };
+                if random::<bool>() { Some(rng.gen_range(0..1_000_000_000)) } else { None };
 
             let header = Header {
                 number: newest_block - i,
@@ -390,13 +329,20 @@ mod tests {
             mock_provider.add_block(hash, Block { header: header.clone(), ..Default::default() });
             mock_provider.add_header(hash, header);
 
-            oldest_block.get_or_insert(hash);
             gas_used_ratios.push(gas_used as f64 / gas_limit as f64);
-            base_fees_per_gas
-                .push(base_fee_per_gas.map(|fee| U256::try_from(fee).unwrap()).unwrap_or_default());
+            base_fees_per_gas.push(U256::from(base_fee_per_gas.unwrap_or_default()));
+            next_base_fee_per_gas = U256::from(base_fee_per_gas.map_or(0, |base_fee| {
+                calculate_next_block_base_fee(gas_used, gas_limit, base_fee)
+            }));
         }
+        base_fees_per_gas.push(next_base_fee_per_gas);
 
-        let eth_api = EthApi::new(mock_provider, testing_pool(), NoopNetwork::default());
+        let eth_api = EthApi::new(
+            mock_provider,
+            testing_pool(),
+            NoopNetwork::default(),
+            Arc::new(MAINNET.clone()),
+        );
 
         let response =
             eth_api.fee_history((newest_block + 1).into(), newest_block.into(), None).await;
@@ -409,6 +355,56 @@ mod tests {
 
         assert_eq!(fee_history.base_fee_per_gas, base_fees_per_gas);
         assert_eq!(fee_history.gas_used_ratio, gas_used_ratios);
-        assert_eq!(fee_history.oldest_block, U256::from_be_bytes(oldest_block.unwrap().0));
+        assert_eq!(fee_history.oldest_block, U256::from(newest_block - block_count + 1));
+
+        let fee_history = eth_api
+            .fee_history(block_count.into(), newest_block.into(), Some(vec![25.0, 75.0]))
+            .await
+            .unwrap();
+        assert_eq!(fee_history.reward, Some(vec![vec![U256::ZERO; 2]; block_count as usize]));
+
+        let response = eth_api
+            .fee_history(block_count.into(), newest_block.into(), Some(vec![75.0, 25.0]))
+            .await;
+        let Err(RpcError::Call(CallError::Custom(error_object))) = response else { unreachable!() };
+        assert_eq!(error_object.code(), INVALID_PARAMS_CODE);
+    }
+
+    #[tokio::test]
+    async fn test_block_and_transaction_by_hash() {
+        // https://etherscan.io/tx/0xa694b71e6c128a2ed8e2e0f6770bddbe52e3bb8f10e8472f9a79ab81497a8b5d
+        let transaction = TransactionSigned::decode(&mut &hex!("02f871018303579880850555633d1b82520894eee27662c2b8eba3cd936a23f039f3189633e4c887ad591c62bdaeb180c080a07ea72c68abfb8fca1bd964f0f99132ed9280261bdca3e549546c0205e800f7d0a05b4ef3039e9c9b9babc179a1878fb825b5aaf5aed2fa8744854150157b08d6f3")[..]).unwrap();
+
+        let header = Header { number: 1, difficulty: U256::from(1), ..Default::default() };
+        let hash = header.hash_slow();
+        let mock_provider = MockEthProvider::default();
+        mock_provider.add_block(
+            hash,
+            Block { header, body: vec![transaction.clone()], ..Default::default() },
+        );
+
+        let eth_api = EthApi::new(
+            mock_provider,
+            testing_pool(),
+            NoopNetwork::default(),
+            Arc::new(MAINNET.clone()),
+        );
+
+        let block = EthApiServer::block_by_hash(&eth_api, hash, false).await.unwrap().unwrap();
+        assert_eq!(block.header.hash, Some(hash));
+        assert_eq!(block.total_difficulty, U256::from(1));
+        assert_eq!(block.transactions, BlockTransactions::Hashes(vec![transaction.hash]));
+
+        let rpc_transaction =
+            EthApiServer::transaction_by_hash(&eth_api, transaction.hash).await.unwrap().unwrap();
+        assert_eq!(rpc_transaction.hash, transaction.hash);
+        assert_eq!(rpc_transaction.block_hash, Some(hash));
+        assert_eq!(rpc_transaction.block_number, Some(U256::from(1)));
+        assert_eq!(rpc_transaction.transaction_index, Some(U256::ZERO));
+
+        assert_eq!(
+            EthApiServer::transaction_by_hash(&eth_api, H256::random()).await.unwrap(),
+            None
+        );
     }
 }
@@ -4,9 +4,12 @@ use crate::{
     eth::error::{EthApiError, EthResult},
     EthApi,
 };
-use reth_interfaces::Result;
-use reth_primitives::{rpc::BlockId, Address, Bytes, H256, U256};
-use reth_provider::{BlockProvider, StateProvider, StateProviderFactory};
+use reth_primitives::{rpc::BlockId, Address, Bytes, H256, KECCAK_EMPTY, U256, U64};
+use reth_provider::{
+    trie::account_proof, BlockProvider, HeaderProvider, StateProvider, StateProviderFactory,
+    StateTrieProvider,
+};
+use reth_rpc_types::{EIP1186AccountProofResponse, StorageProof};
 
 impl<Client, Pool, Network> EthApi<Client, Pool, Network>
 where
@@ -19,12 +22,89 @@ where
         Ok(code)
     }
 
-    async fn storage_at(
+    pub(crate) fn balance(&self, address: Address, block_id: Option<BlockId>) -> EthResult<U256> {
+        let state =
+            self.state_at_block_id_or_latest(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let balance = state.basic_account(address)?.map(|acc| acc.balance).unwrap_or_default();
+        Ok(balance)
+    }
+
+    pub(crate) fn get_transaction_count(
         &self,
-        _address: Address,
-        _index: U256,
-        _block_number: Option<BlockId>,
-    ) -> Result<H256> {
-        todo!()
+        address: Address,
+        block_id: Option<BlockId>,
+    ) -> EthResult<U256> {
+        let state =
+            self.state_at_block_id_or_latest(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let nonce = state.basic_account(address)?.map(|acc| acc.nonce).unwrap_or_default();
+        Ok(U256::from(nonce))
+    }
+
+    pub(crate) fn storage_at(
+        &self,
+        address: Address,
+        index: U256,
+        block_id: Option<BlockId>,
+    ) -> EthResult<H256> {
+        let state =
+            self.state_at_block_id_or_latest(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let storage_key = H256(index.to_be_bytes());
+        let value = state.storage(address, storage_key)?.unwrap_or_default();
+        Ok(H256(value.to_be_bytes()))
+    }
+}
+
+impl<Client, Pool, Network> EthApi<Client, Pool, Network>
+where
+    Client: BlockProvider + HeaderProvider + StateTrieProvider + 'static,
+{
+    /// Returns the account and the requested storage slots with their merkle proofs.
+    ///
+    /// The state tries are only stored for the latest block, so proofs for other blocks are
+    /// rejected.
+    pub(crate) fn get_proof(
+        &self,
+        address: Address,
+        keys: Vec<H256>,
+        block_id: Option<BlockId>,
+    ) -> EthResult<EIP1186AccountProofResponse> {
+        let chain_info = self.client().chain_info()?;
+        if let Some(block_id) = block_id {
+            let number = self
+                .client()
+                .block_number_for_id(block_id)?
+                .ok_or(EthApiError::UnknownBlockNumber)?;
+            if number != chain_info.best_number {
+                return Err(EthApiError::HistoricalProofUnavailable)
+            }
+        }
+        let header =
+            self.client().header(&chain_info.best_hash)?.ok_or(EthApiError::UnknownBlockNumber)?;
+
+        let proof = account_proof(self.client(), header.state_root, address, &keys)?;
+        let (nonce, balance, code_hash) = match proof.account {
+            Some(account) => (account.nonce, account.balance, account.code_hash),
+            None => (0, U256::ZERO, KECCAK_EMPTY),
+        };
+        let storage_hash = proof.storage_root();
+        let storage_proof = proof
+            .storage_proofs
+            .into_iter()
+            .map(|slot| StorageProof {
+                key: U256::from_be_bytes(slot.key.0),
+                value: slot.value,
+                proof: slot.proof.nodes,
+            })
+            .collect();
+
+        Ok(EIP1186AccountProofResponse {
+            address,
+            balance,
+            code_hash,
+            nonce: U64::from(nonce),
+            storage_hash,
+            account_proof: proof.proof.nodes,
+            storage_proof,
+        })
     }
 }
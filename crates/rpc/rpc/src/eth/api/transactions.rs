@@ -4,10 +4,15 @@ use crate::{
     eth::error::{EthApiError, EthResult},
     EthApi,
 };
-use reth_primitives::{Bytes, FromRecoveredTransaction, TransactionSigned, H256};
-use reth_provider::{BlockProvider, StateProviderFactory};
+use reth_primitives::{
+    rpc::{self, BlockId},
+    rpc_utils::get_contract_address,
+    Address, Block, BlockHashOrNumber, Bytes, FromRecoveredTransaction, TransactionKind,
+    TransactionSigned, TransactionSignedEcRecovered, H256, U128, U256, U64,
+};
+use reth_provider::{BlockProvider, ReceiptProvider, StateProviderFactory, TransactionsProvider};
 use reth_rlp::Decodable;
-use reth_rpc_types::TransactionRequest;
+use reth_rpc_types::{Index, Log, Transaction, TransactionReceipt, TransactionRequest};
 use reth_transaction_pool::{TransactionOrigin, TransactionPool};
 
 impl<Client, Pool, Network> EthApi<Client, Pool, Network>
@@ -22,19 +27,12 @@ where
 
     /// Decodes and recovers the transaction and submits it to the pool.
     ///
+    /// The transaction is submitted with a `Local` origin, so the pool propagates it to the
+    /// network right away.
+    ///
     /// Returns the hash of the transaction.
     pub(crate) async fn send_raw_transaction(&self, tx: Bytes) -> EthResult<H256> {
-        let mut data = tx.as_ref();
-        if data.is_empty() {
-            return Err(EthApiError::EmptyRawTransactionData)
-        }
-
-        let transaction = TransactionSigned::decode(&mut data)
-            .map_err(|_| EthApiError::FailedToDecodeSignedTransaction)?;
-
-        let recovered =
-            transaction.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)?;
-
+        let recovered = recover_raw_transaction(tx)?;
         let pool_transaction = <Pool::Transaction>::from_recovered_transaction(recovered);
 
         // submit the transaction to the pool with a `Local` origin
@@ -42,13 +40,177 @@ where
 
         Ok(hash)
     }
+
+    /// Decodes and recovers the transactions and submits them to the pool as a batch.
+    ///
+    /// Returns the hashes of the transactions, or the error of the first transaction the pool
+    /// rejected.
+    pub(crate) async fn send_raw_transactions(&self, txs: Vec<Bytes>) -> EthResult<Vec<H256>> {
+        let pool_transactions = txs
+            .into_iter()
+            .map(|tx| {
+                recover_raw_transaction(tx).map(<Pool::Transaction>::from_recovered_transaction)
+            })
+            .collect::<EthResult<Vec<_>>>()?;
+
+        // submit the transactions to the pool with a `Local` origin
+        let hashes =
+            self.pool().add_transactions(TransactionOrigin::Local, pool_transactions).await?;
+
+        Ok(hashes.into_iter().collect::<Result<_, _>>()?)
+    }
+}
+
+/// Decodes the "raw" format of a transaction and recovers its signer.
+fn recover_raw_transaction(tx: Bytes) -> EthResult<TransactionSignedEcRecovered> {
+    let mut data = tx.as_ref();
+    if data.is_empty() {
+        return Err(EthApiError::EmptyRawTransactionData)
+    }
+
+    let transaction = TransactionSigned::decode(&mut data)
+        .map_err(|_| EthApiError::FailedToDecodeSignedTransaction)?;
+
+    transaction.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)
+}
+
+impl<Client, Pool, Network> EthApi<Client, Pool, Network>
+where
+    Client: BlockProvider + TransactionsProvider + ReceiptProvider + 'static,
+{
+    /// Returns the block that includes the mined transaction with the given hash, and the index of
+    /// the transaction in the block.
+    pub(crate) fn transaction_location(&self, hash: H256) -> EthResult<Option<(Block, usize)>> {
+        let Some(id) = self.client().transaction_id(hash)? else { return Ok(None) };
+        let Some(number) = self.client().transaction_block(id)? else { return Ok(None) };
+        let Some(block) = self.client().block(BlockId::Number(number.into()))? else {
+            return Ok(None)
+        };
+        Ok(block.body.iter().position(|tx| tx.hash == hash).map(|index| (block, index)))
+    }
+
+    /// Returns the mined transaction with the given hash, together with its block context.
+    pub(crate) fn transaction_by_hash_with_context(
+        &self,
+        hash: H256,
+    ) -> EthResult<Option<Transaction>> {
+        let Some((block, index)) = self.transaction_location(hash)? else { return Ok(None) };
+        transaction_with_block_context(block, index)
+    }
+
+    /// Returns the transaction at the given index of the block with the given id.
+    pub(crate) fn transaction_by_block_and_index(
+        &self,
+        block_id: BlockId,
+        index: Index,
+    ) -> EthResult<Option<Transaction>> {
+        let Some(block) = self.client().block(block_id)? else { return Ok(None) };
+        transaction_with_block_context(block, index.into())
+    }
+
+    /// Returns the receipt of the mined transaction with the given hash.
+    pub(crate) fn transaction_receipt(&self, hash: H256) -> EthResult<Option<TransactionReceipt>> {
+        let Some((block, index)) = self.transaction_location(hash)? else { return Ok(None) };
+        let Some(receipts) =
+            self.client().receipts_by_block(BlockHashOrNumber::Number(block.number))?
+        else {
+            return Ok(None)
+        };
+        let Some(receipt) = receipts.get(index) else { return Ok(None) };
+
+        let block_hash = block.header.hash_slow();
+        let transaction = &block.body[index];
+        let sender =
+            transaction.recover_signer().ok_or(EthApiError::InvalidTransactionSignature)?;
+
+        // The receipts only store the cumulative gas used of the block.
+        let gas_used_before =
+            index.checked_sub(1).map(|prev| receipts[prev].cumulative_gas_used).unwrap_or_default();
+        let first_log_index = receipts[..index].iter().map(|r| r.logs.len()).sum::<usize>();
+
+        let (to, contract_address) = match transaction.kind() {
+            TransactionKind::Call(to) => (Some(*to), None),
+            TransactionKind::Create => {
+                let address = get_contract_address(rpc::H160(sender.0), transaction.nonce());
+                (None, Some(Address::from(address.0)))
+            }
+        };
+
+        let logs = receipt
+            .logs
+            .iter()
+            .enumerate()
+            .map(|(log_index, log)| Log {
+                address: log.address,
+                topics: log.topics.clone(),
+                data: log.data.clone(),
+                block_hash: Some(block_hash),
+                block_number: Some(U256::from(block.number)),
+                transaction_hash: Some(transaction.hash),
+                transaction_index: Some(U256::from(index)),
+                log_index: Some(U256::from(first_log_index + log_index)),
+                transaction_log_index: Some(U256::from(log_index)),
+                removed: false,
+            })
+            .collect();
+
+        Ok(Some(TransactionReceipt {
+            transaction_hash: Some(transaction.hash),
+            transaction_index: Some(U256::from(index)),
+            block_hash: Some(block_hash),
+            block_number: Some(U256::from(block.number)),
+            from: sender,
+            to,
+            cumulative_gas_used: U256::from(receipt.cumulative_gas_used),
+            gas_used: Some(U256::from(receipt.cumulative_gas_used - gas_used_before)),
+            contract_address,
+            logs,
+            state_root: None,
+            logs_bloom: receipt.bloom,
+            status_code: Some(U64::from(receipt.success as u8)),
+            effective_gas_price: U128::from(effective_gas_price(
+                transaction,
+                block.base_fee_per_gas,
+            )),
+            transaction_type: U256::from(transaction.tx_type() as u8),
+        }))
+    }
+}
+
+/// Converts the transaction at the given index of the block to an rpc [Transaction].
+fn transaction_with_block_context(block: Block, index: usize) -> EthResult<Option<Transaction>> {
+    let block_hash = block.header.hash_slow();
+    let block_number = block.number;
+    let Some(transaction) = block.body.into_iter().nth(index) else { return Ok(None) };
+    let transaction =
+        transaction.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)?;
+    Ok(Some(Transaction::from_recovered_with_block_context(
+        transaction,
+        block_hash,
+        block_number,
+        U256::from(index),
+    )))
+}
+
+/// Returns the price per gas the transaction pays in a block with the given base fee.
+///
+/// For EIP-1559 style transactions, this is the base fee plus the priority fee, capped by the max
+/// fee. Other transactions pay their gas price.
+pub(crate) fn effective_gas_price(transaction: &TransactionSigned, base_fee: Option<u64>) -> u128 {
+    match (transaction.max_priority_fee_per_gas(), base_fee) {
+        (Some(priority_fee), Some(base_fee)) => {
+            transaction.max_fee_per_gas().min(base_fee as u128 + priority_fee)
+        }
+        _ => transaction.max_fee_per_gas(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use reth_primitives::{hex_literal::hex, Bytes};
+    use reth_primitives::{hex_literal::hex, Bytes, MAINNET};
     use reth_provider::test_utils::NoopProvider;
     use reth_transaction_pool::{test_utils::testing_pool, TransactionPool};
+    use std::sync::Arc;
 
     use crate::EthApi;
 
@@ -58,7 +220,7 @@ mod tests {
 
         let pool = testing_pool();
 
-        let eth_api = EthApi::new(noop_provider, pool.clone(), ());
+        let eth_api = EthApi::new(noop_provider, pool.clone(), (), Arc::new(MAINNET.clone()));
 
         // https://etherscan.io/tx/0xa694b71e6c128a2ed8e2e0f6770bddbe52e3bb8f10e8472f9a79ab81497a8b5d
         let tx_1 = Bytes::from(hex!("02f871018303579880850555633d1b82520894eee27662c2b8eba3cd936a23f039f3189633e4c887ad591c62bdaeb180c080a07ea72c68abfb8fca1bd964f0f99132ed9280261bdca3e549546c0205e800f7d0a05b4ef3039e9c9b9babc179a1878fb825b5aaf5aed2fa8744854150157b08d6f3"));
@@ -85,4 +247,22 @@ mod tests {
         assert!(pool.get(&tx_1_result).is_some(), "tx1 not found in the pool");
         assert!(pool.get(&tx_2_result).is_some(), "tx2 not found in the pool");
     }
+
+    #[tokio::test]
+    async fn send_raw_transactions() {
+        let pool = testing_pool();
+        let eth_api =
+            EthApi::new(NoopProvider::default(), pool.clone(), (), Arc::new(MAINNET.clone()));
+
+        // https://etherscan.io/tx/0xa694b71e6c128a2ed8e2e0f6770bddbe52e3bb8f10e8472f9a79ab81497a8b5d
+        let tx = Bytes::from(hex!("02f871018303579880850555633d1b82520894eee27662c2b8eba3cd936a23f039f3189633e4c887ad591c62bdaeb180c080a07ea72c68abfb8fca1bd964f0f99132ed9280261bdca3e549546c0205e800f7d0a05b4ef3039e9c9b9babc179a1878fb825b5aaf5aed2fa8744854150157b08d6f3"));
+
+        // an undecodable transaction fails the whole batch
+        assert!(eth_api.send_raw_transactions(vec![tx.clone(), Bytes::default()]).await.is_err());
+        assert!(pool.is_empty());
+
+        let hashes = eth_api.send_raw_transactions(vec![tx]).await.unwrap();
+        assert_eq!(hashes.len(), 1);
+        assert!(pool.get(&hashes[0]).unwrap().is_local());
+    }
 }
@@ -1,10 +1,14 @@
 //! Error variants for the `eth_` namespace.
 
 use jsonrpsee::{core::Error as RpcError, types::error::INVALID_PARAMS_CODE};
+use reth_primitives::{Address, Bytes};
 use reth_transaction_pool::error::PoolError;
 
 use crate::result::{internal_rpc_err, rpc_err};
 
+/// The error code `geth` returns for reverted calls.
+const EXECUTION_REVERTED_CODE: i32 = 3;
+
 /// Result alias
 pub(crate) type EthResult<T> = Result<T, EthApiError>;
 
@@ -25,6 +29,43 @@ pub(crate) enum EthApiError {
     UnknownBlockNumber,
     #[error("Invalid block range")]
     InvalidBlockRange,
+    #[error("filter not found")]
+    FilterNotFound,
+    #[error("transaction not found")]
+    TransactionNotFound,
+    /// The reward percentiles of `eth_feeHistory` are not monotonically increasing values between
+    /// 0 and 100
+    #[error("invalid reward percentiles")]
+    InvalidRewardPercentiles,
+    /// The state tries are only stored for the latest block
+    #[error("proofs are only available for the latest block")]
+    HistoricalProofUnavailable,
+    /// The config of the tracer could not be parsed
+    #[error("invalid tracer config: {0}")]
+    InvalidTracerConfig(String),
+    /// The log query spans more blocks than allowed
+    #[error("query exceeds max block range {0}")]
+    QueryExceedsMaxBlocks(u64),
+    /// The log query matches more logs than allowed
+    #[error("query exceeds max results {0}")]
+    QueryExceedsMaxResults(usize),
+    /// The call was reverted, with the revert data
+    #[error("execution reverted")]
+    Reverted(Bytes),
+    #[error("execution halted: {0}")]
+    ExecutionHalted(String),
+    /// The call runs out of gas with the highest gas limit the sender can pay for
+    #[error("gas required exceeds allowance ({0})")]
+    GasRequiredExceedsAllowance(u64),
+    /// The sender can't pay for the value of the call
+    #[error("insufficient funds for transfer")]
+    InsufficientFunds,
+    /// A state override sets both the entire storage and individual slots of an account
+    #[error("account {0:?} has both 'state' and 'stateDiff'")]
+    BothStateAndStateDiffInOverride(Address),
+    /// The call could not be executed, e.g. because of an invalid transaction
+    #[error("failed to execute call: {0}")]
+    Execution(String),
     /// Other internal error
     #[error(transparent)]
     Internal(#[from] reth_interfaces::Error),
@@ -33,9 +74,19 @@ pub(crate) enum EthApiError {
 impl From<EthApiError> for RpcError {
     fn from(value: EthApiError) -> Self {
         match value {
-            EthApiError::UnknownBlockNumber | EthApiError::InvalidBlockRange => {
+            EthApiError::UnknownBlockNumber |
+            EthApiError::InvalidBlockRange |
+            EthApiError::HistoricalProofUnavailable |
+            EthApiError::InvalidRewardPercentiles |
+            EthApiError::BothStateAndStateDiffInOverride(_) |
+            EthApiError::QueryExceedsMaxBlocks(_) |
+            EthApiError::QueryExceedsMaxResults(_) |
+            EthApiError::InvalidTracerConfig(_) => {
                 rpc_err(INVALID_PARAMS_CODE, value.to_string(), None)
             }
+            EthApiError::Reverted(ref output) => {
+                rpc_err(EXECUTION_REVERTED_CODE, value.to_string(), Some(output))
+            }
             err => internal_rpc_err(err.to_string()),
         }
     }
@@ -60,6 +111,20 @@ pub(crate) enum GethTxPoolError {
     NegativeValue,
     #[error("oversized data")]
     OversizedData,
+    #[error("invalid chain id for signer")]
+    InvalidChainId,
+    #[error("transaction type not supported")]
+    TxTypeNotSupported,
+    #[error("max priority fee per gas higher than max fee per gas")]
+    TipAboveFeeCap,
+    #[error("intrinsic gas too low")]
+    IntrinsicGas,
+    #[error("nonce too low")]
+    NonceTooLow,
+    #[error("insufficient funds for gas * price + value")]
+    InsufficientFunds,
+    #[error("{0}")]
+    Other(String),
 }
 
 impl From<PoolError> for GethTxPoolError {
@@ -71,6 +136,17 @@ impl From<PoolError> for GethTxPoolError {
             PoolError::DiscardedOnInsert(_) => GethTxPoolError::TxPoolOverflow,
             PoolError::TxExceedsGasLimit(_, _, _) => GethTxPoolError::GasLimit,
             PoolError::TxExceedsMaxInitCodeSize(_, _, _) => GethTxPoolError::OversizedData,
+            PoolError::InvalidChainId(_, _) => GethTxPoolError::InvalidChainId,
+            PoolError::TxTypeNotSupported(_, _) => GethTxPoolError::TxTypeNotSupported,
+            PoolError::TipAboveFeeCap(_) => GethTxPoolError::TipAboveFeeCap,
+            PoolError::IntrinsicGasTooLow(_, _, _) => GethTxPoolError::IntrinsicGas,
+            PoolError::NonceTooLow(_, _, _) => GethTxPoolError::NonceTooLow,
+            PoolError::InsufficientFunds(_, _, _) => GethTxPoolError::InsufficientFunds,
+            PoolError::BlobFeeCapTooLow(_, _, _) => GethTxPoolError::Underpriced,
+            err @ (PoolError::MissingBlobSidecar(_) |
+            PoolError::InvalidBlobSidecar(_, _) |
+            PoolError::MissingKzgSettings(_)) => GethTxPoolError::Other(err.to_string()),
+            PoolError::Other(_, err) => GethTxPoolError::Other(err.to_string()),
         }
     }
 }
@@ -1,18 +1,59 @@
-use crate::result::{internal_rpc_err, ToRpcResult};
+use crate::{
+    eth::error::{EthApiError, EthResult},
+    result::{internal_rpc_err, ToRpcResult},
+};
 use async_trait::async_trait;
 use jsonrpsee::{
     core::RpcResult,
     server::{IdProvider, RandomIntegerIdProvider},
 };
-use reth_primitives::rpc::Filter;
-use reth_provider::BlockProvider;
+use reth_primitives::{
+    rpc::{BlockId, Filter, FilterBlockOption, ValueOrArray},
+    Address, BlockHashOrNumber, BlockNumber, Bloom, Receipt, TransactionSigned, TxHash, H256, U256,
+};
+use reth_provider::{BlockProvider, HeaderProvider, ReceiptProvider};
 use reth_rpc_api::EthFilterApiServer;
 use reth_rpc_types::{FilterChanges, FilterId, Log};
+use reth_tasks::{TaskSpawner, TokioTaskExecutor};
 use reth_transaction_pool::TransactionPool;
-use std::{collections::HashMap, sync::Arc, time::Instant};
-use tokio::sync::Mutex;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Weak},
+    time::{Duration, Instant},
+};
+use tokio::sync::{mpsc::Receiver, Mutex};
 use tracing::trace;
 
+/// The default maximum number of blocks a single log query can span.
+pub const DEFAULT_MAX_BLOCKS_PER_FILTER: u64 = 100_000;
+
+/// The default maximum number of logs a single log query can return.
+pub const DEFAULT_MAX_LOGS_PER_RESPONSE: usize = 20_000;
+
+/// The default duration after which a filter that was not polled is uninstalled.
+pub const DEFAULT_STALE_FILTER_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Limits of the log queries and the filters served by [EthFilter].
+#[derive(Debug, Clone, Copy)]
+pub struct EthFilterConfig {
+    /// The maximum number of blocks a single query can span.
+    pub max_blocks_per_filter: u64,
+    /// The maximum number of logs a single query can return.
+    pub max_logs_per_response: usize,
+    /// The duration after which a filter that was not polled is uninstalled.
+    pub stale_filter_ttl: Duration,
+}
+
+impl Default for EthFilterConfig {
+    fn default() -> Self {
+        Self {
+            max_blocks_per_filter: DEFAULT_MAX_BLOCKS_PER_FILTER,
+            max_logs_per_response: DEFAULT_MAX_LOGS_PER_RESPONSE,
+            stale_filter_ttl: DEFAULT_STALE_FILTER_TTL,
+        }
+    }
+}
+
 /// `Eth` filter RPC implementation.
 #[derive(Debug, Clone)]
 pub struct EthFilter<Client, Pool> {
@@ -22,11 +63,32 @@ pub struct EthFilter<Client, Pool> {
 
 impl<Client, Pool> EthFilter<Client, Pool> {
     /// Creates a new, shareable instance.
-    pub fn new(client: Client, pool: Pool) -> Self {
+    ///
+    /// The task uninstalling the stale filters is spawned via [tokio::task::spawn]
+    pub fn new(client: Client, pool: Pool, config: EthFilterConfig) -> Self {
+        Self::with_spawner(client, pool, config, Box::<TokioTaskExecutor>::default())
+    }
+
+    /// Creates a new, shareable instance.
+    ///
+    /// The filters which are not polled within the [EthFilterConfig::stale_filter_ttl] are
+    /// uninstalled by a task spawned with the `task_spawner`, which ends once the instance and its
+    /// clones are dropped.
+    pub fn with_spawner(
+        client: Client,
+        pool: Pool,
+        config: EthFilterConfig,
+        task_spawner: Box<dyn TaskSpawner>,
+    ) -> Self {
+        let active_filters = ActiveFilters::default();
+        let filters = Arc::downgrade(&active_filters.inner);
+        task_spawner.spawn(Box::pin(clear_stale_filters(filters, config.stale_filter_ttl)));
+
         let inner = EthFilterInner {
             client,
-            active_filters: Default::default(),
+            active_filters,
             pool,
+            config,
             id_provider: Arc::new(RandomIntegerIdProvider),
         };
         Self { inner: Arc::new(inner) }
@@ -41,7 +103,7 @@ impl<Client, Pool> EthFilter<Client, Pool> {
 #[async_trait]
 impl<Client, Pool> EthFilterApiServer for EthFilter<Client, Pool>
 where
-    Client: BlockProvider + 'static,
+    Client: BlockProvider + HeaderProvider + ReceiptProvider + 'static,
     Pool: TransactionPool + 'static,
 {
     async fn new_filter(&self, filter: Filter) -> RpcResult<FilterId> {
@@ -53,15 +115,25 @@ where
     }
 
     async fn new_pending_transaction_filter(&self) -> RpcResult<FilterId> {
-        self.inner.install_filter(FilterKind::PendingTransaction).await
+        let listener = self.inner.pool.pending_transactions_listener();
+        self.inner.install_filter(FilterKind::PendingTransaction(listener)).await
     }
 
-    async fn filter_changes(&self, _id: FilterId) -> RpcResult<FilterChanges> {
-        todo!()
+    async fn filter_changes(&self, id: FilterId) -> RpcResult<FilterChanges> {
+        Ok(self.inner.filter_changes(id).await?)
     }
 
-    async fn filter_logs(&self, _id: FilterId) -> RpcResult<Vec<Log>> {
-        todo!()
+    async fn filter_logs(&self, id: FilterId) -> RpcResult<Vec<Log>> {
+        let filter = {
+            let mut filters = self.inner.active_filters.inner.lock().await;
+            let filter = filters.get_mut(&id).ok_or(EthApiError::FilterNotFound)?;
+            filter.last_poll_timestamp = Instant::now();
+            match &filter.kind {
+                FilterKind::Log(filter) => filter.clone(),
+                _ => return Err(EthApiError::FilterNotFound.into()),
+            }
+        };
+        Ok(self.inner.logs(&filter)?)
     }
 
     async fn uninstall_filter(&self, id: FilterId) -> RpcResult<bool> {
@@ -74,8 +146,8 @@ where
         }
     }
 
-    async fn logs(&self, _filter: Filter) -> RpcResult<Vec<Log>> {
-        todo!()
+    async fn logs(&self, filter: Filter) -> RpcResult<Vec<Log>> {
+        Ok(self.inner.logs(&filter)?)
     }
 }
 
@@ -88,12 +160,14 @@ struct EthFilterInner<Client, Pool> {
     client: Client,
     /// All currently installed filters.
     active_filters: ActiveFilters,
+    /// Limits of the log queries.
+    config: EthFilterConfig,
     id_provider: Arc<dyn IdProvider>,
 }
 
 impl<Client, Pool> EthFilterInner<Client, Pool>
 where
-    Client: BlockProvider + 'static,
+    Client: BlockProvider + HeaderProvider + ReceiptProvider + 'static,
     Pool: TransactionPool + 'static,
 {
     /// Installs a new filter and returns the new identifier.
@@ -107,6 +181,257 @@ where
         );
         Ok(id)
     }
+
+    /// Returns the changes of the filter since it was last polled.
+    ///
+    /// Block and log filters return the hashes of, respectively the logs of, the blocks that were
+    /// added to the chain since the last poll. Pending transaction filters return the hashes of
+    /// the transactions that became pending since the last poll.
+    async fn filter_changes(&self, id: FilterId) -> EthResult<FilterChanges> {
+        let best_number = self.client.chain_info()?.best_number;
+
+        let (from, filter) = {
+            let mut filters = self.active_filters.inner.lock().await;
+            let active = filters.get_mut(&id).ok_or(EthApiError::FilterNotFound)?;
+            active.last_poll_timestamp = Instant::now();
+            let filter = match &mut active.kind {
+                FilterKind::PendingTransaction(listener) => {
+                    let mut hashes = Vec::new();
+                    while let Ok(hash) = listener.try_recv() {
+                        hashes.push(hash);
+                    }
+                    return Ok(FilterChanges::Hashes(hashes))
+                }
+                FilterKind::Block => None,
+                FilterKind::Log(filter) => Some(filter.clone()),
+            };
+            (active.last_poll_block_number + 1, filter)
+        };
+
+        let changes = self.block_changes(filter.as_ref(), from, best_number)?;
+
+        // The blocks are only marked as polled once their changes were fetched, so that the next
+        // poll returns them if this one failed.
+        if let Some(active) = self.active_filters.inner.lock().await.get_mut(&id) {
+            active.last_poll_block_number = best_number.max(active.last_poll_block_number);
+        }
+        Ok(changes)
+    }
+
+    /// Returns the hashes of the blocks in the range, or the logs of the blocks in the range that
+    /// match the filter.
+    fn block_changes(
+        &self,
+        filter: Option<&Filter>,
+        from: BlockNumber,
+        best_number: BlockNumber,
+    ) -> EthResult<FilterChanges> {
+        let Some(filter) = filter else {
+            let mut hashes = Vec::new();
+            for number in from..=best_number {
+                if let Some(hash) = self.client.block_hash(U256::from(number))? {
+                    hashes.push(hash);
+                }
+            }
+            return Ok(FilterChanges::Hashes(hashes))
+        };
+
+        // Only the new blocks that are within the range of the filter are queried.
+        let (filter_from, filter_to) = self.block_range(filter, best_number)?;
+        let (from, to) = (from.max(filter_from), best_number.min(filter_to));
+        if from > to {
+            return Ok(FilterChanges::Logs(Vec::new()))
+        }
+        let logs = self.logs_in_range(&LogFilter::new(filter), from, to)?;
+        Ok(FilterChanges::Logs(logs))
+    }
+
+    /// Returns the logs of the canonical chain that match the filter.
+    fn logs(&self, filter: &Filter) -> EthResult<Vec<Log>> {
+        let best_number = self.client.chain_info()?.best_number;
+        let (from, to) = self.block_range(filter, best_number)?;
+        if from > to {
+            return Err(EthApiError::InvalidBlockRange)
+        }
+        self.logs_in_range(&LogFilter::new(filter), from, to)
+    }
+
+    /// Resolves the inclusive range of blocks the filter applies to.
+    ///
+    /// Missing bounds and the pending block default to the latest block.
+    fn block_range(
+        &self,
+        filter: &Filter,
+        best_number: BlockNumber,
+    ) -> EthResult<(BlockNumber, BlockNumber)> {
+        match &filter.block_option {
+            FilterBlockOption::Range { from_block, to_block } => {
+                let resolve = |number| -> EthResult<BlockNumber> {
+                    let number = match number {
+                        Some(number) => self.client.convert_block_number(number)?,
+                        None => None,
+                    };
+                    Ok(number.unwrap_or(best_number).min(best_number))
+                };
+                Ok((resolve(*from_block)?, resolve(*to_block)?))
+            }
+            FilterBlockOption::AtBlockHash(hash) => {
+                let number = self
+                    .client
+                    .block_number(H256(hash.0))?
+                    .ok_or(EthApiError::UnknownBlockNumber)?;
+                Ok((number, number))
+            }
+        }
+    }
+
+    /// Returns the logs of the blocks in the inclusive range that match the filter.
+    ///
+    /// Blocks whose bloom can't match the filter are skipped without reading their receipts.
+    fn logs_in_range(
+        &self,
+        filter: &LogFilter,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> EthResult<Vec<Log>> {
+        if to - from >= self.config.max_blocks_per_filter {
+            return Err(EthApiError::QueryExceedsMaxBlocks(self.config.max_blocks_per_filter))
+        }
+
+        let mut logs = Vec::new();
+        for number in from..=to {
+            let Some(header) = self.client.header_by_number(number)? else { continue };
+            if !filter.matches_bloom(&header.logs_bloom) {
+                continue
+            }
+
+            logs.extend(matching_block_logs(&self.client, filter, number, header.hash_slow())?);
+            if logs.len() > self.config.max_logs_per_response {
+                return Err(EthApiError::QueryExceedsMaxResults(self.config.max_logs_per_response))
+            }
+        }
+        Ok(logs)
+    }
+}
+
+/// Returns the logs of the canonical block that match the filter.
+pub(crate) fn matching_block_logs<Client>(
+    client: &Client,
+    filter: &LogFilter,
+    number: BlockNumber,
+    block_hash: H256,
+) -> EthResult<Vec<Log>>
+where
+    Client: BlockProvider + ReceiptProvider,
+{
+    let Some(block) = client.block(BlockId::Number(number.into()))? else { return Ok(Vec::new()) };
+    let Some(receipts) = client.receipts_by_block(BlockHashOrNumber::Number(number))? else {
+        return Ok(Vec::new())
+    };
+    Ok(matching_receipt_logs(filter, number, block_hash, &block.body, &receipts, false))
+}
+
+/// Returns the logs of the receipts of the block's transactions that match the filter.
+///
+/// `removed` marks the logs of a block that was reverted by a reorg.
+pub(crate) fn matching_receipt_logs<'a>(
+    filter: &LogFilter,
+    number: BlockNumber,
+    block_hash: H256,
+    transactions: &[TransactionSigned],
+    receipts: impl IntoIterator<Item = &'a Receipt>,
+    removed: bool,
+) -> Vec<Log> {
+    let mut logs = Vec::new();
+    let mut log_index = 0;
+    for (tx_index, (transaction, receipt)) in transactions.iter().zip(receipts).enumerate() {
+        for (transaction_log_index, log) in receipt.logs.iter().enumerate() {
+            if filter.matches(log) {
+                logs.push(Log {
+                    address: log.address,
+                    topics: log.topics.clone(),
+                    data: log.data.clone(),
+                    block_hash: Some(block_hash),
+                    block_number: Some(U256::from(number)),
+                    transaction_hash: Some(transaction.hash),
+                    transaction_index: Some(U256::from(tx_index)),
+                    log_index: Some(U256::from(log_index)),
+                    transaction_log_index: Some(U256::from(transaction_log_index)),
+                    removed,
+                });
+            }
+            log_index += 1;
+        }
+    }
+    logs
+}
+
+/// The addresses and topics of a [Filter], converted to reth types.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct LogFilter {
+    /// The log must be emitted by one of the addresses, any address matches if empty.
+    addresses: Vec<Address>,
+    /// The log must have one of the topics at each position, any topic matches if `None`.
+    ///
+    /// Trailing wildcards are trimmed.
+    topics: Vec<Option<Vec<H256>>>,
+}
+
+impl LogFilter {
+    /// Converts the addresses and topics of the filter.
+    pub(crate) fn new(filter: &Filter) -> Self {
+        let addresses = match &filter.address {
+            Some(ValueOrArray::Value(address)) => vec![Address::from(address.0)],
+            Some(ValueOrArray::Array(addresses)) => {
+                addresses.iter().map(|address| Address::from(address.0)).collect()
+            }
+            None => Vec::new(),
+        };
+
+        let mut topics = filter
+            .topics
+            .iter()
+            .map(|topic| match topic {
+                Some(ValueOrArray::Value(Some(topic))) => Some(vec![H256(topic.0)]),
+                // a `null` entry matches any topic
+                Some(ValueOrArray::Array(topics)) if topics.iter().all(Option::is_some) => {
+                    let topics = topics.iter().flatten().map(|topic| H256(topic.0)).collect();
+                    Some(topics).filter(|topics: &Vec<H256>| !topics.is_empty())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        while matches!(topics.last(), Some(None)) {
+            topics.pop();
+        }
+
+        Self { addresses, topics }
+    }
+
+    /// Returns whether a block with the given bloom may contain logs that match the filter.
+    pub(crate) fn matches_bloom(&self, bloom: &Bloom) -> bool {
+        let matches_address = self.addresses.is_empty() ||
+            self.addresses.iter().any(|address| bloom.contains_input(address.as_bytes()));
+        matches_address &&
+            self.topics
+                .iter()
+                .flatten()
+                .all(|topics| topics.iter().any(|topic| bloom.contains_input(topic.as_bytes())))
+    }
+
+    /// Returns whether the log matches the filter.
+    pub(crate) fn matches(&self, log: &reth_primitives::Log) -> bool {
+        if !self.addresses.is_empty() && !self.addresses.contains(&log.address) {
+            return false
+        }
+        if log.topics.len() < self.topics.len() {
+            return false
+        }
+        self.topics
+            .iter()
+            .zip(&log.topics)
+            .all(|(topics, topic)| topics.as_ref().map_or(true, |topics| topics.contains(topic)))
+    }
 }
 
 /// All active filters
@@ -115,6 +440,31 @@ pub struct ActiveFilters {
     inner: Arc<Mutex<HashMap<FilterId, ActiveFilter>>>,
 }
 
+impl ActiveFilters {
+    /// Uninstalls the filters which were last polled longer than `ttl` ago.
+    async fn clear_stale(&self, ttl: Duration) {
+        let now = Instant::now();
+        self.inner.lock().await.retain(|id, filter| {
+            let is_stale = now.saturating_duration_since(filter.last_poll_timestamp) > ttl;
+            if is_stale {
+                trace!(target: "rpc::eth::filter", ?id, "uninstalled stale filter");
+            }
+            !is_stale
+        })
+    }
+}
+
+/// Uninstalls the stale filters every `ttl`, at most every second, until the filters are dropped.
+async fn clear_stale_filters(filters: Weak<Mutex<HashMap<FilterId, ActiveFilter>>>, ttl: Duration) {
+    let period = ttl.max(Duration::from_secs(1));
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    loop {
+        interval.tick().await;
+        let Some(inner) = filters.upgrade() else { return };
+        ActiveFilters { inner }.clear_stale(ttl).await;
+    }
+}
+
 /// An installed filter
 #[derive(Debug)]
 struct ActiveFilter {
@@ -126,10 +476,98 @@ struct ActiveFilter {
     kind: FilterKind,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 #[allow(clippy::large_enum_variant)] // stored on heap
 enum FilterKind {
     Log(Filter),
     Block,
-    PendingTransaction,
+    /// Receives the hashes of the transactions that became pending since the filter was installed.
+    PendingTransaction(Receiver<TxHash>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{bloom::logs_bloom, rpc, Bytes};
+    use reth_provider::test_utils::NoopProvider;
+    use reth_transaction_pool::test_utils::testing_pool;
+
+    fn log(address: Address, topics: Vec<H256>) -> reth_primitives::Log {
+        reth_primitives::Log { address, topics, data: Bytes::default() }
+    }
+
+    #[test]
+    fn log_filter_matches() {
+        let address = Address::random();
+        let (first, second) = (H256::random(), H256::random());
+        let filter = Filter {
+            address: Some(ValueOrArray::Value(rpc::H160(address.0))),
+            topics: [
+                None,
+                Some(ValueOrArray::Array(vec![
+                    Some(rpc::H256(first.0)),
+                    Some(rpc::H256(second.0)),
+                ])),
+                Some(ValueOrArray::Value(None)),
+                None,
+            ],
+            ..Default::default()
+        };
+        let filter = LogFilter::new(&filter);
+        assert_eq!(filter.addresses, vec![address]);
+        assert_eq!(filter.topics, vec![None, Some(vec![first, second])]);
+
+        assert!(filter.matches(&log(address, vec![H256::random(), second, H256::random()])));
+        assert!(!filter.matches(&log(address, vec![H256::random(), H256::random()])));
+        assert!(!filter.matches(&log(address, vec![first])));
+        assert!(!filter.matches(&log(Address::random(), vec![H256::random(), first])));
+
+        assert!(LogFilter::new(&Filter::default()).matches(&log(Address::random(), Vec::new())));
+    }
+
+    #[test]
+    fn log_filter_matches_bloom() {
+        let address = Address::random();
+        let topic = H256::random();
+        let bloom = logs_bloom([log(address, vec![topic])].iter());
+
+        let filter = LogFilter { addresses: vec![address], topics: vec![None, Some(vec![topic])] };
+        assert!(filter.matches_bloom(&bloom));
+        assert!(LogFilter::default().matches_bloom(&Bloom::zero()));
+
+        let filter = LogFilter { addresses: vec![Address::random()], topics: Vec::new() };
+        assert!(!filter.matches_bloom(&bloom));
+        let filter = LogFilter { addresses: Vec::new(), topics: vec![Some(vec![H256::random()])] };
+        assert!(!filter.matches_bloom(&bloom));
+    }
+
+    #[tokio::test]
+    async fn clear_stale_filters() {
+        let ttl = Duration::from_secs(60);
+        let config = EthFilterConfig { stale_filter_ttl: ttl, ..Default::default() };
+        let filter = EthFilter::new(NoopProvider::default(), testing_pool(), config);
+        let stale = filter.inner.install_filter(FilterKind::Block).await.unwrap();
+        let polled = filter.inner.install_filter(FilterKind::Block).await.unwrap();
+
+        let active_filters = filter.active_filters();
+        if let Some(since) = Instant::now().checked_sub(ttl * 2) {
+            active_filters.inner.lock().await.get_mut(&stale).unwrap().last_poll_timestamp = since;
+        }
+        active_filters.clear_stale(ttl).await;
+
+        let filters = active_filters.inner.lock().await;
+        assert!(!filters.contains_key(&stale));
+        assert!(filters.contains_key(&polled));
+    }
+
+    #[tokio::test]
+    async fn reject_too_many_blocks() {
+        let config = EthFilterConfig { max_blocks_per_filter: 10, ..Default::default() };
+        let filter = EthFilter::new(NoopProvider::default(), testing_pool(), config);
+        assert!(filter.inner.logs_in_range(&LogFilter::default(), 0, 9).unwrap().is_empty());
+        assert!(matches!(
+            filter.inner.logs_in_range(&LogFilter::default(), 0, 10),
+            Err(EthApiError::QueryExceedsMaxBlocks(10))
+        ));
+    }
 }
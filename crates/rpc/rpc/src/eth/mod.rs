@@ -6,6 +6,9 @@ mod filter;
 mod pubsub;
 mod signer;
 
-pub use api::{EthApi, EthApiSpec};
-pub use filter::EthFilter;
+pub use api::{EthApi, EthApiConfig, EthApiSpec, GasPriceOracleConfig, RPC_DEFAULT_GAS_CAP};
+pub use filter::{
+    EthFilter, EthFilterConfig, DEFAULT_MAX_BLOCKS_PER_FILTER, DEFAULT_MAX_LOGS_PER_RESPONSE,
+    DEFAULT_STALE_FILTER_TTL,
+};
 pub use pubsub::EthPubSub;
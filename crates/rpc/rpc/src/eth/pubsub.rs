@@ -1,51 +1,62 @@
 //! `eth_` PubSub RPC handler implementation
 
+use crate::eth::filter::{matching_receipt_logs, LogFilter};
+use futures::{future::ready, stream, Stream, StreamExt};
 use jsonrpsee::{types::SubscriptionResult, SubscriptionSink};
-use reth_primitives::{rpc::FilteredParams, TxHash};
-use reth_provider::BlockProvider;
+use reth_executor::blockchain_tree::{CanonStateNotification, CanonStateSubscriptions, Chain};
+use reth_network_api::NetworkInfo;
+use reth_primitives::{IntoRecoveredTransaction, TxHash};
 use reth_rpc_api::EthPubSubApiServer;
-use reth_rpc_types::pubsub::{
-    Params, SubscriptionKind, SubscriptionResult as EthSubscriptionResult,
+use reth_rpc_types::{
+    pubsub::{
+        Params, PubSubSyncStatus, SubscriptionKind, SubscriptionResult as EthSubscriptionResult,
+    },
+    Header, Log, Transaction,
 };
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
 use reth_transaction_pool::TransactionPool;
-use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
+use tracing::debug;
 
 /// `Eth` pubsub RPC implementation.
 ///
-/// This handles
-pub struct EthPubSub<Client, Pool> {
+/// This handles `eth_subscribe` requests for new headers and logs of the blocks that become
+/// canonical, transactions that become pending in the pool, and changes of the sync status.
+pub struct EthPubSub<Pool, Events, Network> {
     /// All nested fields bundled together.
-    inner: EthPubSubInner<Client, Pool>,
+    inner: EthPubSubInner<Pool, Events, Network>,
     /// The type that's used to spawn subscription tasks.
     subscription_task_spawner: Box<dyn TaskSpawner>,
 }
 
 // === impl EthPubSub ===
 
-impl<Client, Pool> EthPubSub<Client, Pool> {
+impl<Pool, Events, Network> EthPubSub<Pool, Events, Network> {
     /// Creates a new, shareable instance.
     ///
     /// Subscription tasks are spawned via [tokio::task::spawn]
-    pub fn new(client: Client, pool: Pool) -> Self {
-        Self::with_spawner(client, pool, Box::<TokioTaskExecutor>::default())
+    pub fn new(pool: Pool, chain_events: Events, network: Network) -> Self {
+        Self::with_spawner(pool, chain_events, network, Box::<TokioTaskExecutor>::default())
     }
 
     /// Creates a new, shareable instance.
     pub fn with_spawner(
-        client: Client,
         pool: Pool,
+        chain_events: Events,
+        network: Network,
         subscription_task_spawner: Box<dyn TaskSpawner>,
     ) -> Self {
-        let inner = EthPubSubInner { client, pool };
+        let inner = EthPubSubInner { pool, chain_events, network };
         Self { inner, subscription_task_spawner }
     }
 }
 
-impl<Client, Pool> EthPubSubApiServer for EthPubSub<Client, Pool>
+impl<Pool, Events, Network> EthPubSubApiServer for EthPubSub<Pool, Events, Network>
 where
-    Client: BlockProvider + Clone + 'static,
-    Pool: TransactionPool + 'static,
+    Pool: TransactionPool + Clone + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
+    Events: CanonStateSubscriptions + Clone + Send + Sync + 'static,
+    Network: NetworkInfo + Clone + 'static,
 {
     fn subscribe(
         &self,
@@ -65,41 +76,55 @@ where
 }
 
 /// The actual handler for and accepted [`EthPubSub::subscribe`] call.
-async fn handle_accepted<Client, Pool>(
-    pubsub: EthPubSubInner<Client, Pool>,
+async fn handle_accepted<Pool, Events, Network>(
+    pubsub: EthPubSubInner<Pool, Events, Network>,
     mut accepted_sink: SubscriptionSink,
     kind: SubscriptionKind,
     params: Option<Params>,
 ) where
-    Client: BlockProvider + 'static,
     Pool: TransactionPool + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
+    Events: CanonStateSubscriptions + 'static,
+    Network: NetworkInfo + 'static,
 {
-    // if no params are provided, used default filter params
-    let _params = match params {
-        Some(Params::Logs(filter)) => FilteredParams::new(Some(*filter)),
-        _ => FilteredParams::default(),
-    };
-
     match kind {
         SubscriptionKind::NewHeads => {
-            // TODO subscribe new blocks -> convert
+            let stream = pubsub
+                .into_new_headers_stream()
+                .map(|header| EthSubscriptionResult::Header(Box::new(header.into())));
+            accepted_sink.pipe_from_stream(stream).await;
         }
         SubscriptionKind::Logs => {
-            // TODO subscribe new blocks -> fetch logs via bloom
+            // if no params are provided, all logs are matched
+            let filter = match params {
+                Some(Params::Logs(filter)) => LogFilter::new(&filter),
+                _ => LogFilter::default(),
+            };
+            let stream =
+                pubsub.into_log_stream(filter).map(|log| EthSubscriptionResult::Log(Box::new(log)));
+            accepted_sink.pipe_from_stream(stream).await;
         }
         SubscriptionKind::NewPendingTransactions => {
-            let stream = pubsub
-                .into_pending_transaction_stream()
-                .map(EthSubscriptionResult::TransactionHash);
-            accepted_sink.pipe_from_stream(stream).await;
+            if let Some(Params::Bool(true)) = params {
+                let stream = pubsub
+                    .into_full_pending_transaction_stream()
+                    .map(|tx| EthSubscriptionResult::FullTransaction(Box::new(tx)));
+                accepted_sink.pipe_from_stream(stream).await;
+            } else {
+                let stream = pubsub
+                    .into_pending_transaction_stream()
+                    .map(EthSubscriptionResult::TransactionHash);
+                accepted_sink.pipe_from_stream(stream).await;
+            }
         }
         SubscriptionKind::Syncing => {
-            // TODO subscribe new blocks -> read is_syncing from network
+            let stream = pubsub.into_sync_status_stream().map(EthSubscriptionResult::SyncState);
+            accepted_sink.pipe_from_stream(stream).await;
         }
     }
 }
 
-impl<Client, Pool> std::fmt::Debug for EthPubSub<Client, Pool> {
+impl<Pool, Events, Network> std::fmt::Debug for EthPubSub<Pool, Events, Network> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("EthPubSub").finish_non_exhaustive()
     }
@@ -107,22 +132,211 @@ impl<Client, Pool> std::fmt::Debug for EthPubSub<Client, Pool> {
 
 /// Container type `EthPubSub`
 #[derive(Clone)]
-struct EthPubSubInner<Client, Pool> {
+struct EthPubSubInner<Pool, Events, Network> {
     /// The transaction pool.
     pool: Pool,
-    /// The client that can interact with the chain.
-    client: Client,
+    /// Notifies about the changes of the canonical chain.
+    chain_events: Events,
+    /// The network, to read the sync status from.
+    network: Network,
 }
 
 // == impl EthPubSubInner ===
 
-impl<Client, Pool> EthPubSubInner<Client, Pool>
+impl<Pool, Events, Network> EthPubSubInner<Pool, Events, Network>
 where
-    Client: BlockProvider + 'static,
     Pool: TransactionPool + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
+    Events: CanonStateSubscriptions + 'static,
+    Network: NetworkInfo + 'static,
 {
     /// Returns a stream that yields all transactions emitted by the txpool.
     fn into_pending_transaction_stream(self) -> impl Stream<Item = TxHash> {
         ReceiverStream::new(self.pool.pending_transactions_listener())
     }
+
+    /// Returns a stream that yields the full transactions that become pending in the txpool.
+    fn into_full_pending_transaction_stream(self) -> impl Stream<Item = Transaction> {
+        let pool = self.pool;
+        ReceiverStream::new(pool.pending_transactions_listener()).filter_map(move |hash| {
+            // the transaction may have been mined or replaced in the meantime
+            let tx = pool
+                .get(&hash)
+                .map(|tx| Transaction::from_recovered(tx.transaction.to_recovered_transaction()));
+            ready(tx)
+        })
+    }
+
+    /// Returns a stream that yields the headers of the blocks that become canonical.
+    fn into_new_headers_stream(self) -> impl Stream<Item = Header> {
+        self.canon_state_stream().flat_map(|notification| {
+            let headers = notification
+                .committed()
+                .blocks()
+                .iter()
+                .map(|block| {
+                    Header::from_primitive_with_hash(block.header.clone().unseal(), block.hash())
+                })
+                .collect::<Vec<_>>();
+            stream::iter(headers)
+        })
+    }
+
+    /// Returns a stream that yields the logs matching the filter of the blocks that become
+    /// canonical.
+    ///
+    /// On a reorg, the logs of the reverted blocks are yielded first, marked as removed.
+    fn into_log_stream(self, filter: LogFilter) -> impl Stream<Item = Log> {
+        self.canon_state_stream().flat_map(move |notification| {
+            let mut logs = Vec::new();
+            if let Some(reverted) = notification.reverted() {
+                logs.extend(chain_logs(&filter, &reverted, true));
+            }
+            logs.extend(chain_logs(&filter, &notification.committed(), false));
+            stream::iter(logs)
+        })
+    }
+
+    /// Returns a stream that yields the current sync status, and then the sync status whenever
+    /// it changes, checked each time a block becomes canonical.
+    fn into_sync_status_stream(self) -> impl Stream<Item = PubSubSyncStatus> {
+        let notifications = self.canon_state_stream();
+        let network = self.network;
+        let mut is_syncing = network.is_syncing();
+        let changes = notifications.filter_map(move |_| {
+            let syncing = network.is_syncing();
+            let changed = syncing != is_syncing;
+            is_syncing = syncing;
+            ready(changed.then_some(PubSubSyncStatus::Simple(syncing)))
+        });
+        stream::once(ready(PubSubSyncStatus::Simple(is_syncing))).chain(changes)
+    }
+
+    /// Returns a stream of the changes of the canonical chain.
+    ///
+    /// Notifications the subscription lagged behind on are skipped.
+    fn canon_state_stream(&self) -> impl Stream<Item = CanonStateNotification> {
+        BroadcastStream::new(self.chain_events.subscribe_to_canonical_state()).filter_map(
+            |notification| {
+                if let Err(err) = &notification {
+                    debug!(
+                        target: "rpc::eth::pubsub",
+                        ?err,
+                        "Skipped canonical state notifications"
+                    );
+                }
+                ready(notification.ok())
+            },
+        )
+    }
+}
+
+/// Returns the logs of the blocks of the chain that match the filter, in the order the blocks
+/// were committed, or in reverse order if they were `removed`.
+fn chain_logs(filter: &LogFilter, chain: &Chain, removed: bool) -> Vec<Log> {
+    let mut blocks = chain.blocks().iter().zip(chain.results()).collect::<Vec<_>>();
+    if removed {
+        blocks.reverse();
+    }
+    blocks
+        .into_iter()
+        .filter(|(block, _)| filter.matches_bloom(&block.header.logs_bloom))
+        .flat_map(|(block, result)| {
+            matching_receipt_logs(
+                filter,
+                block.number,
+                block.hash(),
+                &block.body,
+                result.tx_changesets.iter().map(|changeset| &changeset.receipt),
+                removed,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_executor::{
+        blockchain_tree::CanonStateNotificationSender,
+        execution_result::{ExecutionResult, TransactionChangeSet},
+    };
+    use reth_network_api::test_utils::NoopNetwork;
+    use reth_primitives::{
+        Header as PrimitiveHeader, Log as PrimitiveLog, Receipt, SealedBlock, TransactionSigned,
+        H256,
+    };
+    use reth_transaction_pool::test_utils::testing_pool;
+    use std::sync::Arc;
+
+    /// A chain of a block on top of the genesis block, with a transaction that emits a log.
+    fn chain(log: PrimitiveLog) -> Chain {
+        let header = PrimitiveHeader { number: 1, ..Default::default() };
+        let block = SealedBlock {
+            header: header.seal_slow(),
+            body: vec![TransactionSigned::default()],
+            ..Default::default()
+        };
+        let receipt = Receipt { logs: vec![log], ..Default::default() };
+        let result = ExecutionResult {
+            tx_changesets: vec![TransactionChangeSet {
+                receipt,
+                changeset: Default::default(),
+                new_bytecodes: Default::default(),
+            }],
+            block_changesets: Default::default(),
+        };
+        Chain::new((0, H256::zero()), block, result)
+    }
+
+    fn pubsub(
+        chain_events: &CanonStateNotificationSender,
+    ) -> EthPubSubInner<
+        reth_transaction_pool::test_utils::TestPool,
+        CanonStateNotificationSender,
+        NoopNetwork,
+    > {
+        EthPubSubInner {
+            pool: testing_pool(),
+            chain_events: chain_events.clone(),
+            network: NoopNetwork,
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_new_heads_and_sync_status() {
+        let chain_events = CanonStateNotificationSender::default();
+        let pubsub = pubsub(&chain_events);
+        let mut headers = pubsub.clone().into_new_headers_stream();
+        let mut sync_status = pubsub.into_sync_status_stream();
+
+        let new = Arc::new(chain(PrimitiveLog::default()));
+        let hash = new.tip().hash();
+        chain_events.notify(CanonStateNotification::Commit { new });
+
+        let header = headers.next().await.unwrap();
+        assert_eq!(header.hash, Some(hash));
+        assert_eq!(header.number, Some(reth_primitives::U256::from(1)));
+        assert_eq!(sync_status.next().await, Some(PubSubSyncStatus::Simple(false)));
+    }
+
+    #[tokio::test]
+    async fn stream_removed_logs_on_reorg() {
+        let chain_events = CanonStateNotificationSender::default();
+        let mut logs = pubsub(&chain_events).into_log_stream(LogFilter::default());
+
+        let old = PrimitiveLog { topics: vec![H256::random()], ..Default::default() };
+        let new = PrimitiveLog { topics: vec![H256::random()], ..Default::default() };
+        chain_events.notify(CanonStateNotification::Reorg {
+            old: Arc::new(chain(old.clone())),
+            new: Arc::new(chain(new.clone())),
+        });
+
+        let removed = logs.next().await.unwrap();
+        assert!(removed.removed);
+        assert_eq!(removed.topics, old.topics);
+        let added = logs.next().await.unwrap();
+        assert!(!added.removed);
+        assert_eq!(added.topics, new.topics);
+    }
 }
@@ -3,9 +3,11 @@ use http::{HeaderMap, Response};
 mod auth_layer;
 mod jwt_secret;
 mod jwt_validator;
+mod rate_limit;
 pub use auth_layer::AuthLayer;
 pub use jwt_secret::{JwtError, JwtSecret};
 pub use jwt_validator::JwtAuthValidator;
+pub use rate_limit::{RateLimitConfig, RateLimitLayer, RateLimitService};
 
 /// General purpose trait to validate Http Authorization
 /// headers. It's supposed to be integrated as a validator
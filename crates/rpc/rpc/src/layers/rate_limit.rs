@@ -0,0 +1,348 @@
+use http::{header::CONTENT_TYPE, Request, Response, StatusCode};
+use hyper::{body::HttpBody, Body};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+
+/// The error code of requests that exceed a limit, see EIP-1474.
+const LIMIT_EXCEEDED_CODE: i32 = -32005;
+
+/// The window the rates of requests are measured in.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+
+/// The default maximum size of a request whose calls are counted: 15MB.
+const DEFAULT_MAX_REQUEST_BODY_SIZE: usize = 15 * 1024 * 1024;
+
+/// The limits of the [RateLimitLayer].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// The maximum number of requests a connection can make per second, unlimited if `None`.
+    pub max_requests_per_connection: Option<u32>,
+    /// The maximum number of calls of a method per second, across all connections.
+    ///
+    /// Every call of a batch request counts.
+    pub max_calls_per_method: HashMap<String, u32>,
+    /// The maximum size of a request, if calls of methods are limited.
+    ///
+    /// Requests are read to count their calls before they're passed on to the server, so this
+    /// should match the limit of the server.
+    pub max_request_body_size: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_requests_per_connection: None,
+            max_calls_per_method: Default::default(),
+            max_request_body_size: DEFAULT_MAX_REQUEST_BODY_SIZE,
+        }
+    }
+}
+
+/// This is an Http and IPC middleware layer that limits the rate of requests per connection, and
+/// of the calls of the configured methods across all connections.
+///
+/// Http requests that exceed a limit are rejected with a `429 Too Many Requests` response, IPC
+/// requests with a JSON-RPC error.
+///
+/// The limit per connection applies to each service the layer creates, i.e. jsonrpsee applies it
+/// per connection.
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    config: Arc<RateLimitConfig>,
+    /// The windows of the limited methods, shared by all connections.
+    method_windows: Arc<Mutex<HashMap<String, Window>>>,
+}
+
+impl RateLimitLayer {
+    /// Creates an instance of [`RateLimitLayer`][crate::layers::RateLimitLayer] with the given
+    /// limits.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self { config: Arc::new(config), method_windows: Default::default() }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            config: self.config.clone(),
+            method_windows: self.method_windows.clone(),
+            connection_window: Default::default(),
+            inner,
+        }
+    }
+}
+
+/// This type is the actual implementation of the middleware. It follows the
+/// [`Service`](tower::Service) specification to proxy the Http and IPC requests within the limits
+/// to its inner service.
+#[derive(Debug, Clone)]
+pub struct RateLimitService<S> {
+    config: Arc<RateLimitConfig>,
+    method_windows: Arc<Mutex<HashMap<String, Window>>>,
+    /// The window of the connection, shared by the clones of the service.
+    connection_window: Arc<Mutex<Window>>,
+    /// Recipient of the Http requests within the limits
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: From<hyper::Error> + Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if !self.count_request() {
+            return Box::pin(async { Ok(limit_exceeded()) })
+        }
+
+        if self.config.max_calls_per_method.is_empty() {
+            return Box::pin(self.inner.call(req))
+        }
+
+        // The service that was polled ready is taken, and replaced by a clone.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let config = self.config.clone();
+        let method_windows = self.method_windows.clone();
+        Box::pin(async move {
+            let (parts, mut body) = req.into_parts();
+            let mut bytes = Vec::new();
+            while let Some(chunk) = body.data().await {
+                bytes.extend_from_slice(&chunk?);
+                if bytes.len() > config.max_request_body_size {
+                    return Ok(error_response(StatusCode::PAYLOAD_TOO_LARGE, "request too large"))
+                }
+            }
+
+            if !count_method_calls(&config, &method_windows, &bytes) {
+                return Ok(limit_exceeded())
+            }
+
+            inner.call(Request::from_parts(parts, Body::from(bytes))).await
+        })
+    }
+}
+
+impl<S> Service<String> for RateLimitService<S>
+where
+    S: Service<String, Response = String> + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = String;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: String) -> Self::Future {
+        if !self.count_request() ||
+            !count_method_calls(&self.config, &self.method_windows, req.as_bytes())
+        {
+            return Box::pin(async { Ok(limit_exceeded_error("rate limit exceeded")) })
+        }
+        Box::pin(self.inner.call(req))
+    }
+}
+
+impl<S> RateLimitService<S> {
+    /// Counts a request of the connection.
+    ///
+    /// Returns whether the request is within the limit of the connection.
+    fn count_request(&self) -> bool {
+        match self.config.max_requests_per_connection {
+            Some(limit) => self.connection_window.lock().unwrap().try_count(1, limit),
+            None => true,
+        }
+    }
+}
+
+/// Counts the calls of the limited methods in the request.
+///
+/// Returns whether all calls are within the limits of their methods.
+fn count_method_calls(
+    config: &RateLimitConfig,
+    method_windows: &Mutex<HashMap<String, Window>>,
+    request: &[u8],
+) -> bool {
+    if config.max_calls_per_method.is_empty() {
+        return true
+    }
+    let mut windows = method_windows.lock().unwrap();
+    count_calls(request).into_iter().all(|(method, calls)| {
+        match config.max_calls_per_method.get(&method) {
+            Some(&limit) => windows.entry(method).or_default().try_count(calls, limit),
+            None => true,
+        }
+    })
+}
+
+/// Counts the requests of the current window.
+#[derive(Debug)]
+struct Window {
+    start: Instant,
+    count: u32,
+}
+
+impl Window {
+    /// Counts the given number of requests, unless that exceeds the limit.
+    ///
+    /// Returns whether the requests are within the limit.
+    fn try_count(&mut self, requests: u32, limit: u32) -> bool {
+        let now = Instant::now();
+        if now.duration_since(self.start) >= RATE_LIMIT_WINDOW {
+            self.start = now;
+            self.count = 0;
+        }
+        if self.count.saturating_add(requests) > limit {
+            return false
+        }
+        self.count += requests;
+        true
+    }
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self { start: Instant::now(), count: 0 }
+    }
+}
+
+/// The method of a call.
+#[derive(Deserialize)]
+struct Call {
+    method: String,
+}
+
+/// A single or batch request.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Calls {
+    Single(Call),
+    Batch(Vec<Call>),
+}
+
+/// Returns the number of calls of each method in the request.
+///
+/// Invalid requests have no calls, they are rejected by the server.
+fn count_calls(body: &[u8]) -> HashMap<String, u32> {
+    let calls = match serde_json::from_slice(body) {
+        Ok(Calls::Single(call)) => vec![call],
+        Ok(Calls::Batch(calls)) => calls,
+        Err(_) => Vec::new(),
+    };
+
+    let mut counts = HashMap::<_, u32>::new();
+    for call in calls {
+        *counts.entry(call.method).or_default() += 1;
+    }
+    counts
+}
+
+/// The response to requests that exceed a limit.
+fn limit_exceeded() -> Response<Body> {
+    error_response(StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded")
+}
+
+/// Creates a response with the status and a JSON-RPC error with the message.
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(limit_exceeded_error(message)))
+        .expect("response is valid")
+}
+
+/// Creates a JSON-RPC error with the message.
+fn limit_exceeded_error(message: &str) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","error":{{"code":{LIMIT_EXCEEDED_CODE},"message":"{message}"}},"id":null}}"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_limits_requests() {
+        let mut window = Window::default();
+        assert!(window.try_count(2, 3));
+        assert!(!window.try_count(2, 3));
+        assert!(window.try_count(1, 3));
+        assert!(!window.try_count(1, 3));
+
+        window.start -= RATE_LIMIT_WINDOW;
+        assert!(window.try_count(3, 3));
+    }
+
+    #[test]
+    fn counts_calls_per_method() {
+        let single = br#"{"jsonrpc":"2.0","id":1,"method":"eth_getLogs","params":[{}]}"#;
+        assert_eq!(count_calls(single), HashMap::from([("eth_getLogs".to_string(), 1)]));
+
+        let batch = br#"[
+            {"jsonrpc":"2.0","id":1,"method":"eth_getLogs","params":[{}]},
+            {"jsonrpc":"2.0","id":2,"method":"eth_blockNumber"},
+            {"jsonrpc":"2.0","id":3,"method":"eth_getLogs","params":[{}]}
+        ]"#;
+        assert_eq!(
+            count_calls(batch),
+            HashMap::from([("eth_getLogs".to_string(), 2), ("eth_blockNumber".to_string(), 1)])
+        );
+
+        assert!(count_calls(b"invalid").is_empty());
+    }
+
+    /// Echoes the IPC requests.
+    #[derive(Clone)]
+    struct Echo;
+
+    impl Service<String> for Echo {
+        type Response = String;
+        type Error = std::convert::Infallible;
+        type Future = std::future::Ready<Result<String, Self::Error>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: String) -> Self::Future {
+            std::future::ready(Ok(req))
+        }
+    }
+
+    #[tokio::test]
+    async fn limits_ipc_requests() {
+        let mut config = RateLimitConfig::default();
+        config.max_calls_per_method.insert("eth_getLogs".to_string(), 1);
+        let mut service = RateLimitLayer::new(config).layer(Echo);
+
+        let limited = r#"{"jsonrpc":"2.0","id":1,"method":"eth_getLogs","params":[{}]}"#;
+        let unlimited = r#"{"jsonrpc":"2.0","id":2,"method":"eth_blockNumber"}"#;
+        assert_eq!(service.call(limited.to_string()).await.unwrap(), limited);
+        assert!(service.call(limited.to_string()).await.unwrap().contains("rate limit exceeded"));
+        assert_eq!(service.call(unlimited.to_string()).await.unwrap(), unlimited);
+    }
+}
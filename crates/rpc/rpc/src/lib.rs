@@ -12,21 +12,32 @@
 //! Provides the implementation of all RPC interfaces.
 
 mod admin;
+mod call_guard;
 mod debug;
 mod engine;
 mod eth;
 mod layers;
 mod net;
 mod trace;
+mod txpool;
 mod web3;
 
 pub use admin::AdminApi;
+pub use call_guard::{TracingCallGuard, DEFAULT_MAX_TRACING_REQUESTS};
 pub use debug::DebugApi;
 pub use engine::EngineApi;
-pub use eth::{EthApi, EthApiSpec, EthFilter, EthPubSub};
-pub use layers::{AuthLayer, AuthValidator, JwtAuthValidator, JwtError, JwtSecret};
+pub use eth::{
+    EthApi, EthApiConfig, EthApiSpec, EthFilter, EthFilterConfig, EthPubSub, GasPriceOracleConfig,
+    DEFAULT_MAX_BLOCKS_PER_FILTER, DEFAULT_MAX_LOGS_PER_RESPONSE, DEFAULT_STALE_FILTER_TTL,
+    RPC_DEFAULT_GAS_CAP,
+};
+pub use layers::{
+    AuthLayer, AuthValidator, JwtAuthValidator, JwtError, JwtSecret, RateLimitConfig,
+    RateLimitLayer, RateLimitService,
+};
 pub use net::NetApi;
 pub use trace::TraceApi;
+pub use txpool::TxPoolApi;
 pub use web3::Web3Api;
 
 pub(crate) mod result;
@@ -0,0 +1,220 @@
+//! A revm [Inspector] that records the calls, creates and selfdestructs of a transaction as
+//! parity-style traces.
+
+use reth_primitives::U64;
+use reth_rpc_types::trace::parity::{
+    Action, CallAction, CallOutput, CallType, CreateAction, CreateOutput, SelfdestructAction,
+    TraceOutput, TraceResult, TransactionTrace,
+};
+use revm::{
+    interpreter::{
+        opcode, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult, Interpreter,
+    },
+    primitives::{Bytes as RevmBytes, B160},
+    Database, EVMData, Inspector,
+};
+
+/// A call, create or selfdestruct of the transaction.
+#[derive(Debug)]
+struct TraceNode {
+    action: Action,
+    /// The result of the call or create, `None` while it's executing and for selfdestructs.
+    result: Option<TraceResult>,
+    /// Indices of the calls, creates and selfdestructs made by this call or create.
+    children: Vec<usize>,
+}
+
+/// Records the call tree of a transaction.
+///
+/// The traces are returned in depth-first order by [ParityTracer::into_traces], with the path of
+/// indices that leads to them from the top-level call.
+#[derive(Debug, Default)]
+pub(crate) struct ParityTracer {
+    nodes: Vec<TraceNode>,
+    /// The calls and creates that are executing, innermost last.
+    stack: Vec<usize>,
+}
+
+impl ParityTracer {
+    /// Returns the traces of the transaction.
+    pub(crate) fn into_traces(self) -> Vec<TransactionTrace> {
+        let mut traces = Vec::with_capacity(self.nodes.len());
+        if !self.nodes.is_empty() {
+            self.push_traces(0, Vec::new(), &mut traces);
+        }
+        traces
+    }
+
+    fn push_traces(
+        &self,
+        index: usize,
+        trace_address: Vec<usize>,
+        traces: &mut Vec<TransactionTrace>,
+    ) {
+        let node = &self.nodes[index];
+        traces.push(TransactionTrace {
+            trace_address: trace_address.clone(),
+            subtraces: node.children.len(),
+            action: node.action.clone(),
+            result: node.result.clone(),
+        });
+        for (position, child) in node.children.iter().enumerate() {
+            let mut child_address = trace_address.clone();
+            child_address.push(position);
+            self.push_traces(*child, child_address, traces);
+        }
+    }
+
+    /// Adds a node as a child of the executing call, and returns its index.
+    fn push_node(&mut self, action: Action) -> usize {
+        let index = self.nodes.len();
+        if let Some(parent) = self.stack.last() {
+            self.nodes[*parent].children.push(index);
+        }
+        self.nodes.push(TraceNode { action, result: None, children: Vec::new() });
+        index
+    }
+
+    /// Sets the result of the innermost executing call or create.
+    fn pop_node(&mut self, result: TraceResult) {
+        if let Some(index) = self.stack.pop() {
+            self.nodes[index].result = Some(result);
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for ParityTracer {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> InstructionResult {
+        // Selfdestructs are recorded before they are executed, since the balance of the contract
+        // is transferred to the refund address.
+        if interp.current_opcode() == opcode::SELFDESTRUCT {
+            if let Ok(refund_address) = interp.stack.peek(0) {
+                let address = interp.contract.address;
+                let balance = data
+                    .journaled_state
+                    .state
+                    .get(&address)
+                    .map(|account| account.info.balance)
+                    .unwrap_or_default();
+                self.push_node(Action::Selfdestruct(SelfdestructAction {
+                    address,
+                    refund_address: B160::from_slice(&refund_address.to_be_bytes::<32>()[12..]),
+                    balance,
+                }));
+            }
+        }
+        InstructionResult::Continue
+    }
+
+    fn call(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+        _is_static: bool,
+    ) -> (InstructionResult, Gas, RevmBytes) {
+        let (call_type, to) = match inputs.context.scheme {
+            CallScheme::Call => (CallType::Call, inputs.contract),
+            CallScheme::StaticCall => (CallType::StaticCall, inputs.contract),
+            CallScheme::CallCode => (CallType::CallCode, inputs.context.code_address),
+            CallScheme::DelegateCall => (CallType::DelegateCall, inputs.context.code_address),
+        };
+        let index = self.push_node(Action::Call(CallAction {
+            from: inputs.context.caller,
+            to,
+            value: inputs.context.apparent_value,
+            gas: U64::from(inputs.gas_limit),
+            input: inputs.input.clone().into(),
+            call_type,
+        }));
+        self.stack.push(index);
+        (InstructionResult::Continue, Gas::new(0), RevmBytes::new())
+    }
+
+    fn call_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CallInputs,
+        remaining_gas: Gas,
+        ret: InstructionResult,
+        out: RevmBytes,
+        _is_static: bool,
+    ) -> (InstructionResult, Gas, RevmBytes) {
+        let result = match trace_error(ret) {
+            None => TraceResult::Success {
+                result: TraceOutput::Call(CallOutput {
+                    gas_used: U64::from(remaining_gas.spend()),
+                    output: out.clone().into(),
+                }),
+            },
+            Some(error) => TraceResult::Error { error },
+        };
+        self.pop_node(result);
+        (ret, remaining_gas, out)
+    }
+
+    fn create(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CreateInputs,
+    ) -> (InstructionResult, Option<B160>, Gas, RevmBytes) {
+        let index = self.push_node(Action::Create(CreateAction {
+            from: inputs.caller,
+            value: inputs.value,
+            gas: U64::from(inputs.gas_limit),
+            init: inputs.init_code.clone().into(),
+        }));
+        self.stack.push(index);
+        (InstructionResult::Continue, None, Gas::new(0), RevmBytes::default())
+    }
+
+    fn create_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CreateInputs,
+        ret: InstructionResult,
+        address: Option<B160>,
+        remaining_gas: Gas,
+        out: RevmBytes,
+    ) -> (InstructionResult, Option<B160>, Gas, RevmBytes) {
+        let result = match (trace_error(ret), address) {
+            (None, Some(address)) => TraceResult::Success {
+                result: TraceOutput::Create(CreateOutput {
+                    gas_used: U64::from(remaining_gas.spend()),
+                    code: out.clone().into(),
+                    address,
+                }),
+            },
+            (error, _) => TraceResult::Error { error: error.unwrap_or_else(|| format!("{ret:?}")) },
+        };
+        self.pop_node(result);
+        (ret, address, remaining_gas, out)
+    }
+}
+
+/// Returns the parity error message of a call or create that failed with the result, or `None` if
+/// it succeeded.
+fn trace_error(ret: InstructionResult) -> Option<String> {
+    let error = match ret {
+        InstructionResult::Continue |
+        InstructionResult::Stop |
+        InstructionResult::Return |
+        InstructionResult::SelfDestruct => return None,
+        InstructionResult::Revert => "Reverted",
+        InstructionResult::OutOfGas |
+        InstructionResult::MemoryOOG |
+        InstructionResult::MemoryLimitOOG |
+        InstructionResult::PrecompileOOG |
+        InstructionResult::InvalidOperandOOG => "Out of gas",
+        InstructionResult::OpcodeNotFound | InstructionResult::InvalidFEOpcode => "Bad instruction",
+        InstructionResult::InvalidJump => "Bad jump destination",
+        InstructionResult::StackUnderflow => "Stack underflow",
+        InstructionResult::StackOverflow => "Out of stack",
+        ret => return Some(format!("{ret:?}")),
+    };
+    Some(error.to_string())
+}
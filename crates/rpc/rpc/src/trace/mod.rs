@@ -0,0 +1,443 @@
+use crate::{
+    eth::error::{EthApiError, EthResult},
+    result::internal_rpc_err,
+    EthApi, TracingCallGuard,
+};
+use async_trait::async_trait;
+use inspector::ParityTracer;
+use jsonrpsee::core::RpcResult as Result;
+use reth_executor::revm_wrap::{fill_tx_env, SubState};
+use reth_primitives::{rpc::BlockId, Address, BlockNumber, Bytes, Header, H256, U64};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProvider, StateProviderFactory,
+    TransactionsProvider,
+};
+use reth_rpc_api::TraceApiServer;
+use reth_rpc_types::{
+    trace::{filter::TraceFilter, parity::*},
+    CallRequest, Index,
+};
+use revm::{
+    db::DatabaseCommit,
+    primitives::{Env, ExecutionResult, Output, ResultAndState},
+    EVM,
+};
+use std::collections::HashSet;
+
+mod inspector;
+
+/// The maximum number of blocks a `trace_filter` request can re-execute.
+const MAX_TRACE_FILTER_BLOCKS: u64 = 100;
+
+/// `trace` API implementation.
+///
+/// This type provides the functionality for handling `trace` related requests.
+///
+/// Traces are produced by re-executing the transactions on top of the historical state they were
+/// executed on.
+pub struct TraceApi<Client, Pool, Network> {
+    /// The implementation of `eth` API
+    eth: EthApi<Client, Pool, Network>,
+    /// Limits the number of tracing requests that are served concurrently
+    tracing_call_guard: TracingCallGuard,
+}
+
+// === impl TraceApi ===
+
+impl<Client, Pool, Network> TraceApi<Client, Pool, Network> {
+    /// Create a new instance of the [TraceApi]
+    pub fn new(eth: EthApi<Client, Pool, Network>, tracing_call_guard: TracingCallGuard) -> Self {
+        Self { eth, tracing_call_guard }
+    }
+}
+
+impl<Client, Pool, Network> TraceApi<Client, Pool, Network>
+where
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + TransactionsProvider
+        + ReceiptProvider
+        + 'static,
+{
+    /// Executes the request on top of the state of the given block and traces it.
+    fn trace_call(
+        &self,
+        request: CallRequest,
+        trace_types: HashSet<TraceType>,
+        block_id: Option<BlockId>,
+    ) -> EthResult<TraceResults> {
+        let (env, mut db) = self.eth.prepare_call(request, block_id)?;
+        let (ResultAndState { result, .. }, traces) = inspect(&mut db, env)?;
+        Ok(trace_results(&result, traces, &trace_types))
+    }
+
+    /// Re-executes the transactions of the block with the given number and traces them.
+    ///
+    /// If `last` is given, only the transactions up to and including the transaction at that
+    /// index are executed.
+    ///
+    /// Returns `None` if the block does not exist.
+    fn replay_block(
+        &self,
+        number: BlockNumber,
+        last: Option<usize>,
+    ) -> EthResult<Option<ReplayedBlock>> {
+        let Some(block) = self.eth.client().block(BlockId::Number(number.into()))? else {
+            return Ok(None)
+        };
+        let (mut env, mut db) = self.eth.prepare_block_replay(&block.header)?;
+
+        let count = last.map_or(block.body.len(), |last| last + 1);
+        let mut transactions = Vec::with_capacity(count.min(block.body.len()));
+        for (position, tx) in block.body.iter().take(count).enumerate() {
+            let sender = tx.recover_signer().ok_or(EthApiError::InvalidTransactionSignature)?;
+            fill_tx_env(&mut env.tx, tx, sender);
+
+            // Later transactions of the block are executed on top of the changes of this one.
+            let (ResultAndState { result, state }, traces) = inspect(&mut db, env.clone())?;
+            db.commit(state);
+
+            transactions.push(ReplayedTransaction { hash: tx.hash, position, result, traces });
+        }
+
+        Ok(Some(ReplayedBlock {
+            hash: block.header.hash_slow(),
+            header: block.header,
+            transactions,
+        }))
+    }
+
+    /// Returns the traces of all transactions of the block with the given id.
+    fn trace_block(&self, block_id: BlockId) -> EthResult<Option<Vec<LocalizedTransactionTrace>>> {
+        match self.eth.client().block_number_for_id(block_id)? {
+            Some(number) => self.block_traces(number),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the traces of all transactions of the block with the given number.
+    fn block_traces(
+        &self,
+        number: BlockNumber,
+    ) -> EthResult<Option<Vec<LocalizedTransactionTrace>>> {
+        Ok(self.replay_block(number, None)?.map(ReplayedBlock::into_localized_traces))
+    }
+
+    /// Returns the traces of the mined transaction with the given hash.
+    fn transaction_localized_traces(
+        &self,
+        hash: H256,
+    ) -> EthResult<Option<Vec<LocalizedTransactionTrace>>> {
+        let Some((block, index)) = self.eth.transaction_location(hash)? else { return Ok(None) };
+        let Some(mut replayed) = self.replay_block(block.number, Some(index))? else {
+            return Ok(None)
+        };
+        // Only the traces of the last executed transaction are requested.
+        replayed.transactions.drain(..index);
+        Ok(Some(replayed.into_localized_traces()))
+    }
+
+    /// Re-executes the mined transaction with the given hash and traces it.
+    fn replay_transaction(
+        &self,
+        hash: H256,
+        trace_types: HashSet<TraceType>,
+    ) -> EthResult<TraceResults> {
+        let (block, index) =
+            self.eth.transaction_location(hash)?.ok_or(EthApiError::TransactionNotFound)?;
+        let tx = self
+            .replay_block(block.number, Some(index))?
+            .and_then(|mut replayed| replayed.transactions.pop())
+            .ok_or(EthApiError::TransactionNotFound)?;
+        Ok(trace_results(&tx.result, tx.traces, &trace_types))
+    }
+
+    /// Re-executes the transactions of the block with the given id and traces them.
+    fn replay_block_transactions(
+        &self,
+        block_id: BlockId,
+        trace_types: HashSet<TraceType>,
+    ) -> EthResult<Option<Vec<TraceResultsWithTransactionHash>>> {
+        let Some(number) = self.eth.client().block_number_for_id(block_id)? else {
+            return Ok(None)
+        };
+        let Some(replayed) = self.replay_block(number, None)? else { return Ok(None) };
+        let traces = replayed
+            .transactions
+            .into_iter()
+            .map(|tx| TraceResultsWithTransactionHash {
+                full_trace: trace_results(&tx.result, tx.traces, &trace_types),
+                transaction_hash: tx.hash,
+            })
+            .collect();
+        Ok(Some(traces))
+    }
+
+    /// Returns the traces of the blocks in the range of the filter that match its addresses.
+    fn filter_traces(&self, filter: TraceFilter) -> EthResult<Vec<LocalizedTransactionTrace>> {
+        let to_block = match filter.to_block {
+            Some(to_block) => to_block,
+            None => self.eth.client().chain_info()?.best_number,
+        };
+        let from_block = filter.from_block.unwrap_or(to_block);
+        if from_block > to_block {
+            return Err(EthApiError::InvalidBlockRange)
+        }
+        if to_block - from_block >= MAX_TRACE_FILTER_BLOCKS {
+            return Err(EthApiError::QueryExceedsMaxBlocks(MAX_TRACE_FILTER_BLOCKS))
+        }
+
+        let mut traces = Vec::new();
+        for number in from_block..=to_block {
+            let Some(block_traces) = self.block_traces(number)? else { break };
+            traces.extend(block_traces.into_iter().filter(|trace| {
+                matches_addresses(filter.from_address.as_deref(), trace_from(&trace.trace)) &&
+                    matches_addresses(filter.to_address.as_deref(), trace_to(&trace.trace))
+            }));
+        }
+
+        let traces = traces.into_iter().skip(filter.after.unwrap_or_default());
+        Ok(match filter.count {
+            Some(count) => traces.take(count).collect(),
+            None => traces.collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl<Client, Pool, Network> TraceApiServer for TraceApi<Client, Pool, Network>
+where
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + TransactionsProvider
+        + ReceiptProvider
+        + 'static,
+    Pool: Send + Sync + 'static,
+    Network: Send + Sync + 'static,
+{
+    /// Handler for `trace_call`
+    async fn call(
+        &self,
+        call: CallRequest,
+        trace_types: HashSet<TraceType>,
+        block_id: Option<BlockId>,
+    ) -> Result<TraceResults> {
+        let _permit = self.tracing_call_guard.acquire().await;
+        Ok(self.trace_call(call, trace_types, block_id)?)
+    }
+
+    async fn call_many(
+        &self,
+        _calls: Vec<(CallRequest, HashSet<TraceType>)>,
+        _block_id: Option<BlockId>,
+    ) -> Result<Vec<TraceResults>> {
+        Err(internal_rpc_err("unimplemented"))
+    }
+
+    async fn raw_transaction(
+        &self,
+        _data: Bytes,
+        _trace_types: HashSet<TraceType>,
+        _block_id: Option<BlockId>,
+    ) -> Result<TraceResults> {
+        Err(internal_rpc_err("unimplemented"))
+    }
+
+    /// Handler for `trace_replayBlockTransactions`
+    async fn replay_block_transactions(
+        &self,
+        block_id: BlockId,
+        trace_types: HashSet<TraceType>,
+    ) -> Result<Option<Vec<TraceResultsWithTransactionHash>>> {
+        let _permit = self.tracing_call_guard.acquire().await;
+        Ok(TraceApi::replay_block_transactions(self, block_id, trace_types)?)
+    }
+
+    /// Handler for `trace_replayTransaction`
+    async fn replay_transaction(
+        &self,
+        transaction: H256,
+        trace_types: HashSet<TraceType>,
+    ) -> Result<TraceResults> {
+        let _permit = self.tracing_call_guard.acquire().await;
+        Ok(TraceApi::replay_transaction(self, transaction, trace_types)?)
+    }
+
+    /// Handler for `trace_block`
+    async fn block(&self, block_id: BlockId) -> Result<Option<Vec<LocalizedTransactionTrace>>> {
+        let _permit = self.tracing_call_guard.acquire().await;
+        Ok(self.trace_block(block_id)?)
+    }
+
+    /// Handler for `trace_filter`
+    async fn filter(&self, filter: TraceFilter) -> Result<Vec<LocalizedTransactionTrace>> {
+        let _permit = self.tracing_call_guard.acquire().await;
+        Ok(self.filter_traces(filter)?)
+    }
+
+    /// Handler for `trace_get`
+    async fn trace(
+        &self,
+        hash: H256,
+        indices: Vec<Index>,
+    ) -> Result<Option<LocalizedTransactionTrace>> {
+        let indices = indices.into_iter().map(usize::from).collect::<Vec<_>>();
+        let _permit = self.tracing_call_guard.acquire().await;
+        let traces = self.transaction_localized_traces(hash)?;
+        Ok(traces.and_then(|traces| {
+            traces.into_iter().find(|trace| trace.trace.trace_address == indices)
+        }))
+    }
+
+    /// Handler for `trace_transaction`
+    async fn transaction_traces(
+        &self,
+        hash: H256,
+    ) -> Result<Option<Vec<LocalizedTransactionTrace>>> {
+        let _permit = self.tracing_call_guard.acquire().await;
+        Ok(self.transaction_localized_traces(hash)?)
+    }
+}
+
+impl<Client, Pool, Network> std::fmt::Debug for TraceApi<Client, Pool, Network> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TraceApi").finish_non_exhaustive()
+    }
+}
+
+/// The transactions of a block that were re-executed, with their traces.
+struct ReplayedBlock {
+    hash: H256,
+    header: Header,
+    transactions: Vec<ReplayedTransaction>,
+}
+
+impl ReplayedBlock {
+    /// Returns the traces of all transactions, with their block and transaction context.
+    fn into_localized_traces(self) -> Vec<LocalizedTransactionTrace> {
+        let Self { hash, header, transactions } = self;
+        transactions
+            .into_iter()
+            .flat_map(|tx| {
+                tx.traces.into_iter().map(move |trace| LocalizedTransactionTrace {
+                    trace,
+                    transaction_position: Some(tx.position),
+                    transaction_hash: Some(tx.hash),
+                    block_number: U64::from(header.number),
+                    block_hash: hash,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A transaction that was re-executed, with its traces.
+struct ReplayedTransaction {
+    hash: H256,
+    /// The index of the transaction in the block.
+    position: usize,
+    result: ExecutionResult,
+    traces: Vec<TransactionTrace>,
+}
+
+/// Executes the transaction of the environment with a [ParityTracer], without committing its state
+/// changes.
+fn inspect<S: StateProvider>(
+    db: &mut SubState<S>,
+    env: Env,
+) -> EthResult<(ResultAndState, Vec<TransactionTrace>)> {
+    let mut evm = EVM::new();
+    evm.env = env;
+    evm.database(db);
+    let mut tracer = ParityTracer::default();
+    let result_and_state =
+        evm.inspect(&mut tracer).map_err(|err| EthApiError::Execution(format!("{err:?}")))?;
+    Ok((result_and_state, tracer.into_traces()))
+}
+
+/// Returns the [TraceResults] of the execution for the requested trace types.
+///
+/// Only [TraceType::Trace] is supported, the vm trace and state diff are never included.
+fn trace_results(
+    result: &ExecutionResult,
+    traces: Vec<TransactionTrace>,
+    trace_types: &HashSet<TraceType>,
+) -> TraceResults {
+    let output = match result {
+        ExecutionResult::Success { output, .. } => match output {
+            Output::Call(output) | Output::Create(output, _) => output.clone().into(),
+        },
+        ExecutionResult::Revert { output, .. } => output.clone().into(),
+        ExecutionResult::Halt { .. } => Bytes::default(),
+    };
+    TraceResults {
+        output,
+        trace: trace_types.contains(&TraceType::Trace).then_some(traces),
+        vm_trace: None,
+        state_diff: None,
+    }
+}
+
+/// Returns the address that made the call, create or selfdestruct of the trace.
+fn trace_from(trace: &TransactionTrace) -> Option<Address> {
+    match &trace.action {
+        Action::Call(call) => Some(call.from),
+        Action::Create(create) => Some(create.from),
+        Action::Selfdestruct(selfdestruct) => Some(selfdestruct.address),
+        Action::Reward(_) => None,
+    }
+}
+
+/// Returns the address that received the call, was created or was refunded by the trace.
+fn trace_to(trace: &TransactionTrace) -> Option<Address> {
+    match (&trace.action, &trace.result) {
+        (Action::Call(call), _) => Some(call.to),
+        (Action::Create(_), Some(TraceResult::Success { result: TraceOutput::Create(create) })) => {
+            Some(create.address)
+        }
+        (Action::Selfdestruct(selfdestruct), _) => Some(selfdestruct.refund_address),
+        (Action::Reward(reward), _) => Some(reward.author),
+        _ => None,
+    }
+}
+
+/// Returns true if no addresses are given, or the address is one of them.
+fn matches_addresses(addresses: Option<&[Address]>, address: Option<Address>) -> bool {
+    match addresses {
+        None => true,
+        Some([]) => true,
+        Some(addresses) => address.map_or(false, |address| addresses.contains(&address)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_trace_addresses() {
+        let from = Address::random();
+        let to = Address::random();
+        let trace = TransactionTrace {
+            trace_address: vec![],
+            subtraces: 0,
+            action: Action::Call(CallAction {
+                from,
+                to,
+                value: Default::default(),
+                gas: Default::default(),
+                input: Default::default(),
+                call_type: CallType::Call,
+            }),
+            result: None,
+        };
+
+        assert!(matches_addresses(None, trace_from(&trace)));
+        assert!(matches_addresses(Some(&[]), trace_to(&trace)));
+        assert!(matches_addresses(Some(&[from]), trace_from(&trace)));
+        assert!(matches_addresses(Some(&[to]), trace_to(&trace)));
+        assert!(!matches_addresses(Some(&[to]), trace_from(&trace)));
+    }
+}
@@ -0,0 +1,110 @@
+use jsonrpsee::core::RpcResult as Result;
+use reth_primitives::{Address, IntoRecoveredTransaction, U64};
+use reth_rpc_api::TxPoolApiServer;
+use reth_rpc_types::{
+    txpool::{
+        TxpoolByAddress, TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolInspectSummary,
+        TxpoolStatus,
+    },
+    Transaction,
+};
+use reth_transaction_pool::{PoolTransaction, TransactionPool, ValidPoolTransaction};
+use std::sync::Arc;
+
+/// `txpool` API implementation.
+///
+/// This type provides the functionality for handling `txpool` related requests.
+pub struct TxPoolApi<Pool> {
+    /// An interface to interact with the pool
+    pool: Pool,
+}
+
+// === impl TxPoolApi ===
+
+impl<Pool> TxPoolApi<Pool> {
+    /// Creates a new instance of `TxPoolApi`.
+    pub fn new(pool: Pool) -> Self {
+        TxPoolApi { pool }
+    }
+}
+
+impl<Pool> TxPoolApi<Pool>
+where
+    Pool: TransactionPool + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    /// Returns the pending and queued transactions of the pool, converted with `f`, grouped by
+    /// sender and nonce.
+    fn content<T>(&self, f: impl Fn(Transaction) -> T) -> (TxpoolByAddress<T>, TxpoolByAddress<T>) {
+        (
+            group_by_sender(self.pool.pending_transactions(), &f),
+            group_by_sender(self.pool.queued_transactions(), &f),
+        )
+    }
+}
+
+impl<Pool> TxPoolApiServer for TxPoolApi<Pool>
+where
+    Pool: TransactionPool + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    /// Handler for `txpool_status`
+    fn txpool_status(&self) -> Result<TxpoolStatus> {
+        let size = self.pool.status();
+        Ok(TxpoolStatus {
+            pending: U64::from(size.pending as u64),
+            queued: U64::from((size.basefee + size.queued) as u64),
+        })
+    }
+
+    /// Handler for `txpool_inspect`
+    fn txpool_inspect(&self) -> Result<TxpoolInspect> {
+        let (pending, queued) = self.content(|tx| TxpoolInspectSummary {
+            to: tx.to,
+            value: tx.value,
+            gas: tx.gas.saturating_to(),
+            gas_price: tx.gas_price.or(tx.max_fee_per_gas).unwrap_or_default().saturating_to(),
+        });
+        Ok(TxpoolInspect { pending, queued })
+    }
+
+    /// Handler for `txpool_contentFrom`
+    fn txpool_content_from(&self, from: Address) -> Result<TxpoolContentFrom> {
+        let from_sender = |txs: Vec<Arc<ValidPoolTransaction<Pool::Transaction>>>| {
+            let txs = txs.into_iter().filter(|tx| tx.transaction.sender() == from).collect();
+            group_by_sender(txs, &|tx| tx).remove(&from).unwrap_or_default()
+        };
+        Ok(TxpoolContentFrom {
+            pending: from_sender(self.pool.pending_transactions()),
+            queued: from_sender(self.pool.queued_transactions()),
+        })
+    }
+
+    /// Handler for `txpool_content`
+    fn txpool_content(&self) -> Result<TxpoolContent> {
+        let (pending, queued) = self.content(|tx| tx);
+        Ok(TxpoolContent { pending, queued })
+    }
+}
+
+impl<Pool> std::fmt::Debug for TxPoolApi<Pool> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TxPoolApi").finish_non_exhaustive()
+    }
+}
+
+/// Converts the transactions with `f` and groups them by sender and nonce.
+fn group_by_sender<T, P>(
+    txs: Vec<Arc<ValidPoolTransaction<P>>>,
+    f: &impl Fn(Transaction) -> T,
+) -> TxpoolByAddress<T>
+where
+    P: PoolTransaction + IntoRecoveredTransaction,
+{
+    let mut grouped = TxpoolByAddress::new();
+    for tx in txs {
+        let tx = Transaction::from_recovered(tx.transaction.to_recovered_transaction());
+        grouped.entry(tx.from).or_default().insert(tx.nonce.to_string(), f(tx));
+    }
+    grouped
+}
@@ -40,3 +40,21 @@ impl<N> std::fmt::Debug for Web3Api<N> {
         f.debug_struct("Web3Api").finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_network_api::test_utils::NoopNetwork;
+    use reth_primitives::hex_literal::hex;
+
+    #[tokio::test]
+    async fn test_web3_calls() {
+        let api = Web3Api::new(NoopNetwork);
+
+        assert_eq!(api.client_version().await.unwrap(), "reth-test");
+        assert_eq!(
+            api.sha3(Bytes::default()).unwrap(),
+            H256(hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"))
+        );
+    }
+}
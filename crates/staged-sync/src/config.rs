@@ -1,4 +1,5 @@
 //! Configuration files.
+use reth_db::mdbx::{EnvConfig, GIGABYTE, MEGABYTE};
 use reth_discv4::Discv4Config;
 use reth_downloaders::{
     bodies::bodies::BodiesDownloaderBuilder,
@@ -6,9 +7,11 @@ use reth_downloaders::{
 };
 use reth_network::{config::rng_secret_key, NetworkConfigBuilder, PeersConfig};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{net::IpAddr, path::PathBuf, time::Duration};
 
 /// Configuration for the reth node.
+///
+/// The settings given on the command line take precedence over the ones of the configuration file.
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
 pub struct Config {
@@ -17,6 +20,12 @@ pub struct Config {
     pub stages: StageConfig,
     /// Configuration for the discovery service.
     pub peers: PeersConfig,
+    /// Configuration for pruning the data of old blocks.
+    pub prune: PruneConfig,
+    /// Configuration for the RPC servers.
+    pub rpc: RpcConfig,
+    /// Configuration for the database.
+    pub db: DbConfig,
 }
 
 impl Config {
@@ -25,12 +34,17 @@ impl Config {
         &self,
         nat_resolution_method: reth_net_nat::NatResolver,
         peers_file: Option<PathBuf>,
+        banned_peers_file: Option<PathBuf>,
     ) -> NetworkConfigBuilder {
         let peer_config = self
             .peers
             .clone()
             .with_basic_nodes_from_file(peers_file)
             .unwrap_or_else(|_| self.peers.clone());
+        let peer_config = peer_config
+            .clone()
+            .with_banned_peers_from_file(banned_peers_file)
+            .unwrap_or(peer_config);
         let discv4 =
             Discv4Config::builder().external_ip_resolver(Some(nat_resolution_method)).clone();
         NetworkConfigBuilder::new(rng_secret_key()).peer_config(peer_config).discovery(discv4)
@@ -39,6 +53,7 @@ impl Config {
 
 /// Configuration for each stage in the pipeline.
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
 pub struct StageConfig {
     /// Header stage configuration.
     pub headers: HeadersConfig,
@@ -50,10 +65,13 @@ pub struct StageConfig {
     pub sender_recovery: SenderRecoveryConfig,
     /// Execution stage configuration.
     pub execution: ExecutionConfig,
+    /// Transaction lookup stage configuration.
+    pub transaction_lookup: TransactionLookupConfig,
 }
 
 /// Header stage configuration.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
 pub struct HeadersConfig {
     /// The maximum number of headers to download before committing progress to the database.
     pub commit_threshold: u64,
@@ -77,6 +95,7 @@ impl From<HeadersConfig> for ReverseHeadersDownloaderBuilder {
 
 /// Total difficulty stage configuration
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
 pub struct TotalDifficultyConfig {
     /// The maximum number of total difficulty entries to sum up before committing progress to the
     /// database.
@@ -91,6 +110,7 @@ impl Default for TotalDifficultyConfig {
 
 /// Body stage configuration.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
 pub struct BodiesConfig {
     /// The batch size of non-empty blocks per one request
     pub downloader_request_limit: u64,
@@ -99,6 +119,8 @@ pub struct BodiesConfig {
     /// Maximum amount of received bodies to buffer internally.
     /// The response contains multiple bodies.
     pub downloader_max_buffered_responses: usize,
+    /// Maximum size in bytes of the blocks buffered internally.
+    pub downloader_max_buffered_blocks_size_bytes: usize,
     /// The minimum number of requests to send concurrently.
     pub downloader_min_concurrent_requests: usize,
     /// The maximum number of requests to send concurrently.
@@ -111,6 +133,7 @@ impl Default for BodiesConfig {
             downloader_request_limit: 200,
             downloader_stream_batch_size: 10000,
             downloader_max_buffered_responses: 1000,
+            downloader_max_buffered_blocks_size_bytes: 2 * 1024 * 1024 * 1024, // ~2GB
             downloader_min_concurrent_requests: 5,
             downloader_max_concurrent_requests: 100,
         }
@@ -123,6 +146,7 @@ impl From<BodiesConfig> for BodiesDownloaderBuilder {
             .with_stream_batch_size(config.downloader_stream_batch_size)
             .with_request_limit(config.downloader_request_limit)
             .with_max_buffered_responses(config.downloader_max_buffered_responses)
+            .with_max_buffered_blocks_size_bytes(config.downloader_max_buffered_blocks_size_bytes)
             .with_concurrent_requests_range(
                 config.downloader_min_concurrent_requests..=
                     config.downloader_max_concurrent_requests,
@@ -132,6 +156,7 @@ impl From<BodiesConfig> for BodiesDownloaderBuilder {
 
 /// Sender recovery stage configuration.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
 pub struct SenderRecoveryConfig {
     /// The maximum number of blocks to process before committing progress to the database.
     pub commit_threshold: u64,
@@ -145,6 +170,7 @@ impl Default for SenderRecoveryConfig {
 
 /// Execution stage configuration.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
 pub struct ExecutionConfig {
     /// The maximum number of blocks to execution before committing progress to the database.
     pub commit_threshold: u64,
@@ -156,6 +182,128 @@ impl Default for ExecutionConfig {
     }
 }
 
+/// Transaction lookup stage configuration.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct TransactionLookupConfig {
+    /// Whether to index the transactions by hash. Nodes that do not serve lookups by transaction
+    /// hash, like `eth_getTransactionByHash`, can disable it to save the space of the index.
+    pub enabled: bool,
+    /// The maximum number of blocks to process before committing progress to the database.
+    pub commit_threshold: u64,
+}
+
+impl Default for TransactionLookupConfig {
+    fn default() -> Self {
+        Self { enabled: true, commit_threshold: 50_000 }
+    }
+}
+
+/// Pruning configuration. Each distance is the number of recent blocks to keep the data of, the
+/// data is kept for all the blocks if it is not set.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct PruneConfig {
+    /// Distance for the transaction receipts and logs.
+    pub receipts_distance: Option<u64>,
+    /// Distance for the transaction hash lookups.
+    pub transaction_lookup_distance: Option<u64>,
+    /// Distance for the recovered transaction senders.
+    pub sender_recovery_distance: Option<u64>,
+    /// Distance for the account changesets and history.
+    pub account_history_distance: Option<u64>,
+    /// Distance for the storage changesets and history.
+    pub storage_history_distance: Option<u64>,
+}
+
+/// RPC servers configuration. The settings which are not set fall back to their defaults.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct RpcConfig {
+    /// Whether to enable the HTTP-RPC server.
+    pub http: bool,
+    /// The address the HTTP-RPC server listens on.
+    pub http_addr: Option<IpAddr>,
+    /// The port the HTTP-RPC server listens on.
+    pub http_port: Option<u16>,
+    /// Whether to enable the WS-RPC server.
+    pub ws: bool,
+    /// The address the WS-RPC server listens on.
+    pub ws_addr: Option<IpAddr>,
+    /// The port the WS-RPC server listens on.
+    pub ws_port: Option<u16>,
+    /// The address the auth server listens on.
+    pub auth_addr: Option<IpAddr>,
+    /// The port the auth server listens on.
+    pub auth_port: Option<u16>,
+    /// The maximum size of a request payload in megabytes.
+    pub max_request_size: Option<u32>,
+    /// The maximum size of a response payload in megabytes.
+    pub max_response_size: Option<u32>,
+    /// The maximum number of connections to the servers.
+    pub max_connections: Option<u32>,
+    /// The maximum gas a call of `eth_call`, `eth_estimateGas` or `eth_createAccessList` can use.
+    pub gas_cap: Option<u64>,
+    /// The maximum number of tracing requests served concurrently.
+    pub max_tracing_requests: Option<usize>,
+    /// The maximum number of blocks a log query can span.
+    pub max_blocks_per_filter: Option<u64>,
+    /// The maximum number of logs a log query can return.
+    pub max_logs_per_response: Option<usize>,
+    /// The number of seconds after which a filter that was not polled is uninstalled.
+    pub filter_ttl: Option<u64>,
+}
+
+/// Database configuration. The settings which are not set fall back to the defaults of
+/// [`EnvConfig`].
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct DbConfig {
+    /// The maximum size of the database map, in gigabytes.
+    pub max_size_gb: Option<usize>,
+    /// The step by which the database file grows, in megabytes.
+    pub growth_step_mb: Option<usize>,
+    /// The limit up to which the maximum size is grown when the map is full, in gigabytes.
+    pub max_size_limit_gb: Option<usize>,
+    /// The database page size, in bytes.
+    pub page_size: Option<usize>,
+    /// The maximum number of concurrent readers.
+    pub max_readers: Option<u32>,
+    /// Whether to disable the OS readahead.
+    pub no_rdahead: Option<bool>,
+    /// The number of seconds after which an open read-only transaction is reported.
+    pub read_tx_timeout: Option<u64>,
+}
+
+impl DbConfig {
+    /// Returns the configuration the database is opened with.
+    pub fn env_config(&self) -> EnvConfig {
+        let mut config = EnvConfig::default();
+        if let Some(max_size_gb) = self.max_size_gb {
+            config = config.max_size(max_size_gb * GIGABYTE);
+        }
+        if let Some(growth_step_mb) = self.growth_step_mb {
+            config = config.growth_step((growth_step_mb * MEGABYTE) as isize);
+        }
+        if let Some(max_size_limit_gb) = self.max_size_limit_gb {
+            config = config.max_size_limit(max_size_limit_gb * GIGABYTE);
+        }
+        if let Some(page_size) = self.page_size {
+            config = config.page_size(page_size);
+        }
+        if let Some(max_readers) = self.max_readers {
+            config = config.max_readers(max_readers);
+        }
+        if let Some(no_rdahead) = self.no_rdahead {
+            config = config.no_rdahead(no_rdahead);
+        }
+        if let Some(read_tx_timeout) = self.read_tx_timeout {
+            config = config.read_tx_timeout(Duration::from_secs(read_tx_timeout));
+        }
+        config
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Config;
@@ -189,4 +337,30 @@ mod tests {
             assert_eq!(config, loaded_config);
         })
     }
+
+    #[test]
+    fn test_load_partial_config() {
+        with_tempdir("config-partial-test", |config_path| {
+            std::fs::write(
+                config_path,
+                "[stages.execution]\ncommit_threshold = 100\n\n\
+                 [rpc]\nhttp = true\ngas_cap = 1000\n\n\
+                 [db]\nmax_size_gb = 8\nread_tx_timeout = 60\n",
+            )
+            .unwrap();
+
+            let loaded_config: Config = confy::load_path(config_path).unwrap();
+            assert_eq!(loaded_config.stages.execution.commit_threshold, 100);
+            assert_eq!(loaded_config.stages.headers, Default::default());
+            assert!(loaded_config.rpc.http);
+            assert_eq!(loaded_config.rpc.gas_cap, Some(1000));
+            assert_eq!(loaded_config.rpc.http_port, None);
+            assert_eq!(loaded_config.db.env_config().max_size, 8 * reth_db::mdbx::GIGABYTE);
+            assert_eq!(loaded_config.db.max_readers, None);
+            assert_eq!(
+                loaded_config.db.env_config().read_tx_timeout,
+                Some(std::time::Duration::from_secs(60))
+            );
+        })
+    }
 }
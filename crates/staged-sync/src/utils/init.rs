@@ -1,29 +1,37 @@
 use reth_db::{
     cursor::DbCursorRO,
     database::Database,
-    mdbx::{Env, WriteMap},
+    mdbx::{Env, EnvConfig, WriteMap},
     tables,
     transaction::{DbTx, DbTxMut},
 };
-use reth_primitives::{Account, ChainSpec, H256};
+use reth_primitives::{keccak256, Account, ChainSpec, StorageEntry, H256, U256};
+use reth_trie::StateRoot;
 use std::{path::Path, sync::Arc};
 use tracing::debug;
 
-/// Opens up an existing database or creates a new one at the specified path.
-pub fn init_db<P: AsRef<Path>>(path: P) -> eyre::Result<Env<WriteMap>> {
+/// Opens up an existing database or creates a new one at the specified path, and migrates it to
+/// the current schema version.
+pub fn init_db<P: AsRef<Path>>(path: P, config: EnvConfig) -> eyre::Result<Env<WriteMap>> {
     std::fs::create_dir_all(path.as_ref())?;
-    let db = reth_db::mdbx::Env::<reth_db::mdbx::WriteMap>::open(
+    let db = reth_db::mdbx::Env::<reth_db::mdbx::WriteMap>::open_with_config(
         path.as_ref(),
         reth_db::mdbx::EnvKind::RW,
+        config,
     )?;
     db.create_tables()?;
+    db.migrate()?;
 
     Ok(db)
 }
 
-/// Write the genesis block if it has not already been written
+/// Write the genesis block and the accounts, code and storage allocated by it, if it has not
+/// already been written.
+///
+/// The allocated state is hashed and its tries are written as well, so that the state roots of
+/// the blocks on top of the genesis can be computed incrementally.
 #[allow(clippy::field_reassign_with_default)]
-pub fn init_genesis<DB: Database>(db: Arc<DB>, chain: ChainSpec) -> Result<H256, reth_db::Error> {
+pub fn init_genesis<DB: Database>(db: Arc<DB>, chain: ChainSpec) -> eyre::Result<H256> {
     let genesis = chain.genesis();
     let tx = db.tx()?;
     if let Some((_, hash)) = tx.cursor_read::<tables::CanonicalHeaders>()?.first()? {
@@ -37,16 +45,39 @@ pub fn init_genesis<DB: Database>(db: Arc<DB>, chain: ChainSpec) -> Result<H256,
 
     // Insert account state
     for (address, account) in &genesis.alloc {
-        tx.put::<tables::PlainAccountState>(
-            *address,
-            Account {
-                nonce: account.nonce.unwrap_or_default(),
-                balance: account.balance,
-                bytecode_hash: None,
-            },
-        )?;
+        let bytecode_hash = match &account.code {
+            Some(code) if !code.is_empty() => {
+                let hash = keccak256(code);
+                tx.put::<tables::Bytecodes>(hash, code.to_vec())?;
+                Some(hash)
+            }
+            _ => None,
+        };
+        let plain_account = Account {
+            nonce: account.nonce.unwrap_or_default(),
+            balance: account.balance,
+            bytecode_hash,
+        };
+        let hashed_address = keccak256(address);
+        tx.put::<tables::PlainAccountState>(*address, plain_account)?;
+        tx.put::<tables::HashedAccount>(hashed_address, plain_account)?;
+
+        // Zero slots are not stored, as if they had been cleared.
+        for (key, value) in account.storage.iter().flatten() {
+            let value = U256::from_be_bytes(value.0);
+            if value != U256::ZERO {
+                tx.put::<tables::PlainStorageState>(*address, StorageEntry { key: *key, value })?;
+                tx.put::<tables::HashedStorage>(
+                    hashed_address,
+                    StorageEntry { key: keccak256(key), value },
+                )?;
+            }
+        }
     }
 
+    // Insert the tries of the allocated state
+    StateRoot::new(&tx).write_root()?;
+
     // Insert header
     let header = chain.genesis_header();
 
@@ -66,10 +97,21 @@ pub fn init_genesis<DB: Database>(db: Arc<DB>, chain: ChainSpec) -> Result<H256,
 mod tests {
 
     use super::init_genesis;
-    use reth_db::mdbx::test_utils::create_test_rw_db;
+    use reth_db::{
+        cursor::{DbCursorRO, DbDupCursorRO},
+        database::Database,
+        mdbx::test_utils::create_test_rw_db,
+        mem::MemDb,
+        tables,
+        transaction::DbTx,
+    };
     use reth_primitives::{
-        GOERLI, GOERLI_GENESIS, MAINNET, MAINNET_GENESIS, SEPOLIA, SEPOLIA_GENESIS,
+        keccak256, Account, Address, Bytes, ChainSpecBuilder, Genesis, GenesisAccount,
+        StorageEntry, GOERLI, GOERLI_GENESIS, H256, MAINNET, MAINNET_GENESIS, SEPOLIA,
+        SEPOLIA_GENESIS, U256,
     };
+    use reth_trie::StateRoot;
+    use std::{collections::HashMap, sync::Arc};
 
     #[test]
     fn success_init_genesis_mainnet() {
@@ -97,4 +139,73 @@ mod tests {
         // actual, expected
         assert_eq!(genesis_hash, SEPOLIA_GENESIS);
     }
+
+    #[test]
+    fn init_genesis_alloc() {
+        let address_with_code = Address::from_low_u64_be(1);
+        let address_with_balance = Address::from_low_u64_be(2);
+        let code = Bytes::from(vec![0x60, 0x00]);
+        let genesis = Genesis {
+            alloc: HashMap::from([
+                (
+                    address_with_code,
+                    GenesisAccount {
+                        nonce: Some(1),
+                        code: Some(code.clone()),
+                        storage: Some(HashMap::from([
+                            (H256::from_low_u64_be(1), H256::from_low_u64_be(2)),
+                            (H256::from_low_u64_be(3), H256::zero()),
+                        ])),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    address_with_balance,
+                    GenesisAccount { balance: U256::from(10), ..Default::default() },
+                ),
+            ]),
+            ..Default::default()
+        };
+        let chain = ChainSpecBuilder::mainnet().genesis(genesis).build();
+        let state_root = chain.genesis_header().state_root;
+
+        // does not touch the disk, init_genesis is generic over the database
+        let db = Arc::new(MemDb::new());
+        init_genesis(db.clone(), chain).unwrap();
+
+        let tx = db.tx().unwrap();
+        let code_hash = keccak256(&code);
+        assert_eq!(
+            tx.get::<tables::PlainAccountState>(address_with_code).unwrap(),
+            Some(Account { nonce: 1, balance: U256::ZERO, bytecode_hash: Some(code_hash) })
+        );
+        assert_eq!(
+            tx.get::<tables::PlainAccountState>(address_with_balance).unwrap(),
+            Some(Account { nonce: 0, balance: U256::from(10), bytecode_hash: None })
+        );
+        assert_eq!(tx.get::<tables::Bytecodes>(code_hash).unwrap(), Some(code.to_vec()));
+
+        let storage = tx
+            .cursor_dup_read::<tables::PlainStorageState>()
+            .unwrap()
+            .walk_dup(Some(address_with_code), None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            storage,
+            vec![(
+                address_with_code,
+                StorageEntry { key: H256::from_low_u64_be(1), value: U256::from(2) }
+            )]
+        );
+
+        // the hashed state and its tries match the genesis state root
+        assert_eq!(
+            tx.get::<tables::HashedAccount>(keccak256(address_with_balance)).unwrap(),
+            Some(Account { nonce: 0, balance: U256::from(10), bytecode_hash: None })
+        );
+        assert_eq!(StateRoot::new(&tx).root().unwrap(), state_root);
+        assert!(tx.cursor_read::<tables::AccountsTrie>().unwrap().first().unwrap().is_some());
+    }
 }
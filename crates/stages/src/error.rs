@@ -1,10 +1,11 @@
-use crate::pipeline::PipelineEvent;
+use crate::{pipeline::PipelineEvent, PrunerError};
 use reth_interfaces::{
     consensus, db::Error as DbError, executor, p2p::error::DownloadError,
     provider::Error as ProviderError,
 };
 use reth_primitives::BlockNumber;
 use reth_provider::TransactionError;
+use reth_static_file::StaticFileError;
 use thiserror::Error;
 use tokio::sync::mpsc::error::SendError;
 
@@ -86,6 +87,12 @@ pub enum PipelineError {
     /// The pipeline encountered a database error.
     #[error("A database error occurred.")]
     Database(#[from] DbError),
+    /// The pipeline encountered an error while pruning.
+    #[error("The pruner encountered an error: {0}")]
+    Prune(#[from] PrunerError),
+    /// The pipeline encountered an error while moving blocks to the static files.
+    #[error("The static file producer encountered an error: {0}")]
+    StaticFile(#[from] StaticFileError),
     /// The pipeline encountered an error while trying to send an event.
     #[error("The pipeline encountered an error while trying to send an event.")]
     Channel(#[from] SendError<PipelineEvent>),
@@ -52,8 +52,8 @@
 mod error;
 mod id;
 mod pipeline;
+mod prune;
 mod stage;
-mod trie;
 mod util;
 
 #[allow(missing_docs)]
@@ -71,6 +71,7 @@ pub mod sets;
 pub use error::*;
 pub use id::*;
 pub use pipeline::*;
+pub use prune::*;
 pub use stage::*;
 
 // NOTE: Needed so the link in the module-level rustdoc works.
@@ -1,7 +1,11 @@
-use crate::{Pipeline, Stage, StageSet};
+use super::ctrl::ShutdownSignal;
+use crate::{Pipeline, Pruner, Stage, StageSet};
 use reth_db::database::Database;
 use reth_interfaces::sync::{NoopSyncStateUpdate, SyncStateUpdater};
 use reth_primitives::BlockNumber;
+use reth_provider::StateCache;
+use reth_static_file::{StaticFileProducer, StaticFileProvider};
+use std::future::Future;
 
 /// Builds a [`Pipeline`].
 #[derive(Debug)]
@@ -62,6 +66,47 @@ where
         self
     }
 
+    /// Set a [`Pruner`] to run after each successful pass of the pipeline.
+    pub fn with_pruner(mut self, pruner: Pruner) -> Self {
+        self.pipeline.pruner = Some(pruner);
+        self
+    }
+
+    /// Set a [`StaticFileProducer`] to run after each successful pass of the pipeline, once the
+    /// [`Pruner`] ran.
+    ///
+    /// The stages read the blocks it moves from its static files.
+    pub fn with_static_file_producer(mut self, producer: StaticFileProducer) -> Self {
+        self.pipeline.static_files = Some(producer.provider().clone());
+        self.pipeline.static_file_producer = Some(producer);
+        self
+    }
+
+    /// Set the static files the stages read the finalized blocks from, when they were moved out
+    /// of the database.
+    pub fn with_static_files(mut self, static_files: StaticFileProvider) -> Self {
+        self.pipeline.static_files = Some(static_files);
+        self
+    }
+
+    /// Set the cache of the latest state, which is cleared whenever the stages commit changes.
+    pub fn with_state_cache(mut self, state_cache: StateCache) -> Self {
+        self.pipeline.state_cache = Some(state_cache);
+        self
+    }
+
+    /// Set a signal asking the pipeline to shut down once it resolves.
+    ///
+    /// The signal is checked each time a stage committed its progress, so the pipeline stops with
+    /// the database in a consistent state.
+    pub fn with_shutdown_signal<F>(mut self, signal: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.pipeline.shutdown = Some(ShutdownSignal::new(signal));
+        self
+    }
+
     /// Builds the final [`Pipeline`].
     pub fn build(self) -> Pipeline<DB, U> {
         self.pipeline
@@ -1,4 +1,6 @@
+use futures_util::FutureExt;
 use reth_primitives::BlockNumber;
+use std::{future::Future, pin::Pin};
 
 /// Determines the control flow during pipeline execution.
 #[derive(Debug, Eq, PartialEq)]
@@ -16,6 +18,8 @@ pub(crate) enum ControlFlow {
         progress: BlockNumber,
     },
     NoProgress,
+    /// The pipeline was asked to shut down, the progress of the stages is committed.
+    Shutdown,
 }
 
 impl ControlFlow {
@@ -23,3 +27,23 @@ impl ControlFlow {
         matches!(self, ControlFlow::Continue { .. } | ControlFlow::NoProgress)
     }
 }
+
+/// A signal asking the pipeline to shut down.
+pub(crate) struct ShutdownSignal {
+    signal: Pin<Box<dyn Future<Output = ()> + Send>>,
+    received: bool,
+}
+
+impl ShutdownSignal {
+    pub(crate) fn new(signal: impl Future<Output = ()> + Send + 'static) -> Self {
+        Self { signal: Box::pin(signal), received: false }
+    }
+
+    /// Returns whether the signal was received, without waiting for it.
+    pub(crate) fn received(&mut self) -> bool {
+        if !self.received {
+            self.received = self.signal.as_mut().now_or_never().is_some();
+        }
+        self.received
+    }
+}
@@ -1,10 +1,13 @@
-use crate::{error::*, util::opt, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput};
+use crate::{
+    error::*, util::opt, ExecInput, ExecOutput, Pruner, Stage, StageError, StageId, UnwindInput,
+};
 use metrics::Gauge;
 use reth_db::database::Database;
 use reth_interfaces::sync::{SyncState, SyncStateUpdater};
 use reth_metrics_derive::Metrics;
 use reth_primitives::BlockNumber;
-use reth_provider::Transaction;
+use reth_provider::{StateCache, Transaction};
+use reth_static_file::{StaticFileProducer, StaticFileProvider};
 use std::{
     collections::HashMap,
     fmt::{Debug, Formatter},
@@ -33,7 +36,9 @@ pub use set::*;
 /// tip.
 ///
 /// After the entire pipeline has been run, it will run again unless asked to stop (see
-/// [Pipeline::set_max_block]).
+/// [Pipeline::set_max_block]) or to shut down (see [PipelineBuilder::with_shutdown_signal]). If a
+/// [`Pruner`] or a [`StaticFileProducer`] is set, it runs after
+/// each successful pass.
 ///
 /// ```mermaid
 /// graph TB
@@ -79,6 +84,13 @@ pub struct Pipeline<DB: Database, U: SyncStateUpdater> {
     max_block: Option<BlockNumber>,
     listeners: PipelineEventListeners,
     sync_state_updater: Option<U>,
+    pruner: Option<Pruner>,
+    static_file_producer: Option<StaticFileProducer>,
+    /// The static files the stages read the finalized blocks from.
+    static_files: Option<StaticFileProvider>,
+    /// The cache of the latest state, cleared whenever the stages commit changes to it.
+    state_cache: Option<StateCache>,
+    shutdown: Option<ShutdownSignal>,
     progress: PipelineProgress,
     metrics: Metrics,
 }
@@ -90,6 +102,11 @@ impl<DB: Database, U: SyncStateUpdater> Default for Pipeline<DB, U> {
             max_block: None,
             listeners: PipelineEventListeners::default(),
             sync_state_updater: None,
+            pruner: None,
+            static_file_producer: None,
+            static_files: None,
+            state_cache: None,
+            shutdown: None,
             progress: PipelineProgress::default(),
             metrics: Metrics::default(),
         }
@@ -101,6 +118,8 @@ impl<DB: Database, U: SyncStateUpdater> Debug for Pipeline<DB, U> {
         f.debug_struct("Pipeline")
             .field("stages", &self.stages.iter().map(|stage| stage.id()).collect::<Vec<StageId>>())
             .field("max_block", &self.max_block)
+            .field("pruner", &self.pruner)
+            .field("static_file_producer", &self.static_file_producer)
             .finish()
     }
 }
@@ -130,12 +149,20 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
     }
 
     /// Run the pipeline in an infinite loop. Will terminate early if the user has specified
-    /// a `max_block` in the pipeline.
+    /// a `max_block` in the pipeline, or once the shutdown signal is received.
     pub async fn run(&mut self, db: Arc<DB>) -> Result<(), PipelineError> {
         self.register_metrics(db.clone());
 
         loop {
             let next_action = self.run_loop(db.as_ref()).await?;
+            if next_action == ControlFlow::Shutdown {
+                info!(target: "sync::pipeline", "Pipeline shut down");
+                return Ok(())
+            }
+
+            if next_action.should_continue() {
+                self.prune(db.as_ref())?;
+            }
 
             // Terminate the loop early if it's reached the maximum user
             // configured block.
@@ -150,6 +177,38 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
         }
     }
 
+    /// Runs the [`Pruner`] and then the [`StaticFileProducer`], if any, up to the lowest progress
+    /// of all the stages.
+    fn prune(&self, db: &DB) -> Result<(), PipelineError> {
+        if self.pruner.is_none() && self.static_file_producer.is_none() {
+            return Ok(())
+        }
+
+        let tip = {
+            let mut tx = Transaction::new(db)?.with_static_files(self.static_files.clone());
+            let mut tip = None;
+            for stage in &self.stages {
+                let progress = stage.id().get_progress(tx.deref())?.unwrap_or_default();
+                tip = opt::min(tip, progress);
+            }
+            let Some(tip) = tip else { return Ok(()) };
+
+            // the pruner commits its batches
+            if let Some(pruner) = &self.pruner {
+                trace!(target: "sync::pipeline", tip, "Pruning");
+                pruner.run(&mut tx, tip)?;
+            }
+            tip
+        };
+
+        // the producer opens a transaction per chunk, once the one of the pruner is dropped
+        if let Some(producer) = &self.static_file_producer {
+            trace!(target: "sync::pipeline", tip, "Moving finalized blocks to static files");
+            producer.run(db, tip)?;
+        }
+        Ok(())
+    }
+
     /// Performs one pass of the pipeline across all stages. After successful
     /// execution of each stage, it proceeds to commit it to the database.
     ///
@@ -159,6 +218,10 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
     async fn run_loop(&mut self, db: &DB) -> Result<ControlFlow, PipelineError> {
         let mut previous_stage = None;
         for stage_index in 0..self.stages.len() {
+            if self.shutdown.as_mut().map_or(false, ShutdownSignal::received) {
+                return Ok(ControlFlow::Shutdown)
+            }
+
             let stage = &self.stages[stage_index];
             let stage_id = stage.id();
 
@@ -180,6 +243,7 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
 
             match next {
                 ControlFlow::NoProgress => {} // noop
+                ControlFlow::Shutdown => return Ok(ControlFlow::Shutdown),
                 ControlFlow::Continue { progress } => self.progress.update(progress),
                 ControlFlow::Unwind { target, bad_block } => {
                     // reset the sync state
@@ -210,7 +274,7 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
         // Unwind stages in reverse order of execution
         let unwind_pipeline = self.stages.iter_mut().rev();
 
-        let mut tx = Transaction::new(db)?;
+        let mut tx = Transaction::new(db)?.with_static_files(self.static_files.clone());
 
         for stage in unwind_pipeline {
             let stage_id = stage.id();
@@ -248,6 +312,10 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
         }
 
         tx.commit()?;
+        // the unwound changes are not known, so nothing cached can be trusted anymore
+        if let Some(state_cache) = &self.state_cache {
+            state_cache.clear();
+        }
         Ok(())
     }
 
@@ -261,7 +329,7 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
         let stage_id = stage.id();
         let mut made_progress = false;
         loop {
-            let mut tx = Transaction::new(db)?;
+            let mut tx = Transaction::new(db)?.with_static_files(self.static_files.clone());
 
             let prev_progress = stage_id.get_progress(tx.deref())?;
 
@@ -303,6 +371,13 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
 
                     // TODO: Make the commit interval configurable
                     tx.commit()?;
+                    // the changes of the stage are not known, so nothing cached can be trusted
+                    // anymore
+                    if let Some(state_cache) =
+                        self.state_cache.as_ref().filter(|_| Some(stage_progress) != prev_progress)
+                    {
+                        state_cache.clear();
+                    }
 
                     if done {
                         return Ok(if made_progress {
@@ -311,6 +386,10 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
                             ControlFlow::NoProgress
                         })
                     }
+
+                    if self.shutdown.as_mut().map_or(false, ShutdownSignal::received) {
+                        return Ok(ControlFlow::Shutdown)
+                    }
                 }
                 Err(err) => {
                     self.listeners.notify(PipelineEvent::Error { stage_id });
@@ -503,6 +582,51 @@ mod tests {
         );
     }
 
+    /// Shuts a pipeline down in the middle of a stage.
+    #[tokio::test]
+    async fn shutdown_pipeline() {
+        let db = test_utils::create_test_db::<mdbx::WriteMap>(EnvKind::RW);
+
+        // the signal is pending before the first stage runs, then received after its first commit
+        let mut polled = false;
+        let signal = std::future::poll_fn(move |_| {
+            if std::mem::replace(&mut polled, true) {
+                std::task::Poll::Ready(())
+            } else {
+                std::task::Poll::Pending
+            }
+        });
+
+        let mut pipeline: Pipeline<_, NoopSyncStateUpdate> = Pipeline::builder()
+            .add_stage(
+                TestStage::new(StageId("A"))
+                    .add_exec(Ok(ExecOutput { stage_progress: 10, done: false }))
+                    .add_exec(Ok(ExecOutput { stage_progress: 20, done: true })),
+            )
+            .add_stage(
+                TestStage::new(StageId("B"))
+                    .add_exec(Ok(ExecOutput { stage_progress: 10, done: true })),
+            )
+            .with_shutdown_signal(signal)
+            .build();
+        let events = pipeline.events();
+
+        pipeline.run(db.clone()).await.expect("Could not run pipeline");
+        drop(pipeline);
+
+        assert_eq!(
+            events.collect::<Vec<PipelineEvent>>().await,
+            vec![
+                PipelineEvent::Running { stage_id: StageId("A"), stage_progress: None },
+                PipelineEvent::Ran {
+                    stage_id: StageId("A"),
+                    result: ExecOutput { stage_progress: 10, done: false },
+                },
+            ]
+        );
+        assert_eq!(db.view(|tx| StageId("A").get_progress(tx)).unwrap().unwrap(), Some(10));
+    }
+
     /// Unwinds a simple pipeline.
     #[tokio::test]
     async fn unwind_pipeline() {
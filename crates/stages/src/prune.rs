@@ -0,0 +1,624 @@
+//! Pruning of the data that is only needed for the recent blocks.
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    models::{storage_sharded_key::StorageShardedKey, ShardedKey, TransitionIdAddress},
+    table::Table,
+    tables::{self, TransitionList},
+    transaction::{DbTx, DbTxMut},
+    Error as DbError,
+};
+use reth_executor::blockchain_tree::CanonStateNotifications;
+use reth_exex::FinishedExExHeight;
+use reth_primitives::{Address, BlockNumber, TransitionId, TxNumber};
+use reth_provider::{Transaction, TransactionError};
+use reth_static_file::StaticFileProvider;
+use std::{
+    collections::BTreeSet,
+    fmt::Display,
+    ops::{Deref, Range},
+    sync::Arc,
+};
+use thiserror::Error;
+use tokio::sync::{broadcast::error::RecvError, watch};
+use tracing::*;
+
+/// The minimum distance from the tip of the pruned blocks, so that the pipeline can still unwind
+/// the blocks of a reorg.
+pub const MINIMUM_PRUNING_DISTANCE: u64 = 128;
+
+/// The default number of blocks of a part pruned per database transaction.
+pub const DEFAULT_PRUNE_BATCH_SIZE: u64 = 1_000;
+
+/// A part of the database that can be pruned by the [`Pruner`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PrunePart {
+    /// The [`tables::Receipts`] and [`tables::Logs`] of the transactions.
+    Receipts,
+    /// The transaction hash lookups in [`tables::TxHashNumber`].
+    TransactionLookup,
+    /// The recovered transaction senders in [`tables::TxSenders`].
+    SenderRecovery,
+    /// The [`tables::AccountChangeSet`] and the [`tables::AccountHistory`] shards indexing it.
+    AccountHistory,
+    /// The [`tables::StorageChangeSet`] and the [`tables::StorageHistory`] shards indexing it.
+    StorageHistory,
+}
+
+impl PrunePart {
+    /// All the parts, in the order they are pruned.
+    pub const ALL: [PrunePart; 5] = [
+        PrunePart::Receipts,
+        PrunePart::TransactionLookup,
+        PrunePart::SenderRecovery,
+        PrunePart::AccountHistory,
+        PrunePart::StorageHistory,
+    ];
+
+    /// Returns the key of the part in the [`tables::PruneCheckpoints`] table.
+    pub const fn id(&self) -> &'static str {
+        match self {
+            PrunePart::Receipts => "Receipts",
+            PrunePart::TransactionLookup => "TransactionLookup",
+            PrunePart::SenderRecovery => "SenderRecovery",
+            PrunePart::AccountHistory => "AccountHistory",
+            PrunePart::StorageHistory => "StorageHistory",
+        }
+    }
+
+    /// Get the highest pruned block of this part.
+    pub fn get_checkpoint<'db>(&self, tx: &impl DbTx<'db>) -> Result<Option<BlockNumber>, DbError> {
+        tx.get::<tables::PruneCheckpoints>(self.id().as_bytes().to_vec())
+    }
+
+    /// Save the highest pruned block of this part.
+    pub fn save_checkpoint<'db>(
+        &self,
+        tx: &impl DbTxMut<'db>,
+        block: BlockNumber,
+    ) -> Result<(), DbError> {
+        tx.put::<tables::PruneCheckpoints>(self.id().as_bytes().to_vec(), block)
+    }
+}
+
+impl Display for PrunePart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+/// The distance from the tip beyond which the blocks of each [`PrunePart`] are pruned. Parts
+/// without a distance are kept for all the blocks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PruneModes {
+    /// Distance for [`PrunePart::Receipts`].
+    pub receipts: Option<u64>,
+    /// Distance for [`PrunePart::TransactionLookup`].
+    pub transaction_lookup: Option<u64>,
+    /// Distance for [`PrunePart::SenderRecovery`].
+    pub sender_recovery: Option<u64>,
+    /// Distance for [`PrunePart::AccountHistory`].
+    pub account_history: Option<u64>,
+    /// Distance for [`PrunePart::StorageHistory`].
+    pub storage_history: Option<u64>,
+}
+
+impl PruneModes {
+    /// Returns the distance of the part, if it is pruned.
+    pub fn distance(&self, part: PrunePart) -> Option<u64> {
+        match part {
+            PrunePart::Receipts => self.receipts,
+            PrunePart::TransactionLookup => self.transaction_lookup,
+            PrunePart::SenderRecovery => self.sender_recovery,
+            PrunePart::AccountHistory => self.account_history,
+            PrunePart::StorageHistory => self.storage_history,
+        }
+    }
+
+    /// Returns `true` if no part is pruned.
+    pub fn is_empty(&self) -> bool {
+        PrunePart::ALL.iter().all(|part| self.distance(*part).is_none())
+    }
+}
+
+/// A pruner error.
+#[derive(Error, Debug)]
+pub enum PrunerError {
+    /// The pruner encountered a database error.
+    #[error("An internal database error occurred: {0}")]
+    Database(#[from] DbError),
+    /// The pruner encountered an error related to the current database transaction.
+    #[error("A database transaction error occurred: {0}")]
+    Transaction(#[from] TransactionError),
+}
+
+/// Deletes the data of the old blocks that is not needed to follow the chain, depending on the
+/// configured [`PruneModes`].
+///
+/// The [`Pipeline`](crate::Pipeline) runs it after every successful pass, and
+/// [`Pruner::run_on_canonical_state`] after every change of the canonical chain. The highest
+/// pruned block of each part is recorded in [`tables::PruneCheckpoints`].
+///
+/// The historical state of blocks whose history is pruned can not be queried anymore. The history
+/// shards of the accounts and slots changed in the pruned blocks are trimmed, and deleted once all
+/// their transitions are pruned.
+///
+/// If ExExs are installed, blocks are only pruned up to the [`FinishedExExHeight`].
+///
+/// The blocks are pruned in batches, each one committed along with the checkpoint of its part, so
+/// that the first run on a synced node does not have to delete all the old data in a single
+/// transaction.
+#[derive(Clone, Debug)]
+pub struct Pruner {
+    modes: PruneModes,
+    /// The maximum number of blocks of a part pruned per database transaction.
+    batch_size: u64,
+    /// The highest block the ExExs finished processing, if they gate pruning.
+    finished_exex_height: Option<watch::Receiver<FinishedExExHeight>>,
+    /// The static files the transactions moved out of the database are read from.
+    static_files: Option<StaticFileProvider>,
+}
+
+impl Pruner {
+    /// Creates a new pruner. Distances below [`MINIMUM_PRUNING_DISTANCE`] are raised to it.
+    pub fn new(modes: PruneModes) -> Self {
+        let min = |distance: Option<u64>| distance.map(|d| d.max(MINIMUM_PRUNING_DISTANCE));
+        Self {
+            modes: PruneModes {
+                receipts: min(modes.receipts),
+                transaction_lookup: min(modes.transaction_lookup),
+                sender_recovery: min(modes.sender_recovery),
+                account_history: min(modes.account_history),
+                storage_history: min(modes.storage_history),
+            },
+            batch_size: DEFAULT_PRUNE_BATCH_SIZE,
+            finished_exex_height: None,
+            static_files: None,
+        }
+    }
+
+    /// Configures the maximum number of blocks of a part pruned per database transaction, which
+    /// is at least one.
+    pub fn with_batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Configures the finished height of the ExExs, which blocks are not pruned beyond.
+    pub fn with_finished_exex_height(
+        mut self,
+        finished_exex_height: watch::Receiver<FinishedExExHeight>,
+    ) -> Self {
+        self.finished_exex_height = Some(finished_exex_height);
+        self
+    }
+
+    /// Configures the static files the transactions are read from by
+    /// [`Pruner::run_on_canonical_state`], once they were moved out of the database.
+    pub fn with_static_files(mut self, static_files: StaticFileProvider) -> Self {
+        self.static_files = Some(static_files);
+        self
+    }
+
+    /// Returns the distances the pruner uses.
+    pub fn modes(&self) -> &PruneModes {
+        &self.modes
+    }
+
+    /// Prunes the blocks of every part which are further than its distance from `tip`, or from
+    /// the finished height of the ExExs if it is lower.
+    ///
+    /// The transaction is committed after every batch of pruned blocks.
+    pub fn run<DB: Database>(
+        &self,
+        tx: &mut Transaction<'_, DB>,
+        tip: BlockNumber,
+    ) -> Result<(), PrunerError> {
+        let finished_exex_height = self
+            .finished_exex_height
+            .as_ref()
+            .map_or(FinishedExExHeight::NoExExs, |height| *height.borrow());
+        let Some(tip) = finished_exex_height.prune_tip(tip) else {
+            debug!(target: "sync::pruner", tip, "Not all ExExs reported a finished height");
+            return Ok(())
+        };
+
+        for part in PrunePart::ALL {
+            let Some(target) = self.modes.distance(part).and_then(|d| tip.checked_sub(d)) else {
+                continue
+            };
+            loop {
+                let from = match part.get_checkpoint(tx.deref())? {
+                    Some(checkpoint) if checkpoint >= target => break,
+                    Some(checkpoint) => checkpoint + 1,
+                    None => 0,
+                };
+                let to = target.min(from.saturating_add(self.batch_size - 1));
+
+                debug!(target: "sync::pruner", %part, from, to, target, "Pruning blocks");
+                prune_blocks(tx, part, from, to)?;
+                part.save_checkpoint(tx.deref(), to)?;
+                tx.commit()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prunes the blocks up to the new tip of every canonical state notification, in new database
+    /// transactions, until the sender of the `events` is dropped.
+    ///
+    /// Failures are logged and retried with the next notification. Skipped notifications are
+    /// ignored, since the next one prunes all the blocks up to its tip anyway.
+    pub async fn run_on_canonical_state<DB: Database>(
+        self,
+        db: Arc<DB>,
+        mut events: CanonStateNotifications,
+    ) {
+        loop {
+            let tip = match events.recv().await {
+                Ok(notification) => notification.tip().number,
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(
+                        target: "sync::pruner",
+                        skipped,
+                        "Skipped canonical state notifications"
+                    );
+                    continue
+                }
+                Err(RecvError::Closed) => return,
+            };
+
+            let result = Transaction::new(db.as_ref()).map_err(PrunerError::from).and_then(|tx| {
+                let mut tx = tx.with_static_files(self.static_files.clone());
+                self.run(&mut tx, tip)
+            });
+            if let Err(err) = result {
+                warn!(target: "sync::pruner", tip, ?err, "Failed to prune blocks");
+            }
+        }
+    }
+}
+
+/// Deletes the data of the `part` for the blocks `from..=to`.
+fn prune_blocks<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    part: PrunePart,
+    from: BlockNumber,
+    to: BlockNumber,
+) -> Result<(), PrunerError> {
+    let (start_tx, start_transition) = tx.get_next_block_ids(from)?;
+    let (end_tx, end_transition) = tx.get_next_block_ids(to + 1)?;
+    match part {
+        PrunePart::Receipts => {
+            tx.delete_range::<tables::Receipts>(start_tx..end_tx)?;
+            tx.delete_range::<tables::Logs>(start_tx..end_tx)?;
+        }
+        PrunePart::TransactionLookup => {
+            prune_transaction_lookup(tx, start_tx..end_tx)?;
+        }
+        PrunePart::SenderRecovery => {
+            tx.delete_range::<tables::TxSenders>(start_tx..end_tx)?;
+        }
+        PrunePart::AccountHistory => {
+            let transitions = start_transition..end_transition;
+            let addresses = tx
+                .cursor_read::<tables::AccountChangeSet>()?
+                .walk_range(transitions.clone())?
+                .map(|entry| entry.map(|(_, account)| account.address))
+                .collect::<Result<BTreeSet<_>, _>>()?;
+            tx.delete_range::<tables::AccountChangeSet>(transitions)?;
+            prune_shards::<_, tables::AccountHistory>(
+                tx,
+                addresses.into_iter().map(|address| ShardedKey::new(address, 0)),
+                end_transition,
+                |first, key| first.key == key.key,
+            )?;
+        }
+        PrunePart::StorageHistory => {
+            let transitions = TransitionIdAddress((start_transition, Address::zero()))..
+                TransitionIdAddress((end_transition, Address::zero()));
+            let slots = tx
+                .cursor_read::<tables::StorageChangeSet>()?
+                .walk_range(transitions.clone())?
+                .map(|entry| entry.map(|(key, slot)| (key.address(), slot.key)))
+                .collect::<Result<BTreeSet<_>, _>>()?;
+            tx.delete_range::<tables::StorageChangeSet>(transitions)?;
+            prune_shards::<_, tables::StorageHistory>(
+                tx,
+                slots.into_iter().map(|(address, key)| StorageShardedKey::new(address, key, 0)),
+                end_transition,
+                |first, key| {
+                    first.address == key.address && first.sharded_key.key == key.sharded_key.key
+                },
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes the [`tables::TxHashNumber`] entries of the transactions in `range`.
+///
+/// The transactions are read from the static files once they were moved there.
+fn prune_transaction_lookup<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    range: Range<TxNumber>,
+) -> Result<(), TransactionError> {
+    for (_, transaction) in tx.get_transactions(range)? {
+        tx.delete::<tables::TxHashNumber>(transaction.hash(), None)?;
+    }
+    Ok(())
+}
+
+/// Removes the transitions below `end` from the shards of the history table `T`, for each of the
+/// `keys` of the first shard of an account or slot, whose highest transition is zero.
+///
+/// The shards of a key are walked from its first one: the shards with only pruned transitions are
+/// deleted, and the first shard left is trimmed. `same` tells if a key belongs to the same account
+/// or slot as the first one.
+fn prune_shards<DB, T>(
+    tx: &Transaction<'_, DB>,
+    keys: impl IntoIterator<Item = T::Key>,
+    end: TransitionId,
+    same: impl Fn(&T::Key, &T::Key) -> bool,
+) -> Result<(), DbError>
+where
+    DB: Database,
+    T: Table<Value = TransitionList>,
+{
+    let mut cursor = tx.cursor_write::<T>()?;
+    for first in keys {
+        let mut entry = cursor.seek(first.clone())?;
+        while let Some((key, list)) = entry.filter(|(key, _)| same(&first, key)) {
+            if list.iter(0).next().expect("lists are not empty") >= end as usize {
+                break
+            }
+
+            let kept = list.iter(0).skip_while(|index| *index < end as usize).collect::<Vec<_>>();
+            if kept.is_empty() {
+                cursor.delete_current()?;
+                entry = cursor.next()?;
+                continue
+            }
+            cursor.upsert(
+                key,
+                TransitionList::new(kept).expect("indices are sorted and not empty"),
+            )?;
+            break
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestTransaction;
+    use reth_db::models::AccountBeforeTx;
+    use reth_executor::{
+        blockchain_tree::{
+            CanonStateNotification, CanonStateNotificationSender, CanonStateSubscriptions, Chain,
+        },
+        execution_result::ExecutionResult,
+    };
+    use reth_interfaces::test_utils::generators::random_block_range;
+    use reth_primitives::{SealedBlock, H256};
+    use reth_static_file::StaticFileProducer;
+
+    /// Inserts the blocks, with the transaction lookups and senders of their transactions.
+    fn insert_blocks(tx: &TestTransaction, blocks: &[SealedBlock]) {
+        tx.insert_blocks(blocks.iter(), None).expect("failed to insert blocks");
+        tx.commit(|tx| {
+            let mut tx_number = 0;
+            for block in &blocks {
+                tx.put::<tables::BlockTransitionIndex>(block.number, block.number)?;
+                for transaction in &block.body {
+                    tx.put::<tables::TxHashNumber>(transaction.hash(), tx_number)?;
+                    tx.put::<tables::TxSenders>(tx_number, Address::zero())?;
+                    tx_number += 1;
+                }
+            }
+            Ok(())
+        })
+        .expect("failed to insert transactions");
+    }
+
+    #[test]
+    fn prune_transactions() {
+        let tx = TestTransaction::default();
+        let blocks = random_block_range(0..201, H256::zero(), 0..3);
+        insert_blocks(&tx, &blocks);
+
+        let pruner = Pruner::new(PruneModes {
+            transaction_lookup: Some(10),
+            sender_recovery: Some(150),
+            ..Default::default()
+        });
+        let mut db = tx.inner();
+        pruner.run(&mut db, 200).expect("failed to prune");
+        // Running again up to the same tip is a noop
+        pruner.run(&mut db, 200).expect("failed to prune");
+        db.commit().expect("failed to commit");
+
+        // The distance of the transaction lookup is raised to the minimum, so blocks up to 72
+        // are pruned for it and up to 50 for the senders.
+        let txs_from =
+            |block: usize| -> usize { blocks[block..].iter().map(|b| b.body.len()).sum() };
+        let total = txs_from(0);
+
+        let lookup = tx.table::<tables::TxHashNumber>().unwrap();
+        assert_eq!(lookup.len(), txs_from(73));
+        assert!(lookup.iter().all(|(_, tx_number)| *tx_number >= (total - txs_from(73)) as u64));
+
+        let senders = tx.table::<tables::TxSenders>().unwrap();
+        assert_eq!(senders.len(), txs_from(51));
+        assert!(senders.iter().all(|(tx_number, _)| *tx_number >= (total - txs_from(51)) as u64));
+
+        tx.query(|tx| {
+            assert_eq!(PrunePart::TransactionLookup.get_checkpoint(tx)?, Some(72));
+            assert_eq!(PrunePart::SenderRecovery.get_checkpoint(tx)?, Some(50));
+            assert_eq!(PrunePart::Receipts.get_checkpoint(tx)?, None);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn prune_in_batches() {
+        let tx = TestTransaction::default();
+        let blocks = random_block_range(0..201, H256::zero(), 0..3);
+        insert_blocks(&tx, &blocks);
+
+        let pruner = Pruner::new(PruneModes { sender_recovery: Some(150), ..Default::default() })
+            .with_batch_size(7);
+        let mut db = tx.inner();
+        pruner.run(&mut db, 200).expect("failed to prune");
+
+        // The batches are committed by the pruner
+        let txs_from =
+            |block: usize| -> usize { blocks[block..].iter().map(|b| b.body.len()).sum() };
+        assert_eq!(tx.table::<tables::TxSenders>().unwrap().len(), txs_from(51));
+        tx.query(|tx| {
+            assert_eq!(PrunePart::SenderRecovery.get_checkpoint(tx)?, Some(50));
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn prune_transactions_moved_to_static_files() {
+        let tx = TestTransaction::default();
+        let blocks = random_block_range(0..201, H256::zero(), 1..3);
+        insert_blocks(&tx, &blocks);
+
+        let pruner =
+            Pruner::new(PruneModes { transaction_lookup: Some(150), ..Default::default() });
+        let mut db = tx.inner();
+        pruner.run(&mut db, 190).expect("failed to prune");
+        drop(db);
+
+        // The blocks up to 49 are moved, along with the body of the last pruned block
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = StaticFileProvider::open(dir.path()).unwrap();
+        let producer = StaticFileProducer::new(static_files.clone(), 150).with_blocks_per_file(10);
+        producer.run(tx.inner_raw().as_ref(), 200).expect("failed to move blocks");
+
+        let mut db = tx.inner().with_static_files(Some(static_files));
+        assert_eq!(db.get::<tables::BlockBodies>(40).unwrap(), None);
+        pruner.run(&mut db, 200).expect("failed to prune");
+        drop(db);
+
+        let txs_from =
+            |block: usize| -> usize { blocks[block..].iter().map(|b| b.body.len()).sum() };
+        assert_eq!(tx.table::<tables::TxHashNumber>().unwrap().len(), txs_from(51));
+        tx.query(|tx| {
+            assert_eq!(PrunePart::TransactionLookup.get_checkpoint(tx)?, Some(50));
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn prune_account_history() {
+        let tx = TestTransaction::default();
+        let blocks = random_block_range(0..201, H256::zero(), 0..3);
+        insert_blocks(&tx, &blocks);
+
+        // The transition of each block is its number, the shards of `a` are split at 40
+        let (a, b, c) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        tx.commit(|tx| {
+            for (transition, address) in [(10, a), (30, b), (40, a), (60, a), (70, b), (100, c)] {
+                tx.put::<tables::AccountChangeSet>(
+                    transition,
+                    AccountBeforeTx { address, info: None },
+                )?;
+            }
+            tx.put::<tables::AccountHistory>(ShardedKey::new(a, 40), vec![10u64, 40].into())?;
+            tx.put::<tables::AccountHistory>(ShardedKey::new(a, u64::MAX), vec![60u64].into())?;
+            tx.put::<tables::AccountHistory>(ShardedKey::new(b, u64::MAX), vec![30u64, 70].into())?;
+            tx.put::<tables::AccountHistory>(ShardedKey::new(c, u64::MAX), vec![100u64].into())?;
+            Ok(())
+        })
+        .expect("failed to insert history");
+
+        let pruner = Pruner::new(PruneModes { account_history: Some(150), ..Default::default() });
+        let mut db = tx.inner();
+        pruner.run(&mut db, 200).expect("failed to prune");
+        db.commit().expect("failed to commit");
+
+        // The transitions up to the one of block 50 are pruned
+        let changesets = tx.table::<tables::AccountChangeSet>().unwrap();
+        assert_eq!(
+            changesets.iter().map(|(transition, _)| *transition).collect::<Vec<_>>(),
+            vec![60, 70, 100]
+        );
+        let history = tx
+            .table::<tables::AccountHistory>()
+            .unwrap()
+            .into_iter()
+            .map(|(key, list)| (key, list.iter(0).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            history,
+            vec![
+                (ShardedKey::new(a, u64::MAX), vec![60]),
+                (ShardedKey::new(b, u64::MAX), vec![70]),
+                (ShardedKey::new(c, u64::MAX), vec![100]),
+            ]
+        );
+        tx.query(|tx| {
+            assert_eq!(PrunePart::AccountHistory.get_checkpoint(tx)?, Some(50));
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn prune_on_canonical_state() {
+        let tx = TestTransaction::default();
+        let blocks = random_block_range(0..201, H256::zero(), 0..3);
+        insert_blocks(&tx, &blocks);
+
+        let sender = CanonStateNotificationSender::default();
+        let events = sender.subscribe_to_canonical_state();
+        let result =
+            ExecutionResult { tx_changesets: vec![], block_changesets: Default::default() };
+        let tip = blocks[200].clone();
+        let new = Arc::new(Chain::new((199, tip.parent_hash), tip, result));
+        sender.notify(CanonStateNotification::Commit { new });
+        drop(sender);
+
+        let pruner = Pruner::new(PruneModes { sender_recovery: Some(150), ..Default::default() });
+        pruner.run_on_canonical_state(tx.inner_raw(), events).await;
+
+        tx.query(|tx| {
+            assert_eq!(PrunePart::SenderRecovery.get_checkpoint(tx)?, Some(50));
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn prune_up_to_finished_exex_height() {
+        let tx = TestTransaction::default();
+        let blocks = random_block_range(0..201, H256::zero(), 0..3);
+        insert_blocks(&tx, &blocks);
+
+        let (finished_exex_height, receiver) = watch::channel(FinishedExExHeight::NotReady);
+        let pruner = Pruner::new(PruneModes { sender_recovery: Some(150), ..Default::default() })
+            .with_finished_exex_height(receiver);
+        let checkpoint = || {
+            let mut db = tx.inner();
+            pruner.run(&mut db, 200).expect("failed to prune");
+            PrunePart::SenderRecovery.get_checkpoint(db.deref()).unwrap()
+        };
+
+        // Nothing is pruned until the ExExs are ready
+        assert_eq!(checkpoint(), None);
+
+        // The distance is counted from the finished height of the ExExs
+        finished_exex_height.send(FinishedExExHeight::Height(190)).unwrap();
+        assert_eq!(checkpoint(), Some(40));
+    }
+}
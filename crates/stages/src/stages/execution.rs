@@ -2,21 +2,14 @@ use crate::{
     exec_or_return, ExecAction, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput,
     UnwindOutput,
 };
-use reth_db::{
-    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
-    database::Database,
-    models::TransitionIdAddress,
-    tables,
-    transaction::{DbTx, DbTxMut},
-};
-use reth_executor::{
-    execution_result::AccountChangeSet,
-    revm_wrap::{State, SubState},
-};
+use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx};
+use reth_executor::revm_wrap::{State, SubState};
 use reth_interfaces::provider::Error as ProviderError;
-use reth_primitives::{Address, Block, ChainSpec, Hardfork, StorageEntry, H256, MAINNET, U256};
-use reth_provider::{LatestStateProviderRef, Transaction};
-use std::fmt::Debug;
+use reth_primitives::{Address, Block, ChainSpec, Hardfork, MAINNET};
+use reth_provider::{
+    LatestStateProviderRef, PrefetchCache, PrefetchTargets, PrefetchedStateProvider, Transaction,
+};
+use std::{fmt::Debug, sync::mpsc};
 use tracing::*;
 
 /// The [`StageId`] of the execution stage.
@@ -56,11 +49,18 @@ pub struct ExecutionStage {
     pub chain_spec: ChainSpec,
     /// Commit threshold
     pub commit_threshold: u64,
+    /// Whether to read the state the next block touches in the background while the current
+    /// block executes, see [PrefetchCache].
+    ///
+    /// The prefetched state is read with a separate read-only transaction, so this must only be
+    /// enabled if the plain state of the transaction the stage runs in is committed, as it is in
+    /// the pipeline, which commits after every stage run.
+    pub prefetch: bool,
 }
 
 impl Default for ExecutionStage {
     fn default() -> Self {
-        Self { chain_spec: MAINNET.clone(), commit_threshold: 1_000 }
+        Self { chain_spec: MAINNET.clone(), commit_threshold: 1_000, prefetch: true }
     }
 }
 
@@ -107,11 +107,8 @@ impl ExecutionStage {
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        // Create state provider with cached state
-        let mut state_provider = SubState::new(State::new(LatestStateProviderRef::new(&**tx)));
-
-        // Fetch transactions, execute them and generate results
-        let mut block_change_patches = Vec::with_capacity(block_batch.len());
+        // Fetch the transactions and their signers
+        let mut blocks = Vec::with_capacity(block_batch.len());
         for (header, td, body, ommers, withdrawals) in block_batch.into_iter() {
             let block_number = header.number;
             tracing::trace!(target: "sync::stages::execution", ?block_number, "Execute block.");
@@ -143,19 +140,53 @@ impl ExecutionStage {
                 signers.push(tx);
             }
 
-            trace!(target: "sync::stages::execution", number = block_number, txs = transactions.len(), "Executing block");
-
-            let changeset = reth_executor::executor::execute_and_verify_receipt(
-                &Block { header, body: transactions, ommers, withdrawals },
-                td,
-                Some(signers),
-                &self.chain_spec,
-                &mut state_provider,
-            )
-            .map_err(|error| StageError::ExecutionError { block: block_number, error })?;
-            block_change_patches.push((changeset, block_number));
+            blocks.push((Block { header, body: transactions, ommers, withdrawals }, td, signers));
         }
 
+        let db = tx.inner();
+        let cache = PrefetchCache::default();
+        let block_change_patches = std::thread::scope(|scope| -> Result<_, StageError> {
+            // Read the state of the next block while the current one executes
+            let prefetcher = self.prefetch.then(|| {
+                let (sender, targets) = mpsc::channel();
+                let cache = cache.clone();
+                scope.spawn(move || prefetch_state(db, cache, targets));
+                sender
+            });
+
+            // Create state provider with cached state
+            let mut state_provider = SubState::new(State::new(PrefetchedStateProvider::new(
+                LatestStateProviderRef::new(&**tx),
+                cache,
+            )));
+
+            // Execute the transactions and generate results
+            let mut block_change_patches = Vec::with_capacity(blocks.len());
+            let mut blocks = blocks.into_iter().peekable();
+            while let Some((block, td, signers)) = blocks.next() {
+                if let (Some(prefetcher), Some((next, _, next_signers))) =
+                    (&prefetcher, blocks.peek())
+                {
+                    // the prefetcher only stops on errors, which it logs
+                    let _ = prefetcher.send(prefetch_targets(next, next_signers));
+                }
+
+                let block_number = block.number;
+                trace!(target: "sync::stages::execution", number = block_number, txs = block.body.len(), "Executing block");
+
+                let changeset = reth_executor::executor::execute_and_verify_receipt(
+                    &block,
+                    td,
+                    Some(signers),
+                    &self.chain_spec,
+                    &mut state_provider,
+                )
+                .map_err(|error| StageError::ExecutionError { block: block_number, error })?;
+                block_change_patches.push((changeset, block_number));
+            }
+            Ok(block_change_patches)
+        })?;
+
         // Get last tx count so that we can know amount of transaction in the block.
         let mut current_transition_id = tx.get_block_transition(last_block)?;
         info!(target: "sync::stages::execution", current_transition_id, blocks = block_change_patches.len(), "Inserting execution results");
@@ -164,102 +195,11 @@ impl ExecutionStage {
         for (results, block_number) in block_change_patches.into_iter() {
             let spurious_dragon_active =
                 self.chain_spec.fork(Hardfork::SpuriousDragon).active_at_block(block_number);
-            // insert state change set
-            for result in results.tx_changesets.into_iter() {
-                for (address, account_change_set) in result.changeset.into_iter() {
-                    let AccountChangeSet { account, wipe_storage, storage } = account_change_set;
-                    // apply account change to db. Updates AccountChangeSet and PlainAccountState
-                    // tables.
-                    trace!(target: "sync::stages::execution", ?address, current_transition_id, ?account, wipe_storage, "Applying account changeset");
-                    account.apply_to_db(
-                        &**tx,
-                        address,
-                        current_transition_id,
-                        spurious_dragon_active,
-                    )?;
-
-                    let storage_id = TransitionIdAddress((current_transition_id, address));
-
-                    // cast key to H256 and trace the change
-                    let storage = storage
-                        .into_iter()
-                        .map(|(key, (old_value,new_value))| {
-                            let hkey = H256(key.to_be_bytes());
-                            trace!(target: "sync::stages::execution", ?address, current_transition_id, ?hkey, ?old_value, ?new_value, "Applying storage changeset");
-                            (hkey, old_value,new_value)
-                        })
-                        .collect::<Vec<_>>();
-
-                    let mut cursor_storage_changeset =
-                        tx.cursor_write::<tables::StorageChangeSet>()?;
-                    cursor_storage_changeset.seek_exact(storage_id)?;
-
-                    if wipe_storage {
-                        // iterate over storage and save them before entry is deleted.
-                        tx.cursor_read::<tables::PlainStorageState>()?
-                            .walk(Some(address))?
-                            .take_while(|res| {
-                                res.as_ref().map(|(k, _)| *k == address).unwrap_or_default()
-                            })
-                            .try_for_each(|entry| {
-                                let (_, old_value) = entry?;
-                                cursor_storage_changeset.append(storage_id, old_value)
-                            })?;
-
-                        // delete all entries
-                        tx.delete::<tables::PlainStorageState>(address, None)?;
-
-                        // insert storage changeset
-                        for (key, _, new_value) in storage {
-                            // old values are already cleared.
-                            if new_value != U256::ZERO {
-                                tx.put::<tables::PlainStorageState>(
-                                    address,
-                                    StorageEntry { key, value: new_value },
-                                )?;
-                            }
-                        }
-                    } else {
-                        // insert storage changeset
-                        for (key, old_value, new_value) in storage {
-                            let old_entry = StorageEntry { key, value: old_value };
-                            let new_entry = StorageEntry { key, value: new_value };
-                            // insert into StorageChangeSet
-                            cursor_storage_changeset.append(storage_id, old_entry)?;
-
-                            // Always delete old value as duplicate table, put will not override it
-                            tx.delete::<tables::PlainStorageState>(address, Some(old_entry))?;
-                            if new_value != U256::ZERO {
-                                tx.put::<tables::PlainStorageState>(address, new_entry)?;
-                            }
-                        }
-                    }
-                }
-                // insert bytecode
-                for (hash, bytecode) in result.new_bytecodes.into_iter() {
-                    // make different types of bytecode. Checked and maybe even analyzed (needs to
-                    // be packed). Currently save only raw bytes.
-                    let bytecode = bytecode.bytes();
-                    trace!(target: "sync::stages::execution", ?hash, ?bytecode, len = bytecode.len(), "Inserting bytecode");
-                    tx.put::<tables::Bytecodes>(hash, bytecode[..bytecode.len()].to_vec())?;
-
-                    // NOTE: bytecode bytes are not inserted in change set and can be found in
-                    // separate table
-                }
-                current_transition_id += 1;
-            }
-
-            // If there are any post block changes, we will add account changesets to db.
-            for (address, changeset) in results.block_changesets.into_iter() {
-                trace!(target: "sync::stages::execution", ?address, current_transition_id, "Applying block reward");
-                changeset.apply_to_db(
-                    &**tx,
-                    address,
-                    current_transition_id,
-                    spurious_dragon_active,
-                )?;
-            }
-            current_transition_id += 1;
+            // one transition per transaction, and one for the post block changes
+            let transitions = results.tx_changesets.len() as u64 + 1;
+            trace!(target: "sync::stages::execution", block_number, current_transition_id, "Applying execution result");
+            results.write_to_db(&**tx, current_transition_id, spurious_dragon_active)?;
+            current_transition_id += transitions;
         }
 
         let done = !capped;
@@ -271,7 +211,45 @@ impl ExecutionStage {
 impl ExecutionStage {
     /// Create new execution stage with specified config.
     pub fn new(chain_spec: ChainSpec, commit_threshold: u64) -> Self {
-        Self { chain_spec, commit_threshold }
+        Self { chain_spec, commit_threshold, prefetch: true }
+    }
+}
+
+/// Returns the accounts and storage slots the block is known to touch before it is executed.
+fn prefetch_targets(block: &Block, signers: &[Address]) -> PrefetchTargets {
+    let mut targets = PrefetchTargets::default();
+    targets.insert_account(block.beneficiary);
+    for (transaction, signer) in block.body.iter().zip(signers) {
+        targets.insert_transaction(&transaction.transaction, *signer);
+    }
+    for withdrawal in block.withdrawals.iter().flatten() {
+        targets.insert_account(withdrawal.address);
+    }
+    targets
+}
+
+/// Reads the state of the received targets into the cache, with a new read-only transaction,
+/// until the sender is dropped.
+///
+/// Prefetching is best effort, so it stops on the first error.
+fn prefetch_state<DB: Database>(
+    db: &DB,
+    cache: PrefetchCache,
+    targets: mpsc::Receiver<PrefetchTargets>,
+) {
+    let tx = match db.tx() {
+        Ok(tx) => tx,
+        Err(err) => {
+            debug!(target: "sync::stages::execution", ?err, "Failed to open prefetch transaction");
+            return
+        }
+    };
+    let provider = LatestStateProviderRef::new(&tx);
+    for targets in targets {
+        if let Err(err) = cache.prefetch(&provider, targets) {
+            debug!(target: "sync::stages::execution", ?err, "Failed to prefetch state");
+            return
+        }
     }
 }
 
@@ -313,10 +291,6 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
     ) -> Result<UnwindOutput, StageError> {
         info!(target: "sync::stages::execution", to_block = input.unwind_to, "Unwinding");
 
-        // Acquire changeset cursors
-        let mut account_changeset = tx.cursor_dup_write::<tables::AccountChangeSet>()?;
-        let mut storage_changeset = tx.cursor_dup_write::<tables::StorageChangeSet>()?;
-
         let from_transition_rev = tx.get_block_transition(input.unwind_to)?;
         let to_transition_rev = tx.get_block_transition(input.stage_progress)?;
 
@@ -331,62 +305,7 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
             return Ok(UnwindOutput { stage_progress: input.unwind_to })
         }
 
-        // get all batches for account change
-        // Check if walk and walk_dup would do the same thing
-        let account_changeset_batch = account_changeset
-            .walk_range(from_transition_rev..to_transition_rev)?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        // revert all changes to PlainState
-        for (_, changeset) in account_changeset_batch.into_iter().rev() {
-            if let Some(account_info) = changeset.info {
-                tx.put::<tables::PlainAccountState>(changeset.address, account_info)?;
-            } else {
-                tx.delete::<tables::PlainAccountState>(changeset.address, None)?;
-            }
-        }
-
-        // get all batches for storage change
-        let storage_changeset_batch = storage_changeset
-            .walk_range(
-                TransitionIdAddress((from_transition_rev, Address::zero()))..
-                    TransitionIdAddress((to_transition_rev, Address::zero())),
-            )?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        // revert all changes to PlainStorage
-        let mut plain_storage_cursor = tx.cursor_dup_write::<tables::PlainStorageState>()?;
-
-        for (key, storage) in storage_changeset_batch.into_iter().rev() {
-            let address = key.address();
-            if let Some(v) = plain_storage_cursor.seek_by_key_subkey(address, storage.key)? {
-                if v.key == storage.key {
-                    plain_storage_cursor.delete_current()?;
-                }
-            }
-            if storage.value != U256::ZERO {
-                plain_storage_cursor.upsert(address, storage)?;
-            }
-        }
-
-        // Discard unwinded changesets
-        let mut rev_acc_changeset_walker = account_changeset.walk_back(None)?;
-        while let Some((transition_id, _)) = rev_acc_changeset_walker.next().transpose()? {
-            if transition_id < from_transition_rev {
-                break
-            }
-            // delete all changesets
-            tx.delete::<tables::AccountChangeSet>(transition_id, None)?;
-        }
-
-        let mut rev_storage_changeset_walker = storage_changeset.walk_back(None)?;
-        while let Some((key, _)) = rev_storage_changeset_walker.next().transpose()? {
-            if key.transition_id() < from_transition_rev {
-                break
-            }
-            // delete all changesets
-            tx.delete::<tables::StorageChangeSet>(key, None)?;
-        }
+        tx.unwind_plain_state(from_transition_rev..to_transition_rev)?;
 
         Ok(UnwindOutput { stage_progress: input.unwind_to })
     }
@@ -402,9 +321,11 @@ mod tests {
     use reth_db::{
         mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
         models::AccountBeforeTx,
+        transaction::DbTxMut,
     };
     use reth_primitives::{
-        hex_literal::hex, keccak256, Account, ChainSpecBuilder, SealedBlock, H160, U256,
+        hex_literal::hex, keccak256, Account, ChainSpecBuilder, SealedBlock, StorageEntry, H160,
+        H256, U256,
     };
     use reth_provider::insert_canonical_block;
     use reth_rlp::Decodable;
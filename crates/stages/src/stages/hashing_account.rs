@@ -1,16 +1,13 @@
 use crate::{ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput};
 use reth_db::{
-    cursor::{DbCursorRO, DbCursorRW},
+    cursor::DbCursorRO,
     database::Database,
     tables,
     transaction::{DbTx, DbTxMut},
 };
-use reth_primitives::{keccak256, Account, Address};
+use reth_primitives::keccak256;
 use reth_provider::Transaction;
-use std::{
-    collections::{BTreeMap, BTreeSet},
-    fmt::Debug,
-};
+use std::{collections::BTreeMap, fmt::Debug};
 use tracing::*;
 
 /// The [`StageId`] of the account hashing stage.
@@ -90,38 +87,9 @@ impl<DB: Database> Stage<DB> for AccountHashingStage {
                 break
             }
         } else {
-            let mut plain_accounts = tx.cursor_read::<tables::PlainAccountState>()?;
-            let mut hashed_accounts = tx.cursor_write::<tables::HashedAccount>()?;
-
-            // Aggregate all transition changesets and and make list of account that have been
-            // changed.
-            tx.cursor_read::<tables::AccountChangeSet>()?
-                .walk_range(from_transition..to_transition)?
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                // fold all account to one set of changed accounts
-                .fold(BTreeSet::new(), |mut accounts: BTreeSet<Address>, (_, account_before)| {
-                    accounts.insert(account_before.address);
-                    accounts
-                })
-                .into_iter()
-                // iterate over plain state and get newest value.
-                // Assumption we are okay to make is that plainstate represent
-                // `previous_stage_progress` state.
-                .map(|address| {
-                    plain_accounts.seek_exact(address).map(|a| (address, a.map(|(_, v)| v)))
-                })
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                .try_for_each(|(address, account)| -> Result<(), StageError> {
-                    let hashed_address = keccak256(address);
-                    if let Some(account) = account {
-                        hashed_accounts.upsert(hashed_address, account)?
-                    } else if hashed_accounts.seek_exact(hashed_address)?.is_some() {
-                        hashed_accounts.delete_current()?;
-                    }
-                    Ok(())
-                })?;
+            // Assumption we are okay to make is that plainstate represent
+            // `previous_stage_progress` state.
+            tx.insert_account_hashes(from_transition..to_transition)?;
         }
 
         info!(target: "sync::stages::hashing_account", "Stage finished");
@@ -140,37 +108,7 @@ impl<DB: Database> Stage<DB> for AccountHashingStage {
         let from_transition_rev = tx.get_block_transition(input.unwind_to)?;
         let to_transition_rev = tx.get_block_transition(input.stage_progress)?;
 
-        let mut hashed_accounts = tx.cursor_write::<tables::HashedAccount>()?;
-
-        // Aggregate all transition changesets and and make list of account that have been changed.
-        tx.cursor_read::<tables::AccountChangeSet>()?
-            .walk_range(from_transition_rev..to_transition_rev)?
-            .collect::<Result<Vec<_>, _>>()?
-            .into_iter()
-            .rev()
-            // fold all account to get the old balance/nonces and account that needs to be removed
-            .fold(
-                BTreeMap::new(),
-                |mut accounts: BTreeMap<Address, Option<Account>>, (_, account_before)| {
-                    accounts.insert(account_before.address, account_before.info);
-                    accounts
-                },
-            )
-            .into_iter()
-            // hash addresses and collect it inside sorted BTreeMap.
-            // We are doing keccak only once per address.
-            .map(|(address, account)| (keccak256(address), account))
-            .collect::<BTreeMap<_, _>>()
-            .into_iter()
-            // Apply values to HashedState (if Account is None remove it);
-            .try_for_each(|(hashed_address, account)| -> Result<(), StageError> {
-                if let Some(account) = account {
-                    hashed_accounts.upsert(hashed_address, account)?;
-                } else if hashed_accounts.seek_exact(hashed_address)?.is_some() {
-                    hashed_accounts.delete_current()?;
-                }
-                Ok(())
-            })?;
+        tx.unwind_account_hashes(from_transition_rev..to_transition_rev)?;
 
         Ok(UnwindOutput { stage_progress: input.unwind_to })
     }
@@ -185,7 +123,7 @@ mod tests {
     };
     use assert_matches::assert_matches;
     use reth_interfaces::test_utils::generators::random_block_range;
-    use reth_primitives::{Account, SealedBlock, H256, U256};
+    use reth_primitives::{Account, Address, SealedBlock, H256, U256};
     use reth_provider::insert_canonical_block;
     use test_utils::*;
 
@@ -1,18 +1,14 @@
 use crate::{ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput};
 use num_traits::Zero;
 use reth_db::{
-    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
+    cursor::DbDupCursorRO,
     database::Database,
-    models::TransitionIdAddress,
     tables,
     transaction::{DbTx, DbTxMut},
 };
-use reth_primitives::{keccak256, Address, StorageEntry, H256, U256};
+use reth_primitives::{keccak256, StorageEntry};
 use reth_provider::Transaction;
-use std::{
-    collections::{BTreeMap, BTreeSet},
-    fmt::Debug,
-};
+use std::{collections::BTreeMap, fmt::Debug};
 use tracing::*;
 
 /// The [`StageId`] of the storage hashing stage.
@@ -133,63 +129,9 @@ impl<DB: Database> Stage<DB> for StorageHashingStage {
                 }
             }
         } else {
-            let mut plain_storage = tx.cursor_dup_read::<tables::PlainStorageState>()?;
-            let mut hashed_storage = tx.cursor_dup_write::<tables::HashedStorage>()?;
-
-            // Aggregate all transition changesets and and make list of storages that have been
-            // changed.
-            tx.cursor_read::<tables::StorageChangeSet>()?
-                .walk_range(
-                    TransitionIdAddress((from_transition, Address::zero()))..
-                        TransitionIdAddress((to_transition, Address::zero())),
-                )?
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                // fold all storages and save its old state so we can remove it from HashedStorage
-                // it is needed as it is dup table.
-                .fold(
-                    BTreeMap::new(),
-                    |mut accounts: BTreeMap<Address, BTreeSet<H256>>,
-                     (TransitionIdAddress((_, address)), storage_entry)| {
-                        accounts.entry(address).or_default().insert(storage_entry.key);
-                        accounts
-                    },
-                )
-                .into_iter()
-                // iterate over plain state and get newest storage value.
-                // Assumption we are okay with is that plain state represent
-                // `previous_stage_progress` state.
-                .map(|(address, storage)| {
-                    storage
-                        .into_iter()
-                        .map(|key| {
-                            plain_storage
-                                .seek_by_key_subkey(address, key)
-                                .map(|ret| (keccak256(key), ret.map(|e| e.value)))
-                        })
-                        .collect::<Result<BTreeMap<_, _>, _>>()
-                        .map(|storage| (keccak256(address), storage))
-                })
-                .collect::<Result<BTreeMap<_, _>, _>>()?
-                .into_iter()
-                // Hash the address and key and apply them to HashedStorage (if Storage is None
-                // just remove it);
-                .try_for_each(|(address, storage)| {
-                    storage.into_iter().try_for_each(|(key, val)| -> Result<(), StageError> {
-                        if hashed_storage
-                            .seek_by_key_subkey(address, key)?
-                            .filter(|entry| entry.key == key)
-                            .is_some()
-                        {
-                            hashed_storage.delete_current()?;
-                        }
-
-                        if let Some(value) = val {
-                            hashed_storage.upsert(address, StorageEntry { key, value })?;
-                        }
-                        Ok(())
-                    })
-                })?;
+            // Assumption we are okay with is that plain state represent
+            // `previous_stage_progress` state.
+            tx.insert_storage_hashes(from_transition..to_transition)?;
         }
 
         info!(target: "sync::stages::hashing_storage", "Stage finished");
@@ -205,47 +147,7 @@ impl<DB: Database> Stage<DB> for StorageHashingStage {
         let from_transition_rev = tx.get_block_transition(input.unwind_to)?;
         let to_transition_rev = tx.get_block_transition(input.stage_progress)?;
 
-        let mut hashed_storage = tx.cursor_dup_write::<tables::HashedStorage>()?;
-
-        // Aggregate all transition changesets and make list of accounts that have been changed.
-        tx.cursor_read::<tables::StorageChangeSet>()?
-            .walk_range(
-                TransitionIdAddress((from_transition_rev, Address::zero()))..
-                    TransitionIdAddress((to_transition_rev, Address::zero())),
-            )?
-            .collect::<Result<Vec<_>, _>>()?
-            .into_iter()
-            .rev()
-            // fold all account to get the old balance/nonces and account that needs to be removed
-            .fold(
-                BTreeMap::new(),
-                |mut accounts: BTreeMap<(Address, H256), U256>,
-                 (TransitionIdAddress((_, address)), storage_entry)| {
-                    accounts.insert((address, storage_entry.key), storage_entry.value);
-                    accounts
-                },
-            )
-            .into_iter()
-            // hash addresses and collect it inside sorted BTreeMap.
-            // We are doing keccak only once per address.
-            .map(|((address, key), value)| ((keccak256(address), keccak256(key)), value))
-            .collect::<BTreeMap<_, _>>()
-            .into_iter()
-            // Apply values to HashedStorage (if Value is zero just remove it);
-            .try_for_each(|((address, key), value)| -> Result<(), StageError> {
-                if hashed_storage
-                    .seek_by_key_subkey(address, key)?
-                    .filter(|entry| entry.key == key)
-                    .is_some()
-                {
-                    hashed_storage.delete_current()?;
-                }
-
-                if value != U256::ZERO {
-                    hashed_storage.upsert(address, StorageEntry { key, value })?;
-                }
-                Ok(())
-            })?;
+        tx.unwind_storage_hashes(from_transition_rev..to_transition_rev)?;
 
         Ok(UnwindOutput { stage_progress: input.unwind_to })
     }
@@ -260,14 +162,14 @@ mod tests {
     };
     use assert_matches::assert_matches;
     use reth_db::{
-        cursor::DbCursorRW,
+        cursor::{DbCursorRO, DbCursorRW},
         mdbx::{tx::Tx, WriteMap, RW},
         models::{StoredBlockBody, TransitionIdAddress},
     };
     use reth_interfaces::test_utils::generators::{
         random_block_range, random_contract_account_range,
     };
-    use reth_primitives::{SealedBlock, StorageEntry, H256, U256};
+    use reth_primitives::{Address, SealedBlock, H256, U256};
 
     stage_test_suite_ext!(StorageHashingTestRunner, storage_hashing);
 
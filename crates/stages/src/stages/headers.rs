@@ -136,7 +136,18 @@ where
         let mut cursor_header = tx.cursor_write::<tables::Headers>()?;
         let mut cursor_canonical = tx.cursor_write::<tables::CanonicalHeaders>()?;
 
+        // If the last header in the database is lower than the first one we write, which is the
+        // case unless we are filling a gap below already written headers, we can just append.
+        let first = headers.iter().rev().find(|header| header.number != 0).map(|h| h.number);
+        let append = match (first, cursor_header.last()?, cursor_canonical.last()?) {
+            (Some(first), Some((last_header, _)), Some((last_canonical, _))) => {
+                last_header < first && last_canonical < first
+            }
+            _ => false,
+        };
+
         let mut latest = None;
+        let mut header_numbers = Vec::with_capacity(headers.len());
         // Since the headers were returned in descending order,
         // iterate them in the reverse order
         for header in headers.into_iter().rev() {
@@ -149,11 +160,18 @@ where
             let header = header.unseal();
             latest = Some(header.number);
 
-            // NOTE: HeaderNumbers are not sorted and can't be inserted with cursor.
-            tx.put::<tables::HeaderNumbers>(header_hash, header_number)?;
-            cursor_header.insert(header_number, header)?;
-            cursor_canonical.insert(header_number, header_hash)?;
+            header_numbers.push((header_hash, header_number));
+            if append {
+                cursor_header.append(header_number, header)?;
+                cursor_canonical.append(header_number, header_hash)?;
+            } else {
+                cursor_header.insert(header_number, header)?;
+                cursor_canonical.insert(header_number, header_hash)?;
+            }
         }
+
+        // NOTE: HeaderNumbers are not sorted by block number, so they are written in one batch.
+        tx.put_many::<tables::HeaderNumbers>(header_numbers)?;
         Ok(latest)
     }
 }
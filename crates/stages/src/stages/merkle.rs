@@ -1,11 +1,14 @@
-use crate::{
-    trie::DBTrieLoader, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput,
-    UnwindOutput,
+use crate::{ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput};
+use reth_db::{
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
 };
-use reth_db::{database::Database, tables, transaction::DbTx};
-use reth_interfaces::consensus;
+use reth_executor::executor::verify_state_root;
+use reth_primitives::{TransitionId, H256};
 use reth_provider::Transaction;
-use std::fmt::Debug;
+use reth_trie::{ParallelStateRoot, PrefixSetLoader, StateRoot};
+use std::{fmt::Debug, ops::Range};
 use tracing::*;
 
 /// The [`StageId`] of the merkle hashing execution stage.
@@ -50,6 +53,32 @@ pub enum MerkleStage {
     Both { clean_threshold: u64 },
 }
 
+/// Rebuilds the state trie from the hashed state and returns its root.
+///
+/// The storage roots are computed in parallel from the committed hashed state, so the hashed
+/// state must not have uncommitted changes. The intermediate hashes are written to the trie
+/// tables of the transaction, so it should be dropped instead of committed if only the root is
+/// needed.
+pub fn calculate_state_root<DB: Database>(tx: &Transaction<'_, DB>) -> Result<H256, StageError> {
+    tx.clear::<tables::AccountsTrie>()?;
+    tx.clear::<tables::StoragesTrie>()?;
+    ParallelStateRoot::new(tx.inner()).write_root(&**tx).map_err(|e| StageError::Fatal(Box::new(e)))
+}
+
+/// Updates the state trie with the given root with the changes of the transitions, reading the
+/// changed keys from the changesets, and returns the updated root.
+fn update_state_root<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    root: H256,
+    transitions: Range<TransitionId>,
+) -> Result<H256, StageError> {
+    let prefix_sets = PrefixSetLoader::new(&**tx).load(transitions)?;
+    StateRoot::new(&**tx)
+        .with_prefix_sets(prefix_sets)
+        .update_root(root)
+        .map_err(|e| StageError::Fatal(Box::new(e)))
+}
+
 impl MerkleStage {
     /// Stage default for the Execution variant.
     pub fn default_execution() -> Self {
@@ -99,31 +128,25 @@ impl<DB: Database> Stage<DB> for MerkleStage {
         let from_transition = tx.get_block_transition(stage_progress)?;
         let to_transition = tx.get_block_transition(previous_stage_progress)?;
 
-        let block_root = tx.get_header(previous_stage_progress)?.state_root;
+        let header = tx.get_header(previous_stage_progress)?;
+        let block_root = header.state_root;
 
         let trie_root = if from_transition == to_transition {
             block_root
         } else if to_transition - from_transition > threshold || stage_progress == 0 {
             debug!(target: "sync::stages::merkle::exec", current = ?stage_progress, target = ?previous_stage_progress, "Rebuilding trie");
             // if there are more blocks than threshold it is faster to rebuild the trie
-            let loader = DBTrieLoader::default();
-            loader.calculate_root(tx).map_err(|e| StageError::Fatal(Box::new(e)))?
+            calculate_state_root(tx)?
         } else {
             debug!(target: "sync::stages::merkle::exec", current = ?stage_progress, target = ?previous_stage_progress, "Updating trie");
-            // Iterate over changeset (similar to Hashing stages) and take new values
+            // only the paths of the keys changed in the changesets are rebuilt
             let current_root = tx.get_header(stage_progress)?.state_root;
-            let loader = DBTrieLoader::default();
-            loader
-                .update_root(tx, current_root, from_transition..to_transition)
-                .map_err(|e| StageError::Fatal(Box::new(e)))?
+            update_state_root(tx, current_root, from_transition..to_transition)?
         };
 
-        if block_root != trie_root {
+        if let Err(error) = verify_state_root(&header, trie_root) {
             warn!(target: "sync::stages::merkle::exec", ?previous_stage_progress, got = ?block_root, expected = ?trie_root, "Block's root state failed verification");
-            return Err(StageError::Validation {
-                block: previous_stage_progress,
-                error: consensus::Error::BodyStateRootDiff { got: trie_root, expected: block_root },
-            })
+            return Err(StageError::Validation { block: previous_stage_progress, error })
         }
 
         info!(target: "sync::stages::merkle::exec", "Stage finished");
@@ -150,15 +173,12 @@ impl<DB: Database> Stage<DB> for MerkleStage {
             return Ok(UnwindOutput { stage_progress: input.unwind_to })
         }
 
-        let loader = DBTrieLoader::default();
         let current_root = tx.get_header(input.stage_progress)?.state_root;
 
         let from_transition = tx.get_block_transition(input.unwind_to)?;
         let to_transition = tx.get_block_transition(input.stage_progress)?;
 
-        loader
-            .update_root(tx, current_root, from_transition..to_transition)
-            .map_err(|e| StageError::Fatal(Box::new(e)))?;
+        update_state_root(tx, current_root, from_transition..to_transition)?;
 
         info!(target: "sync::stages::merkle::unwind", "Stage finished");
         Ok(UnwindOutput { stage_progress: input.unwind_to })
@@ -182,8 +202,16 @@ mod tests {
     use reth_interfaces::test_utils::generators::{
         random_block, random_block_range, random_contract_account_range,
     };
-    use reth_primitives::{keccak256, Account, Address, SealedBlock, StorageEntry, H256, U256};
-    use std::collections::BTreeMap;
+    use reth_primitives::{
+        hex_literal::hex,
+        keccak256,
+        proofs::{genesis_state_root, KeccakHasher},
+        Account, Address, ChainSpec, SealedBlock, StorageEntry, H256, U256,
+    };
+    use reth_rlp::{encode_fixed_size, Encodable};
+    use reth_staged_sync::utils::chainspec::chain_spec_value_parser;
+    use reth_trie::{EthAccount, PrefixSet, StorageRoot};
+    use std::collections::{BTreeMap, HashMap};
 
     stage_test_suite_ext!(MerkleTestRunner, merkle);
 
@@ -244,6 +272,152 @@ mod tests {
         assert!(runner.validate_execution(input, result.ok()).is_ok(), "execution validation");
     }
 
+    /// Returns the root of the secure trie of the RLP encoded values.
+    fn sec_trie_root<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> H256 {
+        H256(triehash::sec_trie_root::<KeccakHasher, _, _, _>(entries).0)
+    }
+
+    fn encode_account(account: Account, storage_root: Option<H256>) -> Vec<u8> {
+        let mut out = Vec::new();
+        match storage_root {
+            Some(root) => EthAccount::from_with_root(account, root).encode(&mut out),
+            None => EthAccount::from(account).encode(&mut out),
+        }
+        out
+    }
+
+    #[test]
+    fn two_accounts_trie() {
+        let tx = TestTransaction::default();
+        let accounts = [
+            (
+                Address::from(hex!("9fe4abd71ad081f091bd06dd1c16f7e92927561e")),
+                Account { nonce: 155, balance: U256::from(414241124), bytecode_hash: None },
+            ),
+            (
+                Address::from(hex!("f8a6edaad4a332e6e550d0915a7fd5300b0b12d1")),
+                Account { nonce: 3, balance: U256::from(78978), bytecode_hash: None },
+            ),
+        ];
+        tx.commit(|tx| {
+            for (address, account) in accounts {
+                tx.put::<tables::HashedAccount>(keccak256(address), account)?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let expected = sec_trie_root(
+            accounts.iter().map(|(address, account)| (address, encode_account(*account, None))),
+        );
+        assert_matches!(calculate_state_root(&tx.inner()), Ok(root) if root == expected);
+    }
+
+    #[test]
+    fn single_account_with_storage_trie() {
+        let tx = TestTransaction::default();
+        let address = Address::from(hex!("9fe4abd71ad081f091bd06dd1c16f7e92927561e"));
+        let hashed_address = keccak256(address);
+        let storage = BTreeMap::from([
+            (H256::zero(), U256::from(3)),
+            (H256::from_low_u64_be(2), U256::from(1)),
+        ]);
+        let account = Account {
+            nonce: 155,
+            balance: U256::from(414241124u32),
+            bytecode_hash: Some(keccak256("el buen fla")),
+        };
+        tx.commit(|tx| {
+            tx.put::<tables::HashedAccount>(hashed_address, account)?;
+            for (slot, value) in &storage {
+                let entry = StorageEntry { key: keccak256(slot), value: *value };
+                tx.put::<tables::HashedStorage>(hashed_address, entry)?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let storage_root = sec_trie_root(
+            storage.iter().map(|(slot, value)| (slot, encode_fixed_size(value).to_vec())),
+        );
+        assert_eq!(StorageRoot::new(&*tx.inner(), hashed_address).root(), Ok(storage_root));
+
+        let expected = sec_trie_root([(address, encode_account(account, Some(storage_root)))]);
+        assert_matches!(calculate_state_root(&tx.inner()), Ok(root) if root == expected);
+    }
+
+    #[test]
+    fn verify_genesis() {
+        let tx = TestTransaction::default();
+        let ChainSpec { genesis, .. } = chain_spec_value_parser("mainnet").unwrap();
+        tx.commit(|tx| {
+            for (address, account) in &genesis.alloc {
+                let account = Account {
+                    nonce: account.nonce.unwrap_or_default(),
+                    balance: account.balance,
+                    bytecode_hash: None,
+                };
+                tx.put::<tables::HashedAccount>(keccak256(address), account)?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let expected =
+            H256(hex!("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544"));
+        assert_eq!(genesis_state_root(&genesis.alloc), expected);
+        assert_matches!(calculate_state_root(&tx.inner()), Ok(root) if root == expected);
+    }
+
+    #[test]
+    fn gather_changes() {
+        let tx = TestTransaction::default();
+        let address = Address::from(hex!("9fe4abd71ad081f091bd06dd1c16f7e92927561e"));
+        let hashed_address = keccak256(address);
+        let slots = [H256::zero(), H256::from_low_u64_be(2)];
+        let account = Account {
+            nonce: 155,
+            balance: U256::from(414241124u32),
+            bytecode_hash: Some(keccak256("el buen fla")),
+        };
+        let mut inner = tx.inner();
+        let previous_root = calculate_state_root(&inner).unwrap();
+        inner.commit().unwrap();
+        drop(inner);
+
+        // The account is created in transition 31 and its storage is set in transition 32
+        tx.commit(|tx| {
+            tx.put::<tables::HashedAccount>(hashed_address, account)?;
+            tx.put::<tables::AccountChangeSet>(31, AccountBeforeTx { address, info: None })?;
+            for (value, slot) in slots.into_iter().enumerate() {
+                let entry = StorageEntry { key: keccak256(slot), value: U256::from(value + 1) };
+                tx.put::<tables::HashedStorage>(hashed_address, entry)?;
+                tx.put::<tables::StorageChangeSet>(
+                    (32, address).into(),
+                    StorageEntry { key: slot, value: U256::ZERO },
+                )?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        // The account of the changed slots is changed as well
+        let sets = PrefixSetLoader::new(&*tx.inner()).load(32..33).unwrap();
+        assert_eq!(sets.accounts, PrefixSet::from_iter([hashed_address]));
+        assert_eq!(
+            sets.storages,
+            HashMap::from([(hashed_address, PrefixSet::from_iter(slots.map(keccak256)))])
+        );
+
+        // The trie updated from the changes of both transitions is the rebuilt one
+        let inner = tx.inner();
+        let root = update_state_root(&inner, previous_root, 31..33).unwrap();
+        drop(inner);
+        assert_matches!(calculate_state_root(&tx.inner()), Ok(rebuilt) if rebuilt == root);
+    }
+
     struct MerkleTestRunner {
         tx: TestTransaction,
         clean_threshold: u64,
@@ -278,11 +452,9 @@ mod tests {
             let n_accounts = 31;
             let mut accounts = random_contract_account_range(&mut (0..n_accounts));
 
-            let SealedBlock { header, body, ommers, withdrawals } =
-                random_block(stage_progress, None, Some(0), None);
-            let mut header = header.unseal();
-            header.state_root = self.generate_initial_trie(&accounts)?;
-            let sealed_head = SealedBlock { header: header.seal_slow(), body, ommers, withdrawals };
+            let mut block = random_block(stage_progress, None, Some(0), None).unseal();
+            block.header.state_root = self.generate_initial_trie(&accounts)?;
+            let sealed_head = block.seal_slow();
 
             let head_hash = sealed_head.hash();
             let mut blocks = vec![sealed_head];
@@ -466,7 +638,7 @@ mod tests {
 
     impl MerkleTestRunner {
         fn state_root(&self) -> Result<H256, TestRunnerError> {
-            Ok(DBTrieLoader::default().calculate_root(&self.tx.inner()).unwrap())
+            Ok(StateRoot::new(&*self.tx.inner()).root().unwrap())
         }
 
         pub(crate) fn generate_initial_trie(
@@ -475,10 +647,8 @@ mod tests {
         ) -> Result<H256, TestRunnerError> {
             self.insert_accounts(accounts)?;
 
-            let loader = DBTrieLoader::default();
-
             let mut tx = self.tx.inner();
-            let root = loader.calculate_root(&tx).expect("couldn't create initial trie");
+            let root = calculate_state_root(&tx).expect("couldn't create initial trie");
 
             tx.commit()?;
 
@@ -540,7 +710,7 @@ mod tests {
             if previous_stage_progress != 0 {
                 let block_root =
                     self.tx.inner().get_header(previous_stage_progress).unwrap().state_root;
-                let root = DBTrieLoader::default().calculate_root(&self.tx.inner()).unwrap();
+                let root = StateRoot::new(&*self.tx.inner()).root().unwrap();
                 assert_eq!(block_root, root);
             }
             Ok(())
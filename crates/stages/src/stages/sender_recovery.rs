@@ -6,7 +6,7 @@ use futures_util::StreamExt;
 
 use crate::stages::stream::SequentialPairStream;
 use reth_db::{
-    cursor::{DbCursorRO, DbCursorRW},
+    cursor::DbCursorRW,
     database::Database,
     tables,
     transaction::{DbTx, DbTxMut},
@@ -73,10 +73,9 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
         // Acquire the cursor for inserting elements
         let mut senders_cursor = tx.cursor_write::<tables::TxSenders>()?;
 
-        // Acquire the cursor over the transactions
-        let mut tx_cursor = tx.cursor_read::<tables::Transactions>()?;
-        // Walk the transactions from start to end index (inclusive)
-        let entries = tx_cursor.walk_range(start_tx_index..=end_tx_index)?;
+        // Read the transactions from start to end index (inclusive), the finalized ones may have
+        // been moved to the static files
+        let entries = tx.get_transactions(start_tx_index..end_tx_index + 1)?;
 
         // Iterate over transactions in chunks
         info!(target: "sync::stages::sender_recovery", start_tx_index, end_tx_index, "Recovering senders");
@@ -86,8 +85,7 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
 
         // spawn recovery jobs onto the default rayon threadpool and send the result through the
         // channel
-        for entry in entries {
-            let (tx_id, transaction) = entry?;
+        for (tx_id, transaction) in entries {
             let tx = tx.clone();
             rayon::spawn_fifo(move || {
                 trace!(target: "sync::stages::sender_recovery", tx_id, hash = ?transaction.hash(), "Recovering sender");
@@ -145,6 +143,7 @@ impl From<SenderRecoveryStageError> for StageError {
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
+    use reth_db::cursor::DbCursorRO;
     use reth_interfaces::test_utils::generators::{random_block, random_block_range};
     use reth_primitives::{BlockNumber, SealedBlock, H256};
 
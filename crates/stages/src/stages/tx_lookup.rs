@@ -56,25 +56,15 @@ impl<DB: Database> Stage<DB> for TransactionLookupStage {
 
         debug!(target: "sync::stages::transaction_lookup", start_block, end_block, "Commencing sync");
 
-        let mut cursor_bodies = tx.cursor_read::<tables::BlockBodies>()?;
-        let mut tx_cursor = tx.cursor_write::<tables::Transactions>()?;
-
-        // Walk over block bodies within a specified range.
-        let bodies = cursor_bodies.walk(Some(start_block))?.take_while(|entry| {
-            entry.as_ref().map(|(num, _)| *num <= end_block).unwrap_or_default()
-        });
-
-        // Collect transactions for each body
-        let mut tx_list = vec![];
-        for body_entry in bodies {
-            let (_, body) = body_entry?;
-            let transactions = tx_cursor.walk(Some(body.start_tx_id))?.take(body.tx_count as usize);
-
-            for tx_entry in transactions {
-                let (id, transaction) = tx_entry?;
-                tx_list.push((transaction.hash(), id));
-            }
-        }
+        // Collect the transactions of the blocks within the range, the finalized ones may have
+        // been moved to the static files
+        let start_tx_id = tx.get_block_body(start_block)?.start_tx_id;
+        let end_tx_id = tx.get_block_body(end_block)?.tx_id_range().end;
+        let mut tx_list = tx
+            .get_transactions(start_tx_id..end_tx_id)?
+            .into_iter()
+            .map(|(id, transaction)| (transaction.hash(), id))
+            .collect::<Vec<_>>();
 
         // Sort before inserting the reverse lookup for hash -> tx_id.
         tx_list.sort_by(|txa, txb| txa.0.cmp(&txb.0));
@@ -84,11 +74,10 @@ impl<DB: Database> Stage<DB> for TransactionLookupStage {
         // If the last inserted element in the database is smaller than the first in our set, then
         // we can just append into the DB. This probably only ever happens during sync, on
         // the first table insertion.
-        let append = tx_list
-            .first()
-            .zip(txhash_cursor.last()?)
-            .map(|((first, _), (last, _))| &last < first)
-            .unwrap_or_default();
+        let append = match (tx_list.first(), txhash_cursor.last()?) {
+            (Some((first, _)), Some((last, _))) => &last < first,
+            _ => true,
+        };
 
         for (tx_hash, id) in tx_list {
             if append {
@@ -114,12 +103,8 @@ impl<DB: Database> Stage<DB> for TransactionLookupStage {
         let mut body_cursor = tx.cursor_write::<tables::BlockBodies>()?;
         let mut tx_hash_number_cursor = tx.cursor_write::<tables::TxHashNumber>()?;
         let mut transaction_cursor = tx.cursor_write::<tables::Transactions>()?;
-        let mut rev_walker = body_cursor.walk_back(None)?;
-        while let Some((number, body)) = rev_walker.next().transpose()? {
-            if number <= input.unwind_to {
-                break
-            }
-
+        let mut rev_walker = body_cursor.walk_back_range(input.unwind_to + 1..)?;
+        while let Some((_, body)) = rev_walker.next().transpose()? {
             // Delete all transactions that belong to this block
             for tx_id in body.tx_id_range() {
                 // First delete the transaction and hash to id mapping
@@ -4,12 +4,38 @@ use syn::{parse_macro_input, DeriveInput};
 
 mod arbitrary;
 mod compact;
+mod table_key;
 
 #[proc_macro_derive(Compact, attributes(maybe_zero))]
 pub fn derive(input: TokenStream) -> TokenStream {
     compact::derive(input)
 }
 
+/// Implements `reth_db::table::Encode` and `reth_db::table::Decode` for a composite key, by
+/// concatenating the encodings of its fields in declaration order.
+///
+/// Every field but the last one must implement `reth_db::table::DecodePrefix`, so its encoding
+/// can be followed by the next field. Since these encodings are prefix free and preserve the
+/// ordering of their type, the byte-wise order of the keys matches the lexicographic order of
+/// their fields, as derived by [Ord].
+///
+/// Sample usage:
+/// ```ignore
+/// use reth_codecs::TableKey;
+/// use reth_primitives::{BlockNumber, H256};
+///
+/// #[derive(TableKey, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// pub struct NumberHash {
+///     number: BlockNumber,
+///     hash: H256,
+/// }
+/// ```
+#[proc_macro_derive(TableKey)]
+pub fn derive_table_key(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    table_key::derive(&input).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
 /// Implements the main codec. If the codec supports it, it will call `derive_arbitrary(..)`.
 /// 
 /// Example usage:
@@ -0,0 +1,73 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Fields, Index, Member, Result};
+
+pub(crate) fn derive(node: &DeriveInput) -> Result<TokenStream> {
+    let ty = &node.ident;
+    let (impl_generics, ty_generics, where_clause) = node.generics.split_for_impl();
+
+    let fields = match &node.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(Error::new_spanned(node, "TableKey can only be derived for structs")),
+    };
+    if fields.is_empty() {
+        return Err(Error::new_spanned(node, "TableKey requires at least one field"))
+    }
+
+    let members = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(idx)),
+        })
+        .collect::<Vec<_>>();
+    let vars = (0..members.len()).map(|idx| format_ident!("field_{idx}")).collect::<Vec<_>>();
+
+    let fields_list = fields.iter().collect::<Vec<_>>();
+    let (last_field, prefix_fields) = fields_list.split_last().expect("at least one field");
+    let (last_var, prefix_vars) = vars.split_last().expect("at least one field");
+    let prefix_types = prefix_fields.iter().map(|field| &field.ty);
+    let last_type = &last_field.ty;
+
+    let construct = match fields {
+        Fields::Named(_) => quote! { Self { #(#members: #vars),* } },
+        _ => quote! { Self(#(#vars),*) },
+    };
+
+    Ok(quote! {
+        impl #impl_generics reth_db::table::Encode for #ty #ty_generics #where_clause {
+            type Encoded = Vec<u8>;
+
+            fn encode(self) -> Self::Encoded {
+                let mut buf = Vec::new();
+                #(
+                    buf.extend_from_slice(
+                        reth_db::table::Encode::encode(self.#members).as_ref()
+                    );
+                )*
+                buf
+            }
+        }
+
+        impl #impl_generics reth_db::table::Decode for #ty #ty_generics #where_clause {
+            fn decode<B: Into<reth_primitives::bytes::Bytes>>(
+                value: B,
+            ) -> Result<Self, reth_db::Error> {
+                let value: reth_primitives::bytes::Bytes = value.into();
+                #[allow(unused_mut)]
+                let mut offset = 0;
+                #(
+                    let (#prefix_vars, len) =
+                        <#prefix_types as reth_db::table::DecodePrefix>::decode_prefix(
+                            &value[offset..],
+                        )?;
+                    offset += len;
+                )*
+                let #last_var =
+                    <#last_type as reth_db::table::Decode>::decode(value.slice(offset..))?;
+                Ok(#construct)
+            }
+        }
+    })
+}
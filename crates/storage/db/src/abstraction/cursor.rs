@@ -5,7 +5,7 @@ use std::{
 
 use crate::{
     common::{IterPairResult, PairResult, ValueOnlyResult},
-    table::{DupSort, Table},
+    table::{DupSort, Encode, Table},
     Error,
 };
 
@@ -60,6 +60,25 @@ pub trait DbCursorRO<'tx, T: Table> {
     ) -> Result<ReverseWalker<'cursor, 'tx, T, Self>, Error>
     where
         Self: Sized;
+
+    /// Returns an iterator that walks backwards through the keys in the specified range. It
+    /// starts at the greatest key inside the range and stops once the start bound of the range is
+    /// crossed.
+    fn walk_back_range<'cursor>(
+        &'cursor mut self,
+        range: impl RangeBounds<T::Key>,
+    ) -> Result<ReverseRangeWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized;
+
+    /// Returns an iterator over the entries whose encoded key starts with the encoding of
+    /// `prefix`, e.g. all the entries of an account in a table keyed by `(Address, ..)`.
+    fn walk_prefix<'cursor, P: Encode>(
+        &'cursor mut self,
+        prefix: P,
+    ) -> Result<PrefixWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized;
 }
 
 /// Read only cursor over DupSort table.
@@ -205,8 +224,6 @@ pub struct RangeWalker<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> {
     cursor: &'cursor mut CURSOR,
     /// `(key, value)` where to start the walk.
     start: IterPairResult<T>,
-    /// `key` where to start the walk.
-    start_key: Bound<T::Key>,
     /// `key` where to stop the walk.
     end_key: Bound<T::Key>,
     /// flag whether is ended
@@ -224,50 +241,169 @@ impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> std::iter::Iterator
             return None
         }
 
-        let start = self.start.take();
-        if start.is_some() && matches!(self.start_key, Bound::Included(_) | Bound::Unbounded) {
-            return start
-        }
-
-        match self.cursor.next().transpose() {
-            Some(Ok((key, value))) => match &self.end_key {
-                Bound::Included(end_key) if &key <= end_key => Some(Ok((key, value))),
-                Bound::Excluded(end_key) if &key < end_key => Some(Ok((key, value))),
-                Bound::Unbounded => Some(Ok((key, value))),
-                _ => {
+        let next_item = match self.start.take() {
+            Some(start) => Some(start),
+            None => self.cursor.next().transpose(),
+        };
+
+        match next_item {
+            Some(Ok((key, value))) => {
+                let in_range = match &self.end_key {
+                    Bound::Included(end_key) => &key <= end_key,
+                    Bound::Excluded(end_key) => &key < end_key,
+                    Bound::Unbounded => true,
+                };
+
+                if in_range {
+                    Some(Ok((key, value)))
+                } else {
                     self.is_done = true;
                     None
                 }
-            },
+            }
             Some(res @ Err(_)) => Some(res),
-            None if matches!(self.end_key, Bound::Unbounded) => {
+            None => {
                 self.is_done = true;
                 None
             }
-            _ => None,
         }
     }
 }
 
 impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> RangeWalker<'cursor, 'tx, T, CURSOR> {
     /// construct RangeWalker
+    ///
+    /// `start` is expected to already satisfy the start bound of the range, only the `end_key`
+    /// bound is checked while walking.
     pub fn new(
         cursor: &'cursor mut CURSOR,
         start: IterPairResult<T>,
-        start_key: Bound<T::Key>,
         end_key: Bound<T::Key>,
     ) -> Self {
-        Self {
-            cursor,
-            start,
-            start_key,
-            end_key,
-            is_done: false,
-            _tx_phantom: std::marker::PhantomData,
+        Self { cursor, start, end_key, is_done: false, _tx_phantom: std::marker::PhantomData }
+    }
+}
+
+/// Provides an iterator over the keys starting with a prefix to `Cursor` when handling `Table`.
+/// Also check [`Walker`]
+pub struct PrefixWalker<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> {
+    /// Cursor to be used to walk through the table.
+    cursor: &'cursor mut CURSOR,
+    /// `(key, value)` where to start the walk.
+    start: IterPairResult<T>,
+    /// Encoded prefix of the keys to walk through.
+    prefix: Vec<u8>,
+    /// flag whether is ended
+    is_done: bool,
+    /// Phantom data for 'tx. As it is only used for `DbCursorRO`.
+    _tx_phantom: PhantomData<&'tx T>,
+}
+
+impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> std::iter::Iterator
+    for PrefixWalker<'cursor, 'tx, T, CURSOR>
+{
+    type Item = Result<(T::Key, T::Value), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None
+        }
+
+        let next_item = match self.start.take() {
+            Some(start) => Some(start),
+            None => self.cursor.next().transpose(),
+        };
+
+        match next_item {
+            Some(Ok((key, value))) if key.clone().encode().as_ref().starts_with(&self.prefix) => {
+                Some(Ok((key, value)))
+            }
+            Some(res @ Err(_)) => Some(res),
+            _ => {
+                self.is_done = true;
+                None
+            }
+        }
+    }
+}
+
+impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> PrefixWalker<'cursor, 'tx, T, CURSOR> {
+    /// construct PrefixWalker
+    ///
+    /// `start` is expected to be the first entry with a key greater or equal than `prefix`.
+    pub fn new(cursor: &'cursor mut CURSOR, start: IterPairResult<T>, prefix: Vec<u8>) -> Self {
+        Self { cursor, start, prefix, is_done: false, _tx_phantom: std::marker::PhantomData }
+    }
+}
+
+/// Provides a reverse range iterator to `Cursor` when handling `Table`.
+/// Also check [`ReverseWalker`]
+pub struct ReverseRangeWalker<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> {
+    /// Cursor to be used to walk through the table.
+    cursor: &'cursor mut CURSOR,
+    /// `(key, value)` where to start the walk.
+    start: IterPairResult<T>,
+    /// `key` where to stop the walk.
+    start_key: Bound<T::Key>,
+    /// flag whether is ended
+    is_done: bool,
+    /// Phantom data for 'tx. As it is only used for `DbCursorRO`.
+    _tx_phantom: PhantomData<&'tx T>,
+}
+
+impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> std::iter::Iterator
+    for ReverseRangeWalker<'cursor, 'tx, T, CURSOR>
+{
+    type Item = Result<(T::Key, T::Value), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None
+        }
+
+        let next_item = match self.start.take() {
+            Some(start) => Some(start),
+            None => self.cursor.prev().transpose(),
+        };
+
+        match next_item {
+            Some(Ok((key, value))) => {
+                let in_range = match &self.start_key {
+                    Bound::Included(start_key) => &key >= start_key,
+                    Bound::Excluded(start_key) => &key > start_key,
+                    Bound::Unbounded => true,
+                };
+
+                if in_range {
+                    Some(Ok((key, value)))
+                } else {
+                    self.is_done = true;
+                    None
+                }
+            }
+            Some(res @ Err(_)) => Some(res),
+            None => {
+                self.is_done = true;
+                None
+            }
         }
     }
 }
 
+impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>>
+    ReverseRangeWalker<'cursor, 'tx, T, CURSOR>
+{
+    /// construct ReverseRangeWalker
+    ///
+    /// `start` is expected to already satisfy the end bound of the range, only the `start_key`
+    /// bound is checked while walking.
+    pub fn new(
+        cursor: &'cursor mut CURSOR,
+        start: IterPairResult<T>,
+        start_key: Bound<T::Key>,
+    ) -> Self {
+        Self { cursor, start, start_key, is_done: false, _tx_phantom: std::marker::PhantomData }
+    }
+}
+
 /// Provides an iterator to `Cursor` when handling a `DupSort` table.
 ///
 /// Reason why we have two lifetimes is to distinguish between `'cursor` lifetime
@@ -278,19 +414,41 @@ pub struct DupWalker<'cursor, 'tx, T: DupSort, CURSOR: DbDupCursorRO<'tx, T>> {
     pub cursor: &'cursor mut CURSOR,
     /// Value where to start the walk.
     pub start: IterPairResult<T>,
+    /// flag whether is ended
+    is_done: bool,
     /// Phantom data for 'tx. As it is only used for `DbDupCursorRO`.
     pub _tx_phantom: PhantomData<&'tx T>,
 }
 
+impl<'cursor, 'tx, T: DupSort, CURSOR: DbDupCursorRO<'tx, T>> DupWalker<'cursor, 'tx, T, CURSOR> {
+    /// construct DupWalker
+    ///
+    /// If `start` is `None`, the cursor could not be positioned and the walker yields nothing.
+    pub fn new(cursor: &'cursor mut CURSOR, start: IterPairResult<T>) -> Self {
+        let is_done = start.is_none();
+        Self { cursor, start, is_done, _tx_phantom: std::marker::PhantomData }
+    }
+}
+
 impl<'cursor, 'tx, T: DupSort, CURSOR: DbDupCursorRO<'tx, T>> std::iter::Iterator
     for DupWalker<'cursor, 'tx, T, CURSOR>
 {
     type Item = Result<(T::Key, T::Value), Error>;
     fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None
+        }
+
         let start = self.start.take();
         if start.is_some() {
             return start
         }
-        self.cursor.next_dup().transpose()
+
+        // Calling `next_dup` on a cursor that was never positioned is not allowed by MDBX.
+        let next = self.cursor.next_dup().transpose();
+        if next.is_none() {
+            self.is_done = true;
+        }
+        next
     }
 }
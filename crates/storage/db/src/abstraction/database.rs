@@ -1,6 +1,6 @@
 use crate::{
     common::{Bounds, Sealed},
-    table::TableImporter,
+    table::{Table, TableImporter},
     transaction::{DbTx, DbTxMut},
     Error,
 };
@@ -52,6 +52,19 @@ pub trait Database: for<'a> DatabaseGAT<'a> {
 
         Ok(res)
     }
+
+    /// Puts all the entries into the table with [`DbTxMut::put_many`] and commits them in a
+    /// single read-write transaction.
+    fn update_batch<T: Table>(
+        &self,
+        entries: impl IntoIterator<Item = (T::Key, T::Value)>,
+    ) -> Result<(), Error> {
+        let tx = self.tx_mut()?;
+        tx.put_many::<T>(entries)?;
+        tx.commit()?;
+
+        Ok(())
+    }
 }
 
 // Generic over Arc
@@ -68,6 +81,13 @@ impl<DB: Database> Database for Arc<DB> {
     fn tx_mut(&self) -> Result<<Self as DatabaseGAT<'_>>::TXMut, Error> {
         <DB as Database>::tx_mut(self)
     }
+
+    fn update<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: Fn(&<Self as DatabaseGAT<'_>>::TXMut) -> T,
+    {
+        <DB as Database>::update(self, f)
+    }
 }
 
 // Generic over reference
@@ -84,4 +104,11 @@ impl<DB: Database> Database for &DB {
     fn tx_mut(&self) -> Result<<Self as DatabaseGAT<'_>>::TXMut, Error> {
         <DB as Database>::tx_mut(self)
     }
+
+    fn update<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: Fn(&<Self as DatabaseGAT<'_>>::TXMut) -> T,
+    {
+        <DB as Database>::update(self, f)
+    }
 }
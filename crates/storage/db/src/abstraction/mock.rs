@@ -4,11 +4,11 @@ use std::{collections::BTreeMap, ops::RangeBounds};
 use crate::{
     common::{PairResult, ValueOnlyResult},
     cursor::{
-        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, RangeWalker,
-        ReverseWalker, Walker,
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, PrefixWalker, RangeWalker,
+        ReverseRangeWalker, ReverseWalker, Walker,
     },
     database::{Database, DatabaseGAT},
-    table::{DupSort, Table, TableImporter},
+    table::{DupSort, Encode, Table, TableImporter},
     transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT},
     Error,
 };
@@ -165,6 +165,26 @@ impl<'tx, T: Table> DbCursorRO<'tx, T> for CursorMock {
     {
         todo!()
     }
+
+    fn walk_back_range<'cursor>(
+        &'cursor mut self,
+        _range: impl RangeBounds<T::Key>,
+    ) -> Result<ReverseRangeWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        todo!()
+    }
+
+    fn walk_prefix<'cursor, P: Encode>(
+        &'cursor mut self,
+        _prefix: P,
+    ) -> Result<PrefixWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        todo!()
+    }
 }
 
 impl<'tx, T: DupSort> DbDupCursorRO<'tx, T> for CursorMock {
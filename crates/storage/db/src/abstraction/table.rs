@@ -1,5 +1,6 @@
 use crate::{
     abstraction::cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
+    tables::codecs::Compression,
     transaction::{DbTx, DbTxMut},
     Error,
 };
@@ -40,6 +41,13 @@ pub trait Decode: Send + Sync + Sized + Debug {
     fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error>;
 }
 
+/// Trait for the types whose encoding can be followed by other data, as in composite keys.
+/// See [`TableKey`](crate::TableKey).
+pub trait DecodePrefix: Decode {
+    /// Decodes the value at the start of `value`, returning it with the length of its encoding.
+    fn decode_prefix(value: &[u8]) -> Result<(Self, usize), Error>;
+}
+
 /// Generic trait that enforces the database key to implement [`Encode`] and [`Decode`].
 pub trait Key: Encode + Decode + Ord + Clone {}
 
@@ -67,6 +75,9 @@ pub trait Table: Send + Sync + Debug + 'static {
     type Key: Key;
     /// Value element of `Table`.
     type Value: Value;
+    /// Codec applied to the values on top of [`Compress`]. Changing it requires a migration of
+    /// the stored values.
+    const COMPRESSION: Compression = Compression::None;
 }
 
 /// DupSort allows for keys to be repeated in the database.
@@ -1,9 +1,10 @@
 use crate::{
     common::{Bounds, Sealed},
     cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
-    table::{DupSort, Table},
+    table::{DupSort, Encode, Table},
     Error,
 };
+use std::ops::{Bound, RangeBounds};
 
 /// Implements the GAT method from:
 /// <https://sabrinajewson.org/blog/the-better-alternative-to-lifetime-gats#the-better-gats>.
@@ -45,6 +46,27 @@ pub trait DbTx<'tx>: for<'a> DbTxGAT<'a> {
     fn cursor_read<T: Table>(&self) -> Result<<Self as DbTxGAT<'_>>::Cursor<T>, Error>;
     /// Iterate over read only values in dup sorted table.
     fn cursor_dup_read<T: DupSort>(&self) -> Result<<Self as DbTxGAT<'_>>::DupCursor<T>, Error>;
+
+    /// Gets the values of all the `keys`, in the same order.
+    ///
+    /// The lookups are done in the order of the encoded keys with a single cursor, which is faster
+    /// than calling [`DbTx::get`] for each of many random keys.
+    fn get_many<T: Table>(&self, keys: &[T::Key]) -> Result<Vec<Option<T::Value>>, Error> {
+        let mut sorted = keys
+            .iter()
+            .enumerate()
+            .map(|(idx, key)| (key.clone().encode(), idx))
+            .collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.0.as_ref().cmp(b.0.as_ref()));
+
+        let mut values = (0..keys.len()).map(|_| None).collect::<Vec<_>>();
+        let mut cursor = self.cursor_read::<T>()?;
+        for (_, idx) in sorted {
+            values[idx] = cursor.seek_exact(keys[idx].clone())?.map(|(_, value)| value);
+        }
+
+        Ok(values)
+    }
 }
 
 /// Read write transaction that allows writing to database
@@ -61,4 +83,71 @@ pub trait DbTxMut<'tx>: for<'a> DbTxMutGAT<'a> {
     fn cursor_dup_write<T: DupSort>(
         &self,
     ) -> Result<<Self as DbTxMutGAT<'_>>::DupCursorMut<T>, Error>;
+
+    /// Puts all the entries into the table, sorted by their encoded key. If a key is given
+    /// several times, only its last value is written, so it should not be used with `DUPSORT`
+    /// tables.
+    ///
+    /// If the table is empty or all the keys are greater than its last key, the entries are
+    /// appended, which is much faster than putting them one by one.
+    fn put_many<T: Table>(
+        &self,
+        entries: impl IntoIterator<Item = (T::Key, T::Value)>,
+    ) -> Result<(), Error> {
+        let mut entries = entries
+            .into_iter()
+            .map(|(key, value)| (key.clone().encode(), key, value))
+            .collect::<Vec<_>>();
+        // The sort is stable, so duplicated keys stay in the order they were given.
+        entries.sort_by(|a, b| a.0.as_ref().cmp(b.0.as_ref()));
+
+        let mut deduped: Vec<(<T::Key as Encode>::Encoded, T::Key, T::Value)> =
+            Vec::with_capacity(entries.len());
+        for entry in entries {
+            match deduped.last_mut() {
+                Some(last) if last.0.as_ref() == entry.0.as_ref() => *last = entry,
+                _ => deduped.push(entry),
+            }
+        }
+
+        let mut cursor = self.cursor_write::<T>()?;
+        let append = match (deduped.first(), cursor.last()?) {
+            (Some((first, _, _)), Some((last, _))) => last.encode().as_ref() < first.as_ref(),
+            _ => true,
+        };
+
+        for (_, key, value) in deduped {
+            if append {
+                cursor.append(key, value)?;
+            } else {
+                cursor.upsert(key, value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deletes every entry whose key is inside `range` with a single cursor pass, including all
+    /// the duplicates of the keys of `DUPSORT` tables. Returns the number of deleted entries.
+    fn delete_range<T: Table>(&self, range: impl RangeBounds<T::Key>) -> Result<usize, Error> {
+        let mut cursor = self.cursor_write::<T>()?;
+        let mut entry = match range.start_bound() {
+            Bound::Included(key) | Bound::Excluded(key) => cursor.seek(key.clone())?,
+            Bound::Unbounded => cursor.first()?,
+        };
+
+        let mut deleted = 0;
+        while let Some((key, _)) = entry {
+            if range.contains(&key) {
+                cursor.delete_current()?;
+                deleted += 1;
+            } else if !matches!(range.start_bound(), Bound::Excluded(start) if start == &key) {
+                break
+            }
+            // Once the current entry is deleted, the cursor yields the one following it.
+            entry = cursor.next()?;
+        }
+
+        Ok(deleted)
+    }
 }
@@ -0,0 +1,100 @@
+//! Online backups of the MDBX environment.
+
+use super::Env;
+use crate::Error;
+use reth_libmdbx::EnvironmentKind;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    os::unix::{io::AsRawFd, net::UnixStream},
+    path::Path,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// Name of the data file inside of the database directory.
+pub const DATA_FILE_NAME: &str = "mdbx.dat";
+
+/// Size of the chunks written by [`Env::backup_throttled`].
+const BACKUP_CHUNK_SIZE: usize = 1024 * 1024;
+
+impl<E: EnvironmentKind> Env<E> {
+    /// Copies the database into the directory `path`, which can then be opened with
+    /// [`Env::open`].
+    ///
+    /// The copy is made from a single read-only transaction, so the node can keep syncing while it
+    /// runs. Free pages are omitted, so the copy is also compacted.
+    pub fn backup(&self, path: &Path) -> Result<(), Error> {
+        fs::create_dir_all(path).map_err(io_error)?;
+        self.inner
+            .copy(&path.join(DATA_FILE_NAME), true)
+            .map_err(|e| Error::DatabaseLocation(e.into()))
+    }
+
+    /// Like [`Env::backup`], but writes at most `bytes_per_sec` bytes per second, to limit its
+    /// I/O impact on a running node.
+    ///
+    /// The read-only transaction of the copy stays open for the whole duration of the backup,
+    /// see [`EnvConfig::read_tx_timeout`](super::EnvConfig::read_tx_timeout).
+    pub fn backup_throttled(&self, path: &Path, bytes_per_sec: u64) -> Result<(), Error> {
+        fs::create_dir_all(path).map_err(io_error)?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path.join(DATA_FILE_NAME))
+            .map_err(io_error)?;
+
+        // MDBX writes the copy into one end of the socket, which is throttled on the other end.
+        let (mut reader, writer) = UnixStream::pair().map_err(io_error)?;
+        std::thread::scope(|scope| {
+            let copy = scope.spawn(move || {
+                // The writer is closed once the copy is done, so the reader stops.
+                self.inner.copy_to_fd(writer.as_raw_fd(), true)
+            });
+
+            let written = copy_throttled(&mut reader, &mut file, bytes_per_sec);
+            // Unblocks the copy if writing the file failed.
+            drop(reader);
+
+            copy.join()
+                .expect("backup thread panicked")
+                .map_err(|e| Error::DatabaseLocation(e.into()))?;
+            written.and_then(|_| file.sync_all()).map_err(io_error)
+        })
+    }
+}
+
+/// Copies everything from `reader` to `writer`, at most `bytes_per_sec` bytes per second.
+fn copy_throttled(
+    reader: &mut impl Read,
+    writer: &mut File,
+    bytes_per_sec: u64,
+) -> io::Result<u64> {
+    let mut buf = vec![0; BACKUP_CHUNK_SIZE];
+    let start = Instant::now();
+    let mut copied = 0u64;
+
+    loop {
+        let len = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        writer.write_all(&buf[..len])?;
+        copied += len as u64;
+
+        // Wait until the average rate is back under the limit.
+        let expected = Duration::from_secs_f64(copied as f64 / bytes_per_sec.max(1) as f64);
+        if let Some(ahead) = expected.checked_sub(start.elapsed()) {
+            sleep(ahead);
+        }
+    }
+
+    Ok(copied)
+}
+
+/// Converts an I/O error into a database error.
+fn io_error(err: io::Error) -> Error {
+    Error::DatabaseLocation(err.raw_os_error().unwrap_or_default())
+}
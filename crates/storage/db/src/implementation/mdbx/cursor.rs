@@ -1,17 +1,18 @@
 //! Cursor wrapper for libmdbx-sys.
 
-use std::{borrow::Cow, collections::Bound, marker::PhantomData, ops::RangeBounds};
+use std::{borrow::Cow, collections::Bound, ops::RangeBounds};
 
+use super::metrics::{record_put, record_seek};
 use crate::{
     cursor::{
-        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, RangeWalker,
-        ReverseWalker, Walker,
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, PrefixWalker, RangeWalker,
+        ReverseRangeWalker, ReverseWalker, Walker,
     },
-    table::{Compress, DupSort, Encode, Table},
-    tables::utils::*,
+    table::{DupSort, Encode, Table},
+    tables::{codecs::compress_value, utils::*},
     Error,
 };
-use reth_libmdbx::{self, Error as MDBXError, TransactionKind, WriteFlags, RO, RW};
+use reth_libmdbx::{self, TransactionKind, WriteFlags, RO, RW};
 
 /// Alias type for a `(key, value)` result coming from a cursor.
 pub type PairResult<T> = Result<Option<(<T as Table>::Key, <T as Table>::Value)>, Error>;
@@ -50,10 +51,12 @@ impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T>
     }
 
     fn seek_exact(&mut self, key: <T as Table>::Key) -> PairResult<T> {
+        record_seek(self.table);
         decode!(self.inner.set_key(key.encode().as_ref()))
     }
 
     fn seek(&mut self, key: <T as Table>::Key) -> PairResult<T> {
+        record_seek(self.table);
         decode!(self.inner.set_range(key.encode().as_ref()))
     }
 
@@ -100,21 +103,33 @@ impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T>
         Self: Sized,
     {
         let start = match range.start_bound().cloned() {
-            Bound::Included(key) => {
+            Bound::Included(key) | Bound::Excluded(key) => {
                 if matches!(range.end_bound().cloned(), Bound::Included(end_key) | Bound::Excluded(end_key) if end_key < key) {
                     return Err(Error::Read(2))
                 }
-                self.inner.set_range(key.encode().as_ref())
-            }
-            Bound::Excluded(_key) => {
-                unreachable!("Rust doesn't allow for Bound::Excluded in starting bounds");
+
+                let encoded_key = key.encode();
+                let start: Option<(Cow<'_, [u8]>, Cow<'_, [u8]>)> = self
+                    .inner
+                    .set_range(encoded_key.as_ref())
+                    .map_err(|e| Error::Read(e.into()))?;
+
+                // An excluded start bound skips the entry with exactly that key, if present.
+                match start {
+                    Some((key, _))
+                        if matches!(range.start_bound(), Bound::Excluded(_)) &&
+                            key.as_ref() == encoded_key.as_ref() =>
+                    {
+                        self.inner.next().map_err(|e| Error::Read(e.into()))?
+                    }
+                    start => start,
+                }
             }
-            Bound::Unbounded => self.inner.first(),
+            Bound::Unbounded => self.inner.first().map_err(|e| Error::Read(e.into()))?,
         }
-        .map_err(|e| Error::Read(e.into()))?
         .map(decoder::<T>);
 
-        Ok(RangeWalker::new(self, start, range.start_bound().cloned(), range.end_bound().cloned()))
+        Ok(RangeWalker::new(self, start, range.end_bound().cloned()))
     }
 
     fn walk_back<'cursor>(
@@ -133,6 +148,60 @@ impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T>
 
         Ok(ReverseWalker::new(self, start))
     }
+
+    fn walk_back_range<'cursor>(
+        &'cursor mut self,
+        range: impl RangeBounds<T::Key>,
+    ) -> Result<ReverseRangeWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match range.end_bound().cloned() {
+            Bound::Included(key) | Bound::Excluded(key) => {
+                if matches!(range.start_bound().cloned(), Bound::Included(start_key) | Bound::Excluded(start_key) if key < start_key) {
+                    return Err(Error::Read(2))
+                }
+
+                let encoded_key = key.encode();
+                let start: Option<(Cow<'_, [u8]>, Cow<'_, [u8]>)> = self
+                    .inner
+                    .set_range(encoded_key.as_ref())
+                    .map_err(|e| Error::Read(e.into()))?;
+
+                match start {
+                    // Entry with exactly the included end key is the first one to be returned.
+                    Some((key, value))
+                        if matches!(range.end_bound(), Bound::Included(_)) &&
+                            key.as_ref() == encoded_key.as_ref() =>
+                    {
+                        Some((key, value))
+                    }
+                    // Cursor is positioned past the end bound, so step back once.
+                    Some(_) => self.inner.prev().map_err(|e| Error::Read(e.into()))?,
+                    // Every key is lower than the end bound.
+                    None => self.inner.last().map_err(|e| Error::Read(e.into()))?,
+                }
+            }
+            Bound::Unbounded => self.inner.last().map_err(|e| Error::Read(e.into()))?,
+        }
+        .map(decoder::<T>);
+
+        Ok(ReverseRangeWalker::new(self, start, range.start_bound().cloned()))
+    }
+
+    fn walk_prefix<'cursor, P: Encode>(
+        &'cursor mut self,
+        prefix: P,
+    ) -> Result<PrefixWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        record_seek(self.table);
+        let prefix = prefix.encode().as_ref().to_vec();
+        let start = decode!(self.inner.set_range(&prefix)).transpose();
+
+        Ok(PrefixWalker::new(self, start, prefix))
+    }
 }
 
 impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx, K, T> {
@@ -156,6 +225,7 @@ impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx,
         key: <T as Table>::Key,
         subkey: <T as DupSort>::SubKey,
     ) -> ValueOnlyResult<T> {
+        record_seek(self.table);
         self.inner
             .get_both_range(key.encode().as_ref(), subkey.encode().as_ref())
             .map_err(|e| Error::Read(e.into()))?
@@ -201,14 +271,22 @@ impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx,
                         .map_err(|e| Error::Read(e.into()))?
                         .map(|val| decoder::<T>((Cow::Owned(key), val)))
                 } else {
-                    let err_code = MDBXError::to_err_code(&MDBXError::NotFound);
-                    Some(Err(Error::Read(err_code)))
+                    None
                 }
             }
             (None, None) => self.first().transpose(),
         };
 
-        Ok(DupWalker::<'cursor, 'tx, T, Self> { cursor: self, start, _tx_phantom: PhantomData {} })
+        Ok(DupWalker::new(self, start))
+    }
+}
+
+impl<'tx, T: Table> Cursor<'tx, RW, T> {
+    /// Writes the encoded pair with the given flags.
+    fn put(&mut self, key: T::Key, value: T::Value, flags: WriteFlags) -> Result<(), Error> {
+        let (key, value) = (key.encode(), compress_value::<T>(value));
+        record_put(self.table, key.as_ref().len() + value.as_ref().len());
+        self.inner.put(key.as_ref(), value.as_ref(), flags).map_err(|e| Error::Write(e.into()))
     }
 }
 
@@ -217,23 +295,17 @@ impl<'tx, T: Table> DbCursorRW<'tx, T> for Cursor<'tx, RW, T> {
     /// exists in a table, and insert a new row if the specified value doesn't already exist
     fn upsert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
         // Default `WriteFlags` is UPSERT
-        self.inner
-            .put(key.encode().as_ref(), value.compress().as_ref(), WriteFlags::UPSERT)
-            .map_err(|e| Error::Write(e.into()))
+        self.put(key, value, WriteFlags::UPSERT)
     }
 
     fn insert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
-        self.inner
-            .put(key.encode().as_ref(), value.compress().as_ref(), WriteFlags::NO_OVERWRITE)
-            .map_err(|e| Error::Write(e.into()))
+        self.put(key, value, WriteFlags::NO_OVERWRITE)
     }
 
     /// Appends the data to the end of the table. Consequently, the append operation
     /// will fail if the inserted key is less than the last table key
     fn append(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
-        self.inner
-            .put(key.encode().as_ref(), value.compress().as_ref(), WriteFlags::APPEND)
-            .map_err(|e| Error::Write(e.into()))
+        self.put(key, value, WriteFlags::APPEND)
     }
 
     fn delete_current(&mut self) -> Result<(), Error> {
@@ -247,8 +319,6 @@ impl<'tx, T: DupSort> DbDupCursorRW<'tx, T> for Cursor<'tx, RW, T> {
     }
 
     fn append_dup(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
-        self.inner
-            .put(key.encode().as_ref(), value.compress().as_ref(), WriteFlags::APPEND_DUP)
-            .map_err(|e| Error::Write(e.into()))
+        self.put(key, value, WriteFlags::APPEND_DUP)
     }
 }
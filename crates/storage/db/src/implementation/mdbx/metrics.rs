@@ -0,0 +1,43 @@
+//! Metrics of the MDBX key-value layer, labeled by table.
+
+use metrics::{counter, decrement_gauge, increment_gauge};
+use reth_libmdbx::{TransactionKind, RO};
+use std::any::TypeId;
+
+/// Records a point read from a table.
+pub(crate) fn record_get(table: &'static str) {
+    counter!("db.gets", 1, "table" => table);
+}
+
+/// Records a cursor seek in a table.
+pub(crate) fn record_seek(table: &'static str) {
+    counter!("db.seeks", 1, "table" => table);
+}
+
+/// Records a write of `bytes` encoded bytes into a table.
+pub(crate) fn record_put(table: &'static str, bytes: usize) {
+    counter!("db.puts", 1, "table" => table);
+    counter!("db.bytes_written", bytes as u64, "table" => table);
+}
+
+/// Keeps track of an open transaction in the `db.tx.open` gauge until it is dropped.
+#[derive(Debug)]
+pub(crate) struct OpenTxGuard {
+    /// Kind of the transaction, either `ro` or `rw`.
+    mode: &'static str,
+}
+
+impl OpenTxGuard {
+    /// Increments the gauge of open transactions of kind `K`.
+    pub(crate) fn new<K: TransactionKind>() -> Self {
+        let mode = if TypeId::of::<K>() == TypeId::of::<RO>() { "ro" } else { "rw" };
+        increment_gauge!("db.tx.open", 1.0, "mode" => mode);
+        Self { mode }
+    }
+}
+
+impl Drop for OpenTxGuard {
+    fn drop(&mut self) {
+        decrement_gauge!("db.tx.open", 1.0, "mode" => self.mode);
+    }
+}
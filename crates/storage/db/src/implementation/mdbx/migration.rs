@@ -0,0 +1,449 @@
+//! Versioning of the database schema and migrations between versions.
+
+use super::{tx::Tx, Env};
+use crate::{
+    database::Database,
+    models::EncodedU64,
+    table::Table,
+    tables::{self, CanonicalHeaders, DatabaseVersion, Headers, Receipts, SCHEMA_VERSION_KEY},
+    transaction::{DbTx, DbTxMut},
+    Error,
+};
+use reth_libmdbx::{Database as MdbxDatabase, DatabaseFlags, EnvironmentKind, WriteFlags, RW};
+use std::borrow::Cow;
+use tracing::info;
+
+/// Schema version written by this binary.
+pub const SCHEMA_VERSION: u64 = 3;
+
+/// Schema version of the databases created before the version was stored.
+pub const BASE_SCHEMA_VERSION: u64 = 1;
+
+/// Number of tables that migrations can create, on top of
+/// [`Tables::ALL`](crate::tables::Tables::ALL).
+pub(crate) const MIGRATION_TABLES: usize = 2;
+
+/// Name of the table a migration uses while rewriting the keys of another one.
+const MIGRATION_TABLE: &str = "Migration";
+
+/// Name of the table which keeps, for each table being migrated, the step of its migration and
+/// the last key it migrated. It is cleared once the schema version is updated.
+const MIGRATION_PROGRESS_TABLE: &str = "MigrationProgress";
+
+/// Number of entries a migration rewrites per transaction.
+const MIGRATION_BATCH_SIZE: usize = 100_000;
+
+/// Step of [`rekey_table`] copying the entries into the scratch table.
+const REKEY_TO_SCRATCH: u8 = 0;
+
+/// Step of [`rekey_table`] copying the entries back from the scratch table.
+const REKEY_FROM_SCRATCH: u8 = 1;
+
+/// Step of [`compress_table`] rewriting the values.
+const COMPRESS: u8 = 0;
+
+/// Step of a table whose migration is done.
+const MIGRATED: u8 = u8::MAX;
+
+/// Migration of the database from the schema version `version - 1` to `version`.
+pub struct Migration<E: EnvironmentKind> {
+    /// Schema version the database is at once the migration is done.
+    pub version: u64,
+    /// Short description of the migration, for the logs.
+    pub description: &'static str,
+    /// Migrates the tables. It can commit several transactions, so it must keep its progress in
+    /// the [`MIGRATION_PROGRESS_TABLE`] and resume from it when it is run again after a failure.
+    /// The schema version is only updated once it is done.
+    pub migrate: fn(&Env<E>) -> Result<(), Error>,
+}
+
+impl<E: EnvironmentKind> std::fmt::Debug for Migration<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Migration")
+            .field("version", &self.version)
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the migrations up to [`SCHEMA_VERSION`].
+///
+/// Any change to the layout or the encoding of a table must bump [`SCHEMA_VERSION`] and come with
+/// a migration here.
+pub fn migrations<E: EnvironmentKind>() -> Vec<Migration<E>> {
+    vec![
+        Migration {
+            version: 2,
+            description: "compress headers and receipts",
+            migrate: |env| {
+                compress_table::<Headers, E>(env)?;
+                compress_table::<Receipts, E>(env)
+            },
+        },
+        Migration {
+            version: 3,
+            description: "compact block and transaction number keys",
+            migrate: |env| {
+                rekey_table::<CanonicalHeaders, E>(env, false, compact_u64_key)?;
+                rekey_table::<tables::HeaderTD, E>(env, false, compact_u64_key)?;
+                rekey_table::<Headers, E>(env, false, compact_u64_key)?;
+                rekey_table::<tables::BlockBodies, E>(env, false, compact_u64_key)?;
+                rekey_table::<tables::BlockOmmers, E>(env, false, compact_u64_key)?;
+                rekey_table::<tables::BlockWithdrawals, E>(env, false, compact_u64_key)?;
+                rekey_table::<tables::Transactions, E>(env, false, compact_u64_key)?;
+                rekey_table::<Receipts, E>(env, false, compact_u64_key)?;
+                rekey_table::<tables::Logs, E>(env, false, compact_u64_key)?;
+                rekey_table::<tables::BlockTransitionIndex, E>(env, false, compact_u64_key)?;
+                rekey_table::<tables::TxTransitionIndex, E>(env, false, compact_u64_key)?;
+                rekey_table::<tables::TxSenders, E>(env, false, compact_u64_key)?;
+                rekey_table::<tables::AccountChangeSet, E>(env, true, compact_u64_key)?;
+                rekey_table::<tables::StorageChangeSet, E>(env, true, compact_transition_id_address)
+            },
+        },
+    ]
+}
+
+/// Converts a big-endian `u64` key to its compact encoding.
+fn compact_u64_key(key: &[u8]) -> Result<Vec<u8>, Error> {
+    let number = u64::from_be_bytes(key.try_into().map_err(|_| Error::DecodeError)?);
+    Ok(EncodedU64::new(number).as_ref().to_vec())
+}
+
+/// Converts a [`TransitionIdAddress`](crate::models::TransitionIdAddress) key with a big-endian
+/// transition id to its compact encoding.
+fn compact_transition_id_address(key: &[u8]) -> Result<Vec<u8>, Error> {
+    if key.len() != 28 {
+        return Err(Error::DecodeError)
+    }
+    let mut compact = compact_u64_key(&key[..8])?;
+    compact.extend_from_slice(&key[8..]);
+    Ok(compact)
+}
+
+/// Rewrites the keys of the table `T` with `rekey`, which must preserve their order.
+///
+/// The entries are copied into a scratch table with their new keys, then back into the cleared
+/// table. The copies commit every [`MIGRATION_BATCH_SIZE`] entries along with their progress, so
+/// that an interrupted migration resumes from the last copied key.
+fn rekey_table<T: Table, E: EnvironmentKind>(
+    env: &Env<E>,
+    dupsort: bool,
+    rekey: fn(&[u8]) -> Result<Vec<u8>, Error>,
+) -> Result<(), Error> {
+    let flags = if dupsort { DatabaseFlags::DUP_SORT } else { DatabaseFlags::default() };
+    loop {
+        let tx = env.tx_mut()?;
+        let (step, last_key) = read_progress(&tx, T::NAME)?;
+        let table = tx.inner.open_db(Some(T::NAME)).map_err(|e| Error::Read(e.into()))?;
+        let scratch = tx
+            .inner
+            .create_db(Some(MIGRATION_TABLE), flags)
+            .map_err(|e| Error::TableCreation(e.into()))?;
+
+        match step {
+            REKEY_TO_SCRATCH => {
+                match copy_entries(&tx, &table, &scratch, dupsort, last_key.as_deref(), rekey)? {
+                    Some(key) => write_progress(&tx, T::NAME, REKEY_TO_SCRATCH, &key)?,
+                    None => {
+                        tx.inner.clear_db(&table).map_err(|e| Error::Delete(e.into()))?;
+                        write_progress(&tx, T::NAME, REKEY_FROM_SCRATCH, &[])?;
+                    }
+                }
+            }
+            REKEY_FROM_SCRATCH => {
+                match copy_entries(&tx, &scratch, &table, dupsort, last_key.as_deref(), |key| {
+                    Ok(key.to_vec())
+                })? {
+                    Some(key) => write_progress(&tx, T::NAME, REKEY_FROM_SCRATCH, &key)?,
+                    None => {
+                        // SAFETY: no cursor over the scratch table is left open.
+                        unsafe { tx.inner.drop_db(scratch) }
+                            .map_err(|e| Error::Delete(e.into()))?;
+                        write_progress(&tx, T::NAME, MIGRATED, &[])?;
+                    }
+                }
+            }
+            _ => return Ok(()),
+        }
+
+        tx.commit()?;
+    }
+}
+
+/// Appends the entries of `from` which come after the key `after`, with their keys rewritten by
+/// `rekey`, to `to`, until [`MIGRATION_BATCH_SIZE`] entries are copied.
+///
+/// Returns the last copied key if the batch is full, and `None` once all the entries are copied.
+/// A batch ends at the last duplicate of a key.
+fn copy_entries<'txn, E: EnvironmentKind>(
+    tx: &'txn Tx<'_, RW, E>,
+    from: &MdbxDatabase<'txn>,
+    to: &MdbxDatabase<'txn>,
+    dupsort: bool,
+    after: Option<&[u8]>,
+    rekey: fn(&[u8]) -> Result<Vec<u8>, Error>,
+) -> Result<Option<Vec<u8>>, Error> {
+    let flags = if dupsort { WriteFlags::APPEND_DUP } else { WriteFlags::APPEND };
+    let mut source = tx.inner.cursor(from).map_err(|e| Error::InitCursor(e.into()))?;
+    let mut destination = tx.inner.cursor(to).map_err(|e| Error::InitCursor(e.into()))?;
+
+    // Owned pairs, since the write can modify the pages the source points into.
+    let mut entry = match after {
+        Some(after) => match source.set_range::<Vec<u8>, Vec<u8>>(after) {
+            Ok(Some((key, _))) if key == after => source.next_nodup(),
+            entry => entry,
+        },
+        None => source.first(),
+    };
+    let mut copied = 0;
+    while let Some((key, value)) = entry.map_err(|e| Error::Read(e.into()))? {
+        destination.put(&rekey(&key)?, &value, flags).map_err(|e| Error::Write(e.into()))?;
+        copied += 1;
+
+        entry = source.next();
+        if copied >= MIGRATION_BATCH_SIZE &&
+            !matches!(&entry, Ok(Some((next_key, _))) if *next_key == key)
+        {
+            return Ok(Some(key))
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the step of the migration of `table` and the last key it migrated, if any.
+fn read_progress<E: EnvironmentKind>(
+    tx: &Tx<'_, RW, E>,
+    table: &str,
+) -> Result<(u8, Option<Vec<u8>>), Error> {
+    let db = progress_table(tx)?;
+    let progress =
+        tx.inner.get::<Vec<u8>>(&db, table.as_bytes()).map_err(|e| Error::Read(e.into()))?;
+    Ok(match progress.as_deref() {
+        None | Some([]) => (0, None),
+        Some([step]) => (*step, None),
+        Some([step, last_key @ ..]) => (*step, Some(last_key.to_vec())),
+    })
+}
+
+/// Stores the step of the migration of `table` and the last key it migrated, which is empty at
+/// the start of the step.
+fn write_progress<E: EnvironmentKind>(
+    tx: &Tx<'_, RW, E>,
+    table: &str,
+    step: u8,
+    last_key: &[u8],
+) -> Result<(), Error> {
+    let db = progress_table(tx)?;
+    let progress = [&[step], last_key].concat();
+    tx.inner
+        .put(&db, table.as_bytes(), progress, WriteFlags::UPSERT)
+        .map_err(|e| Error::Write(e.into()))
+}
+
+/// Opens the [`MIGRATION_PROGRESS_TABLE`], which is created if it does not exist yet.
+fn progress_table<'txn, E: EnvironmentKind>(
+    tx: &'txn Tx<'_, RW, E>,
+) -> Result<MdbxDatabase<'txn>, Error> {
+    tx.inner
+        .create_db(Some(MIGRATION_PROGRESS_TABLE), DatabaseFlags::default())
+        .map_err(|e| Error::TableCreation(e.into()))
+}
+
+/// Rewrites the values of the table `T`, which were stored without codec, with its
+/// [`Table::COMPRESSION`].
+///
+/// The values are rewritten in place, and committed every [`MIGRATION_BATCH_SIZE`] entries along
+/// with the last rewritten key, from which an interrupted migration resumes.
+fn compress_table<T: Table, E: EnvironmentKind>(env: &Env<E>) -> Result<(), Error> {
+    loop {
+        let tx = env.tx_mut()?;
+        let (step, after) = read_progress(&tx, T::NAME)?;
+        if step == MIGRATED {
+            return Ok(())
+        }
+
+        let last_key = {
+            let db = tx.inner.open_db(Some(T::NAME)).map_err(|e| Error::Read(e.into()))?;
+            let mut cursor = tx.inner.cursor(&db).map_err(|e| Error::InitCursor(e.into()))?;
+
+            let mut entry = match after.as_deref() {
+                Some(after) => match cursor.set_range::<Cow<'_, [u8]>, Cow<'_, [u8]>>(after) {
+                    Ok(Some((key, _))) if *key == *after => cursor.next(),
+                    entry => entry,
+                },
+                None => cursor.first(),
+            };
+            let mut compressed = 0;
+            let mut last_key = None;
+            while let Some((key, value)) = entry.map_err(|e| Error::Read(e.into()))? {
+                // The pair can point into the map, which is modified by the write.
+                let (key, value) = (key.into_owned(), T::COMPRESSION.compress(&value));
+                cursor
+                    .put(&key, &value, WriteFlags::CURRENT)
+                    .map_err(|e| Error::Write(e.into()))?;
+                compressed += 1;
+                if compressed >= MIGRATION_BATCH_SIZE {
+                    last_key = Some(key);
+                    break
+                }
+                entry = cursor.next();
+            }
+            last_key
+        };
+
+        match last_key {
+            Some(key) => write_progress(&tx, T::NAME, COMPRESS, &key)?,
+            None => write_progress(&tx, T::NAME, MIGRATED, &[])?,
+        }
+        tx.commit()?;
+    }
+}
+
+/// Error while migrating the database.
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+pub enum MigrationError {
+    /// The database was written by a newer binary.
+    #[error("Database schema version {found} is newer than the supported version {supported}")]
+    UnsupportedVersion {
+        /// Schema version of the database.
+        found: u64,
+        /// Schema version of this binary.
+        supported: u64,
+    },
+    /// The database must be migrated, by opening it read-write, before it is used.
+    #[error("Database schema version {found} is older than the supported version {supported}")]
+    OutdatedVersion {
+        /// Schema version of the database.
+        found: u64,
+        /// Schema version of this binary.
+        supported: u64,
+    },
+    /// There is no migration to the given schema version.
+    #[error("Missing migration to schema version {0}")]
+    MissingMigration(u64),
+    /// A migration failed.
+    #[error("Migration to schema version {version} failed: {source}")]
+    Migration {
+        /// Schema version of the failed migration.
+        version: u64,
+        /// Error of the migration.
+        source: Error,
+    },
+    /// Failed to read or write the schema version.
+    #[error(transparent)]
+    Database(#[from] Error),
+}
+
+impl<E: EnvironmentKind> Env<E> {
+    /// Returns the schema version stored in the database, if any.
+    pub fn schema_version(&self) -> Result<Option<u64>, Error> {
+        self.tx()?.get::<DatabaseVersion>(SCHEMA_VERSION_KEY.to_vec())
+    }
+
+    /// Checks, without writing to the database, that it is at [`SCHEMA_VERSION`]. Returns the
+    /// schema version.
+    ///
+    /// It is meant for the databases opened read-only, or without holding the lock of the data
+    /// directory, which can not be migrated.
+    pub fn check_schema_version(&self) -> Result<u64, MigrationError> {
+        let tx = self.tx()?;
+        // The table of the version is missing in the databases created before it was stored.
+        let found = match tx.inner.open_db(Some(DatabaseVersion::NAME)) {
+            Ok(_) => tx.get::<DatabaseVersion>(SCHEMA_VERSION_KEY.to_vec())?,
+            Err(_) => None,
+        };
+        let found = match found {
+            Some(version) => version,
+            None => {
+                let is_empty = match tx.inner.open_db(Some(CanonicalHeaders::NAME)) {
+                    Ok(table) => {
+                        tx.inner.db_stat(&table).map_err(|e| Error::Read(e.into()))?.entries() == 0
+                    }
+                    Err(_) => true,
+                };
+                if is_empty {
+                    SCHEMA_VERSION
+                } else {
+                    BASE_SCHEMA_VERSION
+                }
+            }
+        };
+
+        match found.cmp(&SCHEMA_VERSION) {
+            std::cmp::Ordering::Less => {
+                Err(MigrationError::OutdatedVersion { found, supported: SCHEMA_VERSION })
+            }
+            std::cmp::Ordering::Greater => {
+                Err(MigrationError::UnsupportedVersion { found, supported: SCHEMA_VERSION })
+            }
+            std::cmp::Ordering::Equal => Ok(found),
+        }
+    }
+
+    /// Migrates the database to [`SCHEMA_VERSION`]. The tables must have been created with
+    /// [`Env::create_tables`] beforehand.
+    pub fn migrate(&self) -> Result<u64, MigrationError> {
+        self.migrate_to(SCHEMA_VERSION, &migrations())
+    }
+
+    /// Runs, in order, the `migrations` needed to bring the database to the schema version
+    /// `target`. Returns the new schema version.
+    ///
+    /// The schema version is updated after each migration, along with the clearing of its
+    /// progress. A failed migration resumes from its progress when it is run again.
+    ///
+    /// An empty database is directly set to `target`. A database without a schema version is
+    /// assumed to be at [`BASE_SCHEMA_VERSION`].
+    pub fn migrate_to(
+        &self,
+        target: u64,
+        migrations: &[Migration<E>],
+    ) -> Result<u64, MigrationError> {
+        let tx = self.tx_mut()?;
+        let current = match tx.get::<DatabaseVersion>(SCHEMA_VERSION_KEY.to_vec())? {
+            Some(version) => version,
+            None => {
+                // The keys are not decoded, since their encoding depends on the schema version.
+                let table = tx
+                    .inner
+                    .open_db(Some(CanonicalHeaders::NAME))
+                    .map_err(|e| Error::Read(e.into()))?;
+                let stat = tx.inner.db_stat(&table).map_err(|e| Error::Read(e.into()))?;
+                let is_empty = stat.entries() == 0;
+                let version = if is_empty { target } else { BASE_SCHEMA_VERSION };
+                tx.put::<DatabaseVersion>(SCHEMA_VERSION_KEY.to_vec(), version)?;
+                version
+            }
+        };
+        tx.commit()?;
+
+        if current > target {
+            return Err(MigrationError::UnsupportedVersion { found: current, supported: target })
+        }
+
+        for version in current + 1..=target {
+            let migration = migrations
+                .iter()
+                .find(|migration| migration.version == version)
+                .ok_or(MigrationError::MissingMigration(version))?;
+            info!(
+                target: "storage::db::mdbx",
+                version,
+                description = migration.description,
+                "Migrating database"
+            );
+
+            (migration.migrate)(self)
+                .map_err(|source| MigrationError::Migration { version, source })?;
+
+            let tx = self.tx_mut()?;
+            let progress = progress_table(&tx)?;
+            tx.inner.clear_db(&progress).map_err(|e| Error::Delete(e.into()))?;
+            tx.put::<DatabaseVersion>(SCHEMA_VERSION_KEY.to_vec(), version)?;
+            tx.commit()?;
+        }
+
+        Ok(target)
+    }
+}
@@ -2,18 +2,30 @@
 
 use crate::{
     database::{Database, DatabaseGAT},
-    tables::{TableType, TABLES},
+    tables::{TableType, Tables},
+    transaction::DbTx,
     utils::default_page_size,
     Error,
 };
 use reth_libmdbx::{
-    DatabaseFlags, Environment, EnvironmentFlags, EnvironmentKind, Geometry, Mode, PageSize,
-    SyncMode, RO, RW,
+    DatabaseFlags, Environment, EnvironmentFlags, EnvironmentKind, Error::MapFull, Geometry, Mode,
+    PageSize, SyncMode, RO, RW,
 };
-use std::{ops::Deref, path::Path};
+use std::{ops::Deref, path::Path, sync::Arc, time::Duration};
+
+pub mod backup;
 
 pub mod cursor;
 
+mod metrics;
+
+pub mod migration;
+
+mod watchdog;
+use watchdog::{spawn_watchdog, ReadTxRegistry};
+
+pub mod stats;
+
 pub mod tx;
 use tx::Tx;
 
@@ -26,11 +38,114 @@ pub enum EnvKind {
     RW,
 }
 
+/// 1 KB in bytes
+pub const KILOBYTE: usize = 1024;
+/// 1 MB in bytes
+pub const MEGABYTE: usize = KILOBYTE * 1024;
+/// 1 GB in bytes
+pub const GIGABYTE: usize = MEGABYTE * 1024;
+/// 1 TB in bytes
+pub const TERABYTE: usize = GIGABYTE * 1024;
+
+/// Tunable parameters of a MDBX environment.
+///
+/// The default values are the ones used by [`Env::open`].
+#[derive(Debug, Clone, Copy)]
+pub struct EnvConfig {
+    /// Maximum size of the database map, in bytes.
+    pub max_size: usize,
+    /// Step by which the database file grows when it is full, in bytes.
+    pub growth_step: isize,
+    /// Database page size. If not set, it is derived from the OS page size.
+    pub page_size: Option<usize>,
+    /// Sync mode used by read-write environments.
+    pub sync_mode: SyncMode,
+    /// Maximum number of concurrent readers. If not set, the MDBX default is used.
+    pub max_readers: Option<u32>,
+    /// Disables the OS readahead, which helps with random reads on databases larger than RAM.
+    pub no_rdahead: bool,
+    /// Limit up to which the maximum size is grown when the database map is full. See
+    /// [`Env::update_with_map_growth`].
+    pub max_size_limit: usize,
+    /// Read-only transactions open for longer than this are reported. See
+    /// [`Env::long_lived_read_txs`].
+    pub read_tx_timeout: Option<Duration>,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 4 * TERABYTE,
+            growth_step: 256 * MEGABYTE as isize,
+            page_size: None,
+            sync_mode: SyncMode::Durable,
+            max_readers: None,
+            no_rdahead: true,
+            max_size_limit: 16 * TERABYTE,
+            read_tx_timeout: None,
+        }
+    }
+}
+
+impl EnvConfig {
+    /// Sets the maximum size of the database map.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets the growth step of the database file.
+    pub fn growth_step(mut self, growth_step: isize) -> Self {
+        self.growth_step = growth_step;
+        self
+    }
+
+    /// Sets the database page size.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Sets the sync mode of read-write environments.
+    pub fn sync_mode(mut self, sync_mode: SyncMode) -> Self {
+        self.sync_mode = sync_mode;
+        self
+    }
+
+    /// Sets the maximum number of concurrent readers.
+    pub fn max_readers(mut self, max_readers: u32) -> Self {
+        self.max_readers = Some(max_readers);
+        self
+    }
+
+    /// Enables or disables the OS readahead.
+    pub fn no_rdahead(mut self, no_rdahead: bool) -> Self {
+        self.no_rdahead = no_rdahead;
+        self
+    }
+
+    /// Sets the limit up to which the maximum size is grown when the database map is full.
+    pub fn max_size_limit(mut self, max_size_limit: usize) -> Self {
+        self.max_size_limit = max_size_limit;
+        self
+    }
+
+    /// Sets the duration after which open read-only transactions are reported.
+    pub fn read_tx_timeout(mut self, read_tx_timeout: Duration) -> Self {
+        self.read_tx_timeout = Some(read_tx_timeout);
+        self
+    }
+}
+
 /// Wrapper for the libmdbx environment.
 #[derive(Debug)]
 pub struct Env<E: EnvironmentKind> {
     /// Libmdbx-sys environment.
     pub inner: Environment<E>,
+    /// Configuration the environment was opened with.
+    config: EnvConfig,
+    /// Open read-only transactions.
+    read_txs: Arc<ReadTxRegistry>,
 }
 
 impl<'a, E: EnvironmentKind> DatabaseGAT<'a> for Env<E> {
@@ -40,12 +155,26 @@ impl<'a, E: EnvironmentKind> DatabaseGAT<'a> for Env<E> {
 
 impl<E: EnvironmentKind> Database for Env<E> {
     fn tx(&self) -> Result<<Self as DatabaseGAT<'_>>::TX, Error> {
-        Ok(Tx::new(self.inner.begin_ro_txn().map_err(|e| Error::InitTransaction(e.into()))?))
+        let tx = Tx::new(self.inner.begin_ro_txn().map_err(|e| Error::InitTransaction(e.into()))?);
+        let guard = self.read_txs.register(tx.id());
+        Ok(tx.with_read_guard(guard))
     }
 
+    /// The map is grown ahead of the transaction if it's more than half full, see
+    /// [`Env::reserve_map_space`].
     fn tx_mut(&self) -> Result<<Self as DatabaseGAT<'_>>::TXMut, Error> {
+        self.reserve_map_space()?;
         Ok(Tx::new(self.inner.begin_rw_txn().map_err(|e| Error::InitTransaction(e.into()))?))
     }
+
+    /// Same as [`Database::update`], but the map is grown and the function is retried if the
+    /// commit fails because the map is full. See [`Env::update_with_map_growth`].
+    fn update<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: Fn(&<Self as DatabaseGAT<'_>>::TXMut) -> T,
+    {
+        self.update_with_map_growth(|tx| Ok(f(tx)))
+    }
 }
 
 impl<E: EnvironmentKind> Env<E> {
@@ -53,44 +182,157 @@ impl<E: EnvironmentKind> Env<E> {
     ///
     /// It does not create the tables, for that call [`Env::create_tables`].
     pub fn open(path: &Path, kind: EnvKind) -> Result<Env<E>, Error> {
+        Self::open_with_config(path, kind, EnvConfig::default())
+    }
+
+    /// Opens the database at the specified path with the given `EnvKind` and [`EnvConfig`].
+    ///
+    /// It does not create the tables, for that call [`Env::create_tables`].
+    pub fn open_with_config(
+        path: &Path,
+        kind: EnvKind,
+        config: EnvConfig,
+    ) -> Result<Env<E>, Error> {
         let mode = match kind {
             EnvKind::RO => Mode::ReadOnly,
-            EnvKind::RW => Mode::ReadWrite { sync_mode: SyncMode::Durable },
+            EnvKind::RW => Mode::ReadWrite { sync_mode: config.sync_mode },
         };
 
-        let env = Env {
-            inner: Environment::new()
-                .set_max_dbs(TABLES.len())
-                .set_geometry(Geometry {
-                    size: Some(0..(1024 * 1024 * 1024 * 1024 * 4)), // TODO: reevaluate (4 tb)
-                    growth_step: Some(1024 * 1024 * 256),           // TODO: reevaluate (256 mb)
-                    shrink_threshold: None,
-                    page_size: Some(PageSize::Set(default_page_size())),
-                })
-                .set_flags(EnvironmentFlags {
-                    mode,
-                    no_rdahead: true, // TODO: reevaluate
-                    coalesce: true,
-                    ..Default::default()
-                })
-                .open(path)
-                .map_err(|e| Error::DatabaseLocation(e.into()))?,
-        };
+        let mut builder = Environment::new();
+        builder
+            .set_max_dbs(Tables::ALL.len() + migration::MIGRATION_TABLES)
+            .set_geometry(Geometry {
+                size: Some(0..config.max_size),
+                growth_step: Some(config.growth_step),
+                shrink_threshold: None,
+                page_size: Some(PageSize::Set(config.page_size.unwrap_or_else(default_page_size))),
+            })
+            .set_flags(EnvironmentFlags {
+                mode,
+                no_rdahead: config.no_rdahead,
+                coalesce: true,
+                ..Default::default()
+            });
+        if let Some(max_readers) = config.max_readers {
+            builder.set_max_readers(max_readers);
+        }
+
+        let inner = builder.open(path).map_err(|e| Error::DatabaseLocation(e.into()))?;
 
-        Ok(env)
+        Ok(Self::with_inner(inner, config))
+    }
+
+    /// Opens the database at the specified path read-only, without locking it exclusively, so
+    /// that external tools can read it while the node writes to it.
+    ///
+    /// The geometry and flags of the database are taken from the writer. Like the other opens, it
+    /// sets `MDBX_NOTLS`, so its read-only transactions are not bound to the thread which opened
+    /// them.
+    pub fn open_shared_ro(path: &Path) -> Result<Env<E>, Error> {
+        let mut builder = Environment::new();
+        builder.set_max_dbs(Tables::ALL.len() + migration::MIGRATION_TABLES).set_flags(
+            EnvironmentFlags {
+                mode: Mode::ReadOnly,
+                exclusive: false,
+                accede: true,
+                ..Default::default()
+            },
+        );
+
+        let inner = builder.open(path).map_err(|e| Error::DatabaseLocation(e.into()))?;
+
+        Ok(Self::with_inner(inner, EnvConfig::default()))
+    }
+
+    /// Wraps an opened environment, starting the watchdog of read-only transactions if needed.
+    fn with_inner(inner: Environment<E>, config: EnvConfig) -> Self {
+        let read_txs = Arc::new(ReadTxRegistry::default());
+        if let Some(timeout) = config.read_tx_timeout {
+            spawn_watchdog(Arc::downgrade(&read_txs), timeout);
+        }
+
+        Env { inner, config, read_txs }
+    }
+
+    /// Returns the MDBX id and age of the read-only transactions open for longer than
+    /// [`EnvConfig::read_tx_timeout`].
+    ///
+    /// Such transactions prevent MDBX from reusing the pages freed since they were opened. If the
+    /// timeout is set, they are also logged periodically and counted in the
+    /// `db.tx.long_lived_ro` gauge.
+    pub fn long_lived_read_txs(&self) -> Vec<(u64, Duration)> {
+        self.config
+            .read_tx_timeout
+            .map(|timeout| self.read_txs.long_lived(timeout))
+            .unwrap_or_default()
+    }
+
+    /// Takes a function and passes a read-write transaction into it, committing it at the end.
+    ///
+    /// If the database map gets full, the transaction is aborted, the maximum size of the map is
+    /// doubled and the function is retried, until [`EnvConfig::max_size_limit`] is reached.
+    pub fn update_with_map_growth<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: Fn(&Tx<'_, RW, E>) -> Result<T, Error>,
+    {
+        loop {
+            let tx = self.tx_mut()?;
+            let res = match f(&tx) {
+                Ok(res) => tx.commit().map(|_| res),
+                Err(err) => {
+                    drop(tx);
+                    Err(err)
+                }
+            };
+
+            match res {
+                Err(err) if is_map_full(&err) => self.grow_map().map_err(|_| err)?,
+                res => return res,
+            }
+        }
+    }
+
+    /// Doubles the maximum size of the database map if more than half of it is used, without
+    /// exceeding [`EnvConfig::max_size_limit`].
+    ///
+    /// The writes of transactions that are opened directly, e.g. by the stages, can't be retried
+    /// like those of [`Env::update_with_map_growth`], so the map is grown before they fill it.
+    fn reserve_map_space(&self) -> Result<(), Error> {
+        let info = self.inner.info().map_err(|e| Error::InitTransaction(e.into()))?;
+        let page_size =
+            self.inner.stat().map_err(|e| Error::InitTransaction(e.into()))?.page_size();
+        let used = (info.last_pgno() + 1).saturating_mul(page_size as usize);
+        let max = info.geometry().max() as usize;
+        if used > max / 2 && max < self.config.max_size_limit {
+            self.grow_map()?;
+        }
+        Ok(())
+    }
+
+    /// Doubles the maximum size of the database map, without exceeding
+    /// [`EnvConfig::max_size_limit`].
+    fn grow_map(&self) -> Result<(), Error> {
+        let info = self.inner.info().map_err(|e| Error::Write(e.into()))?;
+        let current = info.geometry().max() as usize;
+        let max_size = current.saturating_mul(2).min(self.config.max_size_limit);
+        if max_size <= current {
+            return Err(Error::Write(MapFull.to_err_code()))
+        }
+
+        self.inner.set_max_size(max_size).map_err(|e| Error::Write(e.into()))
     }
 
     /// Creates all the defined tables, if necessary.
     pub fn create_tables(&self) -> Result<(), Error> {
         let tx = self.inner.begin_rw_txn().map_err(|e| Error::InitTransaction(e.into()))?;
 
-        for (table_type, table) in TABLES {
-            let flags = match table_type {
+        for table in Tables::ALL {
+            let flags = match table.table_type() {
                 TableType::Table => DatabaseFlags::default(),
                 TableType::DupSort => DatabaseFlags::DUP_SORT,
             };
 
-            tx.create_db(Some(table), flags).map_err(|e| Error::TableCreation(e.into()))?;
+            tx.create_db(Some(table.name()), flags).map_err(|e| Error::TableCreation(e.into()))?;
         }
 
         tx.commit().map_err(|e| Error::Commit(e.into()))?;
@@ -99,6 +341,11 @@ impl<E: EnvironmentKind> Env<E> {
     }
 }
 
+/// Returns `true` if the error was caused by a full database map.
+fn is_map_full(err: &Error) -> bool {
+    matches!(err, Error::Write(code) | Error::Commit(code) if *code == MapFull.to_err_code())
+}
+
 impl<E: EnvironmentKind> Deref for Env<E> {
     type Target = reth_libmdbx::Environment<E>;
 
@@ -143,24 +390,34 @@ pub mod test_utils {
 
 #[cfg(test)]
 mod tests {
-    use super::{test_utils, Env, EnvKind};
+    use super::{
+        migration::{Migration, MigrationError, BASE_SCHEMA_VERSION, SCHEMA_VERSION},
+        test_utils::{self, ERROR_TABLE_CREATION, ERROR_TEMPDIR},
+        tx::Tx,
+        Env, EnvConfig, EnvKind, KILOBYTE, MEGABYTE,
+    };
     use crate::{
         cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, ReverseWalker, Walker},
         database::Database,
         models::{AccountBeforeTx, ShardedKey},
-        tables::{AccountHistory, CanonicalHeaders, Headers, PlainAccountState, PlainStorageState},
+        table::Compress,
+        tables::{
+            codecs::Compression, AccountHistory, CanonicalHeaders, DatabaseVersion, Headers,
+            PlainAccountState, PlainStorageState, RawKey, RawTable, RawValue, SCHEMA_VERSION_KEY,
+        },
         transaction::{DbTx, DbTxMut},
         AccountChangeSet, Error,
     };
-    use reth_libmdbx::{NoWriteMap, WriteMap};
+    use reth_libmdbx::{Error::MapFull, NoWriteMap, SyncMode, WriteMap, RW};
     use reth_primitives::{Account, Address, Header, IntegerList, StorageEntry, H256, U256};
-    use std::{str::FromStr, sync::Arc};
+    use std::{ops::Bound, str::FromStr, sync::Arc, time::Duration};
     use tempfile::TempDir;
 
     const ERROR_DB_CREATION: &str = "Not able to create the mdbx file.";
     const ERROR_PUT: &str = "Not able to insert value into table.";
     const ERROR_APPEND: &str = "Not able to append the value to the table.";
     const ERROR_GET: &str = "Not able to get value from table.";
+    const ERROR_DELETE: &str = "Not able to delete from table.";
     const ERROR_COMMIT: &str = "Not able to commit transaction.";
     const ERROR_RETURN_VALUE: &str = "Mismatching result.";
     const ERROR_INIT_TX: &str = "Failed to create a MDBX transaction.";
@@ -171,6 +428,211 @@ mod tests {
         test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
     }
 
+    #[test]
+    fn db_open_with_config() {
+        let path = TempDir::new().expect(ERROR_TEMPDIR).into_path();
+        let config = EnvConfig::default()
+            .max_size(64 * MEGABYTE)
+            .growth_step(4 * MEGABYTE as isize)
+            .sync_mode(SyncMode::SafeNoSync)
+            .max_readers(32);
+        let env =
+            Env::<WriteMap>::open_with_config(&path, EnvKind::RW, config).expect(ERROR_DB_CREATION);
+        env.create_tables().expect(ERROR_TABLE_CREATION);
+
+        let info = env.info().expect(ERROR_DB_CREATION);
+        assert_eq!(info.max_readers(), 32);
+
+        let tx = env.tx_mut().expect(ERROR_INIT_TX);
+        tx.put::<CanonicalHeaders>(1, H256::zero()).expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get::<CanonicalHeaders>(1).expect(ERROR_GET), Some(H256::zero()));
+    }
+
+    #[test]
+    fn db_map_growth() {
+        let path = TempDir::new().expect(ERROR_TEMPDIR).into_path();
+        let config = EnvConfig::default()
+            .max_size(MEGABYTE)
+            .growth_step(64 * KILOBYTE as isize)
+            .max_size_limit(64 * MEGABYTE);
+        let env =
+            Env::<WriteMap>::open_with_config(&path, EnvKind::RW, config).expect(ERROR_DB_CREATION);
+        env.create_tables().expect(ERROR_TABLE_CREATION);
+
+        fn put_all(tx: &Tx<'_, RW, WriteMap>) -> Result<(), Error> {
+            (0..100_000u64)
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::from_low_u64_be(key)))
+        }
+
+        // The initial map is too small
+        let tx = env.tx_mut().expect(ERROR_INIT_TX);
+        assert_eq!(put_all(&tx), Err(Error::Write(MapFull.to_err_code())));
+        drop(tx);
+
+        // The map is grown until the transaction fits
+        env.update_with_map_growth(put_all).expect(ERROR_PUT);
+        assert!(env.info().unwrap().geometry().max() > MEGABYTE as u64);
+
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        let last = tx.cursor_read::<CanonicalHeaders>().unwrap().last().unwrap();
+        assert_eq!(last, Some((99_999, H256::from_low_u64_be(99_999))));
+    }
+
+    #[test]
+    fn db_map_growth_ahead_of_write_txs() {
+        let path = TempDir::new().expect(ERROR_TEMPDIR).into_path();
+        let config = EnvConfig::default()
+            .max_size(MEGABYTE)
+            .growth_step(64 * KILOBYTE as isize)
+            .max_size_limit(64 * MEGABYTE);
+        let env =
+            Env::<WriteMap>::open_with_config(&path, EnvKind::RW, config).expect(ERROR_DB_CREATION);
+        env.create_tables().expect(ERROR_TABLE_CREATION);
+
+        // Every transaction fits into the free half of the map, which is grown before it's full
+        for batch in 0..100u64 {
+            let tx = env.tx_mut().expect(ERROR_INIT_TX);
+            for key in batch * 1_000..(batch + 1) * 1_000 {
+                tx.put::<CanonicalHeaders>(key, H256::from_low_u64_be(key)).expect(ERROR_PUT);
+            }
+            tx.commit().expect(ERROR_COMMIT);
+        }
+        assert!(env.info().unwrap().geometry().max() > MEGABYTE as u64);
+    }
+
+    #[test]
+    fn db_long_lived_read_txs() {
+        let path = TempDir::new().expect(ERROR_TEMPDIR).into_path();
+        let config = EnvConfig::default().read_tx_timeout(Duration::from_millis(10));
+        let env =
+            Env::<WriteMap>::open_with_config(&path, EnvKind::RW, config).expect(ERROR_DB_CREATION);
+        env.create_tables().expect(ERROR_TABLE_CREATION);
+
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert!(env.long_lived_read_txs().is_empty());
+
+        std::thread::sleep(Duration::from_millis(20));
+        let long_lived = env.long_lived_read_txs();
+        assert_eq!(long_lived.len(), 1);
+        assert_eq!(long_lived[0].0, tx.id());
+
+        drop(tx);
+        assert!(env.long_lived_read_txs().is_empty());
+    }
+
+    #[test]
+    fn db_backup() {
+        let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
+        env.update_batch::<CanonicalHeaders>((0..100).map(|key| (key, H256::from_low_u64_be(key))))
+            .expect(ERROR_PUT);
+
+        let path = TempDir::new().expect(ERROR_TEMPDIR).into_path();
+        let (full, throttled) = (path.join("full"), path.join("throttled"));
+        env.backup(&full).expect("Not able to backup the database.");
+        env.backup_throttled(&throttled, 64 * MEGABYTE as u64)
+            .expect("Not able to backup the database.");
+
+        for path in [full, throttled] {
+            let backup = Env::<NoWriteMap>::open(&path, EnvKind::RO).expect(ERROR_DB_CREATION);
+            let tx = backup.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+            assert_eq!(cursor.walk(None).unwrap().count(), 100);
+            assert_eq!(cursor.last(), Ok(Some((99, H256::from_low_u64_be(99)))));
+        }
+    }
+
+    #[test]
+    fn db_migrations() {
+        let env = test_utils::create_test_rw_db();
+        let migrations = [
+            Migration {
+                version: 2,
+                description: "insert a header",
+                migrate: |env| env.update(|tx| tx.put::<CanonicalHeaders>(1, H256::zero()))?,
+            },
+            Migration { version: 3, description: "fail", migrate: |_| Err(Error::Write(0)) },
+        ];
+
+        // A new database is directly at the target version
+        assert_eq!(env.migrate_to(1, &migrations), Ok(1));
+        assert_eq!(env.schema_version(), Ok(Some(1)));
+
+        // A failed migration keeps the schema version of the previous one
+        assert_eq!(
+            env.migrate_to(3, &migrations),
+            Err(MigrationError::Migration { version: 3, source: Error::Write(0) })
+        );
+        assert_eq!(env.schema_version(), Ok(Some(2)));
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get::<CanonicalHeaders>(1), Ok(Some(H256::zero())));
+        drop(tx);
+
+        assert_eq!(env.migrate_to(4, &migrations[..1]), Err(MigrationError::MissingMigration(3)));
+        assert_eq!(
+            env.migrate_to(1, &migrations),
+            Err(MigrationError::UnsupportedVersion { found: 2, supported: 1 })
+        );
+    }
+
+    #[test]
+    fn db_check_schema_version() {
+        let env = test_utils::create_test_rw_db();
+
+        // An empty database can be used as is
+        assert_eq!(env.check_schema_version(), Ok(SCHEMA_VERSION));
+
+        // A database without a schema version is at the base version
+        env.update(|tx| tx.put::<CanonicalHeaders>(1, H256::zero())).unwrap().expect(ERROR_PUT);
+        assert_eq!(
+            env.check_schema_version(),
+            Err(MigrationError::OutdatedVersion {
+                found: BASE_SCHEMA_VERSION,
+                supported: SCHEMA_VERSION
+            })
+        );
+
+        env.update(|tx| tx.put::<DatabaseVersion>(SCHEMA_VERSION_KEY.to_vec(), SCHEMA_VERSION))
+            .unwrap()
+            .expect(ERROR_PUT);
+        assert_eq!(env.check_schema_version(), Ok(SCHEMA_VERSION));
+
+        env.update(|tx| tx.put::<DatabaseVersion>(SCHEMA_VERSION_KEY.to_vec(), SCHEMA_VERSION + 1))
+            .unwrap()
+            .expect(ERROR_PUT);
+        assert_eq!(
+            env.check_schema_version(),
+            Err(MigrationError::UnsupportedVersion {
+                found: SCHEMA_VERSION + 1,
+                supported: SCHEMA_VERSION
+            })
+        );
+    }
+
+    #[test]
+    fn db_open_shared_ro() {
+        let path = TempDir::new().expect(ERROR_TEMPDIR).into_path();
+        let env = test_utils::create_test_db_with_path::<WriteMap>(EnvKind::RW, &path);
+        env.update(|tx| tx.put::<CanonicalHeaders>(1, H256::zero())).unwrap().expect(ERROR_PUT);
+        drop(env);
+
+        let env = Env::<NoWriteMap>::open_shared_ro(&path).expect(ERROR_DB_CREATION);
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get::<CanonicalHeaders>(1), Ok(Some(H256::zero())));
+        assert!(env.tx_mut().is_err());
+
+        // Without `MDBX_NOTLS`, a second read-only transaction of the thread would fail, and it
+        // could not be used from another thread.
+        let other = env.tx().expect(ERROR_INIT_TX);
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                assert_eq!(other.get::<CanonicalHeaders>(1), Ok(Some(H256::zero())));
+            });
+        });
+    }
+
     #[test]
     fn db_manual_put_get() {
         let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
@@ -190,6 +652,93 @@ mod tests {
         tx.commit().expect(ERROR_COMMIT);
     }
 
+    #[test]
+    fn db_clear_and_drop_table() {
+        let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
+
+        let tx = env.tx_mut().expect(ERROR_INIT_TX);
+        tx.put::<CanonicalHeaders>(1, H256::zero()).expect(ERROR_PUT);
+        tx.put::<Headers>(1, Header::default()).expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        // CLEAR
+        let tx = env.tx_mut().expect(ERROR_INIT_TX);
+        tx.clear::<CanonicalHeaders>().expect(ERROR_DELETE);
+        assert_eq!(tx.get::<CanonicalHeaders>(1), Ok(None));
+        tx.commit().expect(ERROR_COMMIT);
+
+        // DROP
+        let tx = env.tx_mut().expect(ERROR_INIT_TX);
+        unsafe { tx.drop_table::<Headers>() }.expect(ERROR_DELETE);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert_eq!(
+            tx.get::<Headers>(1),
+            Err(Error::Read(reth_libmdbx::Error::NotFound.to_err_code()))
+        );
+        drop(tx);
+
+        // The dropped table is created again empty
+        env.create_tables().expect(ERROR_TABLE_CREATION);
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get::<Headers>(1), Ok(None));
+    }
+
+    #[test]
+    fn db_put_many() {
+        let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
+        let hash = H256::from_low_u64_be;
+
+        // Unsorted entries with a duplicated key are appended to the empty table
+        env.update_batch::<CanonicalHeaders>(vec![(3, hash(3)), (1, hash(1)), (3, hash(33))])
+            .expect(ERROR_PUT);
+
+        // Entries below the last key are upserted
+        let tx = env.tx_mut().expect(ERROR_INIT_TX);
+        tx.put_many::<CanonicalHeaders>(vec![(2, hash(2)), (1, hash(11))]).expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+        let res = cursor.walk(None).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(res, vec![(1, hash(11)), (2, hash(2)), (3, hash(33))]);
+    }
+
+    #[test]
+    fn db_get_many() {
+        let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
+        let hash = H256::from_low_u64_be;
+        env.update(|tx| {
+            tx.put_many::<CanonicalHeaders>([(1, hash(1)), (3, hash(3)), (300, hash(300))])
+        })
+        .unwrap()
+        .expect(ERROR_PUT);
+
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert_eq!(
+            tx.get_many::<CanonicalHeaders>(&[300, 2, 1, 300, 0]),
+            Ok(vec![Some(hash(300)), None, Some(hash(1)), Some(hash(300)), None])
+        );
+        assert_eq!(tx.get_many::<CanonicalHeaders>(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn db_stats() {
+        let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
+        env.update_batch::<CanonicalHeaders>((0..100).map(|key| (key, H256::zero())))
+            .expect(ERROR_PUT);
+
+        let stats = env.stats().expect(ERROR_GET);
+        assert_eq!(stats.tables.len(), crate::tables::Tables::ALL.len());
+        assert!(stats.file_size > 0);
+
+        let canonical = stats.tables.iter().find(|table| table.name == "CanonicalHeaders").unwrap();
+        assert_eq!(canonical.entries, 100);
+        assert!(canonical.depth > 0);
+        assert!(stats.table_size(canonical) >= stats.page_size);
+    }
+
     #[test]
     fn db_cursor_walk() {
         let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
@@ -279,6 +828,75 @@ mod tests {
         assert_eq!(walker.next(), None);
     }
 
+    #[test]
+    fn db_cursor_walk_prefix() {
+        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
+        let address = |n| Address::from_low_u64_be(n);
+        let entry = |n| StorageEntry { key: H256::from_low_u64_be(n), value: U256::from(n) };
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        for (addr, slot) in [(1, 1), (2, 1), (2, 2), (3, 1)] {
+            tx.put::<PlainStorageState>(address(addr), entry(slot)).expect(ERROR_PUT);
+        }
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+
+        let walker = cursor.walk_prefix(address(2)).unwrap();
+        assert_eq!(
+            walker.map(|res| res.unwrap()).collect::<Vec<_>>(),
+            vec![(address(2), entry(1)), (address(2), entry(2))]
+        );
+
+        assert_eq!(cursor.walk_prefix(address(4)).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn db_delete_range() {
+        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        tx.put_many::<CanonicalHeaders>((0..5).map(|key| (key, H256::zero()))).expect(ERROR_PUT);
+
+        assert_eq!(tx.delete_range::<CanonicalHeaders>(1..3), Ok(2));
+        assert_eq!(
+            tx.delete_range::<CanonicalHeaders>((Bound::Excluded(3), Bound::Unbounded)),
+            Ok(1)
+        );
+        assert_eq!(tx.delete_range::<CanonicalHeaders>(5..), Ok(0));
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+        let keys = cursor.walk(None).unwrap().map(|res| res.unwrap().0).collect::<Vec<_>>();
+        assert_eq!(keys, vec![0, 3]);
+    }
+
+    #[test]
+    fn db_raw_table() {
+        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
+        let header = Header { number: 1, gas_limit: 30_000_000, ..Default::default() };
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        tx.put::<Headers>(1, header.clone()).expect(ERROR_PUT);
+
+        // Raw values of a compressed table are copied without being decoded
+        let raw = tx.get::<RawTable<Headers>>(RawKey::new(1)).expect(ERROR_GET).unwrap();
+        assert_eq!(raw.raw_value(), Compression::Zstd.compress(header.clone().compress().as_ref()));
+        assert_eq!(raw.value(), Ok(header.clone()));
+        tx.put::<RawTable<Headers>>(RawKey::new(2), raw).expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get::<Headers>(2).expect(ERROR_GET), Some(header.clone()));
+
+        let mut cursor = tx.cursor_read::<RawTable<Headers>>().unwrap();
+        let (key, value) = cursor.last().unwrap().unwrap();
+        assert_eq!(key.key(), Ok(2));
+        assert_eq!(value, RawValue::new(header));
+    }
+
     #[test]
     fn db_cursor_walk_range_invalid() {
         let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
@@ -303,6 +921,41 @@ mod tests {
         assert!(matches!(res, Err(Error::Read(2))));
     }
 
+    #[test]
+    fn db_cursor_walk_range_bounds() {
+        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
+
+        // PUT (0, 0), (1, 0), (3, 0)
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        vec![0, 1, 3]
+            .into_iter()
+            .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+            .expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+
+        // [2, 3): first entry after the seek is already out of range
+        let mut walker = cursor.walk_range(2..3).unwrap();
+        assert_eq!(walker.next(), None);
+
+        // [4, ∞): nothing to seek
+        let mut walker = cursor.walk_range(4..).unwrap();
+        assert_eq!(walker.next(), None);
+
+        // (0, 3]
+        let mut walker = cursor.walk_range((Bound::Excluded(0), Bound::Included(3))).unwrap();
+        assert_eq!(walker.next(), Some(Ok((1, H256::zero()))));
+        assert_eq!(walker.next(), Some(Ok((3, H256::zero()))));
+        assert_eq!(walker.next(), None);
+
+        // (2, ∞): excluded key is not present in the table
+        let mut walker = cursor.walk_range((Bound::Excluded(2), Bound::Unbounded)).unwrap();
+        assert_eq!(walker.next(), Some(Ok((3, H256::zero()))));
+        assert_eq!(walker.next(), None);
+    }
+
     #[test]
     fn db_walker() {
         let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
@@ -402,6 +1055,55 @@ mod tests {
         assert_eq!(reverse_walker.next(), None);
     }
 
+    #[test]
+    fn db_walk_back_range() {
+        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
+
+        // PUT (0, 0), (1, 0), (3, 0), (4, 0)
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        vec![0, 1, 3, 4]
+            .into_iter()
+            .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+            .expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+
+        // [1, 3]
+        let mut reverse_walker = cursor.walk_back_range(1..=3).unwrap();
+        assert_eq!(reverse_walker.next(), Some(Ok((3, H256::zero()))));
+        assert_eq!(reverse_walker.next(), Some(Ok((1, H256::zero()))));
+        assert_eq!(reverse_walker.next(), None);
+        // next() returns None after walker is done
+        assert_eq!(reverse_walker.next(), None);
+
+        // [1, 3)
+        let mut reverse_walker = cursor.walk_back_range(1..3).unwrap();
+        assert_eq!(reverse_walker.next(), Some(Ok((1, H256::zero()))));
+        assert_eq!(reverse_walker.next(), None);
+
+        // [2, ∞)
+        let mut reverse_walker = cursor.walk_back_range(2..).unwrap();
+        assert_eq!(reverse_walker.next(), Some(Ok((4, H256::zero()))));
+        assert_eq!(reverse_walker.next(), Some(Ok((3, H256::zero()))));
+        assert_eq!(reverse_walker.next(), None);
+
+        // (∞, 2]
+        let mut reverse_walker = cursor.walk_back_range(..=2).unwrap();
+        assert_eq!(reverse_walker.next(), Some(Ok((1, H256::zero()))));
+        assert_eq!(reverse_walker.next(), Some(Ok((0, H256::zero()))));
+        assert_eq!(reverse_walker.next(), None);
+
+        // [5, 10): every key is lower than the range
+        let mut reverse_walker = cursor.walk_back_range(5..10).unwrap();
+        assert_eq!(reverse_walker.next(), None);
+
+        // start bound greater than end bound
+        let res = cursor.walk_back_range(3..1);
+        assert!(matches!(res, Err(Error::Read(2))));
+    }
+
     #[test]
     fn db_cursor_seek_exact_or_previous_key() {
         let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
@@ -724,6 +1426,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn db_walk_dup_missing_key() {
+        let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
+        let key1 = Address::from_str("0x1111111111111111111111111111111111111111")
+            .expect(ERROR_ETH_ADDRESS);
+        let key2 = Address::from_str("0x2222222222222222222222222222222222222222")
+            .expect(ERROR_ETH_ADDRESS);
+
+        // Walking an empty table
+        {
+            let tx = env.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+            let mut walker = cursor.walk_dup(None, Some(H256::zero())).unwrap();
+            assert_eq!(None, walker.next());
+        }
+
+        // PUT key1 (1,1)
+        let value11 = StorageEntry { key: H256::from_low_u64_be(1), value: U256::from(1) };
+        env.update(|tx| tx.put::<PlainStorageState>(key1, value11.clone()).expect(ERROR_PUT))
+            .unwrap();
+
+        // Walking a key that is not present does not move onto other keys
+        {
+            let tx = env.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+            let mut walker = cursor.walk_dup(Some(key2), None).unwrap();
+            assert_eq!(None, walker.next());
+
+            let mut walker = cursor.walk_dup(Some(key2), Some(H256::zero())).unwrap();
+            assert_eq!(None, walker.next());
+        }
+
+        // Walking from a subkey greater than every duplicate of the key
+        {
+            let tx = env.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+            let mut walker = cursor.walk_dup(Some(key1), Some(H256::from_low_u64_be(2))).unwrap();
+            assert_eq!(None, walker.next());
+
+            let mut walker = cursor.walk_dup(Some(key1), Some(H256::zero())).unwrap();
+            assert_eq!(Some(Ok((key1, value11))), walker.next());
+            assert_eq!(None, walker.next());
+        }
+    }
+
     #[test]
     fn dup_value_with_same_subkey() {
         let env = test_utils::create_test_db::<NoWriteMap>(EnvKind::RW);
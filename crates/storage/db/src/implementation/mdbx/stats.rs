@@ -0,0 +1,85 @@
+//! Statistics of the MDBX environment and its tables.
+
+use super::Env;
+use crate::{tables::Tables, Error};
+use reth_libmdbx::EnvironmentKind;
+
+/// Statistics of a single table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableStats {
+    /// Name of the table.
+    pub name: &'static str,
+    /// Number of entries in the table.
+    pub entries: usize,
+    /// Depth of the table B-tree.
+    pub depth: u32,
+    /// Number of branch pages.
+    pub branch_pages: usize,
+    /// Number of leaf pages.
+    pub leaf_pages: usize,
+    /// Number of overflow pages.
+    pub overflow_pages: usize,
+}
+
+impl TableStats {
+    /// Total number of pages used by the table.
+    pub fn pages(&self) -> usize {
+        self.branch_pages + self.leaf_pages + self.overflow_pages
+    }
+}
+
+/// Statistics of the whole environment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvStats {
+    /// Size of a database page, in bytes.
+    pub page_size: usize,
+    /// Statistics of every table, in the order of [`Tables::ALL`].
+    pub tables: Vec<TableStats>,
+    /// Number of pages in the freelist, which can be reused by new writes.
+    pub freelist_pages: usize,
+    /// Size of the database file, in bytes.
+    pub file_size: u64,
+}
+
+impl EnvStats {
+    /// Size of a table, in bytes.
+    pub fn table_size(&self, table: &TableStats) -> usize {
+        table.pages() * self.page_size
+    }
+
+    /// Size of the freelist, in bytes.
+    pub fn freelist_size(&self) -> usize {
+        self.freelist_pages * self.page_size
+    }
+}
+
+impl<E: EnvironmentKind> Env<E> {
+    /// Collects the statistics of the environment and all its tables.
+    pub fn stats(&self) -> Result<EnvStats, Error> {
+        // The freelist is read with its own read-only transaction, so it needs to be done before
+        // opening ours.
+        let freelist_pages = self.inner.freelist().map_err(|e| Error::Read(e.into()))?;
+        let info = self.inner.info().map_err(|e| Error::Read(e.into()))?;
+        let page_size = self.inner.stat().map_err(|e| Error::Read(e.into()))?.page_size() as usize;
+
+        let tx = self.inner.begin_ro_txn().map_err(|e| Error::InitTransaction(e.into()))?;
+        let tables = Tables::ALL
+            .iter()
+            .map(|table| {
+                let name = table.name();
+                let db = tx.open_db(Some(name)).map_err(|e| Error::Read(e.into()))?;
+                let stat = tx.db_stat(&db).map_err(|e| Error::Read(e.into()))?;
+                Ok(TableStats {
+                    name,
+                    entries: stat.entries(),
+                    depth: stat.depth(),
+                    branch_pages: stat.branch_pages(),
+                    leaf_pages: stat.leaf_pages(),
+                    overflow_pages: stat.overflow_pages(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(EnvStats { page_size, tables, freelist_pages, file_size: info.geometry().current() })
+    }
+}
@@ -1,9 +1,13 @@
 //! Transaction wrapper for libmdbx-sys.
 
-use super::cursor::Cursor;
+use super::{
+    cursor::Cursor,
+    metrics::{record_get, record_put, OpenTxGuard},
+    watchdog::ReadTxGuard,
+};
 use crate::{
-    table::{Compress, DupSort, Encode, Table, TableImporter},
-    tables::utils::decode_one,
+    table::{DupSort, Encode, Table, TableImporter},
+    tables::{codecs::compress_value, utils::decode_one},
     transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT},
     Error,
 };
@@ -16,6 +20,10 @@ use std::{marker::PhantomData, time::Instant};
 pub struct Tx<'a, K: TransactionKind, E: EnvironmentKind> {
     /// Libmdbx-sys transaction.
     pub inner: Transaction<'a, K, E>,
+    /// Tracks the transaction in the metrics of open transactions.
+    _open: OpenTxGuard,
+    /// Keeps read-only transactions registered in the watchdog of the environment.
+    _read_guard: Option<ReadTxGuard>,
 }
 
 impl<'env, K: TransactionKind, E: EnvironmentKind> Tx<'env, K, E> {
@@ -24,7 +32,13 @@ impl<'env, K: TransactionKind, E: EnvironmentKind> Tx<'env, K, E> {
     where
         'a: 'env,
     {
-        Self { inner }
+        Self { inner, _open: OpenTxGuard::new::<K>(), _read_guard: None }
+    }
+
+    /// Attaches the guard registering this transaction in the watchdog.
+    pub(crate) fn with_read_guard(mut self, guard: ReadTxGuard) -> Self {
+        self._read_guard = Some(guard);
+        self
     }
 
     /// Gets this transaction ID.
@@ -47,6 +61,20 @@ impl<'env, K: TransactionKind, E: EnvironmentKind> Tx<'env, K, E> {
     }
 }
 
+impl<E: EnvironmentKind> Tx<'_, RW, E> {
+    /// Drops the table `T` from the database, together with all its entries.
+    ///
+    /// Unlike [`DbTxMut::clear`], the table does not exist anymore after the transaction is
+    /// committed, until it is created again with [`Env::create_tables`](super::Env::create_tables).
+    ///
+    /// # Safety
+    /// Caller must close ALL other cursors pointing to the same table BEFORE calling this function.
+    pub unsafe fn drop_table<T: Table>(&self) -> Result<(), Error> {
+        let db = self.inner.open_db(Some(T::NAME)).map_err(|e| Error::Delete(e.into()))?;
+        self.inner.drop_db(db).map_err(|e| Error::Delete(e.into()))
+    }
+}
+
 impl<'a, K: TransactionKind, E: EnvironmentKind> DbTxGAT<'a> for Tx<'_, K, E> {
     type Cursor<T: Table> = Cursor<'a, K, T>;
     type DupCursor<T: DupSort> = Cursor<'a, K, T>;
@@ -82,6 +110,7 @@ impl<'tx, K: TransactionKind, E: EnvironmentKind> DbTx<'tx> for Tx<'tx, K, E> {
     }
 
     fn get<T: Table>(&self, key: T::Key) -> Result<Option<<T as Table>::Value>, Error> {
+        record_get(T::NAME);
         self.inner
             .get(
                 &self.inner.open_db(Some(T::NAME)).map_err(|e| Error::Read(e.into()))?,
@@ -95,11 +124,13 @@ impl<'tx, K: TransactionKind, E: EnvironmentKind> DbTx<'tx> for Tx<'tx, K, E> {
 
 impl<E: EnvironmentKind> DbTxMut<'_> for Tx<'_, RW, E> {
     fn put<T: Table>(&self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let (key, value) = (key.encode(), compress_value::<T>(value));
+        record_put(T::NAME, key.as_ref().len() + value.as_ref().len());
         self.inner
             .put(
                 &self.inner.open_db(Some(T::NAME)).map_err(|e| Error::Write(e.into()))?,
-                &key.encode(),
-                &value.compress(),
+                &key,
+                &value,
                 WriteFlags::UPSERT,
             )
             .map_err(|e| Error::Write(e.into()))
@@ -108,7 +139,7 @@ impl<E: EnvironmentKind> DbTxMut<'_> for Tx<'_, RW, E> {
     fn delete<T: Table>(&self, key: T::Key, value: Option<T::Value>) -> Result<bool, Error> {
         let mut data = None;
 
-        let value = value.map(Compress::compress);
+        let value = value.map(compress_value::<T>);
         if let Some(value) = &value {
             data = Some(value.as_ref());
         };
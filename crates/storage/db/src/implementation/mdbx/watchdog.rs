@@ -0,0 +1,80 @@
+//! Watchdog of long-lived read-only transactions.
+//!
+//! MDBX can not reuse the pages freed by write transactions while an older read-only transaction
+//! is still open, so holding one for too long makes the database file grow.
+
+use metrics::gauge;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// Registry of the open read-only transactions of an environment.
+#[derive(Debug, Default)]
+pub(crate) struct ReadTxRegistry {
+    /// Key of the next registered transaction.
+    next_key: AtomicU64,
+    /// MDBX id and opening time of every open transaction.
+    open: Mutex<HashMap<u64, (u64, Instant)>>,
+}
+
+impl ReadTxRegistry {
+    /// Registers an open transaction, which stays registered until the guard is dropped.
+    pub(crate) fn register(self: &Arc<Self>, txn_id: u64) -> ReadTxGuard {
+        let key = self.next_key.fetch_add(1, Ordering::Relaxed);
+        self.open.lock().expect("not poisoned").insert(key, (txn_id, Instant::now()));
+        ReadTxGuard { registry: Arc::clone(self), key }
+    }
+
+    /// Returns the MDBX id and age of the transactions open for longer than `timeout`.
+    pub(crate) fn long_lived(&self, timeout: Duration) -> Vec<(u64, Duration)> {
+        self.open
+            .lock()
+            .expect("not poisoned")
+            .values()
+            .map(|(txn_id, opened_at)| (*txn_id, opened_at.elapsed()))
+            .filter(|(_, age)| *age > timeout)
+            .collect()
+    }
+}
+
+/// Keeps a read-only transaction registered until it is dropped.
+#[derive(Debug)]
+pub(crate) struct ReadTxGuard {
+    /// Registry the transaction is registered in.
+    registry: Arc<ReadTxRegistry>,
+    /// Key of the transaction in the registry.
+    key: u64,
+}
+
+impl Drop for ReadTxGuard {
+    fn drop(&mut self) {
+        self.registry.open.lock().expect("not poisoned").remove(&self.key);
+    }
+}
+
+/// Spawns a thread that warns about the transactions open for longer than `timeout` and reports
+/// their number in the `db.tx.long_lived_ro` gauge. It stops once the registry is dropped.
+pub(crate) fn spawn_watchdog(registry: Weak<ReadTxRegistry>, timeout: Duration) {
+    std::thread::Builder::new()
+        .name("mdbx-read-tx-watchdog".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(timeout);
+
+            let long_lived = match registry.upgrade() {
+                Some(registry) => registry.long_lived(timeout),
+                None => break,
+            };
+
+            gauge!("db.tx.long_lived_ro", long_lived.len() as f64);
+            for (txn_id, age) in long_lived {
+                warn!(target: "storage::db::mdbx", txn_id, ?age, "Read-only transaction open for too long");
+            }
+        })
+        .expect("thread name is valid");
+}
@@ -0,0 +1,420 @@
+//! Cursor of the in-memory database.
+
+use super::{tx::with_table, MemTable, MemTables, ACCESS, KEY_EXIST, KEY_MISMATCH};
+use crate::{
+    common::{PairResult, ValueOnlyResult},
+    cursor::{
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, PrefixWalker, RangeWalker,
+        ReverseRangeWalker, ReverseWalker, Walker,
+    },
+    table::{DupSort, Encode, Table},
+    tables::{codecs::compress_value, utils::*},
+    Error,
+};
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    sync::{Arc, Mutex},
+};
+
+/// Encoded `(key, value)` pair.
+type RawPair = (Vec<u8>, Vec<u8>);
+
+/// Cursor over a table of the in-memory database.
+///
+/// The position is kept as the encoded `(key, value)` pair it points to, so the cursor stays
+/// valid while the table is modified through the same transaction.
+#[derive(Debug)]
+pub struct MemCursor<T: Table> {
+    /// Tables of the transaction that created this cursor.
+    tables: Arc<Mutex<MemTables>>,
+    /// Whether the cursor is allowed to write.
+    writable: bool,
+    /// Current position of the cursor.
+    position: Option<RawPair>,
+    /// Phantom data to enforce encoding/decoding.
+    _dbi: PhantomData<T>,
+}
+
+impl<T: Table> MemCursor<T> {
+    /// Creates a new cursor that is not positioned yet.
+    pub(crate) fn new(tables: Arc<Mutex<MemTables>>, writable: bool) -> Self {
+        Self { tables, writable, position: None, _dbi: PhantomData }
+    }
+
+    /// Runs `f` over the table of the cursor.
+    fn with_table<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut MemTable) -> R,
+    {
+        with_table::<T, _, _>(&self.tables, f)
+    }
+
+    /// Moves the cursor to the pair found by `f`. If nothing is found, the cursor keeps its
+    /// position.
+    fn move_to<F>(&mut self, f: F) -> Result<Option<RawPair>, Error>
+    where
+        F: FnOnce(&MemTable, Option<&RawPair>) -> Option<RawPair>,
+    {
+        let position = self.position.clone();
+        let found = self.with_table(|table| f(table, position.as_ref()))?;
+        if found.is_some() {
+            self.position = found.clone();
+        }
+        Ok(found)
+    }
+
+    /// Moves the cursor and decodes the pair it lands on.
+    fn move_and_decode<F>(&mut self, f: F) -> PairResult<T>
+    where
+        F: FnOnce(&MemTable, Option<&RawPair>) -> Option<RawPair>,
+    {
+        self.move_to(f)?.map(decode_pair::<T>).transpose()
+    }
+
+    /// Returns an error if the cursor was created by a read-only transaction.
+    fn ensure_writable(&self, err: fn(i32) -> Error) -> Result<(), Error> {
+        if self.writable {
+            Ok(())
+        } else {
+            Err(err(ACCESS))
+        }
+    }
+}
+
+/// Decodes a raw `(key, value)` pair.
+fn decode_pair<T: Table>((key, value): RawPair) -> Result<(T::Key, T::Value), Error> {
+    decoder::<T>((Cow::Owned(key), Cow::Owned(value)))
+}
+
+/// First pair with a key greater or equal than `key`.
+fn seek_pair(table: &MemTable, key: &[u8]) -> Option<RawPair> {
+    table.entries.range((key.to_vec(), Vec::new())..).next().cloned()
+}
+
+/// First pair after `position`. Unpositioned cursors move to the first pair.
+fn next_pair(table: &MemTable, position: Option<&RawPair>) -> Option<RawPair> {
+    match position {
+        Some(position) => {
+            table.entries.range((Bound::Excluded(position), Bound::Unbounded)).next().cloned()
+        }
+        None => table.entries.iter().next().cloned(),
+    }
+}
+
+/// Last pair before `position`. Unpositioned cursors move to the last pair.
+fn prev_pair(table: &MemTable, position: Option<&RawPair>) -> Option<RawPair> {
+    match position {
+        Some(position) => table.entries.range(..position).next_back().cloned(),
+        None => table.entries.iter().next_back().cloned(),
+    }
+}
+
+/// First pair with `key` whose value is greater or equal than `subkey`.
+fn seek_subkey(table: &MemTable, key: &[u8], subkey: &[u8]) -> Option<RawPair> {
+    table
+        .entries
+        .range((key.to_vec(), subkey.to_vec())..)
+        .next()
+        .filter(|(k, _)| k.as_slice() == key)
+        .cloned()
+}
+
+impl<'tx, T: Table> DbCursorRO<'tx, T> for MemCursor<T> {
+    fn first(&mut self) -> PairResult<T> {
+        self.move_and_decode(|table, _| table.entries.iter().next().cloned())
+    }
+
+    fn seek_exact(&mut self, key: T::Key) -> PairResult<T> {
+        let key = key.encode().as_ref().to_vec();
+        // Like MDBX, the cursor is positioned on the next key if there is no exact match.
+        self.move_to(|table, _| seek_pair(table, &key))?
+            .filter(|(found, _)| *found == key)
+            .map(decode_pair::<T>)
+            .transpose()
+    }
+
+    fn seek(&mut self, key: T::Key) -> PairResult<T> {
+        let key = key.encode().as_ref().to_vec();
+        self.move_and_decode(|table, _| seek_pair(table, &key))
+    }
+
+    fn next(&mut self) -> PairResult<T> {
+        self.move_and_decode(next_pair)
+    }
+
+    fn prev(&mut self) -> PairResult<T> {
+        self.move_and_decode(prev_pair)
+    }
+
+    fn last(&mut self) -> PairResult<T> {
+        self.move_and_decode(|table, _| table.entries.iter().next_back().cloned())
+    }
+
+    fn current(&mut self) -> PairResult<T> {
+        self.move_and_decode(|table, position| {
+            position.filter(|position| table.entries.contains(*position)).cloned()
+        })
+    }
+
+    fn walk<'cursor>(
+        &'cursor mut self,
+        start_key: Option<T::Key>,
+    ) -> Result<Walker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match start_key {
+            Some(start_key) => self.seek(start_key),
+            None => self.first(),
+        }
+        .transpose();
+
+        Ok(Walker::new(self, start))
+    }
+
+    fn walk_range<'cursor>(
+        &'cursor mut self,
+        range: impl RangeBounds<T::Key>,
+    ) -> Result<RangeWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match range.start_bound().cloned() {
+            Bound::Included(key) | Bound::Excluded(key) => {
+                if matches!(range.end_bound().cloned(), Bound::Included(end_key) | Bound::Excluded(end_key) if end_key < key) {
+                    return Err(Error::Read(2))
+                }
+
+                let excluded = matches!(range.start_bound(), Bound::Excluded(_));
+                let key = key.encode().as_ref().to_vec();
+                self.move_to(|table, _| {
+                    seek_pair(table, &key).and_then(|found| {
+                        // An excluded start bound skips the entry with exactly that key.
+                        if excluded && found.0 == key {
+                            table
+                                .entries
+                                .range((Bound::Excluded(&found), Bound::Unbounded))
+                                .find(|(k, _)| *k != key)
+                                .cloned()
+                        } else {
+                            Some(found)
+                        }
+                    })
+                })?
+            }
+            Bound::Unbounded => self.move_to(|table, _| table.entries.iter().next().cloned())?,
+        }
+        .map(decode_pair::<T>);
+
+        Ok(RangeWalker::new(self, start, range.end_bound().cloned()))
+    }
+
+    fn walk_back<'cursor>(
+        &'cursor mut self,
+        start_key: Option<T::Key>,
+    ) -> Result<ReverseWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match start_key {
+            Some(start_key) => self.seek(start_key),
+            None => self.last(),
+        }
+        .transpose();
+
+        Ok(ReverseWalker::new(self, start))
+    }
+
+    fn walk_back_range<'cursor>(
+        &'cursor mut self,
+        range: impl RangeBounds<T::Key>,
+    ) -> Result<ReverseRangeWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match range.end_bound().cloned() {
+            Bound::Included(key) | Bound::Excluded(key) => {
+                if matches!(range.start_bound().cloned(), Bound::Included(start_key) | Bound::Excluded(start_key) if key < start_key) {
+                    return Err(Error::Read(2))
+                }
+
+                let included = matches!(range.end_bound(), Bound::Included(_));
+                let key = key.encode().as_ref().to_vec();
+                self.move_to(|table, _| {
+                    let mut below = table.entries.range(..(key.clone(), Vec::new()));
+                    if included {
+                        // Last duplicate of the end key, if it exists.
+                        table
+                            .entries
+                            .range((key.clone(), Vec::new())..)
+                            .take_while(|(k, _)| *k == key)
+                            .last()
+                            .or_else(|| below.next_back())
+                            .cloned()
+                    } else {
+                        below.next_back().cloned()
+                    }
+                })?
+            }
+            Bound::Unbounded => {
+                self.move_to(|table, _| table.entries.iter().next_back().cloned())?
+            }
+        }
+        .map(decode_pair::<T>);
+
+        Ok(ReverseRangeWalker::new(self, start, range.start_bound().cloned()))
+    }
+
+    fn walk_prefix<'cursor, P: Encode>(
+        &'cursor mut self,
+        prefix: P,
+    ) -> Result<PrefixWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let prefix = prefix.encode().as_ref().to_vec();
+        let start = self.move_and_decode(|table, _| seek_pair(table, &prefix)).transpose();
+
+        Ok(PrefixWalker::new(self, start, prefix))
+    }
+}
+
+impl<'tx, T: DupSort> DbDupCursorRO<'tx, T> for MemCursor<T> {
+    fn next_dup(&mut self) -> PairResult<T> {
+        self.move_and_decode(|table, position| {
+            let position = position?;
+            next_pair(table, Some(position)).filter(|(key, _)| *key == position.0)
+        })
+    }
+
+    fn next_no_dup(&mut self) -> PairResult<T> {
+        self.move_and_decode(|table, position| match position {
+            Some(position) => table
+                .entries
+                .range((Bound::Excluded(position), Bound::Unbounded))
+                .find(|(key, _)| *key != position.0)
+                .cloned(),
+            None => table.entries.iter().next().cloned(),
+        })
+    }
+
+    fn next_dup_val(&mut self) -> ValueOnlyResult<T> {
+        Ok(self.next_dup()?.map(|(_, value)| value))
+    }
+
+    fn seek_by_key_subkey(
+        &mut self,
+        key: <T as Table>::Key,
+        subkey: <T as DupSort>::SubKey,
+    ) -> ValueOnlyResult<T> {
+        let key = key.encode().as_ref().to_vec();
+        let subkey = subkey.encode().as_ref().to_vec();
+        self.move_to(|table, _| seek_subkey(table, &key, &subkey))?
+            .map(|(_, value)| decode_one::<T>(Cow::Owned(value)))
+            .transpose()
+    }
+
+    fn walk_dup<'cursor>(
+        &'cursor mut self,
+        key: Option<T::Key>,
+        subkey: Option<T::SubKey>,
+    ) -> Result<DupWalker<'cursor, 'tx, T, Self>, Error> {
+        let subkey = subkey.map(|subkey| subkey.encode().as_ref().to_vec()).unwrap_or_default();
+        let start = match key {
+            Some(key) => {
+                let key = key.encode().as_ref().to_vec();
+                self.move_to(|table, _| seek_subkey(table, &key, &subkey))?
+            }
+            None => self.move_to(|table, _| {
+                let (key, _) = table.entries.iter().next()?;
+                seek_subkey(table, key, &subkey)
+            })?,
+        }
+        .map(decode_pair::<T>);
+
+        Ok(DupWalker::new(self, start))
+    }
+}
+
+impl<'tx, T: Table> DbCursorRW<'tx, T> for MemCursor<T> {
+    fn upsert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        self.ensure_writable(Error::Write)?;
+        let pair = (key.encode().as_ref().to_vec(), compress_value::<T>(value).as_ref().to_vec());
+        self.with_table(|table| table.upsert(pair.0.clone(), pair.1.clone()))?;
+        self.position = Some(pair);
+        Ok(())
+    }
+
+    fn insert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        self.ensure_writable(Error::Write)?;
+        let pair = (key.encode().as_ref().to_vec(), compress_value::<T>(value).as_ref().to_vec());
+        self.with_table(|table| {
+            if table.contains_key(&pair.0) {
+                return Err(Error::Write(KEY_EXIST))
+            }
+            table.entries.insert(pair.clone());
+            Ok(())
+        })??;
+        self.position = Some(pair);
+        Ok(())
+    }
+
+    /// Appends the data to the end of the table. Consequently, the append operation
+    /// will fail if the inserted key is less than the last table key
+    fn append(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        self.ensure_writable(Error::Write)?;
+        let pair = (key.encode().as_ref().to_vec(), compress_value::<T>(value).as_ref().to_vec());
+        self.with_table(|table| {
+            if let Some((last_key, _)) = table.entries.iter().next_back() {
+                // Only `DUPSORT` tables accept another value for the last key.
+                let in_order = if table.dupsort { pair.0 >= *last_key } else { pair.0 > *last_key };
+                if !in_order {
+                    return Err(Error::Write(KEY_MISMATCH))
+                }
+            }
+            table.entries.insert(pair.clone());
+            Ok(())
+        })??;
+        self.position = Some(pair);
+        Ok(())
+    }
+
+    fn delete_current(&mut self) -> Result<(), Error> {
+        self.ensure_writable(Error::Delete)?;
+        // The position is kept, so that `next` returns the entry following the deleted one.
+        let position = self.position.clone();
+        self.with_table(|table| {
+            if let Some(position) = position {
+                table.entries.remove(&position);
+            }
+        })
+    }
+}
+
+impl<'tx, T: DupSort> DbDupCursorRW<'tx, T> for MemCursor<T> {
+    fn delete_current_duplicates(&mut self) -> Result<(), Error> {
+        self.ensure_writable(Error::Delete)?;
+        let position = self.position.clone();
+        self.with_table(|table| {
+            if let Some((key, _)) = position {
+                table.remove_key(&key);
+            }
+        })
+    }
+
+    fn append_dup(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        self.ensure_writable(Error::Write)?;
+        let pair = (key.encode().as_ref().to_vec(), compress_value::<T>(value).as_ref().to_vec());
+        self.with_table(|table| {
+            if table.entries.iter().next_back().map_or(false, |last| pair <= *last) {
+                return Err(Error::Write(KEY_MISMATCH))
+            }
+            table.entries.insert(pair.clone());
+            Ok(())
+        })??;
+        self.position = Some(pair);
+        Ok(())
+    }
+}
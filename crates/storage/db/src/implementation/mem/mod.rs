@@ -0,0 +1,336 @@
+//! In-memory database used for testing.
+//!
+//! Every table is stored as an ordered set of encoded `(key, value)` pairs, which mirrors the
+//! byte-wise ordering used by MDBX. `DUPSORT` tables are emulated by allowing several pairs
+//! with the same key, sorted by their value.
+
+use crate::{
+    database::{Database, DatabaseGAT},
+    tables::{TableType, Tables},
+    Error,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Condvar, Mutex, RwLock},
+};
+
+pub mod cursor;
+
+pub mod tx;
+use tx::MemTx;
+
+/// `MDBX_KEYEXIST` error code, returned when inserting an existing key.
+pub(crate) const KEY_EXIST: i32 = -30799;
+/// `MDBX_NOTFOUND` error code, returned when a table does not exist.
+pub(crate) const NOT_FOUND: i32 = -30798;
+/// `MDBX_EKEYMISMATCH` error code, returned when appending out of order.
+pub(crate) const KEY_MISMATCH: i32 = -30418;
+/// `MDBX_EACCESS` error code, returned when writing through a read-only transaction.
+pub(crate) const ACCESS: i32 = 13;
+
+/// Content of a single in-memory table.
+#[derive(Debug, Clone, Default)]
+pub struct MemTable {
+    /// Whether the table allows duplicate keys.
+    pub dupsort: bool,
+    /// Ordered encoded `(key, value)` pairs.
+    pub entries: BTreeSet<(Vec<u8>, Vec<u8>)>,
+}
+
+/// All the tables of an in-memory database, indexed by name.
+pub type MemTables = BTreeMap<&'static str, MemTable>;
+
+/// In-memory database backed by [`BTreeSet`]s.
+///
+/// Each transaction works on its own copy of the tables. Committing a read-write transaction
+/// replaces the content of the database. Like in MDBX, there is a single writer: opening a
+/// read-write transaction blocks until the previous one is committed or dropped.
+#[derive(Debug, Clone)]
+pub struct MemDb {
+    /// Committed tables.
+    tables: Arc<RwLock<MemTables>>,
+    /// Held by the open read-write transaction.
+    writer: Arc<WriterLock>,
+}
+
+impl Default for MemDb {
+    fn default() -> Self {
+        let tables = Tables::ALL
+            .iter()
+            .map(|table| {
+                let dupsort = table.table_type() == TableType::DupSort;
+                (table.name(), MemTable { dupsort, entries: BTreeSet::new() })
+            })
+            .collect();
+
+        Self { tables: Arc::new(RwLock::new(tables)), writer: Default::default() }
+    }
+}
+
+impl MemDb {
+    /// Creates a new in-memory database with all the default tables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes a snapshot of the committed tables.
+    fn snapshot(&self) -> Result<Arc<Mutex<MemTables>>, Error> {
+        let tables = self.tables.read().map_err(|_| Error::InitTransaction(ACCESS))?;
+        Ok(Arc::new(Mutex::new(tables.clone())))
+    }
+}
+
+impl<'a> DatabaseGAT<'a> for MemDb {
+    type TX = MemTx;
+    type TXMut = MemTx;
+}
+
+impl Database for MemDb {
+    fn tx(&self) -> Result<<Self as DatabaseGAT<'_>>::TX, Error> {
+        Ok(MemTx::new(self.snapshot()?, None))
+    }
+
+    fn tx_mut(&self) -> Result<<Self as DatabaseGAT<'_>>::TXMut, Error> {
+        // The snapshot is taken once the previous writer is done, so it has its changes.
+        let writer = self.writer.acquire()?;
+        Ok(MemTx::new(self.snapshot()?, Some((self.tables.clone(), writer))))
+    }
+}
+
+/// Lock of the single writer of a [`MemDb`].
+#[derive(Debug, Default)]
+pub(crate) struct WriterLock {
+    /// Whether a read-write transaction is open.
+    locked: Mutex<bool>,
+    /// Notified when the read-write transaction is committed or dropped.
+    released: Condvar,
+}
+
+impl WriterLock {
+    /// Blocks until there is no open read-write transaction, and takes the lock.
+    fn acquire(self: &Arc<Self>) -> Result<WriterGuard, Error> {
+        let mut locked = self.locked.lock().map_err(|_| Error::InitTransaction(ACCESS))?;
+        while *locked {
+            locked = self.released.wait(locked).map_err(|_| Error::InitTransaction(ACCESS))?;
+        }
+        *locked = true;
+        Ok(WriterGuard(self.clone()))
+    }
+}
+
+/// Releases the [`WriterLock`] when dropped.
+#[derive(Debug)]
+pub(crate) struct WriterGuard(Arc<WriterLock>);
+
+impl Drop for WriterGuard {
+    fn drop(&mut self) {
+        // The lock must be released even if a writer panicked
+        let mut locked = self.0.locked.lock().unwrap_or_else(|err| err.into_inner());
+        *locked = false;
+        self.0.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemDb;
+    use crate::{
+        cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
+        database::Database,
+        models::AccountBeforeTx,
+        tables::{AccountChangeSet, CanonicalHeaders, Headers, PlainStorageState},
+        transaction::{DbTx, DbTxMut},
+        Error,
+    };
+    use reth_primitives::{Address, Header, StorageEntry, H256, U256};
+    use std::{thread, time::Duration};
+
+    const ERROR_PUT: &str = "Not able to insert value into table.";
+    const ERROR_GET: &str = "Not able to get value from table.";
+    const ERROR_COMMIT: &str = "Not able to commit transaction.";
+    const ERROR_INIT_TX: &str = "Failed to create a transaction.";
+
+    fn db_with_canonical_headers(keys: Vec<u64>) -> MemDb {
+        let db = MemDb::new();
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        keys.into_iter()
+            .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+            .expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+        db
+    }
+
+    #[test]
+    fn mem_single_writer() {
+        let db = MemDb::new();
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        tx.put::<CanonicalHeaders>(1, H256::zero()).expect(ERROR_PUT);
+
+        // The second writer waits for the first one, and sees its changes
+        let writer = thread::spawn({
+            let db = db.clone();
+            move || {
+                let tx = db.tx_mut().expect(ERROR_INIT_TX);
+                let first = tx.get::<CanonicalHeaders>(1).expect(ERROR_GET);
+                tx.put::<CanonicalHeaders>(2, H256::zero()).expect(ERROR_PUT);
+                tx.commit().expect(ERROR_COMMIT);
+                first
+            }
+        });
+        thread::sleep(Duration::from_millis(50));
+        assert!(!writer.is_finished());
+
+        tx.commit().expect(ERROR_COMMIT);
+        assert_eq!(writer.join().unwrap(), Some(H256::zero()));
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get::<CanonicalHeaders>(1).expect(ERROR_GET), Some(H256::zero()));
+        assert_eq!(tx.get::<CanonicalHeaders>(2).expect(ERROR_GET), Some(H256::zero()));
+    }
+
+    #[test]
+    fn mem_put_get() {
+        let db = MemDb::new();
+
+        let value = Header::default();
+        let key = 1u64;
+
+        // Uncommitted writes are discarded
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        tx.put::<Headers>(key, value.clone()).expect(ERROR_PUT);
+        drop(tx);
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get::<Headers>(key).expect(ERROR_GET), None);
+
+        // PUT
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        tx.put::<Headers>(key, value.clone()).expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        // GET
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get::<Headers>(key).expect(ERROR_GET), Some(value));
+
+        // Read only transactions can not write
+        assert_eq!(tx.put::<Headers>(2, Header::default()), Err(Error::Write(super::ACCESS)));
+    }
+
+    #[test]
+    fn mem_cursor_walk() {
+        let db = db_with_canonical_headers(vec![0, 1, 3]);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+
+        let walker = cursor.walk(Some(1)).unwrap();
+        assert_eq!(walker.map(|res| res.unwrap().0).collect::<Vec<_>>(), vec![1, 3]);
+
+        let walker = cursor.walk_range(1..3).unwrap();
+        assert_eq!(walker.map(|res| res.unwrap().0).collect::<Vec<_>>(), vec![1]);
+
+        let walker = cursor.walk_back(None).unwrap();
+        assert_eq!(walker.map(|res| res.unwrap().0).collect::<Vec<_>>(), vec![3, 1, 0]);
+
+        let walker = cursor.walk_back_range(1..).unwrap();
+        assert_eq!(walker.map(|res| res.unwrap().0).collect::<Vec<_>>(), vec![3, 1]);
+
+        // Seek exact positions the cursor on the next key
+        assert_eq!(cursor.seek_exact(2), Ok(None));
+        assert_eq!(cursor.current(), Ok(Some((3, H256::zero()))));
+        assert_eq!(cursor.prev(), Ok(Some((1, H256::zero()))));
+    }
+
+    #[test]
+    fn mem_cursor_write() {
+        let db = db_with_canonical_headers(vec![0, 1, 3]);
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_write::<CanonicalHeaders>().unwrap();
+
+        assert_eq!(cursor.insert(2, H256::zero()), Ok(()));
+        assert_eq!(cursor.insert(2, H256::zero()), Err(Error::Write(super::KEY_EXIST)));
+        assert_eq!(cursor.append(1, H256::zero()), Err(Error::Write(super::KEY_MISMATCH)));
+        assert_eq!(cursor.append(4, H256::zero()), Ok(()));
+
+        cursor.seek_exact(0).unwrap();
+        cursor.delete_current().unwrap();
+        assert_eq!(cursor.next(), Ok(Some((1, H256::zero()))));
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+        let res = cursor.walk(None).unwrap().map(|res| res.unwrap().0).collect::<Vec<_>>();
+        assert_eq!(res, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn mem_dup_sort() {
+        let db = MemDb::new();
+        let key1 = Address::from_low_u64_be(1);
+        let key2 = Address::from_low_u64_be(2);
+
+        let value00 = StorageEntry::default();
+        let value11 = StorageEntry { key: H256::from_low_u64_be(1), value: U256::from(1) };
+        let value22 = StorageEntry { key: H256::from_low_u64_be(2), value: U256::from(2) };
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        tx.put::<PlainStorageState>(key1, value11.clone()).expect(ERROR_PUT);
+        tx.put::<PlainStorageState>(key1, value00.clone()).expect(ERROR_PUT);
+        tx.put::<PlainStorageState>(key2, value22.clone()).expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+
+        // Duplicates are sorted and the walker stays on the same key
+        let mut walker = cursor.walk_dup(Some(key1), None).unwrap();
+        assert_eq!(walker.next(), Some(Ok((key1, value00.clone()))));
+        assert_eq!(walker.next(), Some(Ok((key1, value11.clone()))));
+        assert_eq!(walker.next(), None);
+
+        assert_eq!(cursor.seek_by_key_subkey(key1, value11.key), Ok(Some(value11.clone())));
+        assert_eq!(cursor.seek_by_key_subkey(key1, value22.key), Ok(None));
+
+        cursor.first().unwrap();
+        assert_eq!(cursor.next_no_dup(), Ok(Some((key2, value22))));
+
+        // Deleting all duplicates of a key
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup_write::<PlainStorageState>().unwrap();
+        cursor.seek_exact(key1).unwrap();
+        cursor.delete_current_duplicates().unwrap();
+        assert_eq!(cursor.first().unwrap().map(|(key, _)| key), Some(key2));
+    }
+
+    #[test]
+    fn mem_dup_sort_append() {
+        let db = MemDb::new();
+        let transition_id = 2;
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_dup_write::<AccountChangeSet>().unwrap();
+        for val in [0, 1, 3] {
+            cursor
+                .append_dup(
+                    transition_id,
+                    AccountBeforeTx { address: Address::from_low_u64_be(val), info: None },
+                )
+                .unwrap();
+        }
+
+        assert_eq!(
+            cursor.append_dup(
+                transition_id,
+                AccountBeforeTx { address: Address::from_low_u64_be(2), info: None }
+            ),
+            Err(Error::Write(super::KEY_MISMATCH))
+        );
+        assert_eq!(
+            cursor.append(
+                transition_id,
+                AccountBeforeTx { address: Address::from_low_u64_be(2), info: None }
+            ),
+            Ok(())
+        );
+    }
+}
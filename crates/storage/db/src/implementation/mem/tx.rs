@@ -0,0 +1,177 @@
+//! Transaction of the in-memory database.
+
+use super::{cursor::MemCursor, MemTable, MemTables, WriterGuard, ACCESS, NOT_FOUND};
+use crate::{
+    table::{DupSort, Encode, Table, TableImporter},
+    tables::{codecs::compress_value, utils::decode_one},
+    transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT},
+    Error,
+};
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex, MutexGuard, RwLock},
+};
+
+/// Transaction over a snapshot of the [`MemDb`](super::MemDb) tables.
+#[derive(Debug)]
+pub struct MemTx {
+    /// Tables as seen by this transaction.
+    tables: Arc<Mutex<MemTables>>,
+    /// Committed tables of the database. Only set for read-write transactions.
+    committed: Option<Arc<RwLock<MemTables>>>,
+    /// Lock of the single writer, released when the transaction is committed or dropped. Only
+    /// set for read-write transactions.
+    _writer: Option<WriterGuard>,
+}
+
+impl MemTx {
+    /// Creates a new transaction. It is read-write if the committed tables and the writer lock
+    /// are set.
+    pub(crate) fn new(
+        tables: Arc<Mutex<MemTables>>,
+        writer: Option<(Arc<RwLock<MemTables>>, WriterGuard)>,
+    ) -> Self {
+        let (committed, _writer) =
+            writer.map_or((None, None), |(tables, writer)| (Some(tables), Some(writer)));
+        Self { tables, committed, _writer }
+    }
+
+    /// Create db Cursor
+    pub fn new_cursor<T: Table>(&self) -> Result<MemCursor<T>, Error> {
+        // Make sure the table exists, as opening a cursor in MDBX would do.
+        with_table::<T, _, _>(&self.tables, |_| ()).map_err(|_| Error::InitCursor(NOT_FOUND))?;
+        Ok(MemCursor::new(self.tables.clone(), self.committed.is_some()))
+    }
+
+    /// Returns an error if this is a read-only transaction.
+    fn ensure_writable(&self, err: fn(i32) -> Error) -> Result<(), Error> {
+        if self.committed.is_some() {
+            Ok(())
+        } else {
+            Err(err(ACCESS))
+        }
+    }
+}
+
+/// Locks the tables and runs `f` over the table `T`.
+pub(crate) fn with_table<T: Table, F, R>(tables: &Mutex<MemTables>, f: F) -> Result<R, Error>
+where
+    F: FnOnce(&mut MemTable) -> R,
+{
+    let mut tables: MutexGuard<'_, MemTables> = tables.lock().map_err(|_| Error::Read(ACCESS))?;
+    let table = tables.get_mut(T::NAME).ok_or(Error::Read(NOT_FOUND))?;
+    Ok(f(table))
+}
+
+impl<'a> DbTxGAT<'a> for MemTx {
+    type Cursor<T: Table> = MemCursor<T>;
+    type DupCursor<T: DupSort> = MemCursor<T>;
+}
+
+impl<'a> DbTxMutGAT<'a> for MemTx {
+    type CursorMut<T: Table> = MemCursor<T>;
+    type DupCursorMut<T: DupSort> = MemCursor<T>;
+}
+
+impl<'a> TableImporter<'a> for MemTx {}
+
+impl<'tx> DbTx<'tx> for MemTx {
+    fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>, Error> {
+        let key = key.encode().as_ref().to_vec();
+        with_table::<T, _, _>(&self.tables, |table| {
+            table
+                .entries
+                .range((key.clone(), Vec::new())..)
+                .next()
+                .filter(|(k, _)| *k == key)
+                .map(|(_, v)| v.clone())
+        })?
+        .map(|value| decode_one::<T>(Cow::Owned(value)))
+        .transpose()
+    }
+
+    fn commit(self) -> Result<bool, Error> {
+        if let Some(committed) = self.committed {
+            let tables =
+                std::mem::take(&mut *self.tables.lock().map_err(|_| Error::Commit(ACCESS))?);
+            *committed.write().map_err(|_| Error::Commit(ACCESS))? = tables;
+        }
+        Ok(false)
+    }
+
+    fn drop(self) {
+        drop(self)
+    }
+
+    fn cursor_read<T: Table>(&self) -> Result<<Self as DbTxGAT<'_>>::Cursor<T>, Error> {
+        self.new_cursor()
+    }
+
+    fn cursor_dup_read<T: DupSort>(&self) -> Result<<Self as DbTxGAT<'_>>::DupCursor<T>, Error> {
+        self.new_cursor()
+    }
+}
+
+impl<'tx> DbTxMut<'tx> for MemTx {
+    fn put<T: Table>(&self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        self.ensure_writable(Error::Write)?;
+        let key = key.encode().as_ref().to_vec();
+        let value = compress_value::<T>(value).as_ref().to_vec();
+        with_table::<T, _, _>(&self.tables, |table| table.upsert(key, value))
+    }
+
+    fn delete<T: Table>(&self, key: T::Key, value: Option<T::Value>) -> Result<bool, Error> {
+        self.ensure_writable(Error::Delete)?;
+        let key = key.encode().as_ref().to_vec();
+        let value = value.map(|value| compress_value::<T>(value).as_ref().to_vec());
+        with_table::<T, _, _>(&self.tables, |table| match value {
+            // Like MDBX, the value is only taken into account for `DUPSORT` tables.
+            Some(value) if table.dupsort => table.entries.remove(&(key, value)),
+            _ => table.remove_key(&key),
+        })
+    }
+
+    fn clear<T: Table>(&self) -> Result<(), Error> {
+        self.ensure_writable(Error::Delete)?;
+        with_table::<T, _, _>(&self.tables, |table| table.entries.clear())
+    }
+
+    fn cursor_write<T: Table>(&self) -> Result<<Self as DbTxMutGAT<'_>>::CursorMut<T>, Error> {
+        self.new_cursor()
+    }
+
+    fn cursor_dup_write<T: DupSort>(
+        &self,
+    ) -> Result<<Self as DbTxMutGAT<'_>>::DupCursorMut<T>, Error> {
+        self.new_cursor()
+    }
+}
+
+impl MemTable {
+    /// Inserts the pair, replacing the value of the key for non `DUPSORT` tables.
+    pub(crate) fn upsert(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        if !self.dupsort {
+            self.remove_key(&key);
+        }
+        self.entries.insert((key, value));
+    }
+
+    /// Removes every entry with the given key. Returns `true` if anything was removed.
+    pub(crate) fn remove_key(&mut self, key: &[u8]) -> bool {
+        let removed = self
+            .entries
+            .range((key.to_vec(), Vec::new())..)
+            .take_while(|(k, _)| k == key)
+            .cloned()
+            .collect::<Vec<_>>();
+        for entry in &removed {
+            self.entries.remove(entry);
+        }
+        !removed.is_empty()
+    }
+
+    /// Returns `true` if there is any entry with the given key.
+    pub(crate) fn contains_key(&self, key: &[u8]) -> bool {
+        self.entries.range((key.to_vec(), Vec::new())..).next().map_or(false, |(k, _)| k == key)
+    }
+}
@@ -1,2 +1,5 @@
 #[cfg(feature = "mdbx")]
 pub(crate) mod mdbx;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub(crate) mod mem;
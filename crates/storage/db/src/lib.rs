@@ -13,6 +13,7 @@ pub mod abstraction;
 mod implementation;
 pub mod tables;
 mod utils;
+pub mod verify;
 
 #[cfg(feature = "mdbx")]
 /// Bindings for [MDBX](https://libmdbx.dqdkfa.ru/).
@@ -21,6 +22,17 @@ pub mod mdbx {
     pub use reth_libmdbx::*;
 }
 
+#[cfg(any(test, feature = "test-utils"))]
+/// In-memory database, useful to test code generic over [`Database`](crate::database::Database)
+/// without touching the disk.
+pub mod mem {
+    pub use crate::implementation::mem::*;
+}
+
+// Lets the code generated by `TableKey` refer to this crate as `reth_db`, inside of it too.
+extern crate self as reth_db;
+
 pub use abstraction::*;
+pub use reth_codecs::TableKey;
 pub use reth_interfaces::db::Error;
 pub use tables::*;
@@ -0,0 +1,128 @@
+//! Compression codecs applied to the values of a table, on top of [`Compress`].
+
+use crate::{
+    table::{Compress, Decompress, Table},
+    Error,
+};
+use reth_primitives::bytes::Bytes;
+use std::{borrow::Cow, io::Read};
+
+/// Compression level used by the zstd codecs.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Codec applied to the values of a table after they are compressed with [`Compress`], and
+/// removed before they are decompressed with [`Decompress`]. See [`Table::COMPRESSION`].
+///
+/// `DUPSORT` tables must not use a codec, since their values are sorted and searched by their
+/// subkey prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Values are stored as they are.
+    None,
+    /// Values are compressed with zstd.
+    Zstd,
+    /// Values are compressed with zstd, using a dictionary shared by all the values of the
+    /// table. Changing the dictionary of a table requires a migration.
+    ZstdWithDictionary(&'static [u8]),
+}
+
+impl Compression {
+    /// Compresses raw value bytes.
+    pub fn compress(&self, value: &[u8]) -> Vec<u8> {
+        let compressed = match self {
+            Compression::None => return value.to_vec(),
+            Compression::Zstd => zstd::bulk::compress(value, ZSTD_LEVEL),
+            Compression::ZstdWithDictionary(dictionary) => {
+                zstd::bulk::Compressor::with_dictionary(ZSTD_LEVEL, dictionary)
+                    .and_then(|mut compressor| compressor.compress(value))
+            }
+        };
+        // Compressing into a growable buffer only fails on invalid parameters.
+        compressed.expect("valid zstd parameters")
+    }
+
+    /// Decompresses value bytes written by [`Compression::compress`].
+    pub fn decompress<'a>(&self, value: Cow<'a, [u8]>) -> Result<Cow<'a, [u8]>, Error> {
+        let mut decompressed = Vec::new();
+        let res = match self {
+            Compression::None => return Ok(value),
+            Compression::Zstd => zstd::stream::Decoder::new(value.as_ref())
+                .and_then(|mut decoder| decoder.read_to_end(&mut decompressed)),
+            Compression::ZstdWithDictionary(dictionary) => {
+                zstd::stream::Decoder::with_dictionary(value.as_ref(), dictionary)
+                    .and_then(|mut decoder| decoder.read_to_end(&mut decompressed))
+            }
+        };
+        res.map_err(|_| Error::DecodeError)?;
+        Ok(Cow::Owned(decompressed))
+    }
+}
+
+/// Value as it is stored in a table.
+#[derive(Debug)]
+pub(crate) enum StoredValue<C> {
+    /// Value of a table without codec.
+    Plain(C),
+    /// Value compressed with the codec of the table.
+    Compressed(Vec<u8>),
+}
+
+impl<C: AsRef<[u8]>> AsRef<[u8]> for StoredValue<C> {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            StoredValue::Plain(value) => value.as_ref(),
+            StoredValue::Compressed(value) => value,
+        }
+    }
+}
+
+/// Compresses a value of the table `T`, applying its [`Table::COMPRESSION`].
+pub(crate) fn compress_value<T: Table>(
+    value: T::Value,
+) -> StoredValue<<T::Value as Compress>::Compressed> {
+    let value = value.compress();
+    match T::COMPRESSION {
+        Compression::None => StoredValue::Plain(value),
+        compression => StoredValue::Compressed(compression.compress(value.as_ref())),
+    }
+}
+
+/// Decompresses a value of the table `T`, removing its [`Table::COMPRESSION`].
+pub(crate) fn decompress_value<T: Table>(value: Cow<'_, [u8]>) -> Result<T::Value, Error> {
+    let value = T::COMPRESSION.decompress(value)?;
+    Decompress::decompress(Bytes::from(value.into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::Headers;
+    use reth_primitives::Header;
+
+    #[test]
+    fn compression_roundtrip() {
+        let value = [7u8; 256];
+        for compression in
+            [Compression::None, Compression::Zstd, Compression::ZstdWithDictionary(&[7u8; 64])]
+        {
+            let compressed = compression.compress(&value);
+            if compression != Compression::None {
+                assert!(compressed.len() < value.len());
+            }
+            assert_eq!(compression.decompress(Cow::Owned(compressed)).unwrap().as_ref(), value);
+        }
+
+        assert_eq!(
+            Compression::Zstd.decompress(Cow::Borrowed(&value[..])),
+            Err(Error::DecodeError)
+        );
+    }
+
+    #[test]
+    fn compress_table_value() {
+        let header = Header { number: 1, gas_limit: 30_000_000, ..Default::default() };
+        let stored = compress_value::<Headers>(header.clone());
+        assert!(matches!(stored, StoredValue::Compressed(_)));
+        assert_eq!(decompress_value::<Headers>(Cow::Borrowed(stored.as_ref())), Ok(header));
+    }
+}
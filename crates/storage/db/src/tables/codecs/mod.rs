@@ -3,6 +3,10 @@
 mod compact;
 pub use compact::CompactU256;
 
+mod compression;
+pub use compression::Compression;
+pub(crate) use compression::{compress_value, decompress_value};
+
 pub mod fuzz;
 
 mod postcard;
@@ -2,13 +2,16 @@
 
 pub mod codecs;
 pub mod models;
+mod raw;
 pub mod utils;
 
+pub use raw::{RawKey, RawTable, RawValue};
+
 /// Declaration of all Database tables.
 use crate::{
-    table::DupSort,
+    table::{DupSort, Table},
     tables::{
-        codecs::CompactU256,
+        codecs::{CompactU256, Compression},
         models::{
             accounts::{AccountBeforeTx, TransitionIdAddress},
             blocks::{HeaderHash, StoredBlockOmmers},
@@ -23,7 +26,7 @@ use reth_primitives::{
 };
 
 /// Enum for the types of tables present in libmdbx.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TableType {
     /// key value table
     Table,
@@ -31,78 +34,119 @@ pub enum TableType {
     DupSort,
 }
 
-/// Default tables that should be present inside database.
-pub const TABLES: [(TableType, &str); 27] = [
-    (TableType::Table, CanonicalHeaders::const_name()),
-    (TableType::Table, HeaderTD::const_name()),
-    (TableType::Table, HeaderNumbers::const_name()),
-    (TableType::Table, Headers::const_name()),
-    (TableType::Table, BlockBodies::const_name()),
-    (TableType::Table, BlockOmmers::const_name()),
-    (TableType::Table, BlockWithdrawals::const_name()),
-    (TableType::Table, Transactions::const_name()),
-    (TableType::Table, TxHashNumber::const_name()),
-    (TableType::Table, Receipts::const_name()),
-    (TableType::Table, Logs::const_name()),
-    (TableType::Table, PlainAccountState::const_name()),
-    (TableType::DupSort, PlainStorageState::const_name()),
-    (TableType::Table, Bytecodes::const_name()),
-    (TableType::Table, BlockTransitionIndex::const_name()),
-    (TableType::Table, TxTransitionIndex::const_name()),
-    (TableType::Table, AccountHistory::const_name()),
-    (TableType::Table, StorageHistory::const_name()),
-    (TableType::DupSort, AccountChangeSet::const_name()),
-    (TableType::DupSort, StorageChangeSet::const_name()),
-    (TableType::Table, HashedAccount::const_name()),
-    (TableType::DupSort, HashedStorage::const_name()),
-    (TableType::Table, AccountsTrie::const_name()),
-    (TableType::DupSort, StoragesTrie::const_name()),
-    (TableType::Table, TxSenders::const_name()),
-    (TableType::Table, Config::const_name()),
-    (TableType::Table, SyncStage::const_name()),
-];
-
-#[macro_export]
-/// Macro to declare all necessary tables.
-macro_rules! table {
-    ($(#[$docs:meta])+ ( $table_name:ident ) $key:ty | $value:ty) => {
-        $(#[$docs])+
-        ///
-        #[doc = concat!("Takes [`", stringify!($key), "`] as a key and returns [`", stringify!($value), "`]")]
-        #[derive(Clone, Copy, Debug, Default)]
-        pub struct $table_name;
-
-        impl $crate::table::Table for $table_name {
-            const NAME: &'static str = $table_name::const_name();
-            type Key = $key;
-            type Value = $value;
+/// Visitor over the type of a table that is only known at runtime, through [`Tables::view`].
+pub trait TableViewer<R> {
+    /// Error returned by the viewer.
+    type Error;
+
+    /// Called with the type of the table.
+    fn view<T: Table>(&self) -> Result<R, Self::Error>;
+
+    /// Called instead of [`TableViewer::view`] with the type of `DUPSORT` tables.
+    fn view_dupsort<T: DupSort>(&self) -> Result<R, Self::Error> {
+        self.view::<T>()
+    }
+}
+
+/// Declares all the tables of the database.
+///
+/// For each table, this generates a struct implementing [`Table`], and [`DupSort`] if it has a
+/// `SubKey`, as well as the matching variant of [`Tables`].
+macro_rules! tables {
+    (@type) => { TableType::Table };
+    (@type $subkey:ty) => { TableType::DupSort };
+    (@view $viewer:ident $table_name:ident) => { $viewer.view::<$table_name>() };
+    (@view $viewer:ident $table_name:ident $subkey:ty) => { $viewer.view_dupsort::<$table_name>() };
+    ($(
+        $(#[$docs:meta])+
+        table $table_name:ident<
+            Key = $key:ty,
+            Value = $value:ty
+            $(, SubKey = $subkey:ty)?
+            $(, Compression = $compression:path)?
+        >;
+    )*) => {
+        $(
+            $(#[$docs])+
+            ///
+            #[doc = concat!("Takes [`", stringify!($key), "`] as a key and returns [`", stringify!($value), "`]")]
+            $(
+                ///
+                #[doc = concat!("`DUPSORT` table with subkey being: [`", stringify!($subkey), "`].")]
+            )?
+            #[derive(Clone, Copy, Debug, Default)]
+            pub struct $table_name;
+
+            impl Table for $table_name {
+                const NAME: &'static str = Tables::$table_name.name();
+                type Key = $key;
+                type Value = $value;
+                $(const COMPRESSION: Compression = $compression;)?
+            }
+
+            $(
+                impl DupSort for $table_name {
+                    type SubKey = $subkey;
+                }
+            )?
+
+            impl std::fmt::Display for $table_name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "{}", Self::NAME)
+                }
+            }
+        )*
+
+        /// All the tables of the database.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum Tables {
+            $(
+                #[doc = concat!("The [`", stringify!($table_name), "`] table.")]
+                $table_name,
+            )*
         }
 
-        impl $table_name {
-            #[doc=concat!("Return ", stringify!($table_name), " as it is present inside the database.")]
-            pub const fn const_name() -> &'static str {
-                stringify!($table_name)
+        impl Tables {
+            /// All the tables, in the order they are declared.
+            pub const ALL: &'static [Tables] = &[$(Tables::$table_name,)*];
+
+            /// Returns the name of the table inside the database.
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(Tables::$table_name => stringify!($table_name),)*
+                }
+            }
+
+            /// Returns the type of the table.
+            pub const fn table_type(&self) -> TableType {
+                match self {
+                    $(Tables::$table_name => tables!(@type $($subkey)?),)*
+                }
+            }
+
+            /// Calls `viewer` with the type of the table.
+            pub fn view<V: TableViewer<R>, R>(&self, viewer: &V) -> Result<R, V::Error> {
+                match self {
+                    $(Tables::$table_name => tables!(@view viewer $table_name $($subkey)?),)*
+                }
             }
         }
 
-        impl std::fmt::Display for $table_name {
+        impl std::fmt::Display for Tables {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, "{}", stringify!($table_name))
+                write!(f, "{}", self.name())
             }
         }
-    };
-}
 
-macro_rules! dupsort {
-    ($(#[$docs:meta])+ ( $table_name:ident ) $key:ty | [$subkey:ty] $value:ty) => {
-        table!(
-            $(#[$docs])+
-            ///
-            #[doc = concat!("`DUPSORT` table with subkey being: [`", stringify!($subkey), "`].")]
-            ( $table_name ) $key | $value
-        );
-        impl DupSort for $table_name {
-            type SubKey = $subkey;
+        impl std::str::FromStr for Tables {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $(stringify!($table_name) => Ok(Tables::$table_name),)*
+                    _ => Err(format!("unknown table `{s}`")),
+                }
+            }
         }
     };
 }
@@ -111,94 +155,62 @@ macro_rules! dupsort {
 //  TABLE DEFINITIONS
 //
 
-table!(
+tables! {
     /// Stores the header hashes belonging to the canonical chain.
-    ( CanonicalHeaders ) BlockNumber | HeaderHash
-);
+    table CanonicalHeaders<Key = BlockNumber, Value = HeaderHash>;
 
-table!(
     /// Stores the total difficulty from a block header.
-    ( HeaderTD ) BlockNumber | CompactU256
-);
+    table HeaderTD<Key = BlockNumber, Value = CompactU256>;
 
-table!(
     /// Stores the block number corresponding to an header.
-    ( HeaderNumbers ) BlockHash | BlockNumber
-);
+    table HeaderNumbers<Key = BlockHash, Value = BlockNumber>;
 
-table!(
-    /// Stores header bodies.
-    ( Headers ) BlockNumber | Header
-);
+    /// Stores header bodies, compressed with zstd.
+    table Headers<Key = BlockNumber, Value = Header, Compression = Compression::Zstd>;
 
-table!(
     /// Stores block bodies.
-    ( BlockBodies ) BlockNumber | StoredBlockBody
-);
+    table BlockBodies<Key = BlockNumber, Value = StoredBlockBody>;
 
-table!(
     /// Stores the uncles/ommers of the block.
-    ( BlockOmmers ) BlockNumber | StoredBlockOmmers
-);
+    table BlockOmmers<Key = BlockNumber, Value = StoredBlockOmmers>;
 
-table!(
     /// Stores the block withdrawals.
-    ( BlockWithdrawals ) BlockNumber | StoredBlockWithdrawals
-);
+    table BlockWithdrawals<Key = BlockNumber, Value = StoredBlockWithdrawals>;
 
-table!(
     /// (Canonical only) Stores the transaction body for canonical transactions.
-    ( Transactions ) TxNumber | TransactionSigned
-);
+    table Transactions<Key = TxNumber, Value = TransactionSigned>;
 
-table!(
     /// Stores the mapping of the transaction hash to the transaction number.
-    ( TxHashNumber ) TxHash | TxNumber
-);
+    table TxHashNumber<Key = TxHash, Value = TxNumber>;
 
-table!(
-    /// (Canonical only) Stores transaction receipts.
-    ( Receipts ) TxNumber | Receipt
-);
+    /// (Canonical only) Stores transaction receipts, compressed with zstd.
+    table Receipts<Key = TxNumber, Value = Receipt, Compression = Compression::Zstd>;
 
-table!(
     /// (Canonical only) Stores transaction logs.
-    ( Logs ) TxNumber | Receipt
-);
+    table Logs<Key = TxNumber, Value = Receipt>;
 
-table!(
     /// Stores all smart contract bytecodes.
     /// There will be multiple accounts that have same bytecode
     /// So we would need to introduce reference counter.
     /// This will be small optimization on state.
-    ( Bytecodes ) H256 | Bytecode
-);
+    table Bytecodes<Key = H256, Value = Bytecode>;
 
-table!(
     /// Stores the mapping of block number to state transition id.
     /// The block transition marks the final state at the end of the block.
     /// Increment the transition if the block contains an addition block reward.
     /// If the block does not have a reward and transaction, the transition will be the same as the
     /// transition at the last transaction of this block.
-    ( BlockTransitionIndex ) BlockNumber | TransitionId
-);
+    table BlockTransitionIndex<Key = BlockNumber, Value = TransitionId>;
 
-table!(
     /// Stores the mapping of transaction number to state transition id.
-    ( TxTransitionIndex ) TxNumber | TransitionId
-);
+    table TxTransitionIndex<Key = TxNumber, Value = TransitionId>;
 
-table!(
     /// Stores the current state of an [`Account`].
-    ( PlainAccountState ) Address | Account
-);
+    table PlainAccountState<Key = Address, Value = Account>;
 
-dupsort!(
     /// Stores the current value of a storage key.
-    ( PlainStorageState ) Address | [H256] StorageEntry
-);
+    table PlainStorageState<Key = Address, Value = StorageEntry, SubKey = H256>;
 
-table!(
     /// Stores pointers to transition changeset with changes for each account key.
     ///
     /// Last shard key of the storage will contains `u64::MAX` `TransitionId`,
@@ -217,10 +229,8 @@ table!(
     /// * If there were no shard we would get `None` entry or entry of different storage key.
     ///
     /// Code example can be found in `reth_provider::HistoricalStateProviderRef`
-    ( AccountHistory ) ShardedKey<Address> | TransitionList
-);
+    table AccountHistory<Key = ShardedKey<Address>, Value = TransitionList>;
 
-table!(
     /// Stores pointers to transition changeset with changes for each storage key.
     ///
     /// Last shard key of the storage will contains `u64::MAX` `TransitionId`,
@@ -239,65 +249,53 @@ table!(
     /// * If there were no shard we would get `None` entry or entry of different storage key.
     ///
     /// Code example can be found in `reth_provider::HistoricalStateProviderRef`
-    ( StorageHistory ) StorageShardedKey | TransitionList
-);
+    table StorageHistory<Key = StorageShardedKey, Value = TransitionList>;
 
-dupsort!(
     /// Stores the state of an account before a certain transaction changed it.
     /// Change on state can be: account is created, selfdestructed, touched while empty
     /// or changed (balance,nonce).
-    ( AccountChangeSet ) TransitionId | [Address] AccountBeforeTx
-);
+    table AccountChangeSet<Key = TransitionId, Value = AccountBeforeTx, SubKey = Address>;
 
-dupsort!(
     /// Stores the state of a storage key before a certain transaction changed it.
     /// If [`StorageEntry::value`] is zero, this means storage was not existing
     /// and needs to be removed.
-    ( StorageChangeSet ) TransitionIdAddress | [H256] StorageEntry
-);
+    table StorageChangeSet<Key = TransitionIdAddress, Value = StorageEntry, SubKey = H256>;
 
-table!(
     /// Stores the current state of an [`Account`] indexed with `keccak256(Address)`
     /// This table is in preparation for merkelization and calculation of state root.
     /// We are saving whole account data as it is needed for partial update when
     /// part of storage is changed. Benefit for merkelization is that hashed addresses are sorted.
-    ( HashedAccount ) H256 | Account
-);
+    table HashedAccount<Key = H256, Value = Account>;
 
-dupsort!(
     /// Stores the current storage values indexed with `keccak256(Address)` and
     /// hash of storage key `keccak256(key)`.
     /// This table is in preparation for merkelization and calculation of state root.
     /// Benefit for merklization is that hashed addresses/keys are sorted.
-    ( HashedStorage ) H256 | [H256] StorageEntry
-);
+    table HashedStorage<Key = H256, Value = StorageEntry, SubKey = H256>;
 
-table!(
     /// Stores the current state's Merkle Patricia Tree.
-    ( AccountsTrie ) H256 | Vec<u8>
-);
+    table AccountsTrie<Key = H256, Value = Vec<u8>>;
 
-dupsort!(
     /// Stores the Merkle Patricia Trees of each [`Account`]'s storage.
-    ( StoragesTrie ) H256 | [H256] StorageTrieEntry
-);
+    table StoragesTrie<Key = H256, Value = StorageTrieEntry, SubKey = H256>;
 
-table!(
     /// Stores the transaction sender for each transaction.
     /// It is needed to speed up execution stage and allows fetching signer without doing
     /// transaction signed recovery
-    ( TxSenders ) TxNumber | Address
-);
+    table TxSenders<Key = TxNumber, Value = Address>;
 
-table!(
     /// Configuration values.
-    ( Config ) ConfigKey | ConfigValue
-);
+    table Config<Key = ConfigKey, Value = ConfigValue>;
 
-table!(
     /// Stores the highest synced block number of each stage.
-    ( SyncStage ) StageId | BlockNumber
-);
+    table SyncStage<Key = StageId, Value = BlockNumber>;
+
+    /// Stores the highest pruned block number of each pruned part of the database.
+    table PruneCheckpoints<Key = PrunePartId, Value = BlockNumber>;
+
+    /// Stores the schema version of the database, under [`SCHEMA_VERSION_KEY`].
+    table DatabaseVersion<Key = DatabaseVersionKey, Value = u64>;
+}
 
 ///
 /// Alias Types
@@ -306,6 +304,13 @@ table!(
 pub type TransitionList = IntegerList;
 /// Encoded stage id.
 pub type StageId = Vec<u8>;
+/// Encoded prune part id.
+pub type PrunePartId = Vec<u8>;
+/// Key of the [`DatabaseVersion`] table.
+pub type DatabaseVersionKey = Vec<u8>;
+
+/// Key under which the schema version is stored in the [`DatabaseVersion`] table.
+pub const SCHEMA_VERSION_KEY: &[u8] = b"schema";
 
 //
 // TODO: Temporary types, until they're properly defined alongside with the Encode and Decode Trait
@@ -317,3 +322,32 @@ pub type ConfigKey = Vec<u8>;
 pub type ConfigValue = Vec<u8>;
 /// Temporary placeholder type for DB.
 pub type Bytecode = Vec<u8>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TableTypeViewer;
+
+    impl TableViewer<TableType> for TableTypeViewer {
+        type Error = ();
+
+        fn view<T: Table>(&self) -> Result<TableType, Self::Error> {
+            Ok(TableType::Table)
+        }
+
+        fn view_dupsort<T: DupSort>(&self) -> Result<TableType, Self::Error> {
+            Ok(TableType::DupSort)
+        }
+    }
+
+    #[test]
+    fn tables_by_name() {
+        for table in Tables::ALL {
+            assert_eq!(table.name().parse::<Tables>(), Ok(*table));
+            assert_eq!(table.view(&TableTypeViewer), Ok(table.table_type()));
+        }
+        assert_eq!(Tables::PlainStorageState.name(), PlainStorageState::NAME);
+        assert!("Unknown".parse::<Tables>().is_err());
+    }
+}
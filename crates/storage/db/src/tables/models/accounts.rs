@@ -1,15 +1,8 @@
 //! Account related models and types.
 
-use crate::{
-    impl_fixed_arbitrary,
-    table::{Decode, Encode},
-    Error,
-};
+use crate::{impl_num_hash_arbitrary, TableKey};
 use reth_codecs::Compact;
-use reth_primitives::{
-    bytes::{BufMut, Bytes},
-    Account, Address, TransitionId,
-};
+use reth_primitives::{bytes::BufMut, Account, Address, TransitionId};
 use serde::{Deserialize, Serialize};
 
 /// Account as it is saved inside [`AccountChangeSet`][crate::tables::AccountChangeSet].
@@ -46,8 +39,10 @@ impl Compact for AccountBeforeTx {
 /// [`TransitionId`] concatenated with [`Address`]. Used as the key for
 /// [`StorageChangeSet`](crate::tables::StorageChangeSet)
 ///
-/// Since it's used as a key, it isn't compressed when encoding it.
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Ord, PartialOrd)]
+/// Since it's used as a key, it is encoded with [`TableKey`], which keeps the ordering.
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Ord, PartialOrd, TableKey,
+)]
 pub struct TransitionIdAddress(pub (TransitionId, Address));
 
 impl TransitionIdAddress {
@@ -73,38 +68,13 @@ impl From<(u64, Address)> for TransitionIdAddress {
     }
 }
 
-impl Encode for TransitionIdAddress {
-    type Encoded = [u8; 28];
-
-    fn encode(self) -> Self::Encoded {
-        let tx = self.0 .0;
-        let address = self.0 .1;
-
-        let mut buf = [0u8; 28];
-
-        buf[..8].copy_from_slice(&tx.to_be_bytes());
-        buf[8..].copy_from_slice(address.as_bytes());
-        buf
-    }
-}
-
-impl Decode for TransitionIdAddress {
-    fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
-        let value: Bytes = value.into();
-
-        let num =
-            u64::from_be_bytes(value.as_ref()[..8].try_into().map_err(|_| Error::DecodeError)?);
-        let hash = Address::from_slice(&value.slice(8..));
-
-        Ok(TransitionIdAddress((num, hash)))
-    }
-}
-
-impl_fixed_arbitrary!(TransitionIdAddress, 28);
+impl_num_hash_arbitrary!(TransitionIdAddress, Address);
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::table::{Decode, Encode};
+    use arbitrary::{Arbitrary, Unstructured};
     use rand::{thread_rng, Rng};
     use std::str::FromStr;
 
@@ -114,11 +84,10 @@ mod test {
         let hash = Address::from_str("ba5e000000000000000000000000000000000000").unwrap();
         let key = TransitionIdAddress((num, hash));
 
-        let mut bytes = [0u8; 28];
-        bytes[..8].copy_from_slice(&num.to_be_bytes());
-        bytes[8..].copy_from_slice(&hash.0);
+        let mut bytes = vec![1, 1];
+        bytes.extend_from_slice(&hash.0);
 
-        let encoded = Encode::encode(key.clone());
+        let encoded = Encode::encode(key);
         assert_eq!(encoded, bytes);
 
         let decoded: TransitionIdAddress = Decode::decode(encoded.to_vec()).unwrap();
@@ -130,6 +99,6 @@ mod test {
         let mut bytes = [0u8; 28];
         thread_rng().fill(bytes.as_mut_slice());
         let key = TransitionIdAddress::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
-        assert_eq!(bytes, Encode::encode(key));
+        assert_eq!(Decode::decode(Encode::encode(key)), Ok(key));
     }
 }
@@ -2,13 +2,9 @@
 
 use std::ops::Range;
 
-use crate::{
-    impl_fixed_arbitrary,
-    table::{Decode, Encode},
-    Error,
-};
+use crate::{impl_num_hash_arbitrary, TableKey};
 use reth_codecs::{main_codec, Compact};
-use reth_primitives::{bytes::Bytes, BlockHash, BlockNumber, Header, TxNumber, Withdrawal, H256};
+use reth_primitives::{BlockHash, BlockNumber, Header, TxNumber, Withdrawal, H256};
 use serde::{Deserialize, Serialize};
 
 /// Total number of transactions.
@@ -71,8 +67,10 @@ pub type HeaderHash = H256;
 /// BlockNumber concatenated with BlockHash. Used as a key for multiple tables. Having the first
 /// element as BlockNumber, helps out with querying/sorting.
 ///
-/// Since it's used as a key, the `BlockNumber` is not compressed when encoding it.
-#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Ord, PartialOrd, Hash)]
+/// Since it's used as a key, it is encoded with [`TableKey`], which keeps the ordering.
+#[derive(
+    Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Ord, PartialOrd, Hash, TableKey,
+)]
 pub struct BlockNumHash(pub (BlockNumber, BlockHash));
 
 impl std::fmt::Debug for BlockNumHash {
@@ -110,38 +108,12 @@ impl From<u64> for BlockNumHash {
     }
 }
 
-impl Encode for BlockNumHash {
-    type Encoded = [u8; 40];
-
-    fn encode(self) -> Self::Encoded {
-        let number = self.0 .0;
-        let hash = self.0 .1;
-
-        let mut rnum = [0; 40];
-
-        rnum[..8].copy_from_slice(&number.to_be_bytes());
-        rnum[8..].copy_from_slice(hash.as_bytes());
-        rnum
-    }
-}
-
-impl Decode for BlockNumHash {
-    fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
-        let value: Bytes = value.into();
-
-        let num =
-            u64::from_be_bytes(value.as_ref()[..8].try_into().map_err(|_| Error::DecodeError)?);
-        let hash = H256::from_slice(&value.slice(8..));
-
-        Ok(BlockNumHash((num, hash)))
-    }
-}
-
-impl_fixed_arbitrary!(BlockNumHash, 40);
+impl_num_hash_arbitrary!(BlockNumHash, H256);
 
 #[cfg(test)]
 mod test {
-    use crate::table::{Compress, Decompress};
+    use crate::table::{Compress, Decode, Decompress, Encode};
+    use arbitrary::{Arbitrary, Unstructured};
 
     use super::*;
     use rand::{thread_rng, Rng};
@@ -152,9 +124,8 @@ mod test {
         let hash = H256::from_low_u64_be(2);
         let key = BlockNumHash((num, hash));
 
-        let mut bytes = [0u8; 40];
-        bytes[..8].copy_from_slice(&num.to_be_bytes());
-        bytes[8..].copy_from_slice(&hash.0);
+        let mut bytes = vec![1, 1];
+        bytes.extend_from_slice(&hash.0);
 
         let encoded = Encode::encode(key);
         assert_eq!(encoded, bytes);
@@ -168,7 +139,7 @@ mod test {
         let mut bytes = [0u8; 40];
         thread_rng().fill(bytes.as_mut_slice());
         let key = BlockNumHash::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
-        assert_eq!(bytes, Encode::encode(key));
+        assert_eq!(Decode::decode(Encode::encode(key)), Ok(key));
     }
 
     #[test]
@@ -11,7 +11,7 @@ pub use blocks::*;
 pub use sharded_key::ShardedKey;
 
 use crate::{
-    table::{Decode, Encode},
+    table::{Decode, DecodePrefix, Encode},
     Error,
 };
 use reth_primitives::{bytes::Bytes, Address, H256};
@@ -40,11 +40,87 @@ macro_rules! impl_uints {
                     )
                 }
             }
+
+            impl DecodePrefix for $name
+            {
+                fn decode_prefix(value: &[u8]) -> Result<(Self, usize), Error> {
+                    let len = std::mem::size_of::<$name>();
+                    let value = value.get(..len).ok_or(Error::DecodeError)?;
+                    Ok(($name::from_be_bytes(value.try_into().map_err(|_| Error::DecodeError)?), len))
+                }
+            }
         )+
     };
 }
 
-impl_uints!(u64, u32, u16, u8);
+impl_uints!(u32, u16, u8);
+
+/// Compact encoding of a `u64` key: the number of its significant bytes, followed by these bytes
+/// in big-endian order.
+///
+/// Smaller numbers have shorter encodings, which start with a smaller byte, so the byte-wise order
+/// of the encodings matches the numeric order.
+#[derive(Debug, Clone, Copy)]
+pub struct EncodedU64 {
+    buf: [u8; 9],
+    len: usize,
+}
+
+impl EncodedU64 {
+    /// Encodes `value`.
+    pub fn new(value: u64) -> Self {
+        let significant = 8 - value.leading_zeros() as usize / 8;
+        let mut buf = [0; 9];
+        buf[0] = significant as u8;
+        buf[1..=significant].copy_from_slice(&value.to_be_bytes()[8 - significant..]);
+        Self { buf, len: significant + 1 }
+    }
+
+    /// Decodes the `u64` at the start of `buf`, returning it with the length of its encoding.
+    pub fn decode_prefix(buf: &[u8]) -> Result<(u64, usize), Error> {
+        let significant = *buf.first().ok_or(Error::DecodeError)? as usize;
+        let bytes =
+            buf.get(1..=significant).filter(|_| significant <= 8).ok_or(Error::DecodeError)?;
+        // Leading zeroes would break the ordering.
+        if bytes.first() == Some(&0) {
+            return Err(Error::DecodeError)
+        }
+
+        let mut value = [0; 8];
+        value[8 - significant..].copy_from_slice(bytes);
+        Ok((u64::from_be_bytes(value), significant + 1))
+    }
+}
+
+impl AsRef<[u8]> for EncodedU64 {
+    fn as_ref(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl Encode for u64 {
+    type Encoded = EncodedU64;
+
+    fn encode(self) -> Self::Encoded {
+        EncodedU64::new(self)
+    }
+}
+
+impl Decode for u64 {
+    fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+        let value: Bytes = value.into();
+        match EncodedU64::decode_prefix(&value)? {
+            (number, len) if len == value.len() => Ok(number),
+            _ => Err(Error::DecodeError),
+        }
+    }
+}
+
+impl DecodePrefix for u64 {
+    fn decode_prefix(value: &[u8]) -> Result<(Self, usize), Error> {
+        EncodedU64::decode_prefix(value)
+    }
+}
 
 impl Encode for Vec<u8> {
     type Encoded = Vec<u8>;
@@ -68,9 +144,17 @@ impl Encode for Address {
 
 impl Decode for Address {
     fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
-        Ok(Address::from_slice(&value.into()[..]))
+        let value: [u8; 20] = value.into()[..].try_into().map_err(|_| Error::DecodeError)?;
+        Ok(Address::from(value))
+    }
+}
+
+impl DecodePrefix for Address {
+    fn decode_prefix(value: &[u8]) -> Result<(Self, usize), Error> {
+        Ok((Address::from_slice(value.get(..20).ok_or(Error::DecodeError)?), 20))
     }
 }
+
 impl Encode for H256 {
     type Encoded = [u8; 32];
     fn encode(self) -> Self::Encoded {
@@ -80,6 +164,113 @@ impl Encode for H256 {
 
 impl Decode for H256 {
     fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
-        Ok(H256::from_slice(&value.into()[..]))
+        let value: [u8; 32] = value.into()[..].try_into().map_err(|_| Error::DecodeError)?;
+        Ok(H256::from(value))
+    }
+}
+
+impl DecodePrefix for H256 {
+    fn decode_prefix(value: &[u8]) -> Result<(Self, usize), Error> {
+        Ok((H256::from_slice(value.get(..32).ok_or(Error::DecodeError)?), 32))
+    }
+}
+
+/// Implements [`Encode`] and [`Decode`] for tuples, the same way as
+/// [`TableKey`](crate::TableKey): all the fields but the last one must implement [`DecodePrefix`].
+macro_rules! impl_tuples {
+    ($(($($prefix:ident),* ; $last:ident)),+) => {
+        $(
+            #[allow(non_snake_case)]
+            impl<$($prefix,)* $last> Encode for ($($prefix,)* $last,)
+            where
+                $($prefix: Encode,)*
+                $last: Encode,
+            {
+                type Encoded = Vec<u8>;
+
+                fn encode(self) -> Self::Encoded {
+                    let ($($prefix,)* $last,) = self;
+                    let mut buf = Vec::new();
+                    $(buf.extend_from_slice($prefix.encode().as_ref());)*
+                    buf.extend_from_slice($last.encode().as_ref());
+                    buf
+                }
+            }
+
+            #[allow(non_snake_case)]
+            impl<$($prefix,)* $last> Decode for ($($prefix,)* $last,)
+            where
+                $($prefix: DecodePrefix,)*
+                $last: Decode,
+            {
+                fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+                    let value: Bytes = value.into();
+                    let mut offset = 0;
+                    $(
+                        let ($prefix, len) = $prefix::decode_prefix(&value[offset..])?;
+                        offset += len;
+                    )*
+                    let $last = $last::decode(value.slice(offset..))?;
+                    Ok(($($prefix,)* $last,))
+                }
+            }
+        )+
+    };
+}
+
+impl_tuples!((A; B), (A, B; C));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TableKey;
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, TableKey)]
+    struct NumbersAddress {
+        block: u64,
+        tx: u64,
+        address: Address,
+    }
+
+    #[test]
+    fn derived_table_key() {
+        let keys = [(0, 5, 1), (1, 0, 0), (1, 0, 2), (1, 256, 0), (300, 1, 0)].map(
+            |(block, tx, address)| NumbersAddress {
+                block,
+                tx,
+                address: Address::from_low_u64_be(address),
+            },
+        );
+
+        for key in keys.clone() {
+            let encoded = Encode::encode(key.clone());
+            assert_eq!(NumbersAddress::decode(encoded), Ok(key));
+        }
+
+        // The byte-wise order matches the lexicographic order of the fields
+        let encoded = keys.map(Encode::encode);
+        assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert_eq!(NumbersAddress::decode(vec![1, 1, 1, 1]), Err(Error::DecodeError));
+    }
+
+    #[test]
+    fn u64_compact_encoding() {
+        let numbers = [0, 1, 0xff, 0x100, 0xffff_ffff, u64::MAX];
+        for number in numbers {
+            let encoded = Encode::encode(number);
+            assert_eq!(u64::decode(encoded.as_ref().to_vec()), Ok(number));
+        }
+        assert_eq!(Encode::encode(0u64).as_ref(), [0]);
+        assert_eq!(Encode::encode(0x1234u64).as_ref(), [2, 0x12, 0x34]);
+
+        // The byte-wise order matches the numeric order
+        let encoded = numbers.map(|number| Encode::encode(number).as_ref().to_vec());
+        assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Non canonical or truncated encodings are rejected
+        assert_eq!(u64::decode(vec![1, 0]), Err(Error::DecodeError));
+        assert_eq!(u64::decode(vec![2, 1]), Err(Error::DecodeError));
+        assert_eq!(u64::decode(vec![9; 10]), Err(Error::DecodeError));
     }
 }
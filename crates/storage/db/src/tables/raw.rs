@@ -0,0 +1,138 @@
+//! Access to the tables without encoding and decoding their keys and values.
+
+use crate::{
+    table::{Compress, Decode, Decompress, DupSort, Encode, Key, Table},
+    tables::codecs::{compress_value, decompress_value},
+    Error,
+};
+use reth_primitives::bytes::Bytes;
+use serde::Serialize;
+use std::{borrow::Cow, marker::PhantomData};
+
+/// Table `T` with its keys and values kept as raw bytes, as [`RawKey`] and [`RawValue`].
+///
+/// It has the same name as `T`, so it opens the same database table. Its values are the bytes
+/// stored in the table, still compressed with the [`Table::COMPRESSION`] of `T`, so they can be
+/// copied without being decompressed.
+#[derive(Debug, Default)]
+pub struct RawTable<T: Table> {
+    phantom: PhantomData<T>,
+}
+
+impl<T: Table> Table for RawTable<T> {
+    const NAME: &'static str = T::NAME;
+    type Key = RawKey<T::Key>;
+    type Value = RawValue<T>;
+}
+
+impl<T: DupSort> DupSort for RawTable<T> {
+    type SubKey = T::SubKey;
+}
+
+/// Encoded key of type `K`. Raw keys are ordered by their bytes, as in the database.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RawKey<K: Key> {
+    key: Vec<u8>,
+    _phantom: PhantomData<K>,
+}
+
+impl<K: Key> RawKey<K> {
+    /// Encodes `key`.
+    pub fn new(key: K) -> Self {
+        Self::from_vec(key.encode().as_ref().to_vec())
+    }
+
+    /// Wraps already encoded key bytes.
+    pub fn from_vec(key: Vec<u8>) -> Self {
+        Self { key, _phantom: PhantomData }
+    }
+
+    /// Decodes the key.
+    pub fn key(&self) -> Result<K, Error> {
+        K::decode(self.key.clone())
+    }
+
+    /// Returns the encoded key.
+    pub fn raw_key(&self) -> &[u8] {
+        &self.key
+    }
+}
+
+impl<K: Key> From<K> for RawKey<K> {
+    fn from(key: K) -> Self {
+        Self::new(key)
+    }
+}
+
+impl<K: Key> Encode for RawKey<K> {
+    type Encoded = Vec<u8>;
+
+    fn encode(self) -> Self::Encoded {
+        self.key
+    }
+}
+
+impl<K: Key> Decode for RawKey<K> {
+    fn decode<B: Into<Bytes>>(key: B) -> Result<Self, Error> {
+        Ok(Self::from_vec(key.into().to_vec()))
+    }
+}
+
+/// Value of the table `T` as it is stored, with the [`Table::COMPRESSION`] of `T` applied.
+#[derive(Debug, Serialize)]
+pub struct RawValue<T: Table> {
+    value: Vec<u8>,
+    #[serde(skip)]
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Table> RawValue<T> {
+    /// Compresses `value` the way it is stored in `T`.
+    pub fn new(value: T::Value) -> Self {
+        Self::from_vec(compress_value::<T>(value).as_ref().to_vec())
+    }
+
+    /// Wraps value bytes as they are stored in `T`.
+    pub fn from_vec(value: Vec<u8>) -> Self {
+        Self { value, _phantom: PhantomData }
+    }
+
+    /// Decompresses the value.
+    pub fn value(&self) -> Result<T::Value, Error> {
+        decompress_value::<T>(Cow::Borrowed(&self.value))
+    }
+
+    /// Returns the stored value bytes.
+    pub fn raw_value(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+// Implemented by hand, the tables themselves are neither `PartialEq` nor `Eq`.
+impl<T: Table> Clone for RawValue<T> {
+    fn clone(&self) -> Self {
+        Self::from_vec(self.value.clone())
+    }
+}
+
+impl<T: Table> PartialEq for RawValue<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Table> Eq for RawValue<T> {}
+
+impl<T: Table> Compress for RawValue<T> {
+    type Compressed = Vec<u8>;
+
+    fn compress(self) -> Self::Compressed {
+        self.value
+    }
+}
+
+impl<T: Table> Decompress for RawValue<T> {
+    fn decompress<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+        Ok(Self::from_vec(value.into().to_vec()))
+    }
+}
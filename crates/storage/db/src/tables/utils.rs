@@ -1,6 +1,7 @@
 //! Small database table utilities and helper functions
 use crate::{
     table::{Decode, Decompress, Table},
+    tables::codecs::decompress_value,
     Error,
 };
 use reth_primitives::bytes::Bytes;
@@ -40,6 +41,34 @@ macro_rules! impl_fixed_arbitrary {
     };
 }
 
+#[macro_export]
+/// Implements the `Arbitrary` trait for newtypes over a `(u64, hash)` tuple, whose encoding is not
+/// fixed width.
+macro_rules! impl_num_hash_arbitrary {
+    ($name:tt, $hash:tt) => {
+        #[cfg(any(test, feature = "arbitrary"))]
+        impl<'a> arbitrary::Arbitrary<'a> for $name {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                let hash: [u8; std::mem::size_of::<$hash>()] = u.arbitrary()?;
+                Ok($name((u.arbitrary()?, $hash::from(hash))))
+            }
+        }
+
+        #[cfg(any(test, feature = "arbitrary"))]
+        impl proptest::prelude::Arbitrary for $name {
+            type Parameters = ();
+            type Strategy = proptest::prelude::BoxedStrategy<$name>;
+
+            fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                use proptest::strategy::Strategy;
+                proptest::prelude::any::<(u64, [u8; std::mem::size_of::<$hash>()])>()
+                    .prop_map(|(num, hash)| $name((num, $hash::from(hash))))
+                    .boxed()
+            }
+        }
+    };
+}
+
 /// Helper function to decode a `(key, value)` pair.
 pub(crate) fn decoder<'a, T>(
     kv: (Cow<'a, [u8]>, Cow<'a, [u8]>),
@@ -49,10 +78,7 @@ where
     T::Key: Decode,
     T::Value: Decompress,
 {
-    Ok((
-        Decode::decode(Bytes::from(kv.0.into_owned()))?,
-        Decompress::decompress(Bytes::from(kv.1.into_owned()))?,
-    ))
+    Ok((Decode::decode(Bytes::from(kv.0.into_owned()))?, decompress_value::<T>(kv.1)?))
 }
 
 /// Helper function to decode only a value from a `(key, value)` pair.
@@ -60,7 +86,7 @@ pub(crate) fn decode_value<'a, T>(kv: (Cow<'a, [u8]>, Cow<'a, [u8]>)) -> Result<
 where
     T: Table,
 {
-    Decompress::decompress(Bytes::from(kv.1.into_owned()))
+    decompress_value::<T>(kv.1)
 }
 
 /// Helper function to decode a value. It can be a key or subkey.
@@ -68,5 +94,5 @@ pub(crate) fn decode_one<T>(value: Cow<'_, [u8]>) -> Result<T::Value, Error>
 where
     T: Table,
 {
-    Decompress::decompress(Bytes::from(value.into_owned()))
+    decompress_value::<T>(value)
 }
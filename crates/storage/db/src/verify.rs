@@ -0,0 +1,364 @@
+//! Integrity checks of the database.
+//!
+//! Each check walks some of the canonical tables and returns the inconsistencies it found, so that
+//! a corrupted datadir can be detected before it breaks the sync in a less obvious way.
+//!
+//! The oldest headers, bodies and transactions may have been moved out of the database, so they
+//! are read through a [`BlockTables`] implementation which also looks them up where they were
+//! moved to.
+
+use crate::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    models::StoredBlockBody,
+    tables,
+    transaction::DbTx,
+};
+use reth_interfaces::Result;
+use reth_primitives::{keccak256, Account, Address, BlockNumber, Header, TxNumber, H256, U256};
+
+/// Read access to the blocks, wherever they are stored.
+pub trait BlockTables {
+    /// Returns the header of the block `number`.
+    fn header(&self, number: BlockNumber) -> Result<Option<Header>>;
+
+    /// Returns the body of the block `number`.
+    fn block_body(&self, number: BlockNumber) -> Result<Option<StoredBlockBody>>;
+
+    /// Returns `true` if the transaction `id` is stored.
+    fn has_transaction(&self, id: TxNumber) -> Result<bool>;
+}
+
+/// An inconsistency found in the database.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Inconsistency {
+    /// The header of a canonical block is missing.
+    #[error("header #{number} with canonical hash {canonical:?} is missing")]
+    MissingHeader {
+        /// Number of the block.
+        number: BlockNumber,
+        /// Canonical hash stored for that number.
+        canonical: H256,
+    },
+    /// The hash of a header is not its canonical hash.
+    #[error("header #{number} has hash {hash:?}, but the canonical hash is {canonical:?}")]
+    CanonicalHash {
+        /// Number of the header.
+        number: BlockNumber,
+        /// Hash of the header.
+        hash: H256,
+        /// Canonical hash stored for that number.
+        canonical: H256,
+    },
+    /// The hash of a header is not mapped to its number.
+    #[error("header #{number} with hash {hash:?} is mapped to block {mapped:?}")]
+    HeaderNumber {
+        /// Number of the header.
+        number: BlockNumber,
+        /// Hash of the header.
+        hash: H256,
+        /// Block number stored for that hash.
+        mapped: Option<BlockNumber>,
+    },
+    /// A block body does not start right after the transactions of the previous block.
+    #[error("body of block #{number} starts at transaction {start_tx_id}, expected {expected}")]
+    BodyStart {
+        /// Number of the block.
+        number: BlockNumber,
+        /// First transaction of the body.
+        start_tx_id: TxNumber,
+        /// Transaction following the previous body.
+        expected: TxNumber,
+    },
+    /// The transactions of a block body are not all stored.
+    #[error("body of block #{number} has {tx_count} transactions, but {found} were found")]
+    BodyTransactions {
+        /// Number of the block.
+        number: BlockNumber,
+        /// Number of transactions of the body.
+        tx_count: u64,
+        /// Number of transactions found.
+        found: u64,
+    },
+    /// A receipt belongs to a transaction that does not exist.
+    #[error("receipt of transaction {tx_id} which does not exist")]
+    OrphanReceipt {
+        /// Id of the transaction of the receipt.
+        tx_id: TxNumber,
+    },
+    /// The hashed account does not match the plain account.
+    #[error("account {address:?} is {account:?}, but its hashed account is {hashed:?}")]
+    HashedAccount {
+        /// Address of the account.
+        address: Address,
+        /// Plain account.
+        account: Account,
+        /// Hashed account, if any.
+        hashed: Option<Account>,
+    },
+    /// The hashed storage slot does not match the plain storage slot.
+    #[error("slot {slot:?} of {address:?} is {value}, but its hashed slot is {hashed:?}")]
+    HashedStorage {
+        /// Address of the account.
+        address: Address,
+        /// Plain storage slot.
+        slot: H256,
+        /// Value of the plain storage slot.
+        value: U256,
+        /// Value of the hashed storage slot, if any.
+        hashed: Option<U256>,
+    },
+    /// The state root recomputed from the hashed state does not match the header.
+    #[error("header #{number} has state root {expected:?}, but the state hashes to {got:?}")]
+    StateRoot {
+        /// Number of the block the state was computed up to.
+        number: BlockNumber,
+        /// State root of the header.
+        expected: H256,
+        /// Recomputed state root.
+        got: H256,
+    },
+}
+
+/// Checks that every canonical header is stored, hashes to its canonical hash, and that its hash
+/// maps to its number.
+pub fn check_headers<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    blocks: &impl BlockTables,
+) -> Result<Vec<Inconsistency>> {
+    let mut inconsistencies = Vec::new();
+
+    let mut canonical_headers = tx.cursor_read::<tables::CanonicalHeaders>()?;
+    for entry in canonical_headers.walk(None)? {
+        let (number, canonical) = entry?;
+        let Some(header) = blocks.header(number)? else {
+            inconsistencies.push(Inconsistency::MissingHeader { number, canonical });
+            continue
+        };
+
+        let hash = header.hash_slow();
+        if canonical != hash {
+            inconsistencies.push(Inconsistency::CanonicalHash { number, hash, canonical });
+        }
+
+        let mapped = tx.get::<tables::HeaderNumbers>(hash)?;
+        if mapped != Some(number) {
+            inconsistencies.push(Inconsistency::HeaderNumber { number, hash, mapped });
+        }
+    }
+
+    Ok(inconsistencies)
+}
+
+/// Checks that the bodies of the canonical blocks are contiguous and that all their transactions
+/// are stored. The check stops at the first canonical block without a body.
+pub fn check_bodies<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    blocks: &impl BlockTables,
+) -> Result<Vec<Inconsistency>> {
+    let mut inconsistencies = Vec::new();
+
+    let mut canonical_headers = tx.cursor_read::<tables::CanonicalHeaders>()?;
+    let mut expected = None;
+    for entry in canonical_headers.walk(None)? {
+        let (number, _) = entry?;
+        let Some(body) = blocks.block_body(number)? else { break };
+
+        if let Some(expected) = expected.filter(|expected| *expected != body.start_tx_id) {
+            inconsistencies.push(Inconsistency::BodyStart {
+                number,
+                start_tx_id: body.start_tx_id,
+                expected,
+            });
+        }
+        expected = Some(body.start_tx_id + body.tx_count);
+
+        let mut found = 0;
+        for id in body.tx_id_range() {
+            if blocks.has_transaction(id)? {
+                found += 1;
+            }
+        }
+        if found != body.tx_count {
+            inconsistencies.push(Inconsistency::BodyTransactions {
+                number,
+                tx_count: body.tx_count,
+                found,
+            });
+        }
+    }
+
+    Ok(inconsistencies)
+}
+
+/// Checks that every receipt of the database belongs to a stored transaction.
+pub fn check_receipts<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    blocks: &impl BlockTables,
+) -> Result<Vec<Inconsistency>> {
+    let mut inconsistencies = Vec::new();
+
+    let mut receipts = tx.cursor_read::<tables::Receipts>()?;
+    for entry in receipts.walk(None)? {
+        let (tx_id, _) = entry?;
+        if !blocks.has_transaction(tx_id)? {
+            inconsistencies.push(Inconsistency::OrphanReceipt { tx_id });
+        }
+    }
+
+    Ok(inconsistencies)
+}
+
+/// Checks that every account and storage slot of the plain state is in the hashed state, with the
+/// same value.
+///
+/// The hashed state lags behind the plain state until the hashing stages caught up with the
+/// execution, so this is only meaningful on a fully synced database.
+pub fn check_plain_state<'a, TX: DbTx<'a>>(tx: &TX) -> Result<Vec<Inconsistency>> {
+    let mut inconsistencies = Vec::new();
+
+    let mut accounts = tx.cursor_read::<tables::PlainAccountState>()?;
+    for entry in accounts.walk(None)? {
+        let (address, account) = entry?;
+        let hashed = tx.get::<tables::HashedAccount>(keccak256(address))?;
+        if hashed != Some(account) {
+            inconsistencies.push(Inconsistency::HashedAccount { address, account, hashed });
+        }
+    }
+
+    let mut storage = tx.cursor_dup_read::<tables::PlainStorageState>()?;
+    let mut hashed_storage = tx.cursor_dup_read::<tables::HashedStorage>()?;
+    for entry in storage.walk(None)? {
+        let (address, entry) = entry?;
+        let hashed_slot = keccak256(entry.key);
+        let hashed = hashed_storage
+            .seek_by_key_subkey(keccak256(address), hashed_slot)?
+            .filter(|hashed| hashed.key == hashed_slot)
+            .map(|hashed| hashed.value);
+        if hashed != Some(entry.value) {
+            inconsistencies.push(Inconsistency::HashedStorage {
+                address,
+                slot: entry.key,
+                value: entry.value,
+                hashed,
+            });
+        }
+    }
+
+    Ok(inconsistencies)
+}
+
+/// Runs the checks of the blocks, and the one of the plain state if `state` is set.
+pub fn check_all<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    blocks: &impl BlockTables,
+    state: bool,
+) -> Result<Vec<Inconsistency>> {
+    let mut inconsistencies = check_headers(tx, blocks)?;
+    inconsistencies.extend(check_bodies(tx, blocks)?);
+    inconsistencies.extend(check_receipts(tx, blocks)?);
+    if state {
+        inconsistencies.extend(check_plain_state(tx)?);
+    }
+
+    Ok(inconsistencies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::Database,
+        mdbx::{test_utils::create_test_rw_db, Env, WriteMap},
+        transaction::DbTxMut,
+    };
+    use reth_primitives::StorageEntry;
+
+    /// Reads the blocks from the database only.
+    struct DbBlocks<'a>(&'a Env<WriteMap>);
+
+    impl BlockTables for DbBlocks<'_> {
+        fn header(&self, number: BlockNumber) -> Result<Option<Header>> {
+            Ok(self.0.view(|tx| tx.get::<tables::Headers>(number))??)
+        }
+
+        fn block_body(&self, number: BlockNumber) -> Result<Option<StoredBlockBody>> {
+            Ok(self.0.view(|tx| tx.get::<tables::BlockBodies>(number))??)
+        }
+
+        fn has_transaction(&self, id: TxNumber) -> Result<bool> {
+            Ok(self.0.view(|tx| tx.get::<tables::Transactions>(id))??.is_some())
+        }
+    }
+
+    #[test]
+    fn detects_inconsistencies() {
+        let db = create_test_rw_db();
+        let blocks = DbBlocks(&db);
+        let header = Header { number: 1, ..Default::default() };
+        let hash = header.hash_slow();
+
+        db.update(|tx| {
+            tx.put::<tables::Headers>(1, header.clone())?;
+            tx.put::<tables::CanonicalHeaders>(1, hash)?;
+            tx.put::<tables::HeaderNumbers>(hash, 1)?;
+            tx.put::<tables::CanonicalHeaders>(2, H256::zero())?;
+            tx.put::<tables::BlockBodies>(1, StoredBlockBody { start_tx_id: 0, tx_count: 0 })?;
+            tx.put::<tables::BlockBodies>(2, StoredBlockBody { start_tx_id: 1, tx_count: 1 })
+        })
+        .unwrap()
+        .unwrap();
+
+        let tx = db.tx().unwrap();
+        assert_eq!(
+            check_headers(&tx, &blocks).unwrap(),
+            vec![Inconsistency::MissingHeader { number: 2, canonical: H256::zero() }]
+        );
+        assert_eq!(
+            check_bodies(&tx, &blocks).unwrap(),
+            vec![
+                Inconsistency::BodyStart { number: 2, start_tx_id: 1, expected: 0 },
+                Inconsistency::BodyTransactions { number: 2, tx_count: 1, found: 0 },
+            ]
+        );
+        drop(tx);
+
+        db.update(|tx| tx.delete::<tables::HeaderNumbers>(hash, None)).unwrap().unwrap();
+        let tx = db.tx().unwrap();
+        assert_eq!(
+            check_headers(&tx, &blocks).unwrap()[0],
+            Inconsistency::HeaderNumber { number: 1, hash, mapped: None }
+        );
+    }
+
+    #[test]
+    fn detects_plain_state_inconsistencies() {
+        let db = create_test_rw_db();
+        let address = Address::from_low_u64_be(1);
+        let account = Account { nonce: 1, ..Default::default() };
+        let slot = StorageEntry { key: H256::from_low_u64_be(2), value: U256::from(3) };
+        let hashed_slot = StorageEntry { key: keccak256(slot.key), value: slot.value };
+
+        db.update(|tx| {
+            tx.put::<tables::PlainAccountState>(address, account)?;
+            tx.put::<tables::HashedAccount>(keccak256(address), account)?;
+            tx.put::<tables::PlainStorageState>(address, slot)?;
+            tx.put::<tables::HashedStorage>(keccak256(address), hashed_slot)
+        })
+        .unwrap()
+        .unwrap();
+        assert_eq!(check_plain_state(&db.tx().unwrap()).unwrap(), vec![]);
+
+        db.update(|tx| tx.delete::<tables::HashedStorage>(keccak256(address), None))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            check_plain_state(&db.tx().unwrap()).unwrap(),
+            vec![Inconsistency::HashedStorage {
+                address,
+                slot: slot.key,
+                value: slot.value,
+                hashed: None
+            }]
+        );
+    }
+}
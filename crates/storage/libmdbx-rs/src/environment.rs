@@ -15,7 +15,7 @@ use std::{
     marker::PhantomData,
     mem,
     ops::{Bound, RangeBounds},
-    os::unix::ffi::OsStrExt,
+    os::unix::{ffi::OsStrExt, io::RawFd},
     path::Path,
     ptr, result,
     sync::mpsc::{sync_channel, SyncSender},
@@ -152,6 +152,34 @@ where
         }
     }
 
+    /// Sets the upper bound of the memory map of an open environment, keeping the rest of its
+    /// geometry.
+    ///
+    /// It must not be called while the current thread has an open read-write transaction.
+    pub fn set_max_size(&self, max_size: usize) -> Result<()> {
+        mdbx_result(unsafe {
+            ffi::mdbx_env_set_geometry(self.env(), -1, -1, max_size as isize, -1, -1, -1)
+        })?;
+        Ok(())
+    }
+
+    /// Copies the environment into a new file at `path`, which must not exist yet.
+    ///
+    /// The copy is made from a read-only transaction, so it is consistent and does not block
+    /// writers. If `compact` is set, free pages are omitted and the pages are renumbered.
+    pub fn copy(&self, path: &Path, compact: bool) -> Result<()> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::Invalid)?;
+        mdbx_result(unsafe { ffi::mdbx_env_copy(self.env(), path.as_ptr(), copy_flags(compact)) })?;
+        Ok(())
+    }
+
+    /// Copies the environment into the file descriptor, which must be open for writing. See
+    /// [Environment::copy].
+    pub fn copy_to_fd(&self, fd: RawFd, compact: bool) -> Result<()> {
+        mdbx_result(unsafe { ffi::mdbx_env_copy2fd(self.env(), fd, copy_flags(compact)) })?;
+        Ok(())
+    }
+
     /// Retrieves info about this environment.
     pub fn info(&self) -> Result<Info> {
         unsafe {
@@ -216,6 +244,15 @@ where
     }
 }
 
+/// Flags of an environment copy.
+fn copy_flags(compact: bool) -> ffi::MDBX_copy_flags_t {
+    if compact {
+        ffi::MDBX_CP_COMPACT
+    } else {
+        ffi::MDBX_CP_DEFAULTS
+    }
+}
+
 /// Environment statistics.
 ///
 /// Contains information about the size and layout of an MDBX environment or database.
@@ -279,6 +316,14 @@ impl GeometryInfo {
     pub fn min(&self) -> u64 {
         self.0.lower
     }
+
+    pub fn max(&self) -> u64 {
+        self.0.upper
+    }
+
+    pub fn current(&self) -> u64 {
+        self.0.current
+    }
 }
 
 /// Environment information.
@@ -11,15 +11,16 @@
 /// Various provider traits.
 mod traits;
 pub use traits::{
-    AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider, StateProvider,
-    StateProviderFactory,
+    AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider, ReceiptProvider,
+    StateProvider, StateProviderFactory, StateTrieProvider, TransactionsProvider,
 };
 
 /// Provider trait implementations.
 pub mod providers;
 pub use providers::{
-    HistoricalStateProvider, HistoricalStateProviderRef, LatestStateProvider,
-    LatestStateProviderRef, ShareableDatabase,
+    CachedStateProvider, HistoricalStateProvider, HistoricalStateProviderRef, LatestStateProvider,
+    LatestStateProviderRef, OverlayStateProvider, PrefetchCache, PrefetchTargets,
+    PrefetchedStateProvider, ShareableDatabase, StateCache, StateCacheConfig, StateOverlay,
 };
 
 /// Helper types for interacting with the database
@@ -30,6 +31,9 @@ pub use transaction::{Transaction, TransactionError};
 mod utils;
 pub use utils::{insert_block, insert_canonical_block};
 
+/// Reading the state tries.
+pub mod trie;
+
 #[cfg(any(test, feature = "test-utils"))]
 /// Common test helpers for mocking the Provider.
 pub mod test_utils;
@@ -1,51 +1,106 @@
-use crate::{BlockHashProvider, BlockProvider, Error, HeaderProvider, StateProviderFactory};
+use crate::{
+    trie::{self, HashedStateChanges},
+    BlockHashProvider, BlockProvider, Error, HeaderProvider, ReceiptProvider, StateProviderFactory,
+    StateTrieProvider, TransactionsProvider,
+};
 use reth_db::{
     database::{Database, DatabaseGAT},
+    models::StoredBlockBody,
     tables,
-    transaction::DbTx,
+    transaction::{DbTx, DbTxMut},
 };
 use reth_interfaces::Result;
-use reth_primitives::{rpc::BlockId, Block, BlockHash, BlockNumber, ChainInfo, Header, H256, U256};
+use reth_primitives::{
+    rpc::BlockId, Account, Block, BlockHash, BlockHashOrNumber, BlockNumber, Bytes, ChainInfo,
+    Header, Receipt, TransactionSigned, TransitionId, TxHash, TxNumber, H256, U256,
+};
+use reth_static_file::{StaticFileProvider, StaticFileTable};
 use std::ops::RangeBounds;
 
 mod state;
-use reth_db::cursor::DbCursorRO;
+use reth_db::cursor::{DbCursorRO, DbDupCursorRO};
 pub use state::{
+    cached::{CachedStateProvider, StateCache, StateCacheConfig},
     chain::ChainState,
     historical::{HistoricalStateProvider, HistoricalStateProviderRef},
     latest::{LatestStateProvider, LatestStateProviderRef},
+    overlay::{OverlayStateProvider, StateOverlay},
+    prefetch::{PrefetchCache, PrefetchTargets, PrefetchedStateProvider},
 };
 
 /// A common provider that fetches data from a database.
 ///
 /// This provider implements most provider or provider factory traits.
+///
+/// If it has static files, the values of the finalized blocks which were moved out of the database
+/// are read from them.
+///
+/// If it has a [StateCache], the latest state is read through it.
 pub struct ShareableDatabase<DB> {
     /// Database
     db: DB,
+    /// Static files holding the finalized blocks.
+    static_files: Option<StaticFileProvider>,
+    /// Cache of the latest state.
+    state_cache: Option<StateCache>,
 }
 
 impl<DB> ShareableDatabase<DB> {
     /// create new database provider
     pub fn new(db: DB) -> Self {
-        Self { db }
+        Self { db, static_files: None, state_cache: None }
+    }
+
+    /// Set the static files to read the finalized blocks from.
+    pub fn with_static_files(mut self, static_files: StaticFileProvider) -> Self {
+        self.static_files = Some(static_files);
+        self
+    }
+
+    /// Set the cache to read the latest state through.
+    ///
+    /// The changes of the latest state must be reported to the cache, see [StateCache].
+    pub fn with_state_cache(mut self, state_cache: StateCache) -> Self {
+        self.state_cache = Some(state_cache);
+        self
+    }
+
+    /// Returns the value of `key` in the static files, if any.
+    fn static_file<T: StaticFileTable>(&self, key: u64) -> Result<Option<T::Value>> {
+        match &self.static_files {
+            Some(static_files) => Ok(static_files.get::<T>(key)?),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<DB: Database> ShareableDatabase<DB> {
+    /// Returns the body of the block `number`, from the database or the static files.
+    fn block_body(&self, number: BlockNumber) -> Result<Option<StoredBlockBody>> {
+        match self.db.view(|tx| tx.get::<tables::BlockBodies>(number))?? {
+            Some(body) => Ok(Some(body)),
+            None => self.static_file::<tables::BlockBodies>(number),
+        }
     }
 }
 
 impl<DB: Clone> Clone for ShareableDatabase<DB> {
     fn clone(&self) -> Self {
-        Self { db: self.db.clone() }
+        Self {
+            db: self.db.clone(),
+            static_files: self.static_files.clone(),
+            state_cache: self.state_cache.clone(),
+        }
     }
 }
 
 impl<DB: Database> HeaderProvider for ShareableDatabase<DB> {
     fn header(&self, block_hash: &BlockHash) -> Result<Option<Header>> {
-        self.db.view(|tx| {
-            if let Some(num) = tx.get::<tables::HeaderNumbers>(*block_hash)? {
-                Ok(tx.get::<tables::Headers>(num)?)
-            } else {
-                Ok(None)
-            }
-        })?
+        let Some(num) = self.block_number(*block_hash)? else { return Ok(None) };
+        match self.db.view(|tx| tx.get::<tables::Headers>(num))?? {
+            Some(header) => Ok(Some(header)),
+            None => self.static_file::<tables::Headers>(num),
+        }
     }
 
     fn header_by_number(&self, num: BlockNumber) -> Result<Option<Header>> {
@@ -67,15 +122,30 @@ impl<DB: Database> HeaderProvider for ShareableDatabase<DB> {
     }
 
     fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> Result<Vec<Header>> {
-        self.db
-            .view(|tx| {
-                let mut cursor = tx.cursor_read::<tables::Headers>()?;
-                cursor
-                    .walk_range(range)?
-                    .map(|result| result.map(|(_, header)| header).map_err(Into::into))
-                    .collect::<Result<Vec<_>>>()
-            })?
-            .map_err(Into::into)
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+
+        // The oldest headers may have been moved to the static files, and the ones still in the
+        // database after being moved are skipped.
+        let (mut headers, highest_static) = match &self.static_files {
+            Some(static_files) => (
+                static_files.get_range::<tables::Headers>(range)?,
+                static_files.highest(tables::Headers::SEGMENT),
+            ),
+            None => (Vec::new(), None),
+        };
+
+        self.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<tables::Headers>()?;
+            for result in cursor.walk_range(range)? {
+                let (num, header) = result?;
+                if highest_static.map_or(true, |highest| num > highest) {
+                    headers.push((num, header));
+                }
+            }
+            Ok::<_, reth_interfaces::db::Error>(())
+        })??;
+
+        Ok(headers.into_iter().map(|(_, header)| header).collect())
     }
 }
 
@@ -99,9 +169,34 @@ impl<DB: Database> BlockProvider for ShareableDatabase<DB> {
         Ok(ChainInfo { best_hash, best_number, last_finalized: None, safe_finalized: None })
     }
 
-    fn block(&self, _id: BlockId) -> Result<Option<Block>> {
-        // TODO
-        Ok(None)
+    fn block(&self, id: BlockId) -> Result<Option<Block>> {
+        let Some(number) = self.block_number_for_id(id)? else { return Ok(None) };
+        let Some(header) = self.header_by_number(number)? else { return Ok(None) };
+        let Some(body) = self.block_body(number)? else { return Ok(None) };
+
+        let Some(transactions) = body
+            .tx_id_range()
+            .map(|id| self.transaction_by_id(id))
+            .collect::<Result<Option<Vec<_>>>>()?
+        else {
+            return Ok(None)
+        };
+
+        let (ommers, withdrawals) = self.db.view(|tx| {
+            let ommers = tx.get::<tables::BlockOmmers>(number)?.map(|o| o.ommers);
+            let withdrawals = tx.get::<tables::BlockWithdrawals>(number)?.map(|w| w.withdrawals);
+            Ok::<_, reth_interfaces::db::Error>((ommers, withdrawals))
+        })??;
+        // Blocks before Shanghai have no withdrawals, and empty ones are not stored.
+        let withdrawals =
+            header.withdrawals_root.is_some().then(|| withdrawals.unwrap_or_default());
+
+        Ok(Some(Block {
+            header,
+            body: transactions,
+            ommers: ommers.unwrap_or_default(),
+            withdrawals,
+        }))
     }
 
     fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
@@ -109,22 +204,146 @@ impl<DB: Database> BlockProvider for ShareableDatabase<DB> {
     }
 }
 
+impl<DB: Database> ReceiptProvider for ShareableDatabase<DB> {
+    fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>> {
+        match self.db.view(|tx| tx.get::<tables::Receipts>(id))?? {
+            Some(receipt) => Ok(Some(receipt)),
+            None => self.static_file::<tables::Receipts>(id),
+        }
+    }
+
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        let number = match block {
+            BlockHashOrNumber::Hash(hash) => match self.block_number(hash)? {
+                Some(number) => number,
+                None => return Ok(None),
+            },
+            BlockHashOrNumber::Number(number) => number,
+        };
+        let Some(body) = self.block_body(number)? else { return Ok(None) };
+
+        // If any receipt of the block was pruned, none are returned.
+        body.tx_id_range().map(|id| self.receipt(id)).collect::<Result<Option<Vec<_>>>>()
+    }
+}
+
+impl<DB: Database> TransactionsProvider for ShareableDatabase<DB> {
+    fn transaction_id(&self, tx_hash: TxHash) -> Result<Option<TxNumber>> {
+        Ok(self.db.view(|tx| tx.get::<tables::TxHashNumber>(tx_hash))??)
+    }
+
+    fn transaction_by_id(&self, id: TxNumber) -> Result<Option<TransactionSigned>> {
+        match self.db.view(|tx| tx.get::<tables::Transactions>(id))?? {
+            Some(transaction) => Ok(Some(transaction)),
+            None => self.static_file::<tables::Transactions>(id),
+        }
+    }
+
+    fn transaction_block(&self, id: TxNumber) -> Result<Option<BlockNumber>> {
+        let last_in_db = self
+            .db
+            .view(|tx| tx.cursor_read::<tables::BlockBodies>()?.last())??
+            .map(|(number, _)| number);
+        let last = match (last_in_db, &self.static_files) {
+            (Some(last), _) => last,
+            (None, Some(static_files)) => {
+                match static_files.highest(tables::BlockBodies::SEGMENT) {
+                    Some(last) => last,
+                    None => return Ok(None),
+                }
+            }
+            (None, None) => return Ok(None),
+        };
+
+        // The blocks are ordered by their transactions, so the block is found by a binary search.
+        let (mut low, mut high) = (0, last);
+        while low <= high {
+            let mid = low + (high - low) / 2;
+            let Some(body) = self.block_body(mid)? else { return Ok(None) };
+            if id < body.start_tx_id {
+                let Some(below) = mid.checked_sub(1) else { return Ok(None) };
+                high = below;
+            } else if id >= body.start_tx_id + body.tx_count {
+                low = mid + 1;
+            } else {
+                return Ok(Some(mid))
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<DB: Database> StateTrieProvider for ShareableDatabase<DB> {
+    fn account_trie_node(&self, hash: H256) -> Result<Option<Bytes>> {
+        Ok(self.db.view(|tx| tx.get::<tables::AccountsTrie>(hash))??.map(Bytes::from))
+    }
+
+    fn storage_trie_node(&self, hashed_address: H256, hash: H256) -> Result<Option<Bytes>> {
+        let entry = self.db.view(|tx| {
+            tx.cursor_dup_read::<tables::StoragesTrie>()?.seek_by_key_subkey(hashed_address, hash)
+        })??;
+        // the cursor is positioned at the first node with a hash >= the requested one
+        Ok(entry.filter(|entry| entry.hash == hash).map(|entry| Bytes::from(entry.node)))
+    }
+
+    fn hashed_accounts(&self, start: H256, limit: usize) -> Result<Vec<(H256, Account)>> {
+        Ok(self.db.view(|tx| {
+            tx.cursor_read::<tables::HashedAccount>()?
+                .walk(Some(start))?
+                .take(limit)
+                .collect::<std::result::Result<Vec<_>, _>>()
+        })??)
+    }
+
+    fn hashed_storage(
+        &self,
+        hashed_address: H256,
+        start: H256,
+        limit: usize,
+    ) -> Result<Vec<(H256, U256)>> {
+        Ok(self.db.view(|tx| {
+            tx.cursor_dup_read::<tables::HashedStorage>()?
+                .walk_dup(Some(hashed_address), Some(start))?
+                .take(limit)
+                .map(|result| result.map(|(_, entry)| (entry.key, entry.value)))
+                .collect::<std::result::Result<Vec<_>, _>>()
+        })??)
+    }
+
+    fn state_root_with_changes(
+        &self,
+        previous_root: H256,
+        changes: &HashedStateChanges,
+    ) -> Result<H256> {
+        trie::state_root_with_changes(&self.db, previous_root, changes)
+    }
+}
+
 impl<DB: Database> StateProviderFactory for ShareableDatabase<DB> {
-    type HistorySP<'a> = HistoricalStateProvider<'a,<DB as DatabaseGAT<'a>>::TX> where Self: 'a;
-    type LatestSP<'a> = LatestStateProvider<'a,<DB as DatabaseGAT<'a>>::TX> where Self: 'a;
+    type HistorySP<'a>
+        = HistoricalStateProvider<'a, <DB as DatabaseGAT<'a>>::TX>
+    where
+        Self: 'a;
+    type LatestSP<'a>
+        = CachedStateProvider<LatestStateProvider<'a, <DB as DatabaseGAT<'a>>::TX>>
+    where
+        Self: 'a;
     /// Storage provider for latest block
     fn latest(&self) -> Result<Self::LatestSP<'_>> {
-        Ok(LatestStateProvider::new(self.db.tx()?))
+        match &self.state_cache {
+            Some(cache) => {
+                // The generation is read before the transaction is opened, see
+                // `StateCache::generation`
+                let generation = cache.generation();
+                Ok(cache.provider(LatestStateProvider::new(self.db.tx()?), generation))
+            }
+            None => Ok(CachedStateProvider::uncached(LatestStateProvider::new(self.db.tx()?))),
+        }
     }
 
     fn history_by_block_number(&self, block_number: BlockNumber) -> Result<Self::HistorySP<'_>> {
         let tx = self.db.tx()?;
-
-        // get transition id
-        let transition = tx
-            .get::<tables::BlockTransitionIndex>(block_number)?
-            .ok_or(Error::BlockTransition { block_number })?;
-
+        let transition = history_transition(&tx, block_number)?;
         Ok(HistoricalStateProvider::new(tx, transition))
     }
 
@@ -133,23 +352,53 @@ impl<DB: Database> StateProviderFactory for ShareableDatabase<DB> {
         // get block number
         let block_number =
             tx.get::<tables::HeaderNumbers>(block_hash)?.ok_or(Error::BlockHash { block_hash })?;
+        let transition = history_transition(&tx, block_number)?;
+        Ok(HistoricalStateProvider::new(tx, transition))
+    }
+}
 
-        // get transition id
-        let transition = tx
-            .get::<tables::BlockTransitionIndex>(block_number)?
-            .ok_or(Error::BlockTransition { block_number })?;
+/// The parts of the pruner whose checkpoints bound the readable history, by their id in
+/// [tables::PruneCheckpoints].
+const HISTORY_PRUNE_PARTS: [&str; 2] = ["AccountHistory", "StorageHistory"];
 
-        Ok(HistoricalStateProvider::new(tx, transition))
+/// Get the transition the state of the block is read from the history at.
+///
+/// The changesets of the blocks below the highest pruned block of the account or storage history
+/// are deleted, so their state can not be read anymore.
+fn history_transition<'a>(tx: &impl DbTx<'a>, block_number: BlockNumber) -> Result<TransitionId> {
+    for part in HISTORY_PRUNE_PARTS {
+        let checkpoint = tx.get::<tables::PruneCheckpoints>(part.as_bytes().to_vec())?;
+        if let Some(checkpoint) = checkpoint.filter(|checkpoint| block_number < *checkpoint) {
+            return Err(Error::HistoryPruned { block_number, checkpoint }.into())
+        }
     }
+
+    Ok(tx
+        .get::<tables::BlockTransitionIndex>(block_number)?
+        .ok_or(Error::BlockTransition { block_number })?)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{BlockProvider, StateProviderFactory};
+    use crate::{
+        BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, TransactionsProvider,
+    };
 
     use super::ShareableDatabase;
-    use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
-    use reth_primitives::H256;
+    use crate::Error;
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        models::StoredBlockBody,
+        table::{Compress, Table},
+        tables,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{
+        rpc::BlockId, Block, BlockHashOrNumber, Header, Receipt, Transaction, TransactionSigned,
+        TxLegacy, H256,
+    };
+    use reth_static_file::{StaticFileProvider, StaticFileSegment, StaticFileWriter};
 
     #[test]
     fn common_history_provider() {
@@ -158,6 +407,25 @@ mod tests {
         let _ = provider.latest();
     }
 
+    #[test]
+    fn history_below_prune_checkpoint() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let tx = db.tx_mut().unwrap();
+        for number in 0..4 {
+            tx.put::<tables::BlockTransitionIndex>(number, number).unwrap();
+        }
+        tx.put::<tables::PruneCheckpoints>(b"StorageHistory".to_vec(), 2).unwrap();
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db);
+        assert_eq!(
+            provider.history_by_block_number(1).err(),
+            Some(Error::HistoryPruned { block_number: 1, checkpoint: 2 }.into())
+        );
+        assert!(provider.history_by_block_number(2).is_ok());
+        assert!(provider.history_by_block_number(3).is_ok());
+    }
+
     #[test]
     fn default_chain_info() {
         let db = create_test_db::<WriteMap>(EnvKind::RW);
@@ -169,4 +437,123 @@ mod tests {
         assert_eq!(chain_info.last_finalized, None);
         assert_eq!(chain_info.safe_finalized, None);
     }
+
+    #[test]
+    fn receipts_by_block() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let receipt = |cumulative_gas_used| Receipt { cumulative_gas_used, ..Default::default() };
+
+        // Block 0 has receipts 0 and 1, block 1 has receipt 2 of which the receipt was pruned
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::BlockBodies>(0, StoredBlockBody { start_tx_id: 0, tx_count: 2 }).unwrap();
+        tx.put::<tables::BlockBodies>(1, StoredBlockBody { start_tx_id: 2, tx_count: 1 }).unwrap();
+        tx.put::<tables::Receipts>(0, receipt(1)).unwrap();
+        tx.put::<tables::Receipts>(1, receipt(2)).unwrap();
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db);
+        assert_eq!(provider.receipt(1), Ok(Some(receipt(2))));
+        assert_eq!(
+            provider.receipts_by_block(BlockHashOrNumber::Number(0)),
+            Ok(Some(vec![receipt(1), receipt(2)]))
+        );
+        assert_eq!(provider.receipts_by_block(BlockHashOrNumber::Number(1)), Ok(None));
+        assert_eq!(provider.receipts_by_block(BlockHashOrNumber::Number(2)), Ok(None));
+        assert_eq!(provider.receipts_by_block(BlockHashOrNumber::Hash(H256::zero())), Ok(None));
+    }
+
+    #[test]
+    fn blocks_and_transactions() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let transaction = |nonce| {
+            let mut tx = TransactionSigned {
+                transaction: Transaction::Legacy(TxLegacy { nonce, ..Default::default() }),
+                ..Default::default()
+            };
+            tx.hash = tx.recalculate_hash();
+            tx
+        };
+        let transactions = (0..3).map(transaction).collect::<Vec<_>>();
+
+        // Block 0 has transactions 0 and 1, block 1 is empty and block 2 has transaction 2
+        let bodies = [(0, 2), (2, 0), (2, 1)];
+        let tx = db.tx_mut().unwrap();
+        for (number, (start_tx_id, tx_count)) in bodies.into_iter().enumerate() {
+            let header = Header { number: number as u64, ..Default::default() }.seal_slow();
+            tx.put::<tables::CanonicalHeaders>(header.number, header.hash()).unwrap();
+            tx.put::<tables::HeaderNumbers>(header.hash(), header.number).unwrap();
+            tx.put::<tables::Headers>(header.number, header.unseal()).unwrap();
+            tx.put::<tables::BlockBodies>(number as u64, StoredBlockBody { start_tx_id, tx_count })
+                .unwrap();
+        }
+        for (id, transaction) in transactions.iter().enumerate() {
+            tx.put::<tables::Transactions>(id as u64, transaction.clone()).unwrap();
+            tx.put::<tables::TxHashNumber>(transaction.hash, id as u64).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db);
+        let block = |number: u64| provider.block(BlockId::Number(number.into())).unwrap();
+        assert_eq!(
+            block(0),
+            Some(Block {
+                header: Header { number: 0, ..Default::default() },
+                body: transactions[..2].to_vec(),
+                ommers: vec![],
+                withdrawals: None,
+            })
+        );
+        assert_eq!(block(1).unwrap().body, vec![]);
+        assert_eq!(block(2).unwrap().body, transactions[2..].to_vec());
+        assert_eq!(block(3), None);
+
+        assert_eq!(
+            provider.transaction_by_hash(transactions[2].hash),
+            Ok(Some(transactions[2].clone()))
+        );
+        assert_eq!(provider.transaction_by_hash(H256::zero()), Ok(None));
+        assert_eq!(provider.transaction_block(0), Ok(Some(0)));
+        assert_eq!(provider.transaction_block(1), Ok(Some(0)));
+        assert_eq!(provider.transaction_block(2), Ok(Some(2)));
+        assert_eq!(provider.transaction_block(3), Ok(None));
+    }
+
+    #[test]
+    fn headers_from_static_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let headers = (0..4)
+            .map(|number| Header { number, ..Default::default() }.seal_slow())
+            .collect::<Vec<_>>();
+
+        // Headers 0 and 1 were moved to the static files, 1 is still in the database
+        let static_files = StaticFileProvider::open(dir.path()).unwrap();
+        let mut writer = StaticFileWriter::new(dir.path(), StaticFileSegment::Headers, 0).unwrap();
+        let tx = db.tx_mut().unwrap();
+        for header in &headers {
+            let stored =
+                tables::Headers::COMPRESSION.compress(header.as_ref().clone().compress().as_ref());
+            if header.number < 2 {
+                writer.append(Some(&stored)).unwrap();
+            }
+            if header.number > 0 {
+                tx.put::<tables::Headers>(header.number, header.as_ref().clone()).unwrap();
+            }
+            tx.put::<tables::HeaderNumbers>(header.hash(), header.number).unwrap();
+            tx.put::<tables::CanonicalHeaders>(header.number, header.hash()).unwrap();
+        }
+        static_files.insert(writer.finish().unwrap());
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db.clone());
+        assert_eq!(provider.header_by_number(0), Ok(None));
+
+        let provider = provider.with_static_files(static_files);
+        assert_eq!(provider.header_by_number(0), Ok(Some(headers[0].as_ref().clone())));
+        assert_eq!(provider.header(&headers[3].hash()), Ok(Some(headers[3].as_ref().clone())));
+        assert_eq!(
+            provider.headers_range(..).unwrap(),
+            headers.iter().map(|header| header.as_ref().clone()).collect::<Vec<_>>()
+        );
+    }
 }
@@ -0,0 +1,266 @@
+use crate::{AccountProvider, BlockHashProvider, StateProvider};
+use lru::LruCache;
+use reth_interfaces::Result;
+use reth_primitives::{Account, Address, Bytes, StorageKey, StorageValue, H256, U256};
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+/// The sizes of the caches of a [StateCache].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateCacheConfig {
+    /// The maximum number of cached accounts.
+    pub max_accounts: NonZeroUsize,
+    /// The maximum number of cached storage slots.
+    pub max_storage_slots: NonZeroUsize,
+    /// The maximum number of cached bytecodes.
+    pub max_bytecodes: NonZeroUsize,
+}
+
+impl Default for StateCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_accounts: NonZeroUsize::new(100_000).expect("not zero"),
+            max_storage_slots: NonZeroUsize::new(1_000_000).expect("not zero"),
+            max_bytecodes: NonZeroUsize::new(10_000).expect("not zero"),
+        }
+    }
+}
+
+/// The cached values of a [StateCache].
+#[derive(Debug)]
+struct CachedState {
+    /// Incremented on every invalidation, see [StateCache::provider].
+    generation: u64,
+    /// Accounts, `None` if the account does not exist.
+    accounts: LruCache<Address, Option<Account>>,
+    /// Storage slots, `None` if the slot is not set.
+    storage: LruCache<(Address, StorageKey), Option<StorageValue>>,
+    /// Bytecodes by their hash, which never change.
+    bytecodes: LruCache<H256, Option<Bytes>>,
+}
+
+/// LRU caches of the accounts, storage slots and bytecodes of the latest state, shared by all
+/// the [CachedStateProvider]s created with [StateCache::provider], e.g. across RPC calls and
+/// payload building.
+///
+/// Clones share the same caches.
+///
+/// The cache does not observe the database, so every change of the latest state must be reported
+/// with [StateCache::invalidate], usually for every canonical state notification. The changes of
+/// the pipeline are not notified, so it must clear the cache whenever it commits.
+#[derive(Debug, Clone)]
+pub struct StateCache {
+    state: Arc<Mutex<CachedState>>,
+}
+
+impl Default for StateCache {
+    fn default() -> Self {
+        Self::new(StateCacheConfig::default())
+    }
+}
+
+impl StateCache {
+    /// Creates new empty caches with the given sizes.
+    pub fn new(config: StateCacheConfig) -> Self {
+        let state = CachedState {
+            generation: 0,
+            accounts: LruCache::new(config.max_accounts),
+            storage: LruCache::new(config.max_storage_slots),
+            bytecodes: LruCache::new(config.max_bytecodes),
+        };
+        Self { state: Arc::new(Mutex::new(state)) }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CachedState> {
+        self.state.lock().expect("state cache lock is not poisoned")
+    }
+
+    /// Returns the current generation of the cache, which is passed to
+    /// [StateCache::provider].
+    ///
+    /// The generation must be read before the inner provider is opened: if the latest state
+    /// changes in between, the provider bypasses the cache instead of caching the values it read
+    /// from the outdated state.
+    pub fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    /// Layers the cache on top of a provider of the latest state, opened after the `generation`
+    /// was read with [StateCache::generation].
+    pub fn provider<SP: StateProvider>(
+        &self,
+        inner: SP,
+        generation: u64,
+    ) -> CachedStateProvider<SP> {
+        CachedStateProvider { inner, cache: Some(self.clone()), generation }
+    }
+
+    /// Evicts the changed accounts and storage slots, and the whole storage of the accounts whose
+    /// storage was wiped.
+    ///
+    /// Bytecodes are cached by their hash, so they are never evicted by a change.
+    pub fn invalidate(
+        &self,
+        accounts: impl IntoIterator<Item = Address>,
+        storage: impl IntoIterator<Item = (Address, StorageKey)>,
+        wiped_storage: impl IntoIterator<Item = Address>,
+    ) {
+        let mut state = self.lock();
+        state.generation += 1;
+        for address in accounts {
+            state.accounts.pop(&address);
+        }
+        for slot in storage {
+            state.storage.pop(&slot);
+        }
+
+        let wiped_storage = wiped_storage.into_iter().collect::<Vec<_>>();
+        if !wiped_storage.is_empty() {
+            let wiped_slots = state
+                .storage
+                .iter()
+                .filter(|((address, _), _)| wiped_storage.contains(address))
+                .map(|(slot, _)| *slot)
+                .collect::<Vec<_>>();
+            for slot in wiped_slots {
+                state.storage.pop(&slot);
+            }
+        }
+    }
+
+    /// Evicts all accounts and storage slots, e.g. if the changes of the latest state are not
+    /// known.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.generation += 1;
+        state.accounts.clear();
+        state.storage.clear();
+    }
+}
+
+/// State provider that reads the values of the latest state from a [StateCache] before falling
+/// back to the inner [StateProvider], and caches the values it reads from the inner provider.
+///
+/// If the latest state changed after the provider was created, the cache is bypassed.
+pub struct CachedStateProvider<SP: StateProvider> {
+    /// The provider of the latest state.
+    inner: SP,
+    /// The cache, `None` if caching is disabled.
+    cache: Option<StateCache>,
+    /// The generation of the cache when the inner provider was opened.
+    generation: u64,
+}
+
+impl<SP: StateProvider> CachedStateProvider<SP> {
+    /// Create a provider that does not cache, and only forwards to the inner provider.
+    pub fn uncached(inner: SP) -> Self {
+        Self { inner, cache: None, generation: 0 }
+    }
+
+    /// Returns the cached values if the state of the provider is the latest one.
+    fn cached(&self) -> Option<MutexGuard<'_, CachedState>> {
+        let state = self.cache.as_ref()?.lock();
+        (state.generation == self.generation).then_some(state)
+    }
+}
+
+impl<SP: StateProvider> AccountProvider for CachedStateProvider<SP> {
+    /// Get basic account information.
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        if let Some(account) =
+            self.cached().and_then(|mut cached| cached.accounts.get(&address).copied())
+        {
+            return Ok(account)
+        }
+        let account = self.inner.basic_account(address)?;
+        if let Some(mut cached) = self.cached() {
+            cached.accounts.put(address, account);
+        }
+        Ok(account)
+    }
+}
+
+impl<SP: StateProvider> BlockHashProvider for CachedStateProvider<SP> {
+    /// Get block hash by number.
+    fn block_hash(&self, number: U256) -> Result<Option<H256>> {
+        self.inner.block_hash(number)
+    }
+}
+
+impl<SP: StateProvider> StateProvider for CachedStateProvider<SP> {
+    /// Get storage.
+    fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
+        let slot = (account, storage_key);
+        if let Some(value) = self.cached().and_then(|mut cached| cached.storage.get(&slot).copied())
+        {
+            return Ok(value)
+        }
+        let value = self.inner.storage(account, storage_key)?;
+        if let Some(mut cached) = self.cached() {
+            cached.storage.put(slot, value);
+        }
+        Ok(value)
+    }
+
+    /// Get account code by its hash
+    fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytes>> {
+        // Bytecodes never change, so they are cached regardless of the generation
+        let Some(cache) = &self.cache else { return self.inner.bytecode_by_hash(code_hash) };
+        if let Some(bytecode) = cache.lock().bytecodes.get(&code_hash).cloned() {
+            return Ok(bytecode)
+        }
+        let bytecode = self.inner.bytecode_by_hash(code_hash)?;
+        if bytecode.is_some() {
+            cache.lock().bytecodes.put(code_hash, bytecode.clone());
+        }
+        Ok(bytecode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ExtendedAccount, MockEthProvider};
+
+    #[test]
+    fn cache_and_invalidate() {
+        let (address, other) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let slot = H256::from_low_u64_be(1);
+        let state = MockEthProvider::default();
+        state.add_account(
+            address,
+            ExtendedAccount::new(1, U256::from(10)).extend_storage([(slot, U256::from(5))]),
+        );
+
+        let cache = StateCache::default();
+        let provider = cache.provider(state.clone(), cache.generation());
+        assert_eq!(provider.basic_account(address).unwrap().map(|account| account.nonce), Some(1));
+        assert_eq!(provider.storage(address, slot).unwrap(), Some(U256::from(5)));
+        assert_eq!(provider.basic_account(other).unwrap(), None);
+
+        // The cached values are read even if the inner state changed
+        state.add_account(address, ExtendedAccount::new(2, U256::from(10)));
+        state.add_account(other, ExtendedAccount::new(3, U256::ZERO));
+        let provider = cache.provider(state.clone(), cache.generation());
+        assert_eq!(provider.basic_account(address).unwrap().map(|account| account.nonce), Some(1));
+        assert_eq!(provider.basic_account(other).unwrap(), None);
+
+        // Providers created before an invalidation bypass the cache
+        let outdated = cache.provider(state.clone(), cache.generation());
+        cache.invalidate([address], [], [address]);
+        assert_eq!(
+            outdated.basic_account(other).unwrap(),
+            Some(Account { nonce: 3, ..Default::default() })
+        );
+        let provider = cache.provider(state.clone(), cache.generation());
+        assert_eq!(provider.basic_account(address).unwrap().map(|account| account.nonce), Some(2));
+        assert_eq!(provider.storage(address, slot).unwrap(), None);
+        assert_eq!(provider.basic_account(other).unwrap(), None);
+
+        cache.clear();
+        let provider = cache.provider(state, cache.generation());
+        assert_eq!(provider.basic_account(other).unwrap().map(|account| account.nonce), Some(3));
+    }
+}
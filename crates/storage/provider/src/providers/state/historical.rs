@@ -43,12 +43,14 @@ impl<'a, 'b, TX: DbTx<'a>> AccountProvider for HistoricalStateProviderRef<'a, 'b
         // history key to search IntegerList of transition id changesets.
         let history_key = ShardedKey::new(address, self.transition);
 
-        let Some(changeset_transition_id) = self.tx.cursor_read::<tables::AccountHistory>()?
+        let changeset_transition_id = self
+            .tx
+            .cursor_read::<tables::AccountHistory>()?
             .seek(history_key)?
-            .filter(|(key,_)| key.key == address)
-            .map(|(_,list)| list.0.enable_rank().successor(self.transition as usize).map(|i| i as u64)) else {
-                return Ok(None)
-            };
+            .filter(|(key, _)| key.key == address)
+            .and_then(|(_, list)| {
+                list.0.enable_rank().successor(self.transition as usize).map(|i| i as u64)
+            });
 
         // if changeset transition id is present we are getting value from changeset
         if let Some(changeset_transition_id) = changeset_transition_id {
@@ -62,8 +64,9 @@ impl<'a, 'b, TX: DbTx<'a>> AccountProvider for HistoricalStateProviderRef<'a, 'b
                 })?;
             Ok(account.info)
         } else {
-            // if changeset is not present that means that there was history shard but we need to
-            // use newest value from plain state
+            // if changeset is not present that means that the account did not change after the
+            // transition, e.g. genesis accounts which have no history, so we need to use newest
+            // value from plain state
             Ok(self.tx.get::<tables::PlainAccountState>(address)?)
         }
     }
@@ -82,12 +85,14 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for HistoricalStateProviderRef<'a, 'b,
         // history key to search IntegerList of transition id changesets.
         let history_key = StorageShardedKey::new(address, storage_key, self.transition);
 
-        let Some(changeset_transition_id) = self.tx.cursor_read::<tables::StorageHistory>()?
+        let changeset_transition_id = self
+            .tx
+            .cursor_read::<tables::StorageHistory>()?
             .seek(history_key)?
-            .filter(|(key,_)| key.address == address && key.sharded_key.key == storage_key)
-            .map(|(_,list)| list.0.enable_rank().successor(self.transition as usize).map(|i| i as u64)) else {
-                return Ok(None)
-            };
+            .filter(|(key, _)| key.address == address && key.sharded_key.key == storage_key)
+            .and_then(|(_, list)| {
+                list.0.enable_rank().successor(self.transition as usize).map(|i| i as u64)
+            });
 
         // if changeset transition id is present we are getting value from changeset
         if let Some(changeset_transition_id) = changeset_transition_id {
@@ -102,13 +107,14 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for HistoricalStateProviderRef<'a, 'b,
                 })?;
             Ok(Some(storage_entry.value))
         } else {
-            // if changeset is not present that means that there was history shard but we need to
-            // use newest value from plain state
+            // if changeset is not present that means that the slot did not change after the
+            // transition, so we need to use newest value from plain state
             Ok(self
                 .tx
                 .cursor_dup_read::<tables::PlainStorageState>()?
-                .seek_by_key_subkey(address, storage_key)
-                .map(|r| r.map(|entry| entry.value))?)
+                .seek_by_key_subkey(address, storage_key)?
+                .filter(|entry| entry.key == storage_key)
+                .map(|entry| entry.value))
         }
     }
 
@@ -160,6 +166,7 @@ mod tests {
     use reth_primitives::{hex_literal::hex, Account, StorageEntry, H160, H256, U256};
 
     const ADDRESS: H160 = H160(hex!("0000000000000000000000000000000000000001"));
+    const GENESIS_ADDRESS: H160 = H160(hex!("0000000000000000000000000000000000000002"));
     const STORAGE: H256 =
         H256(hex!("0000000000000000000000000000000000000000000000000000000000000001"));
 
@@ -214,7 +221,9 @@ mod tests {
         .unwrap();
 
         // setup plain state
+        let acc_genesis = Account { nonce: 1, balance: U256::ZERO, bytecode_hash: None };
         tx.put::<tables::PlainAccountState>(ADDRESS, acc_plain).unwrap();
+        tx.put::<tables::PlainAccountState>(GENESIS_ADDRESS, acc_genesis).unwrap();
         tx.commit().unwrap();
 
         let tx = db.tx().unwrap();
@@ -252,6 +261,13 @@ mod tests {
             HistoricalStateProviderRef::new(&tx, 16).basic_account(ADDRESS),
             Ok(Some(acc_plain))
         );
+
+        // accounts without history did not change since genesis
+        assert_eq!(
+            HistoricalStateProviderRef::new(&tx, 1).basic_account(GENESIS_ADDRESS),
+            Ok(Some(acc_genesis))
+        );
+        assert_eq!(HistoricalStateProviderRef::new(&tx, 1).basic_account(H160::zero()), Ok(None));
     }
 
     #[test]
@@ -289,7 +305,9 @@ mod tests {
         tx.put::<tables::StorageChangeSet>((15, ADDRESS).into(), entry_at15).unwrap();
 
         // setup plain state
+        let entry_genesis = StorageEntry { key: STORAGE, value: U256::from(1) };
         tx.put::<tables::PlainStorageState>(ADDRESS, entry_plain).unwrap();
+        tx.put::<tables::PlainStorageState>(GENESIS_ADDRESS, entry_genesis).unwrap();
         tx.commit().unwrap();
 
         let tx = db.tx().unwrap();
@@ -327,5 +345,15 @@ mod tests {
             HistoricalStateProviderRef::new(&tx, 16).storage(ADDRESS, STORAGE),
             Ok(Some(entry_plain.value))
         );
+
+        // slots without history did not change since genesis, and absent slots are empty
+        assert_eq!(
+            HistoricalStateProviderRef::new(&tx, 1).storage(GENESIS_ADDRESS, STORAGE),
+            Ok(Some(entry_genesis.value))
+        );
+        assert_eq!(
+            HistoricalStateProviderRef::new(&tx, 1).storage(ADDRESS, H256::zero()),
+            Ok(None)
+        );
     }
 }
@@ -1,5 +1,8 @@
 //! [StateProvider](crate::StateProvider) implementations
+pub(crate) mod cached;
 pub(crate) mod chain;
 pub(crate) mod historical;
 pub(crate) mod latest;
 pub(crate) mod macros;
+pub(crate) mod overlay;
+pub(crate) mod prefetch;
@@ -0,0 +1,218 @@
+use crate::{
+    trie::{HashedStateChanges, HashedStorageChanges},
+    AccountProvider, BlockHashProvider, StateProvider,
+};
+use reth_interfaces::Result;
+use reth_primitives::{
+    keccak256, Account, Address, BlockNumber, Bytes, StorageKey, StorageValue, H256, U256,
+};
+use std::collections::HashMap;
+
+/// In-memory state changes that are not committed to the database, e.g. those of a block that is
+/// being built or of the pending block.
+#[derive(Debug, Clone, Default)]
+pub struct StateOverlay {
+    /// Changed accounts, `None` if the account was destroyed.
+    accounts: HashMap<Address, Option<Account>>,
+    /// Changed storages.
+    storages: HashMap<Address, OverlayStorage>,
+    /// New bytecodes by their hash.
+    bytecodes: HashMap<H256, Bytes>,
+    /// Hashes of the blocks which are not in the database.
+    block_hashes: HashMap<BlockNumber, H256>,
+}
+
+/// Changed storage of an account.
+#[derive(Debug, Clone, Default)]
+struct OverlayStorage {
+    /// Whether the storage in the database was wiped, e.g. on selfdestruct.
+    wiped: bool,
+    /// Changed slots, a zero value if the slot was cleared.
+    slots: HashMap<StorageKey, StorageValue>,
+}
+
+impl StateOverlay {
+    /// Set the account at `address`, `None` if it was destroyed.
+    pub fn insert_account(&mut self, address: Address, account: Option<Account>) {
+        self.accounts.insert(address, account);
+    }
+
+    /// Set the value of a storage slot of `address`.
+    pub fn insert_storage(&mut self, address: Address, key: StorageKey, value: StorageValue) {
+        self.storages.entry(address).or_default().slots.insert(key, value);
+    }
+
+    /// Clear the whole storage of `address`, including the slots set so far.
+    pub fn wipe_storage(&mut self, address: Address) {
+        let storage = self.storages.entry(address).or_default();
+        storage.wiped = true;
+        storage.slots.clear();
+    }
+
+    /// Add a new bytecode.
+    pub fn insert_bytecode(&mut self, code_hash: H256, bytecode: Bytes) {
+        self.bytecodes.insert(code_hash, bytecode);
+    }
+
+    /// Set the hash of the block `number`.
+    pub fn insert_block_hash(&mut self, number: BlockNumber, hash: H256) {
+        self.block_hashes.insert(number, hash);
+    }
+
+    /// Returns the changes of the accounts and storages by hashed address and hashed slot, from
+    /// which the state root of the inner state with the overlay applied is computed.
+    pub fn hashed_changes(&self) -> HashedStateChanges {
+        let mut changes = HashedStateChanges::default();
+        for (address, account) in &self.accounts {
+            changes.accounts.insert(keccak256(address), *account);
+        }
+        for (address, storage) in &self.storages {
+            let slots =
+                storage.slots.iter().map(|(slot, value)| (keccak256(slot), *value)).collect();
+            changes
+                .storages
+                .insert(keccak256(address), HashedStorageChanges { wiped: storage.wiped, slots });
+        }
+        changes
+    }
+
+    /// Returns `true` if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() &&
+            self.storages.is_empty() &&
+            self.bytecodes.is_empty() &&
+            self.block_hashes.is_empty()
+    }
+}
+
+/// State provider that layers a [StateOverlay] on top of another [StateProvider], usually the
+/// latest one.
+///
+/// Values changed by the overlay are read from it, and the other ones from the inner provider.
+pub struct OverlayStateProvider<SP: StateProvider> {
+    /// The provider of the state before the changes.
+    inner: SP,
+    /// The changes on top of the inner state.
+    overlay: StateOverlay,
+}
+
+impl<SP: StateProvider> OverlayStateProvider<SP> {
+    /// Create new state provider
+    pub fn new(inner: SP, overlay: StateOverlay) -> Self {
+        Self { inner, overlay }
+    }
+
+    /// Returns the changes on top of the inner state.
+    pub fn overlay(&self) -> &StateOverlay {
+        &self.overlay
+    }
+
+    /// Returns a mutable reference to the changes on top of the inner state.
+    pub fn overlay_mut(&mut self) -> &mut StateOverlay {
+        &mut self.overlay
+    }
+
+    /// Consume the provider and return the inner provider and the changes.
+    pub fn into_parts(self) -> (SP, StateOverlay) {
+        (self.inner, self.overlay)
+    }
+}
+
+impl<SP: StateProvider> AccountProvider for OverlayStateProvider<SP> {
+    /// Get basic account information.
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        match self.overlay.accounts.get(&address) {
+            Some(account) => Ok(*account),
+            None => self.inner.basic_account(address),
+        }
+    }
+}
+
+impl<SP: StateProvider> BlockHashProvider for OverlayStateProvider<SP> {
+    /// Get block hash by number.
+    fn block_hash(&self, number: U256) -> Result<Option<H256>> {
+        let hash = u64::try_from(number).ok().and_then(|n| self.overlay.block_hashes.get(&n));
+        match hash {
+            Some(hash) => Ok(Some(*hash)),
+            None => self.inner.block_hash(number),
+        }
+    }
+}
+
+impl<SP: StateProvider> StateProvider for OverlayStateProvider<SP> {
+    /// Get storage.
+    fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
+        if let Some(storage) = self.overlay.storages.get(&account) {
+            if let Some(value) = storage.slots.get(&storage_key) {
+                return Ok((*value != U256::ZERO).then_some(*value))
+            }
+            if storage.wiped {
+                return Ok(None)
+            }
+        }
+        self.inner.storage(account, storage_key)
+    }
+
+    /// Get account code by its hash
+    fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytes>> {
+        match self.overlay.bytecodes.get(&code_hash) {
+            Some(bytecode) => Ok(Some(bytecode.clone())),
+            None => self.inner.bytecode_by_hash(code_hash),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ExtendedAccount, MockEthProvider};
+
+    #[test]
+    fn overlay_on_top_of_inner_state() {
+        let inner = MockEthProvider::default();
+        let (kept, changed, destroyed) =
+            (Address::from_low_u64_be(1), Address::from_low_u64_be(2), Address::from_low_u64_be(3));
+        let slot = |n| H256::from_low_u64_be(n);
+        for address in [kept, changed, destroyed] {
+            inner.add_account(
+                address,
+                ExtendedAccount::new(0, U256::from(1))
+                    .extend_storage([(slot(1), U256::from(1)), (slot(2), U256::from(2))]),
+            );
+        }
+
+        let mut overlay = StateOverlay::default();
+        assert!(overlay.is_empty());
+        let account = Account { nonce: 1, balance: U256::from(2), bytecode_hash: None };
+        overlay.insert_account(changed, Some(account));
+        overlay.insert_storage(changed, slot(1), U256::from(10));
+        overlay.insert_storage(changed, slot(2), U256::ZERO);
+        overlay.insert_account(destroyed, None);
+        overlay.wipe_storage(destroyed);
+        overlay.insert_block_hash(5, H256::from_low_u64_be(5));
+
+        let changes = overlay.hashed_changes();
+        assert_eq!(changes.accounts.get(&keccak256(changed)), Some(&Some(account)));
+        assert_eq!(changes.accounts.get(&keccak256(destroyed)), Some(&None));
+        let changed_slots = &changes.storages[&keccak256(changed)].slots;
+        assert_eq!(changed_slots.get(&keccak256(slot(2))), Some(&U256::ZERO));
+        assert!(changes.storages[&keccak256(destroyed)].wiped);
+
+        let provider = OverlayStateProvider::new(&inner, overlay);
+
+        assert_eq!(
+            provider.basic_account(kept).unwrap(),
+            Some(Account { nonce: 0, balance: U256::from(1), bytecode_hash: None })
+        );
+        assert_eq!(provider.basic_account(changed).unwrap(), Some(account));
+        assert_eq!(provider.basic_account(destroyed).unwrap(), None);
+
+        assert_eq!(provider.storage(kept, slot(1)).unwrap(), Some(U256::from(1)));
+        assert_eq!(provider.storage(changed, slot(1)).unwrap(), Some(U256::from(10)));
+        assert_eq!(provider.storage(changed, slot(2)).unwrap(), None);
+        assert_eq!(provider.storage(destroyed, slot(1)).unwrap(), None);
+
+        assert_eq!(provider.block_hash(U256::from(5)).unwrap(), Some(H256::from_low_u64_be(5)));
+        assert_eq!(provider.block_hash(U256::from(6)).unwrap(), None);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{AccountProvider, BlockHashProvider, StateProvider};
+use reth_interfaces::Result;
+use reth_primitives::{
+    Account, Address, Bytes, StorageKey, StorageValue, Transaction, TransactionKind, H256,
+    KECCAK_EMPTY, U256,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+
+/// The accounts and storage slots a block is expected to touch, see [PrefetchCache::prefetch].
+#[derive(Debug, Clone, Default)]
+pub struct PrefetchTargets {
+    /// Accounts to read, with their bytecode.
+    accounts: HashSet<Address>,
+    /// Storage slots to read.
+    storage: HashSet<(Address, StorageKey)>,
+}
+
+impl PrefetchTargets {
+    /// Add an account.
+    pub fn insert_account(&mut self, address: Address) {
+        self.accounts.insert(address);
+    }
+
+    /// Add the accounts and storage slots the transaction is known to touch: the signer, the
+    /// recipient and the entries of the access list.
+    pub fn insert_transaction(&mut self, transaction: &Transaction, signer: Address) {
+        self.accounts.insert(signer);
+        if let TransactionKind::Call(to) = transaction.kind() {
+            self.accounts.insert(*to);
+        }
+        for item in transaction.access_list().iter().flat_map(|list| list.0.iter()) {
+            self.accounts.insert(item.address);
+            self.storage.extend(item.storage_keys.iter().map(|key| (item.address, *key)));
+        }
+    }
+
+    /// Returns `true` if there is nothing to read.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.storage.is_empty()
+    }
+}
+
+/// State values read ahead of execution.
+#[derive(Debug, Default)]
+struct PrefetchedState {
+    /// Accounts, `None` if the account does not exist.
+    accounts: HashMap<Address, Option<Account>>,
+    /// Storage slots, `None` if the slot is not set.
+    storage: HashMap<(Address, StorageKey), Option<StorageValue>>,
+    /// Bytecodes by their hash.
+    bytecodes: HashMap<H256, Option<Bytes>>,
+}
+
+/// A cache of state values that is filled in the background ahead of execution, e.g. with the
+/// accounts of the next block while the current block executes, so reading them during execution
+/// doesn't wait on the database.
+///
+/// Clones share the same values.
+///
+/// The values are read from the provider [PrefetchCache::prefetch] is called with, which must
+/// provide the same state as the provider the cache is layered on with
+/// [PrefetchedStateProvider]. The changes made by execution must be kept above the cache, since
+/// the cache is never updated with them.
+#[derive(Debug, Clone, Default)]
+pub struct PrefetchCache {
+    state: Arc<RwLock<PrefetchedState>>,
+}
+
+impl PrefetchCache {
+    /// Read the targets from the provider into the cache, skipping the values that are cached
+    /// already.
+    pub fn prefetch<SP: StateProvider>(
+        &self,
+        provider: SP,
+        targets: PrefetchTargets,
+    ) -> Result<()> {
+        let PrefetchTargets { mut accounts, mut storage } = targets;
+        {
+            let state = self.state.read().expect("prefetch cache lock is not poisoned");
+            accounts.retain(|address| !state.accounts.contains_key(address));
+            storage.retain(|slot| !state.storage.contains_key(slot));
+        }
+
+        let mut prefetched = PrefetchedState::default();
+        for address in accounts {
+            let account = provider.basic_account(address)?;
+            if let Some(code_hash) = account.and_then(|account| account.bytecode_hash) {
+                if code_hash != KECCAK_EMPTY {
+                    prefetched.bytecodes.insert(code_hash, provider.bytecode_by_hash(code_hash)?);
+                }
+            }
+            prefetched.accounts.insert(address, account);
+        }
+        for (address, key) in storage {
+            prefetched.storage.insert((address, key), provider.storage(address, key)?);
+        }
+
+        let mut state = self.state.write().expect("prefetch cache lock is not poisoned");
+        state.accounts.extend(prefetched.accounts);
+        state.storage.extend(prefetched.storage);
+        state.bytecodes.extend(prefetched.bytecodes);
+        Ok(())
+    }
+
+    /// Returns the number of cached accounts.
+    pub fn accounts_len(&self) -> usize {
+        self.state.read().expect("prefetch cache lock is not poisoned").accounts.len()
+    }
+}
+
+/// State provider that reads the values of a [PrefetchCache] before falling back to the inner
+/// [StateProvider].
+pub struct PrefetchedStateProvider<SP: StateProvider> {
+    /// The provider of the values that are not prefetched.
+    inner: SP,
+    /// The prefetched values of the inner state.
+    cache: PrefetchCache,
+}
+
+impl<SP: StateProvider> PrefetchedStateProvider<SP> {
+    /// Create new state provider
+    pub fn new(inner: SP, cache: PrefetchCache) -> Self {
+        Self { inner, cache }
+    }
+
+    /// Returns the prefetched values of the inner state.
+    pub fn cache(&self) -> &PrefetchCache {
+        &self.cache
+    }
+}
+
+impl<SP: StateProvider> AccountProvider for PrefetchedStateProvider<SP> {
+    /// Get basic account information.
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        let cached = self.cache.state.read().expect("prefetch cache lock is not poisoned");
+        match cached.accounts.get(&address) {
+            Some(account) => Ok(*account),
+            None => {
+                drop(cached);
+                self.inner.basic_account(address)
+            }
+        }
+    }
+}
+
+impl<SP: StateProvider> BlockHashProvider for PrefetchedStateProvider<SP> {
+    /// Get block hash by number.
+    fn block_hash(&self, number: U256) -> Result<Option<H256>> {
+        self.inner.block_hash(number)
+    }
+}
+
+impl<SP: StateProvider> StateProvider for PrefetchedStateProvider<SP> {
+    /// Get storage.
+    fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
+        let cached = self.cache.state.read().expect("prefetch cache lock is not poisoned");
+        match cached.storage.get(&(account, storage_key)) {
+            Some(value) => Ok(*value),
+            None => {
+                drop(cached);
+                self.inner.storage(account, storage_key)
+            }
+        }
+    }
+
+    /// Get account code by its hash
+    fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytes>> {
+        let cached = self.cache.state.read().expect("prefetch cache lock is not poisoned");
+        match cached.bytecodes.get(&code_hash) {
+            Some(bytecode) => Ok(bytecode.clone()),
+            None => {
+                drop(cached);
+                self.inner.bytecode_by_hash(code_hash)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_primitives::{AccessList, AccessListItem, TxEip1559};
+
+    #[test]
+    fn prefetch_transaction_targets() {
+        let (signer, to, listed, other) = (
+            Address::from_low_u64_be(1),
+            Address::from_low_u64_be(2),
+            Address::from_low_u64_be(3),
+            Address::from_low_u64_be(4),
+        );
+        let slot = H256::from_low_u64_be(1);
+        let state = MockEthProvider::default();
+        state.add_account(signer, ExtendedAccount::new(1, U256::from(10)));
+        state.add_account(
+            listed,
+            ExtendedAccount::new(0, U256::ZERO).extend_storage([(slot, U256::from(5))]),
+        );
+
+        let transaction = Transaction::Eip1559(TxEip1559 {
+            to: TransactionKind::Call(to),
+            access_list: AccessList(vec![AccessListItem {
+                address: listed,
+                storage_keys: vec![slot],
+            }]),
+            ..Default::default()
+        });
+        let mut targets = PrefetchTargets::default();
+        assert!(targets.is_empty());
+        targets.insert_transaction(&transaction, signer);
+
+        let cache = PrefetchCache::default();
+        cache.prefetch(&state, targets).unwrap();
+        assert_eq!(cache.accounts_len(), 3);
+
+        // The prefetched values are read from the cache, even if the inner state is different
+        let provider = PrefetchedStateProvider::new(MockEthProvider::default(), cache);
+        assert_eq!(provider.basic_account(signer).unwrap().map(|account| account.nonce), Some(1));
+        assert_eq!(provider.basic_account(to).unwrap(), None);
+        assert_eq!(provider.storage(listed, slot).unwrap(), Some(U256::from(5)));
+        assert_eq!(provider.basic_account(other).unwrap(), None);
+    }
+}
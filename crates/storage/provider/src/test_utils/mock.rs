@@ -1,15 +1,17 @@
 use crate::{
-    AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider, StateProvider,
-    StateProviderFactory,
+    trie::HashedStateChanges, AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider,
+    ReceiptProvider, StateProvider, StateProviderFactory, StateTrieProvider, TransactionsProvider,
 };
 use parking_lot::Mutex;
 use reth_interfaces::Result;
 use reth_primitives::{
     keccak256,
     rpc::{BlockId, BlockNumber},
-    Account, Address, Block, BlockHash, Bytes, ChainInfo, Header, StorageKey, StorageValue, H256,
-    U256,
+    Account, Address, Block, BlockHash, BlockHashOrNumber, Bytes, ChainInfo, Header, Receipt,
+    StorageKey, StorageValue, TransactionSigned, TxHash, TxNumber, H256, U256,
 };
+use reth_rlp::{encode_fixed_size, Encodable};
+use reth_trie::{nibbles::unpack, EthAccount, HashBuilder};
 use std::{collections::HashMap, ops::RangeBounds, sync::Arc};
 
 /// A mock implementation for Provider interfaces.
@@ -48,6 +50,16 @@ impl ExtendedAccount {
         self.bytecode = Some(bytecode);
         self
     }
+
+    /// Add storage to the extended account. If the storage key is already present,
+    /// the value is updated.
+    pub fn extend_storage(
+        mut self,
+        storage: impl IntoIterator<Item = (StorageKey, StorageValue)>,
+    ) -> Self {
+        self.storage.extend(storage);
+        self
+    }
 }
 
 impl MockEthProvider {
@@ -88,6 +100,18 @@ impl MockEthProvider {
             self.add_account(address, account)
         }
     }
+
+    /// Returns the transactions of all blocks with their block number, numbered in the order of
+    /// the blocks.
+    fn numbered_transactions(&self) -> Vec<(reth_primitives::BlockNumber, TransactionSigned)> {
+        let lock = self.blocks.lock();
+        let mut blocks = lock.values().collect::<Vec<_>>();
+        blocks.sort_by_key(|block| block.number);
+        blocks
+            .into_iter()
+            .flat_map(|block| block.body.iter().map(|tx| (block.number, tx.clone())))
+            .collect()
+    }
 }
 
 impl HeaderProvider for MockEthProvider {
@@ -172,6 +196,82 @@ impl BlockProvider for MockEthProvider {
     }
 }
 
+/// The mock does not store receipts.
+impl ReceiptProvider for MockEthProvider {
+    fn receipt(&self, _id: TxNumber) -> Result<Option<Receipt>> {
+        Ok(None)
+    }
+
+    fn receipts_by_block(&self, _block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        Ok(None)
+    }
+}
+
+impl TransactionsProvider for MockEthProvider {
+    fn transaction_id(&self, tx_hash: TxHash) -> Result<Option<TxNumber>> {
+        Ok(self
+            .numbered_transactions()
+            .iter()
+            .position(|(_, tx)| tx.hash == tx_hash)
+            .map(|id| id as TxNumber))
+    }
+
+    fn transaction_by_id(&self, id: TxNumber) -> Result<Option<TransactionSigned>> {
+        Ok(self.numbered_transactions().into_iter().nth(id as usize).map(|(_, tx)| tx))
+    }
+
+    fn transaction_block(&self, id: TxNumber) -> Result<Option<reth_primitives::BlockNumber>> {
+        Ok(self.numbered_transactions().into_iter().nth(id as usize).map(|(number, _)| number))
+    }
+}
+
+/// The mock does not store the state tries, the state roots are computed from the changes alone.
+impl StateTrieProvider for MockEthProvider {
+    fn account_trie_node(&self, _hash: H256) -> Result<Option<Bytes>> {
+        Ok(None)
+    }
+
+    fn storage_trie_node(&self, _hashed_address: H256, _hash: H256) -> Result<Option<Bytes>> {
+        Ok(None)
+    }
+
+    fn hashed_accounts(&self, _start: H256, _limit: usize) -> Result<Vec<(H256, Account)>> {
+        Ok(Vec::new())
+    }
+
+    fn hashed_storage(
+        &self,
+        _hashed_address: H256,
+        _start: H256,
+        _limit: usize,
+    ) -> Result<Vec<(H256, U256)>> {
+        Ok(Vec::new())
+    }
+
+    fn state_root_with_changes(
+        &self,
+        _previous_root: H256,
+        changes: &HashedStateChanges,
+    ) -> Result<H256> {
+        let mut builder = HashBuilder::default();
+        for (hashed_address, account) in &changes.accounts {
+            let Some(account) = account else { continue };
+            let mut storage_builder = HashBuilder::default();
+            let slots = changes.storages.get(hashed_address).map(|storage| &storage.slots);
+            for (hashed_key, value) in slots.into_iter().flatten() {
+                if *value != U256::ZERO {
+                    storage_builder
+                        .add_leaf(unpack(hashed_key.as_bytes()), &encode_fixed_size(value));
+                }
+            }
+            let mut encoded = Vec::new();
+            EthAccount::from_with_root(*account, storage_builder.root()).encode(&mut encoded);
+            builder.add_leaf(unpack(hashed_address.as_bytes()), &encoded);
+        }
+        Ok(builder.root())
+    }
+}
+
 impl AccountProvider for MockEthProvider {
     fn basic_account(&self, address: Address) -> Result<Option<Account>> {
         Ok(self.accounts.lock().get(&address).cloned().map(|a| a.account))
@@ -198,21 +298,29 @@ impl StateProvider for MockEthProvider {
 }
 
 impl StateProviderFactory for MockEthProvider {
-    type HistorySP<'a> = &'a MockEthProvider where Self: 'a;
-    type LatestSP<'a> = &'a MockEthProvider where Self: 'a;
+    type HistorySP<'a>
+        = &'a MockEthProvider
+    where
+        Self: 'a;
+    type LatestSP<'a>
+        = &'a MockEthProvider
+    where
+        Self: 'a;
 
     fn latest(&self) -> Result<Self::LatestSP<'_>> {
         Ok(self)
     }
 
+    // the mock only holds a single state, which is the one of every block
+
     fn history_by_block_number(
         &self,
         _block: reth_primitives::BlockNumber,
     ) -> Result<Self::HistorySP<'_>> {
-        todo!()
+        Ok(self)
     }
 
     fn history_by_block_hash(&self, _block: BlockHash) -> Result<Self::HistorySP<'_>> {
-        todo!()
+        Ok(self)
     }
 }
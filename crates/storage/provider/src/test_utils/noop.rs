@@ -1,11 +1,12 @@
 use crate::{
-    AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider, StateProvider,
-    StateProviderFactory,
+    trie::HashedStateChanges, AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider,
+    ReceiptProvider, StateProvider, StateProviderFactory, StateTrieProvider, TransactionsProvider,
 };
 use reth_interfaces::Result;
 use reth_primitives::{
-    rpc::BlockId, Account, Address, Block, BlockHash, BlockNumber, Bytes, ChainInfo, Header,
-    StorageKey, StorageValue, H256, U256,
+    rpc::BlockId, Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, Bytes,
+    ChainInfo, Header, Receipt, StorageKey, StorageValue, TransactionSigned, TxHash, TxNumber,
+    H256, U256,
 };
 use std::ops::RangeBounds;
 
@@ -58,6 +59,61 @@ impl HeaderProvider for NoopProvider {
     }
 }
 
+impl ReceiptProvider for NoopProvider {
+    fn receipt(&self, _id: TxNumber) -> Result<Option<Receipt>> {
+        Ok(None)
+    }
+
+    fn receipts_by_block(&self, _block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        Ok(None)
+    }
+}
+
+impl TransactionsProvider for NoopProvider {
+    fn transaction_id(&self, _tx_hash: TxHash) -> Result<Option<TxNumber>> {
+        Ok(None)
+    }
+
+    fn transaction_by_id(&self, _id: TxNumber) -> Result<Option<TransactionSigned>> {
+        Ok(None)
+    }
+
+    fn transaction_block(&self, _id: TxNumber) -> Result<Option<BlockNumber>> {
+        Ok(None)
+    }
+}
+
+impl StateTrieProvider for NoopProvider {
+    fn account_trie_node(&self, _hash: H256) -> Result<Option<Bytes>> {
+        Ok(None)
+    }
+
+    fn storage_trie_node(&self, _hashed_address: H256, _hash: H256) -> Result<Option<Bytes>> {
+        Ok(None)
+    }
+
+    fn hashed_accounts(&self, _start: H256, _limit: usize) -> Result<Vec<(H256, Account)>> {
+        Ok(Vec::new())
+    }
+
+    fn hashed_storage(
+        &self,
+        _hashed_address: H256,
+        _start: H256,
+        _limit: usize,
+    ) -> Result<Vec<(H256, U256)>> {
+        Ok(Vec::new())
+    }
+
+    fn state_root_with_changes(
+        &self,
+        _previous_root: H256,
+        _changes: &HashedStateChanges,
+    ) -> Result<H256> {
+        Ok(H256::zero())
+    }
+}
+
 impl AccountProvider for NoopProvider {
     fn basic_account(&self, _address: Address) -> Result<Option<Account>> {
         Ok(None)
@@ -75,8 +131,14 @@ impl StateProvider for NoopProvider {
 }
 
 impl StateProviderFactory for NoopProvider {
-    type HistorySP<'a> = NoopProvider where Self: 'a;
-    type LatestSP<'a> = NoopProvider where Self: 'a;
+    type HistorySP<'a>
+        = NoopProvider
+    where
+        Self: 'a;
+    type LatestSP<'a>
+        = NoopProvider
+    where
+        Self: 'a;
 
     fn latest(&self) -> Result<Self::LatestSP<'_>> {
         Ok(*self)
@@ -12,5 +12,14 @@ pub use block_hash::BlockHashProvider;
 mod header;
 pub use header::HeaderProvider;
 
+mod receipts;
+pub use receipts::ReceiptProvider;
+
 mod state;
 pub use state::{StateProvider, StateProviderFactory};
+
+mod transactions;
+pub use transactions::TransactionsProvider;
+
+mod trie;
+pub use trie::StateTrieProvider;
@@ -0,0 +1,15 @@
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{BlockHashOrNumber, Receipt, TxNumber};
+
+/// Client trait for fetching [Receipt] data.
+#[auto_impl(&)]
+pub trait ReceiptProvider: Send + Sync {
+    /// Get receipt by transaction number
+    fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>>;
+
+    /// Get receipts of a block by its number or hash, in the order of its transactions.
+    ///
+    /// Returns `None` if the block is not found.
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>>;
+}
@@ -0,0 +1,24 @@
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{BlockNumber, TransactionSigned, TxHash, TxNumber};
+
+/// Client trait for fetching canonical [TransactionSigned] data.
+#[auto_impl(&)]
+pub trait TransactionsProvider: Send + Sync {
+    /// Get the number of the transaction with the given hash.
+    fn transaction_id(&self, tx_hash: TxHash) -> Result<Option<TxNumber>>;
+
+    /// Get transaction by its number.
+    fn transaction_by_id(&self, id: TxNumber) -> Result<Option<TransactionSigned>>;
+
+    /// Get transaction by its hash.
+    fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
+        match self.transaction_id(hash)? {
+            Some(id) => self.transaction_by_id(id),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the number of the block that includes the transaction with the given number.
+    fn transaction_block(&self, id: TxNumber) -> Result<Option<BlockNumber>>;
+}
@@ -0,0 +1,39 @@
+use crate::trie::HashedStateChanges;
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{Account, Bytes, H256, U256};
+
+/// Client trait for reading the merkle patricia tries of the latest state, and the hashed state
+/// they are built from.
+///
+/// See [crate::trie] for walking the tries.
+#[auto_impl(&)]
+pub trait StateTrieProvider: Send + Sync {
+    /// Get a node of the account trie by its hash.
+    fn account_trie_node(&self, hash: H256) -> Result<Option<Bytes>>;
+
+    /// Get a node of the storage trie of the account with the given hashed address by its hash.
+    fn storage_trie_node(&self, hashed_address: H256, hash: H256) -> Result<Option<Bytes>>;
+
+    /// Get up to `limit` accounts, ordered by their hashed address, starting at `start`.
+    fn hashed_accounts(&self, start: H256, limit: usize) -> Result<Vec<(H256, Account)>>;
+
+    /// Get up to `limit` storage slots of the account with the given hashed address, ordered by the
+    /// hashed slot, starting at `start`.
+    fn hashed_storage(
+        &self,
+        hashed_address: H256,
+        start: H256,
+        limit: usize,
+    ) -> Result<Vec<(H256, U256)>>;
+
+    /// Get the state root of the latest state with the given changes applied, without persisting
+    /// the changes nor the updated nodes of the tries.
+    ///
+    /// `previous_root` is the state root of the latest state.
+    fn state_root_with_changes(
+        &self,
+        previous_root: H256,
+        changes: &HashedStateChanges,
+    ) -> Result<H256>;
+}
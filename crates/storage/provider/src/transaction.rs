@@ -1,17 +1,27 @@
 #![allow(dead_code)]
 use reth_db::{
-    cursor::DbCursorRO,
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
     database::{Database, DatabaseGAT},
-    models::StoredBlockBody,
+    models::{
+        sharded_key::NUM_OF_INDICES_IN_SHARD, storage_sharded_key::StorageShardedKey, ShardedKey,
+        StoredBlockBody, TransitionIdAddress,
+    },
     table::Table,
     tables,
     transaction::{DbTx, DbTxMut},
+    TransitionList,
 };
 use reth_interfaces::{db::Error as DbError, provider::Error as ProviderError};
-use reth_primitives::{BlockHash, BlockNumber, Header, TransitionId, TxNumber, U256};
+use reth_primitives::{
+    keccak256, Account, Address, BlockHash, BlockNumber, Header, StorageEntry, TransactionSigned,
+    TransitionId, TxNumber, H256, U256,
+};
+use reth_static_file::{StaticFileProvider, StaticFileTable};
+use reth_trie::{PrefixSetLoader, StateRoot, StateRootError};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fmt::Debug,
-    ops::{Deref, DerefMut},
+    ops::{Bound, Deref, DerefMut, Range},
 };
 
 /// A container for any DB transaction that will open a new inner transaction when the current
@@ -26,6 +36,8 @@ pub struct Transaction<'this, DB: Database> {
     /// A handle to the DB.
     pub(crate) db: &'this DB,
     tx: Option<<DB as DatabaseGAT<'this>>::TXMut>,
+    /// The static files holding the finalized blocks moved out of the database, if any.
+    static_files: Option<StaticFileProvider>,
 }
 
 impl<'a, DB: Database> Debug for Transaction<'a, DB> {
@@ -68,7 +80,34 @@ where
     ///
     /// A new inner transaction will be opened.
     pub fn new(db: &'this DB) -> Result<Self, DbError> {
-        Ok(Self { db, tx: Some(db.tx_mut()?) })
+        Ok(Self { db, tx: Some(db.tx_mut()?), static_files: None })
+    }
+
+    /// Read the headers, bodies and transactions moved out of the database from these static
+    /// files.
+    pub fn with_static_files(mut self, static_files: Option<StaticFileProvider>) -> Self {
+        self.static_files = static_files;
+        self
+    }
+
+    /// Returns the static files the finalized blocks are read from, if any.
+    pub fn static_files(&self) -> Option<&StaticFileProvider> {
+        self.static_files.as_ref()
+    }
+
+    /// Returns the value of `key` of the table `T`, from the database or else from the static
+    /// files.
+    fn get_with_static_files<T: StaticFileTable>(
+        &self,
+        key: u64,
+    ) -> Result<Option<T::Value>, DbError> {
+        match self.get::<T>(key)? {
+            Some(value) => Ok(Some(value)),
+            None => match &self.static_files {
+                Some(static_files) => static_files.get::<T>(key),
+                None => Ok(None),
+            },
+        }
     }
 
     /// Accessor to the internal Database
@@ -120,11 +159,38 @@ where
 
     /// Query the block body by number.
     pub fn get_block_body(&self, number: BlockNumber) -> Result<StoredBlockBody, TransactionError> {
-        let body =
-            self.get::<tables::BlockBodies>(number)?.ok_or(ProviderError::BlockBody { number })?;
+        let body = self
+            .get_with_static_files::<tables::BlockBodies>(number)?
+            .ok_or(ProviderError::BlockBody { number })?;
         Ok(body)
     }
 
+    /// Query the transactions in the range of ids, in order.
+    ///
+    /// The transactions moved to the static files are read from them, the rest from the
+    /// database.
+    pub fn get_transactions(
+        &self,
+        range: Range<TxNumber>,
+    ) -> Result<Vec<(TxNumber, TransactionSigned)>, TransactionError> {
+        let (mut transactions, db_start) = match &self.static_files {
+            Some(static_files) => (
+                static_files.get_range::<tables::Transactions>(range.clone())?,
+                static_files
+                    .highest(tables::Transactions::SEGMENT)
+                    .map_or(range.start, |highest| range.start.max(highest + 1)),
+            ),
+            None => (Vec::new(), range.start),
+        };
+        if db_start < range.end {
+            let mut cursor = self.cursor_read::<tables::Transactions>()?;
+            for entry in cursor.walk_range(db_start..range.end)? {
+                transactions.push(entry?);
+            }
+        }
+        Ok(transactions)
+    }
+
     /// Query the last transition of the block by [BlockNumber] key
     pub fn get_block_transition(&self, key: BlockNumber) -> Result<TransitionId, TransactionError> {
         let last_transition_id = self
@@ -153,8 +219,9 @@ where
 
     /// Query the block header by number
     pub fn get_header(&self, number: BlockNumber) -> Result<Header, TransactionError> {
-        let header =
-            self.get::<tables::Headers>(number)?.ok_or(ProviderError::Header { number })?;
+        let header = self
+            .get_with_static_files::<tables::Headers>(number)?
+            .ok_or(ProviderError::Header { number })?;
         Ok(header)
     }
 
@@ -173,30 +240,7 @@ where
         DB: Database,
         T: Table<Key = u64>,
     {
-        self.unwind_table::<T, _>(num, |key| key)
-    }
-
-    /// Unwind the table to a provided block
-    pub(crate) fn unwind_table<T, F>(
-        &self,
-        block: BlockNumber,
-        mut selector: F,
-    ) -> Result<(), DbError>
-    where
-        DB: Database,
-        T: Table,
-        F: FnMut(T::Key) -> BlockNumber,
-    {
-        let mut cursor = self.cursor_write::<T>()?;
-        let mut reverse_walker = cursor.walk_back(None)?;
-
-        while let Some(Ok((key, _))) = reverse_walker.next() {
-            if selector(key.clone()) <= block {
-                break
-            }
-            self.delete::<T>(key, None)?;
-        }
-        Ok(())
+        self.delete_range::<T>((Bound::Excluded(num), Bound::Unbounded)).map(|_| ())
     }
 
     /// Unwind a table forward by a [Walker][reth_db::abstraction::cursor::Walker] on another table
@@ -213,6 +257,393 @@ where
         }
         Ok(())
     }
+
+    /// Revert the plain state changes of the given transitions, and discard their changesets.
+    ///
+    /// The changesets after the transitions are discarded as well, so the range should end at the
+    /// last transition in the database.
+    pub fn unwind_plain_state(&self, transitions: Range<TransitionId>) -> Result<(), DbError> {
+        // Acquire changeset cursors
+        let mut account_changeset = self.cursor_dup_write::<tables::AccountChangeSet>()?;
+        let mut storage_changeset = self.cursor_dup_write::<tables::StorageChangeSet>()?;
+
+        // get all batches for account change
+        // Check if walk and walk_dup would do the same thing
+        let account_changeset_batch =
+            account_changeset.walk_range(transitions.clone())?.collect::<Result<Vec<_>, _>>()?;
+
+        // revert all changes to PlainState
+        for (_, changeset) in account_changeset_batch.into_iter().rev() {
+            if let Some(account_info) = changeset.info {
+                self.put::<tables::PlainAccountState>(changeset.address, account_info)?;
+            } else {
+                self.delete::<tables::PlainAccountState>(changeset.address, None)?;
+            }
+        }
+
+        // get all batches for storage change
+        let storage_changeset_batch = storage_changeset
+            .walk_range(
+                TransitionIdAddress((transitions.start, Address::zero()))..
+                    TransitionIdAddress((transitions.end, Address::zero())),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // revert all changes to PlainStorage
+        let mut plain_storage_cursor = self.cursor_dup_write::<tables::PlainStorageState>()?;
+
+        for (key, storage) in storage_changeset_batch.into_iter().rev() {
+            let address = key.address();
+            if let Some(v) = plain_storage_cursor.seek_by_key_subkey(address, storage.key)? {
+                if v.key == storage.key {
+                    plain_storage_cursor.delete_current()?;
+                }
+            }
+            if storage.value != U256::ZERO {
+                plain_storage_cursor.upsert(address, storage)?;
+            }
+        }
+
+        // Discard unwinded changesets
+        let mut rev_acc_changeset_walker = account_changeset.walk_back(None)?;
+        while let Some((transition_id, _)) = rev_acc_changeset_walker.next().transpose()? {
+            if transition_id < transitions.start {
+                break
+            }
+            // delete all changesets
+            self.delete::<tables::AccountChangeSet>(transition_id, None)?;
+        }
+
+        let mut rev_storage_changeset_walker = storage_changeset.walk_back(None)?;
+        while let Some((key, _)) = rev_storage_changeset_walker.next().transpose()? {
+            if key.transition_id() < transitions.start {
+                break
+            }
+            // delete all changesets
+            self.delete::<tables::StorageChangeSet>(key, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Hash the accounts changed by the transitions, with their values in the plain state, into
+    /// [tables::HashedAccount].
+    pub fn insert_account_hashes(&self, transitions: Range<TransitionId>) -> Result<(), DbError> {
+        let addresses = self
+            .cursor_read::<tables::AccountChangeSet>()?
+            .walk_range(transitions.clone())?
+            .map(|entry| entry.map(|(_, account)| account.address))
+            .collect::<Result<BTreeSet<_>, _>>()?;
+
+        let mut plain_accounts = self.cursor_read::<tables::PlainAccountState>()?;
+        let mut hashed_accounts = self.cursor_write::<tables::HashedAccount>()?;
+        for address in addresses {
+            let account = plain_accounts.seek_exact(address)?.map(|(_, account)| account);
+            set_hashed_account(&mut hashed_accounts, keccak256(address), account)?;
+        }
+        Ok(())
+    }
+
+    /// Hash the storage slots changed by the transitions, with their values in the plain state,
+    /// into [tables::HashedStorage].
+    pub fn insert_storage_hashes(&self, transitions: Range<TransitionId>) -> Result<(), DbError> {
+        let slots = self
+            .cursor_read::<tables::StorageChangeSet>()?
+            .walk_range(storage_changeset_range(&transitions))?
+            .map(|entry| entry.map(|(key, slot)| (key.address(), slot.key)))
+            .collect::<Result<BTreeSet<_>, _>>()?;
+
+        let mut plain_storage = self.cursor_dup_read::<tables::PlainStorageState>()?;
+        let mut hashed_storage = self.cursor_dup_write::<tables::HashedStorage>()?;
+        for (address, key) in slots {
+            let value = plain_storage
+                .seek_by_key_subkey(address, key)?
+                .filter(|entry| entry.key == key)
+                .map_or(U256::ZERO, |entry| entry.value);
+            set_hashed_slot(&mut hashed_storage, keccak256(address), keccak256(key), value)?;
+        }
+        Ok(())
+    }
+
+    /// Revert the hashed accounts changed by the transitions to their values before them, read
+    /// from the changesets.
+    pub fn unwind_account_hashes(&self, transitions: Range<TransitionId>) -> Result<(), DbError> {
+        // the first change of an account in the transitions holds its value before them
+        let accounts = self
+            .cursor_read::<tables::AccountChangeSet>()?
+            .walk_range(transitions.clone())?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .rev()
+            .map(|(_, account)| (keccak256(account.address), account.info))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut hashed_accounts = self.cursor_write::<tables::HashedAccount>()?;
+        for (hashed_address, account) in accounts {
+            set_hashed_account(&mut hashed_accounts, hashed_address, account)?;
+        }
+        Ok(())
+    }
+
+    /// Revert the hashed storage slots changed by the transitions to their values before them,
+    /// read from the changesets.
+    pub fn unwind_storage_hashes(&self, transitions: Range<TransitionId>) -> Result<(), DbError> {
+        // the first change of a slot in the transitions holds its value before them
+        let slots = self
+            .cursor_read::<tables::StorageChangeSet>()?
+            .walk_range(storage_changeset_range(&transitions))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .rev()
+            .map(|(key, slot)| ((keccak256(key.address()), keccak256(slot.key)), slot.value))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut hashed_storage = self.cursor_dup_write::<tables::HashedStorage>()?;
+        for ((hashed_address, hashed_key), value) in slots {
+            set_hashed_slot(&mut hashed_storage, hashed_address, hashed_key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Update the tries of the state root `previous_root` with the keys changed by the
+    /// transitions, which must already be in the hashed state, and return the new state root.
+    pub fn update_state_root(
+        &self,
+        previous_root: H256,
+        transitions: Range<TransitionId>,
+    ) -> Result<H256, TransactionError> {
+        let prefix_sets = PrefixSetLoader::new(&**self).load(transitions)?;
+        Ok(StateRoot::new(&**self).with_prefix_sets(prefix_sets).update_root(previous_root)?)
+    }
+
+    /// Add the transitions to the history indices of the accounts they change, in
+    /// [tables::AccountHistory].
+    pub fn insert_account_history_indices(
+        &self,
+        transitions: Range<TransitionId>,
+    ) -> Result<(), DbError> {
+        let mut account_indices = BTreeMap::<Address, Vec<usize>>::new();
+        for entry in
+            self.cursor_read::<tables::AccountChangeSet>()?.walk_range(transitions.clone())?
+        {
+            let (transition, account) = entry?;
+            account_indices.entry(account.address).or_default().push(transition as usize);
+        }
+
+        for (address, indices) in account_indices {
+            let last_key = ShardedKey::new(address, u64::MAX);
+            let mut shard = match self.get::<tables::AccountHistory>(last_key.clone())? {
+                Some(list) => {
+                    self.delete::<tables::AccountHistory>(last_key, None)?;
+                    list.iter(0).collect()
+                }
+                None => Vec::new(),
+            };
+            shard.extend(indices);
+            for (list, highest) in into_shards(shard) {
+                self.put::<tables::AccountHistory>(ShardedKey::new(address, highest), list)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the transitions to the history indices of the storage slots they change, in
+    /// [tables::StorageHistory].
+    pub fn insert_storage_history_indices(
+        &self,
+        transitions: Range<TransitionId>,
+    ) -> Result<(), DbError> {
+        let mut storage_indices = BTreeMap::<(Address, H256), Vec<usize>>::new();
+        for entry in self
+            .cursor_read::<tables::StorageChangeSet>()?
+            .walk_range(storage_changeset_range(&transitions))?
+        {
+            let (key, slot) = entry?;
+            storage_indices
+                .entry((key.address(), slot.key))
+                .or_default()
+                .push(key.transition_id() as usize);
+        }
+
+        for ((address, key), indices) in storage_indices {
+            let last_key = StorageShardedKey::new(address, key, u64::MAX);
+            let mut shard = match self.get::<tables::StorageHistory>(last_key.clone())? {
+                Some(list) => {
+                    self.delete::<tables::StorageHistory>(last_key, None)?;
+                    list.iter(0).collect()
+                }
+                None => Vec::new(),
+            };
+            shard.extend(indices);
+            for (list, highest) in into_shards(shard) {
+                self.put::<tables::StorageHistory>(
+                    StorageShardedKey::new(address, key, highest),
+                    list,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the transitions from the history indices of the accounts they change.
+    ///
+    /// The indices after the transitions are removed as well, so the range should end at the
+    /// last transition in the database.
+    pub fn unwind_account_history_indices(
+        &self,
+        transitions: Range<TransitionId>,
+    ) -> Result<(), DbError> {
+        // the first change of an account in the transitions is the first index to remove
+        let accounts = self
+            .cursor_read::<tables::AccountChangeSet>()?
+            .walk_range(transitions.clone())?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .rev()
+            .map(|(transition, account)| (account.address, transition))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut cursor = self.cursor_write::<tables::AccountHistory>()?;
+        for (address, transition) in accounts {
+            let mut item = cursor.seek_exact(ShardedKey::new(address, u64::MAX))?;
+            let mut kept = Vec::new();
+            while let Some((_, list)) = item.filter(|(key, _)| key.key == address) {
+                cursor.delete_current()?;
+                if list.iter(0).next().expect("lists are not empty") < transition as usize {
+                    // the first shard with indices before the transition is the new last shard
+                    kept = list.iter(0).take_while(|index| *index < transition as usize).collect();
+                    break
+                }
+                item = cursor.prev()?;
+            }
+            if !kept.is_empty() {
+                self.put::<tables::AccountHistory>(
+                    ShardedKey::new(address, u64::MAX),
+                    TransitionList::new(kept).expect("indices are sorted and not empty"),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the transitions from the history indices of the storage slots they change.
+    ///
+    /// The indices after the transitions are removed as well, so the range should end at the
+    /// last transition in the database.
+    pub fn unwind_storage_history_indices(
+        &self,
+        transitions: Range<TransitionId>,
+    ) -> Result<(), DbError> {
+        let slots = self
+            .cursor_read::<tables::StorageChangeSet>()?
+            .walk_range(storage_changeset_range(&transitions))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .rev()
+            .map(|(key, slot)| ((key.address(), slot.key), key.transition_id()))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut cursor = self.cursor_write::<tables::StorageHistory>()?;
+        for ((address, key), transition) in slots {
+            let mut item = cursor.seek_exact(StorageShardedKey::new(address, key, u64::MAX))?;
+            let mut kept = Vec::new();
+            while let Some((_, list)) = item.filter(|(sharded_key, _)| {
+                sharded_key.address == address && sharded_key.sharded_key.key == key
+            }) {
+                cursor.delete_current()?;
+                if list.iter(0).next().expect("lists are not empty") < transition as usize {
+                    kept = list.iter(0).take_while(|index| *index < transition as usize).collect();
+                    break
+                }
+                item = cursor.prev()?;
+            }
+            if !kept.is_empty() {
+                self.put::<tables::StorageHistory>(
+                    StorageShardedKey::new(address, key, u64::MAX),
+                    TransitionList::new(kept).expect("indices are sorted and not empty"),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the checkpoints of all the stages that have run to the block, once the tables of the
+    /// blocks up to it were written outside of the pipeline.
+    pub fn update_stage_checkpoints(&self, block: BlockNumber) -> Result<(), DbError> {
+        let stages = self
+            .cursor_read::<tables::SyncStage>()?
+            .walk(None)?
+            .map(|entry| entry.map(|(stage, _)| stage))
+            .collect::<Result<Vec<_>, _>>()?;
+        for stage in stages {
+            self.put::<tables::SyncStage>(stage, block)?;
+        }
+        Ok(())
+    }
+}
+
+/// The range of [tables::StorageChangeSet] keys of the transitions.
+fn storage_changeset_range(transitions: &Range<TransitionId>) -> Range<TransitionIdAddress> {
+    TransitionIdAddress((transitions.start, Address::zero()))..
+        TransitionIdAddress((transitions.end, Address::zero()))
+}
+
+/// Set the hashed account, or remove it if it doesn't exist.
+pub(crate) fn set_hashed_account<'a, C>(
+    cursor: &mut C,
+    hashed_address: H256,
+    account: Option<Account>,
+) -> Result<(), DbError>
+where
+    C: DbCursorRO<'a, tables::HashedAccount> + DbCursorRW<'a, tables::HashedAccount>,
+{
+    match account {
+        Some(account) => cursor.upsert(hashed_address, account),
+        None if cursor.seek_exact(hashed_address)?.is_some() => cursor.delete_current(),
+        None => Ok(()),
+    }
+}
+
+/// Set the hashed storage slot, or remove it if it is zero.
+pub(crate) fn set_hashed_slot<'a, C>(
+    cursor: &mut C,
+    hashed_address: H256,
+    hashed_key: H256,
+    value: U256,
+) -> Result<(), DbError>
+where
+    C: DbDupCursorRO<'a, tables::HashedStorage> + DbCursorRW<'a, tables::HashedStorage>,
+{
+    if cursor
+        .seek_by_key_subkey(hashed_address, hashed_key)?
+        .filter(|entry| entry.key == hashed_key)
+        .is_some()
+    {
+        cursor.delete_current()?;
+    }
+    if value != U256::ZERO {
+        cursor.upsert(hashed_address, StorageEntry { key: hashed_key, value })?;
+    }
+    Ok(())
+}
+
+/// Split the sorted indices of a history index into shards of at most
+/// [NUM_OF_INDICES_IN_SHARD] indices, with the highest index of each shard. The last shard is
+/// keyed by `u64::MAX`.
+fn into_shards(indices: Vec<usize>) -> Vec<(TransitionList, TransitionId)> {
+    let count = (indices.len() + NUM_OF_INDICES_IN_SHARD - 1) / NUM_OF_INDICES_IN_SHARD;
+    indices
+        .chunks(NUM_OF_INDICES_IN_SHARD)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let highest = if index + 1 == count {
+                u64::MAX
+            } else {
+                *chunk.last().expect("chunks are not empty") as TransitionId
+            };
+            (TransitionList::new(chunk).expect("indices are sorted and not empty"), highest)
+        })
+        .collect()
 }
 
 /// An error that can occur when using the transaction container
@@ -224,4 +655,7 @@ pub enum TransactionError {
     /// The transaction encountered a database integrity error.
     #[error("A database integrity error occurred: {0}")]
     DatabaseIntegrity(#[from] ProviderError),
+    /// The tries of the state could not be updated.
+    #[error("State root error: {0}")]
+    StateRoot(#[from] StateRootError),
 }
@@ -0,0 +1,303 @@
+//! Read access to the merkle patricia tries of the state.
+//!
+//! The tries are stored as RLP encoded nodes keyed by their hash, see [tables::AccountsTrie] and
+//! [tables::StoragesTrie]. Nodes that encode to less than 32 bytes are inlined into their parent
+//! and have no entry of their own.
+//!
+//! The tries are walked by [reth_trie::proof], the functions of this module report its errors as
+//! provider errors.
+//!
+//! The state root of changes that are not in the database yet, e.g. of a payload being built, is
+//! computed from their [HashedStateChanges].
+//!
+//! [tables::AccountsTrie]: reth_db::tables::AccountsTrie
+//! [tables::StoragesTrie]: reth_db::tables::StoragesTrie
+
+use crate::{
+    transaction::{set_hashed_account, set_hashed_slot},
+    StateTrieProvider,
+};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO, DbDupCursorRW},
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_interfaces::{db::Error as DbError, provider::Error as ProviderError, Error, Result};
+use reth_primitives::{keccak256, Account, Address, Bytes, H256, U256};
+use reth_trie::{
+    nibbles::{decode_path, unpack},
+    proof, PrefixSets, ProofError, StateRoot, StateRootError,
+};
+use std::collections::BTreeMap;
+
+pub use reth_trie::{AccountProof, StorageProof, TrieProof};
+
+/// Looks up `key` in the trie with the given root, collecting the nodes on the path to it.
+///
+/// `get_node` returns the node with the given hash.
+pub fn trie_proof<F>(root: H256, key: H256, get_node: F) -> Result<TrieProof>
+where
+    F: FnMut(H256) -> Result<Option<Bytes>>,
+{
+    proof::trie_proof(root, key, get_node).map_err(into_provider_error)
+}
+
+/// Returns the node at the nibble `path` of the trie with the given root, if any.
+///
+/// Paths that end inside of an extension or leaf node don't point to a node.
+pub fn trie_node_at_path<F>(root: H256, path: &[u8], get_node: F) -> Result<Option<Bytes>>
+where
+    F: FnMut(H256) -> Result<Option<Bytes>>,
+{
+    proof::trie_node_at_path(root, path, get_node).map_err(into_provider_error)
+}
+
+/// Returns the proof of the account in the state trie with the given root, and the proofs of the
+/// given storage slots of the account, as served by `eth_getProof`.
+pub fn account_proof<Client: StateTrieProvider>(
+    client: &Client,
+    state_root: H256,
+    address: Address,
+    slots: &[H256],
+) -> Result<AccountProof> {
+    let hashed_address = keccak256(address);
+    proof::account_proof(
+        state_root,
+        address,
+        slots,
+        |hash| client.account_trie_node(hash),
+        |hash| client.storage_trie_node(hashed_address, hash),
+    )
+    .map_err(into_provider_error)
+}
+
+/// Splits the bytes into nibbles.
+pub fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    unpack(bytes)
+}
+
+/// Decodes a hex prefix encoded path, as used by the `snap` protocol, into its nibbles.
+///
+/// Returns `None` if the encoding is invalid.
+pub fn compact_to_nibbles(path: &[u8]) -> Option<Vec<u8>> {
+    decode_path(path).map(|(nibbles, _)| nibbles)
+}
+
+/// Changes of the hashed state, by the hashed addresses of the accounts and the hashed keys of the
+/// storage slots.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HashedStateChanges {
+    /// The changed accounts, `None` if the account no longer exists.
+    pub accounts: BTreeMap<H256, Option<Account>>,
+    /// The changed storage of the accounts.
+    pub storages: BTreeMap<H256, HashedStorageChanges>,
+}
+
+/// Changes of the hashed storage of an account.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HashedStorageChanges {
+    /// Whether all the slots of the account were removed before the changed slots were set.
+    pub wiped: bool,
+    /// The changed slots, zero if the slot was cleared.
+    pub slots: BTreeMap<H256, U256>,
+}
+
+impl HashedStateChanges {
+    /// Writes the changes to the hashed state tables, and returns the changed keys of the tries.
+    pub(crate) fn write_to_db<'a, TX: DbTx<'a> + DbTxMut<'a>>(
+        &self,
+        tx: &TX,
+    ) -> std::result::Result<PrefixSets, DbError> {
+        let mut prefix_sets = PrefixSets::default();
+
+        let mut hashed_accounts = tx.cursor_write::<tables::HashedAccount>()?;
+        for (hashed_address, account) in &self.accounts {
+            set_hashed_account(&mut hashed_accounts, *hashed_address, *account)?;
+            prefix_sets.accounts.insert(*hashed_address);
+        }
+
+        let mut hashed_storage = tx.cursor_dup_write::<tables::HashedStorage>()?;
+        for (hashed_address, storage) in &self.storages {
+            let prefix_set = prefix_sets.storages.entry(*hashed_address).or_default();
+            if storage.wiped && hashed_storage.seek_exact(*hashed_address)?.is_some() {
+                // the removed slots are changed keys of the storage trie as well
+                for entry in hashed_storage.walk_dup(Some(*hashed_address), None)? {
+                    let (_, slot) = entry?;
+                    prefix_set.insert(slot.key);
+                }
+                hashed_storage.seek_exact(*hashed_address)?;
+                hashed_storage.delete_current_duplicates()?;
+            }
+            for (hashed_key, value) in &storage.slots {
+                set_hashed_slot(&mut hashed_storage, *hashed_address, *hashed_key, *value)?;
+                prefix_set.insert(*hashed_key);
+            }
+        }
+
+        Ok(prefix_sets)
+    }
+}
+
+/// Computes the state root of the latest state, whose root is `previous_root`, with the changes
+/// applied.
+///
+/// The tries are updated in a write transaction that is dropped instead of committed, which leaves
+/// the database untouched. Other writers wait for the transaction in the meantime.
+pub fn state_root_with_changes<DB: Database>(
+    db: &DB,
+    previous_root: H256,
+    changes: &HashedStateChanges,
+) -> Result<H256> {
+    let tx = db.tx_mut()?;
+    let root = changes.write_to_db(&tx).map_err(Into::into).and_then(|prefix_sets| {
+        StateRoot::new(&tx)
+            .with_prefix_sets(prefix_sets)
+            .update_root(previous_root)
+            .map_err(from_state_root_error)
+    });
+    tx.drop();
+    root
+}
+
+/// Reports the errors of the computation of a state root as provider errors.
+pub(crate) fn from_state_root_error(error: StateRootError) -> Error {
+    match error {
+        StateRootError::Database(error) => error.into(),
+        StateRootError::MissingNode { hash } => ProviderError::TrieNode { hash }.into(),
+        StateRootError::InvalidNode { hash } => ProviderError::InvalidTrieNode { hash }.into(),
+        StateRootError::InvalidAccount(_) => DbError::DecodeError.into(),
+    }
+}
+
+/// Reports the missing and invalid nodes of the tries as provider errors.
+fn into_provider_error(error: ProofError<Error>) -> Error {
+    match error {
+        ProofError::Source(error) => error,
+        ProofError::MissingNode { hash } => ProviderError::TrieNode { hash }.into(),
+        ProofError::InvalidNode { hash } => ProviderError::InvalidTrieNode { hash }.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::proofs::EMPTY_ROOT;
+    use reth_rlp::{Encodable, Header};
+    use std::collections::HashMap;
+
+    fn encode_list(items: &[&[u8]]) -> Vec<u8> {
+        let payload_length = items.iter().map(|item| item.len()).sum();
+        let mut out = Vec::new();
+        Header { list: true, payload_length }.encode(&mut out);
+        for item in items {
+            out.extend_from_slice(item);
+        }
+        out
+    }
+
+    fn encode_string(string: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        string.encode(&mut out);
+        out
+    }
+
+    /// A leaf with the hex prefix encoded path of a single nibble.
+    fn leaf(nibble: u8, value: &[u8]) -> Vec<u8> {
+        encode_list(&[&encode_string(&[0x30 | nibble]), &encode_string(value)])
+    }
+
+    /// Builds the trie of two keys which only differ in the second to last nibble: an extension
+    /// with the shared path, a branch and two leaves. The first leaf is stored by its hash, the
+    /// second one is inlined into the branch.
+    fn two_leaf_trie() -> (H256, HashMap<H256, Bytes>, H256, H256) {
+        let hashed_key = H256::repeat_byte(0x11);
+        let mut inlined_key = H256::repeat_byte(0x11);
+        inlined_key.0[31] = 0x21;
+
+        let mut nodes = HashMap::new();
+        let hashed_leaf = leaf(1, &[0xaa; 32]);
+        let hashed_leaf_hash = keccak256(&hashed_leaf);
+        nodes.insert(hashed_leaf_hash, Bytes::from(hashed_leaf));
+
+        let inlined_leaf = leaf(1, &[0xbb]);
+        assert!(inlined_leaf.len() < 32);
+
+        let empty = encode_string(&[]);
+        let hashed_leaf_ref = encode_string(hashed_leaf_hash.as_bytes());
+        let mut branch: Vec<&[u8]> = vec![&empty; 17];
+        branch[1] = &hashed_leaf_ref;
+        branch[2] = &inlined_leaf;
+        let branch = encode_list(&branch);
+        let branch_hash = keccak256(&branch);
+        nodes.insert(branch_hash, Bytes::from(branch));
+
+        // even length extension path of 62 nibbles
+        let mut path = vec![0x00];
+        path.extend([0x11; 31]);
+        let extension =
+            encode_list(&[&encode_string(&path), &encode_string(branch_hash.as_bytes())]);
+        let root = keccak256(&extension);
+        nodes.insert(root, Bytes::from(extension));
+
+        (root, nodes, hashed_key, inlined_key)
+    }
+
+    #[test]
+    fn proof_of_hashed_and_inlined_leaves() {
+        let (root, nodes, hashed_key, inlined_key) = two_leaf_trie();
+        let get_node = |hash| Ok(nodes.get(&hash).cloned());
+
+        let proof = trie_proof(root, hashed_key, get_node).unwrap();
+        assert_eq!(proof.nodes.len(), 3);
+        assert_eq!(proof.nodes[0], nodes[&root]);
+        assert_eq!(proof.value, Some(Bytes::from(vec![0xaa; 32])));
+
+        // inlined nodes are part of their parent
+        let proof = trie_proof(root, inlined_key, get_node).unwrap();
+        assert_eq!(proof.nodes.len(), 2);
+        assert_eq!(proof.value, Some(Bytes::from(vec![0xbb])));
+
+        let proof = trie_proof(root, H256::repeat_byte(0x33), get_node).unwrap();
+        assert_eq!(proof.nodes, vec![nodes[&root].clone()]);
+        assert_eq!(proof.value, None);
+
+        let proof = trie_proof(EMPTY_ROOT, hashed_key, get_node).unwrap();
+        assert_eq!(proof, TrieProof::default());
+
+        assert_eq!(
+            trie_proof(H256::repeat_byte(0x44), hashed_key, get_node),
+            Err(ProviderError::TrieNode { hash: H256::repeat_byte(0x44) }.into())
+        );
+    }
+
+    #[test]
+    fn node_at_path() {
+        let (root, nodes, hashed_key, _) = two_leaf_trie();
+        let get_node = |hash| Ok(nodes.get(&hash).cloned());
+        let mut path = to_nibbles(hashed_key.as_bytes());
+        path.truncate(62);
+
+        assert_eq!(trie_node_at_path(root, &[], get_node).unwrap(), Some(nodes[&root].clone()));
+        assert_eq!(
+            trie_node_at_path(root, &path, get_node).unwrap(),
+            trie_proof(root, hashed_key, get_node).unwrap().nodes.get(1).cloned()
+        );
+
+        path.push(1);
+        assert_eq!(
+            trie_node_at_path(root, &path, get_node).unwrap(),
+            Some(nodes[&keccak256(leaf(1, &[0xaa; 32]))].clone())
+        );
+        path.pop();
+        path.push(2);
+        assert_eq!(
+            trie_node_at_path(root, &path, get_node).unwrap(),
+            Some(Bytes::from(leaf(1, &[0xbb])))
+        );
+
+        // paths ending inside of the extension
+        assert_eq!(trie_node_at_path(root, &[1], get_node).unwrap(), None);
+        assert_eq!(trie_node_at_path(root, &[3], get_node).unwrap(), None);
+    }
+}
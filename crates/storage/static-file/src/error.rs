@@ -0,0 +1,20 @@
+use reth_db::Error as DbError;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// A static file error.
+#[derive(Error, Debug)]
+pub enum StaticFileError {
+    /// An internal database error occurred.
+    #[error("An internal database error occurred: {0}")]
+    Database(#[from] DbError),
+    /// Reading or writing a static file failed.
+    #[error("Static file I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The name or the content of a static file is not valid.
+    #[error("Static file {path:?} is corrupted")]
+    Corrupted {
+        /// Path of the static file.
+        path: PathBuf,
+    },
+}
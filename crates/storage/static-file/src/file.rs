@@ -0,0 +1,226 @@
+//! Layout of a single static file.
+//!
+//! A static file holding the keys `start..=end` of a segment is made of two files:
+//! - `<name>.dat`: the values, concatenated in key order. They are stored as in the database,
+//!   compressed with [`Table::COMPRESSION`](reth_db::table::Table::COMPRESSION).
+//! - `<name>.idx`: for each key, the little endian `u64` offset and `u32` length of its value in
+//!   the data file. Keys without a value, e.g. pruned receipts, have a length of `u32::MAX`.
+//!
+//! Both are written to temporary files and renamed once complete, the index last, so a static
+//! file is only picked up once it was fully written.
+
+use crate::{StaticFileError, StaticFileSegment, StaticFileTable};
+use memmap2::Mmap;
+use reth_db::{table::Decompress, Error as DbError};
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+/// Size of an entry of the index file.
+const INDEX_ENTRY_SIZE: usize = 12;
+
+/// Length of the index entries of keys without a value.
+const ABSENT: u32 = u32::MAX;
+
+/// Extension of the data files.
+const DATA_EXTENSION: &str = "dat";
+
+/// Extension of the index files.
+const INDEX_EXTENSION: &str = "idx";
+
+/// Returns the paths of the data and index files of a static file.
+pub(crate) fn paths(
+    dir: &Path,
+    segment: StaticFileSegment,
+    range: &RangeInclusive<u64>,
+) -> (PathBuf, PathBuf) {
+    let path = dir.join(segment.file_name(range));
+    (path.with_extension(DATA_EXTENSION), path.with_extension(INDEX_EXTENSION))
+}
+
+/// A memory-mapped static file, holding the values of a range of keys of a segment.
+#[derive(Debug)]
+pub struct StaticFile {
+    segment: StaticFileSegment,
+    range: RangeInclusive<u64>,
+    data: Mmap,
+    index: Mmap,
+}
+
+impl StaticFile {
+    /// Opens the static file of `segment` holding `range`, in the directory `dir`.
+    pub fn open(
+        dir: &Path,
+        segment: StaticFileSegment,
+        range: RangeInclusive<u64>,
+    ) -> Result<Self, StaticFileError> {
+        let (data_path, index_path) = paths(dir, segment, &range);
+        // SAFETY: static files are never modified once they are renamed to their final path.
+        let data = unsafe { Mmap::map(&File::open(&data_path)?)? };
+        let index = unsafe { Mmap::map(&File::open(&index_path)?)? };
+
+        let len = (range.end() - range.start() + 1) as usize;
+        if index.len() != len * INDEX_ENTRY_SIZE {
+            return Err(StaticFileError::Corrupted { path: index_path })
+        }
+
+        Ok(Self { segment, range, data, index })
+    }
+
+    /// Returns the segment of the static file.
+    pub fn segment(&self) -> StaticFileSegment {
+        self.segment
+    }
+
+    /// Returns the range of keys held by the static file.
+    pub fn range(&self) -> &RangeInclusive<u64> {
+        &self.range
+    }
+
+    /// Returns the stored value of `key`, still compressed.
+    ///
+    /// Returns `None` if the key is not in the range of the file, or has no value.
+    pub fn raw(&self, key: u64) -> Result<Option<&[u8]>, DbError> {
+        if !self.range.contains(&key) {
+            return Ok(None)
+        }
+
+        let entry = (key - self.range.start()) as usize * INDEX_ENTRY_SIZE;
+        let entry = &self.index[entry..entry + INDEX_ENTRY_SIZE];
+        let offset = u64::from_le_bytes(entry[..8].try_into().expect("8 bytes")) as usize;
+        let len = u32::from_le_bytes(entry[8..].try_into().expect("4 bytes"));
+        if len == ABSENT {
+            return Ok(None)
+        }
+
+        self.data.get(offset..offset + len as usize).map(Some).ok_or(DbError::DecodeError)
+    }
+
+    /// Returns the decompressed value of `key` of the table `T`.
+    pub fn get<T: StaticFileTable>(&self, key: u64) -> Result<Option<T::Value>, DbError> {
+        debug_assert_eq!(self.segment, T::SEGMENT);
+        let Some(raw) = self.raw(key)? else { return Ok(None) };
+        let value = T::COMPRESSION.decompress(Cow::Borrowed(raw))?;
+        Decompress::decompress(value.into_owned()).map(Some)
+    }
+}
+
+/// Writes a new [`StaticFile`], one key after the other.
+#[derive(Debug)]
+pub struct StaticFileWriter {
+    dir: PathBuf,
+    segment: StaticFileSegment,
+    start: u64,
+    next: u64,
+    offset: u64,
+    data_path: PathBuf,
+    data: BufWriter<File>,
+    index_path: PathBuf,
+    index: BufWriter<File>,
+}
+
+impl StaticFileWriter {
+    /// Starts writing a static file of `segment` in the directory `dir`, whose first key is
+    /// `start`.
+    pub fn new(
+        dir: &Path,
+        segment: StaticFileSegment,
+        start: u64,
+    ) -> Result<Self, StaticFileError> {
+        let path = dir.join(format!("static_file_{segment}_{start}"));
+        let data_path = path.with_extension(format!("{DATA_EXTENSION}.tmp"));
+        let index_path = path.with_extension(format!("{INDEX_EXTENSION}.tmp"));
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            segment,
+            start,
+            next: start,
+            offset: 0,
+            data: BufWriter::new(File::create(&data_path)?),
+            data_path,
+            index: BufWriter::new(File::create(&index_path)?),
+            index_path,
+        })
+    }
+
+    /// Appends the stored value of the next key, or `None` if it has no value.
+    pub fn append(&mut self, value: Option<&[u8]>) -> Result<(), StaticFileError> {
+        let len = match value {
+            Some(value) => {
+                self.data.write_all(value)?;
+                value.len() as u32
+            }
+            None => ABSENT,
+        };
+        self.index.write_all(&self.offset.to_le_bytes())?;
+        self.index.write_all(&len.to_le_bytes())?;
+
+        if len != ABSENT {
+            self.offset += len as u64;
+        }
+        self.next += 1;
+        Ok(())
+    }
+
+    /// Returns the key of the next value to append.
+    pub fn next_key(&self) -> u64 {
+        self.next
+    }
+
+    /// Flushes the files to disk, moves them to their final path and opens the static file.
+    ///
+    /// At least one value must have been appended.
+    pub fn finish(self) -> Result<StaticFile, StaticFileError> {
+        if self.next == self.start {
+            return Err(StaticFileError::Corrupted { path: self.index_path })
+        }
+
+        let range = self.start..=self.next - 1;
+        let (data_path, index_path) = paths(&self.dir, self.segment, &range);
+        for (file, from, to) in
+            [(self.data, self.data_path, data_path), (self.index, self.index_path, index_path)]
+        {
+            file.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+            fs::rename(from, to)?;
+        }
+
+        StaticFile::open(&self.dir, self.segment, range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        table::{Compress, Table},
+        tables,
+    };
+    use reth_primitives::Header;
+
+    #[test]
+    fn write_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let header = Header { number: 11, ..Default::default() };
+        let stored = tables::Headers::COMPRESSION.compress(header.clone().compress().as_ref());
+
+        let mut writer = StaticFileWriter::new(dir.path(), StaticFileSegment::Headers, 10).unwrap();
+        writer.append(None).unwrap();
+        writer.append(Some(&stored)).unwrap();
+        let file = writer.finish().unwrap();
+        assert_eq!(file.range(), &(10..=11));
+
+        assert_eq!(file.get::<tables::Headers>(9), Ok(None));
+        assert_eq!(file.get::<tables::Headers>(10), Ok(None));
+        assert_eq!(file.get::<tables::Headers>(11), Ok(Some(header.clone())));
+        assert_eq!(file.get::<tables::Headers>(12), Ok(None));
+
+        // The temporary files were moved, and the file can be opened again
+        let file = StaticFile::open(dir.path(), StaticFileSegment::Headers, 10..=11).unwrap();
+        assert_eq!(file.get::<tables::Headers>(11), Ok(Some(header)));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
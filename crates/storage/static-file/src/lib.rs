@@ -0,0 +1,28 @@
+#![warn(missing_docs, unreachable_pub)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Static files holding the finalized blocks, outside of the database.
+//!
+//! Headers, bodies, transactions and receipts never change once their block is finalized, so the
+//! [`StaticFileProducer`] moves them out of MDBX into append-only flat files. Each file holds a
+//! contiguous range of keys of a single [`StaticFileSegment`], and is memory-mapped by the
+//! [`StaticFileProvider`] to read them back.
+
+mod error;
+pub use error::StaticFileError;
+
+mod segment;
+pub use segment::{StaticFileSegment, StaticFileTable};
+
+mod file;
+pub use file::{StaticFile, StaticFileWriter};
+
+mod provider;
+pub use provider::StaticFileProvider;
+
+mod producer;
+pub use producer::{StaticFileProducer, BLOCKS_PER_STATIC_FILE};
@@ -0,0 +1,244 @@
+use crate::{
+    StaticFileError, StaticFileProvider, StaticFileSegment, StaticFileTable, StaticFileWriter,
+};
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    tables::{self, RawKey, RawTable},
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::BlockNumber;
+use std::ops::Range;
+use tracing::*;
+
+/// The default number of blocks held by each static file.
+pub const BLOCKS_PER_STATIC_FILE: u64 = 10_000;
+
+/// Moves the values of the finalized blocks from the database into new static files.
+///
+/// Blocks are moved by chunks of [`BLOCKS_PER_STATIC_FILE`], once the whole chunk is further than
+/// the distance of the producer from the tip. A static file is written for each segment and
+/// chunk, and the values of the chunk are deleted from the database afterwards, in a transaction
+/// of its own. A crash in between leaves them in both stores, until
+/// [`StaticFileProvider::reconcile`] deletes them from the database.
+#[derive(Debug, Clone)]
+pub struct StaticFileProducer {
+    provider: StaticFileProvider,
+    distance: u64,
+    blocks_per_file: u64,
+}
+
+impl StaticFileProducer {
+    /// Creates a new producer, which writes to the directory of `provider` the blocks further
+    /// than `distance` from the tip.
+    pub fn new(provider: StaticFileProvider, distance: u64) -> Self {
+        Self { provider, distance, blocks_per_file: BLOCKS_PER_STATIC_FILE }
+    }
+
+    /// Set the number of blocks held by each static file.
+    pub fn with_blocks_per_file(mut self, blocks_per_file: u64) -> Self {
+        self.blocks_per_file = blocks_per_file.max(1);
+        self
+    }
+
+    /// Returns the provider reading the produced static files.
+    pub fn provider(&self) -> &StaticFileProvider {
+        &self.provider
+    }
+
+    /// Moves every full chunk of blocks further than the distance from `tip` into static files.
+    ///
+    /// Each chunk is committed to the database once its static files are written, so no other
+    /// write transaction may be open on `db`.
+    pub fn run<DB: Database>(&self, db: &DB, tip: BlockNumber) -> Result<(), StaticFileError> {
+        let Some(finalized) = tip.checked_sub(self.distance) else { return Ok(()) };
+
+        let mut next_block = self.next_key(StaticFileSegment::Headers);
+        while next_block + self.blocks_per_file - 1 <= finalized {
+            let tx = db.tx_mut()?;
+            let last_block = next_block + self.blocks_per_file - 1;
+            let body = match tx.get::<tables::BlockBodies>(last_block)? {
+                Some(body) => body,
+                None => match self.provider.get::<tables::BlockBodies>(last_block)? {
+                    Some(body) => body,
+                    None => break,
+                },
+            };
+
+            for segment in StaticFileSegment::ALL {
+                let end =
+                    if segment.is_tx_based() { body.tx_id_range().end } else { last_block + 1 };
+                let range = self.next_key(segment)..end;
+                if range.is_empty() {
+                    continue
+                }
+
+                debug!(target: "static_file", %segment, ?range, "Moving to static file");
+                match segment {
+                    StaticFileSegment::Headers => {
+                        self.move_range::<_, tables::Headers>(&tx, range)?
+                    }
+                    StaticFileSegment::BlockBodies => {
+                        self.move_range::<_, tables::BlockBodies>(&tx, range)?
+                    }
+                    StaticFileSegment::Transactions => {
+                        self.move_range::<_, tables::Transactions>(&tx, range)?
+                    }
+                    StaticFileSegment::Receipts => {
+                        self.move_range::<_, tables::Receipts>(&tx, range)?
+                    }
+                }
+            }
+
+            tx.commit()?;
+            next_block = last_block + 1;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the first key of `segment` which is not in the static files.
+    fn next_key(&self, segment: StaticFileSegment) -> u64 {
+        self.provider.highest(segment).map_or(0, |highest| highest + 1)
+    }
+
+    /// Writes the values of `range` of the table `T` into a new static file, and deletes them
+    /// from the database.
+    fn move_range<'tx, TX, T>(&self, tx: &TX, range: Range<u64>) -> Result<(), StaticFileError>
+    where
+        TX: DbTx<'tx> + DbTxMut<'tx>,
+        T: StaticFileTable,
+    {
+        let mut writer = StaticFileWriter::new(self.provider.path(), T::SEGMENT, range.start)?;
+
+        let mut cursor = tx.cursor_read::<RawTable<T>>()?;
+        let mut walker = cursor.walk_range(RawKey::new(range.start)..RawKey::new(range.end))?;
+        while let Some((key, value)) = walker.next().transpose()? {
+            let key = key.key()?;
+            while writer.next_key() < key {
+                writer.append(None)?;
+            }
+            writer.append(Some(value.raw_value()))?;
+        }
+        while writer.next_key() < range.end {
+            writer.append(None)?;
+        }
+
+        self.provider.insert(writer.finish()?);
+        tx.delete_range::<T>(range)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        models::StoredBlockBody,
+    };
+    use reth_primitives::{Header, Receipt};
+
+    #[test]
+    fn move_finalized_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+
+        // Blocks 0..20 with one transaction each, whose receipts up to 5 are pruned
+        let tx = db.tx_mut().unwrap();
+        for number in 0..20 {
+            tx.put::<tables::Headers>(number, Header { number, ..Default::default() }).unwrap();
+            tx.put::<tables::BlockBodies>(
+                number,
+                StoredBlockBody { start_tx_id: number, tx_count: 1 },
+            )
+            .unwrap();
+            if number > 5 {
+                tx.put::<tables::Receipts>(number, Receipt::default()).unwrap();
+            }
+        }
+        tx.commit().unwrap();
+
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        let producer = StaticFileProducer::new(provider.clone(), 5).with_blocks_per_file(4);
+
+        producer.run(db.as_ref(), 13).unwrap();
+
+        // Blocks up to 8 are finalized, so the chunks 0..=3 and 4..=7 are moved
+        assert_eq!(provider.highest(StaticFileSegment::Headers), Some(7));
+        assert_eq!(provider.highest(StaticFileSegment::Receipts), Some(7));
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.get::<tables::Headers>(7).unwrap(), None);
+        assert!(tx.get::<tables::Headers>(8).unwrap().is_some());
+        assert_eq!(
+            provider.get::<tables::Headers>(7),
+            Ok(Some(Header { number: 7, ..Default::default() }))
+        );
+        assert_eq!(provider.get::<tables::Receipts>(5), Ok(None));
+        assert_eq!(provider.get::<tables::Receipts>(6), Ok(Some(Receipt::default())));
+
+        let numbers = |range| {
+            provider
+                .get_range::<tables::Headers>(range)
+                .unwrap()
+                .into_iter()
+                .map(|(number, _)| number)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(numbers(2..6), vec![2, 3, 4, 5]);
+        assert_eq!(numbers(6..20), vec![6, 7]);
+
+        // The files are found again when reopening the directory
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        assert_eq!(provider.highest(StaticFileSegment::BlockBodies), Some(7));
+        assert_eq!(
+            provider.get::<tables::BlockBodies>(3),
+            Ok(Some(StoredBlockBody { start_tx_id: 3, tx_count: 1 }))
+        );
+    }
+
+    #[test]
+    fn reconcile_with_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+
+        // Blocks 0..12 without transactions
+        let tx = db.tx_mut().unwrap();
+        for number in 0..12 {
+            tx.put::<tables::CanonicalHeaders>(number, Default::default()).unwrap();
+            tx.put::<tables::Headers>(number, Header { number, ..Default::default() }).unwrap();
+            tx.put::<tables::BlockBodies>(number, StoredBlockBody::default()).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        let producer = StaticFileProducer::new(provider.clone(), 0).with_blocks_per_file(4);
+        producer.run(db.as_ref(), 11).unwrap();
+        assert_eq!(provider.highest(StaticFileSegment::Headers), Some(11));
+
+        // The deletion of a header was not committed, and the database was reset to block 7
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::Headers>(5, Header { number: 5, ..Default::default() }).unwrap();
+        tx.delete_range::<tables::CanonicalHeaders>(8..).unwrap();
+        tx.commit().unwrap();
+
+        // A temporary file left by a crash is removed on open
+        std::fs::write(dir.path().join("static_file_headers_12.dat.tmp"), []).unwrap();
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        assert!(!dir.path().join("static_file_headers_12.dat.tmp").exists());
+
+        provider.reconcile(db.as_ref()).unwrap();
+        assert_eq!(provider.highest(StaticFileSegment::Headers), Some(7));
+        assert_eq!(provider.highest(StaticFileSegment::BlockBodies), Some(7));
+        assert_eq!(db.tx().unwrap().get::<tables::Headers>(5).unwrap(), None);
+        assert_eq!(
+            provider.get::<tables::Headers>(5),
+            Ok(Some(Header { number: 5, ..Default::default() }))
+        );
+
+        // The removed files are not found again
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        assert_eq!(provider.highest(StaticFileSegment::Headers), Some(7));
+    }
+}
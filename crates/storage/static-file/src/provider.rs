@@ -0,0 +1,212 @@
+use crate::{file::paths, StaticFile, StaticFileError, StaticFileSegment, StaticFileTable};
+use parking_lot::RwLock;
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    Error as DbError,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    ops::{Bound, RangeBounds},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::*;
+
+/// Static files of each segment, indexed by the first key of their range.
+type StaticFiles = HashMap<StaticFileSegment, BTreeMap<u64, Arc<StaticFile>>>;
+
+/// Reads the values held by the static files of a directory.
+///
+/// It is cheap to clone, and the clones share the files, so the files added by a
+/// [`StaticFileProducer`](crate::StaticFileProducer) are seen by all of them.
+#[derive(Debug, Clone)]
+pub struct StaticFileProvider {
+    /// Directory of the static files.
+    path: Arc<PathBuf>,
+    /// Opened static files.
+    files: Arc<RwLock<StaticFiles>>,
+}
+
+impl StaticFileProvider {
+    /// Opens all the static files of the directory `path`, creating it if needed.
+    ///
+    /// Files which were not completely written are removed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StaticFileError> {
+        let path = path.as_ref();
+        fs::create_dir_all(path)?;
+
+        let mut files = StaticFiles::default();
+        for entry in fs::read_dir(path)? {
+            let entry = entry?.path();
+            match entry.extension().and_then(|extension| extension.to_str()) {
+                Some("idx") => {}
+                // the data file is renamed first, so it is incomplete without its index
+                Some("tmp") => {
+                    debug!(target: "static_file", ?entry, "Removing incomplete static file");
+                    fs::remove_file(&entry)?;
+                    continue
+                }
+                Some("dat") if !entry.with_extension("idx").exists() => {
+                    debug!(target: "static_file", ?entry, "Removing incomplete static file");
+                    fs::remove_file(&entry)?;
+                    continue
+                }
+                _ => continue,
+            }
+            let Some((segment, range)) = entry
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(StaticFileSegment::parse_file_name)
+            else {
+                return Err(StaticFileError::Corrupted { path: entry })
+            };
+
+            let file = StaticFile::open(path, segment, range)?;
+            files.entry(segment).or_default().insert(*file.range().start(), Arc::new(file));
+        }
+
+        debug!(target: "static_file", ?path, files = files.values().map(BTreeMap::len).sum::<usize>(), "Opened static files");
+        Ok(Self { path: Arc::new(path.to_path_buf()), files: Arc::new(RwLock::new(files)) })
+    }
+
+    /// Returns the directory of the static files.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Makes a newly written static file available to the readers.
+    pub fn insert(&self, file: StaticFile) {
+        let mut files = self.files.write();
+        files.entry(file.segment()).or_default().insert(*file.range().start(), Arc::new(file));
+    }
+
+    /// Brings the static files in line with the database, on startup.
+    ///
+    /// The static files holding keys the database does not know, e.g. blocks above its canonical
+    /// tip after the database was reset, are removed. The values of the static files which are
+    /// still in the database, since their deletion was not committed before a crash, are deleted
+    /// from it.
+    pub fn reconcile<DB: Database>(&self, db: &DB) -> Result<(), StaticFileError> {
+        let tx = db.tx_mut()?;
+
+        let next_block = tx
+            .cursor_read::<tables::CanonicalHeaders>()?
+            .last()?
+            .map_or(0, |(number, _)| number + 1);
+        self.remove_from(StaticFileSegment::Headers, next_block)?;
+        self.remove_from(StaticFileSegment::BlockBodies, next_block)?;
+
+        let last_body = match tx.cursor_read::<tables::BlockBodies>()?.last()? {
+            Some((_, body)) => Some(body),
+            None => match self.highest(StaticFileSegment::BlockBodies) {
+                Some(highest) => self.get::<tables::BlockBodies>(highest)?,
+                None => None,
+            },
+        };
+        let next_tx = last_body.map_or(0, |body| body.tx_id_range().end);
+        self.remove_from(StaticFileSegment::Transactions, next_tx)?;
+        self.remove_from(StaticFileSegment::Receipts, next_tx)?;
+
+        self.delete_moved::<_, tables::Headers>(&tx)?;
+        self.delete_moved::<_, tables::BlockBodies>(&tx)?;
+        self.delete_moved::<_, tables::Transactions>(&tx)?;
+        self.delete_moved::<_, tables::Receipts>(&tx)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Removes the static files of `segment` holding keys from `key` on.
+    fn remove_from(&self, segment: StaticFileSegment, key: u64) -> Result<(), StaticFileError> {
+        let mut files = self.files.write();
+        let Some(segment_files) = files.get_mut(&segment) else { return Ok(()) };
+        while let Some(entry) =
+            segment_files.last_entry().filter(|file| *file.get().range().end() >= key)
+        {
+            let file = entry.remove();
+            warn!(target: "static_file", %segment, range = ?file.range(), "Removing static file unknown to the database");
+            // the index is removed first, so that the file is not picked up anymore
+            let (data_path, index_path) = paths(&self.path, segment, file.range());
+            fs::remove_file(index_path)?;
+            fs::remove_file(data_path)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the values of the table `T` which are still in the database although they were
+    /// moved to the static files.
+    ///
+    /// The static files of a segment hold all the keys up to the highest one, so every value of
+    /// the database up to it was moved.
+    fn delete_moved<'tx, TX, T>(&self, tx: &TX) -> Result<(), StaticFileError>
+    where
+        TX: DbTx<'tx> + DbTxMut<'tx>,
+        T: StaticFileTable,
+    {
+        let Some(highest) = self.highest(T::SEGMENT) else { return Ok(()) };
+        let Some((first, _)) = tx.cursor_read::<T>()?.first()? else { return Ok(()) };
+        if first <= highest {
+            debug!(target: "static_file", segment = %T::SEGMENT, first, highest, "Deleting values moved to static files");
+            tx.delete_range::<T>(first..=highest)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the highest key of `segment` held by the static files.
+    pub fn highest(&self, segment: StaticFileSegment) -> Option<u64> {
+        let files = self.files.read();
+        files.get(&segment)?.values().next_back().map(|file| *file.range().end())
+    }
+
+    /// Returns the static file of the segment of `T` holding `key`.
+    fn file<T: StaticFileTable>(&self, key: u64) -> Option<Arc<StaticFile>> {
+        let files = self.files.read();
+        let (_, file) = files.get(&T::SEGMENT)?.range(..=key).next_back()?;
+        file.range().contains(&key).then(|| file.clone())
+    }
+
+    /// Returns the value of `key` of the table `T`, if it was moved to the static files.
+    pub fn get<T: StaticFileTable>(&self, key: u64) -> Result<Option<T::Value>, DbError> {
+        match self.file::<T>(key) {
+            Some(file) => file.get::<T>(key),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the values of the table `T` in `range` which were moved to the static files, in
+    /// key order.
+    pub fn get_range<T: StaticFileTable>(
+        &self,
+        range: impl RangeBounds<u64>,
+    ) -> Result<Vec<(u64, T::Value)>, DbError> {
+        let Some(highest) = self.highest(T::SEGMENT) else { return Ok(Vec::new()) };
+
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => (*end).min(highest),
+            Bound::Excluded(0) => return Ok(Vec::new()),
+            Bound::Excluded(end) => (end - 1).min(highest),
+            Bound::Unbounded => highest,
+        };
+
+        let mut values = Vec::new();
+        let mut key = start;
+        while key <= end {
+            let Some(file) = self.file::<T>(key) else { break };
+            for key in key..=end.min(*file.range().end()) {
+                if let Some(value) = file.get::<T>(key)? {
+                    values.push((key, value));
+                }
+            }
+            key = file.range().end() + 1;
+        }
+        Ok(values)
+    }
+}
@@ -0,0 +1,116 @@
+use reth_db::{table::Table, tables};
+use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
+
+/// A kind of data that is moved into static files. Each segment is a table keyed by block or
+/// transaction number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StaticFileSegment {
+    /// [`tables::Headers`], keyed by block number.
+    Headers,
+    /// [`tables::BlockBodies`], keyed by block number.
+    BlockBodies,
+    /// [`tables::Transactions`], keyed by transaction number.
+    Transactions,
+    /// [`tables::Receipts`], keyed by transaction number.
+    Receipts,
+}
+
+impl StaticFileSegment {
+    /// All the segments, in the order they are produced.
+    ///
+    /// The transaction segments come first, since their range is found from the bodies.
+    pub const ALL: [StaticFileSegment; 4] = [
+        StaticFileSegment::Transactions,
+        StaticFileSegment::Receipts,
+        StaticFileSegment::BlockBodies,
+        StaticFileSegment::Headers,
+    ];
+
+    /// Returns the name of the segment, used in the names of its files.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            StaticFileSegment::Headers => "headers",
+            StaticFileSegment::BlockBodies => "bodies",
+            StaticFileSegment::Transactions => "transactions",
+            StaticFileSegment::Receipts => "receipts",
+        }
+    }
+
+    /// Returns `true` if the segment is keyed by transaction number.
+    pub const fn is_tx_based(&self) -> bool {
+        matches!(self, StaticFileSegment::Transactions | StaticFileSegment::Receipts)
+    }
+
+    /// Returns the file name, without extension, of the static file holding `range`.
+    pub fn file_name(&self, range: &RangeInclusive<u64>) -> String {
+        format!("static_file_{}_{}_{}", self.name(), range.start(), range.end())
+    }
+
+    /// Parses a file name returned by [`StaticFileSegment::file_name`].
+    pub fn parse_file_name(name: &str) -> Option<(Self, RangeInclusive<u64>)> {
+        let mut parts = name.strip_prefix("static_file_")?.split('_');
+        let segment = parts.next()?.parse().ok()?;
+        let start = parts.next()?.parse().ok()?;
+        let end = parts.next()?.parse().ok()?;
+        if parts.next().is_some() || start > end {
+            return None
+        }
+        Some((segment, start..=end))
+    }
+}
+
+impl Display for StaticFileSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for StaticFileSegment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StaticFileSegment::ALL
+            .into_iter()
+            .find(|segment| segment.name() == s)
+            .ok_or_else(|| format!("unknown static file segment `{s}`"))
+    }
+}
+
+/// A table whose values can be moved into the static files of a [`StaticFileSegment`].
+pub trait StaticFileTable: Table<Key = u64> {
+    /// The segment holding the values of the table.
+    const SEGMENT: StaticFileSegment;
+}
+
+impl StaticFileTable for tables::Headers {
+    const SEGMENT: StaticFileSegment = StaticFileSegment::Headers;
+}
+
+impl StaticFileTable for tables::BlockBodies {
+    const SEGMENT: StaticFileSegment = StaticFileSegment::BlockBodies;
+}
+
+impl StaticFileTable for tables::Transactions {
+    const SEGMENT: StaticFileSegment = StaticFileSegment::Transactions;
+}
+
+impl StaticFileTable for tables::Receipts {
+    const SEGMENT: StaticFileSegment = StaticFileSegment::Receipts;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_name_roundtrip() {
+        for segment in StaticFileSegment::ALL {
+            let name = segment.file_name(&(10..=20));
+            assert_eq!(StaticFileSegment::parse_file_name(&name), Some((segment, 10..=20)));
+        }
+
+        assert_eq!(StaticFileSegment::parse_file_name("static_file_headers_20_10"), None);
+        assert_eq!(StaticFileSegment::parse_file_name("static_file_ommers_0_10"), None);
+        assert_eq!(StaticFileSegment::parse_file_name("headers_0_10"), None);
+    }
+}
@@ -306,6 +306,37 @@ impl TaskExecutor {
         name: &'static str,
         f: impl FnOnce(Shutdown) -> F,
     ) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_critical_with_signal_as(name, f, TaskKind::Default)
+    }
+
+    /// This spawns a critical blocking task onto the runtime.
+    ///
+    /// Unlike [`TaskExecutor::spawn_critical_blocking`], the task is not dropped once the
+    /// [Shutdown] signal is received: it is expected to stop on its own, e.g. once it reached a
+    /// consistent state.
+    ///
+    /// If this task panics, the [`TaskManager`] is notified.
+    pub fn spawn_critical_blocking_with_signal<F>(
+        &self,
+        name: &'static str,
+        f: impl FnOnce(Shutdown) -> F,
+    ) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_critical_with_signal_as(name, f, TaskKind::Blocking)
+    }
+
+    /// Spawns a critical task given the [Shutdown] signal, depending on the given [TaskKind]
+    fn spawn_critical_with_signal_as<F>(
+        &self,
+        name: &'static str,
+        f: impl FnOnce(Shutdown) -> F,
+        task_kind: TaskKind,
+    ) -> JoinHandle<()>
     where
         F: Future<Output = ()> + Send + 'static,
     {
@@ -323,7 +354,7 @@ impl TaskExecutor {
             .map(|_| ())
             .in_current_span();
 
-        self.handle.spawn(task)
+        self.spawn_on_rt(task, task_kind)
     }
 }
 
@@ -420,4 +451,27 @@ mod tests {
 
         handle.block_on(shutdown);
     }
+
+    // Tests that tasks spawned with the signal are not terminated if the `TaskManager` drops
+    #[test]
+    fn test_manager_shutdown_blocking_with_signal() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+        let manager = TaskManager::new(handle.clone());
+        let executor = manager.executor();
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        executor.spawn_critical_blocking_with_signal(
+            "this is a critical task",
+            |shutdown| async move {
+                shutdown.await;
+                let _ = tx.send(());
+            },
+        );
+
+        drop(manager);
+
+        handle.block_on(rx).unwrap();
+    }
 }
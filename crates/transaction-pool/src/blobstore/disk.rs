@@ -0,0 +1,238 @@
+//! A [`BlobStore`] that keeps the blobs in files on disk.
+
+use crate::blobstore::{BlobStore, BlobStoreError};
+use parking_lot::Mutex;
+use reth_primitives::{
+    eip4844::{kzg_to_versioned_hash, Blob, Bytes48, BYTES_PER_BLOB},
+    BlobTransactionSidecar, TxHash, H256,
+};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs, io,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+use tracing::trace;
+
+/// The size of a commitment or a proof.
+const BYTES_PER_COMMITMENT: usize = 48;
+
+/// The size of a blob file: the blob, its commitment and its proof.
+const BLOB_FILE_SIZE: usize = BYTES_PER_BLOB + 2 * BYTES_PER_COMMITMENT;
+
+/// A [`BlobStore`] that writes every blob, with its commitment and proof, to a file in the blob
+/// directory that is named after the versioned hash of the blob.
+///
+/// Transactions can commit to the same blobs, e.g. the replacement of a blob transaction, so the
+/// blobs are reference counted and a file is only removed once no transaction in the store refers
+/// to it.
+///
+/// Only the index of the store is kept in memory. Since the index does not outlive the store, the
+/// blob directory is cleared when the store is opened.
+#[derive(Debug)]
+pub struct DiskFileBlobStore {
+    /// The directory of the blob files.
+    blob_dir: PathBuf,
+    /// Tracks which blobs the transactions refer to.
+    index: Mutex<BlobIndex>,
+}
+
+// === impl DiskFileBlobStore ===
+
+impl DiskFileBlobStore {
+    /// Opens the store in the given directory, removing all the blobs that are in it.
+    pub fn open(blob_dir: impl Into<PathBuf>) -> Result<Self, BlobStoreError> {
+        let blob_dir = blob_dir.into();
+        match fs::remove_dir_all(&blob_dir) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        fs::create_dir_all(&blob_dir)?;
+        Ok(Self { blob_dir, index: Default::default() })
+    }
+
+    /// Returns the directory of the blob files.
+    pub fn blob_dir(&self) -> &Path {
+        &self.blob_dir
+    }
+
+    /// Returns the path of the file of the blob with the versioned hash.
+    fn blob_path(&self, versioned_hash: &H256) -> PathBuf {
+        self.blob_dir.join(format!("{versioned_hash:x}"))
+    }
+
+    /// Reads the blob with the versioned hash and appends it to the sidecar.
+    fn read_blob(
+        &self,
+        versioned_hash: &H256,
+        sidecar: &mut BlobTransactionSidecar,
+    ) -> Result<(), BlobStoreError> {
+        let data = fs::read(self.blob_path(versioned_hash))?;
+        if data.len() != BLOB_FILE_SIZE {
+            return Err(BlobStoreError::CorruptedBlob(*versioned_hash))
+        }
+
+        let (blob, rest) = data.split_at(BYTES_PER_BLOB);
+        let (commitment, proof) = rest.split_at(BYTES_PER_COMMITMENT);
+        let corrupted = |_| BlobStoreError::CorruptedBlob(*versioned_hash);
+        sidecar.blobs.push(Blob::from_bytes(blob).map_err(corrupted)?);
+        sidecar.commitments.push(Bytes48::from_bytes(commitment).map_err(corrupted)?);
+        sidecar.proofs.push(Bytes48::from_bytes(proof).map_err(corrupted)?);
+        Ok(())
+    }
+
+    /// Releases a reference to the blob, removing its file if it was the last one.
+    fn release(&self, index: &mut BlobIndex, versioned_hash: H256) -> io::Result<()> {
+        let Entry::Occupied(mut entry) = index.refs.entry(versioned_hash) else { return Ok(()) };
+        *entry.get_mut() -= 1;
+        if *entry.get() > 0 {
+            return Ok(())
+        }
+        entry.remove();
+
+        trace!(target: "txpool::blob", ?versioned_hash, "Removing blob");
+        match fs::remove_file(self.blob_path(&versioned_hash)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl BlobStore for DiskFileBlobStore {
+    fn insert(&self, tx: TxHash, sidecar: BlobTransactionSidecar) -> Result<(), BlobStoreError> {
+        let mut index = self.index.lock();
+        if index.txs.contains_key(&tx) {
+            return Ok(())
+        }
+
+        let mut versioned_hashes = Vec::with_capacity(sidecar.blobs.len());
+        for ((blob, commitment), proof) in
+            sidecar.blobs.iter().zip(&sidecar.commitments).zip(&sidecar.proofs)
+        {
+            let versioned_hash = kzg_to_versioned_hash(&commitment[..]);
+            if !index.refs.contains_key(&versioned_hash) {
+                let mut data = Vec::with_capacity(BLOB_FILE_SIZE);
+                data.extend_from_slice(blob.deref());
+                data.extend_from_slice(commitment.deref());
+                data.extend_from_slice(proof.deref());
+                if let Err(err) = fs::write(self.blob_path(&versioned_hash), data) {
+                    // Release the blobs of the transaction that were already counted
+                    for versioned_hash in versioned_hashes {
+                        let _ = self.release(&mut index, versioned_hash);
+                    }
+                    return Err(err.into())
+                }
+                trace!(target: "txpool::blob", ?versioned_hash, "Wrote blob");
+            }
+            *index.refs.entry(versioned_hash).or_default() += 1;
+            versioned_hashes.push(versioned_hash);
+        }
+
+        index.txs.insert(tx, versioned_hashes);
+        Ok(())
+    }
+
+    fn delete(&self, tx: TxHash) -> Result<(), BlobStoreError> {
+        let mut index = self.index.lock();
+        let Some(versioned_hashes) = index.txs.remove(&tx) else { return Ok(()) };
+
+        let mut result = Ok(());
+        for versioned_hash in versioned_hashes {
+            if let Err(err) = self.release(&mut index, versioned_hash) {
+                result = Err(err.into());
+            }
+        }
+        result
+    }
+
+    fn get(&self, tx: TxHash) -> Result<Option<BlobTransactionSidecar>, BlobStoreError> {
+        let index = self.index.lock();
+        let Some(versioned_hashes) = index.txs.get(&tx) else { return Ok(None) };
+
+        let mut sidecar = BlobTransactionSidecar::default();
+        for versioned_hash in versioned_hashes {
+            self.read_blob(versioned_hash, &mut sidecar)?;
+        }
+        Ok(Some(sidecar))
+    }
+
+    fn blobs_len(&self) -> usize {
+        self.index.lock().refs.len()
+    }
+
+    fn data_size_hint(&self) -> usize {
+        self.blobs_len() * BLOB_FILE_SIZE
+    }
+}
+
+/// The in-memory index of a [`DiskFileBlobStore`].
+#[derive(Debug, Default)]
+struct BlobIndex {
+    /// The versioned hashes of the blobs of each transaction.
+    txs: HashMap<TxHash, Vec<H256>>,
+    /// The number of transactions that refer to each blob.
+    refs: HashMap<H256, usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sidecar(blobs: &[u8]) -> BlobTransactionSidecar {
+        let mut sidecar = BlobTransactionSidecar::default();
+        for byte in blobs {
+            sidecar.blobs.push(Blob::from_bytes(&[*byte; BYTES_PER_BLOB]).unwrap());
+            sidecar.commitments.push(Bytes48::from_bytes(&[*byte; 48]).unwrap());
+            sidecar.proofs.push(Bytes48::from_bytes(&[byte.wrapping_add(1); 48]).unwrap());
+        }
+        sidecar
+    }
+
+    #[test]
+    fn stores_blobs_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DiskFileBlobStore::open(dir.path().join("blobstore")).unwrap();
+
+        let tx = TxHash::random();
+        store.insert(tx, sidecar(&[1, 2])).unwrap();
+        assert_eq!(store.blobs_len(), 2);
+        assert_eq!(fs::read_dir(store.blob_dir()).unwrap().count(), 2);
+        assert_eq!(store.get(tx).unwrap(), Some(sidecar(&[1, 2])));
+
+        store.delete(tx).unwrap();
+        assert_eq!(store.get(tx).unwrap(), None);
+        assert_eq!(store.blobs_len(), 0);
+        assert_eq!(fs::read_dir(store.blob_dir()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn shared_blobs_are_reference_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DiskFileBlobStore::open(dir.path()).unwrap();
+
+        let (tx, replacement) = (TxHash::random(), TxHash::random());
+        store.insert(tx, sidecar(&[1, 2])).unwrap();
+        store.insert(replacement, sidecar(&[2, 3])).unwrap();
+        assert_eq!(store.blobs_len(), 3);
+
+        store.delete(tx).unwrap();
+        assert_eq!(store.blobs_len(), 2);
+        assert_eq!(store.get(replacement).unwrap(), Some(sidecar(&[2, 3])));
+
+        store.delete(replacement).unwrap();
+        assert_eq!(fs::read_dir(store.blob_dir()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn open_clears_blob_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DiskFileBlobStore::open(dir.path()).unwrap();
+        store.insert(TxHash::random(), sidecar(&[1])).unwrap();
+        drop(store);
+
+        let store = DiskFileBlobStore::open(dir.path()).unwrap();
+        assert_eq!(store.blobs_len(), 0);
+        assert_eq!(fs::read_dir(store.blob_dir()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,37 @@
+//! A [`BlobStore`] that keeps the sidecars in memory.
+
+use crate::blobstore::{BlobStore, BlobStoreError};
+use parking_lot::RwLock;
+use reth_primitives::{eip4844::BYTES_PER_BLOB, BlobTransactionSidecar, TxHash};
+use std::collections::HashMap;
+
+/// A [`BlobStore`] that keeps the sidecars in memory, e.g. for testing.
+#[derive(Debug, Default)]
+pub struct InMemoryBlobStore {
+    /// The sidecars of the transactions.
+    sidecars: RwLock<HashMap<TxHash, BlobTransactionSidecar>>,
+}
+
+impl BlobStore for InMemoryBlobStore {
+    fn insert(&self, tx: TxHash, sidecar: BlobTransactionSidecar) -> Result<(), BlobStoreError> {
+        self.sidecars.write().entry(tx).or_insert(sidecar);
+        Ok(())
+    }
+
+    fn delete(&self, tx: TxHash) -> Result<(), BlobStoreError> {
+        self.sidecars.write().remove(&tx);
+        Ok(())
+    }
+
+    fn get(&self, tx: TxHash) -> Result<Option<BlobTransactionSidecar>, BlobStoreError> {
+        Ok(self.sidecars.read().get(&tx).cloned())
+    }
+
+    fn blobs_len(&self) -> usize {
+        self.sidecars.read().values().map(|sidecar| sidecar.blobs.len()).sum()
+    }
+
+    fn data_size_hint(&self) -> usize {
+        self.blobs_len() * BYTES_PER_BLOB
+    }
+}
@@ -0,0 +1,51 @@
+//! Storage for the sidecars of blob transactions (EIP-4844).
+//!
+//! The pool only keeps the transactions, which commit to their blobs with the versioned hashes,
+//! while the sidecars are kept in a [`BlobStore`] until the transactions leave the pool.
+
+use reth_primitives::{BlobTransactionSidecar, TxHash, H256};
+use std::fmt;
+
+mod disk;
+mod mem;
+
+pub use disk::DiskFileBlobStore;
+pub use mem::InMemoryBlobStore;
+
+/// A store for the sidecars of blob transactions, keyed by the hash of the transaction.
+pub trait BlobStore: fmt::Debug + Send + Sync + 'static {
+    /// Inserts the sidecar of the transaction.
+    ///
+    /// Inserting the sidecar of a transaction that is already in the store is a no-op.
+    fn insert(&self, tx: TxHash, sidecar: BlobTransactionSidecar) -> Result<(), BlobStoreError>;
+
+    /// Deletes the sidecar of the transaction.
+    ///
+    /// Deleting the sidecar of a transaction that is not in the store is a no-op.
+    fn delete(&self, tx: TxHash) -> Result<(), BlobStoreError>;
+
+    /// Deletes the sidecars of all the transactions.
+    fn delete_all(&self, txs: Vec<TxHash>) -> Result<(), BlobStoreError> {
+        txs.into_iter().try_for_each(|tx| self.delete(tx))
+    }
+
+    /// Returns the sidecar of the transaction, if it is in the store.
+    fn get(&self, tx: TxHash) -> Result<Option<BlobTransactionSidecar>, BlobStoreError>;
+
+    /// Returns the number of distinct blobs in the store.
+    fn blobs_len(&self) -> usize;
+
+    /// Returns the approximate number of bytes the blobs in the store take up.
+    fn data_size_hint(&self) -> usize;
+}
+
+/// Errors of a [`BlobStore`].
+#[derive(Debug, thiserror::Error)]
+pub enum BlobStoreError {
+    /// The blob with the versioned hash could not be decoded from the store.
+    #[error("blob {0:?} is corrupted")]
+    CorruptedBlob(H256),
+    /// The blob store could not be read or written.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
@@ -1,6 +1,10 @@
 /// Guarantees max transactions for one sender, compatible with geth/erigon
 pub(crate) const MAX_ACCOUNT_SLOTS_PER_SENDER: usize = 16;
 
+/// The default percentage by which the fees of a transaction must exceed the fees of the
+/// transaction it replaces, compatible with geth.
+pub const DEFAULT_PRICE_BUMP: u128 = 10;
+
 ///! Configuration options for the Transaction pool.
 #[derive(Debug, Clone)]
 pub struct PoolConfig {
@@ -12,6 +16,14 @@ pub struct PoolConfig {
     pub queued_limit: SubPoolLimit,
     /// Max number of executable transaction slots guaranteed per account
     pub max_account_slots: usize,
+    /// Min percentage by which the fees of a transaction must exceed the fees of the transaction
+    /// with the same sender and nonce it replaces.
+    pub price_bump: u128,
+    /// Whether local transactions are treated like external ones.
+    ///
+    /// By default, local transactions are exempt from the max number of slots per account and
+    /// are never evicted to enforce the limits of the sub-pools.
+    pub no_local_exemptions: bool,
 }
 
 impl Default for PoolConfig {
@@ -21,6 +33,8 @@ impl Default for PoolConfig {
             basefee_limit: Default::default(),
             queued_limit: Default::default(),
             max_account_slots: MAX_ACCOUNT_SLOTS_PER_SENDER,
+            price_bump: DEFAULT_PRICE_BUMP,
+            no_local_exemptions: false,
         }
     }
 }
@@ -1,6 +1,6 @@
 //! Transaction pool errors
 
-use reth_primitives::{Address, TxHash};
+use reth_primitives::{Address, BlobTransactionValidationError, TxHash, TxType, U256};
 
 /// Transaction pool result type.
 pub type PoolResult<T> = Result<T, PoolError>;
@@ -29,6 +29,41 @@ pub enum PoolError {
     /// respect the max_init_code_size.
     #[error("[{0:?}] Transaction's size {1} exceeds max_init_code_size {2}.")]
     TxExceedsMaxInitCodeSize(TxHash, usize, usize),
+    /// Thrown if the chain id of the transaction does not match the chain.
+    #[error("[{0:?}] Transaction's chain id {1} does not match the chain.")]
+    InvalidChainId(TxHash, u64),
+    /// Thrown if the type of the transaction is not enabled by the current fork, e.g. EIP-1559
+    /// transactions before London.
+    #[error("[{0:?}] Transaction type {1:?} is not supported.")]
+    TxTypeNotSupported(TxHash, TxType),
+    /// Thrown if the priority fee of an EIP-1559 transaction exceeds its fee cap.
+    #[error("[{0:?}] Transaction's max priority fee per gas exceeds its max fee per gas.")]
+    TipAboveFeeCap(TxHash),
+    /// Thrown if the gas limit of the transaction does not cover its intrinsic gas.
+    #[error("[{0:?}] Transaction's gas limit {1} is below its intrinsic gas {2}.")]
+    IntrinsicGasTooLow(TxHash, u64, u64),
+    /// Thrown if the nonce of the transaction is lower than the nonce of the sender.
+    #[error("[{0:?}] Transaction's nonce {1} is lower than the sender's nonce {2}.")]
+    NonceTooLow(TxHash, u64, u64),
+    /// Thrown if the sender can't pay for the max cost of the transaction.
+    #[error("[{0:?}] Transaction's cost {1} exceeds the sender's balance {2}.")]
+    InsufficientFunds(TxHash, U256, U256),
+    /// Thrown if a blob transaction was received without its sidecar.
+    #[error("[{0:?}] Blob transaction has no sidecar.")]
+    MissingBlobSidecar(TxHash),
+    /// Thrown if the sidecar of a blob transaction does not hold the blobs it commits to.
+    #[error("[{0:?}] Blob transaction's sidecar is invalid: {1}")]
+    InvalidBlobSidecar(TxHash, BlobTransactionValidationError),
+    /// Thrown if there is no KZG trusted setup to verify the sidecars of blob transactions with.
+    #[error("[{0:?}] No KZG trusted setup to verify the blob transaction with.")]
+    MissingKzgSettings(TxHash),
+    /// Thrown if the max fee per blob gas of a blob transaction is below the blob fee of the
+    /// pending block.
+    #[error("[{0:?}] Transaction's max fee per blob gas {1} is below the blob fee {2}.")]
+    BlobFeeCapTooLow(TxHash, u128, u128),
+    /// Any other error that occurred while validating the transaction, e.g. a database error.
+    #[error("[{0:?}] Failed to validate transaction: {1}")]
+    Other(TxHash, Box<dyn std::error::Error + Send + Sync>),
 }
 
 // === impl PoolError ===
@@ -43,6 +78,17 @@ impl PoolError {
             PoolError::DiscardedOnInsert(hash) => hash,
             PoolError::TxExceedsGasLimit(hash, _, _) => hash,
             PoolError::TxExceedsMaxInitCodeSize(hash, _, _) => hash,
+            PoolError::InvalidChainId(hash, _) => hash,
+            PoolError::TxTypeNotSupported(hash, _) => hash,
+            PoolError::TipAboveFeeCap(hash) => hash,
+            PoolError::IntrinsicGasTooLow(hash, _, _) => hash,
+            PoolError::NonceTooLow(hash, _, _) => hash,
+            PoolError::InsufficientFunds(hash, _, _) => hash,
+            PoolError::MissingBlobSidecar(hash) => hash,
+            PoolError::InvalidBlobSidecar(hash, _) => hash,
+            PoolError::MissingKzgSettings(hash) => hash,
+            PoolError::BlobFeeCapTooLow(hash, _, _) => hash,
+            PoolError::Other(hash, _) => hash,
         }
     }
 }
@@ -45,8 +45,10 @@
 //! ### Validation
 //!
 //! The pool itself does not validate incoming transactions, instead this should be provided by
-//! implementing `TransactionsValidator`. Only transactions that the validator returns as valid are
-//! included in the pool. It is assumed that transaction that are in the pool are either valid on
+//! implementing `TransactionsValidator`, like the [`EthTransactionValidator`] which validates
+//! transactions against the chain spec and the latest state. Validations can be run on a pool of
+//! tasks with the [`TransactionValidationTaskExecutor`]. Only transactions that the validator
+//! returns as valid are included in the pool. It is assumed that transaction that are in the pool are either valid on
 //! the current state or could become valid after certain state changes. transaction that can never
 //! become valid (e.g. nonce lower than current on chain nonce) will never be added to the pool and
 //! instead are discarded right away.
@@ -57,7 +59,11 @@
 //!
 //!   - remove mined transactions
 //!   - update using account changes: balance changes
-//!   - base fee updates
+//!   - base fee updates: transactions are promoted to or demoted from the pending sub-pool
+//!
+//! The [`maintain_transaction_pool`](crate::maintain::maintain_transaction_pool) task applies
+//! the canonical state notifications to the pool and re-injects the transactions of reverted
+//! blocks.
 //!
 //! ## Implementation details
 //!
@@ -75,32 +81,44 @@
 //! guards the low level pool and handles additional listeners or metrics:
 //! [`PoolInner`](crate::pool::PoolInner)
 //!
+//! The sidecars of blob transactions (EIP-4844) are not kept in memory, the pool moves them to a
+//! [`BlobStore`](crate::blobstore::BlobStore), like the
+//! [`DiskFileBlobStore`](crate::blobstore::DiskFileBlobStore), and deletes them once the
+//! transactions are mined or removed from the pool.
+//!
 //! The transaction pool will be used by separate consumers (RPC, P2P), to make sharing easier, the
 //! [`Pool`](crate::Pool) type is just an `Arc` wrapper around `PoolInner`. This is the usable type
 //! that provides the `TransactionPool` interface.
 
-pub use crate::{
-    config::PoolConfig,
-    ordering::TransactionOrdering,
-    traits::{
-        BestTransactions, OnNewBlockEvent, PoolTransaction, PropagateKind, PropagatedTransactions,
-        TransactionOrigin, TransactionPool,
-    },
-    validate::{TransactionValidationOutcome, TransactionValidator},
-};
 use crate::{
+    blobstore::{BlobStore, BlobStoreError},
     error::PoolResult,
     pool::PoolInner,
     traits::{NewTransactionEvent, PoolSize},
     validate::ValidPoolTransaction,
 };
-use reth_primitives::{TxHash, U256};
+pub use crate::{
+    config::{PoolConfig, DEFAULT_PRICE_BUMP},
+    ordering::{CostOrdering, TransactionOrdering},
+    pool::{AllTransactionsEvents, FullTransactionEvent, TransactionEvent, TransactionEvents},
+    traits::{
+        BestTransactions, ChangedAccount, OnNewBlockEvent, PoolTransaction, PooledTransaction,
+        PropagateKind, PropagatedTransactions, StateDiff, TransactionOrigin, TransactionPool,
+    },
+    validate::{
+        EthTransactionValidator, TransactionValidationOutcome, TransactionValidationTaskExecutor,
+        TransactionValidator, ValidationTask, MAX_INIT_CODE_SIZE,
+    },
+};
+use reth_primitives::{BlobTransactionSidecar, TxHash, U256};
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::mpsc::Receiver;
 
+pub mod blobstore;
 mod config;
 pub mod error;
 mod identifier;
+pub mod maintain;
 pub mod metrics;
 mod ordering;
 pub mod pool;
@@ -113,25 +131,28 @@ pub mod test_utils;
 
 /// A shareable, generic, customizable `TransactionPool` implementation.
 #[derive(Debug)]
-pub struct Pool<V: TransactionValidator, T: TransactionOrdering> {
+pub struct Pool<V: TransactionValidator, T: TransactionOrdering, S> {
     /// Arc'ed instance of the pool internals
-    pool: Arc<PoolInner<V, T>>,
+    pool: Arc<PoolInner<V, T, S>>,
 }
 
 // === impl Pool ===
 
-impl<V, T> Pool<V, T>
+impl<V, T, S> Pool<V, T, S>
 where
     V: TransactionValidator,
     T: TransactionOrdering<Transaction = <V as TransactionValidator>::Transaction>,
+    S: BlobStore,
 {
     /// Create a new transaction pool instance.
-    pub fn new(client: Arc<V>, ordering: Arc<T>, config: PoolConfig) -> Self {
-        Self { pool: Arc::new(PoolInner::new(client, ordering, config)) }
+    ///
+    /// The sidecars of blob transactions are kept in the `blob_store`.
+    pub fn new(client: Arc<V>, ordering: Arc<T>, blob_store: S, config: PoolConfig) -> Self {
+        Self { pool: Arc::new(PoolInner::new(client, ordering, blob_store, config)) }
     }
 
     /// Returns the wrapped pool.
-    pub(crate) fn inner(&self) -> &PoolInner<V, T> {
+    pub(crate) fn inner(&self) -> &PoolInner<V, T, S> {
         &self.pool
     }
 
@@ -183,10 +204,11 @@ where
 
 /// implements the `TransactionPool` interface for various transaction pool API consumers.
 #[async_trait::async_trait]
-impl<V, T> TransactionPool for Pool<V, T>
+impl<V, T, S> TransactionPool for Pool<V, T, S>
 where
     V: TransactionValidator,
     T: TransactionOrdering<Transaction = <V as TransactionValidator>::Transaction>,
+    S: BlobStore,
 {
     type Transaction = T::Transaction;
 
@@ -225,10 +247,26 @@ where
         self.pool.add_transaction_listener()
     }
 
+    fn transaction_event_listener(&self, tx_hash: TxHash) -> Option<TransactionEvents> {
+        self.pool.add_transaction_event_listener(tx_hash)
+    }
+
+    fn all_transactions_event_listener(&self) -> AllTransactionsEvents {
+        self.pool.add_all_transactions_event_listener()
+    }
+
     fn pooled_transactions(&self) -> Vec<TxHash> {
         self.pool.pooled_transactions()
     }
 
+    fn pending_transactions(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        self.pool.pending_transactions()
+    }
+
+    fn queued_transactions(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        self.pool.queued_transactions()
+    }
+
     fn best_transactions(
         &self,
     ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<Self::Transaction>>>> {
@@ -260,9 +298,13 @@ where
     fn on_propagated(&self, txs: PropagatedTransactions) {
         self.inner().on_propagated(txs)
     }
+
+    fn get_blob(&self, tx_hash: TxHash) -> Result<Option<BlobTransactionSidecar>, BlobStoreError> {
+        self.inner().get_blob(tx_hash)
+    }
 }
 
-impl<V: TransactionValidator, O: TransactionOrdering> Clone for Pool<V, O> {
+impl<V: TransactionValidator, O: TransactionOrdering, S> Clone for Pool<V, O, S> {
     fn clone(&self) -> Self {
         Self { pool: Arc::clone(&self.pool) }
     }
@@ -0,0 +1,178 @@
+//! Support for maintaining the state of the transaction pool
+
+use crate::{
+    traits::{ChangedAccount, StateDiff},
+    OnNewBlockEvent, TransactionOrigin, TransactionPool,
+};
+use reth_consensus::validation::calculate_next_block_base_fee;
+use reth_executor::blockchain_tree::{CanonStateNotification, CanonStateNotifications};
+use reth_primitives::{FromRecoveredTransaction, Transaction, TransactionSigned, H256};
+use reth_provider::StateProviderFactory;
+use std::collections::HashSet;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, trace, warn};
+
+/// Maintains the state of the transaction pool by applying the changes of the canonical chain.
+///
+/// For every notification, the transactions of the committed blocks are removed from the pool,
+/// and the pool is updated with the base fee of the block after the new tip and the nonces and
+/// balances of the accounts the committed blocks changed. Transactions that are no longer valid,
+/// because their nonce was used, are dropped.
+///
+/// On a reorg, the transactions of the reverted blocks that are not in the committed blocks are
+/// re-injected into the pool. Blob transactions are not re-injected, since their sidecars are not
+/// part of the blocks.
+///
+/// This runs until the sender of the `events` is dropped.
+pub async fn maintain_transaction_pool<Client, P>(
+    client: Client,
+    pool: P,
+    mut events: CanonStateNotifications,
+) where
+    Client: StateProviderFactory,
+    P: TransactionPool,
+{
+    loop {
+        let notification = match events.recv().await {
+            Ok(notification) => notification,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(target: "txpool", skipped, "Missed canonical state notifications");
+                continue
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        let committed = notification.committed();
+        let mined = committed
+            .blocks()
+            .iter()
+            .flat_map(|block| block.body.iter().map(|tx| tx.hash))
+            .collect::<Vec<_>>();
+        let reinjected = reverted_transactions(&notification, &mined)
+            .into_iter()
+            .filter_map(TransactionSigned::into_ecrecovered)
+            .collect::<Vec<_>>();
+
+        let mut changed_accounts = committed
+            .changed_accounts()
+            .into_iter()
+            .map(|(address, account)| {
+                let account = account.unwrap_or_default();
+                ChangedAccount { address, nonce: account.nonce, balance: account.balance }
+            })
+            .collect::<Vec<_>>();
+
+        // The accounts changed only by the reverted blocks, and the senders of the re-injected
+        // transactions, are read from the state of the new tip
+        let known = changed_accounts.iter().map(|account| account.address).collect::<HashSet<_>>();
+        let mut unknown = reinjected.iter().map(|tx| tx.signer()).collect::<HashSet<_>>();
+        if let Some(reverted) = notification.reverted() {
+            unknown.extend(reverted.changed_accounts().into_keys());
+        }
+        unknown.retain(|address| !known.contains(address));
+        if !unknown.is_empty() {
+            match client.latest() {
+                Ok(state) => changed_accounts.extend(unknown.into_iter().filter_map(|address| {
+                    let account = state.basic_account(address).ok()?.unwrap_or_default();
+                    Some(ChangedAccount { address, nonce: account.nonce, balance: account.balance })
+                })),
+                Err(err) => warn!(target: "txpool", ?err, "Failed to read latest state"),
+            }
+        }
+
+        let tip = notification.tip();
+        let pending_block_base_fee = tip.header.base_fee_per_gas.map_or(0, |base_fee| {
+            calculate_next_block_base_fee(tip.header.gas_used, tip.header.gas_limit, base_fee)
+        });
+
+        trace!(
+            target: "txpool",
+            hash = ?tip.hash(),
+            number = tip.number,
+            reinjected = reinjected.len(),
+            "Updating pool for new canonical tip"
+        );
+        pool.on_new_block(OnNewBlockEvent {
+            hash: tip.hash(),
+            pending_block_base_fee: pending_block_base_fee as u128,
+            state_changes: StateDiff { changed_accounts },
+            mined_transactions: mined,
+        });
+
+        if !reinjected.is_empty() {
+            let transactions = reinjected
+                .into_iter()
+                .map(<P::Transaction as FromRecoveredTransaction>::from_recovered_transaction)
+                .collect();
+            if let Err(err) = pool.add_transactions(TransactionOrigin::External, transactions).await
+            {
+                debug!(target: "txpool", ?err, "Failed to re-inject reverted transactions");
+            }
+        }
+    }
+}
+
+/// Returns the transactions of the reverted blocks of the notification that are not `mined` by
+/// the committed blocks and can be re-injected into the pool, in the order they were committed.
+fn reverted_transactions(
+    notification: &CanonStateNotification,
+    mined: &[H256],
+) -> Vec<TransactionSigned> {
+    let Some(reverted) = notification.reverted() else { return Vec::new() };
+    let mined = mined.iter().collect::<HashSet<_>>();
+    reverted
+        .blocks()
+        .iter()
+        .flat_map(|block| block.body.iter())
+        .filter(|tx| {
+            !mined.contains(&tx.hash) && !matches!(tx.transaction, Transaction::Eip4844(_))
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_executor::{blockchain_tree::Chain, execution_result::ExecutionResult};
+    use reth_primitives::{Header, SealedBlock, Signature, TxLegacy};
+    use std::sync::Arc;
+
+    fn transaction(nonce: u64) -> TransactionSigned {
+        TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy { nonce, ..Default::default() }),
+            Signature::default(),
+        )
+    }
+
+    /// A chain on top of the first block with a block for each of the transactions.
+    fn chain(transactions: &[TransactionSigned]) -> Arc<Chain> {
+        let block = |number: u64, tx: &TransactionSigned| SealedBlock {
+            header: Header { number, ..Default::default() }.seal_slow(),
+            body: vec![tx.clone()],
+            ..Default::default()
+        };
+        let result =
+            || ExecutionResult { tx_changesets: vec![], block_changesets: Default::default() };
+        let mut chain = Chain::new((1, H256::zero()), block(2, &transactions[0]), result());
+        for (number, tx) in (3..).zip(&transactions[1..]) {
+            chain.push(block(number, tx), result());
+        }
+        Arc::new(chain)
+    }
+
+    #[test]
+    fn reverted_transactions_on_reorg() {
+        let (tx_2, tx_3, tx_2b) = (transaction(1), transaction(2), transaction(3));
+
+        let commit = CanonStateNotification::Commit { new: chain(&[tx_2.clone(), tx_3.clone()]) };
+        assert!(reverted_transactions(&commit, &[]).is_empty());
+
+        // The second block of the new chain includes a reverted transaction again
+        let new = chain(&[tx_2b.clone(), tx_3.clone()]);
+        let reorg =
+            CanonStateNotification::Reorg { old: chain(&[tx_2.clone(), tx_3.clone()]), new };
+        let reverted = reverted_transactions(&reorg, &[tx_2b.hash, tx_3.hash]);
+        assert_eq!(reverted.into_iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![tx_2.hash]);
+    }
+}
@@ -1,6 +1,12 @@
 use crate::traits::PropagateKind;
+use futures_util::Stream;
 use reth_primitives::{TxHash, H256};
-use std::sync::Arc;
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc::UnboundedReceiver;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -26,3 +32,81 @@ pub enum TransactionEvent {
     /// Transaction was propagated to peers.
     Propagated(Arc<Vec<PropagateKind>>),
 }
+
+/// An event of any transaction in the pool.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FullTransactionEvent {
+    /// Transaction has been added to the pending pool.
+    Pending(TxHash),
+    /// Transaction has been added to the queued pool.
+    Queued(TxHash),
+    /// Transaction has been included in a block.
+    Mined {
+        /// The hash of the mined transaction.
+        tx_hash: TxHash,
+        /// The hash of the block the transaction was included in.
+        block_hash: H256,
+    },
+    /// Transaction has been replaced by a transaction with the same sender and nonce.
+    Replaced {
+        /// The hash of the replaced transaction.
+        tx_hash: TxHash,
+        /// The hash of the transaction that replaced it.
+        replaced_by: TxHash,
+    },
+    /// Transaction was dropped due to configured limits.
+    Discarded(TxHash),
+    /// Transaction became invalid indefinitely.
+    Invalid(TxHash),
+    /// Transaction was propagated to peers.
+    Propagated {
+        /// The hash of the propagated transaction.
+        tx_hash: TxHash,
+        /// The peers the transaction was propagated to.
+        peers: Arc<Vec<PropagateKind>>,
+    },
+}
+
+/// A [`Stream`] that yields the events of a single transaction.
+///
+/// The stream ends once the transaction is mined, replaced or removed from the pool.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct TransactionEvents {
+    /// The hash of the transaction.
+    pub(crate) hash: TxHash,
+    /// The receiver half of the transaction's event channel.
+    pub(crate) events: UnboundedReceiver<TransactionEvent>,
+}
+
+impl TransactionEvents {
+    /// The hash of the transaction the events belong to.
+    pub fn hash(&self) -> TxHash {
+        self.hash
+    }
+}
+
+impl Stream for TransactionEvents {
+    type Item = TransactionEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().events.poll_recv(cx)
+    }
+}
+
+/// A [`Stream`] that yields the events of all transactions in the pool.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct AllTransactionsEvents {
+    /// The receiver half of the event channel.
+    pub(crate) events: UnboundedReceiver<FullTransactionEvent>,
+}
+
+impl Stream for AllTransactionsEvents {
+    type Item = FullTransactionEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().events.poll_recv(cx)
+    }
+}
@@ -1,9 +1,14 @@
 //! Listeners for the transaction-pool
 
-use crate::{pool::events::TransactionEvent, traits::PropagateKind};
+use crate::{
+    pool::events::{
+        AllTransactionsEvents, FullTransactionEvent, TransactionEvent, TransactionEvents,
+    },
+    traits::PropagateKind,
+};
 use reth_primitives::{TxHash, H256};
 use std::{collections::HashMap, sync::Arc};
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{self, UnboundedSender};
 
 type EventBroadcast = UnboundedSender<TransactionEvent>;
 
@@ -13,11 +18,31 @@ type EventBroadcast = UnboundedSender<TransactionEvent>;
 /// all active receivers.
 #[derive(Debug, Default)]
 pub(crate) struct PoolEventBroadcast {
+    /// All listeners for all transaction events.
+    all_events_broadcaster: AllPoolEventsBroadcaster,
     /// All listeners for certain transaction events.
     broadcasters: HashMap<TxHash, PoolEventBroadcaster>,
 }
 
 impl PoolEventBroadcast {
+    /// Create a new subscription for the given transaction hash.
+    pub(crate) fn subscribe(&mut self, tx_hash: TxHash) -> TransactionEvents {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.broadcasters
+            .entry(tx_hash)
+            .or_insert_with(|| PoolEventBroadcaster { is_done: false, senders: Vec::new() })
+            .senders
+            .push(tx);
+        TransactionEvents { hash: tx_hash, events: rx }
+    }
+
+    /// Create a new subscription for the events of all transactions.
+    pub(crate) fn subscribe_all(&mut self) -> AllTransactionsEvents {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.all_events_broadcaster.senders.push(tx);
+        AllTransactionsEvents { events: rx }
+    }
+
     /// Calls the broadcast callback with the `PoolEventBroadcaster` that belongs to the hash.
     fn broadcast_with<F>(&mut self, hash: &TxHash, callback: F)
     where
@@ -38,31 +63,68 @@ impl PoolEventBroadcast {
     /// Notify listeners about a transaction that was added to the pending queue.
     pub(crate) fn pending(&mut self, tx: &TxHash, replaced: Option<&TxHash>) {
         self.broadcast_with(tx, |notifier| notifier.pending());
+        self.all_events_broadcaster.broadcast(FullTransactionEvent::Pending(*tx));
 
         if let Some(replaced) = replaced {
-            // notify listeners that this transaction was replaced
-            self.broadcast_with(replaced, |notifier| notifier.replaced(*tx));
+            self.replaced(replaced, tx);
         }
     }
 
     /// Notify listeners about a transaction that was added to the queued pool.
-    pub(crate) fn queued(&mut self, tx: &TxHash) {
+    pub(crate) fn queued(&mut self, tx: &TxHash, replaced: Option<&TxHash>) {
         self.broadcast_with(tx, |notifier| notifier.queued());
+        self.all_events_broadcaster.broadcast(FullTransactionEvent::Queued(*tx));
+
+        if let Some(replaced) = replaced {
+            self.replaced(replaced, tx);
+        }
+    }
+
+    /// Notify listeners that the transaction was replaced by the given transaction.
+    fn replaced(&mut self, tx: &TxHash, replaced_by: &TxHash) {
+        self.broadcast_with(tx, |notifier| notifier.replaced(*replaced_by));
+        self.all_events_broadcaster
+            .broadcast(FullTransactionEvent::Replaced { tx_hash: *tx, replaced_by: *replaced_by });
     }
 
     /// Notify listeners about a transaction that was propagated.
     pub(crate) fn propagated(&mut self, tx: &TxHash, peers: Vec<PropagateKind>) {
-        self.broadcast_with(tx, |notifier| notifier.propagated(peers));
+        let peers = Arc::new(peers);
+        self.broadcast_with(tx, |notifier| notifier.propagated(peers.clone()));
+        self.all_events_broadcaster
+            .broadcast(FullTransactionEvent::Propagated { tx_hash: *tx, peers });
     }
 
     /// Notify listeners about a transaction that was discarded.
     pub(crate) fn discarded(&mut self, tx: &TxHash) {
         self.broadcast_with(tx, |notifier| notifier.discarded());
+        self.all_events_broadcaster.broadcast(FullTransactionEvent::Discarded(*tx));
+    }
+
+    /// Notify listeners about a transaction that became invalid.
+    pub(crate) fn invalid(&mut self, tx: &TxHash) {
+        self.broadcast_with(tx, |notifier| notifier.invalid());
+        self.all_events_broadcaster.broadcast(FullTransactionEvent::Invalid(*tx));
     }
 
     /// Notify listeners that the transaction was mined
     pub(crate) fn mined(&mut self, tx: &TxHash, block_hash: H256) {
         self.broadcast_with(tx, |notifier| notifier.mined(block_hash));
+        self.all_events_broadcaster
+            .broadcast(FullTransactionEvent::Mined { tx_hash: *tx, block_hash });
+    }
+}
+
+/// All Sender half(s) of the event channels for all transactions.
+#[derive(Debug, Default)]
+struct AllPoolEventsBroadcaster {
+    /// Corresponding sender half(s) for event listener channel
+    senders: Vec<UnboundedSender<FullTransactionEvent>>,
+}
+
+impl AllPoolEventsBroadcaster {
+    fn broadcast(&mut self, event: FullTransactionEvent) {
+        self.senders.retain(|sender| sender.send(event.clone()).is_ok())
     }
 }
 
@@ -110,13 +172,52 @@ impl PoolEventBroadcaster {
     }
 
     /// Transaction was propagated.
-    fn propagated(&mut self, peers: Vec<PropagateKind>) {
-        self.broadcast(TransactionEvent::Propagated(Arc::new(peers)));
+    fn propagated(&mut self, peers: Arc<Vec<PropagateKind>>) {
+        self.broadcast(TransactionEvent::Propagated(peers));
     }
 
-    /// Transaction was replaced with the given transaction
+    /// Transaction was discarded.
     fn discarded(&mut self) {
         self.broadcast(TransactionEvent::Discarded);
         self.is_done = true;
     }
+
+    /// Transaction became invalid.
+    fn invalid(&mut self) {
+        self.broadcast(TransactionEvent::Invalid);
+        self.is_done = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broadcasts_transaction_events() {
+        let mut broadcast = PoolEventBroadcast::default();
+        let tx = TxHash::random();
+        let replacement = TxHash::random();
+        let mut events = broadcast.subscribe(tx);
+        let mut all_events = broadcast.subscribe_all();
+
+        broadcast.queued(&tx, None);
+        broadcast.pending(&replacement, Some(&tx));
+
+        assert_eq!(events.events.try_recv().unwrap(), TransactionEvent::Queued);
+        assert_eq!(events.events.try_recv().unwrap(), TransactionEvent::Replaced(replacement));
+        // the replaced transaction has no further events
+        assert!(events.events.try_recv().is_err());
+        assert!(broadcast.broadcasters.is_empty());
+
+        assert_eq!(all_events.events.try_recv().unwrap(), FullTransactionEvent::Queued(tx));
+        assert_eq!(
+            all_events.events.try_recv().unwrap(),
+            FullTransactionEvent::Pending(replacement)
+        );
+        assert_eq!(
+            all_events.events.try_recv().unwrap(),
+            FullTransactionEvent::Replaced { tx_hash: tx, replaced_by: replacement }
+        );
+    }
 }
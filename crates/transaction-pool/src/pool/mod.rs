@@ -68,22 +68,35 @@
 #![allow(dead_code)] // TODO(mattsse): remove once remaining checks implemented
 
 use crate::{
+    blobstore::{BlobStore, BlobStoreError},
     error::{PoolError, PoolResult},
     identifier::{SenderId, SenderIdentifiers, TransactionId},
-    pool::{listener::PoolEventBroadcast, state::SubPool, txpool::TxPool},
+    pool::{
+        listener::PoolEventBroadcast,
+        state::SubPool,
+        txpool::{SenderInfo, TxPool},
+    },
     traits::{
-        NewTransactionEvent, PoolSize, PoolTransaction, PropagatedTransactions, TransactionOrigin,
+        ChangedAccount, NewTransactionEvent, PoolSize, PoolTransaction, PropagatedTransactions,
+        TransactionOrigin,
     },
     validate::{TransactionValidationOutcome, ValidPoolTransaction},
     OnNewBlockEvent, PoolConfig, TransactionOrdering, TransactionValidator,
 };
 use best::BestTransactions;
-pub use events::TransactionEvent;
+pub use events::{
+    AllTransactionsEvents, FullTransactionEvent, TransactionEvent, TransactionEvents,
+};
 use parking_lot::{Mutex, RwLock};
-use reth_primitives::{Address, TxHash, H256};
-use std::{collections::HashSet, fmt, sync::Arc, time::Instant};
+use reth_primitives::{Address, BlobTransactionSidecar, TxHash, H256};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+    time::Instant,
+};
 use tokio::sync::mpsc;
-use tracing::warn;
+use tracing::{debug, warn};
 
 mod best;
 mod events;
@@ -97,13 +110,15 @@ pub mod txpool;
 mod update;
 
 /// Transaction pool internals.
-pub struct PoolInner<V: TransactionValidator, T: TransactionOrdering> {
+pub struct PoolInner<V: TransactionValidator, T: TransactionOrdering, S> {
     /// Internal mapping of addresses to plain ints.
     identifiers: RwLock<SenderIdentifiers>,
     /// Transaction validation.
     validator: Arc<V>,
     /// The internal pool that manages all transactions.
     pool: RwLock<TxPool<T>>,
+    /// Stores the sidecars of the blob transactions in the pool.
+    blob_store: S,
     /// Pool settings.
     config: PoolConfig,
     /// Manages listeners for transaction state change events.
@@ -116,18 +131,24 @@ pub struct PoolInner<V: TransactionValidator, T: TransactionOrdering> {
 
 // === impl PoolInner ===
 
-impl<V: TransactionValidator, T: TransactionOrdering> PoolInner<V, T>
+impl<V: TransactionValidator, T: TransactionOrdering, S: BlobStore> PoolInner<V, T, S>
 where
     V: TransactionValidator,
     T: TransactionOrdering<Transaction = <V as TransactionValidator>::Transaction>,
 {
     /// Create a new transaction pool instance.
-    pub(crate) fn new(validator: Arc<V>, ordering: Arc<T>, config: PoolConfig) -> Self {
+    pub(crate) fn new(
+        validator: Arc<V>,
+        ordering: Arc<T>,
+        blob_store: S,
+        config: PoolConfig,
+    ) -> Self {
         Self {
             identifiers: Default::default(),
             validator,
             event_listener: Default::default(),
             pool: RwLock::new(TxPool::new(ordering, config.clone())),
+            blob_store,
             pending_transaction_listener: Default::default(),
             transaction_listener: Default::default(),
             config,
@@ -144,6 +165,20 @@ where
         self.identifiers.write().sender_id_or_create(addr)
     }
 
+    /// Converts the changed accounts to the new info of the senders that are tracked by the pool.
+    ///
+    /// Accounts without transactions in the pool are skipped.
+    fn changed_senders(&self, accounts: &[ChangedAccount]) -> HashMap<SenderId, SenderInfo> {
+        let identifiers = self.identifiers.read();
+        accounts
+            .iter()
+            .filter_map(|account| {
+                let sender_id = identifiers.sender_id(&account.address)?;
+                Some((sender_id, SenderInfo::new_incoming(account.nonce, account.balance)))
+            })
+            .collect()
+    }
+
     /// Get the config the pool was configured with.
     pub fn config(&self) -> &PoolConfig {
         &self.config
@@ -154,6 +189,29 @@ where
         &self.validator
     }
 
+    /// Get the blob store reference.
+    pub fn blob_store(&self) -> &S {
+        &self.blob_store
+    }
+
+    /// Returns the sidecar of the blob transaction, if it is in the pool.
+    pub(crate) fn get_blob(
+        &self,
+        tx_hash: TxHash,
+    ) -> Result<Option<BlobTransactionSidecar>, BlobStoreError> {
+        self.blob_store.get(tx_hash)
+    }
+
+    /// Deletes the sidecars of transactions that were removed from the pool.
+    fn delete_blobs(&self, txs: Vec<TxHash>) {
+        if txs.is_empty() {
+            return
+        }
+        if let Err(err) = self.blob_store.delete_all(txs) {
+            debug!(target: "txpool", ?err, "failed to delete blobs of removed transactions");
+        }
+    }
+
     /// Adds a new transaction listener to the pool that gets notified about every new _pending_
     /// transaction.
     pub fn add_pending_listener(&self) -> mpsc::Receiver<TxHash> {
@@ -171,15 +229,43 @@ where
         rx
     }
 
+    /// If the pool contains the transaction, this adds a new listener that gets notified about
+    /// events of the transaction.
+    pub fn add_transaction_event_listener(&self, tx_hash: TxHash) -> Option<TransactionEvents> {
+        let pool = self.pool.read();
+        if pool.contains(&tx_hash) {
+            Some(self.event_listener.write().subscribe(tx_hash))
+        } else {
+            None
+        }
+    }
+
+    /// Adds a listener that gets notified about the events of all transactions in the pool.
+    pub fn add_all_transactions_event_listener(&self) -> AllTransactionsEvents {
+        self.event_listener.write().subscribe_all()
+    }
+
     /// Returns hashes of _all_ transactions in the pool.
     pub(crate) fn pooled_transactions(&self) -> Vec<TxHash> {
         let pool = self.pool.read();
         pool.all().hashes_iter().collect()
     }
 
+    /// Returns all transactions that are ready to be included in a block.
+    pub(crate) fn pending_transactions(&self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.pool.read().pending_transactions()
+    }
+
+    /// Returns all transactions that are not ready to be included in a block yet.
+    pub(crate) fn queued_transactions(&self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.pool.read().queued_transactions()
+    }
+
     /// Updates the entire pool after a new block was executed.
     pub(crate) fn on_new_block(&self, block: OnNewBlockEvent) {
-        let outcome = self.pool.write().on_new_block(block);
+        let changed_senders = self.changed_senders(&block.state_changes.changed_accounts);
+        let outcome = self.pool.write().on_new_block(block, changed_senders);
+        self.delete_blobs(outcome.mined.iter().chain(&outcome.discarded).copied().collect());
         self.notify_on_new_block(outcome);
     }
 
@@ -193,9 +279,22 @@ where
         tx: TransactionValidationOutcome<T::Transaction>,
     ) -> PoolResult<TxHash> {
         match tx {
-            TransactionValidationOutcome::Valid { balance, state_nonce, transaction } => {
+            TransactionValidationOutcome::Valid { balance, state_nonce, mut transaction } => {
                 let sender_id = self.get_sender_id(transaction.sender());
                 let transaction_id = TransactionId::new(sender_id, transaction.nonce());
+                let hash = *transaction.hash();
+
+                // Only the versioned hashes of a blob transaction are kept in the pool, the
+                // sidecar is moved to the blob store. The sidecar of a transaction that is
+                // already in the pool is stored already.
+                let blob_sidecar =
+                    transaction.take_blob_sidecar().filter(|_| !self.pool.read().contains(&hash));
+                let stored_blob_sidecar = blob_sidecar.is_some();
+                if let Some(sidecar) = blob_sidecar {
+                    self.blob_store
+                        .insert(hash, sidecar)
+                        .map_err(|err| PoolError::Other(hash, Box::new(err)))?;
+                }
 
                 let tx = ValidPoolTransaction {
                     cost: transaction.cost(),
@@ -206,8 +305,18 @@ where
                     origin,
                 };
 
-                let added = self.pool.write().add_transaction(tx, balance, state_nonce)?;
-                let hash = *added.hash();
+                let added = match self.pool.write().add_transaction(tx, balance, state_nonce) {
+                    Ok(added) => added,
+                    Err(err) => {
+                        if stored_blob_sidecar {
+                            self.delete_blobs(vec![hash]);
+                        }
+                        return Err(err)
+                    }
+                };
+
+                // Delete the blobs of the transactions that were removed by the new one
+                self.delete_blobs(added.removed_hashes());
 
                 // Notify about new pending transactions
                 if let Some(pending_hash) = added.as_pending() {
@@ -224,9 +333,12 @@ where
             }
             TransactionValidationOutcome::Invalid(tx, err) => {
                 let mut listener = self.event_listener.write();
-                listener.discarded(tx.hash());
+                listener.invalid(tx.hash());
                 Err(err)
             }
+            TransactionValidationOutcome::Error(tx_hash, err) => {
+                Err(PoolError::Other(tx_hash, err))
+            }
         }
     }
 
@@ -246,6 +358,7 @@ where
         if discarded.is_empty() {
             return added
         }
+        self.delete_blobs(discarded.iter().copied().collect());
 
         // It may happen that a newly added transaction is immediately discarded, so we need to
         // adjust the result here
@@ -302,12 +415,13 @@ where
 
     /// Notifies transaction listeners about changes after a block was processed.
     fn notify_on_new_block(&self, outcome: OnNewBlockOutcome) {
-        let OnNewBlockOutcome { mined, promoted, discarded, block_hash } = outcome;
+        let OnNewBlockOutcome { mined, promoted, demoted, discarded, block_hash } = outcome;
 
         let mut listener = self.event_listener.write();
 
         mined.iter().for_each(|tx| listener.mined(tx, block_hash));
         promoted.iter().for_each(|tx| listener.pending(tx, None));
+        demoted.iter().for_each(|tx| listener.queued(tx, None));
         discarded.iter().for_each(|tx| listener.discarded(tx));
     }
 
@@ -317,14 +431,14 @@ where
 
         match tx {
             AddedTransaction::Pending(tx) => {
-                let AddedPendingTransaction { transaction, promoted, discarded, .. } = tx;
+                let AddedPendingTransaction { transaction, replaced, promoted, discarded, .. } = tx;
 
-                listener.pending(transaction.hash(), None);
+                listener.pending(transaction.hash(), replaced.as_ref().map(|tx| tx.hash()));
                 promoted.iter().for_each(|tx| listener.pending(tx, None));
                 discarded.iter().for_each(|tx| listener.discarded(tx));
             }
-            AddedTransaction::Parked { transaction, .. } => {
-                listener.queued(transaction.hash());
+            AddedTransaction::Parked { transaction, replaced, .. } => {
+                listener.queued(transaction.hash(), replaced.as_ref().map(|tx| tx.hash()));
             }
         }
     }
@@ -340,10 +454,11 @@ where
         hashes: impl IntoIterator<Item = TxHash>,
     ) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
         let removed = self.pool.write().remove_invalid(hashes);
+        self.delete_blobs(removed.iter().map(|tx| *tx.hash()).collect());
 
         let mut listener = self.event_listener.write();
 
-        removed.iter().for_each(|tx| listener.discarded(tx.hash()));
+        removed.iter().for_each(|tx| listener.invalid(tx.hash()));
 
         removed
     }
@@ -395,9 +510,14 @@ where
     }
 }
 
-impl<V: TransactionValidator, T: TransactionOrdering> fmt::Debug for PoolInner<V, T> {
+impl<V: TransactionValidator, T: TransactionOrdering, S: BlobStore> fmt::Debug
+    for PoolInner<V, T, S>
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("PoolInner").field("config", &self.config).finish_non_exhaustive()
+        f.debug_struct("PoolInner")
+            .field("config", &self.config)
+            .field("blob_store", &self.blob_store)
+            .finish_non_exhaustive()
     }
 }
 
@@ -406,6 +526,8 @@ impl<V: TransactionValidator, T: TransactionOrdering> fmt::Debug for PoolInner<V
 pub struct AddedPendingTransaction<T: PoolTransaction> {
     /// Inserted transaction.
     transaction: Arc<ValidPoolTransaction<T>>,
+    /// The transaction with the same sender and nonce that was replaced by the inserted one.
+    replaced: Option<Arc<ValidPoolTransaction<T>>>,
     /// transactions promoted to the ready queue
     promoted: Vec<TxHash>,
     /// transaction that failed and became discarded
@@ -419,6 +541,7 @@ impl<T: PoolTransaction> AddedPendingTransaction<T> {
     fn new(transaction: Arc<ValidPoolTransaction<T>>) -> Self {
         Self {
             transaction,
+            replaced: None,
             promoted: Default::default(),
             discarded: Default::default(),
             removed: Default::default(),
@@ -436,6 +559,8 @@ pub enum AddedTransaction<T: PoolTransaction> {
     Parked {
         /// Inserted transaction.
         transaction: Arc<ValidPoolTransaction<T>>,
+        /// The transaction with the same sender and nonce that was replaced by the inserted one.
+        replaced: Option<Arc<ValidPoolTransaction<T>>>,
         /// The subpool it was moved to.
         subpool: SubPool,
     },
@@ -451,6 +576,22 @@ impl<T: PoolTransaction> AddedTransaction<T> {
         }
     }
 
+    /// Returns the hashes of the transactions that were replaced or discarded by adding the
+    /// transaction.
+    pub(crate) fn removed_hashes(&self) -> Vec<TxHash> {
+        match self {
+            AddedTransaction::Pending(tx) => tx
+                .replaced
+                .iter()
+                .map(|tx| *tx.hash())
+                .chain(tx.discarded.iter().copied())
+                .collect(),
+            AddedTransaction::Parked { replaced, .. } => {
+                replaced.iter().map(|tx| *tx.hash()).collect()
+            }
+        }
+    }
+
     /// Returns the hash of the transaction
     pub(crate) fn hash(&self) -> &TxHash {
         match self {
@@ -465,7 +606,7 @@ impl<T: PoolTransaction> AddedTransaction<T> {
             AddedTransaction::Pending(tx) => {
                 NewTransactionEvent { subpool: SubPool::Pending, transaction: tx.transaction }
             }
-            AddedTransaction::Parked { transaction, subpool } => {
+            AddedTransaction::Parked { transaction, subpool, .. } => {
                 NewTransactionEvent { transaction, subpool }
             }
         }
@@ -481,6 +622,8 @@ pub(crate) struct OnNewBlockOutcome {
     pub(crate) mined: Vec<TxHash>,
     /// Transactions promoted to the ready queue.
     pub(crate) promoted: Vec<TxHash>,
+    /// Transactions demoted from the ready queue to a parked pool.
+    pub(crate) demoted: Vec<TxHash>,
     /// transaction that were discarded during the update
     pub(crate) discarded: Vec<TxHash>,
 }
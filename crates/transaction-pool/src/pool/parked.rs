@@ -62,9 +62,25 @@ impl<T: ParkedOrd> ParkedPool<T> {
         Some(tx.transaction.into())
     }
 
+    /// Returns an iterator over all transactions in the pool.
+    pub(crate) fn all(
+        &self,
+    ) -> impl Iterator<Item = Arc<ValidPoolTransaction<T::Transaction>>> + '_ {
+        self.by_id.values().map(|tx| tx.transaction.clone().into())
+    }
+
     /// Removes the worst transaction from this pool.
-    pub(crate) fn pop_worst(&mut self) -> Option<Arc<ValidPoolTransaction<T::Transaction>>> {
-        let worst = self.best.iter().next().map(|tx| *tx.transaction.id())?;
+    ///
+    /// If `exempt_locals` is set, the worst transaction that is not local is removed.
+    pub(crate) fn pop_worst(
+        &mut self,
+        exempt_locals: bool,
+    ) -> Option<Arc<ValidPoolTransaction<T::Transaction>>> {
+        let worst = self
+            .best
+            .iter()
+            .find(|tx| !(exempt_locals && tx.transaction.is_local()))
+            .map(|tx| *tx.transaction.id())?;
         self.remove_transaction(&worst)
     }
 
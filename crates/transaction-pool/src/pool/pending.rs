@@ -83,6 +83,13 @@ impl<T: TransactionOrdering> PendingPool<T> {
         }
     }
 
+    /// Returns an iterator over all transactions in the pool.
+    pub(crate) fn all(
+        &self,
+    ) -> impl Iterator<Item = Arc<ValidPoolTransaction<T::Transaction>>> + '_ {
+        self.by_id.values().map(|tx| tx.transaction.transaction.clone())
+    }
+
     /// Returns the ancestor the given transaction, the transaction with `nonce - 1`.
     ///
     /// Note: for a transaction with nonce higher than the current on chain nonce this will always
@@ -162,8 +169,17 @@ impl<T: TransactionOrdering> PendingPool<T> {
     }
 
     /// Removes the worst transaction from this pool.
-    pub(crate) fn pop_worst(&mut self) -> Option<Arc<ValidPoolTransaction<T::Transaction>>> {
-        let worst = self.all.iter().next_back().map(|tx| *tx.transaction.id())?;
+    ///
+    /// If `exempt_locals` is set, the worst transaction that is not local is removed.
+    pub(crate) fn pop_worst(
+        &mut self,
+        exempt_locals: bool,
+    ) -> Option<Arc<ValidPoolTransaction<T::Transaction>>> {
+        let worst = self
+            .all
+            .iter()
+            .find(|tx| !(exempt_locals && tx.transaction.is_local()))
+            .map(|tx| *tx.transaction.id())?;
         self.remove_transaction(&worst)
     }
 
@@ -1,6 +1,6 @@
 //! The internal transaction pool implementation.
 use crate::{
-    config::MAX_ACCOUNT_SLOTS_PER_SENDER,
+    config::{DEFAULT_PRICE_BUMP, MAX_ACCOUNT_SLOTS_PER_SENDER},
     error::PoolError,
     identifier::{SenderId, TransactionId},
     metrics::TxPoolMetrics,
@@ -12,7 +12,7 @@ use crate::{
         update::{Destination, PoolUpdate},
         AddedPendingTransaction, AddedTransaction, OnNewBlockOutcome,
     },
-    traits::{PoolSize, StateDiff},
+    traits::PoolSize,
     OnNewBlockEvent, PoolConfig, PoolResult, PoolTransaction, TransactionOrdering,
     ValidPoolTransaction, U256,
 };
@@ -100,7 +100,7 @@ impl<T: TransactionOrdering> TxPool<T> {
             pending_pool: PendingPool::new(ordering),
             queued_pool: Default::default(),
             basefee_pool: Default::default(),
-            all_transactions: AllTransactions::new(config.max_account_slots),
+            all_transactions: AllTransactions::new(&config),
             config,
             metrics: Default::default(),
         }
@@ -125,10 +125,12 @@ impl<T: TransactionOrdering> TxPool<T> {
 
     /// Updates the pool based on the changed base fee.
     ///
-    /// This enforces the dynamic fee requirement.
-    pub(crate) fn update_base_fee(&mut self, _new_base_fee: U256) {
-        // TODO update according to the changed base_fee
-        todo!()
+    /// This enforces the dynamic fee requirement: transactions that no longer satisfy the base
+    /// fee are demoted from the pending to the basefee sub-pool, and transactions that satisfy the
+    /// decreased base fee are promoted to the pending sub-pool.
+    pub(crate) fn update_base_fee(&mut self, new_base_fee: u128) -> UpdateOutcome<T::Transaction> {
+        let updates = self.all_transactions.update(new_base_fee, &Default::default());
+        self.process_updates(updates)
     }
 
     /// Returns an iterator that yields transactions that are ready to be included in the block.
@@ -136,6 +138,16 @@ impl<T: TransactionOrdering> TxPool<T> {
         self.pending_pool.best()
     }
 
+    /// Returns all transactions of the pending sub-pool.
+    pub(crate) fn pending_transactions(&self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.pending_pool.all().collect()
+    }
+
+    /// Returns all transactions that are parked in the basefee or queued sub-pool.
+    pub(crate) fn queued_transactions(&self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.basefee_pool.all().chain(self.queued_pool.all()).collect()
+    }
+
     /// Returns if the transaction for the given hash is already included in this pool
     pub(crate) fn contains(&self, tx_hash: &TxHash) -> bool {
         self.all_transactions.contains(tx_hash)
@@ -160,8 +172,12 @@ impl<T: TransactionOrdering> TxPool<T> {
     /// Updates the entire pool after a new block was mined.
     ///
     /// This removes all mined transactions, updates according to the new base fee and rechecks
-    /// sender allowance.
-    pub(crate) fn on_new_block(&mut self, event: OnNewBlockEvent) -> OnNewBlockOutcome {
+    /// sender allowance with the new nonces and balances of the `changed_senders`.
+    pub(crate) fn on_new_block(
+        &mut self,
+        event: OnNewBlockEvent,
+        changed_senders: HashMap<SenderId, SenderInfo>,
+    ) -> OnNewBlockOutcome {
         // Remove all transaction that were included in the block
         for tx_hash in &event.mined_transactions {
             self.remove_transaction_by_hash(tx_hash);
@@ -169,17 +185,30 @@ impl<T: TransactionOrdering> TxPool<T> {
             self.metrics.removed_transactions.increment(1);
         }
 
+        // Remove the transactions whose nonce is now used, e.g. by a mined transaction of the same
+        // sender with a different hash.
+        let mut stale = Vec::new();
+        for (sender, info) in &changed_senders {
+            for id in self.all_transactions.stale_txs(*sender, info.state_nonce) {
+                if let Some(tx) = self.remove_transaction(&id) {
+                    stale.push(*tx.hash());
+                    self.metrics.removed_transactions.increment(1);
+                }
+            }
+        }
+
         // Apply the state changes to the total set of transactions which triggers sub-pool updates.
-        let updates =
-            self.all_transactions.update(event.pending_block_base_fee, &event.state_changes);
+        let updates = self.all_transactions.update(event.pending_block_base_fee, &changed_senders);
 
         // Process the sub-pool updates
-        let UpdateOutcome { promoted, discarded, .. } = self.process_updates(updates);
+        let UpdateOutcome { promoted, demoted, mut discarded, .. } = self.process_updates(updates);
+        discarded.extend(stale);
 
         OnNewBlockOutcome {
             block_hash: event.hash,
             mined: event.mined_transactions,
             promoted,
+            demoted,
             discarded,
         }
     }
@@ -220,21 +249,24 @@ impl<T: TransactionOrdering> TxPool<T> {
 
         match self.all_transactions.insert_tx(tx, on_chain_balance, on_chain_nonce) {
             Ok(InsertOk { transaction, move_to, replaced_tx, updates, .. }) => {
+                let replaced = replaced_tx.as_ref().map(|(tx, _)| tx.clone());
                 self.add_new_transaction(transaction.clone(), replaced_tx, move_to);
                 // Update inserted transactions metric
                 self.metrics.inserted_transactions.increment(1);
-                let UpdateOutcome { promoted, discarded, removed } = self.process_updates(updates);
+                let UpdateOutcome { promoted, discarded, removed, .. } =
+                    self.process_updates(updates);
 
                 // This transaction was moved to the pending pool.
                 let res = if move_to.is_pending() {
                     AddedTransaction::Pending(AddedPendingTransaction {
                         transaction,
+                        replaced,
                         promoted,
                         discarded,
                         removed,
                     })
                 } else {
-                    AddedTransaction::Parked { transaction, subpool: move_to }
+                    AddedTransaction::Parked { transaction, replaced, subpool: move_to }
                 };
 
                 Ok(res)
@@ -285,6 +317,11 @@ impl<T: TransactionOrdering> TxPool<T> {
                 }
                 Destination::Pool(move_to) => {
                     debug_assert!(!move_to.eq(&current), "destination must be different");
+                    if move_to.is_pending() {
+                        outcome.promoted.push(hash);
+                    } else if current.is_pending() {
+                        outcome.demoted.push(hash);
+                    }
                     self.move_transaction(current, move_to, &id);
                 }
             }
@@ -410,8 +447,12 @@ impl<T: TransactionOrdering> TxPool<T> {
     ///
     /// If the current size exceeds the given bounds, the worst transactions are evicted from the
     /// pool and returned.
+    ///
+    /// Local transactions are not evicted, unless they're configured to be treated like external
+    /// transactions, so a sub-pool can exceed its bounds if it's made up of local transactions.
     pub(crate) fn discard_worst(&mut self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
         let mut removed = Vec::new();
+        let exempt_locals = !self.config.no_local_exemptions;
 
         // Helper macro that discards the worst transactions for the pools
         macro_rules! discard_worst {
@@ -422,11 +463,10 @@ impl<T: TransactionOrdering> TxPool<T> {
                         .$limit
                         .is_exceeded($this.$pool.len(), $this.$pool.size())
                     {
-                        if let Some(tx) = $this.$pool.pop_worst() {
-                            let id = tx.transaction_id;
-                            removed.push(tx);
-                            $this.remove_descendants(&id, &mut $removed);
-                        }
+                        let Some(tx) = $this.$pool.pop_worst(exempt_locals) else { break };
+                        let id = tx.transaction_id;
+                        removed.push(tx);
+                        $this.remove_descendants(&id, &mut $removed);
                     }
 
                 )*
@@ -493,6 +533,10 @@ pub(crate) struct AllTransactions<T: PoolTransaction> {
     block_gas_limit: u64,
     /// Max number of executable transaction slots guaranteed per account
     max_account_slots: usize,
+    /// Min percentage by which a replacement transaction must increase the fees.
+    price_bump: u128,
+    /// Whether local transactions are exempt from the max number of slots per account.
+    exempt_locals: bool,
     /// _All_ transactions identified by their hash.
     by_hash: HashMap<TxHash, Arc<ValidPoolTransaction<T>>>,
     /// _All_ transaction in the pool sorted by their sender and nonce pair.
@@ -503,8 +547,13 @@ pub(crate) struct AllTransactions<T: PoolTransaction> {
 
 impl<T: PoolTransaction> AllTransactions<T> {
     /// Create a new instance
-    fn new(max_account_slots: usize) -> Self {
-        Self { max_account_slots, ..Default::default() }
+    fn new(config: &PoolConfig) -> Self {
+        Self {
+            max_account_slots: config.max_account_slots,
+            price_bump: config.price_bump,
+            exempt_locals: !config.no_local_exemptions,
+            ..Default::default()
+        }
     }
 
     /// Returns an iterator over all _unique_ hashes in the pool
@@ -554,12 +603,12 @@ impl<T: PoolTransaction> AllTransactions<T> {
     ///       - `pending` if basefee condition is met.
     ///       - `basefee` if basefee condition is _not_ met.
     ///
-    /// Additionally, this will also update the `cumulative_gas_used` for transactions of a sender
-    /// that got transaction included in the block.
+    /// Additionally, this will also update the `cumulative_cost` and the nonce gaps of the
+    /// transactions of the `changed_senders`, whose nonce or balance changed.
     pub(crate) fn update(
         &mut self,
         pending_block_base_fee: u128,
-        _state_diffs: &StateDiff,
+        changed_senders: &HashMap<SenderId, SenderInfo>,
     ) -> Vec<PoolUpdate> {
         // update new basefee
         self.pending_basefee = pending_block_base_fee;
@@ -567,6 +616,12 @@ impl<T: PoolTransaction> AllTransactions<T> {
         // TODO(mattsse): probably good idea to allocate some capacity here.
         let mut updates = Vec::new();
 
+        // Recheck the nonce gaps and the balance of the senders that changed first, the loop below
+        // then updates the sub-pools of their gapless transactions.
+        for (sender, info) in changed_senders {
+            self.update_sender(*sender, info, &mut updates);
+        }
+
         let mut iter = self.txs.iter_mut().peekable();
 
         // Loop over all individual senders and update all affected transactions.
@@ -596,9 +651,6 @@ impl<T: PoolTransaction> AllTransactions<T> {
                 continue
             }
 
-            // TODO(mattsse): if account has balance changes or mined transactions the balance needs
-            // to be checked here
-
             // Since this is the first transaction of the sender, it has no parked ancestors
             tx.state.insert(TxState::NO_PARKED_ANCESTORS);
 
@@ -642,6 +694,51 @@ impl<T: PoolTransaction> AllTransactions<T> {
         updates
     }
 
+    /// Rechecks the nonce gaps and the balance of all transactions of the sender against its new
+    /// nonce and balance.
+    ///
+    /// Sub-pool changes are only recorded for transactions that now have a nonce gap, since
+    /// [`Self::update`] skips them.
+    fn update_sender(
+        &mut self,
+        sender: SenderId,
+        info: &SenderInfo,
+        updates: &mut Vec<PoolUpdate>,
+    ) {
+        let mut next_nonce = info.state_nonce;
+        let mut cumulative_cost = U256::ZERO;
+        let mut has_nonce_gap = false;
+
+        let on_chain_id = TransactionId::new(sender, info.state_nonce);
+        for (id, tx) in self.descendant_txs_mut(&on_chain_id) {
+            has_nonce_gap |= next_nonce != id.nonce;
+            if has_nonce_gap {
+                tx.state.remove(TxState::NO_NONCE_GAPS | TxState::NO_PARKED_ANCESTORS);
+                Self::record_subpool_update(updates, tx);
+                continue
+            }
+
+            tx.state.insert(TxState::NO_NONCE_GAPS);
+            tx.cumulative_cost = cumulative_cost;
+            cumulative_cost = tx.next_cumulative_cost();
+            if cumulative_cost > info.balance {
+                tx.state.remove(TxState::ENOUGH_BALANCE);
+            } else {
+                tx.state.insert(TxState::ENOUGH_BALANCE);
+            }
+            next_nonce = id.next_nonce();
+        }
+    }
+
+    /// Returns the ids of the transactions of the sender with a nonce below the `state_nonce`,
+    /// which can no longer be included.
+    pub(crate) fn stale_txs(&self, sender: SenderId, state_nonce: u64) -> Vec<TransactionId> {
+        self.txs
+            .range(TransactionId::new(sender, 0)..TransactionId::new(sender, state_nonce))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
     /// This will update the transaction's `subpool` based on its state.
     ///
     /// If the sub-pool derived from the state differs from the current pool, it will record a
@@ -776,14 +873,14 @@ impl<T: PoolTransaction> AllTransactions<T> {
     /// Additional checks for a new transaction.
     ///
     /// This will enforce all additional rules in the context of this pool, such as:
-    ///   - Spam protection: reject new non-local transaction from a sender that exhausted its slot
-    ///     capacity.
+    ///   - Spam protection: reject new transaction from a sender that exhausted its slot capacity.
+    ///     Local transactions are exempt, unless they're treated like external transactions.
     ///   - Gas limit: reject transactions if they exceed a block's maximum gas.
     fn ensure_valid(
         &self,
         transaction: ValidPoolTransaction<T>,
     ) -> Result<ValidPoolTransaction<T>, InsertErr<T>> {
-        if !transaction.origin.is_local() {
+        if !(self.exempt_locals && transaction.origin.is_local()) {
             let current_txs =
                 self.tx_counter.get(&transaction.sender_id()).copied().unwrap_or_default();
             if current_txs >= self.max_account_slots {
@@ -872,7 +969,7 @@ impl<T: PoolTransaction> AllTransactions<T> {
             Entry::Occupied(mut entry) => {
                 // Transaction already exists
                 // Ensure the new transaction is not underpriced
-                if transaction.is_underpriced(entry.get().transaction.as_ref()) {
+                if transaction.is_underpriced(entry.get().transaction.as_ref(), self.price_bump) {
                     return Err(InsertErr::Underpriced {
                         transaction: pool_tx.transaction,
                         existing: *entry.get().transaction.hash(),
@@ -996,6 +1093,8 @@ impl<T: PoolTransaction> Default for AllTransactions<T> {
     fn default() -> Self {
         Self {
             max_account_slots: MAX_ACCOUNT_SLOTS_PER_SENDER,
+            price_bump: DEFAULT_PRICE_BUMP,
+            exempt_locals: true,
             pending_basefee: Default::default(),
             minimal_protocol_basefee: MIN_PROTOCOL_BASE_FEE,
             block_gas_limit: 30_000_000,
@@ -1074,16 +1173,18 @@ impl<T: PoolTransaction> PoolInternalTransaction<T> {
 #[derive(Debug)]
 pub struct UpdateOutcome<T: PoolTransaction> {
     /// transactions promoted to the ready queue
-    promoted: Vec<TxHash>,
+    pub(crate) promoted: Vec<TxHash>,
+    /// transactions demoted from the ready queue to a parked pool
+    pub(crate) demoted: Vec<TxHash>,
     /// transaction that failed and became discarded
-    discarded: Vec<TxHash>,
+    pub(crate) discarded: Vec<TxHash>,
     /// Transactions removed from the Ready pool
-    removed: Vec<Arc<ValidPoolTransaction<T>>>,
+    pub(crate) removed: Vec<Arc<ValidPoolTransaction<T>>>,
 }
 
 impl<T: PoolTransaction> Default for UpdateOutcome<T> {
     fn default() -> Self {
-        Self { promoted: vec![], discarded: vec![], removed: vec![] }
+        Self { promoted: vec![], demoted: vec![], discarded: vec![], removed: vec![] }
     }
 }
 
@@ -1118,18 +1219,18 @@ impl<T: PoolTransaction> fmt::Debug for PruneResult<T> {
 
 /// Stores relevant context about a sender.
 #[derive(Debug, Clone, Default)]
-struct SenderInfo {
+pub(crate) struct SenderInfo {
     /// current nonce of the sender.
-    state_nonce: u64,
+    pub(crate) state_nonce: u64,
     /// Balance of the sender at the current point.
-    balance: U256,
+    pub(crate) balance: U256,
 }
 
 // === impl SenderInfo ===
 
 impl SenderInfo {
     /// Creates a new entry for an incoming, not yet tracked sender.
-    fn new_incoming(state_nonce: u64, balance: U256) -> Self {
+    pub(crate) fn new_incoming(state_nonce: u64, balance: U256) -> Self {
         Self { state_nonce, balance }
     }
 
@@ -1143,7 +1244,8 @@ impl SenderInfo {
 mod tests {
     use super::*;
     use crate::{
-        test_utils::{MockTransaction, MockTransactionFactory},
+        config::SubPoolLimit,
+        test_utils::{mock_tx_pool, MockTransaction, MockTransactionFactory, MockTxPool},
         traits::TransactionOrigin,
     };
 
@@ -1211,6 +1313,36 @@ mod tests {
         assert_eq!(pool.len(), 1);
     }
 
+    #[test]
+    fn reject_replacement_without_price_bump() {
+        let on_chain_balance = U256::ZERO;
+        let on_chain_nonce = 0;
+        let mut f = MockTransactionFactory::default();
+        let mut pool = AllTransactions::default();
+        let tx = MockTransaction::eip1559().with_gas_price(100);
+        let first = f.validated(tx.clone());
+        pool.insert_tx(first.clone(), on_chain_balance, on_chain_nonce).unwrap();
+
+        // the fees must increase by at least the price bump
+        let replacement = f.validated(tx.clone().rng_hash().with_gas_price(109));
+        let err = pool.insert_tx(replacement, on_chain_balance, on_chain_nonce).unwrap_err();
+        assert!(
+            matches!(err, InsertErr::Underpriced { existing, .. } if existing == *first.hash())
+        );
+
+        // this also applies to the priority fee
+        let replacement = f.validated(tx.clone().rng_hash().with_max_fee(110));
+        let err = pool.insert_tx(replacement, on_chain_balance, on_chain_nonce).unwrap_err();
+        assert!(matches!(err, InsertErr::Underpriced { .. }));
+
+        let replacement = f.validated(tx.rng_hash().with_gas_price(110));
+        let InsertOk { replaced_tx, .. } =
+            pool.insert_tx(replacement.clone(), on_chain_balance, on_chain_nonce).unwrap();
+        assert_eq!(replaced_tx.unwrap().0.hash(), first.hash());
+        assert!(pool.contains(replacement.hash()));
+        assert_eq!(pool.len(), 1);
+    }
+
     // insert nonce then nonce - 1
     #[test]
     fn insert_previous() {
@@ -1376,4 +1508,110 @@ mod tests {
             Err(InsertErr::TxGasLimitMoreThanAvailableBlockGas { .. })
         ));
     }
+
+    #[test]
+    fn pending_and_queued_transactions() {
+        let on_chain_balance = U256::from(10_000);
+        let on_chain_nonce = 0;
+        let mut f = MockTransactionFactory::default();
+        let mut pool = mock_tx_pool();
+        let tx = MockTransaction::eip1559().set_gas_price(100).inc_limit();
+        let pending = f.validated(tx.clone());
+        // has nonce gap
+        let queued = f.validated(tx.skip(1));
+
+        pool.add_transaction(pending.clone(), on_chain_balance, on_chain_nonce).unwrap();
+        pool.add_transaction(queued.clone(), on_chain_balance, on_chain_nonce).unwrap();
+
+        let pending_hashes =
+            pool.pending_transactions().iter().map(|tx| *tx.hash()).collect::<Vec<_>>();
+        assert_eq!(pending_hashes, vec![*pending.hash()]);
+        let queued_hashes =
+            pool.queued_transactions().iter().map(|tx| *tx.hash()).collect::<Vec<_>>();
+        assert_eq!(queued_hashes, vec![*queued.hash()]);
+    }
+
+    #[test]
+    fn update_base_fee_moves_transactions() {
+        let on_chain_balance = U256::from(10_000);
+        let on_chain_nonce = 0;
+        let mut f = MockTransactionFactory::default();
+        let mut pool = mock_tx_pool();
+        let tx = f.validated(MockTransaction::eip1559().set_gas_price(100).inc_limit());
+        pool.add_transaction(tx.clone(), on_chain_balance, on_chain_nonce).unwrap();
+        assert_eq!(pool.pending().len(), 1);
+
+        // the fee cap no longer satisfies the base fee
+        let outcome = pool.update_base_fee(101);
+        assert_eq!(outcome.demoted, vec![*tx.hash()]);
+        assert_eq!(pool.pending().len(), 0);
+        assert_eq!(pool.base_fee().len(), 1);
+
+        let outcome = pool.update_base_fee(100);
+        assert_eq!(outcome.promoted, vec![*tx.hash()]);
+        assert_eq!(pool.pending().len(), 1);
+        assert_eq!(pool.base_fee().len(), 0);
+    }
+
+    #[test]
+    fn on_new_block_applies_sender_changes() {
+        let on_chain_balance = U256::from(10_000);
+        let on_chain_nonce = 0;
+        let mut f = MockTransactionFactory::default();
+        let mut pool = mock_tx_pool();
+        let tx = MockTransaction::eip1559().set_gas_price(100).inc_limit();
+        let first = f.validated(tx.clone());
+        let second = f.validated(tx.next());
+        pool.add_transaction(first.clone(), on_chain_balance, on_chain_nonce).unwrap();
+        pool.add_transaction(second.clone(), on_chain_balance, on_chain_nonce).unwrap();
+        assert_eq!(pool.pending().len(), 2);
+
+        // the nonce of the first transaction was used by another transaction of the sender, which
+        // also spent the balance
+        let event = OnNewBlockEvent {
+            hash: H256::random(),
+            pending_block_base_fee: 0,
+            state_changes: Default::default(),
+            mined_transactions: vec![],
+        };
+        let changed_senders =
+            HashMap::from([(first.sender_id(), SenderInfo::new_incoming(1, U256::ZERO))]);
+        let outcome = pool.on_new_block(event, changed_senders);
+
+        assert_eq!(outcome.discarded, vec![*first.hash()]);
+        assert_eq!(outcome.demoted, vec![*second.hash()]);
+        assert!(!pool.contains(first.hash()));
+        assert_eq!(pool.pending().len(), 0);
+        assert_eq!(pool.queued().len(), 1);
+    }
+
+    #[test]
+    fn discard_worst_exempts_local_transactions() {
+        let on_chain_balance = U256::from(100_000);
+        let on_chain_nonce = 0;
+        for no_local_exemptions in [false, true] {
+            let config = PoolConfig {
+                pending_limit: SubPoolLimit { max_txs: 1, max_size: usize::MAX },
+                no_local_exemptions,
+                ..Default::default()
+            };
+            let mut f = MockTransactionFactory::default();
+            let mut pool = MockTxPool::new(Arc::new(Default::default()), config);
+            let local = f.validated_with_origin(
+                TransactionOrigin::Local,
+                MockTransaction::eip1559().set_gas_price(100).inc_limit(),
+            );
+            let external = f.validated(MockTransaction::eip1559().set_gas_price(200).inc_limit());
+            pool.add_transaction(local.clone(), on_chain_balance, on_chain_nonce).unwrap();
+            pool.add_transaction(external.clone(), on_chain_balance, on_chain_nonce).unwrap();
+
+            let discarded = pool.discard_worst().iter().map(|tx| *tx.hash()).collect::<Vec<_>>();
+            if no_local_exemptions {
+                assert_eq!(discarded, vec![*local.hash()]);
+            } else {
+                assert_eq!(discarded, vec![*external.hash()]);
+            }
+            assert_eq!(pool.pending().len(), 1);
+        }
+    }
 }
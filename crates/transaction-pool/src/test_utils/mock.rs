@@ -12,8 +12,9 @@ use rand::{
     prelude::Distribution,
 };
 use reth_primitives::{
-    Address, FromRecoveredTransaction, IntoRecoveredTransaction, Transaction, TransactionKind,
-    TransactionSignedEcRecovered, TxEip1559, TxHash, TxLegacy, H256, U128, U256,
+    AccessList, Address, BlobTransactionSidecar, FromRecoveredTransaction,
+    IntoRecoveredTransaction, Transaction, TransactionKind, TransactionSignedEcRecovered,
+    TxEip1559, TxHash, TxLegacy, TxType, H256, U128, U256,
 };
 use std::{ops::Range, sync::Arc, time::Instant};
 
@@ -345,6 +346,48 @@ impl PoolTransaction for MockTransaction {
     fn size(&self) -> usize {
         0
     }
+
+    fn tx_type(&self) -> TxType {
+        match self {
+            MockTransaction::Legacy { .. } => TxType::Legacy,
+            MockTransaction::Eip1559 { .. } => TxType::EIP1559,
+        }
+    }
+
+    fn chain_id(&self) -> Option<u64> {
+        None
+    }
+
+    fn input(&self) -> &[u8] {
+        &[]
+    }
+
+    fn access_list(&self) -> Option<&AccessList> {
+        None
+    }
+
+    fn max_fee_per_blob_gas(&self) -> Option<u128> {
+        None
+    }
+
+    fn blob_versioned_hashes(&self) -> Option<&[H256]> {
+        None
+    }
+
+    fn blob_sidecar(&self) -> Option<&BlobTransactionSidecar> {
+        None
+    }
+
+    fn take_blob_sidecar(&mut self) -> Option<BlobTransactionSidecar> {
+        None
+    }
+
+    fn from_recovered_blob_transaction(
+        tx: TransactionSignedEcRecovered,
+        _sidecar: BlobTransactionSidecar,
+    ) -> Self {
+        Self::from_recovered_transaction(tx)
+    }
 }
 
 impl FromRecoveredTransaction for MockTransaction {
@@ -390,7 +433,7 @@ impl FromRecoveredTransaction for MockTransaction {
                 to,
                 value: U256::from(value),
             },
-            Transaction::Eip2930 { .. } => {
+            Transaction::Eip2930 { .. } | Transaction::Eip4844 { .. } => {
                 unimplemented!()
             }
         }
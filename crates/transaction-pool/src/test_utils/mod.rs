@@ -5,21 +5,29 @@ mod mock;
 mod pool;
 
 use crate::{
-    Pool, PoolTransaction, TransactionOrigin, TransactionValidationOutcome, TransactionValidator,
+    blobstore::InMemoryBlobStore, Pool, PoolConfig, PoolTransaction, TransactionOrigin,
+    TransactionValidationOutcome, TransactionValidator,
 };
 use async_trait::async_trait;
 pub use mock::*;
 use std::{marker::PhantomData, sync::Arc};
 
 /// A [Pool] used for testing
-pub type TestPool = Pool<NoopTransactionValidator<MockTransaction>, MockOrdering>;
+pub type TestPool =
+    Pool<NoopTransactionValidator<MockTransaction>, MockOrdering, InMemoryBlobStore>;
 
 /// Returns a new [Pool] used for testing purposes
 pub fn testing_pool() -> TestPool {
+    testing_pool_with_config(Default::default())
+}
+
+/// Returns a new [Pool] with the given config used for testing purposes
+pub fn testing_pool_with_config(config: PoolConfig) -> TestPool {
     Pool::new(
         Arc::new(NoopTransactionValidator::default()),
         Arc::new(MockOrdering::default()),
-        Default::default(),
+        InMemoryBlobStore::default(),
+        config,
     )
 }
 
@@ -1,7 +1,13 @@
-use crate::{error::PoolResult, pool::state::SubPool, validate::ValidPoolTransaction};
+use crate::{
+    blobstore::BlobStoreError,
+    error::PoolResult,
+    pool::{state::SubPool, AllTransactionsEvents, TransactionEvents},
+    validate::ValidPoolTransaction,
+};
 use reth_primitives::{
-    Address, FromRecoveredTransaction, IntoRecoveredTransaction, PeerId, Transaction,
-    TransactionKind, TransactionSignedEcRecovered, TxHash, H256, U256,
+    AccessList, Address, BlobTransactionSidecar, FromRecoveredTransaction,
+    IntoRecoveredTransaction, PeerId, Transaction, TransactionKind, TransactionSignedEcRecovered,
+    TxHash, TxType, H256, U256,
 };
 use std::{collections::HashMap, fmt, sync::Arc};
 use tokio::sync::mpsc::Receiver;
@@ -70,6 +76,17 @@ pub trait TransactionPool: Send + Sync + Clone {
     /// Returns a new stream that yields new valid transactions added to the pool.
     fn transactions_listener(&self) -> Receiver<NewTransactionEvent<Self::Transaction>>;
 
+    /// Returns a new Stream that yields the events of the transaction, or `None` if the
+    /// transaction is not in the pool.
+    ///
+    /// Consumer: RPC
+    fn transaction_event_listener(&self, tx_hash: TxHash) -> Option<TransactionEvents>;
+
+    /// Returns a new Stream that yields the events of all transactions in the pool.
+    ///
+    /// Consumer: P2P, RPC
+    fn all_transactions_event_listener(&self) -> AllTransactionsEvents;
+
     /// Returns hashes of all transactions in the pool.
     ///
     /// Note: This returns a `Vec` but should guarantee that all hashes are unique.
@@ -77,6 +94,17 @@ pub trait TransactionPool: Send + Sync + Clone {
     /// Consumer: P2P
     fn pooled_transactions(&self) -> Vec<TxHash>;
 
+    /// Returns all transactions that are ready to be included in a block.
+    ///
+    /// Consumer: RPC
+    fn pending_transactions(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>>;
+
+    /// Returns all transactions that are not ready to be included in a block, because of a nonce
+    /// gap, a lack of funds or a too low fee.
+    ///
+    /// Consumer: RPC
+    fn queued_transactions(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>>;
+
     /// Returns an iterator that yields transactions that are ready for block production.
     ///
     /// Consumer: Block production
@@ -123,6 +151,11 @@ pub trait TransactionPool: Send + Sync + Clone {
     ///
     /// Consumer: P2P
     fn on_propagated(&self, txs: PropagatedTransactions);
+
+    /// Returns the sidecar of the blob transaction with the given hash, if it is in the pool.
+    ///
+    /// Consumer: P2P
+    fn get_blob(&self, tx_hash: TxHash) -> Result<Option<BlobTransactionSidecar>, BlobStoreError>;
 }
 
 /// Represents a transaction that was propagated over the network.
@@ -217,9 +250,21 @@ pub struct OnNewBlockEvent {
 }
 
 /// Contains a list of changed state
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct StateDiff {
-    // TODO(mattsse) this could be an `Arc<revm::State>>`
+    /// The accounts whose nonce or balance changed, with their new nonce and balance.
+    pub changed_accounts: Vec<ChangedAccount>,
+}
+
+/// The nonce and balance of an account after a state change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangedAccount {
+    /// The address of the account.
+    pub address: Address,
+    /// The new nonce of the account.
+    pub nonce: u64,
+    /// The new balance of the account.
+    pub balance: U256,
 }
 
 /// An `Iterator` that only returns transactions that are ready to be executed.
@@ -280,17 +325,64 @@ pub trait PoolTransaction: fmt::Debug + Send + Sync + FromRecoveredTransaction {
 
     /// Returns a measurement of the heap usage of this type and all its internals.
     fn size(&self) -> usize;
+
+    /// Returns the type of the transaction.
+    fn tx_type(&self) -> TxType;
+
+    /// Returns the chain id of the transaction.
+    ///
+    /// This will return `None` for legacy transactions without replay protection (EIP-155).
+    fn chain_id(&self) -> Option<u64>;
+
+    /// Returns the input data of the transaction.
+    fn input(&self) -> &[u8];
+
+    /// Returns the EIP-2930 access list of the transaction.
+    ///
+    /// This will return `None` for legacy transactions.
+    fn access_list(&self) -> Option<&AccessList>;
+
+    /// Returns the max fee per blob gas of an EIP-4844 transaction.
+    ///
+    /// This will return `None` for non-EIP4844 transactions
+    fn max_fee_per_blob_gas(&self) -> Option<u128>;
+
+    /// Returns the versioned hashes of the blobs of an EIP-4844 transaction.
+    ///
+    /// This will return `None` for non-EIP4844 transactions
+    fn blob_versioned_hashes(&self) -> Option<&[H256]>;
+
+    /// Returns the sidecar of a blob transaction, until it is taken by the pool.
+    fn blob_sidecar(&self) -> Option<&BlobTransactionSidecar>;
+
+    /// Takes the sidecar of a blob transaction, if it has one.
+    ///
+    /// The pool stores the sidecars in its [`BlobStore`](crate::blobstore::BlobStore), so only
+    /// the transactions, which commit to their blobs with the versioned hashes, are kept in memory.
+    fn take_blob_sidecar(&mut self) -> Option<BlobTransactionSidecar>;
+
+    /// Creates the transaction from a blob transaction and the sidecar it was received with.
+    ///
+    /// Unlike [`FromRecoveredTransaction`], this keeps the sidecar, so that it ends up in the
+    /// blob store of the pool and can be served to the peers.
+    fn from_recovered_blob_transaction(
+        tx: TransactionSignedEcRecovered,
+        sidecar: BlobTransactionSidecar,
+    ) -> Self;
 }
 
 /// The default [PoolTransaction] for the [Pool](crate::Pool).
 ///
 /// This type is essentially a wrapper around [TransactionSignedEcRecovered] with additional fields
 /// derived from the transaction that are frequently used by the pools for ordering.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub(crate) struct PooledTransaction {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PooledTransaction {
     /// EcRecovered transaction info
     pub(crate) transaction: TransactionSignedEcRecovered,
 
+    /// The sidecar of a blob transaction, until it is moved to the blob store of the pool.
+    pub(crate) blob_sidecar: Option<BlobTransactionSidecar>,
+
     /// For EIP-1559 transactions that is `feeCap x gasLimit + transferred_value
     pub(crate) cost: U256,
 
@@ -341,6 +433,7 @@ impl PoolTransaction for PooledTransaction {
             Transaction::Legacy(_) => None,
             Transaction::Eip2930(_) => None,
             Transaction::Eip1559(tx) => Some(tx.max_fee_per_gas),
+            Transaction::Eip4844(tx) => Some(tx.max_fee_per_gas),
         }
     }
 
@@ -352,6 +445,7 @@ impl PoolTransaction for PooledTransaction {
             Transaction::Legacy(_) => None,
             Transaction::Eip2930(_) => None,
             Transaction::Eip1559(tx) => Some(tx.max_priority_fee_per_gas),
+            Transaction::Eip4844(tx) => Some(tx.max_priority_fee_per_gas),
         }
     }
 
@@ -365,6 +459,70 @@ impl PoolTransaction for PooledTransaction {
     fn size(&self) -> usize {
         self.transaction.transaction.input().len()
     }
+
+    /// Returns the type of the transaction.
+    fn tx_type(&self) -> TxType {
+        self.transaction.tx_type()
+    }
+
+    /// Returns the chain id of the transaction.
+    fn chain_id(&self) -> Option<u64> {
+        self.transaction.chain_id()
+    }
+
+    /// Returns the input data of the transaction.
+    fn input(&self) -> &[u8] {
+        self.transaction.input()
+    }
+
+    /// Returns the EIP-2930 access list of the transaction.
+    fn access_list(&self) -> Option<&AccessList> {
+        match &self.transaction.transaction {
+            Transaction::Legacy(_) => None,
+            Transaction::Eip2930(tx) => Some(&tx.access_list),
+            Transaction::Eip1559(tx) => Some(&tx.access_list),
+            Transaction::Eip4844(tx) => Some(&tx.access_list),
+        }
+    }
+
+    /// Returns the max fee per blob gas of an EIP-4844 transaction.
+    fn max_fee_per_blob_gas(&self) -> Option<u128> {
+        self.transaction.max_fee_per_blob_gas()
+    }
+
+    /// Returns the versioned hashes of the blobs of an EIP-4844 transaction.
+    fn blob_versioned_hashes(&self) -> Option<&[H256]> {
+        self.transaction.blob_versioned_hashes()
+    }
+
+    /// Returns the sidecar of a blob transaction, until it is taken by the pool.
+    fn blob_sidecar(&self) -> Option<&BlobTransactionSidecar> {
+        self.blob_sidecar.as_ref()
+    }
+
+    /// Takes the sidecar of a blob transaction, if it has one.
+    fn take_blob_sidecar(&mut self) -> Option<BlobTransactionSidecar> {
+        self.blob_sidecar.take()
+    }
+
+    fn from_recovered_blob_transaction(
+        tx: TransactionSignedEcRecovered,
+        sidecar: BlobTransactionSidecar,
+    ) -> Self {
+        Self::from_blob_transaction(tx, sidecar)
+    }
+}
+
+// === impl PooledTransaction ===
+
+impl PooledTransaction {
+    /// Creates a pooled blob transaction with the sidecar it was received with.
+    pub fn from_blob_transaction(
+        transaction: TransactionSignedEcRecovered,
+        sidecar: BlobTransactionSidecar,
+    ) -> Self {
+        Self { blob_sidecar: Some(sidecar), ..Self::from_recovered_transaction(transaction) }
+    }
 }
 
 impl FromRecoveredTransaction for PooledTransaction {
@@ -386,9 +544,16 @@ impl FromRecoveredTransaction for PooledTransaction {
                 let effective_gas_price = t.max_priority_fee_per_gas;
                 (cost, effective_gas_price)
             }
+            Transaction::Eip4844(t) => {
+                let cost = U256::from(t.max_fee_per_gas) * U256::from(t.gas_limit) +
+                    U256::from(t.value) +
+                    U256::from(t.max_fee_per_blob_gas) * U256::from(t.blob_gas());
+                let effective_gas_price = t.max_priority_fee_per_gas;
+                (cost, effective_gas_price)
+            }
         };
 
-        PooledTransaction { transaction: tx, cost, effective_gas_price }
+        PooledTransaction { transaction: tx, blob_sidecar: None, cost, effective_gas_price }
     }
 }
 
@@ -0,0 +1,443 @@
+//! Ethereum transaction validator.
+
+use crate::{
+    error::PoolError,
+    traits::{PoolTransaction, TransactionOrigin},
+    validate::{TransactionValidationOutcome, TransactionValidator},
+};
+use reth_primitives::{
+    eip4844::{calculate_blob_gasprice, KzgSettings},
+    ChainSpec, Hardfork, TransactionKind, TxType,
+};
+use reth_provider::{AccountProvider, BlockProvider, HeaderProvider, StateProviderFactory};
+use std::{
+    marker::PhantomData,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The max size of the init code of a contract creation, enforced after Shanghai (EIP-3860).
+pub const MAX_INIT_CODE_SIZE: usize = 2 * 24576;
+
+/// The gas every transaction pays.
+const TX_GAS: u64 = 21_000;
+
+/// The gas a contract creation pays after Homestead.
+const TX_CREATE_GAS: u64 = 53_000;
+
+/// The gas per zero byte of the input.
+const TX_DATA_ZERO_GAS: u64 = 4;
+
+/// The gas per non-zero byte of the input before Istanbul (EIP-2028).
+const TX_DATA_NON_ZERO_GAS_FRONTIER: u64 = 68;
+
+/// The gas per non-zero byte of the input after Istanbul (EIP-2028).
+const TX_DATA_NON_ZERO_GAS_ISTANBUL: u64 = 16;
+
+/// The gas per word of the init code after Shanghai (EIP-3860).
+const INIT_CODE_WORD_GAS: u64 = 2;
+
+/// The gas per address of the access list (EIP-2930).
+const ACCESS_LIST_ADDRESS_GAS: u64 = 2_400;
+
+/// The gas per storage key of the access list (EIP-2930).
+const ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1_900;
+
+/// A [`TransactionValidator`] that validates ethereum transactions against the [`ChainSpec`] and
+/// the latest state.
+///
+/// This checks that the transaction:
+///
+///   * is of a type that is enabled by the forks of the pending block
+///   * belongs to the chain
+///   * pays for its intrinsic gas
+///   * does not exceed the max init code size
+///   * has a nonce that is not lower than the nonce of the sender
+///   * can be paid for by the sender
+///   * if it is a blob transaction, pays the blob fee of the pending block and has a sidecar with
+///     the blobs it commits to, which is verified against the KZG trusted setup
+#[derive(Debug, Clone)]
+pub struct EthTransactionValidator<Client, T> {
+    /// Provides the latest state.
+    client: Client,
+    /// The spec of the chain the transactions are validated for.
+    chain_spec: Arc<ChainSpec>,
+    /// The max size of the init code of contract creations.
+    max_init_code_size: usize,
+    /// The KZG trusted setup the sidecars of blob transactions are verified with.
+    ///
+    /// Blob transactions are rejected without it.
+    kzg_settings: Option<Arc<KzgSettings>>,
+    _marker: PhantomData<T>,
+}
+
+// === impl EthTransactionValidator ===
+
+impl<Client, T> EthTransactionValidator<Client, T> {
+    /// Creates a new validator for the chain.
+    pub fn new(client: Client, chain_spec: Arc<ChainSpec>) -> Self {
+        Self {
+            client,
+            chain_spec,
+            max_init_code_size: MAX_INIT_CODE_SIZE,
+            kzg_settings: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the max size of the init code of contract creations.
+    pub fn with_max_init_code_size(mut self, max_init_code_size: usize) -> Self {
+        self.max_init_code_size = max_init_code_size;
+        self
+    }
+
+    /// Sets the KZG trusted setup the sidecars of blob transactions are verified with.
+    pub fn with_kzg_settings(mut self, kzg_settings: Arc<KzgSettings>) -> Self {
+        self.kzg_settings = Some(kzg_settings);
+        self
+    }
+}
+
+impl<Client, T> EthTransactionValidator<Client, T>
+where
+    Client: StateProviderFactory + BlockProvider + HeaderProvider,
+    T: PoolTransaction,
+{
+    /// Validates the transaction without the state, against the forks of the pending block.
+    fn validate_stateless(&self, transaction: &T, forks: ActiveForks) -> Result<(), PoolError> {
+        let hash = *transaction.hash();
+
+        let tx_type = transaction.tx_type();
+        let is_supported = match tx_type {
+            TxType::Legacy => true,
+            TxType::EIP2930 => forks.berlin,
+            TxType::EIP1559 => forks.london,
+            TxType::EIP4844 => forks.cancun,
+        };
+        if !is_supported {
+            return Err(PoolError::TxTypeNotSupported(hash, tx_type))
+        }
+
+        if let Some(chain_id) = transaction.chain_id() {
+            if chain_id != self.chain_spec.chain().id() {
+                return Err(PoolError::InvalidChainId(hash, chain_id))
+            }
+        }
+
+        if let (Some(max_fee), Some(priority_fee)) =
+            (transaction.max_fee_per_gas(), transaction.max_priority_fee_per_gas())
+        {
+            if priority_fee > max_fee {
+                return Err(PoolError::TipAboveFeeCap(hash))
+            }
+        }
+
+        if forks.shanghai {
+            self.ensure_max_init_code_size(transaction, self.max_init_code_size)?;
+        }
+
+        let intrinsic_gas = intrinsic_gas(transaction, forks);
+        if transaction.gas_limit() < intrinsic_gas {
+            return Err(PoolError::IntrinsicGasTooLow(hash, transaction.gas_limit(), intrinsic_gas))
+        }
+
+        Ok(())
+    }
+
+    /// Validates that the blob transaction pays the blob fee of the pending block, and that its
+    /// sidecar holds the blobs it commits to.
+    fn validate_blob_transaction(&self, transaction: &T, blob_fee: u128) -> Result<(), PoolError> {
+        let hash = *transaction.hash();
+
+        let max_fee_per_blob_gas = transaction.max_fee_per_blob_gas().unwrap_or_default();
+        if max_fee_per_blob_gas < blob_fee {
+            return Err(PoolError::BlobFeeCapTooLow(hash, max_fee_per_blob_gas, blob_fee))
+        }
+
+        let sidecar = transaction.blob_sidecar().ok_or(PoolError::MissingBlobSidecar(hash))?;
+        let kzg_settings =
+            self.kzg_settings.as_deref().ok_or(PoolError::MissingKzgSettings(hash))?;
+        let versioned_hashes = transaction.blob_versioned_hashes().unwrap_or_default();
+        sidecar
+            .validate(versioned_hashes, kzg_settings)
+            .map_err(|err| PoolError::InvalidBlobSidecar(hash, err))
+    }
+}
+
+#[async_trait::async_trait]
+impl<Client, T> TransactionValidator for EthTransactionValidator<Client, T>
+where
+    Client: StateProviderFactory + BlockProvider + HeaderProvider,
+    T: PoolTransaction,
+{
+    type Transaction = T;
+
+    async fn validate_transaction(
+        &self,
+        _origin: TransactionOrigin,
+        transaction: Self::Transaction,
+    ) -> TransactionValidationOutcome<Self::Transaction> {
+        let hash = *transaction.hash();
+
+        // The forks and the blob fee are determined for the pending block, which follows the
+        // head and is not proposed before now.
+        let head = self.client.chain_info().and_then(|info| {
+            let head = self.client.header_by_number(info.best_number)?.unwrap_or_default();
+            Ok((info.best_number + 1, head))
+        });
+        let (number, head) = match head {
+            Ok(head) => head,
+            Err(err) => return TransactionValidationOutcome::Error(hash, Box::new(err)),
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let timestamp = now.max(head.timestamp.saturating_add(1));
+        let forks = ActiveForks::new(&self.chain_spec, number, timestamp);
+
+        if let Err(err) = self.validate_stateless(&transaction, forks) {
+            return TransactionValidationOutcome::Invalid(transaction, err)
+        }
+
+        if transaction.tx_type() == TxType::EIP4844 {
+            let blob_fee = calculate_blob_gasprice(head.next_block_excess_blob_gas().unwrap_or(0));
+            if let Err(err) = self.validate_blob_transaction(&transaction, blob_fee) {
+                return TransactionValidationOutcome::Invalid(transaction, err)
+            }
+        }
+
+        let account = match self
+            .client
+            .latest()
+            .and_then(|state| state.basic_account(transaction.sender()))
+        {
+            Ok(account) => account.unwrap_or_default(),
+            Err(err) => return TransactionValidationOutcome::Error(hash, Box::new(err)),
+        };
+
+        if transaction.nonce() < account.nonce {
+            let err = PoolError::NonceTooLow(hash, transaction.nonce(), account.nonce);
+            return TransactionValidationOutcome::Invalid(transaction, err)
+        }
+
+        if transaction.cost() > account.balance {
+            let err = PoolError::InsufficientFunds(hash, transaction.cost(), account.balance);
+            return TransactionValidationOutcome::Invalid(transaction, err)
+        }
+
+        TransactionValidationOutcome::Valid {
+            balance: account.balance,
+            state_nonce: account.nonce,
+            transaction,
+        }
+    }
+}
+
+/// The forks that affect the validation of transactions.
+#[derive(Debug, Clone, Copy, Default)]
+struct ActiveForks {
+    homestead: bool,
+    istanbul: bool,
+    berlin: bool,
+    london: bool,
+    shanghai: bool,
+    cancun: bool,
+}
+
+impl ActiveForks {
+    /// Returns the forks that are active at the given block.
+    fn new(chain_spec: &ChainSpec, number: u64, timestamp: u64) -> Self {
+        Self {
+            homestead: chain_spec.fork(Hardfork::Homestead).active_at_block(number),
+            istanbul: chain_spec.fork(Hardfork::Istanbul).active_at_block(number),
+            berlin: chain_spec.fork(Hardfork::Berlin).active_at_block(number),
+            london: chain_spec.fork(Hardfork::London).active_at_block(number),
+            shanghai: chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(timestamp),
+            cancun: chain_spec.fork(Hardfork::Cancun).active_at_timestamp(timestamp),
+        }
+    }
+}
+
+/// Returns the gas that is charged for the transaction before it is executed.
+fn intrinsic_gas<T: PoolTransaction>(transaction: &T, forks: ActiveForks) -> u64 {
+    let is_create = matches!(transaction.kind(), TransactionKind::Create);
+    let mut gas = if is_create && forks.homestead { TX_CREATE_GAS } else { TX_GAS };
+
+    let input = transaction.input();
+    let zero_bytes = input.iter().filter(|byte| **byte == 0).count() as u64;
+    let non_zero_bytes = input.len() as u64 - zero_bytes;
+    let non_zero_gas =
+        if forks.istanbul { TX_DATA_NON_ZERO_GAS_ISTANBUL } else { TX_DATA_NON_ZERO_GAS_FRONTIER };
+    gas = gas
+        .saturating_add(zero_bytes * TX_DATA_ZERO_GAS)
+        .saturating_add(non_zero_bytes.saturating_mul(non_zero_gas));
+
+    if is_create && forks.shanghai {
+        let words = (input.len() as u64 + 31) / 32;
+        gas = gas.saturating_add(words * INIT_CODE_WORD_GAS);
+    }
+
+    if let Some(access_list) = transaction.access_list() {
+        let storage_keys =
+            access_list.0.iter().map(|item| item.storage_keys.len() as u64).sum::<u64>();
+        gas = gas
+            .saturating_add(access_list.0.len() as u64 * ACCESS_LIST_ADDRESS_GAS)
+            .saturating_add(storage_keys * ACCESS_LIST_STORAGE_KEY_GAS);
+    }
+
+    gas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::PooledTransaction;
+    use reth_primitives::{
+        AccessList, AccessListItem, Address, ChainSpecBuilder, FromRecoveredTransaction, Header,
+        Signature, Transaction, TransactionSigned, TransactionSignedEcRecovered, TxEip1559,
+        TxEip4844, H256, MAINNET, U256,
+    };
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+
+    fn pooled_transaction(sender: Address, tx: TxEip1559) -> PooledTransaction {
+        let signed = TransactionSigned::from_transaction_and_signature(
+            Transaction::Eip1559(tx),
+            Signature::default(),
+        );
+        PooledTransaction::from_recovered_transaction(
+            TransactionSignedEcRecovered::from_signed_transaction(signed, sender),
+        )
+    }
+
+    fn transfer() -> TxEip1559 {
+        TxEip1559 {
+            chain_id: 1,
+            nonce: 1,
+            gas_limit: TX_GAS,
+            max_fee_per_gas: 1_000_000_000,
+            max_priority_fee_per_gas: 1_000_000_000,
+            to: TransactionKind::Call(Address::random()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn intrinsic_gas_of_transactions() {
+        let sender = Address::random();
+        let forks = ActiveForks { homestead: true, istanbul: true, ..Default::default() };
+        assert_eq!(intrinsic_gas(&pooled_transaction(sender, transfer()), forks), TX_GAS);
+
+        let tx = TxEip1559 {
+            to: TransactionKind::Create,
+            input: vec![0, 1, 2].into(),
+            access_list: AccessList(vec![AccessListItem {
+                address: Address::random(),
+                storage_keys: vec![H256::zero(), H256::zero()],
+            }]),
+            ..transfer()
+        };
+        assert_eq!(
+            intrinsic_gas(&pooled_transaction(sender, tx.clone()), forks),
+            TX_CREATE_GAS + 4 + 2 * 16 + 2_400 + 2 * 1_900
+        );
+
+        // contract creations also pay for the words of the init code after Shanghai
+        let forks = ActiveForks { shanghai: true, ..forks };
+        assert_eq!(
+            intrinsic_gas(&pooled_transaction(sender, tx), forks),
+            TX_CREATE_GAS + 4 + 2 * 16 + 2 + 2_400 + 2 * 1_900
+        );
+    }
+
+    #[tokio::test]
+    async fn validate_against_chain_and_state() {
+        let client = MockEthProvider::default();
+        // a post-London block
+        client.add_header(H256::random(), Header { number: 15_000_000, ..Default::default() });
+        let sender = Address::random();
+        let balance = U256::from(1_000_000_000_000_000u64);
+        client.add_account(sender, ExtendedAccount::new(1, balance));
+        let validator = EthTransactionValidator::new(client, Arc::new(MAINNET.clone()));
+
+        let validate = |tx: TxEip1559| {
+            validator
+                .validate_transaction(TransactionOrigin::External, pooled_transaction(sender, tx))
+        };
+
+        match validate(transfer()).await {
+            TransactionValidationOutcome::Valid { balance: valid_balance, state_nonce, .. } => {
+                assert_eq!(valid_balance, balance);
+                assert_eq!(state_nonce, 1);
+            }
+            outcome => panic!("expected valid transaction, got {outcome:?}"),
+        }
+
+        let invalid = [
+            (TxEip1559 { chain_id: 5, ..transfer() }, "chain id"),
+            (TxEip1559 { nonce: 0, ..transfer() }, "nonce"),
+            (TxEip1559 { gas_limit: TX_GAS - 1, ..transfer() }, "intrinsic gas"),
+            (TxEip1559 { value: 1_000_000_000_000_000, ..transfer() }, "balance"),
+            (TxEip1559 { max_priority_fee_per_gas: 2_000_000_000, ..transfer() }, "fee cap"),
+        ];
+        for (tx, reason) in invalid {
+            let outcome = validate(tx).await;
+            assert!(
+                matches!(outcome, TransactionValidationOutcome::Invalid(..)),
+                "expected invalid {reason}, got {outcome:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn validate_blob_transactions() {
+        let client = MockEthProvider::default();
+        client.add_header(H256::random(), Header { number: 1, ..Default::default() });
+        let sender = Address::random();
+        client.add_account(sender, ExtendedAccount::new(1, U256::from(1_000_000_000_000_000u64)));
+        let chain_spec = ChainSpecBuilder::mainnet().cancun_activated().build();
+        let validator = EthTransactionValidator::new(client, Arc::new(chain_spec));
+
+        let recovered = |tx: TxEip4844| {
+            let signed = TransactionSigned::from_transaction_and_signature(
+                Transaction::Eip4844(tx),
+                Signature::default(),
+            );
+            TransactionSignedEcRecovered::from_signed_transaction(signed, sender)
+        };
+        let blob_transaction = TxEip4844 {
+            chain_id: 1,
+            nonce: 1,
+            gas_limit: TX_GAS,
+            max_fee_per_gas: 1_000_000_000,
+            max_priority_fee_per_gas: 1_000_000_000,
+            to: TransactionKind::Call(Address::random()),
+            blob_versioned_hashes: vec![H256::random()],
+            max_fee_per_blob_gas: 1,
+            ..Default::default()
+        };
+
+        let without_sidecar =
+            PooledTransaction::from_recovered_transaction(recovered(blob_transaction.clone()));
+        assert!(matches!(
+            validator.validate_transaction(TransactionOrigin::External, without_sidecar).await,
+            TransactionValidationOutcome::Invalid(_, PoolError::MissingBlobSidecar(_))
+        ));
+
+        let underpriced = PooledTransaction::from_blob_transaction(
+            recovered(TxEip4844 { max_fee_per_blob_gas: 0, ..blob_transaction.clone() }),
+            Default::default(),
+        );
+        assert!(matches!(
+            validator.validate_transaction(TransactionOrigin::External, underpriced).await,
+            TransactionValidationOutcome::Invalid(_, PoolError::BlobFeeCapTooLow(_, 0, 1))
+        ));
+
+        // the sidecar can't be verified without a KZG trusted setup
+        let unverified = PooledTransaction::from_blob_transaction(
+            recovered(blob_transaction),
+            Default::default(),
+        );
+        assert!(matches!(
+            validator.validate_transaction(TransactionOrigin::External, unverified).await,
+            TransactionValidationOutcome::Invalid(_, PoolError::MissingKzgSettings(_))
+        ));
+    }
+}
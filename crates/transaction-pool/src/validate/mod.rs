@@ -8,6 +8,12 @@ use crate::{
 use reth_primitives::{Address, TransactionKind, TxHash, U256};
 use std::{fmt, time::Instant};
 
+mod eth;
+mod task;
+
+pub use eth::{EthTransactionValidator, MAX_INIT_CODE_SIZE};
+pub use task::{TransactionValidationTaskExecutor, ValidationTask};
+
 /// A Result type returned after checking a transaction's validity.
 #[derive(Debug)]
 pub enum TransactionValidationOutcome<T: PoolTransaction> {
@@ -23,6 +29,9 @@ pub enum TransactionValidationOutcome<T: PoolTransaction> {
     /// The transaction is considered invalid indefinitely: It violates constraints that prevent
     /// this transaction from ever becoming valid.
     Invalid(T, PoolError),
+    /// An error occurred while trying to validate the transaction, e.g. the state could not be
+    /// read.
+    Error(TxHash, Box<dyn std::error::Error + Send + Sync>),
 }
 
 /// Provides support for validating transaction at any given state of the chain
@@ -57,16 +66,13 @@ pub trait TransactionValidator: Send + Sync {
 
     /// Ensure that the code size is not greater than `max_init_code_size`.
     /// `max_init_code_size` should be configurable so this will take it as an argument.
+    ///
+    /// This only applies after Shanghai (EIP-3860), which implementers must check before.
     fn ensure_max_init_code_size(
         &self,
-        transaction: Self::Transaction,
+        transaction: &Self::Transaction,
         max_init_code_size: usize,
     ) -> Result<(), PoolError> {
-        // TODO check whether we are in the Shanghai stage.
-        // if !self.shanghai {
-        //     return Ok(())
-        // }
-
         if *transaction.kind() == TransactionKind::Create && transaction.size() > max_init_code_size
         {
             Err(PoolError::TxExceedsMaxInitCodeSize(
@@ -135,8 +141,25 @@ impl<T: PoolTransaction> ValidPoolTransaction<T> {
     }
 
     /// Returns true if this transaction is underpriced compared to the other.
-    pub(crate) fn is_underpriced(&self, other: &Self) -> bool {
-        self.transaction.effective_gas_price() <= other.transaction.effective_gas_price()
+    ///
+    /// A replacement must pay at least `price_bump` percent more than the transaction it replaces,
+    /// for EIP-1559 transactions this applies to both the fee cap and the priority fee.
+    pub(crate) fn is_underpriced(&self, other: &Self, price_bump: u128) -> bool {
+        let is_bumped = |new: u128, old: u128| {
+            new.saturating_mul(100) >= old.saturating_mul(100 + price_bump) && new > old
+        };
+
+        let fee_cap = |tx: &Self| {
+            tx.transaction.max_fee_per_gas().unwrap_or_else(|| tx.transaction.effective_gas_price())
+        };
+        let priority_fee = |tx: &Self| {
+            tx.transaction
+                .max_priority_fee_per_gas()
+                .unwrap_or_else(|| tx.transaction.effective_gas_price())
+        };
+
+        !is_bumped(fee_cap(self), fee_cap(other)) ||
+            !is_bumped(priority_fee(self), priority_fee(other))
     }
 
     /// Whether the transaction originated locally.
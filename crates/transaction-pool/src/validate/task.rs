@@ -0,0 +1,157 @@
+//! Runs transaction validations on a pool of tasks.
+
+use crate::{
+    traits::{PoolTransaction, TransactionOrigin},
+    validate::{TransactionValidationOutcome, TransactionValidator},
+};
+use futures_util::future::BoxFuture;
+use reth_tasks::TaskSpawner;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+/// The number of validation jobs that can be queued before new jobs have to wait.
+const VALIDATION_JOBS_BUFFER: usize = 1024;
+
+/// A validation that is sent to a [`ValidationTask`].
+type ValidationJob = BoxFuture<'static, ()>;
+
+/// A task that runs validation jobs.
+///
+/// All clones of the task share the same queue of jobs, so running multiple clones validates
+/// transactions concurrently.
+#[derive(Debug, Clone)]
+#[must_use = "the task does nothing unless it is run"]
+pub struct ValidationTask {
+    /// The queue of validation jobs.
+    validation_jobs: Arc<Mutex<mpsc::Receiver<ValidationJob>>>,
+}
+
+impl ValidationTask {
+    /// Runs the jobs of the queue until all senders of jobs are dropped.
+    pub async fn run(self) {
+        loop {
+            let job = self.validation_jobs.lock().await.recv().await;
+            match job {
+                Some(job) => job.await,
+                None => return,
+            }
+        }
+    }
+}
+
+/// A [`TransactionValidator`] that runs the validations of the wrapped validator on
+/// [`ValidationTask`]s.
+#[derive(Debug)]
+pub struct TransactionValidationTaskExecutor<V> {
+    /// The validator that validates the transactions.
+    validator: Arc<V>,
+    /// The sender half of the validation job queue.
+    to_validation_task: mpsc::Sender<ValidationJob>,
+}
+
+// === impl TransactionValidationTaskExecutor ===
+
+impl<V> TransactionValidationTaskExecutor<V> {
+    /// Creates a new executor for the validator and the task that runs its validations.
+    ///
+    /// The task, or any number of clones of it, must be spawned.
+    pub fn new(validator: V) -> (Self, ValidationTask) {
+        let (to_validation_task, validation_jobs) = mpsc::channel(VALIDATION_JOBS_BUFFER);
+        let task = ValidationTask { validation_jobs: Arc::new(Mutex::new(validation_jobs)) };
+        (Self { validator: Arc::new(validator), to_validation_task }, task)
+    }
+
+    /// Creates a new executor for the validator and spawns `num_tasks` tasks that run its
+    /// validations.
+    pub fn spawn(validator: V, tasks: &dyn TaskSpawner, num_tasks: usize) -> Self {
+        let (executor, task) = Self::new(validator);
+        for _ in 0..num_tasks.max(1) {
+            let _ = tasks.spawn(Box::pin(task.clone().run()));
+        }
+        executor
+    }
+
+    /// Returns the wrapped validator.
+    pub fn validator(&self) -> &V {
+        &self.validator
+    }
+}
+
+impl<V> Clone for TransactionValidationTaskExecutor<V> {
+    fn clone(&self) -> Self {
+        Self {
+            validator: Arc::clone(&self.validator),
+            to_validation_task: self.to_validation_task.clone(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<V> TransactionValidator for TransactionValidationTaskExecutor<V>
+where
+    V: TransactionValidator + 'static,
+    V::Transaction: 'static,
+{
+    type Transaction = V::Transaction;
+
+    async fn validate_transaction(
+        &self,
+        origin: TransactionOrigin,
+        transaction: Self::Transaction,
+    ) -> TransactionValidationOutcome<Self::Transaction> {
+        let hash = *transaction.hash();
+        let (tx, rx) = oneshot::channel();
+        let validator = Arc::clone(&self.validator);
+        let job = Box::pin(async move {
+            let outcome = validator.validate_transaction(origin, transaction).await;
+            let _ = tx.send(outcome);
+        });
+
+        if self.to_validation_task.send(job).await.is_err() {
+            return TransactionValidationOutcome::Error(hash, "validation tasks stopped".into())
+        }
+
+        match rx.await {
+            Ok(outcome) => outcome,
+            Err(_) => TransactionValidationOutcome::Error(hash, "validation was cancelled".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{MockTransaction, NoopTransactionValidator};
+    use reth_tasks::TokioTaskExecutor;
+
+    #[tokio::test]
+    async fn runs_validations_on_tasks() {
+        let executor = TransactionValidationTaskExecutor::spawn(
+            NoopTransactionValidator::default(),
+            &TokioTaskExecutor::default(),
+            2,
+        );
+
+        let transaction = MockTransaction::eip1559();
+        let outcome =
+            executor.validate_transaction(TransactionOrigin::External, transaction.clone()).await;
+        assert!(matches!(
+            outcome,
+            TransactionValidationOutcome::Valid { transaction: valid, .. } if valid == transaction
+        ));
+    }
+
+    #[tokio::test]
+    async fn fails_without_tasks() {
+        let (executor, task) =
+            TransactionValidationTaskExecutor::new(NoopTransactionValidator::default());
+        drop(task);
+
+        let transaction = MockTransaction::eip1559();
+        let hash = *transaction.hash();
+        let outcome = executor.validate_transaction(TransactionOrigin::External, transaction).await;
+        assert!(
+            matches!(outcome, TransactionValidationOutcome::Error(err_hash, _) if err_hash == hash)
+        );
+    }
+}
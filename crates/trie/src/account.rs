@@ -0,0 +1,33 @@
+use reth_primitives::{proofs::EMPTY_ROOT, Account, H256, KECCAK_EMPTY, U256};
+use reth_rlp::{RlpDecodable, RlpEncodable};
+
+/// An account as it is encoded in the leaves of the account trie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, RlpEncodable, RlpDecodable)]
+pub struct EthAccount {
+    /// Account nonce.
+    pub nonce: u64,
+    /// Account balance.
+    pub balance: U256,
+    /// Root of the storage trie of the account.
+    pub storage_root: H256,
+    /// Hash of the bytecode of the account.
+    pub code_hash: H256,
+}
+
+impl From<Account> for EthAccount {
+    fn from(account: Account) -> Self {
+        EthAccount {
+            nonce: account.nonce,
+            balance: account.balance,
+            storage_root: EMPTY_ROOT,
+            code_hash: account.bytecode_hash.unwrap_or(KECCAK_EMPTY),
+        }
+    }
+}
+
+impl EthAccount {
+    /// Returns the encoded account with the given storage root.
+    pub fn from_with_root(account: Account, storage_root: H256) -> EthAccount {
+        Self { storage_root, ..Self::from(account) }
+    }
+}
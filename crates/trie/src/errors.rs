@@ -0,0 +1,55 @@
+use reth_primitives::H256;
+use reth_rlp::DecodeError;
+
+/// An error while computing the root of a state trie.
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+pub enum StateRootError {
+    /// The database could not be read or written.
+    #[error(transparent)]
+    Database(#[from] reth_db::Error),
+    /// A node of a trie which is updated is not in the trie tables.
+    #[error("Trie node {hash:?} does not exist in the trie tables")]
+    MissingNode {
+        /// The hash of the node.
+        hash: H256,
+    },
+    /// A node of a trie which is updated could not be decoded.
+    #[error("Trie node {hash:?} could not be decoded")]
+    InvalidNode {
+        /// The hash of the node.
+        hash: H256,
+    },
+    /// An account of the account trie could not be decoded.
+    #[error("Invalid account in the account trie: {0:?}")]
+    InvalidAccount(#[from] DecodeError),
+}
+
+/// An error while walking a trie, `E` being the error of the source of its nodes.
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+pub enum ProofError<E> {
+    /// The node could not be read from its source.
+    #[error(transparent)]
+    Source(E),
+    /// A node on the path is not in the source.
+    #[error("Trie node {hash:?} does not exist")]
+    MissingNode {
+        /// The hash of the node.
+        hash: H256,
+    },
+    /// A node on the path could not be decoded.
+    #[error("Trie node {hash:?} could not be decoded")]
+    InvalidNode {
+        /// The hash of the node, or of the node it is inlined into.
+        hash: H256,
+    },
+}
+
+impl From<ProofError<reth_db::Error>> for StateRootError {
+    fn from(error: ProofError<reth_db::Error>) -> Self {
+        match error {
+            ProofError::Source(error) => StateRootError::Database(error),
+            ProofError::MissingNode { hash } => StateRootError::MissingNode { hash },
+            ProofError::InvalidNode { hash } => StateRootError::InvalidNode { hash },
+        }
+    }
+}
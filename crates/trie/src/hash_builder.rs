@@ -0,0 +1,296 @@
+//! Streaming computation of the root of a trie.
+
+use crate::{
+    nibbles::common_prefix_length,
+    nodes::{branch_node, extension_node, leaf_node, node_ref, ref_hash},
+};
+use reth_primitives::{keccak256, proofs::EMPTY_ROOT, H256};
+
+/// Computes the root of a trie from its leaves, added in increasing order of their keys.
+///
+/// Only the branches on the path of the last added leaf are kept in memory. The position of a
+/// leaf in the trie is known once the next one is added: the subtries on its left are then
+/// complete, so they are encoded and only their references are kept in their parent branch.
+///
+/// Unchanged subtries of an existing trie can be added by the reference to their root branch with
+/// [`HashBuilder::add_branch`], instead of all of their leaves.
+///
+/// The nodes which are referenced by their hash can be retained with
+/// [`HashBuilder::with_updates`], to be persisted in the trie tables.
+#[derive(Debug, Default)]
+pub struct HashBuilder {
+    /// The branches on the path of the last added item, with increasing depths.
+    stack: Vec<Branch>,
+    /// The last added item, whose position depends on the next one.
+    pending: Option<Item>,
+    /// The retained nodes which are referenced by their hash, with their hashes.
+    updates: Option<Vec<(H256, Vec<u8>)>>,
+}
+
+/// A branch whose children are not all known yet.
+#[derive(Debug)]
+struct Branch {
+    /// The length of the path of the branch.
+    depth: usize,
+    /// The references to the children of the branch.
+    children: [Option<Vec<u8>>; 16],
+}
+
+/// An item added to the builder.
+#[derive(Debug)]
+enum Item {
+    /// A leaf with its full key and its value.
+    Leaf(Vec<u8>, Vec<u8>),
+    /// The path of a branch and the reference to it.
+    Branch(Vec<u8>, Vec<u8>),
+}
+
+impl Item {
+    fn path(&self) -> &[u8] {
+        match self {
+            Item::Leaf(path, _) | Item::Branch(path, _) => path,
+        }
+    }
+}
+
+impl HashBuilder {
+    /// Whether to retain the nodes referenced by their hash, including the root.
+    pub fn with_updates(mut self, retain: bool) -> Self {
+        self.updates = retain.then(Vec::new);
+        self
+    }
+
+    /// Adds the leaf with the key of the given nibbles.
+    ///
+    /// The keys of the leaves and the paths of the branches must be added in increasing order,
+    /// and none of them can be a prefix of another one.
+    pub fn add_leaf(&mut self, key: Vec<u8>, value: &[u8]) {
+        self.add(Item::Leaf(key, value.to_vec()));
+    }
+
+    /// Adds the branch at the path of the given nibbles, by its reference as returned by
+    /// [`node_ref`].
+    ///
+    /// The reference can also be the one of a leaf or an extension as long as it stays at the
+    /// same depth, i.e. if the node at the path has siblings in the resulting trie.
+    pub fn add_branch(&mut self, path: Vec<u8>, reference: Vec<u8>) {
+        self.add(Item::Branch(path, reference));
+    }
+
+    fn add(&mut self, item: Item) {
+        if let Some(last) = self.pending.take() {
+            debug_assert!(last.path() < item.path(), "items must be added in increasing order");
+            let shared = common_prefix_length(last.path(), item.path());
+            self.place(last, shared);
+        }
+        self.pending = Some(item);
+    }
+
+    /// Returns the root of the trie of the added items, and resets the builder.
+    pub fn root(&mut self) -> H256 {
+        let Some(last) = self.pending.take() else { return EMPTY_ROOT };
+
+        let root = match (self.stack.first().map(|branch| branch.depth), last) {
+            (None, Item::Leaf(key, value)) => leaf_node(&key, &value),
+            // the root is an existing node, which is already stored
+            (None, Item::Branch(path, reference)) if path.is_empty() => return ref_hash(&reference),
+            (None, Item::Branch(path, reference)) => extension_node(&path, &reference),
+            (Some(depth), last) => {
+                let path = last.path().to_vec();
+                self.place(last, depth);
+                let branch = self.stack.pop().expect("the root branch is on the stack");
+                let branch = branch_node(&branch.children);
+                if depth == 0 {
+                    branch
+                } else {
+                    let child = self.encode(branch);
+                    extension_node(&path[..depth], &child)
+                }
+            }
+        };
+
+        // The root is always referenced by its hash.
+        let hash = keccak256(&root);
+        if let Some(updates) = &mut self.updates {
+            updates.push((hash, root));
+        }
+        hash
+    }
+
+    /// Takes the retained nodes, with their hashes.
+    pub fn take_updates(&mut self) -> Vec<(H256, Vec<u8>)> {
+        self.updates.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Places the item in its branch, given the length of the prefix its path shares with the
+    /// next item, then encodes the branches below that prefix, as they are complete.
+    fn place(&mut self, item: Item, shared: usize) {
+        let depth = self.branch_at(shared);
+        let (path, child) = match item {
+            Item::Leaf(key, value) => {
+                let leaf = leaf_node(&key[depth + 1..], &value);
+                let leaf = self.encode(leaf);
+                (key, leaf)
+            }
+            Item::Branch(path, reference) => {
+                let child = self.below_extension(&path, depth, path.len(), reference);
+                (path, child)
+            }
+        };
+        self.set_child(&path, child);
+
+        while self.stack.last().map_or(false, |branch| branch.depth > shared) {
+            let branch = self.stack.pop().expect("the stack is not empty");
+            let node = branch_node(&branch.children);
+            let depth = self.branch_at(shared);
+            let child = self.encode(node);
+            let child = self.below_extension(&path, depth, branch.depth, child);
+            self.set_child(&path, child);
+        }
+    }
+
+    /// Returns the reference to the child of the branch at `depth`, for the branch at
+    /// `branch_depth` on the path. The branch is below an extension if it is not a direct child.
+    fn below_extension(
+        &mut self,
+        path: &[u8],
+        depth: usize,
+        branch_depth: usize,
+        reference: Vec<u8>,
+    ) -> Vec<u8> {
+        if branch_depth > depth + 1 {
+            let extension = extension_node(&path[depth + 1..branch_depth], &reference);
+            self.encode(extension)
+        } else {
+            reference
+        }
+    }
+
+    /// Returns the depth of the deepest branch, after pushing a branch at `shared` if the
+    /// deepest one is above it.
+    fn branch_at(&mut self, shared: usize) -> usize {
+        match self.stack.last() {
+            Some(branch) if branch.depth >= shared => branch.depth,
+            _ => {
+                self.stack.push(Branch { depth: shared, children: Default::default() });
+                shared
+            }
+        }
+    }
+
+    /// Sets the child of the deepest branch on the path.
+    fn set_child(&mut self, path: &[u8], child: Vec<u8>) {
+        let branch = self.stack.last_mut().expect("a branch is on the stack");
+        branch.children[path[branch.depth] as usize] = Some(child);
+    }
+
+    /// Returns the reference to the node, retaining the node if it is referenced by its hash.
+    fn encode(&mut self, node: Vec<u8>) -> Vec<u8> {
+        let reference = node_ref(&node);
+        if let Some(updates) = &mut self.updates {
+            if node.len() >= 32 {
+                updates.push((keccak256(&node), node));
+            }
+        }
+        reference
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nibbles::unpack;
+    use proptest::{
+        collection::{btree_map, vec},
+        prelude::*,
+    };
+    use reth_primitives::proofs::KeccakHasher;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn expected_root(leaves: &BTreeMap<H256, Vec<u8>>) -> H256 {
+        triehash::trie_root::<KeccakHasher, _, _, _>(leaves.clone())
+    }
+
+    fn build(leaves: &BTreeMap<H256, Vec<u8>>) -> (H256, HashMap<H256, Vec<u8>>) {
+        let mut builder = HashBuilder::default().with_updates(true);
+        for (key, value) in leaves {
+            builder.add_leaf(unpack(key.as_bytes()), value);
+        }
+        let root = builder.root();
+        (root, builder.take_updates().into_iter().collect())
+    }
+
+    #[test]
+    fn empty_and_single_leaf() {
+        assert_eq!(HashBuilder::default().root(), EMPTY_ROOT);
+
+        let leaves = BTreeMap::from([(H256::repeat_byte(0x12), vec![0x01])]);
+        let (root, nodes) = build(&leaves);
+        assert_eq!(root, expected_root(&leaves));
+        assert_eq!(nodes.len(), 1);
+        assert!(nodes.contains_key(&root));
+    }
+
+    #[test]
+    fn extension_at_the_root() {
+        let mut second = H256::repeat_byte(0x11);
+        second.0[31] = 0x21;
+        let mut third = H256::repeat_byte(0x11);
+        third.0[20] = 0xff;
+        let leaves = BTreeMap::from([
+            (H256::repeat_byte(0x11), vec![0xaa; 32]),
+            (second, vec![0xbb]),
+            (third, vec![0xcc; 3]),
+        ]);
+        let (root, nodes) = build(&leaves);
+        assert_eq!(root, expected_root(&leaves));
+        for (hash, node) in nodes {
+            assert_eq!(keccak256(node), hash);
+        }
+    }
+
+    #[test]
+    fn unchanged_branch() {
+        let key = |first: u8, second: u8| H256::from_slice(&[[first, second], [0x33; 30]].concat());
+        let leaves = BTreeMap::from([
+            (key(0x10, 0x00), vec![0xaa; 32]),
+            (key(0x12, 0x00), vec![0xbb; 32]),
+            (key(0x1f, 0x00), vec![0xcc; 32]),
+            (key(0x20, 0x00), vec![0xdd; 32]),
+            (key(0x20, 0x01), vec![0xee; 32]),
+        ]);
+
+        // the subtrie of the branch at [1], built from the keys without their first nibble
+        let mut builder = HashBuilder::default().with_updates(true);
+        for (key, value) in leaves.range(..key(0x20, 0x00)) {
+            builder.add_leaf(unpack(key.as_bytes())[1..].to_vec(), value);
+        }
+        builder.root();
+        let (_, branch) = builder.take_updates().pop().unwrap();
+
+        let mut builder = HashBuilder::default();
+        builder.add_branch(vec![1], node_ref(&branch));
+        for (key, value) in leaves.range(key(0x20, 0x00)..) {
+            builder.add_leaf(unpack(key.as_bytes()), value);
+        }
+        assert_eq!(builder.root(), expected_root(&leaves));
+
+        // the branch below an extension, once its siblings are removed
+        let mut builder = HashBuilder::default();
+        builder.add_branch(vec![1], node_ref(&branch));
+        let only_branch = leaves.range(..key(0x20, 0x00)).map(|(k, v)| (*k, v.clone())).collect();
+        assert_eq!(builder.root(), expected_root(&only_branch));
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_leaves(
+            leaves in btree_map(any::<[u8; 32]>(), vec(any::<u8>(), 1..40), 0..100)
+        ) {
+            let leaves = leaves.into_iter().map(|(key, value)| (H256(key), value)).collect();
+            let (root, nodes) = build(&leaves);
+            prop_assert_eq!(root, expected_root(&leaves));
+            prop_assert_eq!(nodes.contains_key(&root), !leaves.is_empty());
+        }
+    }
+}
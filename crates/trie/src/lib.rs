@@ -0,0 +1,50 @@
+#![warn(missing_docs, unreachable_pub)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Computation of the Merkle Patricia Tries of the state.
+//!
+//! The [`HashBuilder`] computes the root of a trie from its leaves given in the order of their
+//! keys, which is the order of the hashed state tables. [`StateRoot`] and [`StorageRoot`] feed it
+//! from the database, and can give the nodes of the tries as they are built, to persist them in
+//! the trie tables. The persisted tries are then updated incrementally by walking the paths of
+//! the keys changed since, collected in [`PrefixSets`].
+//!
+//! [`ParallelStateRoot`] computes the storage roots of the accounts on a thread pool, which makes
+//! rebuilding the tries of a large state much faster.
+//!
+//! The [`proof`] module generates and verifies the Merkle proofs of the accounts and of their
+//! storage slots, from the trie tables with [`Proof`] or from any source of nodes.
+
+mod errors;
+pub use errors::{ProofError, StateRootError};
+
+mod account;
+pub use account::EthAccount;
+
+/// Paths of the trie.
+pub mod nibbles;
+
+/// Encoding of the trie nodes.
+pub mod nodes;
+
+mod hash_builder;
+pub use hash_builder::HashBuilder;
+
+mod prefix_set;
+pub use prefix_set::{PrefixSet, PrefixSetLoader, PrefixSets};
+
+mod walker;
+
+mod state_root;
+pub use state_root::{StateRoot, StorageRoot, TrieUpdate};
+
+mod parallel;
+pub use parallel::ParallelStateRoot;
+
+/// Generation and verification of Merkle proofs.
+pub mod proof;
+pub use proof::{AccountProof, MultiProof, Proof, StorageProof, TrieProof};
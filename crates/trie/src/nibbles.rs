@@ -0,0 +1,87 @@
+//! Paths of the trie, made of nibbles.
+
+/// Splits the bytes into nibbles.
+pub fn unpack(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect()
+}
+
+/// Joins pairs of nibbles into bytes. An odd number of nibbles is padded with a zero nibble.
+pub fn pack(nibbles: &[u8]) -> Vec<u8> {
+    nibbles.chunks(2).map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or_default()).collect()
+}
+
+/// Returns the length of the longest common prefix of the two paths.
+pub fn common_prefix_length(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// Hex prefix encodes the path of a leaf or an extension node.
+///
+/// The first nibble is a flag telling whether it is the path of a leaf and whether the path has
+/// an odd length, in which case the first nibble of the path is packed with the flag.
+pub fn encode_path(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 0x20 } else { 0x00 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        encoded.push(flag | 0x10 | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag);
+        nibbles
+    };
+    encoded.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    encoded
+}
+
+/// Decodes a hex prefix encoded path into its nibbles, and whether it is the path of a leaf.
+///
+/// Returns `None` if the encoding is invalid.
+pub fn decode_path(path: &[u8]) -> Option<(Vec<u8>, bool)> {
+    let (first, rest) = path.split_first()?;
+    let flag = first >> 4;
+    if flag > 3 || (flag & 1 == 0 && first & 0x0f != 0) {
+        return None
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        // odd length, the first nibble is part of the path
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(unpack(rest));
+    Some((nibbles, flag & 2 == 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_prefix() {
+        assert_eq!(encode_path(&[], false), vec![0x00]);
+        assert_eq!(encode_path(&[], true), vec![0x20]);
+        assert_eq!(encode_path(&[1, 2, 3, 4, 5], false), vec![0x11, 0x23, 0x45]);
+        assert_eq!(encode_path(&[0, 1, 2, 3, 4, 5], false), vec![0x00, 0x01, 0x23, 0x45]);
+        assert_eq!(encode_path(&[0x0f, 0x01, 0x0c, 0x0b, 0x08], true), vec![0x3f, 0x1c, 0xb8]);
+        assert_eq!(
+            encode_path(&[0, 0x0f, 0x01, 0x0c, 0x0b, 0x08], true),
+            vec![0x20, 0x0f, 0x1c, 0xb8]
+        );
+
+        for (nibbles, is_leaf) in [(vec![], false), (vec![1, 2, 3], true), (vec![0, 0xf], false)] {
+            assert_eq!(decode_path(&encode_path(&nibbles, is_leaf)), Some((nibbles, is_leaf)));
+        }
+        assert_eq!(decode_path(&[]), None);
+        assert_eq!(decode_path(&[0x40]), None);
+        assert_eq!(decode_path(&[0x01]), None);
+    }
+
+    #[test]
+    fn prefixes() {
+        assert_eq!(unpack(&[0xab, 0x01]), vec![0x0a, 0x0b, 0x00, 0x01]);
+        assert_eq!(pack(&[0x0a, 0x0b, 0x00, 0x01]), vec![0xab, 0x01]);
+        assert_eq!(pack(&[0x0a, 0x0b, 0x01]), vec![0xab, 0x10]);
+        assert_eq!(common_prefix_length(&[1, 2, 3], &[1, 2, 4]), 2);
+        assert_eq!(common_prefix_length(&[1, 2], &[1, 2, 4]), 2);
+        assert_eq!(common_prefix_length(&[], &[1]), 0);
+    }
+}
@@ -0,0 +1,204 @@
+//! RLP encoding of the trie nodes.
+
+use crate::nibbles::{decode_path, encode_path};
+use reth_primitives::{keccak256, H256};
+use reth_rlp::{Encodable, Header, EMPTY_STRING_CODE};
+
+/// A decoded trie node. The children are given by their references, as returned by [`node_ref`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieNode {
+    /// Node with a child for each nibble. Values are only stored at the leaves of the state
+    /// tries, so the value of branches is ignored.
+    Branch([Option<Vec<u8>>; 16]),
+    /// Node that shares the path to its child, which is a branch.
+    Extension(Vec<u8>, Vec<u8>),
+    /// Node with the rest of the path to the value, and the value.
+    Leaf(Vec<u8>, Vec<u8>),
+}
+
+impl TrieNode {
+    /// Decodes the RLP encoded node, returns `None` if it is invalid.
+    pub fn decode(mut buf: &[u8]) -> Option<Self> {
+        let header = Header::decode(&mut buf).ok()?;
+        if !header.list || buf.len() != header.payload_length {
+            return None
+        }
+
+        let mut items = Vec::with_capacity(17);
+        while !buf.is_empty() {
+            items.push(next_item(&mut buf)?);
+        }
+
+        match items.len() {
+            17 => {
+                let mut children: [Option<Vec<u8>>; 16] = Default::default();
+                for (child, item) in children.iter_mut().zip(items) {
+                    *child = item.child_ref()?;
+                }
+                Some(TrieNode::Branch(children))
+            }
+            2 => {
+                let RlpItem::String(path) = items[0] else { return None };
+                let (path, is_leaf) = decode_path(path)?;
+                if is_leaf {
+                    let RlpItem::String(value) = items[1] else { return None };
+                    Some(TrieNode::Leaf(path, value.to_vec()))
+                } else {
+                    Some(TrieNode::Extension(path, items[1].child_ref()??))
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the RLP encoding of a leaf with the rest of the path to the value.
+pub fn leaf_node(path: &[u8], value: &[u8]) -> Vec<u8> {
+    let path = encode_path(path, true);
+    encode_list(&[&encode_string(&path), &encode_string(value)])
+}
+
+/// Returns the RLP encoding of an extension with the shared path, and the reference to its child
+/// returned by [`node_ref`].
+pub fn extension_node(path: &[u8], child: &[u8]) -> Vec<u8> {
+    let path = encode_path(path, false);
+    encode_list(&[&encode_string(&path), child])
+}
+
+/// Returns the RLP encoding of a branch with the references to its children returned by
+/// [`node_ref`]. Values are only stored at the leaves of the state tries, so the value of the
+/// branch is always empty.
+pub fn branch_node(children: &[Option<Vec<u8>>; 16]) -> Vec<u8> {
+    let empty = [EMPTY_STRING_CODE];
+    let mut items: Vec<&[u8]> =
+        children.iter().map(|child| child.as_deref().unwrap_or(&empty)).collect();
+    items.push(&empty);
+    encode_list(&items)
+}
+
+/// Returns how a node is referenced by its parent: nodes whose encoding is shorter than 32 bytes
+/// are inlined, the longer ones are referenced by their hash.
+pub fn node_ref(node: &[u8]) -> Vec<u8> {
+    if node.len() < 32 {
+        node.to_vec()
+    } else {
+        encode_string(keccak256(node).as_bytes())
+    }
+}
+
+/// Returns the hash of the node the reference returned by [`node_ref`] points to.
+pub fn ref_hash(node_ref: &[u8]) -> H256 {
+    hash_ref(node_ref).unwrap_or_else(|| keccak256(node_ref))
+}
+
+/// Returns the hash of the node if the reference returned by [`node_ref`] is a hash, `None` if
+/// the node is inlined.
+pub fn hash_ref(node_ref: &[u8]) -> Option<H256> {
+    (node_ref.len() == 33 && node_ref[0] == EMPTY_STRING_CODE + 32)
+        .then(|| H256::from_slice(&node_ref[1..]))
+}
+
+/// An item of a RLP list.
+#[derive(Debug, Clone, Copy)]
+enum RlpItem<'a> {
+    /// The payload of a string.
+    String(&'a [u8]),
+    /// The full encoding of a list.
+    List(&'a [u8]),
+}
+
+impl<'a> RlpItem<'a> {
+    /// Returns the reference to a child, `Some(None)` if there is no child and `None` if the item
+    /// is not a reference.
+    fn child_ref(self) -> Option<Option<Vec<u8>>> {
+        match self {
+            RlpItem::String([]) => Some(None),
+            RlpItem::String(hash) if hash.len() == 32 => Some(Some(encode_string(hash))),
+            RlpItem::String(_) => None,
+            RlpItem::List(inline) => Some(Some(inline.to_vec())),
+        }
+    }
+}
+
+/// Decodes the next item of the payload of a RLP list.
+fn next_item<'a>(buf: &mut &'a [u8]) -> Option<RlpItem<'a>> {
+    let start = *buf;
+    let header = Header::decode(buf).ok()?;
+    if buf.len() < header.payload_length {
+        return None
+    }
+    let header_length = start.len() - buf.len();
+    let item = if header.list {
+        RlpItem::List(&start[..header_length + header.payload_length])
+    } else {
+        RlpItem::String(&buf[..header.payload_length])
+    };
+    *buf = &buf[header.payload_length..];
+    Some(item)
+}
+
+fn encode_string(string: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(string.len() + 1);
+    string.encode(&mut out);
+    out
+}
+
+/// Encodes the already RLP encoded items as a list.
+fn encode_list(items: &[&[u8]]) -> Vec<u8> {
+    let payload_length = items.iter().map(|item| item.len()).sum();
+    let mut out = Vec::with_capacity(payload_length + 3);
+    Header { list: true, payload_length }.encode(&mut out);
+    for item in items {
+        out.extend_from_slice(item);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refs() {
+        let short = leaf_node(&[1], &[0xaa]);
+        assert_eq!(short, vec![0xc3, 0x31, 0x81, 0xaa]);
+        assert_eq!(node_ref(&short), short);
+        assert_eq!(ref_hash(&node_ref(&short)), keccak256(&short));
+
+        let long = leaf_node(&[1], &[0xaa; 32]);
+        let long_ref = node_ref(&long);
+        assert_eq!(long_ref.len(), 33);
+        assert_eq!(ref_hash(&long_ref), keccak256(&long));
+    }
+
+    #[test]
+    fn branch_with_empty_children() {
+        let mut children: [Option<Vec<u8>>; 16] = Default::default();
+        children[2] = Some(node_ref(&leaf_node(&[], &[0x01])));
+        let branch = branch_node(&children);
+        // 15 empty children, the empty value and the inlined leaf of 3 bytes
+        assert_eq!(branch, [&[0xd3, 0x80, 0x80, 0xc2, 0x20, 0x01][..], &[0x80; 14]].concat());
+        assert_eq!(TrieNode::decode(&branch), Some(TrieNode::Branch(children)));
+    }
+
+    #[test]
+    fn decode_nodes() {
+        let leaf = leaf_node(&[1, 2, 3], &[0xaa; 40]);
+        assert_eq!(TrieNode::decode(&leaf), Some(TrieNode::Leaf(vec![1, 2, 3], vec![0xaa; 40])));
+        assert_eq!(hash_ref(&node_ref(&leaf)), Some(keccak256(&leaf)));
+
+        let extension = extension_node(&[0xf, 0], &node_ref(&leaf));
+        assert_eq!(
+            TrieNode::decode(&extension),
+            Some(TrieNode::Extension(vec![0xf, 0], node_ref(&leaf)))
+        );
+
+        let short = leaf_node(&[], &[1]);
+        assert_eq!(hash_ref(&node_ref(&short)), None);
+        let extension = extension_node(&[1], &node_ref(&short));
+        assert_eq!(TrieNode::decode(&extension), Some(TrieNode::Extension(vec![1], short)));
+
+        assert_eq!(TrieNode::decode(&[0x80]), None);
+        assert_eq!(TrieNode::decode(&leaf[..leaf.len() - 1]), None);
+    }
+}
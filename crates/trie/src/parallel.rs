@@ -0,0 +1,140 @@
+use crate::{
+    account::EthAccount,
+    hash_builder::HashBuilder,
+    nibbles::unpack,
+    state_root::{flush, OnUpdate},
+    StateRootError, StorageRoot, TrieUpdate,
+};
+use rayon::prelude::*;
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::H256;
+use reth_rlp::Encodable;
+
+/// The default number of accounts whose storage roots are computed at once.
+const DEFAULT_BATCH_SIZE: usize = 1_000;
+
+/// Computes the state root like [`StateRoot`][crate::StateRoot], with the storage roots of the
+/// accounts computed in parallel on the rayon thread pool.
+///
+/// The accounts are read in batches. The storage roots of the accounts of a batch are computed
+/// by the workers of the pool, each reading the storage with its own read transaction, then the
+/// accounts are added to the account trie in order.
+///
+/// As every read goes through a new read transaction, only the committed hashed state is seen.
+#[derive(Debug)]
+pub struct ParallelStateRoot<'db, DB> {
+    /// The database the read transactions are opened on.
+    db: &'db DB,
+    /// The number of accounts whose storage roots are computed at once.
+    batch_size: usize,
+}
+
+impl<'db, DB: Database> ParallelStateRoot<'db, DB> {
+    /// Creates the computation of the state root of the committed state of the database.
+    pub fn new(db: &'db DB) -> Self {
+        Self { db, batch_size: DEFAULT_BATCH_SIZE }
+    }
+
+    /// Sets the number of accounts whose storage roots are computed at once.
+    ///
+    /// The nodes of the storage tries of a batch are held in memory until the accounts are added
+    /// to the account trie.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Returns the state root.
+    pub fn root(&self) -> Result<H256, StateRootError> {
+        self.calculate(None)
+    }
+
+    /// Returns the state root, giving the nodes of the account trie and of the storage tries to
+    /// `on_update` as they are built.
+    pub fn root_with_updates(
+        &self,
+        mut on_update: impl FnMut(TrieUpdate) -> Result<(), StateRootError>,
+    ) -> Result<H256, StateRootError> {
+        self.calculate(Some(&mut on_update))
+    }
+
+    /// Returns the state root, writing the nodes of the tries to the trie tables with the given
+    /// write transaction.
+    ///
+    /// The existing nodes are left in the tables, so they should be cleared first to rebuild the
+    /// tries from scratch.
+    pub fn write_root<'a, TX: DbTxMut<'a>>(&self, tx: &TX) -> Result<H256, StateRootError> {
+        self.root_with_updates(|update| Ok(update.write_to_db(tx)?))
+    }
+
+    fn calculate(&self, mut on_update: OnUpdate<'_>) -> Result<H256, StateRootError> {
+        let retain = on_update.is_some();
+        let mut builder = HashBuilder::default().with_updates(retain);
+
+        let tx = self.db.tx()?;
+        let mut cursor = tx.cursor_read::<tables::HashedAccount>()?;
+        let mut accounts = cursor.walk(None)?;
+        let mut batch = Vec::with_capacity(self.batch_size);
+        loop {
+            batch.clear();
+            for entry in accounts.by_ref().take(self.batch_size) {
+                batch.push(entry?);
+            }
+            if batch.is_empty() {
+                break
+            }
+
+            let storage_roots = batch
+                .par_iter()
+                .map_init(
+                    || self.db.tx(),
+                    |tx, (hashed_address, _)| match tx {
+                        Ok(tx) => storage_root(tx, *hashed_address, retain),
+                        Err(error) => Err(error.clone().into()),
+                    },
+                )
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for ((hashed_address, account), (storage_root, updates)) in
+                batch.iter().zip(storage_roots)
+            {
+                if let Some(on_update) = &mut on_update {
+                    updates.into_iter().try_for_each(&mut **on_update)?;
+                }
+
+                let mut value = Vec::new();
+                EthAccount::from_with_root(*account, storage_root).encode(&mut value);
+                builder.add_leaf(unpack(hashed_address.as_bytes()), &value);
+                flush(&mut builder, &mut on_update)?;
+            }
+        }
+
+        let root = builder.root();
+        flush(&mut builder, &mut on_update)?;
+        Ok(root)
+    }
+}
+
+/// Returns the storage root of the account, with the nodes of its trie if they are retained.
+fn storage_root<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    hashed_address: H256,
+    retain: bool,
+) -> Result<(H256, Vec<TrieUpdate>), StateRootError> {
+    let storage_root = StorageRoot::new(tx, hashed_address);
+    if retain {
+        let mut updates = Vec::new();
+        let root = storage_root.root_with_updates(|update| {
+            updates.push(update);
+            Ok(())
+        })?;
+        Ok((root, updates))
+    } else {
+        Ok((storage_root.root()?, Vec::new()))
+    }
+}
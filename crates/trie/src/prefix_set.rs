@@ -0,0 +1,158 @@
+use crate::nibbles::unpack;
+use reth_db::{
+    cursor::DbCursorRO,
+    models::{AccountBeforeTx, TransitionIdAddress},
+    tables,
+    transaction::DbTx,
+};
+use reth_primitives::{keccak256, Address, StorageEntry, TransitionId, H256};
+use std::{
+    collections::{BTreeSet, HashMap},
+    marker::PhantomData,
+    ops::Range,
+};
+
+/// The set of the changed keys of a trie, as nibbles.
+///
+/// The paths of the trie which are a prefix of none of the keys are unchanged, so their nodes can
+/// be reused as they are.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PrefixSet {
+    keys: BTreeSet<Vec<u8>>,
+}
+
+impl PrefixSet {
+    /// Inserts the changed hashed key.
+    pub fn insert(&mut self, key: H256) {
+        self.keys.insert(unpack(key.as_bytes()));
+    }
+
+    /// Returns whether the path is a prefix of one of the keys.
+    pub fn contains(&self, prefix: &[u8]) -> bool {
+        self.keys.range(prefix.to_vec()..).next().map_or(false, |key| key.starts_with(prefix))
+    }
+
+    /// Returns the number of keys of the set.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns whether the set has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl FromIterator<H256> for PrefixSet {
+    fn from_iter<T: IntoIterator<Item = H256>>(iter: T) -> Self {
+        let mut set = Self::default();
+        iter.into_iter().for_each(|key| set.insert(key));
+        set
+    }
+}
+
+/// The changed keys of the account trie, and of the storage tries by the hashed address of their
+/// account.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PrefixSets {
+    /// The hashed addresses of the changed accounts.
+    pub accounts: PrefixSet,
+    /// The hashed keys of the changed storage slots, by the hashed address of their account.
+    pub storages: HashMap<H256, PrefixSet>,
+}
+
+/// Loads the [`PrefixSets`] of the changes of a range of transitions from the changesets.
+#[derive(Debug)]
+pub struct PrefixSetLoader<'a, 'b, TX: DbTx<'a>> {
+    /// The database transaction.
+    tx: &'b TX,
+    /// Phantom data over the lifetime of the transaction.
+    phantom: PhantomData<&'a TX>,
+}
+
+impl<'a, 'b, TX: DbTx<'a>> PrefixSetLoader<'a, 'b, TX> {
+    /// Creates a loader reading the changesets of the transaction.
+    pub fn new(tx: &'b TX) -> Self {
+        Self { tx, phantom: PhantomData }
+    }
+
+    /// Returns the hashed keys of the accounts and the storage slots changed by the transitions.
+    pub fn load(&self, range: Range<TransitionId>) -> Result<PrefixSets, reth_db::Error> {
+        let mut sets = PrefixSets::default();
+
+        let mut account_cursor = self.tx.cursor_read::<tables::AccountChangeSet>()?;
+        for entry in account_cursor.walk_range(range.clone())? {
+            let (_, AccountBeforeTx { address, .. }) = entry?;
+            sets.accounts.insert(keccak256(address));
+        }
+
+        let mut storage_cursor = self.tx.cursor_dup_read::<tables::StorageChangeSet>()?;
+        let start = TransitionIdAddress((range.start, Address::zero()));
+        let end = TransitionIdAddress((range.end, Address::zero()));
+        for entry in storage_cursor.walk_range(start..end)? {
+            let (TransitionIdAddress((_, address)), StorageEntry { key, .. }) = entry?;
+            let hashed_address = keccak256(address);
+            sets.accounts.insert(hashed_address);
+            sets.storages.entry(hashed_address).or_default().insert(keccak256(key));
+        }
+
+        Ok(sets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{database::Database, mdbx::test_utils::create_test_rw_db, transaction::DbTxMut};
+    use reth_primitives::{hex_literal::hex, U256};
+
+    #[test]
+    fn contains_prefixes() {
+        let set = PrefixSet::from_iter([H256::repeat_byte(0x12), H256::repeat_byte(0x34)]);
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&[]));
+        assert!(set.contains(&[1]));
+        assert!(set.contains(&[1, 2, 1]));
+        assert!(set.contains(&[3, 4]));
+        assert!(!set.contains(&[2]));
+        assert!(!set.contains(&[1, 3]));
+        assert!(!PrefixSet::default().contains(&[]));
+    }
+
+    #[test]
+    fn load_from_changesets() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+
+        let address = Address::from(hex!("9fe4abd71ad081f091bd06dd1c16f7e92927561e"));
+        let other = Address::from(hex!("f8a6edaad4a332e6e550d0915a7fd5300b0b12d1"));
+        tx.put::<tables::AccountChangeSet>(31, AccountBeforeTx { address: other, info: None })
+            .unwrap();
+        tx.put::<tables::AccountChangeSet>(32, AccountBeforeTx { address, info: None }).unwrap();
+        for slot in [H256::zero(), H256::from_low_u64_be(2)] {
+            tx.put::<tables::StorageChangeSet>(
+                (32, address).into(),
+                StorageEntry { key: slot, value: U256::ZERO },
+            )
+            .unwrap();
+        }
+        tx.put::<tables::StorageChangeSet>(
+            (33, other).into(),
+            StorageEntry { key: H256::zero(), value: U256::ZERO },
+        )
+        .unwrap();
+
+        let sets = PrefixSetLoader::new(&tx).load(32..33).unwrap();
+        assert_eq!(sets.accounts, PrefixSet::from_iter([keccak256(address)]));
+        assert_eq!(
+            sets.storages,
+            HashMap::from([(
+                keccak256(address),
+                PrefixSet::from_iter([
+                    keccak256(H256::zero()),
+                    keccak256(H256::from_low_u64_be(2))
+                ])
+            )])
+        );
+    }
+}
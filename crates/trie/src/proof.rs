@@ -0,0 +1,424 @@
+//! Merkle proofs of the keys of the tries.
+
+use crate::{
+    nibbles::unpack,
+    nodes::{hash_ref, TrieNode},
+    EthAccount, ProofError, StateRootError,
+};
+use reth_db::{cursor::DbDupCursorRO, tables, transaction::DbTx};
+use reth_primitives::{keccak256, proofs::EMPTY_ROOT, Address, Bytes, H256, U256};
+use reth_rlp::Decodable;
+use std::{collections::HashMap, convert::Infallible, marker::PhantomData};
+
+/// A proof of the presence or absence of a key in a trie.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrieProof {
+    /// The RLP encoded nodes on the path from the root to the key, starting with the root.
+    pub nodes: Vec<Bytes>,
+    /// The RLP encoded value of the key, `None` if the key is not in the trie.
+    pub value: Option<Bytes>,
+}
+
+/// A proof of the presence or absence of several keys in a trie, with the nodes their paths
+/// share only included once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiProof {
+    /// The RLP encoded nodes on the paths from the root to the keys, in the order they are first
+    /// visited, starting with the root.
+    pub nodes: Vec<Bytes>,
+    /// The RLP encoded values of the keys, in the order of the keys.
+    pub values: Vec<Option<Bytes>>,
+}
+
+/// The proof of an account in the state trie, with the proofs of slots of its storage trie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountProof {
+    /// The address of the account.
+    pub address: Address,
+    /// The account, `None` if it does not exist.
+    pub account: Option<EthAccount>,
+    /// The proof of the hashed address in the state trie.
+    pub proof: TrieProof,
+    /// The proofs of the requested slots, in the order they were requested.
+    pub storage_proofs: Vec<StorageProof>,
+}
+
+impl AccountProof {
+    /// Returns the root of the storage trie of the account, the empty root if it does not exist.
+    pub fn storage_root(&self) -> H256 {
+        self.account.map_or(EMPTY_ROOT, |account| account.storage_root)
+    }
+}
+
+/// The proof of a slot in the storage trie of an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageProof {
+    /// The slot.
+    pub key: H256,
+    /// The value of the slot, zero if it is not set.
+    pub value: U256,
+    /// The proof of the hashed slot in the storage trie.
+    pub proof: TrieProof,
+}
+
+/// Looks up `key` in the trie with the given root, collecting the nodes on the path to it.
+///
+/// `get_node` returns the node with the given hash.
+pub fn trie_proof<E, F>(root: H256, key: H256, mut get_node: F) -> Result<TrieProof, ProofError<E>>
+where
+    F: FnMut(H256) -> Result<Option<Bytes>, E>,
+{
+    let mut proof = TrieProof::default();
+    if root == EMPTY_ROOT {
+        return Ok(proof)
+    }
+
+    let key = unpack(key.as_bytes());
+    let mut hash = root;
+    let mut node = resolve(&mut get_node, root)?;
+    proof.nodes.push(node.clone());
+    let mut offset = 0;
+
+    loop {
+        let child = match TrieNode::decode(&node).ok_or(ProofError::InvalidNode { hash })? {
+            TrieNode::Branch(mut children) => {
+                let Some(nibble) = key.get(offset) else { return Ok(proof) };
+                offset += 1;
+                children[*nibble as usize].take()
+            }
+            TrieNode::Extension(path, child) => {
+                if !key[offset..].starts_with(&path) {
+                    return Ok(proof)
+                }
+                offset += path.len();
+                Some(child)
+            }
+            TrieNode::Leaf(path, value) => {
+                if key[offset..] == path[..] {
+                    proof.value = Some(Bytes::from(value));
+                }
+                return Ok(proof)
+            }
+        };
+
+        let Some(child) = child else { return Ok(proof) };
+        node = match hash_ref(&child) {
+            Some(child) => {
+                hash = child;
+                let node = resolve(&mut get_node, child)?;
+                proof.nodes.push(node.clone());
+                node
+            }
+            None => Bytes::from(child),
+        };
+    }
+}
+
+/// Looks up the keys in the trie with the given root, collecting the nodes on their paths.
+///
+/// `get_node` returns the node with the given hash, it is called once per node.
+pub fn trie_multiproof<E, F>(
+    root: H256,
+    keys: &[H256],
+    mut get_node: F,
+) -> Result<MultiProof, ProofError<E>>
+where
+    F: FnMut(H256) -> Result<Option<Bytes>, E>,
+{
+    let mut multiproof = MultiProof::default();
+    let mut visited = HashMap::new();
+    for key in keys {
+        let proof = trie_proof(root, *key, |hash| {
+            if let Some(node) = visited.get(&hash) {
+                return Ok(Some(node.clone()))
+            }
+            let node = get_node(hash)?;
+            if let Some(node) = &node {
+                visited.insert(hash, node.clone());
+                multiproof.nodes.push(node.clone());
+            }
+            Ok(node)
+        })?;
+        multiproof.values.push(proof.value);
+    }
+    Ok(multiproof)
+}
+
+/// Returns the node at the nibble `path` of the trie with the given root, if any.
+///
+/// Paths that end inside of an extension or leaf node don't point to a node.
+pub fn trie_node_at_path<E, F>(
+    root: H256,
+    path: &[u8],
+    mut get_node: F,
+) -> Result<Option<Bytes>, ProofError<E>>
+where
+    F: FnMut(H256) -> Result<Option<Bytes>, E>,
+{
+    if root == EMPTY_ROOT {
+        return Ok(None)
+    }
+
+    let mut hash = root;
+    let mut node = resolve(&mut get_node, root)?;
+    let mut offset = 0;
+
+    while offset < path.len() {
+        let child = match TrieNode::decode(&node).ok_or(ProofError::InvalidNode { hash })? {
+            TrieNode::Branch(mut children) => {
+                offset += 1;
+                children[path[offset - 1] as usize].take()
+            }
+            TrieNode::Extension(extension, child) if path[offset..].starts_with(&extension) => {
+                offset += extension.len();
+                Some(child)
+            }
+            TrieNode::Extension(..) | TrieNode::Leaf(..) => return Ok(None),
+        };
+
+        let Some(child) = child else { return Ok(None) };
+        node = match hash_ref(&child) {
+            Some(child) => {
+                hash = child;
+                resolve(&mut get_node, child)?
+            }
+            None => Bytes::from(child),
+        };
+    }
+
+    Ok(Some(node))
+}
+
+/// Returns the proof of the account in the state trie with the given root, and the proofs of the
+/// given slots in its storage trie.
+///
+/// `account_node` returns the node of the account trie with the given hash, `storage_node` the
+/// node of the storage trie of the account.
+pub fn account_proof<E, A, S>(
+    state_root: H256,
+    address: Address,
+    slots: &[H256],
+    account_node: A,
+    mut storage_node: S,
+) -> Result<AccountProof, ProofError<E>>
+where
+    A: FnMut(H256) -> Result<Option<Bytes>, E>,
+    S: FnMut(H256) -> Result<Option<Bytes>, E>,
+{
+    let proof = trie_proof(state_root, keccak256(address), account_node)?;
+    let account = decode_value::<EthAccount, E>(&proof)?;
+    let storage_root = account.map_or(EMPTY_ROOT, |account| account.storage_root);
+
+    let storage_proofs = slots
+        .iter()
+        .map(|slot| {
+            let proof = trie_proof(storage_root, keccak256(slot), &mut storage_node)?;
+            let value = decode_value::<U256, E>(&proof)?.unwrap_or_default();
+            Ok(StorageProof { key: *slot, value, proof })
+        })
+        .collect::<Result<_, ProofError<E>>>()?;
+
+    Ok(AccountProof { address, account, proof, storage_proofs })
+}
+
+/// Verifies the proof of the key in the trie with the given root, returns the value of the key,
+/// `None` if the proof shows it is not in the trie.
+///
+/// The nodes can be the ones of a [`TrieProof`] or of a [`MultiProof`] including the key. The
+/// verification fails with [`ProofError::MissingNode`] if a node on the path of the key is not
+/// part of the proof.
+pub fn verify_proof(
+    root: H256,
+    key: H256,
+    nodes: &[Bytes],
+) -> Result<Option<Bytes>, ProofError<Infallible>> {
+    let nodes: HashMap<_, _> = nodes.iter().map(|node| (keccak256(node), node)).collect();
+    let proof =
+        trie_proof::<Infallible, _>(root, key, |hash| Ok(nodes.get(&hash).copied().cloned()))?;
+    Ok(proof.value)
+}
+
+/// Generates the proofs of the accounts and of their storage slots from the trie tables.
+#[derive(Debug)]
+pub struct Proof<'a, 'b, TX: DbTx<'a>> {
+    /// The database transaction.
+    tx: &'b TX,
+    /// Phantom data over the lifetime of the transaction.
+    phantom: PhantomData<&'a TX>,
+}
+
+impl<'a, 'b, TX: DbTx<'a>> Proof<'a, 'b, TX> {
+    /// Creates the generator of the proofs of the tries stored in the transaction.
+    pub fn new(tx: &'b TX) -> Self {
+        Self { tx, phantom: PhantomData }
+    }
+
+    /// Returns the proof of the account in the state trie with the given root, with the proofs of
+    /// the given slots of its storage.
+    pub fn account_proof(
+        &self,
+        state_root: H256,
+        address: Address,
+        slots: &[H256],
+    ) -> Result<AccountProof, StateRootError> {
+        let hashed_address = keccak256(address);
+        let proof = account_proof(
+            state_root,
+            address,
+            slots,
+            |hash| self.tx.get::<tables::AccountsTrie>(hash).map(|node| node.map(Bytes::from)),
+            |hash| self.storage_node(hashed_address, hash),
+        )?;
+        Ok(proof)
+    }
+
+    fn storage_node(
+        &self,
+        hashed_address: H256,
+        hash: H256,
+    ) -> Result<Option<Bytes>, reth_db::Error> {
+        let mut cursor = self.tx.cursor_dup_read::<tables::StoragesTrie>()?;
+        let entry = cursor.seek_by_key_subkey(hashed_address, hash)?;
+        Ok(entry.filter(|entry| entry.hash == hash).map(|entry| Bytes::from(entry.node)))
+    }
+}
+
+/// Returns the node with the given hash, or an error if it is missing.
+fn resolve<E, F>(get_node: &mut F, hash: H256) -> Result<Bytes, ProofError<E>>
+where
+    F: FnMut(H256) -> Result<Option<Bytes>, E>,
+{
+    get_node(hash).map_err(ProofError::Source)?.ok_or(ProofError::MissingNode { hash })
+}
+
+/// Decodes the value the proof leads to, if any.
+fn decode_value<T: Decodable, E>(proof: &TrieProof) -> Result<Option<T>, ProofError<E>> {
+    let Some(value) = &proof.value else { return Ok(None) };
+    let value = T::decode(&mut value.as_ref()).map_err(|_| ProofError::InvalidNode {
+        hash: proof.nodes.last().map(keccak256).unwrap_or_default(),
+    })?;
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HashBuilder, StateRoot};
+    use assert_matches::assert_matches;
+    use reth_db::{database::Database, mdbx::test_utils::create_test_rw_db, transaction::DbTxMut};
+    use reth_primitives::{Account, StorageEntry};
+    use reth_rlp::encode_fixed_size;
+
+    /// Builds the trie of the keys, returns its root and its nodes by hash.
+    fn build(keys: &[H256]) -> (H256, HashMap<H256, Bytes>) {
+        let mut keys = keys.to_vec();
+        keys.sort();
+        let mut builder = HashBuilder::default().with_updates(true);
+        for key in keys {
+            builder.add_leaf(unpack(key.as_bytes()), key.as_bytes());
+        }
+        let root = builder.root();
+        let nodes = builder.take_updates().into_iter().map(|(hash, node)| (hash, node.into()));
+        (root, nodes.collect())
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        let keys = (0..50).map(|i| keccak256(H256::from_low_u64_be(i))).collect::<Vec<_>>();
+        let (root, nodes) = build(&keys);
+        let get_node = |hash| Ok::<_, Infallible>(nodes.get(&hash).cloned());
+
+        for key in &keys {
+            let proof = trie_proof(root, *key, get_node).unwrap();
+            assert_eq!(proof.nodes[0], nodes[&root]);
+            assert_eq!(proof.value, Some(Bytes::from(key.as_bytes().to_vec())));
+            assert_eq!(verify_proof(root, *key, &proof.nodes), Ok(proof.value));
+        }
+
+        let absent = keccak256(H256::from_low_u64_be(100));
+        let proof = trie_proof(root, absent, get_node).unwrap();
+        assert_eq!(proof.value, None);
+        assert_eq!(verify_proof(root, absent, &proof.nodes), Ok(None));
+
+        // the proof of a key doesn't prove the other ones
+        let proof = trie_proof(root, keys[0], get_node).unwrap();
+        assert_matches!(
+            verify_proof(root, keys[1], &proof.nodes[..1]),
+            Err(ProofError::MissingNode { .. })
+        );
+        assert_eq!(
+            verify_proof(H256::zero(), keys[0], &proof.nodes),
+            Err(ProofError::MissingNode { hash: H256::zero() })
+        );
+        assert_eq!(verify_proof(EMPTY_ROOT, keys[0], &[]), Ok(None));
+    }
+
+    #[test]
+    fn multiproof() {
+        let keys = (0..50).map(|i| keccak256(H256::from_low_u64_be(i))).collect::<Vec<_>>();
+        let (root, nodes) = build(&keys);
+        let mut reads = 0;
+        let targets = [keys[3], keys[7], keccak256(H256::from_low_u64_be(100)), keys[3]];
+        let multiproof = trie_multiproof(root, &targets, |hash| {
+            reads += 1;
+            Ok::<_, Infallible>(nodes.get(&hash).cloned())
+        })
+        .unwrap();
+
+        assert_eq!(reads, multiproof.nodes.len());
+        assert_eq!(multiproof.nodes[0], nodes[&root]);
+        for (key, value) in targets.iter().zip(&multiproof.values) {
+            assert_eq!(verify_proof(root, *key, &multiproof.nodes).as_ref(), Ok(value));
+        }
+        assert_eq!(multiproof.values[2], None);
+    }
+
+    #[test]
+    fn account_and_storage_proofs() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        let address = Address::from_low_u64_be(1);
+        let account = Account { nonce: 1, balance: U256::from(2), bytecode_hash: None };
+        let slots = [H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        tx.put::<tables::HashedAccount>(keccak256(address), account).unwrap();
+        tx.put::<tables::HashedAccount>(keccak256(Address::from_low_u64_be(2)), account).unwrap();
+        for slot in slots {
+            let entry = StorageEntry { key: keccak256(slot), value: U256::from(7) };
+            tx.put::<tables::HashedStorage>(keccak256(address), entry).unwrap();
+        }
+        let state_root = StateRoot::new(&tx).write_root().unwrap();
+
+        let absent_slot = H256::from_low_u64_be(3);
+        let proof = Proof::new(&tx)
+            .account_proof(state_root, address, &[slots[0], absent_slot, slots[1]])
+            .unwrap();
+        let expected = EthAccount::from_with_root(account, proof.storage_root());
+        assert_eq!(proof.account, Some(expected));
+        assert_eq!(
+            verify_proof(state_root, keccak256(address), &proof.proof.nodes),
+            Ok(proof.proof.value)
+        );
+
+        let values = proof.storage_proofs.iter().map(|proof| proof.value).collect::<Vec<_>>();
+        assert_eq!(values, vec![U256::from(7), U256::ZERO, U256::from(7)]);
+        for storage_proof in &proof.storage_proofs {
+            let value = verify_proof(
+                proof.storage_root(),
+                keccak256(storage_proof.key),
+                &storage_proof.proof.nodes,
+            );
+            assert_eq!(value, Ok(storage_proof.proof.value.clone()));
+        }
+        assert_eq!(
+            proof.storage_proofs[0].proof.value,
+            Some(Bytes::from(encode_fixed_size(&U256::from(7)).to_vec()))
+        );
+
+        let absent = Proof::new(&tx)
+            .account_proof(state_root, Address::from_low_u64_be(3), &[slots[0]])
+            .unwrap();
+        assert_eq!(absent.account, None);
+        assert_eq!(absent.storage_root(), EMPTY_ROOT);
+        assert_eq!(absent.storage_proofs[0].proof, TrieProof::default());
+    }
+}
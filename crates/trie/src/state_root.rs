@@ -0,0 +1,612 @@
+use crate::{
+    account::EthAccount,
+    hash_builder::HashBuilder,
+    nibbles::unpack,
+    walker::{update_trie, TrieSource},
+    PrefixSet, PrefixSets, StateRootError,
+};
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{proofs::EMPTY_ROOT, StorageEntry, StorageTrieEntry, H256};
+use reth_rlp::{encode_fixed_size, Decodable, Encodable};
+use std::{collections::HashMap, marker::PhantomData};
+
+/// A node of the state tries referenced by its hash, to be persisted in the trie tables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieUpdate {
+    /// A node of the account trie.
+    Account {
+        /// The hash of the node.
+        hash: H256,
+        /// The encoded node.
+        node: Vec<u8>,
+    },
+    /// A node of the storage trie of an account.
+    Storage {
+        /// The hashed address of the account.
+        hashed_address: H256,
+        /// The hash of the node.
+        hash: H256,
+        /// The encoded node.
+        node: Vec<u8>,
+    },
+}
+
+impl TrieUpdate {
+    /// Writes the node to the table of its trie.
+    pub fn write_to_db<'a, TX: DbTxMut<'a>>(self, tx: &TX) -> Result<(), reth_db::Error> {
+        match self {
+            TrieUpdate::Account { hash, node } => tx.put::<tables::AccountsTrie>(hash, node),
+            TrieUpdate::Storage { hashed_address, hash, node } => {
+                tx.put::<tables::StoragesTrie>(hashed_address, StorageTrieEntry { hash, node })
+            }
+        }
+    }
+}
+
+/// The callback the nodes of the tries are given to, if they are retained.
+pub(crate) type OnUpdate<'c> = Option<&'c mut dyn FnMut(TrieUpdate) -> Result<(), StateRootError>>;
+
+/// Computes the root of the storage trie of an account from the
+/// [`HashedStorage`][tables::HashedStorage] table.
+#[derive(Debug)]
+pub struct StorageRoot<'a, 'b, TX: DbTx<'a>> {
+    /// The database transaction.
+    tx: &'b TX,
+    /// The hashed address of the account.
+    hashed_address: H256,
+    /// The changed storage slots, for [`StorageRoot::update_root`].
+    prefix_set: PrefixSet,
+    /// Phantom data over the lifetime of the transaction.
+    phantom: PhantomData<&'a TX>,
+}
+
+impl<'a, 'b, TX: DbTx<'a>> StorageRoot<'a, 'b, TX> {
+    /// Creates the computation of the storage root of the account with the given hashed address.
+    pub fn new(tx: &'b TX, hashed_address: H256) -> Self {
+        Self { tx, hashed_address, prefix_set: PrefixSet::default(), phantom: PhantomData }
+    }
+
+    /// Sets the hashed keys of the storage slots changed since the previous root.
+    pub fn with_prefix_set(mut self, prefix_set: PrefixSet) -> Self {
+        self.prefix_set = prefix_set;
+        self
+    }
+
+    /// Returns the storage root.
+    pub fn root(&self) -> Result<H256, StateRootError> {
+        self.calculate(None)
+    }
+
+    /// Returns the storage root, giving the nodes of the trie to `on_update` as they are built.
+    pub fn root_with_updates(
+        &self,
+        mut on_update: impl FnMut(TrieUpdate) -> Result<(), StateRootError>,
+    ) -> Result<H256, StateRootError> {
+        self.calculate(Some(&mut on_update))
+    }
+
+    fn calculate(&self, mut on_update: OnUpdate<'_>) -> Result<H256, StateRootError> {
+        let mut builder = HashBuilder::default().with_updates(on_update.is_some());
+        let mut cursor = self.tx.cursor_dup_read::<tables::HashedStorage>()?;
+        for entry in cursor.walk_prefix(self.hashed_address)? {
+            let (_, StorageEntry { key, value }) = entry?;
+            builder.add_leaf(unpack(key.as_bytes()), &encode_fixed_size(&value));
+            self.flush(&mut builder, &mut on_update)?;
+        }
+
+        let root = builder.root();
+        self.flush(&mut builder, &mut on_update)?;
+        Ok(root)
+    }
+
+    fn flush(
+        &self,
+        builder: &mut HashBuilder,
+        on_update: &mut OnUpdate<'_>,
+    ) -> Result<(), StateRootError> {
+        if let Some(on_update) = on_update {
+            for (hash, node) in builder.take_updates() {
+                on_update(TrieUpdate::Storage { hashed_address: self.hashed_address, hash, node })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'b, TX: DbTx<'a> + DbTxMut<'a>> StorageRoot<'a, 'b, TX> {
+    /// Updates the storage trie of the given previous root in the trie tables with the changed
+    /// slots of the prefix set, returns the new storage root.
+    pub fn update_root(&self, previous_root: H256) -> Result<H256, StateRootError> {
+        update_storage_trie(self.tx, self.hashed_address, &self.prefix_set, previous_root)
+    }
+}
+
+/// Computes the state root from the [`HashedAccount`][tables::HashedAccount] and
+/// [`HashedStorage`][tables::HashedStorage] tables.
+///
+/// The accounts and their storage are read in the order of their hashed keys, so the tries are
+/// built leaf by leaf and only the branches on the path of the last leaf are kept in memory.
+///
+/// Once the tries are persisted, they can be updated with the keys changed since, given by
+/// [`PrefixSets`]: only the paths of the tries leading to the changed keys are walked and
+/// rebuilt, so the cost of an update depends on the number of changes instead of the size of the
+/// state.
+#[derive(Debug)]
+pub struct StateRoot<'a, 'b, TX: DbTx<'a>> {
+    /// The database transaction.
+    tx: &'b TX,
+    /// The changed accounts and storage slots, for [`StateRoot::update_root`].
+    prefix_sets: PrefixSets,
+    /// Phantom data over the lifetime of the transaction.
+    phantom: PhantomData<&'a TX>,
+}
+
+impl<'a, 'b, TX: DbTx<'a>> StateRoot<'a, 'b, TX> {
+    /// Creates the computation of the state root.
+    pub fn new(tx: &'b TX) -> Self {
+        Self { tx, prefix_sets: PrefixSets::default(), phantom: PhantomData }
+    }
+
+    /// Sets the hashed keys of the accounts and storage slots changed since the previous root.
+    pub fn with_prefix_sets(mut self, mut prefix_sets: PrefixSets) -> Self {
+        // the storage root is part of the account
+        for hashed_address in prefix_sets.storages.keys() {
+            prefix_sets.accounts.insert(*hashed_address);
+        }
+        self.prefix_sets = prefix_sets;
+        self
+    }
+
+    /// Returns the state root.
+    pub fn root(&self) -> Result<H256, StateRootError> {
+        self.calculate(None)
+    }
+
+    /// Returns the state root, giving the nodes of the account trie and of the storage tries to
+    /// `on_update` as they are built.
+    pub fn root_with_updates(
+        &self,
+        mut on_update: impl FnMut(TrieUpdate) -> Result<(), StateRootError>,
+    ) -> Result<H256, StateRootError> {
+        self.calculate(Some(&mut on_update))
+    }
+
+    fn calculate(&self, mut on_update: OnUpdate<'_>) -> Result<H256, StateRootError> {
+        let mut builder = HashBuilder::default().with_updates(on_update.is_some());
+        let mut cursor = self.tx.cursor_read::<tables::HashedAccount>()?;
+        for entry in cursor.walk(None)? {
+            let (hashed_address, account) = entry?;
+            let storage_root = match &mut on_update {
+                Some(on_update) => {
+                    StorageRoot::new(self.tx, hashed_address).root_with_updates(&mut **on_update)?
+                }
+                None => StorageRoot::new(self.tx, hashed_address).root()?,
+            };
+
+            let mut value = Vec::new();
+            EthAccount::from_with_root(account, storage_root).encode(&mut value);
+            builder.add_leaf(unpack(hashed_address.as_bytes()), &value);
+            flush(&mut builder, &mut on_update)?;
+        }
+
+        let root = builder.root();
+        flush(&mut builder, &mut on_update)?;
+        Ok(root)
+    }
+}
+
+impl<'a, 'b, TX: DbTx<'a> + DbTxMut<'a>> StateRoot<'a, 'b, TX> {
+    /// Returns the state root, writing the nodes of the tries to the trie tables.
+    ///
+    /// The existing nodes are left in the tables, so they should be cleared first to rebuild the
+    /// tries from scratch.
+    pub fn write_root(&self) -> Result<H256, StateRootError> {
+        self.root_with_updates(|update| Ok(update.write_to_db(self.tx)?))
+    }
+
+    /// Updates the tries of the given previous state root in the trie tables with the changed
+    /// keys of the prefix sets, returns the new state root.
+    ///
+    /// The nodes which are not part of the updated tries anymore are removed, along with the
+    /// storage tries of the removed accounts.
+    pub fn update_root(&self, previous_root: H256) -> Result<H256, StateRootError> {
+        let mut trie =
+            AccountTrie { tx: self.tx, storages: &self.prefix_sets.storages, phantom: PhantomData };
+        update_trie(&mut trie, &self.prefix_sets.accounts, previous_root)
+    }
+}
+
+/// The account trie in the trie tables, whose storage roots are updated with the changed slots.
+struct AccountTrie<'s, 'a, 'b, TX: DbTx<'a>> {
+    tx: &'b TX,
+    storages: &'s HashMap<H256, PrefixSet>,
+    phantom: PhantomData<&'a TX>,
+}
+
+impl<'s, 'a, 'b, TX: DbTx<'a> + DbTxMut<'a>> TrieSource for AccountTrie<'s, 'a, 'b, TX> {
+    fn node(&mut self, hash: H256) -> Result<Option<Vec<u8>>, StateRootError> {
+        Ok(self.tx.get::<tables::AccountsTrie>(hash)?)
+    }
+
+    fn add_leaves(
+        &mut self,
+        builder: &mut HashBuilder,
+        start: H256,
+        end: Option<H256>,
+        previous: Option<(H256, Vec<u8>)>,
+    ) -> Result<(), StateRootError> {
+        let mut previous_exists = false;
+        let mut cursor = self.tx.cursor_read::<tables::HashedAccount>()?;
+        let mut entry = cursor.seek(start)?;
+        while let Some((hashed_address, account)) = entry {
+            if end.map_or(false, |end| hashed_address >= end) {
+                break
+            }
+
+            let previous_root = match &previous {
+                Some((key, value)) if *key == hashed_address => {
+                    previous_exists = true;
+                    EthAccount::decode(&mut value.as_slice())?.storage_root
+                }
+                _ => EMPTY_ROOT,
+            };
+            let storage_root = match self.storages.get(&hashed_address) {
+                Some(prefix_set) => {
+                    update_storage_trie(self.tx, hashed_address, prefix_set, previous_root)?
+                }
+                None => previous_root,
+            };
+
+            let mut value = Vec::new();
+            EthAccount::from_with_root(account, storage_root).encode(&mut value);
+            builder.add_leaf(unpack(hashed_address.as_bytes()), &value);
+            entry = cursor.next()?;
+        }
+
+        // the storage trie of a removed account is removed with it
+        if let Some((hashed_address, _)) = previous.filter(|_| !previous_exists) {
+            self.tx.delete::<tables::StoragesTrie>(hashed_address, None)?;
+        }
+        Ok(())
+    }
+
+    fn insert_node(&mut self, hash: H256, node: Vec<u8>) -> Result<(), StateRootError> {
+        Ok(self.tx.put::<tables::AccountsTrie>(hash, node)?)
+    }
+
+    fn remove_node(&mut self, hash: H256) -> Result<(), StateRootError> {
+        self.tx.delete::<tables::AccountsTrie>(hash, None)?;
+        Ok(())
+    }
+}
+
+/// The storage trie of an account in the trie tables.
+struct StorageTrie<'a, 'b, TX: DbTx<'a>> {
+    tx: &'b TX,
+    hashed_address: H256,
+    phantom: PhantomData<&'a TX>,
+}
+
+impl<'a, 'b, TX: DbTx<'a> + DbTxMut<'a>> TrieSource for StorageTrie<'a, 'b, TX> {
+    fn node(&mut self, hash: H256) -> Result<Option<Vec<u8>>, StateRootError> {
+        let mut cursor = self.tx.cursor_dup_read::<tables::StoragesTrie>()?;
+        let entry = cursor.seek_by_key_subkey(self.hashed_address, hash)?;
+        Ok(entry.filter(|entry| entry.hash == hash).map(|entry| entry.node))
+    }
+
+    fn add_leaves(
+        &mut self,
+        builder: &mut HashBuilder,
+        start: H256,
+        end: Option<H256>,
+        _previous: Option<(H256, Vec<u8>)>,
+    ) -> Result<(), StateRootError> {
+        let mut cursor = self.tx.cursor_dup_read::<tables::HashedStorage>()?;
+        let mut entry = cursor.seek_by_key_subkey(self.hashed_address, start)?;
+        while let Some(StorageEntry { key, value }) = entry {
+            if end.map_or(false, |end| key >= end) {
+                break
+            }
+            builder.add_leaf(unpack(key.as_bytes()), &encode_fixed_size(&value));
+            entry = cursor.next_dup_val()?;
+        }
+        Ok(())
+    }
+
+    fn insert_node(&mut self, hash: H256, node: Vec<u8>) -> Result<(), StateRootError> {
+        let entry = StorageTrieEntry { hash, node };
+        Ok(self.tx.put::<tables::StoragesTrie>(self.hashed_address, entry)?)
+    }
+
+    fn remove_node(&mut self, hash: H256) -> Result<(), StateRootError> {
+        let mut cursor = self.tx.cursor_dup_write::<tables::StoragesTrie>()?;
+        if let Some(entry) = cursor.seek_by_key_subkey(self.hashed_address, hash)? {
+            if entry.hash == hash {
+                cursor.delete_current()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Updates the storage trie of the account, returns its new root.
+fn update_storage_trie<'a, TX: DbTx<'a> + DbTxMut<'a>>(
+    tx: &TX,
+    hashed_address: H256,
+    prefix_set: &PrefixSet,
+    previous_root: H256,
+) -> Result<H256, StateRootError> {
+    let mut trie = StorageTrie { tx, hashed_address, phantom: PhantomData };
+    update_trie(&mut trie, prefix_set, previous_root)
+}
+
+/// Gives the retained nodes of the account trie to `on_update`.
+pub(crate) fn flush(
+    builder: &mut HashBuilder,
+    on_update: &mut OnUpdate<'_>,
+) -> Result<(), StateRootError> {
+    if let Some(on_update) = on_update {
+        for (hash, node) in builder.take_updates() {
+            on_update(TrieUpdate::Account { hash, node })?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParallelStateRoot;
+    use assert_matches::assert_matches;
+    use proptest::{collection::btree_map, prelude::*};
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_rw_db, Env, WriteMap},
+    };
+    use reth_primitives::{
+        hex_literal::hex, keccak256, proofs::KeccakHasher, Account, Address, U256,
+    };
+    use std::collections::{BTreeMap, BTreeSet};
+    use triehash::sec_trie_root;
+
+    type State = BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>;
+
+    fn storage_root(storage: &BTreeSet<StorageEntry>) -> H256 {
+        let encoded = storage.iter().map(|entry| (entry.key, encode_fixed_size(&entry.value)));
+        sec_trie_root::<KeccakHasher, _, _, _>(encoded)
+    }
+
+    fn encode_account(account: Account, storage_root: H256) -> Vec<u8> {
+        let mut out = Vec::new();
+        EthAccount::from_with_root(account, storage_root).encode(&mut out);
+        out
+    }
+
+    /// Replaces the hashed state with the accounts, returns the expected state root.
+    fn insert_state(db: &Env<WriteMap>, accounts: &State) -> H256 {
+        let tx = db.tx_mut().unwrap();
+        tx.clear::<tables::HashedAccount>().unwrap();
+        tx.clear::<tables::HashedStorage>().unwrap();
+        let mut encoded = Vec::new();
+        for (address, (account, storage)) in accounts {
+            let hashed_address = keccak256(address);
+            tx.put::<tables::HashedAccount>(hashed_address, *account).unwrap();
+            for entry in storage {
+                let hashed = StorageEntry { key: keccak256(entry.key), value: entry.value };
+                tx.put::<tables::HashedStorage>(hashed_address, hashed).unwrap();
+            }
+            encoded.push((address, encode_account(*account, storage_root(storage))));
+        }
+        tx.commit().unwrap();
+        sec_trie_root::<KeccakHasher, _, _, _>(encoded)
+    }
+
+    #[test]
+    fn empty_state() {
+        let db = create_test_rw_db();
+        let tx = db.tx().unwrap();
+        assert_matches!(StateRoot::new(&tx).root(), Ok(root) if root == EMPTY_ROOT);
+    }
+
+    #[test]
+    fn accounts_with_storage() {
+        let db = create_test_rw_db();
+        let storage = BTreeSet::from([
+            StorageEntry { key: H256::zero(), value: U256::from(3) },
+            StorageEntry { key: H256::from_low_u64_be(2), value: U256::from(1) },
+        ]);
+        let accounts = BTreeMap::from([
+            (
+                Address::from(hex!("9fe4abd71ad081f091bd06dd1c16f7e92927561e")),
+                (
+                    Account {
+                        nonce: 155,
+                        balance: U256::from(414241124u32),
+                        bytecode_hash: Some(keccak256("el buen fla")),
+                    },
+                    storage.clone(),
+                ),
+            ),
+            (
+                Address::from(hex!("f8a6edaad4a332e6e550d0915a7fd5300b0b12d1")),
+                (
+                    Account { nonce: 3, balance: U256::from(78978), bytecode_hash: None },
+                    Default::default(),
+                ),
+            ),
+        ]);
+        let expected = insert_state(&db, &accounts);
+
+        let tx = db.tx().unwrap();
+        let hashed_address = keccak256(hex!("9fe4abd71ad081f091bd06dd1c16f7e92927561e"));
+        assert_eq!(StorageRoot::new(&tx, hashed_address).root(), Ok(storage_root(&storage)));
+        assert_eq!(StateRoot::new(&tx).root(), Ok(expected));
+    }
+
+    #[test]
+    fn write_trie_nodes() {
+        let db = create_test_rw_db();
+        let address = Address::from(hex!("9fe4abd71ad081f091bd06dd1c16f7e92927561e"));
+        let storage = (0..20)
+            .map(|slot| StorageEntry {
+                key: H256::from_low_u64_be(slot),
+                value: U256::from(slot + 1),
+            })
+            .collect::<BTreeSet<_>>();
+        let accounts = (0..20u64)
+            .map(|i| {
+                let account = Account { nonce: i, balance: U256::from(i), bytecode_hash: None };
+                (Address::from_low_u64_be(i), (account, BTreeSet::new()))
+            })
+            .chain([(address, (Account::default(), storage.clone()))])
+            .collect();
+        let expected = insert_state(&db, &accounts);
+
+        let tx = db.tx_mut().unwrap();
+        assert_eq!(StateRoot::new(&tx).write_root(), Ok(expected));
+
+        // every node is stored by its hash, starting with the roots
+        let mut account_nodes = tx.cursor_read::<tables::AccountsTrie>().unwrap();
+        assert!(account_nodes.seek_exact(expected).unwrap().is_some());
+        for entry in account_nodes.walk(None).unwrap() {
+            let (hash, node) = entry.unwrap();
+            assert_eq!(keccak256(node), hash);
+        }
+        let mut storage_nodes = tx.cursor_dup_read::<tables::StoragesTrie>().unwrap();
+        let root = storage_root(&storage);
+        let entry = storage_nodes.seek_by_key_subkey(keccak256(address), root).unwrap().unwrap();
+        assert_eq!(entry.hash, root);
+        assert_eq!(keccak256(entry.node), root);
+    }
+
+    /// Returns the stored nodes of the account trie and the stored node hashes of the storage
+    /// tries.
+    fn trie_tables<'a>(tx: &impl DbTx<'a>) -> (BTreeMap<H256, Vec<u8>>, BTreeSet<(H256, H256)>) {
+        let accounts = tx
+            .cursor_read::<tables::AccountsTrie>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .map(|entry| entry.unwrap())
+            .collect();
+        let storages = tx
+            .cursor_dup_read::<tables::StoragesTrie>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .map(|entry| entry.map(|(hashed_address, entry)| (hashed_address, entry.hash)).unwrap())
+            .collect();
+        (accounts, storages)
+    }
+
+    /// Returns the prefix sets of the changes between the two states.
+    fn changes(before: &State, after: &State) -> PrefixSets {
+        let mut sets = PrefixSets::default();
+        let storage = |state: &State, address| {
+            state.get(address).map_or_else(BTreeMap::new, |(_, storage)| {
+                storage.iter().map(|entry| (entry.key, entry.value)).collect()
+            })
+        };
+        for address in before.keys().chain(after.keys()) {
+            let hashed_address = keccak256(address);
+            if before.get(address).map(|(account, _)| account) !=
+                after.get(address).map(|(account, _)| account)
+            {
+                sets.accounts.insert(hashed_address);
+            }
+            let (storage_before, storage_after) =
+                (storage(before, address), storage(after, address));
+            for slot in storage_before.keys().chain(storage_after.keys()) {
+                if storage_before.get(slot) != storage_after.get(slot) {
+                    sets.storages.entry(hashed_address).or_default().insert(keccak256(slot));
+                }
+            }
+        }
+        sets
+    }
+
+    fn state() -> impl Strategy<Value = State> {
+        let account = (0u64..3, btree_map(0u64..16, 1u64..4, 0..8));
+        btree_map(0u64..32, account, 0..24).prop_map(|accounts| {
+            accounts
+                .into_iter()
+                .map(|(address, (nonce, storage))| {
+                    let account = Account { nonce, balance: U256::ZERO, bytecode_hash: None };
+                    let storage = storage
+                        .into_iter()
+                        .map(|(slot, value)| StorageEntry {
+                            key: H256::from_low_u64_be(slot),
+                            value: U256::from(value),
+                        })
+                        .collect();
+                    (Address::from_low_u64_be(address), (account, storage))
+                })
+                .collect()
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn incremental_update(before in state(), after in state()) {
+            let db = create_test_rw_db();
+            insert_state(&db, &before);
+            let tx = db.tx_mut().unwrap();
+            let previous_root = StateRoot::new(&tx).write_root().unwrap();
+            tx.commit().unwrap();
+
+            let expected = insert_state(&db, &after);
+            let tx = db.tx_mut().unwrap();
+            let root = StateRoot::new(&tx)
+                .with_prefix_sets(changes(&before, &after))
+                .update_root(previous_root);
+            prop_assert_eq!(root, Ok(expected));
+
+            // the tables only hold the nodes of the updated tries
+            let rebuilt = create_test_rw_db();
+            insert_state(&rebuilt, &after);
+            let rebuilt_tx = rebuilt.tx_mut().unwrap();
+            StateRoot::new(&rebuilt_tx).write_root().unwrap();
+            prop_assert_eq!(trie_tables(&tx), trie_tables(&rebuilt_tx));
+        }
+
+        #[test]
+        fn parallel_root(accounts in state(), batch_size in 1usize..8) {
+            let db = create_test_rw_db();
+            let expected = insert_state(&db, &accounts);
+            let parallel = ParallelStateRoot::new(db.as_ref()).with_batch_size(batch_size);
+            prop_assert_eq!(parallel.root(), Ok(expected));
+
+            let tx = db.tx_mut().unwrap();
+            prop_assert_eq!(parallel.write_root(&tx), Ok(expected));
+            let tables = trie_tables(&tx);
+            drop(tx);
+            let tx = db.tx_mut().unwrap();
+            StateRoot::new(&tx).write_root().unwrap();
+            prop_assert_eq!(tables, trie_tables(&tx));
+        }
+    }
+
+    #[test]
+    fn arbitrary_state() {
+        proptest!(ProptestConfig::with_cases(10), |(accounts: BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>)| {
+            // a slot only has a single value
+            let accounts = accounts
+                .into_iter()
+                .map(|(address, (account, storage))| {
+                    let storage: BTreeMap<_, _> =
+                        storage.into_iter().map(|entry| (entry.key, entry)).collect();
+                    (address, (account, storage.into_values().collect()))
+                })
+                .collect();
+            let db = create_test_rw_db();
+            let expected = insert_state(&db, &accounts);
+            let tx = db.tx().unwrap();
+            assert_eq!(StateRoot::new(&tx).root(), Ok(expected));
+        });
+    }
+}
@@ -0,0 +1,230 @@
+//! Incremental update of a stored trie.
+
+use crate::{
+    nibbles::pack,
+    nodes::{hash_ref, ref_hash, TrieNode},
+    HashBuilder, PrefixSet, StateRootError,
+};
+use reth_primitives::{proofs::EMPTY_ROOT, H256};
+use std::collections::HashSet;
+
+/// The nodes and the leaves of a trie being updated.
+pub(crate) trait TrieSource {
+    /// Returns the stored node with the given hash.
+    fn node(&mut self, hash: H256) -> Result<Option<Vec<u8>>, StateRootError>;
+
+    /// Adds the leaves of the current state whose keys are in `start..end` to the builder.
+    ///
+    /// `previous` is the leaf of the trie inside the range, with its full key, if there is one.
+    fn add_leaves(
+        &mut self,
+        builder: &mut HashBuilder,
+        start: H256,
+        end: Option<H256>,
+        previous: Option<(H256, Vec<u8>)>,
+    ) -> Result<(), StateRootError>;
+
+    /// Stores a node of the updated trie.
+    fn insert_node(&mut self, hash: H256, node: Vec<u8>) -> Result<(), StateRootError>;
+
+    /// Removes a node which is not part of the updated trie anymore.
+    fn remove_node(&mut self, hash: H256) -> Result<(), StateRootError>;
+}
+
+/// Updates the stored trie with the given root, returns the root of the updated trie.
+///
+/// The trie is walked from the root down the paths which are prefixes of changed keys: the leaves
+/// below them are read from the current state, while the subtries off these paths are reused by
+/// their reference. The replaced nodes are removed from the source once the trie is built.
+pub(crate) fn update_trie<S: TrieSource>(
+    source: &mut S,
+    prefix_set: &PrefixSet,
+    root: H256,
+) -> Result<H256, StateRootError> {
+    if prefix_set.is_empty() {
+        return Ok(root)
+    }
+
+    let mut walker = TrieWalker {
+        source,
+        prefix_set,
+        builder: HashBuilder::default().with_updates(true),
+        removed: HashSet::new(),
+    };
+    if root == EMPTY_ROOT {
+        walker.add_leaves(&[], None)?;
+    } else {
+        let node = walker.resolve(root)?;
+        walker.removed.insert(root);
+        walker.visit(Vec::new(), &node)?;
+    }
+
+    let root = walker.builder.root();
+    walker.flush()?;
+    for hash in walker.removed {
+        walker.source.remove_node(hash)?;
+    }
+    Ok(root)
+}
+
+/// The walk of a trie, building the updated trie along the way.
+struct TrieWalker<'s, S> {
+    source: &'s mut S,
+    prefix_set: &'s PrefixSet,
+    builder: HashBuilder,
+    /// The hashes of the replaced nodes.
+    removed: HashSet<H256>,
+}
+
+impl<'s, S: TrieSource> TrieWalker<'s, S> {
+    /// Rebuilds the subtrie of the node at the path, which is the prefix of changed keys.
+    fn visit(&mut self, path: Vec<u8>, node: &[u8]) -> Result<(), StateRootError> {
+        match TrieNode::decode(node).ok_or(StateRootError::InvalidNode { hash: ref_hash(node) })? {
+            TrieNode::Leaf(rest, value) => {
+                let key = H256::from_slice(&pack(&[path.as_slice(), &rest].concat()));
+                self.add_leaves(&path, Some((key, value)))?;
+            }
+            TrieNode::Extension(shared, child) => {
+                let child_path = [path.as_slice(), &shared].concat();
+                // new keys can branch off the shared path, before or after the child
+                let (start, end) = (first_key(&path), key_after(&path));
+                self.source.add_leaves(
+                    &mut self.builder,
+                    start,
+                    Some(first_key(&child_path)),
+                    None,
+                )?;
+                if self.prefix_set.contains(&child_path) {
+                    self.visit_child(child_path.clone(), &child)?;
+                } else {
+                    self.builder.add_branch(child_path.clone(), child);
+                }
+                if let Some(start) = key_after(&child_path) {
+                    self.source.add_leaves(&mut self.builder, start, end, None)?;
+                }
+            }
+            TrieNode::Branch(children) => {
+                let child_path = |nibble: usize| [path.as_slice(), &[nibble as u8]].concat();
+                // The branch stays at the same depth if at least two of its children are
+                // unchanged, then their references can be reused whatever their kind.
+                let unchanged = children
+                    .iter()
+                    .enumerate()
+                    .filter(|(nibble, child)| {
+                        child.is_some() && !self.prefix_set.contains(&child_path(*nibble))
+                    })
+                    .count();
+
+                for (nibble, child) in children.into_iter().enumerate() {
+                    let path = child_path(nibble);
+                    if self.prefix_set.contains(&path) {
+                        match child {
+                            Some(child) => self.visit_child(path, &child)?,
+                            None => self.add_leaves(&path, None)?,
+                        }
+                    } else if let Some(child) = child {
+                        if unchanged >= 2 {
+                            self.builder.add_branch(path, child);
+                        } else {
+                            self.add_unchanged(path, child)?;
+                        }
+                    }
+                }
+            }
+        }
+        self.flush()
+    }
+
+    /// Rebuilds the subtrie of the child with the given reference.
+    fn visit_child(&mut self, path: Vec<u8>, child: &[u8]) -> Result<(), StateRootError> {
+        match hash_ref(child) {
+            Some(hash) => {
+                let node = self.resolve(hash)?;
+                self.removed.insert(hash);
+                self.visit(path, &node)
+            }
+            None => self.visit(path, child),
+        }
+    }
+
+    /// Adds the unchanged child of a branch which may get merged with its parent, so its kind has
+    /// to be known.
+    fn add_unchanged(&mut self, path: Vec<u8>, child: Vec<u8>) -> Result<(), StateRootError> {
+        let hash = hash_ref(&child);
+        let node = match hash {
+            Some(hash) => self.resolve(hash)?,
+            None => child.clone(),
+        };
+        match TrieNode::decode(&node)
+            .ok_or(StateRootError::InvalidNode { hash: ref_hash(&child) })?
+        {
+            TrieNode::Branch(_) => self.builder.add_branch(path, child),
+            TrieNode::Extension(shared, grandchild) => {
+                self.removed.extend(hash);
+                self.builder.add_branch([path, shared].concat(), grandchild);
+            }
+            TrieNode::Leaf(rest, value) => {
+                self.removed.extend(hash);
+                self.builder.add_leaf([path, rest].concat(), &value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the leaves with keys prefixed by `path`.
+    fn add_leaves(
+        &mut self,
+        path: &[u8],
+        previous: Option<(H256, Vec<u8>)>,
+    ) -> Result<(), StateRootError> {
+        self.source.add_leaves(&mut self.builder, first_key(path), key_after(path), previous)
+    }
+
+    fn resolve(&mut self, hash: H256) -> Result<Vec<u8>, StateRootError> {
+        self.source.node(hash)?.ok_or(StateRootError::MissingNode { hash })
+    }
+
+    /// Stores the nodes built so far.
+    fn flush(&mut self) -> Result<(), StateRootError> {
+        for (hash, node) in self.builder.take_updates() {
+            // the node may be one of the replaced ones, if the changes were reverted
+            self.removed.remove(&hash);
+            self.source.insert_node(hash, node)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the first key with the given prefix.
+fn first_key(prefix: &[u8]) -> H256 {
+    let mut nibbles = prefix.to_vec();
+    nibbles.resize(64, 0);
+    H256::from_slice(&pack(&nibbles))
+}
+
+/// Returns the first key after the ones with the given prefix, `None` if there is none.
+fn key_after(prefix: &[u8]) -> Option<H256> {
+    let mut nibbles = prefix.to_vec();
+    while let Some(last) = nibbles.pop() {
+        if last < 0x0f {
+            nibbles.push(last + 1);
+            return Some(first_key(&nibbles))
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_ranges() {
+        assert_eq!(first_key(&[]), H256::zero());
+        assert_eq!(first_key(&[1, 2, 3]), H256::from_slice(&[[0x12, 0x30], [0; 30]].concat()));
+        assert_eq!(key_after(&[]), None);
+        assert_eq!(key_after(&[0x0f, 0x0f]), None);
+        assert_eq!(key_after(&[1, 0x0f]), Some(first_key(&[2])));
+        assert_eq!(key_after(&[1, 2]), Some(first_key(&[1, 3])));
+    }
+}